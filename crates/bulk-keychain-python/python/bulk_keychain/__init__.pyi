@@ -44,6 +44,7 @@ PythOraclePriceEntry = tuple[int, int, int, int]  # (timestamp, feed_index, pric
 
 class SignedTransaction(TypedDict):
     actions: list[dict[str, Any]]
+    action_type: str  # Action kind: 'order', 'faucet', 'agentWalletCreation', ...
     nonce: int
     account: str
     signer: str
@@ -100,7 +101,7 @@ class Signer:
     @staticmethod
     def with_nonce_manager(
         keypair: Keypair,
-        strategy: Literal["timestamp", "counter", "high_frequency"]
+        strategy: Literal["timestamp", "counter", "high_frequency", "random"]
     ) -> "Signer":
         """Create a signer with nonce management"""
         ...
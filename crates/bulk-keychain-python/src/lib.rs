@@ -94,7 +94,7 @@ impl PySigner {
     #[new]
     fn new(keypair: &PyKeypair) -> Self {
         Self {
-            inner: Signer::new(keypair.inner.clone()),
+            inner: Signer::new(keypair.inner.clone_tracked("PySigner::new")),
         }
     }
 
@@ -114,22 +114,24 @@ impl PySigner {
             "timestamp" => NonceStrategy::Timestamp,
             "counter" => NonceStrategy::Counter,
             "high_frequency" => NonceStrategy::TimestampWithCounter,
-            _ => {
-                return Err(PyValueError::new_err(
-                    "Invalid nonce strategy. Use 'timestamp', 'counter', or 'high_frequency'",
-                ))
-            }
+            "random" => NonceStrategy::RandomWithTimestampFallback,
+            _ => return Err(PyValueError::new_err(
+                "Invalid nonce strategy. Use 'timestamp', 'counter', 'high_frequency', or 'random'",
+            )),
         };
         let nonce_manager = NonceManager::new(nonce_strategy);
         Ok(Self {
-            inner: Signer::with_nonce_manager(keypair.inner.clone(), nonce_manager),
+            inner: Signer::with_nonce_manager(
+                keypair.inner.clone_tracked("PySigner::with_nonce_manager"),
+                nonce_manager,
+            ),
         })
     }
 
     /// Get the signer's public key
     #[getter]
     fn pubkey(&self) -> String {
-        self.inner.pubkey().to_base58()
+        self.inner.signer_pubkey().to_base58()
     }
 
     /// Enable/disable single-order ID computation.
@@ -982,10 +984,14 @@ fn parse_compact_order_item(dict: &Bound<'_, PyDict>) -> PyResult<OrderItem> {
 
 fn signed_to_py(py: Python<'_>, signed: &bulk_keychain::SignedTransaction) -> PyResult<PyObject> {
     let dict = PyDict::new(py);
+    let actions = signed
+        .actions_json()
+        .map_err(|e| PyValueError::new_err(e.to_string()))?;
     dict.set_item(
         "actions",
-        json_to_py(py, &serde_json::Value::Array(signed.actions.clone()))?,
+        json_to_py(py, &serde_json::Value::Array(actions))?,
     )?;
+    dict.set_item("action_type", signed.action_type().as_str())?;
     dict.set_item("nonce", signed.nonce)?;
     dict.set_item("account", &signed.account)?;
     dict.set_item("signer", &signed.signer)?;
@@ -3,23 +3,244 @@
 //! This crate provides WebAssembly bindings for the bulk-keychain signing library,
 //! enabling high-performance transaction signing in browser environments.
 
+use bulk_keychain::input::check_fields;
 use bulk_keychain::{
-    finalize_transaction, prepare_agent_wallet, prepare_all, prepare_create_multisig,
-    prepare_create_sub_account, prepare_faucet, prepare_group, prepare_message,
-    prepare_multisig_approve, prepare_multisig_cancel, prepare_multisig_execute,
+    finalize_transaction, prepare_agent_wallet, prepare_all, prepare_all_indexed, prepare_cancel,
+    prepare_cancel_all, prepare_cancel_replace, prepare_create_multisig,
+    prepare_create_sub_account, prepare_faucet, prepare_group, prepare_group_with_options,
+    prepare_message, prepare_multisig_approve, prepare_multisig_cancel, prepare_multisig_execute,
     prepare_multisig_propose, prepare_multisig_reject, prepare_remove_sub_account,
     prepare_rename_sub_account, prepare_transfer, prepare_update_multisig_policy,
-    prepare_user_settings, Action, AgentWallet, Cancel, CancelAll, CreateMultisig,
-    CreateSubAccount, Faucet, Hash, Keypair, Modify, MultisigApprove, MultisigCancel,
-    MultisigExecute, MultisigPropose, MultisigReject, NonceManager, NonceStrategy, OnFill,
-    OraclePrice, Order, OrderItem, OrderType, PreparedMessage, Pubkey, PythOraclePrice, RangeOco,
-    RenameSubAccount, Signer, Stop, TakeProfit, TimeInForce, TrailingStop, Transfer, TransferKind,
-    TriggerBasket, UpdateMultisigPolicy, UserSettings, WhitelistFaucet,
+    prepare_user_settings, Action, CreateMultisig, CreateSubAccount, GroupKind, GroupOptions, Hash,
+    InputMode, Keypair, MultisigApprove, MultisigCancel, MultisigExecute, MultisigPropose,
+    MultisigReject, NonceManager, NonceStrategy, OrderItem, OrderSpec, PreparedMessage, Pubkey,
+    RenameSubAccount, SignOptions, SignedTransaction, Signer, TimestampMs, Transfer, TransferKind,
+    UpdateMultisigPolicy, UserSettings,
+};
+#[cfg(feature = "full")]
+use bulk_keychain::{
+    flatten_position, verify_commitment, BatchBuilder, BatchEntry, Cancel, FlattenStyle,
+    LintContext, OraclePrice, Order, PositionSnapshot, PythOraclePrice, RemoteSession,
+    ResponderBackend, SessionInitiator, SessionResponder, SymbolSpecs, TimeInForce,
+    TriggerCondition,
 };
 use serde::Deserialize;
 use serde_json::Value as JsonValue;
+#[cfg(feature = "full")]
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 use wasm_bindgen::prelude::*;
 
+// ============================================================================
+// Diagnostics
+// ============================================================================
+
+static DIAGNOSTICS_ENABLED: AtomicBool = AtomicBool::new(false);
+static LIVE_KEYPAIRS: AtomicU64 = AtomicU64::new(0);
+static LIVE_SIGNERS: AtomicU64 = AtomicU64::new(0);
+static LIVE_PREPARED_MESSAGES: AtomicU64 = AtomicU64::new(0);
+static RETAINED_BYTES: AtomicU64 = AtomicU64::new(0);
+
+/// Counts one live object, and the bytes it retains, until dropped. Only
+/// objects created while diagnostics are enabled are counted.
+struct Tracked {
+    live: &'static AtomicU64,
+    bytes: u64,
+}
+
+impl Tracked {
+    fn new(live: &'static AtomicU64, bytes: usize) -> Option<Self> {
+        if !DIAGNOSTICS_ENABLED.load(Ordering::Relaxed) {
+            return None;
+        }
+        live.fetch_add(1, Ordering::Relaxed);
+        RETAINED_BYTES.fetch_add(bytes as u64, Ordering::Relaxed);
+        Some(Self {
+            live,
+            bytes: bytes as u64,
+        })
+    }
+}
+
+impl Drop for Tracked {
+    fn drop(&mut self) {
+        self.live.fetch_sub(1, Ordering::Relaxed);
+        RETAINED_BYTES.fetch_sub(self.bytes, Ordering::Relaxed);
+    }
+}
+
+/// Start or stop counting objects for `diagnostics()`
+///
+/// Off by default. Objects created while it is off are never counted.
+#[wasm_bindgen(js_name = setDiagnosticsEnabled)]
+pub fn set_diagnostics_enabled(enabled: bool) {
+    DIAGNOSTICS_ENABLED.store(enabled, Ordering::Relaxed);
+}
+
+/// Live keypairs, signers and prepared messages, and the bytes they retain
+///
+/// Objects are released by `free()` or when the JS wrapper is collected,
+/// so counts that keep growing point at wrappers that are never freed.
+/// `secretCensus` lists the live copies of each secret key and what holds
+/// them, whether or not diagnostics are enabled.
+///
+/// @returns { enabled, liveKeypairs, liveSigners, livePreparedMessages, retainedBytes, secretCensus }
+#[wasm_bindgen]
+pub fn diagnostics() -> Result<JsValue, JsError> {
+    let count = |c: &AtomicU64| c.load(Ordering::Relaxed);
+    let value = serde_json::json!({
+        "enabled": DIAGNOSTICS_ENABLED.load(Ordering::Relaxed),
+        "liveKeypairs": count(&LIVE_KEYPAIRS),
+        "liveSigners": count(&LIVE_SIGNERS),
+        "livePreparedMessages": count(&LIVE_PREPARED_MESSAGES),
+        "retainedBytes": count(&RETAINED_BYTES),
+        "secretCensus": bulk_keychain::secret_census(),
+    });
+    serde::Serialize::serialize(&value, &serde_wasm_bindgen::Serializer::json_compatible())
+        .map_err(|e| JsError::new(&e.to_string()))
+}
+
+// ============================================================================
+// Large integers
+// ============================================================================
+
+/// `Number.MAX_SAFE_INTEGER`: larger integers lose precision as JS numbers
+const MAX_SAFE_INTEGER: u64 = (1 << 53) - 1;
+
+static LARGE_INTS_AS_BIGINT: AtomicBool = AtomicBool::new(false);
+
+/// Choose how integers above `Number.MAX_SAFE_INTEGER` (such as large
+/// nonces) appear in outputs: "string" (the default) or "bigint". Smaller
+/// integers are always plain numbers.
+///
+/// Both forms are accepted back wherever a signed transaction is an input.
+#[wasm_bindgen(js_name = setSerializeLargeIntsAs)]
+pub fn set_serialize_large_ints_as(mode: &str) -> Result<(), JsError> {
+    let bigint = match mode {
+        "string" => false,
+        "bigint" => true,
+        _ => {
+            return Err(JsError::new(
+                "serializeLargeIntsAs must be 'string' or 'bigint'",
+            ))
+        }
+    };
+    LARGE_INTS_AS_BIGINT.store(bigint, Ordering::Relaxed);
+    Ok(())
+}
+
+/// Integers above `MAX_SAFE_INTEGER` as strings, or left as u64 for the
+/// serializer to turn into BigInts; every other integer as an f64.
+fn widen_large_ints(value: &mut JsonValue, bigint: bool) {
+    match value {
+        JsonValue::Number(n) if !n.is_f64() => {
+            let safe = n
+                .as_i64()
+                .is_some_and(|v| v.unsigned_abs() <= MAX_SAFE_INTEGER);
+            if safe {
+                if let Some(f) = n.as_f64().and_then(serde_json::Number::from_f64) {
+                    *n = f;
+                }
+            } else if !bigint {
+                *value = JsonValue::String(n.to_string());
+            }
+        }
+        JsonValue::Array(items) => items.iter_mut().for_each(|v| widen_large_ints(v, bigint)),
+        JsonValue::Object(map) => map.values_mut().for_each(|v| widen_large_ints(v, bigint)),
+        _ => {}
+    }
+}
+
+#[wasm_bindgen]
+extern "C" {
+    /// A nonce: a number up to `Number.MAX_SAFE_INTEGER`, or a BigInt for
+    /// any u64.
+    #[wasm_bindgen(typescript_type = "number | bigint")]
+    pub type Nonce;
+}
+
+fn nonce_error(nonce: impl std::fmt::Display) -> String {
+    format!("nonce {nonce} is not a safe integer; pass larger nonces as a BigInt")
+}
+
+fn nonce_in(nonce: Option<Nonce>) -> Result<Option<u64>, JsError> {
+    let Some(nonce) = nonce else {
+        return Ok(None);
+    };
+    let value = JsValue::from(nonce);
+    match value.as_f64() {
+        Some(n) if n >= 0.0 && n.fract() == 0.0 && n <= MAX_SAFE_INTEGER as f64 => {
+            Ok(Some(n as u64))
+        }
+        Some(n) => Err(JsError::new(&nonce_error(n))),
+        None => u64::try_from(value)
+            .map(Some)
+            .map_err(|_| JsError::new("nonce must be a number or a BigInt that fits in a u64")),
+    }
+}
+
+/// A number when exact, a BigInt above `Number.MAX_SAFE_INTEGER`.
+fn nonce_out(nonce: u64) -> JsValue {
+    if nonce <= MAX_SAFE_INTEGER {
+        JsValue::from_f64(nonce as f64)
+    } else {
+        js_sys::BigInt::from(nonce).into()
+    }
+}
+
+/// Serde `deserialize_with` for an optional nonce in an options object.
+fn de_nonce<'de, D: serde::Deserializer<'de>>(d: D) -> Result<Option<u64>, D::Error> {
+    let Some(value) = Option::<JsonValue>::deserialize(d)?.filter(|v| !v.is_null()) else {
+        return Ok(None);
+    };
+    let nonce = match &value {
+        JsonValue::Number(n) => n.as_u64().or_else(|| {
+            n.as_f64()
+                .filter(|f| *f >= 0.0 && f.fract() == 0.0 && *f <= MAX_SAFE_INTEGER as f64)
+                .map(|f| f as u64)
+        }),
+        _ => None,
+    };
+    nonce
+        .map(Some)
+        .ok_or_else(|| serde::de::Error::custom(nonce_error(value)))
+}
+
+/// Undo the string form of [`widen_large_ints`]: strings of at most 20
+/// digits above `MAX_SAFE_INTEGER` become integers again. Base58 IDs are
+/// far longer, so they are never mistaken for one.
+fn narrow_large_ints(value: &mut JsonValue) {
+    match value {
+        JsonValue::String(s) if s.len() <= 20 && s.bytes().all(|b| b.is_ascii_digit()) => {
+            if let Some(n) = s.parse::<u64>().ok().filter(|n| *n > MAX_SAFE_INTEGER) {
+                *value = n.into();
+            }
+        }
+        JsonValue::Array(items) => items.iter_mut().for_each(narrow_large_ints),
+        JsonValue::Object(map) => map.values_mut().for_each(narrow_large_ints),
+        _ => {}
+    }
+}
+
+/// Serialize an output for JS without losing integer precision: objects as
+/// plain objects, and large integers per `setSerializeLargeIntsAs`.
+fn to_js<T: serde::Serialize + ?Sized>(value: &T) -> Result<JsValue, JsError> {
+    let mut json = serde_json::to_value(value).map_err(|e| JsError::new(&e.to_string()))?;
+    let bigint = LARGE_INTS_AS_BIGINT.load(Ordering::Relaxed);
+    widen_large_ints(&mut json, bigint);
+    let serializer = serde_wasm_bindgen::Serializer::json_compatible()
+        .serialize_large_number_types_as_bigints(bigint);
+    serde::Serialize::serialize(&json, &serializer).map_err(|e| JsError::new(&e.to_string()))
+}
+
+/// Read a signed transaction whose large integers may be strings or BigInts.
+fn tx_from_js(tx: JsValue) -> Result<SignedTransaction, JsError> {
+    let mut json: JsonValue =
+        serde_wasm_bindgen::from_value(tx).map_err(|e| JsError::new(&e.to_string()))?;
+    narrow_large_ints(&mut json);
+    serde_json::from_value(json).map_err(|e| JsError::new(&e.to_string()))
+}
+
 // Initialize panic hook for better error messages in development
 #[cfg(feature = "console_error_panic_hook")]
 fn set_panic_hook() {
@@ -35,6 +256,127 @@ pub fn init() {
     set_panic_hook();
 }
 
+// ============================================================================
+// Explicit initialization
+// ============================================================================
+
+static INITIALIZED: AtomicBool = AtomicBool::new(false);
+
+/// What the host environment offers, as reported by `initKeychain`
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Capabilities {
+    /// A cryptographic RNG for key generation and random nonces
+    secure_random: bool,
+    /// `BigInt`, needed for `setSerializeLargeIntsAs("bigint")`
+    bigint: bool,
+    /// `SharedArrayBuffer` in a cross-origin isolated context
+    threads: bool,
+    version: &'static str,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+struct InitOptions {
+    require_secure_random: bool,
+    require_bigint: bool,
+    require_threads: bool,
+}
+
+impl Default for InitOptions {
+    fn default() -> Self {
+        Self {
+            require_secure_random: true,
+            require_bigint: false,
+            require_threads: false,
+        }
+    }
+}
+
+/// Capabilities visible on `global`; pure so a stripped global can be probed.
+fn probe_capabilities(global: &JsValue) -> Capabilities {
+    let get = |target: &JsValue, key: &str| {
+        js_sys::Reflect::get(target, &JsValue::from_str(key)).unwrap_or(JsValue::UNDEFINED)
+    };
+    let crypto = get(global, "crypto");
+    // Outside browsers `crossOriginIsolated` is undefined and does not gate
+    // SharedArrayBuffer; only an explicit `false` does.
+    let isolated = get(global, "crossOriginIsolated").as_bool() != Some(false);
+    Capabilities {
+        secure_random: crypto.is_object() && get(&crypto, "getRandomValues").is_function(),
+        bigint: get(global, "BigInt").is_function(),
+        threads: isolated && get(global, "SharedArrayBuffer").is_function(),
+        version: env!("CARGO_PKG_VERSION"),
+    }
+}
+
+/// Sign and verify with a fixed key, so a broken build fails here rather
+/// than on the first real signature.
+fn signing_self_test() -> Result<(), JsError> {
+    let keypair = Keypair::from_bytes(&[7u8; 32]).map_err(|e| js_err(e.to_string()))?;
+    let order = bulk_keychain::Order::market("BTC-USD", true, 1.0);
+    let verified = Signer::new(keypair)
+        .sign(order.into(), Some(1))
+        .and_then(|tx| tx.verify());
+    match verified {
+        Ok(true) => Ok(()),
+        _ => Err(js_err(
+            "signing self-test failed; the module build is broken",
+        )),
+    }
+}
+
+/// In debug builds, fail entry points that run before `initKeychain`.
+fn require_init() -> Result<(), JsError> {
+    if cfg!(all(debug_assertions, not(test))) && !INITIALIZED.load(Ordering::Acquire) {
+        return Err(js_err(
+            "bulk-keychain is not initialized: call `await initKeychain()` before using it",
+        ));
+    }
+    Ok(())
+}
+
+/// Probe the environment, run one-time setup, and unlock the other entry points
+///
+/// Rejects with the missing capability when `options` requires one the
+/// environment lacks. Safe to call more than once. Debug builds make key
+/// constructors throw until this has resolved; release builds do not check.
+///
+/// @param options - { requireSecureRandom = true, requireBigint = false, requireThreads = false }
+/// @returns { secureRandom, bigint, threads, version }
+#[wasm_bindgen(js_name = initKeychain)]
+pub async fn init_keychain(options: JsValue) -> Result<JsValue, JsError> {
+    let options: InitOptions = if options.is_undefined() || options.is_null() {
+        InitOptions::default()
+    } else {
+        serde_wasm_bindgen::from_value(options).map_err(|e| js_err(e.to_string()))?
+    };
+    set_panic_hook();
+
+    let mut caps = probe_capabilities(&js_sys::global());
+    // getrandom also reaches Node's `crypto` module when `globalThis.crypto` is absent.
+    caps.secure_random |= getrandom::getrandom(&mut [0u8; 32]).is_ok();
+    if options.require_secure_random && !caps.secure_random {
+        return Err(js_err(
+            "no secure random source: expose Web Crypto as `globalThis.crypto`, \
+             or pass { requireSecureRandom: false } and only import existing keys",
+        ));
+    }
+    if options.require_bigint && !caps.bigint {
+        return Err(js_err("BigInt is not available in this environment"));
+    }
+    if options.require_threads && !caps.threads {
+        return Err(js_err(
+            "SharedArrayBuffer is unavailable: serve the page cross-origin isolated \
+             (COOP: same-origin, COEP: require-corp)",
+        ));
+    }
+    signing_self_test()?;
+
+    INITIALIZED.store(true, Ordering::Release);
+    to_js(&caps)
+}
+
 // ============================================================================
 // Keypair
 // ============================================================================
@@ -43,30 +385,109 @@ pub fn init() {
 #[wasm_bindgen]
 pub struct WasmKeypair {
     inner: Keypair,
+    _tracked: Option<Tracked>,
+}
+
+impl WasmKeypair {
+    fn from_keypair(inner: Keypair) -> Self {
+        Self {
+            inner,
+            _tracked: Tracked::new(&LIVE_KEYPAIRS, 0),
+        }
+    }
+}
+
+/// Wipe the secret on `free()` or when the JS wrapper is collected.
+impl Drop for WasmKeypair {
+    fn drop(&mut self) {
+        self.inner.zeroize();
+    }
 }
 
 #[wasm_bindgen]
 impl WasmKeypair {
     /// Generate a new random keypair
     #[wasm_bindgen(constructor)]
-    pub fn new() -> Self {
-        Self {
-            inner: Keypair::generate(),
-        }
+    pub fn new() -> Result<WasmKeypair, JsError> {
+        require_init()?;
+        Ok(Self::from_keypair(Keypair::generate()))
     }
 
     /// Create from base58-encoded secret key or full keypair
     #[wasm_bindgen(js_name = fromBase58)]
     pub fn from_base58(s: &str) -> Result<WasmKeypair, JsError> {
-        let inner = Keypair::from_base58(s).map_err(|e| JsError::new(&e.to_string()))?;
-        Ok(Self { inner })
+        require_init()?;
+        let inner = Keypair::from_base58(s).map_err(keychain_error)?;
+        Ok(Self::from_keypair(inner))
     }
 
     /// Create from raw bytes (32-byte secret or 64-byte full keypair)
     #[wasm_bindgen(js_name = fromBytes)]
     pub fn from_bytes(bytes: &[u8]) -> Result<WasmKeypair, JsError> {
-        let inner = Keypair::from_bytes(bytes).map_err(|e| JsError::new(&e.to_string()))?;
-        Ok(Self { inner })
+        require_init()?;
+        let inner = Keypair::from_bytes(bytes).map_err(keychain_error)?;
+        Ok(Self::from_keypair(inner))
+    }
+
+    /// Derive from a 12- or 24-word English BIP39 seed phrase at
+    /// `derivationPath` (default `m/44'/501'/0'/0'`, Phantom's first account)
+    #[cfg(feature = "full")]
+    #[wasm_bindgen(js_name = fromMnemonic)]
+    pub fn from_mnemonic(
+        mnemonic: &str,
+        passphrase: Option<String>,
+        derivation_path: Option<String>,
+    ) -> Result<WasmKeypair, JsError> {
+        require_init()?;
+        let inner = Keypair::from_mnemonic(
+            mnemonic,
+            passphrase.as_deref().unwrap_or(""),
+            derivation_path
+                .as_deref()
+                .unwrap_or(bulk_keychain::mnemonic::SOLANA_DERIVATION_PATH),
+        )
+        .map_err(keychain_error)?;
+        Ok(Self::from_keypair(inner))
+    }
+
+    /// Keypair whose secret is the first 32 bytes of a BIP39 phrase's seed,
+    /// as `solana-keygen recover` without a derivation path
+    #[cfg(feature = "full")]
+    #[wasm_bindgen(js_name = fromMnemonicSeed)]
+    pub fn from_mnemonic_seed(
+        mnemonic: &str,
+        passphrase: Option<String>,
+    ) -> Result<WasmKeypair, JsError> {
+        require_init()?;
+        let inner = Keypair::from_mnemonic_seed(mnemonic, passphrase.as_deref().unwrap_or(""))
+            .map_err(keychain_error)?;
+        Ok(Self::from_keypair(inner))
+    }
+
+    /// Generate a keypair and the 24-word seed phrase it derives from
+    ///
+    /// @returns { keypair: WasmKeypair, mnemonic: string }
+    #[cfg(feature = "full")]
+    #[wasm_bindgen(js_name = generateMnemonic)]
+    pub fn generate_mnemonic() -> Result<JsValue, JsError> {
+        require_init()?;
+        let (inner, mnemonic) = Keypair::generate_mnemonic();
+        let result = js_sys::Object::new();
+        let set = |key: &str, value: JsValue| {
+            js_sys::Reflect::set(&result, &key.into(), &value)
+                .map_err(|_| JsError::new("failed to build generateMnemonic result"))
+        };
+        set("keypair", Self::from_keypair(inner).into())?;
+        set("mnemonic", mnemonic.into())?;
+        Ok(result.into())
+    }
+
+    /// Import a private Ed25519 JSON Web Key (e.g. from `crypto.subtle.exportKey("jwk", ...)`)
+    #[wasm_bindgen(js_name = fromJwk)]
+    pub fn from_jwk(json: &str) -> Result<WasmKeypair, JsError> {
+        require_init()?;
+        let inner = Keypair::from_jwk(json).map_err(keychain_error)?;
+        Ok(Self::from_keypair(inner))
     }
 
     /// Get the public key as base58 string
@@ -92,11 +513,38 @@ impl WasmKeypair {
     pub fn secret_key(&self) -> Vec<u8> {
         self.inner.secret_key().to_vec()
     }
-}
 
-impl Default for WasmKeypair {
-    fn default() -> Self {
-        Self::new()
+    /// Export as a private JSON Web Key string, for `crypto.subtle.importKey("jwk", ...)`
+    #[wasm_bindgen(js_name = toJwk)]
+    pub fn to_jwk(&self) -> Result<String, JsError> {
+        self.inner.to_jwk().map_err(keychain_error)
+    }
+
+    /// Short display-safe identifier (`bk1:...`)
+    #[wasm_bindgen]
+    pub fn fingerprint(&self) -> String {
+        self.inner.fingerprint()
+    }
+
+    /// Derive the SLIP-0010 child at `index`; `hardened` must be true
+    #[wasm_bindgen(js_name = deriveChild)]
+    pub fn derive_child(&self, index: u32, hardened: bool) -> Result<WasmKeypair, JsError> {
+        let inner = self
+            .inner
+            .derive_child(index, hardened)
+            .map_err(keychain_error)?;
+        Ok(Self::from_keypair(inner))
+    }
+
+    /// Derive the key at a hardened path like `m/44'/501'/0'/0'`, where `m`
+    /// is this keypair
+    #[wasm_bindgen(js_name = fromDerivationPath)]
+    pub fn from_derivation_path(&self, path: &str) -> Result<WasmKeypair, JsError> {
+        let inner = self
+            .inner
+            .from_derivation_path(path)
+            .map_err(keychain_error)?;
+        Ok(Self::from_keypair(inner))
     }
 }
 
@@ -108,6 +556,78 @@ impl Default for WasmKeypair {
 #[wasm_bindgen]
 pub struct WasmSigner {
     inner: Signer,
+    pre_sign_hook: Option<js_sys::Function>,
+    input_mode: InputMode,
+    _tracked: Option<Tracked>,
+}
+
+impl WasmSigner {
+    fn from_signer(inner: Signer) -> Self {
+        Self {
+            inner,
+            pre_sign_hook: None,
+            input_mode: InputMode::Tolerant,
+            _tracked: Tracked::new(&LIVE_SIGNERS, 0),
+        }
+    }
+
+    /// Nonce to sign under, resolved up front when a hook needs to see it.
+    fn hooked_nonce(&self, nonce: Option<Nonce>) -> Result<Option<u64>, JsError> {
+        match (nonce, &self.pre_sign_hook) {
+            (Some(n), _) => nonce_in(Some(n)),
+            (None, Some(_)) => self.inner.next_nonce().map(Some).map_err(keychain_error),
+            (None, None) => Ok(None),
+        }
+    }
+
+    #[cfg(feature = "full")]
+    fn hooked_base_nonce(&self, base_nonce: Option<Nonce>) -> Result<Option<u64>, JsError> {
+        match (base_nonce, &self.pre_sign_hook) {
+            (Some(n), _) => nonce_in(Some(n)),
+            (None, Some(_)) => Ok(Some(bulk_keychain::nonce::current_timestamp_millis())),
+            (None, None) => Ok(None),
+        }
+    }
+
+    /// Call the JS hook with `(order, nonce)`; a throw aborts signing.
+    fn run_pre_sign_hook(&self, order: &JsValue, nonce: u64) -> Result<(), JsError> {
+        let Some(hook) = &self.pre_sign_hook else {
+            return Ok(());
+        };
+        hook.call2(&JsValue::NULL, order, &nonce_out(nonce))
+            .map_err(|e| {
+                let reason = match e.dyn_ref::<js_sys::Error>() {
+                    Some(err) => String::from(err.message()),
+                    None => e.as_string().unwrap_or_else(|| format!("{e:?}")),
+                };
+                JsError::new(&bulk_keychain::Error::PreSignRejected(reason).to_string())
+            })?;
+        Ok(())
+    }
+
+    /// Run the hook over an array of orders, order `i` under `nonce(i)`.
+    fn run_pre_sign_hook_all(
+        &self,
+        orders: &JsValue,
+        nonce: impl Fn(u32) -> u64,
+    ) -> Result<(), JsError> {
+        if self.pre_sign_hook.is_none() {
+            return Ok(());
+        }
+        let orders = js_sys::Array::from(orders);
+        for i in 0..orders.length() {
+            self.run_pre_sign_hook(&orders.get(i), nonce(i))?;
+        }
+        Ok(())
+    }
+
+    /// [`Self::run_pre_sign_hook_all`] for a batch signed from `base`.
+    #[cfg(feature = "full")]
+    fn run_pre_sign_hook_batch(&self, orders: &JsValue, base: u64) -> Result<(), JsError> {
+        let count = js_sys::Array::from(orders).length() as usize;
+        bulk_keychain::nonce::check_nonce_range(base, count).map_err(keychain_error)?;
+        self.run_pre_sign_hook_all(orders, |i| base + u64::from(i))
+    }
 }
 
 #[wasm_bindgen]
@@ -115,18 +635,32 @@ impl WasmSigner {
     /// Create a new signer from a keypair
     #[wasm_bindgen(constructor)]
     pub fn new(keypair: &WasmKeypair) -> Self {
-        Self {
-            inner: Signer::new(keypair.inner.clone()),
-        }
+        Self::from_signer(Signer::new(keypair.inner.clone_tracked("WasmSigner::new")))
     }
 
     /// Create a signer from base58-encoded secret key
     #[wasm_bindgen(js_name = fromBase58)]
     pub fn from_base58(s: &str) -> Result<WasmSigner, JsError> {
-        let keypair = Keypair::from_base58(s).map_err(|e| JsError::new(&e.to_string()))?;
-        Ok(Self {
-            inner: Signer::new(keypair),
-        })
+        require_init()?;
+        let keypair = Keypair::from_base58(s).map_err(keychain_error)?;
+        Ok(Self::from_signer(Signer::new(keypair)))
+    }
+
+    /// Create a signer that trades on behalf of `account` (agent wallet pattern)
+    #[wasm_bindgen(js_name = fromKeypairAndAccount)]
+    pub fn from_keypair_and_account(
+        keypair: &WasmKeypair,
+        account: &str,
+    ) -> Result<WasmSigner, JsError> {
+        let account = Pubkey::from_base58(account).map_err(keychain_error)?;
+        Ok(Self::from_signer(
+            Signer::new(
+                keypair
+                    .inner
+                    .clone_tracked("WasmSigner::fromKeypairAndAccount"),
+            )
+            .with_account(account),
+        ))
     }
 
     /// Create a signer with nonce management
@@ -135,26 +669,120 @@ impl WasmSigner {
         keypair: &WasmKeypair,
         strategy: &str,
     ) -> Result<WasmSigner, JsError> {
-        let nonce_strategy = match strategy {
-            "timestamp" => NonceStrategy::Timestamp,
-            "counter" => NonceStrategy::Counter,
-            "highFrequency" => NonceStrategy::TimestampWithCounter,
-            _ => {
-                return Err(JsError::new(
-                    "Invalid nonce strategy. Use 'timestamp', 'counter', or 'highFrequency'",
-                ))
-            }
-        };
-        let nonce_manager = NonceManager::new(nonce_strategy);
-        Ok(Self {
-            inner: Signer::with_nonce_manager(keypair.inner.clone(), nonce_manager),
-        })
+        let nonce_manager = NonceManager::new(parse_nonce_strategy(strategy)?);
+        Ok(Self::from_signer(Signer::with_nonce_manager(
+            keypair.inner.clone_tracked("WasmSigner::withNonceManager"),
+            nonce_manager,
+        )))
     }
 
-    /// Get the signer's public key
+    /// Create a signer from a 12- or 24-word BIP39 phrase, derived at
+    /// `derivationPath` (e.g. `m/44'/501'/0'/0'`), with nonce management if
+    /// `strategy` is given
+    #[cfg(feature = "full")]
+    #[wasm_bindgen(js_name = fromMnemonic)]
+    pub fn from_mnemonic(
+        phrase: &str,
+        derivation_path: &str,
+        strategy: Option<String>,
+    ) -> Result<WasmSigner, JsError> {
+        require_init()?;
+        let keypair =
+            Keypair::from_mnemonic(phrase, "", derivation_path).map_err(keychain_error)?;
+        Ok(Self::from_signer(match strategy {
+            Some(strategy) => Signer::with_nonce_manager(
+                keypair,
+                NonceManager::new(parse_nonce_strategy(&strategy)?),
+            ),
+            None => Signer::new(keypair),
+        }))
+    }
+
+    /// Get the signer's public key (alias for signerPubkey)
     #[wasm_bindgen(getter)]
     pub fn pubkey(&self) -> String {
-        self.inner.pubkey().to_base58()
+        self.signer_pubkey()
+    }
+
+    /// Public key of the signing keypair
+    #[wasm_bindgen(js_name = signerPubkey)]
+    pub fn signer_pubkey(&self) -> String {
+        self.inner.signer_pubkey().to_base58()
+    }
+
+    /// Public key of the trading account (differs from signerPubkey for agent wallets)
+    #[wasm_bindgen(js_name = accountPubkey)]
+    pub fn account_pubkey(&self) -> String {
+        self.inner.account_pubkey().to_base58()
+    }
+
+    /// Short display-safe identifier (`bk1:...`) of the signing key
+    #[wasm_bindgen]
+    pub fn fingerprint(&self) -> String {
+        self.inner.signer_pubkey().fingerprint()
+    }
+
+    /// Get the trading account public key
+    #[wasm_bindgen(getter)]
+    pub fn account(&self) -> String {
+        self.account_pubkey()
+    }
+
+    /// Cap resting orders per symbol (`undefined` removes the cap and resets counts)
+    ///
+    /// Signing past the cap throws. Report fills and exchange-side cancels
+    /// with `notifyClosed`.
+    #[wasm_bindgen(js_name = setMaxOpenOrdersPerSymbol)]
+    pub fn set_max_open_orders_per_symbol(&mut self, cap: Option<u32>) {
+        self.inner
+            .set_max_open_orders_per_symbol(cap.map(|c| c as usize));
+    }
+
+    /// Refuse orders whose `clientId` a live order signed here already holds
+    ///
+    /// Signing one throws `client id <id> in use by live order <orderId>`.
+    /// Signed cancels and `notifyClosed` release IDs; pass
+    /// `{ allowClientIdReuse: true }` to reuse one on purpose.
+    #[wasm_bindgen(js_name = setUniqueClientIds)]
+    pub fn set_unique_client_ids(&mut self, enabled: bool) {
+        self.inner.set_unique_client_ids(enabled);
+    }
+
+    #[wasm_bindgen(js_name = uniqueClientIds)]
+    pub fn unique_client_ids(&self) -> bool {
+        self.inner.unique_client_ids()
+    }
+
+    /// Sign orders whose `clientId` is the all-zero hash
+    /// (`11111111111111111111111111111111`); refused by default, since it is
+    /// usually a defaulted field rather than a chosen ID
+    #[wasm_bindgen(js_name = setAllowZeroClientId)]
+    pub fn set_allow_zero_client_id(&mut self, allowed: bool) {
+        self.inner.set_allow_zero_client_id(allowed);
+    }
+
+    /// Live order (base58 ID) holding `clientId`, if any
+    #[wasm_bindgen(js_name = clientIdOrder)]
+    pub fn client_id_order(&self, client_id: &str) -> Result<Option<String>, JsError> {
+        let client_id = Hash::from_base58(client_id).map_err(keychain_error)?;
+        Ok(self
+            .inner
+            .client_id_order(&client_id)
+            .map(|id| id.to_base58()))
+    }
+
+    /// Stop counting an order (base58 ID) as open and release its client ID;
+    /// returns whether either was tracked
+    #[wasm_bindgen(js_name = notifyClosed)]
+    pub fn notify_closed(&self, order_id: &str) -> Result<bool, JsError> {
+        let order_id = Hash::from_base58(order_id).map_err(keychain_error)?;
+        Ok(self.inner.notify_closed(&order_id))
+    }
+
+    /// Orders counted as open on `symbol`
+    #[wasm_bindgen(js_name = openOrderCount)]
+    pub fn open_order_count(&self, symbol: &str) -> u32 {
+        self.inner.open_order_count(symbol) as u32
     }
 
     /// Enable/disable single-order ID computation.
@@ -181,65 +809,609 @@ impl WasmSigner {
         self.inner.computes_batch_order_ids()
     }
 
+    /// Label the network this signer is meant for (`undefined` clears it);
+    /// reported by `capabilities()` only
+    #[wasm_bindgen(js_name = setNetwork)]
+    pub fn set_network(&mut self, network: Option<String>) {
+        self.inner.set_network(network);
+    }
+
+    /// The nonce the next auto-nonced call would use, without taking it
+    #[wasm_bindgen(js_name = peekNextNonce, unchecked_return_type = "number | string | bigint")]
+    pub fn peek_next_nonce(&self) -> Result<JsValue, JsError> {
+        let nonce = self.inner.peek_next_nonce().map_err(keychain_error)?;
+        to_js(&nonce)
+    }
+
+    /// Reset state that would refuse resubmitted transactions after a
+    /// gateway reconnect, counted in `usage().reconnects`
+    ///
+    /// `policy` is `{ clearOpenOrders?, clearDedupe?, bumpNonceFloor? }`:
+    /// forget the orders counted against the open-order cap (resetting it
+    /// for every symbol), release held client IDs, raise auto nonces to at
+    /// least the current time.
+    ///
+    /// @returns { openOrdersCleared, clientIdsCleared, nonceFloor? }
+    #[wasm_bindgen(js_name = notifyReconnect)]
+    pub fn notify_reconnect(&self, policy: JsValue) -> Result<JsValue, JsError> {
+        let policy: bulk_keychain::ReconnectPolicy =
+            serde_wasm_bindgen::from_value(policy).map_err(|e| JsError::new(&e.to_string()))?;
+        let report = self
+            .inner
+            .notify_reconnect(policy)
+            .map_err(keychain_error)?;
+        to_js(&report)
+    }
+
+    /// Reset the nonce manager; `counter` and `highFrequency` start from zero
+    #[wasm_bindgen(js_name = resetNonce)]
+    pub fn reset_nonce(&self) {
+        self.inner.reset_nonce();
+    }
+
+    /// TEST ONLY: draw `randomClientId()` from a generator seeded with
+    /// `seed`, so a simulation replays exactly; `undefined` goes back to the
+    /// OS RNG. Never call this on a signer that trades for real. Keys are
+    /// not affected.
+    #[wasm_bindgen(js_name = setDeterministicSeed)]
+    pub fn set_deterministic_seed(&mut self, seed: Option<f64>) {
+        self.inner.set_rng(match seed {
+            Some(seed) => bulk_keychain::RngProvider::seeded(seed as u64),
+            None => bulk_keychain::RngProvider::os(),
+        });
+    }
+
+    /// A fresh client order ID (base58) from this signer's RNG
+    #[wasm_bindgen(js_name = randomClientId)]
+    pub fn random_client_id(&self) -> String {
+        self.inner.random_client_id().to_base58()
+    }
+
+    /// What this signer may sign
+    ///
+    /// @returns { account, signer, network, policySummary, scopes, supportedActions, features }
+    #[wasm_bindgen]
+    pub fn capabilities(&self) -> Result<JsValue, JsError> {
+        to_js(&self.inner.capabilities())
+    }
+
+    /// `capabilities()` as JSON signed with this signer's key; check it with
+    /// `verifyCapabilities`
+    ///
+    /// @returns { payload, signer, signature }
+    #[wasm_bindgen(js_name = capabilitiesSigned)]
+    pub fn capabilities_signed(&self) -> Result<JsValue, JsError> {
+        let signed = self.inner.capabilities_signed().map_err(keychain_error)?;
+        to_js(&signed)
+    }
+
+    /// `{ signed, deadlineRejections, reconnects, lastBatchMode, lastBatchThreads }`
+    /// since the signer was created; `lastBatchMode` is `"serial"` (WASM has no
+    /// worker threads), and both batch fields are absent before the first batch
+    #[wasm_bindgen]
+    pub fn usage(&self) -> Result<JsValue, JsError> {
+        let usage = self.inner.usage();
+        let mut out = serde_json::json!({
+            "signed": usage.signed,
+            "deadlineRejections": usage.deadline_rejections,
+            "reconnects": usage.reconnects,
+        });
+        let last_batch = usage.last_batch_mode.map(|mode| match mode {
+            bulk_keychain::BatchMode::Serial => ("serial", 1),
+            bulk_keychain::BatchMode::Parallel { threads } => ("parallel", threads),
+        });
+        if let Some((mode, threads)) = last_batch {
+            out["lastBatchMode"] = mode.into();
+            out["lastBatchThreads"] = threads.into();
+        }
+        to_js(&out)
+    }
+
     // ========================================================================
     // Simplified API
     // ========================================================================
 
+    /// Run `hook(order, nonce)` before signing each order; throwing from it aborts signing.
+    /// Pass null to remove the hook.
+    ///
+    /// Applies to sign, signAll, signAllIndexed, signAllPartialIndexed, signGroup and signConditionalOrder.
+    #[wasm_bindgen(js_name = setPreSignHook)]
+    pub fn set_pre_sign_hook(&mut self, hook: Option<js_sys::Function>) {
+        self.pre_sign_hook = hook;
+    }
+
+    /// Reject unknown fields (suggesting the nearest valid name) and mixed
+    /// camelCase/snake_case in order and settings inputs instead of ignoring
+    /// them.
+    #[wasm_bindgen(js_name = setStrictInput)]
+    pub fn set_strict_input(&mut self, strict: bool) {
+        self.input_mode = if strict {
+            InputMode::Strict
+        } else {
+            InputMode::Tolerant
+        };
+    }
+
     /// Sign a single order/cancel/cancelAll
+    ///
+    /// `options.deadline` (ms since the Unix epoch) refuses to sign, or to
+    /// return the signed transaction, once the signer's clock is past it.
     #[wasm_bindgen]
-    pub fn sign(&mut self, order: JsValue, nonce: Option<f64>) -> Result<JsValue, JsError> {
-        let order_input: OrderInput =
-            serde_wasm_bindgen::from_value(order).map_err(|e| JsError::new(&e.to_string()))?;
+    pub fn sign(
+        &mut self,
+        order: JsValue,
+        nonce: Option<Nonce>,
+        options: JsValue,
+    ) -> Result<JsValue, JsError> {
+        let options = parse_sign_options(options)?;
+        let nonce_val = self.hooked_nonce(nonce)?;
+        if let Some(n) = nonce_val {
+            self.run_pre_sign_hook(&order, n)?;
+        }
+        let order_input = parse_order(order, self.input_mode)?;
 
-        let order_item: OrderItem = order_input
-            .try_into()
-            .map_err(|e: String| JsError::new(&e))?;
-        let nonce_val = nonce.map(|n| n as u64);
+        let order_item: OrderItem = order_input.try_into().map_err(keychain_error)?;
 
         let signed = self
             .inner
-            .sign(order_item, nonce_val)
-            .map_err(|e| JsError::new(&e.to_string()))?;
+            .sign_with_options(order_item, nonce_val, &options)
+            .map_err(keychain_error)?;
+
+        signed_to_js(&signed)
+    }
+
+    /// Sign multiple orders atomically in ONE transaction
+    ///
+    /// `groupKind` ('bracket' | 'oco' | 'batch' | custom) tags the output for
+    /// downstream routing; it is not signed. Legs that conflict with each
+    /// other (see `lint_group`) are reported as `groupWarnings`, or refused
+    /// when `strictGroup` is true.
+    #[wasm_bindgen(js_name = signGroup)]
+    pub fn sign_group(
+        &mut self,
+        orders: JsValue,
+        nonce: Option<Nonce>,
+        group_kind: Option<String>,
+        strict_group: Option<bool>,
+    ) -> Result<JsValue, JsError> {
+        let nonce_val = self.hooked_nonce(nonce)?;
+        if let Some(n) = nonce_val {
+            self.run_pre_sign_hook_all(&orders, |_| n)?;
+        }
+        let order_inputs = parse_orders(orders, self.input_mode)?;
+
+        let order_items: Result<Vec<OrderItem>, _> =
+            order_inputs.into_iter().map(|o| o.try_into()).collect();
+        let order_items = order_items.map_err(keychain_error)?;
+
+        let options = GroupOptions {
+            kind: group_kind.map(GroupKind::from),
+            strict: strict_group == Some(true),
+        };
+        let signed = self
+            .inner
+            .sign_group_with_options(order_items, &options, nonce_val)
+            .map_err(keychain_error)?;
+
+        signed_to_js(&signed)
+    }
+
+    /// Dry-run `orders` as `signAll` would: the rejections, limit-order
+    /// notional per symbol, nonces and transaction count, without signing
+    /// or taking nonces. With `specs` (symbol to `{ priceDecimals,
+    /// sizeDecimals, tickUnits?, lotUnits? }`), also the prices and sizes
+    /// rounding to the grid would change.
+    ///
+    /// The pre-sign hook set from JS is not called.
+    #[wasm_bindgen(js_name = planBatch)]
+    pub fn plan_batch(
+        &self,
+        orders: JsValue,
+        base_nonce: Option<Nonce>,
+        specs: JsValue,
+    ) -> Result<JsValue, JsError> {
+        let order_inputs = parse_orders(orders, self.input_mode)?;
+        let order_items: Result<Vec<OrderItem>, _> =
+            order_inputs.into_iter().map(|o| o.try_into()).collect();
+        let order_items = order_items.map_err(keychain_error)?;
+        let specs: std::collections::BTreeMap<String, bulk_keychain::input::SymbolSpecInput> =
+            if specs.is_undefined() || specs.is_null() {
+                Default::default()
+            } else {
+                serde_wasm_bindgen::from_value(specs).map_err(|e| JsError::new(&e.to_string()))?
+            };
+        let specs = specs
+            .into_iter()
+            .fold(bulk_keychain::SymbolSpecs::new(), |out, (symbol, spec)| {
+                out.with_spec(symbol, spec.into())
+            });
+
+        let plan = self
+            .inner
+            .plan_batch_with_specs(&order_items, nonce_in(base_nonce)?, &specs)
+            .map_err(keychain_error)?;
+        let field = |f| match f {
+            bulk_keychain::plan::AdjustedField::Price => "price",
+            bulk_keychain::plan::AdjustedField::Size => "size",
+        };
+        to_js(&serde_json::json!({
+            "perSymbolNotional": plan.per_symbol_notional,
+            "rejections": plan.rejections.iter().map(|(index, e)| {
+                serde_json::json!({ "index": index, "error": e.to_string() })
+            }).collect::<Vec<_>>(),
+            "adjustments": plan.adjustments.iter().map(|(index, a)| {
+                serde_json::json!({ "index": index, "field": field(a.field), "from": a.from, "to": a.to })
+            }).collect::<Vec<_>>(),
+            "nonceRange": { "start": plan.nonce_range.start, "end": plan.nonce_range.end },
+            "estimatedGroups": plan.estimated_groups,
+        }))
+    }
+
+    /// Order IDs `orders` will get, before signing them (`null` for
+    /// cancels). With `baseNonce`, order `i` is computed at `baseNonce + i`;
+    /// without one, fresh nonces are reserved and returned as
+    /// `reservation`, to pass to `signAllReserved` before it expires.
+    #[wasm_bindgen(js_name = peekOrderIds)]
+    pub fn peek_order_ids(
+        &self,
+        orders: JsValue,
+        base_nonce: Option<Nonce>,
+    ) -> Result<JsValue, JsError> {
+        let order_inputs = parse_orders(orders, self.input_mode)?;
+        let order_items: Result<Vec<OrderItem>, _> =
+            order_inputs.into_iter().map(|o| o.try_into()).collect();
+        let order_items = order_items.map_err(keychain_error)?;
+        let plan = nonce_in(base_nonce)?.map_or(
+            bulk_keychain::NoncePlan::Reserve,
+            bulk_keychain::NoncePlan::Explicit,
+        );
+        let peeked = self
+            .inner
+            .peek_order_ids(&order_items, plan)
+            .map_err(keychain_error)?;
+        to_js(&serde_json::json!({
+            "orderIds": peeked.order_ids,
+            "reservation": peeked.reservation,
+        }))
+    }
+
+    /// Sign raw bytes with this signer's key; returns the 64-byte signature.
+    /// Throws `KeypairZeroized` if the key was wiped.
+    #[wasm_bindgen(js_name = signBytes)]
+    pub fn sign_bytes(&self, bytes: &[u8]) -> Result<Vec<u8>, JsError> {
+        let signature = self
+            .inner
+            .try_sign_bytes_raw(bytes)
+            .map_err(keychain_error)?;
+        Ok(signature.to_vec())
+    }
 
-        serde_wasm_bindgen::to_value(&signed).map_err(|e| JsError::new(&e.to_string()))
+    /// Check a 64-byte Ed25519 signature over `message` against `pubkey` (base58).
+    /// Throws if `sig` is not 64 bytes.
+    #[wasm_bindgen(js_name = verifySignature)]
+    pub fn verify_signature(pubkey: &str, message: &[u8], sig: &[u8]) -> Result<bool, JsError> {
+        let pubkey = Pubkey::from_base58(pubkey).map_err(keychain_error)?;
+        let sig: [u8; 64] = sig.try_into().map_err(|_| {
+            JsError::new(&bulk_keychain::Error::InvalidSignatureLength(sig.len()).to_string())
+        })?;
+        Ok(Signer::verify(&pubkey, message, &sig))
     }
 
+    /// Check a signed transaction's signature against this signer's key.
+    /// Pass `messageBytes` to skip rebuilding the signed message from `tx.actions`.
+    #[wasm_bindgen(js_name = verifySignedTransaction)]
+    pub fn verify_signed_transaction(
+        &self,
+        tx: JsValue,
+        message_bytes: Option<Vec<u8>>,
+    ) -> Result<bool, JsError> {
+        let tx = tx_from_js(tx)?;
+        self.inner
+            .verify_signed_transaction(&tx, message_bytes.as_deref())
+            .map_err(keychain_error)
+    }
+
+    /// Sign a message prepared for this signer's key, keeping its account
+    ///
+    /// Completes agent-wallet flows with a local key: prepare with
+    /// `{ account: mainAccount, signer: agentPubkey }`, then sign here.
+    /// Throws if the message was prepared for another signer.
+    #[wasm_bindgen(js_name = signPrepared)]
+    pub fn sign_prepared(&mut self, prepared: &WasmPreparedMessage) -> Result<JsValue, JsError> {
+        let signed = self
+            .inner
+            .sign_prepared(prepared.inner.clone())
+            .map_err(keychain_error)?;
+        signed_to_js(&signed)
+    }
+}
+
+#[cfg(feature = "full")]
+#[wasm_bindgen]
+impl WasmSigner {
     /// Sign multiple orders - each becomes its own transaction (parallel)
+    ///
+    /// With `options.deadline`, orders are signed in sequence and the call
+    /// throws as soon as the deadline passes, naming how many were signed.
     #[wasm_bindgen(js_name = signAll)]
-    pub fn sign_all(&self, orders: JsValue, base_nonce: Option<f64>) -> Result<JsValue, JsError> {
-        let order_inputs: Vec<OrderInput> =
-            serde_wasm_bindgen::from_value(orders).map_err(|e| JsError::new(&e.to_string()))?;
+    pub fn sign_all(
+        &self,
+        orders: JsValue,
+        base_nonce: Option<Nonce>,
+        options: JsValue,
+    ) -> Result<JsValue, JsError> {
+        let options = parse_sign_options(options)?;
+        let base = self.hooked_base_nonce(base_nonce)?;
+        if let Some(base) = base {
+            self.run_pre_sign_hook_batch(&orders, base)?;
+        }
+        let order_inputs = parse_orders(orders, self.input_mode)?;
+
+        let order_items: Result<Vec<OrderItem>, _> =
+            order_inputs.into_iter().map(|o| o.try_into()).collect();
+        let order_items = order_items.map_err(keychain_error)?;
+
+        let signed = self
+            .inner
+            .sign_all_with_options(order_items, base, &options)
+            .map_err(keychain_error)?;
+
+        signed_all_to_js(&signed)
+    }
+
+    /// Sign `orders` at the nonces `peekOrderIds` reserved for them. The
+    /// reservation is single-use; an expired one, or different orders,
+    /// throw.
+    #[wasm_bindgen(js_name = signAllReserved)]
+    pub fn sign_all_reserved(
+        &self,
+        orders: JsValue,
+        reservation: JsValue,
+    ) -> Result<JsValue, JsError> {
+        let mut reservation: JsonValue = serde_wasm_bindgen::from_value(reservation)
+            .map_err(|e| JsError::new(&e.to_string()))?;
+        narrow_large_ints(&mut reservation);
+        let reservation: bulk_keychain::NonceReservation =
+            serde_json::from_value(reservation).map_err(|e| JsError::new(&e.to_string()))?;
+        let base = reservation.base_nonce;
+        self.run_pre_sign_hook_batch(&orders, base)?;
+        let order_inputs = parse_orders(orders, self.input_mode)?;
+        let order_items: Result<Vec<OrderItem>, _> =
+            order_inputs.into_iter().map(|o| o.try_into()).collect();
+        let order_items = order_items.map_err(keychain_error)?;
+
+        let signed = self
+            .inner
+            .sign_all_reserved(order_items, &reservation)
+            .map_err(keychain_error)?;
+        signed_all_to_js(&signed)
+    }
+
+    /// Sign the reduce-only orders that flatten a reported position
+    ///
+    /// `position` is `{ symbol, size, markPrice }` (size negative when short);
+    /// `style` is `{ type: 'market' }` or `{ type: 'limit', offsetBps }`; and
+    /// `specs` maps symbols to `{ spec?, maxOrderSize? }`. Each order is its own
+    /// transaction with nonce `baseNonce + i`; a flat position signs nothing.
+    #[wasm_bindgen(js_name = signFlatten)]
+    pub fn sign_flatten(
+        &self,
+        position: JsValue,
+        style: JsValue,
+        specs: JsValue,
+        base_nonce: Option<Nonce>,
+    ) -> Result<JsValue, JsError> {
+        let position: PositionInput =
+            serde_wasm_bindgen::from_value(position).map_err(|e| JsError::new(&e.to_string()))?;
+        let style: FlattenStyleInput =
+            serde_wasm_bindgen::from_value(style).map_err(|e| JsError::new(&e.to_string()))?;
+        let specs: Option<HashMap<String, FlattenSpecInput>> =
+            serde_wasm_bindgen::from_value(specs).map_err(|e| JsError::new(&e.to_string()))?;
+        let position = PositionSnapshot {
+            symbol: position.symbol,
+            size: position.size,
+            mark_price: position.mark_price,
+        };
+        let orders = flatten_position(&position, style.try_into()?, &flatten_specs(specs))
+            .map_err(keychain_error)?;
+        let items: Vec<OrderItem> = orders.into_iter().map(OrderItem::from).collect();
+
+        let base = self.hooked_base_nonce(base_nonce)?;
+        if let Some(base) = base {
+            bulk_keychain::nonce::check_nonce_range(base, items.len()).map_err(keychain_error)?;
+            for (i, item) in items.iter().enumerate() {
+                let order = serde::Serialize::serialize(
+                    &item.to_input_json(),
+                    &serde_wasm_bindgen::Serializer::json_compatible(),
+                )
+                .map_err(|e| JsError::new(&e.to_string()))?;
+                self.run_pre_sign_hook(&order, base + i as u64)?;
+            }
+        }
+
+        let signed = self.inner.sign_all(items, base).map_err(keychain_error)?;
+
+        signed_all_to_js(&signed)
+    }
+
+    /// Sign one limit order per price level - each becomes its own transaction (parallel)
+    ///
+    /// `prices[i]` is quoted with `sizes[i]`; level `i` uses nonce `baseNonce + i`.
+    #[wasm_bindgen(js_name = signAllPrices)]
+    pub fn sign_all_prices(
+        &self,
+        symbol: &str,
+        prices: &[f64],
+        sizes: &[f64],
+        is_buy: bool,
+        tif: &str,
+        base_nonce: Option<Nonce>,
+    ) -> Result<JsValue, JsError> {
+        if prices.len() != sizes.len() {
+            return Err(JsError::new(&format!(
+                "prices and sizes must have the same length, got {} and {}",
+                prices.len(),
+                sizes.len()
+            )));
+        }
+        let tif = parse_tif(tif).map_err(|e| JsError::new(&e))?;
+
+        let order_items: Vec<OrderItem> = prices
+            .iter()
+            .zip(sizes)
+            .map(|(&price, &size)| Order::limit(symbol, is_buy, price, size, tif).into())
+            .collect();
+
+        let base = nonce_in(base_nonce)?;
+        let signed = self
+            .inner
+            .sign_all(order_items, base)
+            .map_err(keychain_error)?;
+
+        signed_all_to_js(&signed)
+    }
+
+    /// Like signAll, but each result carries the `index` of its input order
+    #[wasm_bindgen(js_name = signAllIndexed)]
+    pub fn sign_all_indexed(
+        &self,
+        orders: JsValue,
+        base_nonce: Option<Nonce>,
+    ) -> Result<JsValue, JsError> {
+        let base = self.hooked_base_nonce(base_nonce)?;
+        if let Some(base) = base {
+            self.run_pre_sign_hook_batch(&orders, base)?;
+        }
+        let order_inputs = parse_orders(orders, self.input_mode)?;
 
         let order_items: Result<Vec<OrderItem>, _> =
             order_inputs.into_iter().map(|o| o.try_into()).collect();
-        let order_items = order_items.map_err(|e: String| JsError::new(&e))?;
+        let order_items = order_items.map_err(keychain_error)?;
 
-        let base = base_nonce.map(|n| n as u64);
         let signed = self
             .inner
-            .sign_all(order_items, base)
-            .map_err(|e| JsError::new(&e.to_string()))?;
+            .sign_all_indexed(order_items, base)
+            .map_err(keychain_error)?;
+
+        indexed_to_js(&signed)
+    }
+
+    /// Like signAllIndexed, but signs every order it can: result `i` is
+    /// `{ index, transaction }` or, if order `i` was refused, `{ index, error, code }`
+    #[wasm_bindgen(js_name = signAllPartialIndexed)]
+    pub fn sign_all_partial_indexed(
+        &self,
+        orders: JsValue,
+        base_nonce: Option<Nonce>,
+    ) -> Result<JsValue, JsError> {
+        let base = self.hooked_base_nonce(base_nonce)?;
+        if let Some(base) = base {
+            self.run_pre_sign_hook_batch(&orders, base)?;
+        }
+        let order_inputs = parse_orders(orders, self.input_mode)?;
+
+        let order_items: Result<Vec<OrderItem>, _> =
+            order_inputs.into_iter().map(|o| o.try_into()).collect();
+        let order_items = order_items.map_err(keychain_error)?;
+
+        let results = self
+            .inner
+            .sign_all_partial_indexed(order_items, base)
+            .map_err(keychain_error)?;
+
+        let array = js_sys::Array::new();
+        for (index, result) in results {
+            let value = js_sys::Object::new();
+            let set = |key: &str, v: JsValue| {
+                js_sys::Reflect::set(&value, &key.into(), &v)
+                    .map_err(|_| JsError::new("failed to set result field"))
+            };
+            set("index", (index as u32).into())?;
+            match result {
+                Ok(tx) => set("transaction", signed_to_js(&tx)?)?,
+                Err(e) => {
+                    set("error", e.to_string().into())?;
+                    set("code", e.code().into())?
+                }
+            };
+            array.push(&value);
+        }
+        Ok(array.into())
+    }
+
+    /// Re-sign a transaction rejected for a stale nonce under the next nonce
+    ///
+    /// Actions are preserved exactly; only nonce and signature change.
+    #[wasm_bindgen(js_name = resignWithNewNonce)]
+    pub fn resign_with_new_nonce(&mut self, tx: JsValue) -> Result<JsValue, JsError> {
+        let tx = tx_from_js(tx)?;
+        let resigned = self
+            .inner
+            .resign_with_new_nonce(&tx)
+            .map_err(keychain_error)?;
 
-        serde_wasm_bindgen::to_value(&signed).map_err(|e| JsError::new(&e.to_string()))
+        signed_to_js(&resigned)
     }
 
-    /// Sign multiple orders atomically in ONE transaction
-    #[wasm_bindgen(js_name = signGroup)]
-    pub fn sign_group(&mut self, orders: JsValue, nonce: Option<f64>) -> Result<JsValue, JsError> {
-        let order_inputs: Vec<OrderInput> =
-            serde_wasm_bindgen::from_value(orders).map_err(|e| JsError::new(&e.to_string()))?;
+    /// Refuse to resign transactions whose timestamp nonce is older than this (undefined = no limit)
+    #[wasm_bindgen(js_name = setMaxResignAgeMs)]
+    pub fn set_max_resign_age_ms(&mut self, max_age_ms: Option<f64>) {
+        self.inner
+            .set_max_resign_age_ms(max_age_ms.map(|ms| ms as u64));
+    }
 
-        let order_items: Result<Vec<OrderItem>, _> =
-            order_inputs.into_iter().map(|o| o.try_into()).collect();
-        let order_items = order_items.map_err(|e: String| JsError::new(&e))?;
+    /// Sign an order that only activates once a price condition is met
+    ///
+    /// `condition` is `{ symbol, above, price }`; `order` must be of type 'order'.
+    #[wasm_bindgen(js_name = signConditionalOrder)]
+    pub fn sign_conditional_order(
+        &mut self,
+        condition: JsValue,
+        order: JsValue,
+        nonce: Option<Nonce>,
+    ) -> Result<JsValue, JsError> {
+        let nonce = self.hooked_nonce(nonce)?;
+        if let Some(n) = nonce {
+            self.run_pre_sign_hook(&order, n)?;
+        }
+        let condition: ConditionInput =
+            serde_wasm_bindgen::from_value(condition).map_err(|e| JsError::new(&e.to_string()))?;
+        let order_input = parse_order(order, self.input_mode)?;
+        let OrderItem::Order(order) = order_input.try_into().map_err(keychain_error)? else {
+            return Err(JsError::new("conditional order must be of type 'order'"));
+        };
 
-        let nonce_val = nonce.map(|n| n as u64);
+        let condition = TriggerCondition::new(condition.symbol, condition.above, condition.price);
         let signed = self
             .inner
-            .sign_group(order_items, nonce_val)
-            .map_err(|e| JsError::new(&e.to_string()))?;
+            .sign_order_conditional(condition, order, nonce)
+            .map_err(keychain_error)?;
 
-        serde_wasm_bindgen::to_value(&signed).map_err(|e| JsError::new(&e.to_string()))
+        signed_to_js(&signed)
+    }
+
+    /// Sign a new size for a live order; same as `sign` with a `modify` item,
+    /// so the pre-sign hook runs. A price change needs a cancel-replace.
+    ///
+    /// @param orderId - order to amend (base58)
+    /// @param symbol - its market
+    /// @param amount - new size
+    /// @param nonce - optional nonce
+    #[wasm_bindgen(js_name = signModifyOrder)]
+    pub fn sign_modify_order(
+        &mut self,
+        order_id: &str,
+        symbol: &str,
+        amount: f64,
+        nonce: Option<Nonce>,
+    ) -> Result<JsValue, JsError> {
+        let modify = to_js(&serde_json::json!({
+            "type": "modify",
+            "orderId": order_id,
+            "symbol": symbol,
+            "amount": amount,
+        }))?;
+        self.sign(modify, nonce, JsValue::UNDEFINED)
     }
 
     // ========================================================================
@@ -248,14 +1420,11 @@ impl WasmSigner {
 
     /// Sign a faucet request (testnet only)
     #[wasm_bindgen(js_name = signFaucet)]
-    pub fn sign_faucet(&mut self, nonce: Option<f64>) -> Result<JsValue, JsError> {
-        let nonce_val = nonce.map(|n| n as u64);
-        let signed = self
-            .inner
-            .sign_faucet(nonce_val)
-            .map_err(|e| JsError::new(&e.to_string()))?;
+    pub fn sign_faucet(&mut self, nonce: Option<Nonce>) -> Result<JsValue, JsError> {
+        let nonce_val = nonce_in(nonce)?;
+        let signed = self.inner.sign_faucet(nonce_val).map_err(keychain_error)?;
 
-        serde_wasm_bindgen::to_value(&signed).map_err(|e| JsError::new(&e.to_string()))
+        signed_to_js(&signed)
     }
 
     /// Sign agent wallet creation/deletion
@@ -264,38 +1433,38 @@ impl WasmSigner {
         &mut self,
         agent_pubkey: &str,
         delete: bool,
-        nonce: Option<f64>,
+        nonce: Option<Nonce>,
     ) -> Result<JsValue, JsError> {
-        let agent = Pubkey::from_base58(agent_pubkey).map_err(|e| JsError::new(&e.to_string()))?;
-        let nonce_val = nonce.map(|n| n as u64);
+        let agent = Pubkey::from_base58(agent_pubkey).map_err(keychain_error)?;
+        let nonce_val = nonce_in(nonce)?;
 
         let signed = self
             .inner
             .sign_agent_wallet(agent, delete, nonce_val)
-            .map_err(|e| JsError::new(&e.to_string()))?;
+            .map_err(keychain_error)?;
 
-        serde_wasm_bindgen::to_value(&signed).map_err(|e| JsError::new(&e.to_string()))
+        signed_to_js(&signed)
     }
 
     /// Sign user settings update
+    ///
+    /// `settings` is `{ maxLeverage: [{ symbol, leverage }] }` or just the
+    /// array; entries may also be `[symbol, leverage]` pairs.
     #[wasm_bindgen(js_name = signUserSettings)]
     pub fn sign_user_settings(
         &mut self,
         settings: JsValue,
-        nonce: Option<f64>,
+        nonce: Option<Nonce>,
     ) -> Result<JsValue, JsError> {
-        let settings_input: UserSettingsInput =
-            serde_wasm_bindgen::from_value(settings).map_err(|e| JsError::new(&e.to_string()))?;
-
-        let user_settings = UserSettings::new(settings_input.max_leverage);
-        let nonce_val = nonce.map(|n| n as u64);
+        let user_settings = parse_user_settings(settings, self.input_mode)?;
+        let nonce_val = nonce_in(nonce)?;
 
         let signed = self
             .inner
             .sign_user_settings(user_settings, nonce_val)
-            .map_err(|e| JsError::new(&e.to_string()))?;
+            .map_err(keychain_error)?;
 
-        serde_wasm_bindgen::to_value(&signed).map_err(|e| JsError::new(&e.to_string()))
+        signed_to_js(&signed)
     }
 
     /// Sign one or more oracle price updates (`px`)
@@ -303,7 +1472,7 @@ impl WasmSigner {
     pub fn sign_oracle_prices(
         &mut self,
         oracles: JsValue,
-        nonce: Option<f64>,
+        nonce: Option<Nonce>,
     ) -> Result<JsValue, JsError> {
         let oracle_inputs: Vec<OraclePriceInput> =
             serde_wasm_bindgen::from_value(oracles).map_err(|e| JsError::new(&e.to_string()))?;
@@ -315,14 +1484,14 @@ impl WasmSigner {
                 price: o.price,
             })
             .collect();
-        let nonce_val = nonce.map(|n| n as u64);
+        let nonce_val = nonce_in(nonce)?;
 
         let signed = self
             .inner
             .sign_oracle_prices(oracle_prices, nonce_val)
-            .map_err(|e| JsError::new(&e.to_string()))?;
+            .map_err(keychain_error)?;
 
-        serde_wasm_bindgen::to_value(&signed).map_err(|e| JsError::new(&e.to_string()))
+        signed_to_js(&signed)
     }
 
     /// Sign a batch Pyth oracle update (`o`)
@@ -330,7 +1499,7 @@ impl WasmSigner {
     pub fn sign_pyth_oracle(
         &mut self,
         oracles: JsValue,
-        nonce: Option<f64>,
+        nonce: Option<Nonce>,
     ) -> Result<JsValue, JsError> {
         let oracle_inputs: Vec<PythOraclePriceInput> =
             serde_wasm_bindgen::from_value(oracles).map_err(|e| JsError::new(&e.to_string()))?;
@@ -343,42 +1512,42 @@ impl WasmSigner {
                 exponent: o.exponent,
             })
             .collect();
-        let nonce_val = nonce.map(|n| n as u64);
+        let nonce_val = nonce_in(nonce)?;
 
         let signed = self
             .inner
             .sign_pyth_oracle(pyth_oracles, nonce_val)
-            .map_err(|e| JsError::new(&e.to_string()))?;
+            .map_err(keychain_error)?;
 
-        serde_wasm_bindgen::to_value(&signed).map_err(|e| JsError::new(&e.to_string()))
+        signed_to_js(&signed)
     }
 
     /// Sign a margin transfer between accounts
     ///
-    /// @param kind - "internal" or "external" (defaults to "internal")
     /// @param fromPubkey - source account pubkey (base58)
     /// @param toPubkey - destination account pubkey (base58)
     /// @param marginSymbol - margin asset symbol (e.g. "USDC")
     /// @param marginAmount - amount to transfer
+    /// @param kind - "internal" or "external" (defaults to "internal")
     /// @param nonce - optional nonce
     #[wasm_bindgen(js_name = signTransfer)]
     pub fn sign_transfer(
         &mut self,
-        kind: Option<String>,
         from_pubkey: &str,
         to_pubkey: &str,
         margin_symbol: String,
         margin_amount: f64,
-        nonce: Option<f64>,
+        kind: Option<String>,
+        nonce: Option<Nonce>,
     ) -> Result<JsValue, JsError> {
-        let from = Pubkey::from_base58(from_pubkey).map_err(|e| JsError::new(&e.to_string()))?;
-        let to = Pubkey::from_base58(to_pubkey).map_err(|e| JsError::new(&e.to_string()))?;
+        let from = Pubkey::from_base58(from_pubkey).map_err(keychain_error)?;
+        let to = Pubkey::from_base58(to_pubkey).map_err(keychain_error)?;
         let kind = match kind.as_deref() {
             Some("external") => TransferKind::External,
             Some("internal") | None => TransferKind::Internal,
             Some(other) => return Err(JsError::new(&format!("Invalid transfer kind: {}", other))),
         };
-        let nonce_val = nonce.map(|n| n as u64);
+        let nonce_val = nonce_in(nonce)?;
         let transfer = Transfer {
             kind,
             from,
@@ -390,9 +1559,9 @@ impl WasmSigner {
         let signed = self
             .inner
             .sign_transfer(transfer, nonce_val)
-            .map_err(|e| JsError::new(&e.to_string()))?;
+            .map_err(keychain_error)?;
 
-        serde_wasm_bindgen::to_value(&signed).map_err(|e| JsError::new(&e.to_string()))
+        signed_to_js(&signed)
     }
 
     /// Sign a sub-account creation (optional initial margin transfer)
@@ -402,9 +1571,9 @@ impl WasmSigner {
         name: String,
         margin_symbol: Option<String>,
         margin_amount: Option<f64>,
-        nonce: Option<f64>,
+        nonce: Option<Nonce>,
     ) -> Result<JsValue, JsError> {
-        let nonce_val = nonce.map(|n| n as u64);
+        let nonce_val = nonce_in(nonce)?;
         let sub_account = CreateSubAccount {
             name,
             margin_symbol,
@@ -414,9 +1583,9 @@ impl WasmSigner {
         let signed = self
             .inner
             .sign_create_sub_account(sub_account, nonce_val)
-            .map_err(|e| JsError::new(&e.to_string()))?;
+            .map_err(keychain_error)?;
 
-        serde_wasm_bindgen::to_value(&signed).map_err(|e| JsError::new(&e.to_string()))
+        signed_to_js(&signed)
     }
 
     /// Sign a multisig creation
@@ -427,15 +1596,15 @@ impl WasmSigner {
         threshold: u32,
         time_lock_secs: Option<u32>,
         proposal_lifetime_secs: Option<u32>,
-        nonce: Option<f64>,
+        nonce: Option<Nonce>,
     ) -> Result<JsValue, JsError> {
         let signer_inputs: Vec<String> =
             serde_wasm_bindgen::from_value(signers).map_err(|e| JsError::new(&e.to_string()))?;
         let signers = signer_inputs
             .into_iter()
-            .map(|s| Pubkey::from_base58(&s).map_err(|e| JsError::new(&e.to_string())))
+            .map(|s| Pubkey::from_base58(&s).map_err(keychain_error))
             .collect::<Result<Vec<_>, _>>()?;
-        let nonce_val = nonce.map(|n| n as u64);
+        let nonce_val = nonce_in(nonce)?;
 
         let signed = self
             .inner
@@ -448,9 +1617,9 @@ impl WasmSigner {
                 },
                 nonce_val,
             )
-            .map_err(|e| JsError::new(&e.to_string()))?;
+            .map_err(keychain_error)?;
 
-        serde_wasm_bindgen::to_value(&signed).map_err(|e| JsError::new(&e.to_string()))
+        signed_to_js(&signed)
     }
 
     /// Sign a multisig proposal
@@ -459,18 +1628,18 @@ impl WasmSigner {
         &mut self,
         multisig: &str,
         actions: JsValue,
-        nonce: Option<f64>,
+        nonce: Option<Nonce>,
     ) -> Result<JsValue, JsError> {
-        let multisig = Pubkey::from_base58(multisig).map_err(|e| JsError::new(&e.to_string()))?;
+        let multisig = Pubkey::from_base58(multisig).map_err(keychain_error)?;
         let actions = parse_action_values(actions)?;
-        let nonce_val = nonce.map(|n| n as u64);
+        let nonce_val = nonce_in(nonce)?;
 
         let signed = self
             .inner
             .sign_multisig_propose(MultisigPropose::new(multisig, actions), nonce_val)
-            .map_err(|e| JsError::new(&e.to_string()))?;
+            .map_err(keychain_error)?;
 
-        serde_wasm_bindgen::to_value(&signed).map_err(|e| JsError::new(&e.to_string()))
+        signed_to_js(&signed)
     }
 
     /// Sign a multisig approval
@@ -479,10 +1648,10 @@ impl WasmSigner {
         &mut self,
         multisig: &str,
         proposal_id: f64,
-        nonce: Option<f64>,
+        nonce: Option<Nonce>,
     ) -> Result<JsValue, JsError> {
-        let multisig = Pubkey::from_base58(multisig).map_err(|e| JsError::new(&e.to_string()))?;
-        let nonce_val = nonce.map(|n| n as u64);
+        let multisig = Pubkey::from_base58(multisig).map_err(keychain_error)?;
+        let nonce_val = nonce_in(nonce)?;
 
         let signed = self
             .inner
@@ -490,9 +1659,9 @@ impl WasmSigner {
                 MultisigApprove::new(multisig, proposal_id as u64),
                 nonce_val,
             )
-            .map_err(|e| JsError::new(&e.to_string()))?;
+            .map_err(keychain_error)?;
 
-        serde_wasm_bindgen::to_value(&signed).map_err(|e| JsError::new(&e.to_string()))
+        signed_to_js(&signed)
     }
 
     /// Sign a multisig rejection
@@ -501,17 +1670,17 @@ impl WasmSigner {
         &mut self,
         multisig: &str,
         proposal_id: f64,
-        nonce: Option<f64>,
+        nonce: Option<Nonce>,
     ) -> Result<JsValue, JsError> {
-        let multisig = Pubkey::from_base58(multisig).map_err(|e| JsError::new(&e.to_string()))?;
-        let nonce_val = nonce.map(|n| n as u64);
+        let multisig = Pubkey::from_base58(multisig).map_err(keychain_error)?;
+        let nonce_val = nonce_in(nonce)?;
 
         let signed = self
             .inner
             .sign_multisig_reject(MultisigReject::new(multisig, proposal_id as u64), nonce_val)
-            .map_err(|e| JsError::new(&e.to_string()))?;
+            .map_err(keychain_error)?;
 
-        serde_wasm_bindgen::to_value(&signed).map_err(|e| JsError::new(&e.to_string()))
+        signed_to_js(&signed)
     }
 
     /// Sign a multisig cancellation
@@ -520,17 +1689,17 @@ impl WasmSigner {
         &mut self,
         multisig: &str,
         proposal_id: f64,
-        nonce: Option<f64>,
+        nonce: Option<Nonce>,
     ) -> Result<JsValue, JsError> {
-        let multisig = Pubkey::from_base58(multisig).map_err(|e| JsError::new(&e.to_string()))?;
-        let nonce_val = nonce.map(|n| n as u64);
+        let multisig = Pubkey::from_base58(multisig).map_err(keychain_error)?;
+        let nonce_val = nonce_in(nonce)?;
 
         let signed = self
             .inner
             .sign_multisig_cancel(MultisigCancel::new(multisig, proposal_id as u64), nonce_val)
-            .map_err(|e| JsError::new(&e.to_string()))?;
+            .map_err(keychain_error)?;
 
-        serde_wasm_bindgen::to_value(&signed).map_err(|e| JsError::new(&e.to_string()))
+        signed_to_js(&signed)
     }
 
     /// Sign a multisig execution
@@ -539,10 +1708,10 @@ impl WasmSigner {
         &mut self,
         multisig: &str,
         proposal_id: f64,
-        nonce: Option<f64>,
+        nonce: Option<Nonce>,
     ) -> Result<JsValue, JsError> {
-        let multisig = Pubkey::from_base58(multisig).map_err(|e| JsError::new(&e.to_string()))?;
-        let nonce_val = nonce.map(|n| n as u64);
+        let multisig = Pubkey::from_base58(multisig).map_err(keychain_error)?;
+        let nonce_val = nonce_in(nonce)?;
 
         let signed = self
             .inner
@@ -550,9 +1719,9 @@ impl WasmSigner {
                 MultisigExecute::new(multisig, proposal_id as u64),
                 nonce_val,
             )
-            .map_err(|e| JsError::new(&e.to_string()))?;
+            .map_err(keychain_error)?;
 
-        serde_wasm_bindgen::to_value(&signed).map_err(|e| JsError::new(&e.to_string()))
+        signed_to_js(&signed)
     }
 
     /// Sign a multisig policy update
@@ -564,16 +1733,16 @@ impl WasmSigner {
         threshold: u32,
         time_lock_secs: Option<u32>,
         proposal_lifetime_secs: Option<u32>,
-        nonce: Option<f64>,
+        nonce: Option<Nonce>,
     ) -> Result<JsValue, JsError> {
-        let multisig = Pubkey::from_base58(multisig).map_err(|e| JsError::new(&e.to_string()))?;
+        let multisig = Pubkey::from_base58(multisig).map_err(keychain_error)?;
         let signer_inputs: Vec<String> =
             serde_wasm_bindgen::from_value(signers).map_err(|e| JsError::new(&e.to_string()))?;
         let signers = signer_inputs
             .into_iter()
-            .map(|s| Pubkey::from_base58(&s).map_err(|e| JsError::new(&e.to_string())))
+            .map(|s| Pubkey::from_base58(&s).map_err(keychain_error))
             .collect::<Result<Vec<_>, _>>()?;
-        let nonce_val = nonce.map(|n| n as u64);
+        let nonce_val = nonce_in(nonce)?;
 
         let signed = self
             .inner
@@ -587,9 +1756,9 @@ impl WasmSigner {
                 },
                 nonce_val,
             )
-            .map_err(|e| JsError::new(&e.to_string()))?;
+            .map_err(keychain_error)?;
 
-        serde_wasm_bindgen::to_value(&signed).map_err(|e| JsError::new(&e.to_string()))
+        signed_to_js(&signed)
     }
 
     /// Sign a sub-account removal
@@ -600,17 +1769,17 @@ impl WasmSigner {
     pub fn sign_remove_sub_account(
         &mut self,
         to_remove: &str,
-        nonce: Option<f64>,
+        nonce: Option<Nonce>,
     ) -> Result<JsValue, JsError> {
-        let target = Pubkey::from_base58(to_remove).map_err(|e| JsError::new(&e.to_string()))?;
-        let nonce_val = nonce.map(|n| n as u64);
+        let target = Pubkey::from_base58(to_remove).map_err(keychain_error)?;
+        let nonce_val = nonce_in(nonce)?;
 
         let signed = self
             .inner
             .sign_remove_sub_account(target, nonce_val)
-            .map_err(|e| JsError::new(&e.to_string()))?;
+            .map_err(keychain_error)?;
 
-        serde_wasm_bindgen::to_value(&signed).map_err(|e| JsError::new(&e.to_string()))
+        signed_to_js(&signed)
     }
 
     /// Sign a sub-account rename
@@ -623,17 +1792,17 @@ impl WasmSigner {
         &mut self,
         subaccount: &str,
         name: String,
-        nonce: Option<f64>,
+        nonce: Option<Nonce>,
     ) -> Result<JsValue, JsError> {
-        let account = Pubkey::from_base58(subaccount).map_err(|e| JsError::new(&e.to_string()))?;
-        let nonce_val = nonce.map(|n| n as u64);
+        let account = Pubkey::from_base58(subaccount).map_err(keychain_error)?;
+        let nonce_val = nonce_in(nonce)?;
 
         let signed = self
             .inner
             .sign_rename_sub_account(RenameSubAccount { account, name }, nonce_val)
-            .map_err(|e| JsError::new(&e.to_string()))?;
+            .map_err(keychain_error)?;
 
-        serde_wasm_bindgen::to_value(&signed).map_err(|e| JsError::new(&e.to_string()))
+        signed_to_js(&signed)
     }
 
     /// Sign whitelist/un-whitelist faucet access (`whitelistFaucet`)
@@ -642,18 +1811,17 @@ impl WasmSigner {
         &mut self,
         target_pubkey: &str,
         whitelist: bool,
-        nonce: Option<f64>,
+        nonce: Option<Nonce>,
     ) -> Result<JsValue, JsError> {
-        let target =
-            Pubkey::from_base58(target_pubkey).map_err(|e| JsError::new(&e.to_string()))?;
-        let nonce_val = nonce.map(|n| n as u64);
+        let target = Pubkey::from_base58(target_pubkey).map_err(keychain_error)?;
+        let nonce_val = nonce_in(nonce)?;
 
         let signed = self
             .inner
             .sign_whitelist_faucet(target, whitelist, nonce_val)
-            .map_err(|e| JsError::new(&e.to_string()))?;
+            .map_err(keychain_error)?;
 
-        serde_wasm_bindgen::to_value(&signed).map_err(|e| JsError::new(&e.to_string()))
+        signed_to_js(&signed)
     }
 
     // ========================================================================
@@ -662,8 +1830,12 @@ impl WasmSigner {
 
     /// @deprecated Use sign(), signAll(), or signGroup() instead
     #[wasm_bindgen(js_name = signOrder)]
-    pub fn sign_order(&mut self, orders: JsValue, nonce: Option<f64>) -> Result<JsValue, JsError> {
-        self.sign_group(orders, nonce)
+    pub fn sign_order(
+        &mut self,
+        orders: JsValue,
+        nonce: Option<Nonce>,
+    ) -> Result<JsValue, JsError> {
+        self.sign_group(orders, nonce, None, None)
     }
 
     /// @deprecated Use signAll() instead
@@ -671,27 +1843,299 @@ impl WasmSigner {
     pub fn sign_orders_batch(
         &self,
         batches: JsValue,
-        base_nonce: Option<f64>,
+        base_nonce: Option<Nonce>,
     ) -> Result<JsValue, JsError> {
         #[allow(deprecated)]
         {
-            let batch_inputs: Vec<Vec<OrderInput>> = serde_wasm_bindgen::from_value(batches)
+            let batch_inputs: Vec<Vec<JsonValue>> = serde_wasm_bindgen::from_value(batches)
                 .map_err(|e| JsError::new(&e.to_string()))?;
+            let batch_inputs = batch_inputs
+                .into_iter()
+                .map(|batch| {
+                    batch
+                        .into_iter()
+                        .map(|order| order_from_json(order, self.input_mode))
+                        .collect::<Result<Vec<_>, _>>()
+                })
+                .collect::<Result<Vec<_>, _>>()?;
 
-            let order_batches: Result<Vec<Vec<OrderItem>>, String> = batch_inputs
+            let order_batches: Result<Vec<Vec<OrderItem>>, _> = batch_inputs
                 .into_iter()
                 .map(|batch| batch.into_iter().map(|o| o.try_into()).collect())
                 .collect();
-            let order_batches = order_batches.map_err(|e| JsError::new(&e))?;
+            let order_batches = order_batches.map_err(keychain_error)?;
 
-            let base = base_nonce.map(|n| n as u64);
+            let base = nonce_in(base_nonce)?;
             let signed = self
                 .inner
                 .sign_orders_batch(order_batches, base)
+                .map_err(keychain_error)?;
+
+            signed_all_to_js(&signed)
+        }
+    }
+
+    /// @deprecated Use signAllIndexed() instead
+    #[wasm_bindgen(js_name = signOrdersBatchIndexed)]
+    pub fn sign_orders_batch_indexed(
+        &self,
+        batches: JsValue,
+        base_nonce: Option<Nonce>,
+    ) -> Result<JsValue, JsError> {
+        #[allow(deprecated)]
+        {
+            let batch_inputs: Vec<Vec<JsonValue>> = serde_wasm_bindgen::from_value(batches)
                 .map_err(|e| JsError::new(&e.to_string()))?;
+            let batch_inputs = batch_inputs
+                .into_iter()
+                .map(|batch| {
+                    batch
+                        .into_iter()
+                        .map(|order| order_from_json(order, self.input_mode))
+                        .collect::<Result<Vec<_>, _>>()
+                })
+                .collect::<Result<Vec<_>, _>>()?;
 
-            serde_wasm_bindgen::to_value(&signed).map_err(|e| JsError::new(&e.to_string()))
+            let order_batches: Result<Vec<Vec<OrderItem>>, _> = batch_inputs
+                .into_iter()
+                .map(|batch| batch.into_iter().map(|o| o.try_into()).collect())
+                .collect();
+            let order_batches = order_batches.map_err(keychain_error)?;
+
+            let base = nonce_in(base_nonce)?;
+            let signed = self
+                .inner
+                .sign_orders_batch_indexed(order_batches, base)
+                .map_err(keychain_error)?;
+
+            indexed_to_js(&signed)
+        }
+    }
+}
+
+// ============================================================================
+// Batch Builder
+// ============================================================================
+
+/// Builder for a batch of mixed transactions (singles, atomic groups, cancel batches)
+#[cfg(feature = "full")]
+#[wasm_bindgen]
+pub struct WasmBatchBuilder {
+    inner: BatchBuilder,
+}
+
+#[cfg(feature = "full")]
+#[wasm_bindgen]
+impl WasmBatchBuilder {
+    /// Create an empty batch
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> Self {
+        Self {
+            inner: BatchBuilder::new(),
+        }
+    }
+
+    /// Append a single order/cancel/cancelAll
+    #[wasm_bindgen(js_name = addSingle)]
+    pub fn add_single(&mut self, order: JsValue) -> Result<(), JsError> {
+        let order_input: OrderInput =
+            serde_wasm_bindgen::from_value(order).map_err(|e| JsError::new(&e.to_string()))?;
+        let item: OrderItem = order_input.try_into().map_err(keychain_error)?;
+        self.inner.push(BatchEntry::Single(item));
+        Ok(())
+    }
+
+    /// Append orders to be signed atomically in one transaction
+    #[wasm_bindgen(js_name = addGroup)]
+    pub fn add_group(&mut self, orders: JsValue) -> Result<(), JsError> {
+        let order_inputs: Vec<OrderInput> =
+            serde_wasm_bindgen::from_value(orders).map_err(|e| JsError::new(&e.to_string()))?;
+        let items: Result<Vec<OrderItem>, _> =
+            order_inputs.into_iter().map(|o| o.try_into()).collect();
+        let items = items.map_err(keychain_error)?;
+        self.inner.push(BatchEntry::Group(items));
+        Ok(())
+    }
+
+    /// Append cancels ([{ symbol, orderId }]) to be signed together in one transaction
+    #[wasm_bindgen(js_name = addCancelBatch)]
+    pub fn add_cancel_batch(&mut self, cancels: JsValue) -> Result<(), JsError> {
+        let cancel_inputs: Vec<CancelInput> =
+            serde_wasm_bindgen::from_value(cancels).map_err(|e| JsError::new(&e.to_string()))?;
+        let cancels: Result<Vec<Cancel>, JsError> = cancel_inputs
+            .into_iter()
+            .map(|c| {
+                let order_id = Hash::from_base58(&c.order_id)
+                    .map_err(|e| JsError::new(&format!("Invalid orderId: {}", e)))?;
+                Ok(Cancel::new(c.symbol, order_id))
+            })
+            .collect();
+        self.inner.push(BatchEntry::CancelBatch(cancels?));
+        Ok(())
+    }
+
+    /// Number of pending entries
+    #[wasm_bindgen(getter)]
+    pub fn length(&self) -> usize {
+        self.inner.len()
+    }
+
+    /// Sign all entries in order (entry i uses nonce base + i); the builder is emptied
+    #[wasm_bindgen]
+    pub fn build(
+        &mut self,
+        signer: &mut WasmSigner,
+        base_nonce: Option<Nonce>,
+    ) -> Result<JsValue, JsError> {
+        let base = nonce_in(base_nonce)?;
+        let signed = self
+            .inner
+            .build(&mut signer.inner, base)
+            .map_err(keychain_error)?;
+
+        signed_all_to_js(&signed)
+    }
+}
+
+#[cfg(feature = "full")]
+impl Default for WasmBatchBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// ============================================================================
+// Remote Session
+// ============================================================================
+
+/// App side of a prepare/finalize session over postMessage strings
+#[cfg(feature = "full")]
+#[wasm_bindgen]
+pub struct WasmSessionInitiator {
+    inner: SessionInitiator,
+}
+
+#[cfg(feature = "full")]
+#[wasm_bindgen]
+impl WasmSessionInitiator {
+    /// Start a session keyed by a shared secret (at least 16 bytes)
+    #[wasm_bindgen(constructor)]
+    pub fn new(secret: &[u8], timeout_ms: Option<f64>) -> Result<WasmSessionInitiator, JsError> {
+        let mut inner = RemoteSession::initiator(secret).map_err(keychain_error)?;
+        if let Some(ms) = timeout_ms {
+            inner = inner.with_timeout_ms(ms as u64);
         }
+        Ok(Self { inner })
+    }
+
+    /// Session id shared by every envelope
+    #[wasm_bindgen(getter, js_name = sessionId)]
+    pub fn session_id(&self) -> String {
+        self.inner.session_id().to_string()
+    }
+
+    /// Requests awaiting a response
+    #[wasm_bindgen(getter, js_name = pendingCount)]
+    pub fn pending_count(&self) -> usize {
+        self.inner.pending_count()
+    }
+
+    /// Wrap a prepared message in a request envelope to post to the wallet
+    #[wasm_bindgen]
+    pub fn request(&mut self, prepared: &WasmPreparedMessage) -> Result<String, JsError> {
+        self.inner
+            .request(prepared.inner.clone())
+            .map_err(keychain_error)
+    }
+
+    /// Check a response envelope and return the finalized SignedTransaction
+    #[wasm_bindgen(js_name = handleResponse)]
+    pub fn handle_response(&mut self, envelope: &str) -> Result<JsValue, JsError> {
+        let signed = self
+            .inner
+            .handle_response(envelope)
+            .map_err(keychain_error)?;
+        signed_to_js(&signed)
+    }
+
+    /// Drop requests past the timeout; returns their sequence numbers
+    #[wasm_bindgen]
+    pub fn expire(&mut self, now_ms: Option<f64>) -> Vec<f64> {
+        let now = now_ms.map_or_else(bulk_keychain::nonce::current_timestamp_millis, |n| n as u64);
+        self.inner
+            .expire(now)
+            .into_iter()
+            .map(|seq| seq as f64)
+            .collect()
+    }
+}
+
+/// Wallet side of a prepare/finalize session over postMessage strings
+#[cfg(feature = "full")]
+#[wasm_bindgen]
+pub struct WasmSessionResponder {
+    inner: SessionResponder,
+}
+
+#[cfg(feature = "full")]
+#[wasm_bindgen]
+impl WasmSessionResponder {
+    /// Answer requests by signing with `keypair`
+    #[wasm_bindgen(js_name = fromKeypair)]
+    pub fn from_keypair(
+        secret: &[u8],
+        keypair: &WasmKeypair,
+    ) -> Result<WasmSessionResponder, JsError> {
+        let inner = RemoteSession::responder(
+            secret,
+            Signer::new(
+                keypair
+                    .inner
+                    .clone_tracked("WasmSessionResponder::fromKeypair"),
+            ),
+        )
+        .map_err(keychain_error)?;
+        Ok(Self { inner })
+    }
+
+    /// Answer requests with `sign(prepared)`, which returns a base58 string or
+    /// 64-byte Uint8Array signature over `prepared.messageBytes`. Throwing rejects.
+    #[wasm_bindgen(js_name = withCallback)]
+    pub fn with_callback(
+        secret: &[u8],
+        sign: js_sys::Function,
+    ) -> Result<WasmSessionResponder, JsError> {
+        let backend = ResponderBackend::callback(move |prepared| {
+            let arg = JsValue::from(WasmPreparedMessage::new(prepared.clone()));
+            let out = sign.call1(&JsValue::NULL, &arg).map_err(|e| {
+                let reason = match e.dyn_ref::<js_sys::Error>() {
+                    Some(err) => String::from(err.message()),
+                    None => e.as_string().unwrap_or_else(|| format!("{:?}", e)),
+                };
+                bulk_keychain::Error::SigningFailed(reason)
+            })?;
+            match out.as_string() {
+                Some(signature) => Ok(signature),
+                None => Ok(
+                    bulk_keychain::bs58::encode(js_sys::Uint8Array::new(&out).to_vec())
+                        .into_string(),
+                ),
+            }
+        });
+        let inner = RemoteSession::responder(secret, backend).map_err(keychain_error)?;
+        Ok(Self { inner })
+    }
+
+    /// Session id, once the first request arrived
+    #[wasm_bindgen(getter, js_name = sessionId)]
+    pub fn session_id(&self) -> Option<String> {
+        self.inner.session_id().map(str::to_string)
+    }
+
+    /// Check a request envelope and return the response envelope to post back
+    #[wasm_bindgen(js_name = handleRequest)]
+    pub fn handle_request(&mut self, envelope: &str) -> Result<String, JsError> {
+        self.inner.handle_request(envelope).map_err(keychain_error)
     }
 }
 
@@ -699,719 +2143,238 @@ impl WasmSigner {
 // Input types for JS interop
 // ============================================================================
 
+#[cfg(feature = "full")]
 #[derive(Debug, Deserialize)]
 #[serde(rename_all = "camelCase")]
-struct OnFillInput {
-    p: u32,
-    actions: Vec<OrderInput>,
+struct CancelInput {
+    symbol: String,
+    order_id: String,
 }
 
+#[cfg(feature = "full")]
 #[derive(Debug, Deserialize)]
 #[serde(rename_all = "camelCase")]
-struct OrderInput {
-    #[serde(rename = "type")]
-    item_type: String,
-    symbol: Option<String>,
-    is_buy: Option<bool>,
-    price: Option<f64>,
-    size: Option<f64>,
-    reduce_only: Option<bool>,
-    iso: Option<bool>,
-    order_type: Option<OrderTypeInput>,
-    client_id: Option<String>,
-    order_id: Option<String>,
-    amount: Option<f64>,
-    symbols: Option<Vec<String>>,
-    trigger_price: Option<f64>,
-    limit_price: Option<f64>,
-    pmin: Option<f64>,
-    pmax: Option<f64>,
-    lmin: Option<f64>,
-    lmax: Option<f64>,
-    actions: Option<Vec<OrderInput>>,
-    on_fill: Option<OnFillInput>,
-    trail_bps: Option<u32>,
-    step_bps: Option<u32>,
+struct PositionInput {
+    symbol: String,
+    size: f64,
+    mark_price: f64,
 }
 
+#[cfg(feature = "full")]
 #[derive(Debug, Deserialize)]
 #[serde(rename_all = "camelCase")]
-struct OrderTypeInput {
+struct FlattenStyleInput {
     #[serde(rename = "type")]
-    type_name: String,
-    tif: Option<String>,
-    is_market: Option<bool>,
-    trigger_px: Option<f64>,
+    style_type: String,
+    offset_bps: Option<f64>,
 }
 
-#[derive(Debug, Deserialize)]
-#[serde(rename_all = "camelCase")]
-struct UserSettingsInput {
-    max_leverage: Vec<(String, f64)>,
+#[cfg(feature = "full")]
+impl TryFrom<FlattenStyleInput> for FlattenStyle {
+    type Error = JsError;
+
+    fn try_from(style: FlattenStyleInput) -> Result<Self, Self::Error> {
+        match style.style_type.as_str() {
+            "market" => Ok(FlattenStyle::Market),
+            "limit" => style
+                .offset_bps
+                .map(FlattenStyle::LimitAtOffsetBps)
+                .ok_or_else(|| JsError::new("style.offsetBps is required")),
+            other => Err(JsError::new(&format!(
+                "style.type: unknown flatten style {}",
+                other
+            ))),
+        }
+    }
 }
 
+/// Per-symbol granularity and order-size cap for `signFlatten`
+#[cfg(feature = "full")]
 #[derive(Debug, Deserialize)]
 #[serde(rename_all = "camelCase")]
-struct OraclePriceInput {
-    timestamp: u64,
-    asset: String,
-    price: f64,
+struct FlattenSpecInput {
+    spec: Option<bulk_keychain::input::SymbolSpecInput>,
+    max_order_size: Option<f64>,
 }
 
-#[derive(Debug, Deserialize)]
-#[serde(rename_all = "camelCase")]
-struct PythOraclePriceInput {
-    timestamp: u64,
-    feed_index: u64,
-    price: u64,
-    exponent: i16,
+#[cfg(feature = "full")]
+fn flatten_specs(specs: Option<HashMap<String, FlattenSpecInput>>) -> SymbolSpecs {
+    let mut out = SymbolSpecs::new();
+    for (symbol, input) in specs.unwrap_or_default() {
+        if let Some(spec) = input.spec {
+            out = out.with_spec(symbol.clone(), spec.into());
+        }
+        if let Some(max) = input.max_order_size {
+            out = out.with_max_order_size(symbol, max);
+        }
+    }
+    out
 }
 
-impl TryFrom<OrderInput> for OrderItem {
-    type Error = String;
-
-    fn try_from(input: OrderInput) -> Result<Self, Self::Error> {
-        match input.item_type.as_str() {
-            "order" => {
-                let symbol = input.symbol.ok_or("order.symbol is required")?;
-                let is_buy = input.is_buy.ok_or("order.isBuy is required")?;
-                let price = input.price.ok_or("order.price is required")?;
-                let size = input.size.ok_or("order.size is required")?;
-                let reduce_only = input.reduce_only.unwrap_or(false);
-                let iso = input.iso.unwrap_or(false);
-
-                let order_type = match input.order_type {
-                    Some(ot) => match ot.type_name.as_str() {
-                        "limit" => {
-                            let tif_str = ot.tif.as_deref().unwrap_or("GTC");
-                            let tif = match tif_str.to_uppercase().as_str() {
-                                "GTC" => TimeInForce::Gtc,
-                                "IOC" => TimeInForce::Ioc,
-                                "ALO" => TimeInForce::Alo,
-                                _ => return Err(format!("Invalid tif: {}", tif_str)),
-                            };
-                            OrderType::limit(tif)
-                        }
-                        "trigger" | "market" => OrderType::Trigger {
-                            is_market: ot.is_market.unwrap_or(true),
-                            trigger_px: ot.trigger_px.unwrap_or(0.0),
-                        },
-                        _ => return Err(format!("Invalid orderType: {}", ot.type_name)),
-                    },
-                    None => OrderType::limit(TimeInForce::Gtc),
-                };
-
-                let client_id = input
-                    .client_id
-                    .map(|s| Hash::from_base58(&s))
-                    .transpose()
-                    .map_err(|e| format!("Invalid clientId: {}", e))?;
-
-                let mut order = Order {
-                    symbol,
-                    is_buy,
-                    price,
-                    size,
-                    reduce_only,
-                    iso,
-                    order_type,
-                    client_id: None,
-                };
-                if let Some(cid) = client_id {
-                    order.client_id = Some(cid);
-                }
+#[cfg(feature = "full")]
+#[derive(Debug, Deserialize)]
+struct ConditionInput {
+    symbol: String,
+    above: bool,
+    price: f64,
+}
 
-                Ok(OrderItem::Order(order))
-            }
-            "cancel" => {
-                let symbol = input.symbol.ok_or("cancel.symbol is required")?;
-                let order_id_str = input.order_id.ok_or("cancel.orderId is required")?;
-                let order_id = Hash::from_base58(&order_id_str)
-                    .map_err(|e| format!("Invalid orderId: {}", e))?;
+/// An order intent, read by the core's casing-tolerant [`OrderSpec`] parser
+#[derive(Debug)]
+struct OrderInput(OrderSpec);
 
-                Ok(OrderItem::Cancel(Cancel::new(symbol, order_id)))
-            }
-            "modify" => {
-                let symbol = input.symbol.ok_or("modify.symbol is required")?;
-                let order_id_str = input.order_id.ok_or("modify.orderId is required")?;
-                let amount = input.amount.ok_or("modify.amount is required")?;
-                let order_id = Hash::from_base58(&order_id_str)
-                    .map_err(|e| format!("Invalid orderId: {}", e))?;
-                Ok(OrderItem::Modify(Modify::new(order_id, symbol, amount)))
-            }
-            "cancelAll" => {
-                let symbols = input.symbols.unwrap_or_default();
-                Ok(OrderItem::CancelAll(CancelAll::for_symbols(symbols)))
-            }
-            "stop" | "st" => {
-                let symbol = input.symbol.ok_or("stop.symbol is required")?;
-                let is_buy = input.is_buy.ok_or("stop.isBuy is required")?;
-                let size = input.size.ok_or("stop.size is required")?;
-                let trigger_price = input.trigger_price.ok_or("stop.triggerPrice is required")?;
-                let limit_price = input.limit_price.unwrap_or(f64::NAN);
-                Ok(OrderItem::Stop(Stop {
-                    symbol,
-                    is_buy,
-                    size,
-                    trigger_price,
-                    limit_price,
-                    iso: input.iso.unwrap_or(false),
-                }))
-            }
-            "takeProfit" | "tp" => {
-                let symbol = input.symbol.ok_or("takeProfit.symbol is required")?;
-                let is_buy = input.is_buy.ok_or("takeProfit.isBuy is required")?;
-                let size = input.size.ok_or("takeProfit.size is required")?;
-                let trigger_price = input
-                    .trigger_price
-                    .ok_or("takeProfit.triggerPrice is required")?;
-                let limit_price = input.limit_price.unwrap_or(f64::NAN);
-                Ok(OrderItem::TakeProfit(TakeProfit {
-                    symbol,
-                    is_buy,
-                    size,
-                    trigger_price,
-                    limit_price,
-                    iso: input.iso.unwrap_or(false),
-                }))
-            }
-            "range" | "rng" => {
-                let symbol = input.symbol.ok_or("range.symbol is required")?;
-                let is_buy = input.is_buy.ok_or("range.isBuy is required")?;
-                let size = input.size.ok_or("range.size is required")?;
-                let collar_min = input.pmin.ok_or("range.pmin is required")?;
-                let collar_max = input.pmax.ok_or("range.pmax is required")?;
-                let limit_min = input.lmin.unwrap_or(f64::NAN);
-                let limit_max = input.lmax.unwrap_or(f64::NAN);
-                Ok(OrderItem::RangeOco(RangeOco {
-                    symbol,
-                    is_buy,
-                    size,
-                    collar_min,
-                    collar_max,
-                    limit_min,
-                    limit_max,
-                    iso: input.iso.unwrap_or(false),
-                }))
-            }
-            "trig" => {
-                let symbol = input.symbol.ok_or("trig.symbol is required")?;
-                let is_buy = input.is_buy.ok_or("trig.isBuy is required")?;
-                let trigger_price = input.trigger_price.ok_or("trig.triggerPrice is required")?;
-                let raw_actions = input.actions.ok_or("trig.actions is required")?;
-                let iso = input.iso.unwrap_or(false);
-                let actions: Result<Vec<OrderItem>, String> =
-                    raw_actions.into_iter().map(|a| a.try_into()).collect();
-                Ok(OrderItem::TriggerBasket(TriggerBasket {
-                    symbol,
-                    is_buy,
-                    trigger_price,
-                    actions: actions?,
-                    iso,
-                }))
-            }
-            "onFill" | "of" => {
-                let raw_actions = input.actions.ok_or("onFill.actions is required")?;
-                let actions: Result<Vec<OrderItem>, String> =
-                    raw_actions.into_iter().map(|a| a.try_into()).collect();
-                Ok(OrderItem::OnFill(OnFill {
-                    p: 0,
-                    actions: actions?,
-                }))
-            }
-            "trailingStop" | "trl" => {
-                let symbol = input.symbol.ok_or("trl.symbol is required")?;
-                let is_buy = input.is_buy.ok_or("trl.isBuy is required")?;
-                let size = input.size.ok_or("trl.size is required")?;
-                let trail_bps = input.trail_bps.ok_or("trl.trailBps is required")?;
-                let step_bps = input.step_bps.ok_or("trl.stepBps is required")?;
-                let limit_price = input.limit_price;
-                Ok(OrderItem::TrailingStop(TrailingStop {
-                    symbol,
-                    is_buy,
-                    size,
-                    trail_bps,
-                    step_bps,
-                    limit_price,
-                    iso: input.iso.unwrap_or(false),
-                }))
-            }
-            _ => Err(format!("Invalid item type: {}", input.item_type)),
-        }
+impl<'de> Deserialize<'de> for OrderInput {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let value = JsonValue::deserialize(deserializer)?;
+        OrderSpec::from_json(value, InputMode::Tolerant)
+            .map(|parsed| OrderInput(parsed.spec))
+            .map_err(serde::de::Error::custom)
     }
 }
 
-fn js_err(message: impl Into<String>) -> JsError {
-    JsError::new(&message.into())
+fn order_from_json(value: JsonValue, mode: InputMode) -> Result<OrderInput, JsError> {
+    OrderSpec::from_json(value, mode)
+        .map(|parsed| OrderInput(parsed.spec))
+        .map_err(keychain_error)
 }
 
-fn json_obj<'a>(
-    value: &'a JsonValue,
-    ctx: &str,
-) -> Result<&'a serde_json::Map<String, JsonValue>, JsError> {
-    value
-        .as_object()
-        .ok_or_else(|| js_err(format!("{ctx} must be an object")))
+/// Read an intent; under [`InputMode::Strict`] unknown fields are an error
+fn parse_order(order: JsValue, mode: InputMode) -> Result<OrderInput, JsError> {
+    let value: JsonValue =
+        serde_wasm_bindgen::from_value(order).map_err(|e| JsError::new(&e.to_string()))?;
+    order_from_json(value, mode)
 }
 
-fn json_str<'a>(
-    obj: &'a serde_json::Map<String, JsonValue>,
-    key: &str,
-) -> Result<&'a str, JsError> {
-    obj.get(key)
-        .and_then(JsonValue::as_str)
-        .ok_or_else(|| js_err(format!("{key} is required")))
+fn parse_orders(orders: JsValue, mode: InputMode) -> Result<Vec<OrderInput>, JsError> {
+    let values: Vec<JsonValue> =
+        serde_wasm_bindgen::from_value(orders).map_err(|e| JsError::new(&e.to_string()))?;
+    values
+        .into_iter()
+        .map(|value| order_from_json(value, mode))
+        .collect()
 }
 
-fn json_bool(
-    obj: &serde_json::Map<String, JsonValue>,
-    key: &str,
-    default: bool,
-) -> Result<bool, JsError> {
-    Ok(obj.get(key).and_then(JsonValue::as_bool).unwrap_or(default))
+/// Read a flat input object after checking its field names against `known`
+fn parse_checked<T: serde::de::DeserializeOwned>(
+    value: JsValue,
+    known: &[&'static str],
+    mode: InputMode,
+) -> Result<T, JsError> {
+    let value: JsonValue =
+        serde_wasm_bindgen::from_value(value).map_err(|e| JsError::new(&e.to_string()))?;
+    check_fields(&value, known, mode).map_err(keychain_error)?;
+    serde_json::from_value(value).map_err(|e| JsError::new(&e.to_string()))
 }
 
-fn json_f64(obj: &serde_json::Map<String, JsonValue>, key: &str) -> Result<f64, JsError> {
-    obj.get(key)
-        .and_then(JsonValue::as_f64)
-        .ok_or_else(|| js_err(format!("{key} is required")))
-}
+const USER_SETTINGS_FIELDS: &[&str] = &["maxLeverage"];
 
-fn json_u32(
-    obj: &serde_json::Map<String, JsonValue>,
-    key: &str,
-    default: Option<u32>,
-) -> Result<u32, JsError> {
-    match obj.get(key).and_then(JsonValue::as_u64) {
-        Some(v) => u32::try_from(v).map_err(|_| js_err(format!("{key} out of range"))),
-        None => default.ok_or_else(|| js_err(format!("{key} is required"))),
-    }
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct UserSettingsInput {
+    max_leverage: Vec<LeverageInput>,
 }
 
-fn json_u64(
-    obj: &serde_json::Map<String, JsonValue>,
-    key: &str,
-    default: Option<u64>,
-) -> Result<u64, JsError> {
-    match obj.get(key).and_then(JsonValue::as_u64) {
-        Some(v) => Ok(v),
-        None => default.ok_or_else(|| js_err(format!("{key} is required"))),
-    }
+/// `{ symbol, leverage }`, as in the Node binding, or a `[symbol, leverage]` pair
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum LeverageInput {
+    Setting { symbol: String, leverage: f64 },
+    Pair(String, f64),
 }
 
-fn json_pubkey(obj: &serde_json::Map<String, JsonValue>, key: &str) -> Result<Pubkey, JsError> {
-    Pubkey::from_base58(json_str(obj, key)?).map_err(|e| js_err(e.to_string()))
+/// `{ maxLeverage: [...] }`, or its bare `maxLeverage` array
+fn parse_user_settings(settings: JsValue, mode: InputMode) -> Result<UserSettings, JsError> {
+    let settings = if js_sys::Array::is_array(&settings) {
+        let wrapped = js_sys::Object::new();
+        js_sys::Reflect::set(&wrapped, &"maxLeverage".into(), &settings)
+            .map_err(|_| JsError::new("failed to read settings"))?;
+        wrapped.into()
+    } else {
+        settings
+    };
+    let input: UserSettingsInput = parse_checked(settings, USER_SETTINGS_FIELDS, mode)?;
+    Ok(input.into_settings())
 }
 
-fn json_hash(obj: &serde_json::Map<String, JsonValue>, key: &str) -> Result<Hash, JsError> {
-    Hash::from_base58(json_str(obj, key)?).map_err(|e| js_err(e.to_string()))
+impl UserSettingsInput {
+    fn into_settings(self) -> UserSettings {
+        UserSettings::new(
+            self.max_leverage
+                .into_iter()
+                .map(|l| match l {
+                    LeverageInput::Setting { symbol, leverage }
+                    | LeverageInput::Pair(symbol, leverage) => (symbol, leverage),
+                })
+                .collect(),
+        )
+    }
 }
 
-fn parse_order_input_value(value: JsonValue) -> Result<OrderInput, JsError> {
-    serde_json::from_value(value).map_err(|e| js_err(e.to_string()))
+#[cfg(feature = "full")]
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct OraclePriceInput {
+    timestamp: u64,
+    asset: String,
+    price: f64,
 }
 
-fn parse_order_item_value(value: JsonValue) -> Result<OrderItem, JsError> {
-    let obj = json_obj(&value, "order item")?;
-    let (tag, payload) = obj
-        .iter()
-        .next()
-        .ok_or_else(|| js_err("order item cannot be empty"))?;
-
-    if obj.len() != 1 {
-        return Err(js_err("order item must have exactly one top-level key"));
-    }
-
-    match tag.as_str() {
-        "l" => {
-            let p = json_obj(payload, "l")?;
-            let tif = match json_str(p, "tif")?.to_uppercase().as_str() {
-                "GTC" => TimeInForce::Gtc,
-                "IOC" => TimeInForce::Ioc,
-                "ALO" => TimeInForce::Alo,
-                other => return Err(js_err(format!("invalid tif: {other}"))),
-            };
-            Ok(OrderItem::Order(Order {
-                symbol: json_str(p, "c")?.to_string(),
-                is_buy: json_bool(p, "b", false)?,
-                price: json_f64(p, "px")?,
-                size: json_f64(p, "sz")?,
-                reduce_only: json_bool(p, "r", false)?,
-                iso: json_bool(p, "i", false)?,
-                order_type: OrderType::limit(tif),
-                client_id: p
-                    .get("cloid")
-                    .and_then(JsonValue::as_str)
-                    .map(Hash::from_base58)
-                    .transpose()
-                    .map_err(|e| js_err(e.to_string()))?,
-            }))
-        }
-        "m" => {
-            let p = json_obj(payload, "m")?;
-            Ok(OrderItem::Order(Order {
-                symbol: json_str(p, "c")?.to_string(),
-                is_buy: json_bool(p, "b", false)?,
-                price: 0.0,
-                size: json_f64(p, "sz")?,
-                reduce_only: json_bool(p, "r", false)?,
-                iso: json_bool(p, "i", false)?,
-                order_type: OrderType::market(),
-                client_id: None,
-            }))
-        }
-        "cx" => {
-            let p = json_obj(payload, "cx")?;
-            Ok(OrderItem::Cancel(Cancel::new(
-                json_str(p, "c")?.to_string(),
-                json_hash(p, "oid")?,
-            )))
-        }
-        "mod" => {
-            let p = json_obj(payload, "mod")?;
-            Ok(OrderItem::Modify(Modify::new(
-                json_hash(p, "oid")?,
-                json_str(p, "c")?.to_string(),
-                json_f64(p, "sz")?,
-            )))
-        }
-        "cxa" => {
-            let p = json_obj(payload, "cxa")?;
-            let symbols = p
-                .get("c")
-                .and_then(JsonValue::as_array)
-                .ok_or_else(|| js_err("c is required"))?
-                .iter()
-                .map(|v| {
-                    v.as_str()
-                        .map(ToOwned::to_owned)
-                        .ok_or_else(|| js_err("symbol must be a string"))
-                })
-                .collect::<Result<Vec<_>, _>>()?;
-            Ok(OrderItem::CancelAll(CancelAll::for_symbols(symbols)))
-        }
-        "st" => {
-            let p = json_obj(payload, "st")?;
-            Ok(OrderItem::Stop(Stop {
-                symbol: json_str(p, "c")?.to_string(),
-                is_buy: json_bool(p, "d", false)?,
-                size: json_f64(p, "sz")?,
-                trigger_price: json_f64(p, "tr")?,
-                limit_price: p.get("lim").and_then(JsonValue::as_f64).unwrap_or(f64::NAN),
-                iso: json_bool(p, "i", false)?,
-            }))
-        }
-        "tp" => {
-            let p = json_obj(payload, "tp")?;
-            Ok(OrderItem::TakeProfit(TakeProfit {
-                symbol: json_str(p, "c")?.to_string(),
-                is_buy: json_bool(p, "d", false)?,
-                size: json_f64(p, "sz")?,
-                trigger_price: json_f64(p, "tr")?,
-                limit_price: p.get("lim").and_then(JsonValue::as_f64).unwrap_or(f64::NAN),
-                iso: json_bool(p, "i", false)?,
-            }))
-        }
-        "rng" => {
-            let p = json_obj(payload, "rng")?;
-            Ok(OrderItem::RangeOco(RangeOco {
-                symbol: json_str(p, "c")?.to_string(),
-                is_buy: json_bool(p, "d", false)?,
-                size: json_f64(p, "sz")?,
-                collar_min: json_f64(p, "pmin")?,
-                collar_max: json_f64(p, "pmax")?,
-                limit_min: p
-                    .get("lmin")
-                    .and_then(JsonValue::as_f64)
-                    .unwrap_or(f64::NAN),
-                limit_max: p
-                    .get("lmax")
-                    .and_then(JsonValue::as_f64)
-                    .unwrap_or(f64::NAN),
-                iso: json_bool(p, "i", false)?,
-            }))
-        }
-        "trig" => {
-            let p = json_obj(payload, "trig")?;
-            let nested = p
-                .get("actions")
-                .and_then(JsonValue::as_array)
-                .ok_or_else(|| js_err("actions is required"))?
-                .iter()
-                .cloned()
-                .map(parse_order_item_value)
-                .collect::<Result<Vec<_>, _>>()?;
-            Ok(OrderItem::TriggerBasket(TriggerBasket {
-                symbol: json_str(p, "c")?.to_string(),
-                is_buy: json_bool(p, "d", false)?,
-                trigger_price: json_f64(p, "tr")?,
-                actions: nested,
-                iso: json_bool(p, "i", false)?,
-            }))
-        }
-        "of" => {
-            let p = json_obj(payload, "of")?;
-            let nested = p
-                .get("actions")
-                .and_then(JsonValue::as_array)
-                .ok_or_else(|| js_err("actions is required"))?
-                .iter()
-                .cloned()
-                .map(parse_order_item_value)
-                .collect::<Result<Vec<_>, _>>()?;
-            Ok(OrderItem::OnFill(OnFill {
-                p: json_u32(p, "p", Some(0))?,
-                actions: nested,
-            }))
-        }
-        "trl" => {
-            let p = json_obj(payload, "trl")?;
-            Ok(OrderItem::TrailingStop(TrailingStop {
-                symbol: json_str(p, "c")?.to_string(),
-                is_buy: json_bool(p, "b", false)?,
-                size: json_f64(p, "sz")?,
-                trail_bps: json_u32(p, "trb", None)?,
-                step_bps: json_u32(p, "stb", None)?,
-                limit_price: p.get("lim").and_then(JsonValue::as_f64),
-                iso: json_bool(p, "i", false)?,
-            }))
-        }
-        _ => parse_order_input_value(value)?.try_into().map_err(js_err),
-    }
+#[cfg(feature = "full")]
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PythOraclePriceInput {
+    timestamp: u64,
+    feed_index: u64,
+    price: u64,
+    exponent: i16,
 }
 
-fn parse_action_value(value: JsonValue) -> Result<Action, JsError> {
-    let obj = json_obj(&value, "action")?;
-    let (tag, payload) = obj
-        .iter()
-        .next()
-        .ok_or_else(|| js_err("action cannot be empty"))?;
+impl TryFrom<OrderInput> for OrderItem {
+    type Error = bulk_keychain::Error;
 
-    if obj.len() != 1 {
-        return Err(js_err("action must have exactly one top-level key"));
+    fn try_from(input: OrderInput) -> Result<Self, Self::Error> {
+        OrderItem::try_from(input.0)
     }
+}
 
-    match tag.as_str() {
-        "l" | "m" | "cx" | "mod" | "cxa" | "st" | "tp" | "rng" | "trig" | "of" | "trl" => {
-            Ok(Action::Order {
-                orders: vec![parse_order_item_value(value)?],
-            })
-        }
-        "order" => {
-            let orders = match payload {
-                JsonValue::Array(items) => items
-                    .iter()
-                    .cloned()
-                    .map(parse_order_item_value)
-                    .collect::<Result<Vec<_>, _>>()?,
-                _ => vec![parse_order_item_value(payload.clone())?],
-            };
-            Ok(Action::Order { orders })
-        }
-        "faucet" => {
-            let p = json_obj(payload, "faucet")?;
-            let mut faucet = Faucet::new(json_pubkey(p, "u").or_else(|_| json_pubkey(p, "user"))?);
-            faucet.amount = p.get("amount").and_then(JsonValue::as_f64);
-            Ok(Action::Faucet(faucet))
-        }
-        "agentWalletCreation" => {
-            let p = json_obj(payload, "agentWalletCreation")?;
-            Ok(Action::AgentWalletCreation(AgentWallet {
-                agent: json_pubkey(p, "a").or_else(|_| json_pubkey(p, "agent"))?,
-                delete: json_bool(p, "d", false).or_else(|_| json_bool(p, "delete", false))?,
-            }))
-        }
-        "updateUserSettings" => {
-            let p = json_obj(payload, "updateUserSettings")?;
-            let leverage_map = p
-                .get("m")
-                .or_else(|| p.get("maxLeverage"))
-                .and_then(JsonValue::as_object)
-                .ok_or_else(|| js_err("m is required"))?;
-            let max_leverage = leverage_map
-                .iter()
-                .map(|(k, v)| {
-                    v.as_f64()
-                        .map(|lev| (k.clone(), lev))
-                        .ok_or_else(|| js_err("max leverage values must be numbers"))
-                })
-                .collect::<Result<Vec<_>, _>>()?;
-            Ok(Action::UpdateUserSettings(UserSettings::new(max_leverage)))
-        }
-        "px" => {
-            let p = json_obj(payload, "px")?;
-            Ok(Action::Oracle {
-                oracles: vec![OraclePrice {
-                    timestamp: json_u64(p, "t", None)?,
-                    asset: json_str(p, "c")?.to_string(),
-                    price: json_f64(p, "px")?,
-                }],
-            })
-        }
-        "o" => {
-            let p = json_obj(payload, "o")?;
-            let entries = p
-                .get("oracles")
-                .and_then(JsonValue::as_array)
-                .ok_or_else(|| js_err("oracles is required"))?;
-            let oracles = entries
-                .iter()
-                .map(|entry| {
-                    let e = json_obj(entry, "oracle")?;
-                    Ok(PythOraclePrice {
-                        timestamp: json_u64(e, "t", None)?,
-                        feed_index: json_u64(e, "fi", None)?,
-                        price: json_u64(e, "px", None)?,
-                        exponent: e
-                            .get("e")
-                            .and_then(JsonValue::as_i64)
-                            .and_then(|v| i16::try_from(v).ok())
-                            .ok_or_else(|| js_err("e is required"))?,
-                    })
-                })
-                .collect::<Result<Vec<_>, JsError>>()?;
-            Ok(Action::PythOracle { oracles })
-        }
-        "whitelistFaucet" => {
-            let p = json_obj(payload, "whitelistFaucet")?;
-            Ok(Action::WhitelistFaucet(WhitelistFaucet {
-                target: json_pubkey(p, "target")?,
-                whitelist: json_bool(p, "whitelist", false)?,
-            }))
-        }
-        "createSubAccount" => {
-            let p = json_obj(payload, "createSubAccount")?;
-            Ok(Action::CreateSubAccount(CreateSubAccount {
-                name: json_str(p, "name")?.to_string(),
-                margin_symbol: p
-                    .get("marginSymbol")
-                    .and_then(JsonValue::as_str)
-                    .map(ToOwned::to_owned),
-                margin_amount: p.get("marginAmount").and_then(JsonValue::as_f64),
-            }))
-        }
-        "removeSubAccount" => {
-            let p = json_obj(payload, "removeSubAccount")?;
-            Ok(Action::RemoveSubAccount(bulk_keychain::RemoveSubAccount {
-                to_remove: json_pubkey(p, "toRemove")?,
-            }))
-        }
-        "renameSubAccount" => {
-            let p = json_obj(payload, "renameSubAccount")?;
-            Ok(Action::RenameSubAccount(RenameSubAccount {
-                account: json_pubkey(p, "account")?,
-                name: json_str(p, "name")?.to_string(),
-            }))
-        }
-        "transfer" => {
-            let p = json_obj(payload, "transfer")?;
-            let kind = match p.get("k").and_then(JsonValue::as_str).unwrap_or("internal") {
-                "internal" => TransferKind::Internal,
-                "external" => TransferKind::External,
-                other => return Err(js_err(format!("invalid transfer kind: {other}"))),
-            };
-            Ok(Action::Transfer(Transfer {
-                kind,
-                from: json_pubkey(p, "from")?,
-                to: json_pubkey(p, "to")?,
-                margin_symbol: json_str(p, "marginSymbol")?.to_string(),
-                margin_amount: json_f64(p, "marginAmount")?,
-            }))
-        }
-        "createMultisig" => {
-            let p = json_obj(payload, "createMultisig")?;
-            let signers = p
-                .get("signers")
-                .and_then(JsonValue::as_array)
-                .ok_or_else(|| js_err("signers is required"))?
-                .iter()
-                .map(|v| {
-                    v.as_str()
-                        .ok_or_else(|| js_err("signer must be a string"))
-                        .and_then(|s| Pubkey::from_base58(s).map_err(|e| js_err(e.to_string())))
-                })
-                .collect::<Result<Vec<_>, _>>()?;
-            Ok(Action::CreateMultisig(CreateMultisig {
-                signers,
-                threshold: json_u32(p, "threshold", None)?,
-                time_lock_secs: json_u32(p, "timeLockSecs", Some(0))?,
-                proposal_lifetime_secs: json_u32(p, "proposalLifetimeSecs", Some(7 * 24 * 3600))?,
-            }))
-        }
-        "msp" | "multisigPropose" => {
-            let p = json_obj(payload, tag)?;
-            let multisig = json_pubkey(p, "m").or_else(|_| json_pubkey(p, "multisig"))?;
-            let raw_actions = p
-                .get("a")
-                .or_else(|| p.get("actions"))
-                .and_then(JsonValue::as_array)
-                .ok_or_else(|| js_err("actions is required"))?;
-            let actions = raw_actions
-                .iter()
-                .cloned()
-                .map(parse_action_value)
-                .collect::<Result<Vec<_>, _>>()?;
-            Ok(Action::MultisigPropose(MultisigPropose::new(
-                multisig, actions,
-            )))
-        }
-        "msa" | "multisigApprove" => {
-            let p = json_obj(payload, tag)?;
-            Ok(Action::MultisigApprove(MultisigApprove::new(
-                json_pubkey(p, "m").or_else(|_| json_pubkey(p, "multisig"))?,
-                json_u64(p, "p", p.get("proposalId").and_then(JsonValue::as_u64))?,
-            )))
-        }
-        "msr" | "multisigReject" => {
-            let p = json_obj(payload, tag)?;
-            Ok(Action::MultisigReject(MultisigReject::new(
-                json_pubkey(p, "m").or_else(|_| json_pubkey(p, "multisig"))?,
-                json_u64(p, "p", p.get("proposalId").and_then(JsonValue::as_u64))?,
-            )))
-        }
-        "msc" | "multisigCancel" => {
-            let p = json_obj(payload, tag)?;
-            Ok(Action::MultisigCancel(MultisigCancel::new(
-                json_pubkey(p, "m").or_else(|_| json_pubkey(p, "multisig"))?,
-                json_u64(p, "p", p.get("proposalId").and_then(JsonValue::as_u64))?,
-            )))
-        }
-        "mse" | "multisigExecute" => {
-            let p = json_obj(payload, tag)?;
-            Ok(Action::MultisigExecute(MultisigExecute::new(
-                json_pubkey(p, "m").or_else(|_| json_pubkey(p, "multisig"))?,
-                json_u64(p, "p", p.get("proposalId").and_then(JsonValue::as_u64))?,
-            )))
-        }
-        "msu" | "updateMultisigPolicy" => {
-            let p = json_obj(payload, tag)?;
-            let signers = p
-                .get("signers")
-                .and_then(JsonValue::as_array)
-                .ok_or_else(|| js_err("signers is required"))?
-                .iter()
-                .map(|v| {
-                    v.as_str()
-                        .ok_or_else(|| js_err("signer must be a string"))
-                        .and_then(|s| Pubkey::from_base58(s).map_err(|e| js_err(e.to_string())))
-                })
-                .collect::<Result<Vec<_>, _>>()?;
-            Ok(Action::UpdateMultisigPolicy(UpdateMultisigPolicy {
-                multisig: json_pubkey(p, "m").or_else(|_| json_pubkey(p, "multisig"))?,
-                signers,
-                threshold: json_u32(p, "threshold", None)?,
-                time_lock_secs: json_u32(p, "timeLockSecs", Some(0))?,
-                proposal_lifetime_secs: json_u32(p, "proposalLifetimeSecs", Some(7 * 24 * 3600))?,
-            }))
-        }
-        _ => Err(js_err(format!("unsupported action type: {tag}"))),
+#[cfg(feature = "full")]
+fn parse_tif(tif: &str) -> Result<TimeInForce, String> {
+    match tif.to_uppercase().as_str() {
+        "GTC" => Ok(TimeInForce::Gtc),
+        "IOC" => Ok(TimeInForce::Ioc),
+        "ALO" => Ok(TimeInForce::Alo),
+        _ => Err(format!("Invalid tif: {}", tif)),
+    }
+}
+
+fn js_err(message: impl Into<String>) -> JsError {
+    JsError::new(&message.into())
+}
+
+/// `JsError` for a core error, its message prefixed with the variant name
+/// (`"InvalidBase58: invalid base58: ..."`) so callers can match on
+/// `error.message` without depending on the wording after it.
+fn keychain_error(e: bulk_keychain::Error) -> JsError {
+    JsError::new(&format!("{}: {}", e.code(), e))
+}
+
+/// Nonce strategy by its JS name.
+fn parse_nonce_strategy(strategy: &str) -> Result<NonceStrategy, JsError> {
+    match strategy {
+        "timestamp" => Ok(NonceStrategy::Timestamp),
+        "counter" => Ok(NonceStrategy::Counter),
+        "highFrequency" => Ok(NonceStrategy::TimestampWithCounter),
+        "random" => Ok(NonceStrategy::RandomWithTimestampFallback),
+        _ => Err(JsError::new(
+            "Invalid nonce strategy. Use 'timestamp', 'counter', 'highFrequency', or 'random'",
+        )),
     }
 }
 
 fn parse_action_values(value: JsValue) -> Result<Vec<Action>, JsError> {
     let raw: Vec<JsonValue> =
         serde_wasm_bindgen::from_value(value).map_err(|e| js_err(e.to_string()))?;
-    raw.into_iter().map(parse_action_value).collect()
+    bulk_keychain::compact::actions_from_json(&raw).map_err(|e| js_err(e.to_string()))
 }
 
 // ============================================================================
@@ -1430,12 +2393,50 @@ pub fn current_timestamp() -> f64 {
     bulk_keychain::nonce::current_timestamp_millis() as f64
 }
 
+/// Which core build this module embeds
+///
+/// @returns { crateVersion, gitSha, features, profile, bindingVersion }
+#[wasm_bindgen(js_name = buildInfo)]
+pub fn build_info() -> Result<JsValue, JsError> {
+    let mut info = serde_json::to_value(bulk_keychain::build_info())
+        .map_err(|e| JsError::new(&e.to_string()))?;
+    info["bindingVersion"] = env!("CARGO_PKG_VERSION").into();
+    serde::Serialize::serialize(&info, &serde_wasm_bindgen::Serializer::json_compatible())
+        .map_err(|e| JsError::new(&e.to_string()))
+}
+
+/// Check a signed transaction from anywhere (a wallet, another process)
+/// before submitting it: `false` if the signature does not verify against
+/// `tx.signer`. Throws on malformed fields, or if `orderId` / `orderIds`
+/// differ from the IDs the signed actions produce.
+#[wasm_bindgen(js_name = verifyTransaction)]
+pub fn verify_transaction(tx: JsValue) -> Result<bool, JsError> {
+    let tx = tx_from_js(tx)?;
+    bulk_keychain::verify_transaction(&tx).map_err(keychain_error)
+}
+
+/// Whether a `capabilitiesSigned()` result is intact and was signed by the
+/// key its descriptor names
+#[wasm_bindgen(js_name = verifyCapabilities)]
+pub fn verify_capabilities(signed: JsValue) -> Result<bool, JsError> {
+    let signed: bulk_keychain::SignedCapabilities =
+        serde_wasm_bindgen::from_value(signed).map_err(|e| JsError::new(&e.to_string()))?;
+    signed.verify().map_err(keychain_error)
+}
+
 /// Validate a base58-encoded public key
 #[wasm_bindgen(js_name = validatePubkey)]
 pub fn validate_pubkey(s: &str) -> bool {
     Pubkey::from_base58(s).is_ok()
 }
 
+/// Short display-safe identifier (`bk1:...`) for a base58-encoded public key
+#[wasm_bindgen(js_name = pubkeyFingerprint)]
+pub fn pubkey_fingerprint(pubkey: &str) -> Result<String, JsError> {
+    let pubkey = Pubkey::from_base58(pubkey).map_err(keychain_error)?;
+    Ok(pubkey.fingerprint())
+}
+
 /// Validate a base58-encoded hash
 #[wasm_bindgen(js_name = validateHash)]
 pub fn validate_hash(s: &str) -> bool {
@@ -1450,6 +2451,246 @@ pub fn compute_order_id(wincode_bytes: &[u8]) -> String {
     Hash::from_wincode_bytes(wincode_bytes).to_base58()
 }
 
+#[cfg(feature = "full")]
+fn commitment_salt(salt: &[u8]) -> Result<[u8; 32], JsError> {
+    salt.try_into()
+        .map_err(|_| JsError::new(&format!("salt must be 32 bytes, got {}", salt.len())))
+}
+
+/// Check an order intent without signing it
+///
+/// Field names may be camelCase or snake_case; with `strict`, one intent must
+/// not mix the two. Returns `{ order, warnings }`: the intent with camelCase
+/// field names, and a `path: unknown field` entry per field that was dropped.
+#[wasm_bindgen(js_name = validateOrderInput)]
+pub fn validate_order_input(order: JsValue, strict: Option<bool>) -> Result<JsValue, JsError> {
+    let value: JsonValue =
+        serde_wasm_bindgen::from_value(order).map_err(|e| JsError::new(&e.to_string()))?;
+    let casing = if strict.unwrap_or(false) {
+        InputMode::Strict
+    } else {
+        InputMode::Tolerant
+    };
+    let parsed = bulk_keychain::validate_order_input(value, casing).map_err(keychain_error)?;
+    let output = serde_json::json!({ "order": parsed.spec, "warnings": parsed.warnings });
+    serde::Serialize::serialize(&output, &serde_wasm_bindgen::Serializer::json_compatible())
+        .map_err(|e| JsError::new(&e.to_string()))
+}
+
+/// Human-readable summary of an order, e.g. `BUY 0.1 BTC-USD @ 50000 (GTC)`
+#[cfg(feature = "full")]
+#[wasm_bindgen(js_name = orderToDisplayString)]
+pub fn order_to_display_string(order: JsValue) -> Result<String, JsError> {
+    let order_input: OrderInput =
+        serde_wasm_bindgen::from_value(order).map_err(|e| JsError::new(&e.to_string()))?;
+    let item: OrderItem = order_input.try_into().map_err(keychain_error)?;
+    Ok(item.to_display_string())
+}
+
+/// Throw unless `displayed` is exactly the price `order` signs
+///
+/// Run right before requesting the wallet signature. `displayed` may use `,`
+/// thousands separators; decimal commas are rejected. With `maxDecimals`, also
+/// throw if showing the price with that many decimals would round it.
+#[cfg(feature = "full")]
+#[wasm_bindgen(js_name = confirmPriceMatches)]
+pub fn confirm_price_matches(
+    displayed: &str,
+    order: JsValue,
+    max_decimals: Option<u32>,
+) -> Result<(), JsError> {
+    let order = order_only(order, "order")?;
+    let mut policy = bulk_keychain::DisplayPolicy::new();
+    if let Some(decimals) = max_decimals {
+        policy = policy.with_max_decimals(order.symbol.clone(), decimals);
+    }
+    bulk_keychain::confirm_price_matches(displayed, &order, &policy)
+        .map_err(|e| JsError::new(&e.to_string()))
+}
+
+/// Price in the symbol's quote asset at the spec's price decimals, e.g.
+/// `100,000.00 USD`; `spec` is `{ priceDecimals, sizeDecimals, tickUnits?, lotUnits? }`
+#[cfg(feature = "full")]
+#[wasm_bindgen(js_name = formatPrice)]
+pub fn format_price(symbol: &str, price: f64, spec: JsValue) -> Result<String, JsError> {
+    let spec: bulk_keychain::input::SymbolSpecInput =
+        serde_wasm_bindgen::from_value(spec).map_err(|e| JsError::new(&e.to_string()))?;
+    Ok(bulk_keychain::format::format_price(
+        symbol,
+        price,
+        &spec.into(),
+    ))
+}
+
+/// Size in the symbol's base asset at the spec's size decimals, e.g. `0.10 BTC`
+#[cfg(feature = "full")]
+#[wasm_bindgen(js_name = formatSize)]
+pub fn format_size(symbol: &str, size: f64, spec: JsValue) -> Result<String, JsError> {
+    let spec: bulk_keychain::input::SymbolSpecInput =
+        serde_wasm_bindgen::from_value(spec).map_err(|e| JsError::new(&e.to_string()))?;
+    Ok(bulk_keychain::format::format_size(
+        symbol,
+        size,
+        &spec.into(),
+    ))
+}
+
+/// Parse a user-entered amount (`1,250.5`, `0.5k`) into an exact decimal
+/// string; throws on a decimal comma (`1.234,56`) with the dot form to use
+#[cfg(feature = "full")]
+#[wasm_bindgen(js_name = parseAmount)]
+pub fn parse_amount(input: &str) -> Result<String, JsError> {
+    bulk_keychain::format::parse_amount(input)
+        .map(|amount| amount.to_string())
+        .map_err(keychain_error)
+}
+
+/// Split a `BASE-QUOTE` symbol, e.g. `BTC-USD` into `{ base: 'BTC', quote: 'USD' }`
+#[cfg(feature = "full")]
+#[wasm_bindgen(js_name = parseSymbol)]
+pub fn parse_symbol(pair: &str) -> Result<JsValue, JsError> {
+    let (base, quote) = bulk_keychain::symbol::split(pair).map_err(keychain_error)?;
+    let obj = js_sys::Object::new();
+    js_sys::Reflect::set(&obj, &"base".into(), &base.into())
+        .map_err(|_| JsError::new("failed to set base"))?;
+    js_sys::Reflect::set(&obj, &"quote".into(), &quote.into())
+        .map_err(|_| JsError::new("failed to set quote"))?;
+    Ok(obj.into())
+}
+
+/// Canonical `BASE-QUOTE` symbol, uppercased
+#[cfg(feature = "full")]
+#[wasm_bindgen(js_name = makeSymbol)]
+pub fn make_symbol(base: &str, quote: &str) -> String {
+    bulk_keychain::symbol::canonical(base, quote)
+}
+
+/// Market context for `lintOrder`
+#[cfg(feature = "full")]
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct LintContextInput {
+    #[serde(default)]
+    reference_prices: HashMap<String, f64>,
+    #[serde(default)]
+    position_hints: HashMap<String, f64>,
+    marketable_bps: Option<u32>,
+    far_ioc_bps: Option<u32>,
+}
+
+/// Flag economically suspicious but valid orders
+///
+/// `context` is `{ referencePrices?, positionHints?, marketableBps?, farIocBps? }`;
+/// returns `{ code, message, severity }[]`.
+#[cfg(feature = "full")]
+#[wasm_bindgen(js_name = lintOrder)]
+pub fn lint_order(order: JsValue, context: JsValue) -> Result<JsValue, JsError> {
+    let order_input: OrderInput =
+        serde_wasm_bindgen::from_value(order).map_err(|e| JsError::new(&e.to_string()))?;
+    let item: OrderItem = order_input.try_into().map_err(keychain_error)?;
+    let input: LintContextInput =
+        serde_wasm_bindgen::from_value(context).map_err(|e| JsError::new(&e.to_string()))?;
+    let defaults = LintContext::default();
+    let ctx = LintContext {
+        reference_prices: input.reference_prices,
+        position_hints: input.position_hints,
+        marketable_bps: input.marketable_bps.unwrap_or(defaults.marketable_bps),
+        far_ioc_bps: input.far_ioc_bps.unwrap_or(defaults.far_ioc_bps),
+    };
+    let warnings = bulk_keychain::lint_order(&item, &ctx);
+    to_js(&warnings)
+}
+
+/// What replacing `original` with `replacement` would change
+///
+/// Both must have type `'order'`. Returns `{ priceDelta, priceDeltaTicks,
+/// sizeDelta, tifChanged, sideChanged, flagsChanged }`; `priceDeltaTicks` is
+/// null without `spec` (`{ priceDecimals, sizeDecimals, tickUnits?, lotUnits? }`).
+/// A `sideChanged` diff is almost always a bug.
+#[cfg(feature = "full")]
+#[wasm_bindgen(js_name = diffOrders)]
+pub fn diff_orders(
+    original: JsValue,
+    replacement: JsValue,
+    spec: JsValue,
+) -> Result<JsValue, JsError> {
+    let original = order_only(original, "original")?;
+    let replacement = order_only(replacement, "replacement")?;
+    let spec: Option<bulk_keychain::input::SymbolSpecInput> =
+        serde_wasm_bindgen::from_value(spec).map_err(|e| JsError::new(&e.to_string()))?;
+    let spec = spec.map(bulk_keychain::SymbolSpec::from);
+    to_js(&bulk_keychain::diff_orders(
+        &original,
+        &replacement,
+        spec.as_ref(),
+    ))
+}
+
+#[cfg(feature = "full")]
+fn order_only(order: JsValue, name: &str) -> Result<Order, JsError> {
+    let order_input: OrderInput =
+        serde_wasm_bindgen::from_value(order).map_err(|e| JsError::new(&e.to_string()))?;
+    match OrderItem::try_from(order_input).map_err(keychain_error)? {
+        OrderItem::Order(order) => Ok(order),
+        _ => Err(JsError::new(&format!("{} must have type 'order'", name))),
+    }
+}
+
+/// Estimated fee of each order at `feeBps` (`price * size * feeBps / 10000`)
+///
+/// Nothing is signed. Cancels, modifies and market orders estimate as 0.
+#[cfg(feature = "full")]
+#[wasm_bindgen(js_name = estimateBatchFees)]
+pub fn estimate_batch_fees(orders: JsValue, fee_bps: f64) -> Result<Vec<f64>, JsError> {
+    let order_inputs: Vec<OrderInput> =
+        serde_wasm_bindgen::from_value(orders).map_err(|e| JsError::new(&e.to_string()))?;
+    let items: Result<Vec<OrderItem>, _> = order_inputs.into_iter().map(|o| o.try_into()).collect();
+    let items = items.map_err(keychain_error)?;
+    Ok(items
+        .iter()
+        .map(|item| item.estimated_fee(fee_bps))
+        .collect())
+}
+
+/// Sum of `estimateBatchFees`
+#[cfg(feature = "full")]
+#[wasm_bindgen(js_name = estimateTotalBatchFee)]
+pub fn estimate_total_batch_fee(orders: JsValue, fee_bps: f64) -> Result<f64, JsError> {
+    Ok(estimate_batch_fees(orders, fee_bps)?.iter().sum())
+}
+
+/// Commit to an order's terms under a 32-byte secret salt (base58 hash).
+///
+/// Nonce and account are not covered, so the commitment can be published
+/// before the order is signed.
+#[cfg(feature = "full")]
+#[wasm_bindgen(js_name = orderCommitment)]
+pub fn order_commitment(order: JsValue, salt: &[u8]) -> Result<String, JsError> {
+    let order_input: OrderInput =
+        serde_wasm_bindgen::from_value(order).map_err(|e| JsError::new(&e.to_string()))?;
+    let item: OrderItem = order_input.try_into().map_err(keychain_error)?;
+    let salt = commitment_salt(salt)?;
+    item.commitment(&salt)
+        .map(|c| c.to_base58())
+        .map_err(keychain_error)
+}
+
+/// Check that an order and salt open a commitment from `orderCommitment`
+#[cfg(feature = "full")]
+#[wasm_bindgen(js_name = verifyOrderCommitment)]
+pub fn verify_order_commitment(
+    order: JsValue,
+    salt: &[u8],
+    commitment: &str,
+) -> Result<bool, JsError> {
+    let order_input: OrderInput =
+        serde_wasm_bindgen::from_value(order).map_err(|e| JsError::new(&e.to_string()))?;
+    let item: OrderItem = order_input.try_into().map_err(keychain_error)?;
+    let salt = commitment_salt(salt)?;
+    let commitment = Hash::from_base58(commitment).map_err(keychain_error)?;
+    Ok(verify_commitment(&item, &salt, &commitment))
+}
+
 // ============================================================================
 // External Wallet Support - Prepare/Finalize API
 // ============================================================================
@@ -1461,10 +2702,29 @@ pub fn compute_order_id(wincode_bytes: &[u8]) -> String {
 #[wasm_bindgen]
 pub struct WasmPreparedMessage {
     inner: PreparedMessage,
+    index: Option<u32>,
+    _tracked: Option<Tracked>,
+}
+
+impl WasmPreparedMessage {
+    fn new(inner: PreparedMessage) -> Self {
+        let tracked = Tracked::new(&LIVE_PREPARED_MESSAGES, inner.message_bytes.len());
+        Self {
+            inner,
+            index: None,
+            _tracked: tracked,
+        }
+    }
 }
 
 #[wasm_bindgen]
 impl WasmPreparedMessage {
+    /// Index of the source order in the input array (prepareAllIndexed only)
+    #[wasm_bindgen(getter)]
+    pub fn index(&self) -> Option<u32> {
+        self.index
+    }
+
     /// Get the raw message bytes to sign (Uint8Array)
     #[wasm_bindgen(getter, js_name = messageBytes)]
     pub fn message_bytes(&self) -> Vec<u8> {
@@ -1504,7 +2764,7 @@ impl WasmPreparedMessage {
     /// Get the actions JSON
     #[wasm_bindgen(getter)]
     pub fn actions(&self) -> JsValue {
-        serde_wasm_bindgen::to_value(&self.inner.actions).unwrap_or(JsValue::NULL)
+        to_js(&self.inner.actions).unwrap_or(JsValue::NULL)
     }
 
     /// Get the account public key (base58)
@@ -1519,29 +2779,93 @@ impl WasmPreparedMessage {
         self.inner.signer.clone()
     }
 
-    /// Get the nonce
-    #[wasm_bindgen(getter)]
-    pub fn nonce(&self) -> f64 {
-        self.inner.nonce as f64
+    /// Get the nonce; above `Number.MAX_SAFE_INTEGER` it is a string or
+    /// BigInt, per `setSerializeLargeIntsAs`
+    #[wasm_bindgen(getter, unchecked_return_type = "number | string | bigint")]
+    pub fn nonce(&self) -> Result<JsValue, JsError> {
+        to_js(&self.inner.nonce)
+    }
+
+    /// Get the group intent tag (prepareGroup only)
+    #[wasm_bindgen(getter, js_name = groupKind)]
+    pub fn group_kind(&self) -> Option<String> {
+        self.inner.group_kind.clone().map(String::from)
+    }
+
+    /// Conflicting-leg findings (prepareGroup only)
+    ///
+    /// @returns Array<{ index, code, message, severity }>
+    #[wasm_bindgen(getter, js_name = groupWarnings)]
+    pub fn group_warnings(&self) -> Result<JsValue, JsError> {
+        to_js(&self.inner.group_warnings)
     }
 
     /// Finalize with a signature (base58 string)
     ///
-    /// Call this after your wallet signs the messageBytes.
+    /// Call this after your wallet signs the messageBytes. Throws unless the
+    /// signature verifies over them, or `{ skipVerify: true }` is passed.
     #[wasm_bindgen]
-    pub fn finalize(&self, signature: &str) -> JsValue {
-        let signed = finalize_transaction(self.inner.clone(), signature);
-        serde_wasm_bindgen::to_value(&signed).unwrap_or(JsValue::NULL)
+    pub fn finalize(&self, signature: &str, options: JsValue) -> Result<JsValue, JsError> {
+        let signed = if parse_finalize_options(options)?.skip_verify {
+            finalize_transaction(self.inner.clone(), signature).map_err(keychain_error)?
+        } else {
+            self.inner
+                .clone()
+                .finalize_verified(signature)
+                .map_err(keychain_error)?
+        };
+        signed_to_js(&signed)
     }
 
     /// Finalize with signature bytes (Uint8Array)
     #[wasm_bindgen(js_name = finalizeBytes)]
-    pub fn finalize_bytes(&self, signature: &[u8]) -> JsValue {
+    pub fn finalize_bytes(&self, signature: &[u8], options: JsValue) -> Result<JsValue, JsError> {
         let sig_b58 = bulk_keychain::bs58::encode(signature).into_string();
-        self.finalize(&sig_b58)
+        self.finalize(&sig_b58, options)
     }
 }
 
+/// Sign a prepared message with a local keypair and finalize it
+///
+/// Throws if `keypair` is not the signer the message was prepared for.
+#[wasm_bindgen(js_name = signPreparedWithKeypair)]
+pub fn sign_prepared_with_keypair(
+    prepared: &WasmPreparedMessage,
+    keypair: &WasmKeypair,
+) -> Result<JsValue, JsError> {
+    let signed = prepared
+        .inner
+        .sign_with_keypair(&keypair.inner)
+        .map_err(keychain_error)?;
+    signed_to_js(&signed)
+}
+
+/// Per-call options for `sign` and `signAll`
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SignOptionsInput {
+    deadline: Option<f64>,
+    #[serde(default)]
+    allow_client_id_reuse: bool,
+}
+
+/// Read `{ deadline?, allowClientIdReuse? }`; `undefined` means no options.
+/// A deadline that looks like seconds is refused.
+fn parse_sign_options(options: JsValue) -> Result<SignOptions, JsError> {
+    let input: SignOptionsInput = serde_wasm_bindgen::from_value::<Option<_>>(options)
+        .map_err(|e| JsError::new(&e.to_string()))?
+        .unwrap_or_default();
+    let deadline_ms = input
+        .deadline
+        .map(|ms| TimestampMs::from_millis_checked(ms as u64))
+        .transpose()
+        .map_err(keychain_error)?;
+    Ok(SignOptions {
+        deadline_ms,
+        allow_client_id_reuse: input.allow_client_id_reuse,
+    })
+}
+
 /// Options for preparing a message
 #[derive(Debug, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -1551,7 +2875,94 @@ struct PrepareOptions {
     /// Signer public key (base58) - defaults to account if not provided
     signer: Option<String>,
     /// Nonce - defaults to current timestamp if not provided
-    nonce: Option<f64>,
+    #[serde(default, deserialize_with = "de_nonce")]
+    nonce: Option<u64>,
+    /// Group intent tag (prepareGroup only)
+    group_kind: Option<String>,
+    /// Refuse conflicting legs instead of reporting them (prepareGroup only)
+    strict_group: Option<bool>,
+    /// Reject unknown fields in the inputs and these options (order and
+    /// user-settings prepare functions only)
+    strict: Option<bool>,
+}
+
+const PREPARE_OPTION_FIELDS: &[&str] = &[
+    "account",
+    "signer",
+    "nonce",
+    "groupKind",
+    "strictGroup",
+    "strict",
+];
+
+impl PrepareOptions {
+    fn input_mode(&self) -> InputMode {
+        if self.strict == Some(true) {
+            InputMode::Strict
+        } else {
+            InputMode::Tolerant
+        }
+    }
+}
+
+/// Read options for a prepare function that honors `strict`
+fn parse_prepare_options(options: JsValue) -> Result<PrepareOptions, JsError> {
+    let value: JsonValue =
+        serde_wasm_bindgen::from_value(options).map_err(|e| JsError::new(&e.to_string()))?;
+    let opts: PrepareOptions =
+        serde_json::from_value(value.clone()).map_err(|e| JsError::new(&e.to_string()))?;
+    check_fields(&value, PREPARE_OPTION_FIELDS, opts.input_mode()).map_err(keychain_error)?;
+    Ok(opts)
+}
+
+/// Serialize a signed transaction, surfacing the unsigned client-side fields
+/// (`orderId`, `orderIds`, `resignCount`, `groupKind`, `groupWarnings`) when
+/// present, the same output shape as the Node binding.
+fn signed_to_js(signed: &SignedTransaction) -> Result<JsValue, JsError> {
+    let value = to_js(signed)?;
+    let set = |key: &str, field: JsValue| {
+        js_sys::Reflect::set(&value, &key.into(), &field)
+            .map(drop)
+            .map_err(|_| JsError::new(&format!("failed to set {}", key)))
+    };
+    set("actionType", signed.action_type().as_str().into())?;
+    if let Some(order_id) = &signed.order_id {
+        set("orderId", order_id.into())?;
+    }
+    if let Some(order_ids) = &signed.order_ids {
+        set("orderIds", to_js(order_ids)?)?;
+    }
+    if signed.resign_count > 0 {
+        set("resignCount", signed.resign_count.into())?;
+    }
+    if let Some(kind) = &signed.group_kind {
+        set("groupKind", kind.as_str().into())?;
+    }
+    if !signed.group_warnings.is_empty() {
+        set("groupWarnings", to_js(&signed.group_warnings)?)?;
+    }
+    Ok(value)
+}
+
+#[cfg(feature = "full")]
+fn signed_all_to_js(signed: &[SignedTransaction]) -> Result<JsValue, JsError> {
+    let array = js_sys::Array::new();
+    for tx in signed {
+        array.push(&signed_to_js(tx)?);
+    }
+    Ok(array.into())
+}
+
+#[cfg(feature = "full")]
+fn indexed_to_js(signed: &[bulk_keychain::IndexedSignedTransaction]) -> Result<JsValue, JsError> {
+    let array = js_sys::Array::new();
+    for indexed in signed {
+        let value = signed_to_js(&indexed.tx)?;
+        js_sys::Reflect::set(&value, &"index".into(), &(indexed.index as u32).into())
+            .map_err(|_| JsError::new("failed to set index"))?;
+        array.push(&value);
+    }
+    Ok(array.into())
 }
 
 /// Prepare a single order for external wallet signing
@@ -1560,7 +2971,7 @@ struct PrepareOptions {
 /// to sign with an external wallet (like Phantom, Privy, etc).
 ///
 /// @param order - The order to prepare
-/// @param options - { account: string, signer?: string, nonce?: number }
+/// @param options - { account: string, signer?: string, nonce?: number, strict?: boolean }
 /// @returns PreparedMessage with messageBytes to sign
 ///
 /// @example
@@ -1574,47 +2985,27 @@ pub fn wasm_prepare_order(
     order: JsValue,
     options: JsValue,
 ) -> Result<WasmPreparedMessage, JsError> {
-    let order_input: OrderInput =
-        serde_wasm_bindgen::from_value(order).map_err(|e| JsError::new(&e.to_string()))?;
-    let opts: PrepareOptions =
-        serde_wasm_bindgen::from_value(options).map_err(|e| JsError::new(&e.to_string()))?;
+    let opts = parse_prepare_options(options)?;
+    let order_input = parse_order(order, opts.input_mode())?;
 
-    let account = Pubkey::from_base58(&opts.account).map_err(|e| JsError::new(&e.to_string()))?;
+    let account = Pubkey::from_base58(&opts.account).map_err(keychain_error)?;
     let signer = opts
         .signer
         .map(|s| Pubkey::from_base58(&s))
         .transpose()
-        .map_err(|e| JsError::new(&e.to_string()))?;
-    let nonce = opts.nonce.map(|n| n as u64);
+        .map_err(keychain_error)?;
+    let nonce = opts.nonce;
 
     // If onFill is present, emit parent + OnFill as an atomic group
-    let on_fill_input = order_input.on_fill;
-    let order_input_no_fill = OrderInput {
-        on_fill: None,
-        ..order_input
-    };
-
-    let prepared = if let Some(of) = on_fill_input {
-        let parent: OrderItem = order_input_no_fill
-            .try_into()
-            .map_err(|e: String| JsError::new(&e))?;
-        let consequents: Result<Vec<OrderItem>, String> =
-            of.actions.into_iter().map(|a| a.try_into()).collect();
-        let of_item = OrderItem::OnFill(OnFill {
-            p: of.p,
-            actions: consequents.map_err(|e| JsError::new(&e))?,
-        });
-        prepare_group(vec![parent, of_item], &account, signer.as_ref(), nonce)
-            .map_err(|e| JsError::new(&e.to_string()))?
+    let mut items = order_input.0.into_items().map_err(keychain_error)?;
+    let prepared = if items.len() > 1 {
+        prepare_group(items, &account, signer.as_ref(), nonce)
     } else {
-        let order_item: OrderItem = order_input_no_fill
-            .try_into()
-            .map_err(|e: String| JsError::new(&e))?;
-        prepare_message(order_item, &account, signer.as_ref(), nonce)
-            .map_err(|e| JsError::new(&e.to_string()))?
-    };
+        prepare_message(items.remove(0), &account, signer.as_ref(), nonce)
+    }
+    .map_err(keychain_error)?;
 
-    Ok(WasmPreparedMessage { inner: prepared })
+    Ok(WasmPreparedMessage::new(prepared))
 }
 
 /// Prepare multiple orders - each becomes its own transaction (parallel)
@@ -1627,29 +3018,60 @@ pub fn wasm_prepare_all(
     orders: JsValue,
     options: JsValue,
 ) -> Result<Vec<WasmPreparedMessage>, JsError> {
-    let order_inputs: Vec<OrderInput> =
-        serde_wasm_bindgen::from_value(orders).map_err(|e| JsError::new(&e.to_string()))?;
-    let opts: PrepareOptions =
-        serde_wasm_bindgen::from_value(options).map_err(|e| JsError::new(&e.to_string()))?;
+    let opts = parse_prepare_options(options)?;
+    let order_inputs = parse_orders(orders, opts.input_mode())?;
 
-    let order_items: Result<Vec<OrderItem>, String> =
+    let order_items: Result<Vec<OrderItem>, _> =
         order_inputs.into_iter().map(|o| o.try_into()).collect();
-    let order_items = order_items.map_err(|e| JsError::new(&e))?;
+    let order_items = order_items.map_err(keychain_error)?;
 
-    let account = Pubkey::from_base58(&opts.account).map_err(|e| JsError::new(&e.to_string()))?;
+    let account = Pubkey::from_base58(&opts.account).map_err(keychain_error)?;
     let signer = opts
         .signer
         .map(|s| Pubkey::from_base58(&s))
         .transpose()
-        .map_err(|e| JsError::new(&e.to_string()))?;
-    let base_nonce = opts.nonce.map(|n| n as u64);
+        .map_err(keychain_error)?;
+    let base_nonce = opts.nonce;
 
-    let prepared = prepare_all(order_items, &account, signer.as_ref(), base_nonce)
-        .map_err(|e| JsError::new(&e.to_string()))?;
+    let prepared =
+        prepare_all(order_items, &account, signer.as_ref(), base_nonce).map_err(keychain_error)?;
+
+    Ok(prepared.into_iter().map(WasmPreparedMessage::new).collect())
+}
+
+/// Like prepareAll, but each PreparedMessage carries the `index` of its input order
+///
+/// @param orders - Array of orders to prepare
+/// @param options - { account: string, signer?: string, nonce?: number }
+/// @returns Array of PreparedMessage with `index` set
+#[wasm_bindgen(js_name = prepareAllIndexed)]
+pub fn wasm_prepare_all_indexed(
+    orders: JsValue,
+    options: JsValue,
+) -> Result<Vec<WasmPreparedMessage>, JsError> {
+    let opts = parse_prepare_options(options)?;
+    let order_inputs = parse_orders(orders, opts.input_mode())?;
+
+    let order_items: Result<Vec<OrderItem>, _> =
+        order_inputs.into_iter().map(|o| o.try_into()).collect();
+    let order_items = order_items.map_err(keychain_error)?;
+
+    let account = Pubkey::from_base58(&opts.account).map_err(keychain_error)?;
+    let signer = opts
+        .signer
+        .map(|s| Pubkey::from_base58(&s))
+        .transpose()
+        .map_err(keychain_error)?;
+
+    let prepared = prepare_all_indexed(order_items, &account, signer.as_ref(), opts.nonce)
+        .map_err(keychain_error)?;
 
     Ok(prepared
         .into_iter()
-        .map(|p| WasmPreparedMessage { inner: p })
+        .map(|indexed| WasmPreparedMessage {
+            index: Some(indexed.index as u32),
+            ..WasmPreparedMessage::new(indexed.message)
+        })
         .collect())
 }
 
@@ -1658,34 +3080,37 @@ pub fn wasm_prepare_all(
 /// Use for bracket orders (entry + stop loss + take profit).
 ///
 /// @param orders - Array of orders for the atomic transaction
-/// @param options - { account: string, signer?: string, nonce?: number }
+/// @param options - { account: string, signer?: string, nonce?: number, groupKind?: string, strictGroup?: boolean }
 /// @returns Single PreparedMessage containing all orders
 #[wasm_bindgen(js_name = prepareGroup)]
 pub fn wasm_prepare_group(
     orders: JsValue,
     options: JsValue,
 ) -> Result<WasmPreparedMessage, JsError> {
-    let order_inputs: Vec<OrderInput> =
-        serde_wasm_bindgen::from_value(orders).map_err(|e| JsError::new(&e.to_string()))?;
-    let opts: PrepareOptions =
-        serde_wasm_bindgen::from_value(options).map_err(|e| JsError::new(&e.to_string()))?;
+    let opts = parse_prepare_options(options)?;
+    let order_inputs = parse_orders(orders, opts.input_mode())?;
 
-    let order_items: Result<Vec<OrderItem>, String> =
+    let order_items: Result<Vec<OrderItem>, _> =
         order_inputs.into_iter().map(|o| o.try_into()).collect();
-    let order_items = order_items.map_err(|e| JsError::new(&e))?;
+    let order_items = order_items.map_err(keychain_error)?;
 
-    let account = Pubkey::from_base58(&opts.account).map_err(|e| JsError::new(&e.to_string()))?;
+    let account = Pubkey::from_base58(&opts.account).map_err(keychain_error)?;
     let signer = opts
         .signer
         .map(|s| Pubkey::from_base58(&s))
         .transpose()
-        .map_err(|e| JsError::new(&e.to_string()))?;
-    let nonce = opts.nonce.map(|n| n as u64);
+        .map_err(keychain_error)?;
+    let nonce = opts.nonce;
 
-    let prepared = prepare_group(order_items, &account, signer.as_ref(), nonce)
-        .map_err(|e| JsError::new(&e.to_string()))?;
+    let options = GroupOptions {
+        kind: opts.group_kind.map(GroupKind::from),
+        strict: opts.strict_group == Some(true),
+    };
+    let prepared =
+        prepare_group_with_options(order_items, &options, &account, signer.as_ref(), nonce)
+            .map_err(keychain_error)?;
 
-    Ok(WasmPreparedMessage { inner: prepared })
+    Ok(WasmPreparedMessage::new(prepared))
 }
 
 /// Prepare agent wallet creation for external signing
@@ -1702,70 +3127,156 @@ pub fn wasm_prepare_agent_wallet(
     let opts: PrepareOptions =
         serde_wasm_bindgen::from_value(options).map_err(|e| JsError::new(&e.to_string()))?;
 
-    let agent = Pubkey::from_base58(agent_pubkey).map_err(|e| JsError::new(&e.to_string()))?;
-    let account = Pubkey::from_base58(&opts.account).map_err(|e| JsError::new(&e.to_string()))?;
+    let agent = Pubkey::from_base58(agent_pubkey).map_err(keychain_error)?;
+    let account = Pubkey::from_base58(&opts.account).map_err(keychain_error)?;
     let signer = opts
         .signer
         .map(|s| Pubkey::from_base58(&s))
         .transpose()
-        .map_err(|e| JsError::new(&e.to_string()))?;
-    let nonce = opts.nonce.map(|n| n as u64);
+        .map_err(keychain_error)?;
+    let nonce = opts.nonce;
 
     let prepared = prepare_agent_wallet(&agent, delete, &account, signer.as_ref(), nonce)
-        .map_err(|e| JsError::new(&e.to_string()))?;
+        .map_err(keychain_error)?;
 
-    Ok(WasmPreparedMessage { inner: prepared })
+    Ok(WasmPreparedMessage::new(prepared))
 }
 
 /// Prepare faucet request for external signing
 ///
 /// @param options - { account: string, signer?: string, nonce?: number }
-#[wasm_bindgen(js_name = prepareFaucet)]
-pub fn wasm_prepare_faucet(options: JsValue) -> Result<WasmPreparedMessage, JsError> {
-    let opts: PrepareOptions =
-        serde_wasm_bindgen::from_value(options).map_err(|e| JsError::new(&e.to_string()))?;
+#[wasm_bindgen(js_name = prepareFaucet)]
+pub fn wasm_prepare_faucet(options: JsValue) -> Result<WasmPreparedMessage, JsError> {
+    let opts: PrepareOptions =
+        serde_wasm_bindgen::from_value(options).map_err(|e| JsError::new(&e.to_string()))?;
+
+    let account = Pubkey::from_base58(&opts.account).map_err(keychain_error)?;
+    let signer = opts
+        .signer
+        .map(|s| Pubkey::from_base58(&s))
+        .transpose()
+        .map_err(keychain_error)?;
+    let nonce = opts.nonce;
+
+    let prepared = prepare_faucet(&account, signer.as_ref(), nonce).map_err(keychain_error)?;
+
+    Ok(WasmPreparedMessage::new(prepared))
+}
+
+/// Prepare a single-order cancel for external signing
+///
+/// @param symbol - Market symbol
+/// @param orderId - Base58 ID of the order to cancel
+/// @param options - { account: string, signer?: string, nonce?: number }
+#[wasm_bindgen(js_name = prepareCancel)]
+pub fn wasm_prepare_cancel(
+    symbol: &str,
+    order_id: &str,
+    options: JsValue,
+) -> Result<WasmPreparedMessage, JsError> {
+    let order_id = Hash::from_base58(order_id)
+        .map_err(|e| JsError::new(&format!("Invalid orderId: {}", e)))?;
+    let opts: PrepareOptions =
+        serde_wasm_bindgen::from_value(options).map_err(|e| JsError::new(&e.to_string()))?;
+
+    let account = Pubkey::from_base58(&opts.account).map_err(keychain_error)?;
+    let signer = opts
+        .signer
+        .map(|s| Pubkey::from_base58(&s))
+        .transpose()
+        .map_err(keychain_error)?;
+    let nonce = opts.nonce;
+
+    let prepared = prepare_cancel(symbol, order_id, &account, signer.as_ref(), nonce)
+        .map_err(keychain_error)?;
+
+    Ok(WasmPreparedMessage::new(prepared))
+}
+
+/// Prepare a cancel-all for external signing
+///
+/// @param symbols - Markets to cancel (empty array = all markets)
+/// @param options - { account: string, signer?: string, nonce?: number }
+#[wasm_bindgen(js_name = prepareCancelAll)]
+pub fn wasm_prepare_cancel_all(
+    symbols: Vec<String>,
+    options: JsValue,
+) -> Result<WasmPreparedMessage, JsError> {
+    let opts: PrepareOptions =
+        serde_wasm_bindgen::from_value(options).map_err(|e| JsError::new(&e.to_string()))?;
+
+    let account = Pubkey::from_base58(&opts.account).map_err(keychain_error)?;
+    let signer = opts
+        .signer
+        .map(|s| Pubkey::from_base58(&s))
+        .transpose()
+        .map_err(keychain_error)?;
+    let nonce = opts.nonce;
+
+    let prepared =
+        prepare_cancel_all(symbols, &account, signer.as_ref(), nonce).map_err(keychain_error)?;
+
+    Ok(WasmPreparedMessage::new(prepared))
+}
+
+/// Prepare an atomic cancel + replacement order for external signing
+///
+/// @param oldOrderId - Base58 ID of the order to cancel
+/// @param newOrder - Replacement order ({ type: 'order', ... })
+/// @param options - { account: string, signer?: string, nonce?: number }
+#[wasm_bindgen(js_name = prepareCancelReplace)]
+pub fn wasm_prepare_cancel_replace(
+    old_order_id: &str,
+    new_order: JsValue,
+    options: JsValue,
+) -> Result<WasmPreparedMessage, JsError> {
+    let old_id = Hash::from_base58(old_order_id)
+        .map_err(|e| JsError::new(&format!("Invalid orderId: {}", e)))?;
+    let opts = parse_prepare_options(options)?;
+    let order_input = parse_order(new_order, opts.input_mode())?;
+    let new_order = match OrderItem::try_from(order_input).map_err(keychain_error)? {
+        OrderItem::Order(order) => order,
+        _ => return Err(JsError::new("newOrder must have type 'order'")),
+    };
 
-    let account = Pubkey::from_base58(&opts.account).map_err(|e| JsError::new(&e.to_string()))?;
+    let account = Pubkey::from_base58(&opts.account).map_err(keychain_error)?;
     let signer = opts
         .signer
         .map(|s| Pubkey::from_base58(&s))
         .transpose()
-        .map_err(|e| JsError::new(&e.to_string()))?;
-    let nonce = opts.nonce.map(|n| n as u64);
+        .map_err(keychain_error)?;
+    let nonce = opts.nonce;
 
-    let prepared = prepare_faucet(&account, signer.as_ref(), nonce)
-        .map_err(|e| JsError::new(&e.to_string()))?;
+    let prepared = prepare_cancel_replace(old_id, new_order, &account, signer.as_ref(), nonce)
+        .map_err(keychain_error)?;
 
-    Ok(WasmPreparedMessage { inner: prepared })
+    Ok(WasmPreparedMessage::new(prepared))
 }
 
 /// Prepare user settings update for external signing
 ///
-/// @param settings - { max_leverage: [[symbol, leverage], ...] }
+/// @param settings - { maxLeverage: [{ symbol, leverage }, ...] } or just the array
 /// @param options - { account: string, signer?: string, nonce?: number }
 #[wasm_bindgen(js_name = prepareUpdateUserSettings)]
 pub fn wasm_prepare_update_user_settings(
     settings: JsValue,
     options: JsValue,
 ) -> Result<WasmPreparedMessage, JsError> {
-    let settings_input: UserSettingsInput =
-        serde_wasm_bindgen::from_value(settings).map_err(|e| JsError::new(&e.to_string()))?;
-    let opts: PrepareOptions =
-        serde_wasm_bindgen::from_value(options).map_err(|e| JsError::new(&e.to_string()))?;
+    let opts = parse_prepare_options(options)?;
+    let user_settings = parse_user_settings(settings, opts.input_mode())?;
 
-    let account = Pubkey::from_base58(&opts.account).map_err(|e| JsError::new(&e.to_string()))?;
+    let account = Pubkey::from_base58(&opts.account).map_err(keychain_error)?;
     let signer = opts
         .signer
         .map(|s| Pubkey::from_base58(&s))
         .transpose()
-        .map_err(|e| JsError::new(&e.to_string()))?;
-    let nonce = opts.nonce.map(|n| n as u64);
+        .map_err(keychain_error)?;
+    let nonce = opts.nonce;
 
-    let user_settings = UserSettings::new(settings_input.max_leverage);
     let prepared = prepare_user_settings(user_settings, &account, signer.as_ref(), nonce)
-        .map_err(|e| JsError::new(&e.to_string()))?;
+        .map_err(keychain_error)?;
 
-    Ok(WasmPreparedMessage { inner: prepared })
+    Ok(WasmPreparedMessage::new(prepared))
 }
 
 /// Prepare a margin transfer for external signing
@@ -1789,8 +3300,8 @@ pub fn wasm_prepare_transfer(
         account: String,
         #[serde(default)]
         signer: Option<String>,
-        #[serde(default)]
-        nonce: Option<f64>,
+        #[serde(default, deserialize_with = "de_nonce")]
+        nonce: Option<u64>,
         #[serde(default)]
         kind: Option<String>,
     }
@@ -1798,15 +3309,15 @@ pub fn wasm_prepare_transfer(
     let opts: TransferOptions =
         serde_wasm_bindgen::from_value(options).map_err(|e| JsError::new(&e.to_string()))?;
 
-    let from = Pubkey::from_base58(from_pubkey).map_err(|e| JsError::new(&e.to_string()))?;
-    let to = Pubkey::from_base58(to_pubkey).map_err(|e| JsError::new(&e.to_string()))?;
-    let account = Pubkey::from_base58(&opts.account).map_err(|e| JsError::new(&e.to_string()))?;
+    let from = Pubkey::from_base58(from_pubkey).map_err(keychain_error)?;
+    let to = Pubkey::from_base58(to_pubkey).map_err(keychain_error)?;
+    let account = Pubkey::from_base58(&opts.account).map_err(keychain_error)?;
     let signer = opts
         .signer
         .map(|s| Pubkey::from_base58(&s))
         .transpose()
-        .map_err(|e| JsError::new(&e.to_string()))?;
-    let nonce = opts.nonce.map(|n| n as u64);
+        .map_err(keychain_error)?;
+    let nonce = opts.nonce;
     let kind = match opts.kind.as_deref() {
         Some("external") => TransferKind::External,
         Some("internal") | None => TransferKind::Internal,
@@ -1821,10 +3332,10 @@ pub fn wasm_prepare_transfer(
         margin_amount,
     };
 
-    let prepared = prepare_transfer(transfer, &account, signer.as_ref(), nonce)
-        .map_err(|e| JsError::new(&e.to_string()))?;
+    let prepared =
+        prepare_transfer(transfer, &account, signer.as_ref(), nonce).map_err(keychain_error)?;
 
-    Ok(WasmPreparedMessage { inner: prepared })
+    Ok(WasmPreparedMessage::new(prepared))
 }
 
 /// Prepare a sub-account removal for external signing
@@ -1839,19 +3350,19 @@ pub fn wasm_prepare_remove_sub_account(
     let opts: PrepareOptions =
         serde_wasm_bindgen::from_value(options).map_err(|e| JsError::new(&e.to_string()))?;
 
-    let target = Pubkey::from_base58(to_remove).map_err(|e| JsError::new(&e.to_string()))?;
-    let account = Pubkey::from_base58(&opts.account).map_err(|e| JsError::new(&e.to_string()))?;
+    let target = Pubkey::from_base58(to_remove).map_err(keychain_error)?;
+    let account = Pubkey::from_base58(&opts.account).map_err(keychain_error)?;
     let signer = opts
         .signer
         .map(|s| Pubkey::from_base58(&s))
         .transpose()
-        .map_err(|e| JsError::new(&e.to_string()))?;
-    let nonce = opts.nonce.map(|n| n as u64);
+        .map_err(keychain_error)?;
+    let nonce = opts.nonce;
 
     let prepared = prepare_remove_sub_account(target, &account, signer.as_ref(), nonce)
-        .map_err(|e| JsError::new(&e.to_string()))?;
+        .map_err(keychain_error)?;
 
-    Ok(WasmPreparedMessage { inner: prepared })
+    Ok(WasmPreparedMessage::new(prepared))
 }
 
 /// Prepare a sub-account rename for external signing
@@ -1868,14 +3379,14 @@ pub fn wasm_prepare_rename_sub_account(
     let opts: PrepareOptions =
         serde_wasm_bindgen::from_value(options).map_err(|e| JsError::new(&e.to_string()))?;
 
-    let subaccount = Pubkey::from_base58(subaccount).map_err(|e| JsError::new(&e.to_string()))?;
-    let account = Pubkey::from_base58(&opts.account).map_err(|e| JsError::new(&e.to_string()))?;
+    let subaccount = Pubkey::from_base58(subaccount).map_err(keychain_error)?;
+    let account = Pubkey::from_base58(&opts.account).map_err(keychain_error)?;
     let signer = opts
         .signer
         .map(|s| Pubkey::from_base58(&s))
         .transpose()
-        .map_err(|e| JsError::new(&e.to_string()))?;
-    let nonce = opts.nonce.map(|n| n as u64);
+        .map_err(keychain_error)?;
+    let nonce = opts.nonce;
 
     let prepared = prepare_rename_sub_account(
         RenameSubAccount {
@@ -1886,9 +3397,9 @@ pub fn wasm_prepare_rename_sub_account(
         signer.as_ref(),
         nonce,
     )
-    .map_err(|e| JsError::new(&e.to_string()))?;
+    .map_err(keychain_error)?;
 
-    Ok(WasmPreparedMessage { inner: prepared })
+    Ok(WasmPreparedMessage::new(prepared))
 }
 
 /// Prepare a sub-account creation for external signing
@@ -1906,8 +3417,8 @@ pub fn wasm_prepare_create_sub_account(
         account: String,
         #[serde(default)]
         signer: Option<String>,
-        #[serde(default)]
-        nonce: Option<f64>,
+        #[serde(default, deserialize_with = "de_nonce")]
+        nonce: Option<u64>,
         #[serde(default)]
         margin_symbol: Option<String>,
         #[serde(default)]
@@ -1917,13 +3428,13 @@ pub fn wasm_prepare_create_sub_account(
     let opts: CreateSubAccountOptions =
         serde_wasm_bindgen::from_value(options).map_err(|e| JsError::new(&e.to_string()))?;
 
-    let account = Pubkey::from_base58(&opts.account).map_err(|e| JsError::new(&e.to_string()))?;
+    let account = Pubkey::from_base58(&opts.account).map_err(keychain_error)?;
     let signer = opts
         .signer
         .map(|s| Pubkey::from_base58(&s))
         .transpose()
-        .map_err(|e| JsError::new(&e.to_string()))?;
-    let nonce = opts.nonce.map(|n| n as u64);
+        .map_err(keychain_error)?;
+    let nonce = opts.nonce;
 
     let sub_account = CreateSubAccount {
         name,
@@ -1932,9 +3443,9 @@ pub fn wasm_prepare_create_sub_account(
     };
 
     let prepared = prepare_create_sub_account(sub_account, &account, signer.as_ref(), nonce)
-        .map_err(|e| JsError::new(&e.to_string()))?;
+        .map_err(keychain_error)?;
 
-    Ok(WasmPreparedMessage { inner: prepared })
+    Ok(WasmPreparedMessage::new(prepared))
 }
 
 /// Prepare a multisig creation for external signing
@@ -1954,8 +3465,8 @@ pub fn wasm_prepare_create_multisig(
         account: String,
         #[serde(default)]
         signer: Option<String>,
-        #[serde(default)]
-        nonce: Option<f64>,
+        #[serde(default, deserialize_with = "de_nonce")]
+        nonce: Option<u64>,
         #[serde(default)]
         time_lock_secs: Option<u32>,
         #[serde(default)]
@@ -1966,18 +3477,18 @@ pub fn wasm_prepare_create_multisig(
         serde_wasm_bindgen::from_value(signers).map_err(|e| JsError::new(&e.to_string()))?;
     let signers = signer_inputs
         .into_iter()
-        .map(|s| Pubkey::from_base58(&s).map_err(|e| JsError::new(&e.to_string())))
+        .map(|s| Pubkey::from_base58(&s).map_err(keychain_error))
         .collect::<Result<Vec<_>, _>>()?;
 
     let opts: CreateMultisigOptions =
         serde_wasm_bindgen::from_value(options).map_err(|e| JsError::new(&e.to_string()))?;
-    let account = Pubkey::from_base58(&opts.account).map_err(|e| JsError::new(&e.to_string()))?;
+    let account = Pubkey::from_base58(&opts.account).map_err(keychain_error)?;
     let signer = opts
         .signer
         .map(|s| Pubkey::from_base58(&s))
         .transpose()
-        .map_err(|e| JsError::new(&e.to_string()))?;
-    let nonce = opts.nonce.map(|n| n as u64);
+        .map_err(keychain_error)?;
+    let nonce = opts.nonce;
 
     let create_multisig = CreateMultisig {
         signers,
@@ -1987,9 +3498,9 @@ pub fn wasm_prepare_create_multisig(
     };
 
     let prepared = prepare_create_multisig(create_multisig, &account, signer.as_ref(), nonce)
-        .map_err(|e| JsError::new(&e.to_string()))?;
+        .map_err(keychain_error)?;
 
-    Ok(WasmPreparedMessage { inner: prepared })
+    Ok(WasmPreparedMessage::new(prepared))
 }
 
 /// Prepare a multisig proposal for external signing
@@ -2002,15 +3513,15 @@ pub fn wasm_prepare_multisig_propose(
     let opts: PrepareOptions =
         serde_wasm_bindgen::from_value(options).map_err(|e| JsError::new(&e.to_string()))?;
 
-    let multisig = Pubkey::from_base58(multisig).map_err(|e| JsError::new(&e.to_string()))?;
+    let multisig = Pubkey::from_base58(multisig).map_err(keychain_error)?;
     let actions = parse_action_values(actions)?;
-    let account = Pubkey::from_base58(&opts.account).map_err(|e| JsError::new(&e.to_string()))?;
+    let account = Pubkey::from_base58(&opts.account).map_err(keychain_error)?;
     let signer = opts
         .signer
         .map(|s| Pubkey::from_base58(&s))
         .transpose()
-        .map_err(|e| JsError::new(&e.to_string()))?;
-    let nonce = opts.nonce.map(|n| n as u64);
+        .map_err(keychain_error)?;
+    let nonce = opts.nonce;
 
     let prepared = prepare_multisig_propose(
         MultisigPropose::new(multisig, actions),
@@ -2018,9 +3529,9 @@ pub fn wasm_prepare_multisig_propose(
         signer.as_ref(),
         nonce,
     )
-    .map_err(|e| JsError::new(&e.to_string()))?;
+    .map_err(keychain_error)?;
 
-    Ok(WasmPreparedMessage { inner: prepared })
+    Ok(WasmPreparedMessage::new(prepared))
 }
 
 /// Prepare a multisig approval for external signing
@@ -2033,14 +3544,14 @@ pub fn wasm_prepare_multisig_approve(
     let opts: PrepareOptions =
         serde_wasm_bindgen::from_value(options).map_err(|e| JsError::new(&e.to_string()))?;
 
-    let multisig = Pubkey::from_base58(multisig).map_err(|e| JsError::new(&e.to_string()))?;
-    let account = Pubkey::from_base58(&opts.account).map_err(|e| JsError::new(&e.to_string()))?;
+    let multisig = Pubkey::from_base58(multisig).map_err(keychain_error)?;
+    let account = Pubkey::from_base58(&opts.account).map_err(keychain_error)?;
     let signer = opts
         .signer
         .map(|s| Pubkey::from_base58(&s))
         .transpose()
-        .map_err(|e| JsError::new(&e.to_string()))?;
-    let nonce = opts.nonce.map(|n| n as u64);
+        .map_err(keychain_error)?;
+    let nonce = opts.nonce;
 
     let prepared = prepare_multisig_approve(
         MultisigApprove::new(multisig, proposal_id as u64),
@@ -2048,9 +3559,9 @@ pub fn wasm_prepare_multisig_approve(
         signer.as_ref(),
         nonce,
     )
-    .map_err(|e| JsError::new(&e.to_string()))?;
+    .map_err(keychain_error)?;
 
-    Ok(WasmPreparedMessage { inner: prepared })
+    Ok(WasmPreparedMessage::new(prepared))
 }
 
 /// Prepare a multisig rejection for external signing
@@ -2063,14 +3574,14 @@ pub fn wasm_prepare_multisig_reject(
     let opts: PrepareOptions =
         serde_wasm_bindgen::from_value(options).map_err(|e| JsError::new(&e.to_string()))?;
 
-    let multisig = Pubkey::from_base58(multisig).map_err(|e| JsError::new(&e.to_string()))?;
-    let account = Pubkey::from_base58(&opts.account).map_err(|e| JsError::new(&e.to_string()))?;
+    let multisig = Pubkey::from_base58(multisig).map_err(keychain_error)?;
+    let account = Pubkey::from_base58(&opts.account).map_err(keychain_error)?;
     let signer = opts
         .signer
         .map(|s| Pubkey::from_base58(&s))
         .transpose()
-        .map_err(|e| JsError::new(&e.to_string()))?;
-    let nonce = opts.nonce.map(|n| n as u64);
+        .map_err(keychain_error)?;
+    let nonce = opts.nonce;
 
     let prepared = prepare_multisig_reject(
         MultisigReject::new(multisig, proposal_id as u64),
@@ -2078,9 +3589,9 @@ pub fn wasm_prepare_multisig_reject(
         signer.as_ref(),
         nonce,
     )
-    .map_err(|e| JsError::new(&e.to_string()))?;
+    .map_err(keychain_error)?;
 
-    Ok(WasmPreparedMessage { inner: prepared })
+    Ok(WasmPreparedMessage::new(prepared))
 }
 
 /// Prepare a multisig cancellation for external signing
@@ -2093,14 +3604,14 @@ pub fn wasm_prepare_multisig_cancel(
     let opts: PrepareOptions =
         serde_wasm_bindgen::from_value(options).map_err(|e| JsError::new(&e.to_string()))?;
 
-    let multisig = Pubkey::from_base58(multisig).map_err(|e| JsError::new(&e.to_string()))?;
-    let account = Pubkey::from_base58(&opts.account).map_err(|e| JsError::new(&e.to_string()))?;
+    let multisig = Pubkey::from_base58(multisig).map_err(keychain_error)?;
+    let account = Pubkey::from_base58(&opts.account).map_err(keychain_error)?;
     let signer = opts
         .signer
         .map(|s| Pubkey::from_base58(&s))
         .transpose()
-        .map_err(|e| JsError::new(&e.to_string()))?;
-    let nonce = opts.nonce.map(|n| n as u64);
+        .map_err(keychain_error)?;
+    let nonce = opts.nonce;
 
     let prepared = prepare_multisig_cancel(
         MultisigCancel::new(multisig, proposal_id as u64),
@@ -2108,9 +3619,9 @@ pub fn wasm_prepare_multisig_cancel(
         signer.as_ref(),
         nonce,
     )
-    .map_err(|e| JsError::new(&e.to_string()))?;
+    .map_err(keychain_error)?;
 
-    Ok(WasmPreparedMessage { inner: prepared })
+    Ok(WasmPreparedMessage::new(prepared))
 }
 
 /// Prepare a multisig execution for external signing
@@ -2123,14 +3634,14 @@ pub fn wasm_prepare_multisig_execute(
     let opts: PrepareOptions =
         serde_wasm_bindgen::from_value(options).map_err(|e| JsError::new(&e.to_string()))?;
 
-    let multisig = Pubkey::from_base58(multisig).map_err(|e| JsError::new(&e.to_string()))?;
-    let account = Pubkey::from_base58(&opts.account).map_err(|e| JsError::new(&e.to_string()))?;
+    let multisig = Pubkey::from_base58(multisig).map_err(keychain_error)?;
+    let account = Pubkey::from_base58(&opts.account).map_err(keychain_error)?;
     let signer = opts
         .signer
         .map(|s| Pubkey::from_base58(&s))
         .transpose()
-        .map_err(|e| JsError::new(&e.to_string()))?;
-    let nonce = opts.nonce.map(|n| n as u64);
+        .map_err(keychain_error)?;
+    let nonce = opts.nonce;
 
     let prepared = prepare_multisig_execute(
         MultisigExecute::new(multisig, proposal_id as u64),
@@ -2138,9 +3649,9 @@ pub fn wasm_prepare_multisig_execute(
         signer.as_ref(),
         nonce,
     )
-    .map_err(|e| JsError::new(&e.to_string()))?;
+    .map_err(keychain_error)?;
 
-    Ok(WasmPreparedMessage { inner: prepared })
+    Ok(WasmPreparedMessage::new(prepared))
 }
 
 /// Prepare a multisig policy update for external signing
@@ -2157,31 +3668,31 @@ pub fn wasm_prepare_update_multisig_policy(
         account: String,
         #[serde(default)]
         signer: Option<String>,
-        #[serde(default)]
-        nonce: Option<f64>,
+        #[serde(default, deserialize_with = "de_nonce")]
+        nonce: Option<u64>,
         #[serde(default)]
         time_lock_secs: Option<u32>,
         #[serde(default)]
         proposal_lifetime_secs: Option<u32>,
     }
 
-    let multisig = Pubkey::from_base58(multisig).map_err(|e| JsError::new(&e.to_string()))?;
+    let multisig = Pubkey::from_base58(multisig).map_err(keychain_error)?;
     let signer_inputs: Vec<String> =
         serde_wasm_bindgen::from_value(signers).map_err(|e| JsError::new(&e.to_string()))?;
     let signers = signer_inputs
         .into_iter()
-        .map(|s| Pubkey::from_base58(&s).map_err(|e| JsError::new(&e.to_string())))
+        .map(|s| Pubkey::from_base58(&s).map_err(keychain_error))
         .collect::<Result<Vec<_>, _>>()?;
 
     let opts: UpdateMultisigPolicyOptions =
         serde_wasm_bindgen::from_value(options).map_err(|e| JsError::new(&e.to_string()))?;
-    let account = Pubkey::from_base58(&opts.account).map_err(|e| JsError::new(&e.to_string()))?;
+    let account = Pubkey::from_base58(&opts.account).map_err(keychain_error)?;
     let signer = opts
         .signer
         .map(|s| Pubkey::from_base58(&s))
         .transpose()
-        .map_err(|e| JsError::new(&e.to_string()))?;
-    let nonce = opts.nonce.map(|n| n as u64);
+        .map_err(keychain_error)?;
+    let nonce = opts.nonce;
 
     let update = UpdateMultisigPolicy {
         multisig,
@@ -2192,40 +3703,1211 @@ pub fn wasm_prepare_update_multisig_policy(
     };
 
     let prepared = prepare_update_multisig_policy(update, &account, signer.as_ref(), nonce)
-        .map_err(|e| JsError::new(&e.to_string()))?;
+        .map_err(keychain_error)?;
+
+    Ok(WasmPreparedMessage::new(prepared))
+}
+
+/// The fields of a prepared message that finalizing needs, as a
+/// `WasmPreparedMessage` or any plain object of the same shape (such as one
+/// from the Node binding). Fields are read one by one, so getters work.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PreparedMessageInput {
+    #[serde(default)]
+    order_id: Option<String>,
+    #[serde(default)]
+    order_ids: Option<Vec<String>>,
+    /// An array, or the JSON string older Node versions produced
+    actions: JsonValue,
+    account: String,
+    signer: String,
+    /// A number, a decimal string or a BigInt
+    nonce: JsonValue,
+    #[serde(default)]
+    group_kind: Option<String>,
+    #[serde(default)]
+    group_warnings: Option<Vec<bulk_keychain::GroupLintWarning>>,
+}
+
+fn prepared_from_js(prepared: JsValue) -> Result<PreparedMessageInput, JsError> {
+    serde_wasm_bindgen::from_value(prepared).map_err(|e| JsError::new(&e.to_string()))
+}
+
+/// `{ skipVerify?: boolean }` for the finalize functions
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct FinalizeOptions {
+    #[serde(default)]
+    skip_verify: bool,
+}
+
+fn parse_finalize_options(options: JsValue) -> Result<FinalizeOptions, JsError> {
+    if options.is_undefined() || options.is_null() {
+        return Ok(FinalizeOptions::default());
+    }
+    serde_wasm_bindgen::from_value(options).map_err(|e| JsError::new(&e.to_string()))
+}
+
+impl PreparedMessageInput {
+    /// Finalize after rebuilding the message from the actions, account,
+    /// signer and nonce and checking `signature` over it.
+    fn finalize_verified(self, signature: &str) -> Result<SignedTransaction, JsError> {
+        let tx = self.finalize(signature)?;
+        let actions = tx.actions_json().map_err(keychain_error)?;
+        let mut rebuilt = PreparedMessage::from_parts(actions, &tx.account, &tx.signer, tx.nonce)
+            .map_err(keychain_error)?;
+        rebuilt.group_kind = tx.group_kind;
+        rebuilt.group_warnings = tx.group_warnings;
+        rebuilt.finalize_verified(signature).map_err(keychain_error)
+    }
 
-    Ok(WasmPreparedMessage { inner: prepared })
+    fn finalize(self, signature: &str) -> Result<SignedTransaction, JsError> {
+        let invalid = |field: &str, e: serde_json::Error| {
+            JsError::new(&format!("invalid prepared.{}: {}", field, e))
+        };
+        let actions: Vec<JsonValue> = match self.actions {
+            JsonValue::String(json) => serde_json::from_str(&json),
+            actions => serde_json::from_value(actions),
+        }
+        .map_err(|e| invalid("actions", e))?;
+        let action = bulk_keychain::compact::action_from_json_list(&actions)
+            .map_err(|e| JsError::new(&format!("invalid prepared.actions: {}", e)))?;
+        let mut nonce = self.nonce;
+        narrow_large_ints(&mut nonce);
+        let nonce = serde_json::from_value(nonce).map_err(|e| invalid("nonce", e))?;
+        Ok(SignedTransaction {
+            action,
+            nonce,
+            account: self.account,
+            signer: self.signer,
+            signature: signature.to_string(),
+            order_id: self.order_id,
+            order_ids: self.order_ids,
+            id_scheme: Default::default(),
+            resign_count: 0,
+            group_kind: self.group_kind.map(GroupKind::from),
+            group_warnings: self.group_warnings.unwrap_or_default(),
+        })
+    }
 }
 
 /// Finalize a prepared message with a signature
 ///
-/// Alternative to calling prepared.finalize() - useful if you have
-/// the prepared message as a plain object.
+/// Alternative to calling prepared.finalize(). `prepared` may be a
+/// `WasmPreparedMessage` or a plain object with the same fields. The message
+/// is rebuilt from its actions, account, signer and nonce, and `signature`
+/// must verify over it against `signer`; pass `{ skipVerify: true }` to
+/// attach it unchecked.
 #[wasm_bindgen(js_name = finalizeTransaction)]
-pub fn wasm_finalize_transaction(prepared: JsValue, signature: &str) -> Result<JsValue, JsError> {
-    let prep: PreparedMessage =
-        serde_wasm_bindgen::from_value(prepared).map_err(|e| JsError::new(&e.to_string()))?;
-    let signed = finalize_transaction(prep, signature);
-    serde_wasm_bindgen::to_value(&signed).map_err(|e| JsError::new(&e.to_string()))
+pub fn wasm_finalize_transaction(
+    prepared: JsValue,
+    signature: &str,
+    options: JsValue,
+) -> Result<JsValue, JsError> {
+    let prepared = prepared_from_js(prepared)?;
+    let signed = if parse_finalize_options(options)?.skip_verify {
+        prepared.finalize(signature)?
+    } else {
+        prepared.finalize_verified(signature)?
+    };
+    signed_to_js(&signed)
+}
+
+/// Finalize a prepared message with a raw 64-byte signature
+///
+/// For wallet adapters and Web Crypto `sign()` calls that return a
+/// `Uint8Array` instead of a base58 string.
+#[wasm_bindgen(js_name = finalizePreparedTransactionBytes)]
+pub fn finalize_prepared_transaction_bytes(
+    prepared: JsValue,
+    signature_bytes: &[u8],
+    options: JsValue,
+) -> Result<JsValue, JsError> {
+    let signature = signature_to_base58(signature_bytes)?;
+    wasm_finalize_transaction(prepared, &signature, options)
+}
+
+/// Finalize a prepared message with a hex-encoded signature (optional `0x` prefix)
+#[wasm_bindgen(js_name = finalizePreparedTransactionHex)]
+pub fn finalize_prepared_transaction_hex(
+    prepared: JsValue,
+    signature_hex: &str,
+    options: JsValue,
+) -> Result<JsValue, JsError> {
+    let bytes = hex::decode(signature_hex.trim_start_matches("0x"))
+        .map_err(|e| JsError::new(&format!("invalid signature hex: {}", e)))?;
+    let signature = signature_to_base58(&bytes)?;
+    wasm_finalize_transaction(prepared, &signature, options)
+}
+
+/// Message bytes behind a Ledger blind-signing header
+/// (`[version u8][length u32 BE][message]`)
+#[wasm_bindgen(js_name = encodeForLedger)]
+pub fn encode_for_ledger(prepared: &WasmPreparedMessage) -> Vec<u8> {
+    prepared.inner.encode_for_ledger()
+}
+
+/// Message bytes as a Trezor `SolanaSignTx` frame on `m/44'/501'/0'/0'`
+#[wasm_bindgen(js_name = encodeForTrezor)]
+pub fn encode_for_trezor(prepared: &WasmPreparedMessage) -> Vec<u8> {
+    prepared.inner.encode_for_trezor()
+}
+
+fn signature_to_base58(bytes: &[u8]) -> Result<String, JsError> {
+    if bytes.len() != 64 {
+        return Err(JsError::new(
+            &bulk_keychain::Error::InvalidSignatureLength(bytes.len()).to_string(),
+        ));
+    }
+    Ok(bulk_keychain::bs58::encode(bytes).into_string())
+}
+
+// ============================================================================
+// Node.js names
+// ============================================================================
+
+/// Deprecated: use `prepareAll`, the name shared with the Node.js binding
+#[wasm_bindgen(js_name = prepareAllOrders)]
+pub fn legacy_prepare_all_orders(
+    orders: JsValue,
+    options: JsValue,
+) -> Result<Vec<WasmPreparedMessage>, JsError> {
+    wasm_prepare_all(orders, options)
+}
+
+/// Deprecated: use `prepareGroup`, the name shared with the Node.js binding
+#[wasm_bindgen(js_name = prepareOrderGroup)]
+pub fn legacy_prepare_order_group(
+    orders: JsValue,
+    options: JsValue,
+) -> Result<WasmPreparedMessage, JsError> {
+    wasm_prepare_group(orders, options)
+}
+
+/// Deprecated: use `prepareAgentWallet`, the name shared with the Node.js binding
+#[wasm_bindgen(js_name = prepareAgentWalletAuth)]
+pub fn legacy_prepare_agent_wallet_auth(
+    agent_pubkey: &str,
+    delete: bool,
+    options: JsValue,
+) -> Result<WasmPreparedMessage, JsError> {
+    wasm_prepare_agent_wallet(agent_pubkey, delete, options)
+}
+
+/// Deprecated: use `prepareFaucet`, the name shared with the Node.js binding
+#[wasm_bindgen(js_name = prepareFaucetRequest)]
+pub fn legacy_prepare_faucet_request(options: JsValue) -> Result<WasmPreparedMessage, JsError> {
+    wasm_prepare_faucet(options)
+}
+
+/// Deprecated: use `finalizeTransaction`, the name shared with the Node.js binding
+#[wasm_bindgen(js_name = finalizePreparedTransaction)]
+pub fn legacy_finalize_prepared_transaction(
+    prepared: JsValue,
+    signature: &str,
+    options: JsValue,
+) -> Result<JsValue, JsError> {
+    wasm_finalize_transaction(prepared, signature, options)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use bulk_keychain::{Order, TimeInForce};
     use wasm_bindgen_test::*;
 
+    fn js_nonce(n: f64) -> Option<Nonce> {
+        Some(Nonce::from(JsValue::from_f64(n)))
+    }
+
     #[wasm_bindgen_test]
     fn test_keypair_generation() {
-        let keypair = WasmKeypair::new();
+        let keypair = WasmKeypair::new().unwrap();
         let pubkey = keypair.pubkey();
         assert!(!pubkey.is_empty());
     }
 
+    #[wasm_bindgen_test]
+    fn test_build_info_reports_core_build() {
+        let info: serde_json::Value =
+            serde_wasm_bindgen::from_value(build_info().unwrap()).unwrap();
+        assert_eq!(
+            info["crateVersion"],
+            bulk_keychain::build_info().crate_version
+        );
+        assert_eq!(info["bindingVersion"], env!("CARGO_PKG_VERSION"));
+        let has_batch = info["features"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .any(|f| f == "batch");
+        assert_eq!(has_batch, cfg!(feature = "full"));
+    }
+
+    #[wasm_bindgen_test]
+    fn test_capabilities_full_and_stripped_globals() {
+        let set = |target: &js_sys::Object, key: &str, value: &JsValue| {
+            js_sys::Reflect::set(target, &key.into(), value).unwrap();
+        };
+        let function = JsValue::from(js_sys::Function::new_no_args(""));
+        let crypto = js_sys::Object::new();
+        set(&crypto, "getRandomValues", &function);
+        let full = js_sys::Object::new();
+        set(&full, "crypto", &crypto);
+        set(&full, "BigInt", &function);
+        set(&full, "SharedArrayBuffer", &function);
+        set(&full, "crossOriginIsolated", &JsValue::TRUE);
+
+        let caps = probe_capabilities(&full);
+        assert!(caps.secure_random && caps.bigint && caps.threads);
+        assert_eq!(caps.version, env!("CARGO_PKG_VERSION"));
+
+        let stripped = probe_capabilities(&js_sys::Object::new());
+        assert!(!stripped.secure_random && !stripped.bigint && !stripped.threads);
+        assert_eq!(stripped.version, caps.version);
+
+        set(&full, "crossOriginIsolated", &JsValue::FALSE);
+        assert!(!probe_capabilities(&full).threads);
+    }
+
+    #[wasm_bindgen_test]
+    async fn test_init_keychain_reports_and_enforces() {
+        let caps: serde_json::Value =
+            serde_wasm_bindgen::from_value(init_keychain(JsValue::UNDEFINED).await.unwrap())
+                .unwrap();
+        assert_eq!(caps["secureRandom"], true);
+        assert_eq!(caps["version"], env!("CARGO_PKG_VERSION"));
+        assert!(INITIALIZED.load(Ordering::Acquire));
+
+        let options = js_sys::Object::new();
+        js_sys::Reflect::set(&options, &"requireThreads".into(), &JsValue::TRUE).unwrap();
+        let result = init_keychain(options.into()).await;
+        assert_eq!(result.is_ok(), caps["threads"] == true);
+    }
+
     #[wasm_bindgen_test]
     fn test_keypair_roundtrip() {
-        let keypair = WasmKeypair::new();
+        let keypair = WasmKeypair::new().unwrap();
         let b58 = keypair.to_base58();
         let restored = WasmKeypair::from_base58(&b58).unwrap();
         assert_eq!(keypair.pubkey(), restored.pubkey());
     }
+
+    #[wasm_bindgen_test]
+    fn test_keypair_jwk_roundtrip() {
+        let keypair = WasmKeypair::new().unwrap();
+        let restored = WasmKeypair::from_jwk(&keypair.to_jwk().unwrap()).unwrap();
+        assert_eq!(restored.pubkey(), keypair.pubkey());
+        assert!(WasmKeypair::from_jwk(r#"{"kty":"EC","crv":"P-256","x":""}"#).is_err());
+    }
+
+    #[wasm_bindgen_test]
+    fn test_keypair_mnemonic_roundtrip() {
+        let generated = WasmKeypair::generate_mnemonic().unwrap();
+        let get = |key: &str| js_sys::Reflect::get(&generated, &key.into()).unwrap();
+        let mnemonic = get("mnemonic").as_string().unwrap();
+        assert_eq!(mnemonic.split(' ').count(), 24);
+        let restored = WasmKeypair::from_mnemonic(&mnemonic, None, None).unwrap();
+        let pubkey = js_sys::Reflect::get(&get("keypair"), &"pubkey".into()).unwrap();
+        assert_eq!(pubkey.as_string(), Some(restored.pubkey()));
+        assert!(WasmKeypair::from_mnemonic("abandon abandon", None, None).is_err());
+
+        let abandon = format!("{}about", "abandon ".repeat(11));
+        let phantom = WasmKeypair::from_mnemonic(&abandon, None, None).unwrap();
+        assert_eq!(
+            phantom.pubkey(),
+            "HAgk14JpMQLgt6rVgv7cBQFJWFto5Dqxi472uT3DKpqk"
+        );
+        let second = WasmKeypair::from_mnemonic(&abandon, None, Some("m/44'/501'/1'/0'".into()));
+        assert_eq!(
+            second.unwrap().pubkey(),
+            "Hh8QwFUA6MtVu1qAoq12ucvFHNwCcVTV7hpWjeY1Hztb"
+        );
+        assert_eq!(
+            WasmKeypair::from_mnemonic_seed(&abandon, None)
+                .unwrap()
+                .pubkey(),
+            "EHqmfkN89RJ7Y33CXM6uCzhVeuywHoJXZZLszBHHZy7o"
+        );
+    }
+
+    #[wasm_bindgen_test]
+    fn test_keypair_derivation_paths() {
+        let root = WasmKeypair::new().unwrap();
+        let path = "m/44'/501'/0'/0'";
+        let a = root.from_derivation_path(path).unwrap();
+        assert_eq!(
+            a.pubkey(),
+            root.from_derivation_path(path).unwrap().pubkey()
+        );
+        assert_ne!(
+            a.pubkey(),
+            root.from_derivation_path("m/44'/501'/1'/0'")
+                .unwrap()
+                .pubkey()
+        );
+        let stepwise = root
+            .derive_child(44, true)
+            .and_then(|k| k.derive_child(501, true))
+            .and_then(|k| k.derive_child(0, true))
+            .and_then(|k| k.derive_child(0, true))
+            .unwrap();
+        assert_eq!(stepwise.pubkey(), a.pubkey());
+        assert!(root.derive_child(0, false).is_err());
+        assert!(root.from_derivation_path("m/44'/60'/0'/0/1").is_err());
+    }
+
+    #[wasm_bindgen_test]
+    fn test_signer_from_keypair_and_account() {
+        let agent = WasmKeypair::new().unwrap();
+        let account = WasmKeypair::new().unwrap().pubkey();
+        let signer = WasmSigner::from_keypair_and_account(&agent, &account).unwrap();
+        assert_eq!(signer.account(), account);
+        assert_ne!(signer.account(), signer.pubkey());
+        assert_eq!(signer.account_pubkey(), account);
+        assert_eq!(signer.signer_pubkey(), agent.pubkey());
+    }
+
+    #[cfg(feature = "full")]
+    #[wasm_bindgen_test]
+    fn test_sign_all_prices() {
+        let signer = WasmSigner::new(&WasmKeypair::new().unwrap());
+        let prices: Vec<f64> = (0..10).map(|i| 100000.0 + i as f64 * 10.0).collect();
+        let sizes = vec![0.1; 10];
+
+        let signed = signer
+            .sign_all_prices("BTC-USD", &prices, &sizes, true, "ALO", js_nonce(1000.0))
+            .unwrap();
+        let signed: Vec<serde_json::Value> = serde_wasm_bindgen::from_value(signed).unwrap();
+        assert_eq!(signed.len(), 10);
+        for (i, tx) in signed.iter().enumerate() {
+            assert_eq!(tx["nonce"].as_u64(), Some(1000 + i as u64));
+            assert_eq!(tx["actions"][0]["l"]["px"].as_f64(), Some(prices[i]));
+            assert_eq!(tx["actions"][0]["l"]["tif"], "ALO");
+        }
+
+        assert!(signer
+            .sign_all_prices("BTC-USD", &prices, &sizes[..9], true, "GTC", None)
+            .is_err());
+    }
+
+    #[cfg(feature = "full")]
+    #[wasm_bindgen_test]
+    fn test_indexed_variants() {
+        let mut signer = WasmSigner::new(&WasmKeypair::new().unwrap());
+        let parse = |json: &str| js_sys::JSON::parse(json).unwrap();
+        let order = r#"{"type":"order","symbol":"BTC-USD","isBuy":true,"price":100,"size":1,"orderType":{"type":"limit","tif":"GTC"}}"#;
+        let orders = || parse(&format!("[{order},{order}]"));
+
+        let batches = parse(&format!("[[{order}],[{order}]]"));
+        let signed = signer
+            .sign_orders_batch_indexed(batches, js_nonce(7.0))
+            .unwrap();
+        let signed: Vec<serde_json::Value> = serde_wasm_bindgen::from_value(signed).unwrap();
+        assert_eq!(
+            (signed[1]["index"].as_u64(), signed[1]["nonce"].as_u64()),
+            (Some(1), Some(8))
+        );
+
+        signer.set_max_open_orders_per_symbol(Some(1));
+        let results = signer
+            .sign_all_partial_indexed(orders(), js_nonce(20.0))
+            .unwrap();
+        let results: Vec<serde_json::Value> = serde_wasm_bindgen::from_value(results).unwrap();
+        assert_eq!(results[0]["index"], 0);
+        assert_eq!(results[0]["transaction"]["nonce"], 20);
+        assert_eq!(results[1]["index"], 1);
+        assert_eq!(results[1]["code"], "OpenOrderCapExceeded");
+
+        let options = parse(&format!(
+            r#"{{"account":"{}","nonce":5}}"#,
+            signer.account()
+        ));
+        let prepared = wasm_prepare_all_indexed(orders(), options).unwrap();
+        assert_eq!(prepared[1].index(), Some(1));
+        assert_eq!(prepared[1].inner.nonce, 6);
+    }
+
+    #[wasm_bindgen_test]
+    fn test_pre_sign_hook() {
+        let mut signer = WasmSigner::new(&WasmKeypair::new().unwrap());
+        signer.set_pre_sign_hook(Some(js_sys::Function::new_with_args(
+            "order, nonce",
+            "if (order.size > 1) throw new Error('too large');",
+        )));
+        let order = |size: f64| {
+            js_sys::JSON::parse(&format!(
+                r#"{{"type":"order","symbol":"BTC-USD","isBuy":true,"price":100000,"size":{size},"orderType":{{"type":"limit","tif":"GTC"}}}}"#
+            ))
+            .unwrap()
+        };
+
+        assert!(signer
+            .sign(order(0.5), js_nonce(1.0), JsValue::UNDEFINED)
+            .is_ok());
+        assert!(signer
+            .sign(order(2.0), js_nonce(2.0), JsValue::UNDEFINED)
+            .is_err());
+
+        signer.set_pre_sign_hook(None);
+        assert!(signer
+            .sign(order(2.0), js_nonce(3.0), JsValue::UNDEFINED)
+            .is_ok());
+    }
+
+    #[wasm_bindgen_test]
+    fn test_finalize_prepared_bytes_and_hex() {
+        let keypair = Keypair::generate();
+        let order = Order::limit("BTC-USD", true, 100000.0, 0.1, TimeInForce::Gtc);
+        let prepared = prepare_message(order.into(), &keypair.pubkey(), None, Some(1)).unwrap();
+        let sig = Signer::new(keypair).sign_bytes_raw(&prepared.message_bytes);
+        let value = || serde_wasm_bindgen::to_value(&prepared).unwrap();
+        let signature = |v: JsValue| {
+            serde_wasm_bindgen::from_value::<SignedTransaction>(v)
+                .unwrap()
+                .signature
+        };
+        let none = || JsValue::UNDEFINED;
+
+        let expected = bulk_keychain::bs58::encode(sig).into_string();
+        let from_bytes = finalize_prepared_transaction_bytes(value(), &sig, none()).unwrap();
+        assert_eq!(signature(from_bytes), expected);
+        let hex_sig = format!("0x{}", hex::encode(sig));
+        let from_hex = finalize_prepared_transaction_hex(value(), &hex_sig, none()).unwrap();
+        assert_eq!(signature(from_hex), expected);
+
+        assert!(finalize_prepared_transaction_bytes(value(), &sig[..63], none()).is_err());
+        assert!(finalize_prepared_transaction_hex(value(), "zz", none()).is_err());
+
+        // A signature over other bytes is refused unless verification is skipped.
+        let bogus = [7u8; 64];
+        assert!(finalize_prepared_transaction_bytes(value(), &bogus, none()).is_err());
+        let skip = js_sys::JSON::parse(r#"{"skipVerify":true}"#).unwrap();
+        let unchecked = finalize_prepared_transaction_bytes(value(), &bogus, skip).unwrap();
+        assert_eq!(
+            signature(unchecked),
+            bulk_keychain::bs58::encode(bogus).into_string()
+        );
+    }
+
+    #[wasm_bindgen_test]
+    fn test_node_names_match_canonical() {
+        let account = Keypair::generate().pubkey().to_base58();
+        let parse = |json: &str| js_sys::JSON::parse(json).unwrap();
+        let orders =
+            || parse(r#"[{"type":"order","symbol":"BTC-USD","isBuy":true,"price":100,"size":1}]"#);
+        let options = || parse(&format!(r#"{{"account":"{}","nonce":5}}"#, account));
+        let hex = |p: WasmPreparedMessage| p.message_hex();
+
+        assert_eq!(
+            hex(legacy_prepare_order_group(orders(), options()).unwrap()),
+            hex(wasm_prepare_group(orders(), options()).unwrap())
+        );
+        let all = |v: Vec<WasmPreparedMessage>| v.into_iter().map(hex).collect::<Vec<_>>();
+        assert_eq!(
+            all(legacy_prepare_all_orders(orders(), options()).unwrap()),
+            all(wasm_prepare_all(orders(), options()).unwrap())
+        );
+        assert_eq!(
+            hex(legacy_prepare_agent_wallet_auth(&account, false, options()).unwrap()),
+            hex(wasm_prepare_agent_wallet(&account, false, options()).unwrap())
+        );
+        assert_eq!(
+            hex(legacy_prepare_faucet_request(options()).unwrap()),
+            hex(wasm_prepare_faucet(options()).unwrap())
+        );
+
+        let keypair = Keypair::generate();
+        let prepared = || {
+            let options = parse(&format!(
+                r#"{{"account":"{}","nonce":5}}"#,
+                keypair.pubkey()
+            ));
+            JsValue::from(wasm_prepare_group(orders(), options).unwrap())
+        };
+        let signature = Signer::new(keypair.clone()).sign_bytes(b"any");
+        let json = |v: JsValue| js_sys::JSON::stringify(&v).unwrap().as_string();
+        let skip = || js_sys::JSON::parse(r#"{"skipVerify":true}"#).unwrap();
+        assert_eq!(
+            json(legacy_finalize_prepared_transaction(prepared(), &signature, skip()).unwrap()),
+            json(wasm_finalize_transaction(prepared(), &signature, skip()).unwrap())
+        );
+    }
+
+    #[wasm_bindgen_test]
+    fn test_sign_group_kind() {
+        let mut signer = WasmSigner::new(&WasmKeypair::new().unwrap());
+        let orders = || {
+            js_sys::JSON::parse(
+                r#"[{"type":"order","symbol":"BTC-USD","isBuy":true,"price":100000,"size":0.1,"orderType":{"type":"limit","tif":"GTC"}},{"type":"order","symbol":"BTC-USD","isBuy":false,"price":110000,"size":0.1,"orderType":{"type":"limit","tif":"GTC"}}]"#,
+            )
+            .unwrap()
+        };
+        let get = |v: &JsValue, key: &str| js_sys::Reflect::get(v, &key.into()).unwrap();
+
+        let tagged = signer
+            .sign_group(orders(), js_nonce(7.0), Some("bracket".into()), None)
+            .unwrap();
+        let plain = signer
+            .sign_group(orders(), js_nonce(7.0), None, None)
+            .unwrap();
+        assert_eq!(get(&tagged, "groupKind").as_string().unwrap(), "bracket");
+        assert!(get(&plain, "groupKind").is_undefined());
+        assert_eq!(get(&tagged, "signature"), get(&plain, "signature"));
+    }
+
+    #[wasm_bindgen_test]
+    fn test_sign_group_warnings_and_strict() {
+        let mut signer = WasmSigner::new(&WasmKeypair::new().unwrap());
+        let leg = r#"{"type":"order","symbol":"BTC-USD","isBuy":true,"price":100000,"size":0.1,"orderType":{"type":"limit","tif":"GTC"}}"#;
+        let doubled = || js_sys::JSON::parse(&format!("[{},{}]", leg, leg)).unwrap();
+
+        let tx = signer
+            .sign_group(doubled(), js_nonce(1.0), None, None)
+            .unwrap();
+        let warnings: Vec<serde_json::Value> = serde_wasm_bindgen::from_value(
+            js_sys::Reflect::get(&tx, &"groupWarnings".into()).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(warnings[0]["index"], 1);
+        assert_eq!(warnings[0]["code"], "duplicate_leg");
+        assert!(signer
+            .sign_group(doubled(), js_nonce(2.0), None, Some(true))
+            .is_err());
+    }
+
+    #[wasm_bindgen_test]
+    fn test_order_from_ticks() {
+        let parse = |json: &str| -> OrderInput {
+            serde_wasm_bindgen::from_value(js_sys::JSON::parse(json).unwrap()).unwrap()
+        };
+        let item: OrderItem = parse(
+            r#"{"type":"order","symbol":"BTC-USD","isBuy":true,"priceTicks":3,"sizeLots":7,"symbolSpec":{"priceDecimals":1,"sizeDecimals":2}}"#,
+        )
+        .try_into()
+        .unwrap();
+        let OrderItem::Order(order) = item else {
+            panic!("expected order");
+        };
+        assert_eq!((order.price, order.size), (0.3, 0.07));
+
+        let both: Result<OrderItem, _> = parse(
+            r#"{"type":"order","symbol":"BTC-USD","isBuy":true,"price":0.3,"priceTicks":3,"size":1,"symbolSpec":{"priceDecimals":1,"sizeDecimals":2}}"#,
+        )
+        .try_into();
+        assert!(both.unwrap_err().to_string().contains("mutually exclusive"));
+    }
+
+    #[wasm_bindgen_test]
+    fn test_camel_and_snake_inputs_agree() {
+        let parse = |json: &str| js_sys::JSON::parse(json).unwrap();
+        let camel = r#"{"type":"order","symbol":"BTC-USD","isBuy":true,"price":100,"size":1,"reduceOnly":true,"orderType":{"type":"limit","tif":"IOC"}}"#;
+        let snake = r#"{"type":"order","symbol":"BTC-USD","is_buy":true,"price":100,"size":1,"reduce_only":true,"order_type":{"type":"limit","tif":"IOC"}}"#;
+        let mixed = r#"{"type":"order","symbol":"BTC-USD","isBuy":true,"price":100,"size":1,"reduce_only":true,"orderType":{"type":"limit","tif":"IOC"},"note":"x"}"#;
+
+        let item = |json: &str| -> OrderItem {
+            let input: OrderInput = serde_wasm_bindgen::from_value(parse(json)).unwrap();
+            input.try_into().unwrap()
+        };
+        assert_eq!(item(snake), item(camel));
+        assert_eq!(item(mixed), item(camel));
+
+        let get = |v: &JsValue, key: &str| js_sys::Reflect::get(v, &key.into()).unwrap();
+        let strict = |json: &str| validate_order_input(parse(json), Some(true));
+        let checked = strict(snake).unwrap();
+        assert_eq!(
+            js_sys::JSON::stringify(&get(&checked, "order")).unwrap(),
+            js_sys::JSON::stringify(&get(&strict(camel).unwrap(), "order")).unwrap()
+        );
+        assert!(strict(mixed).is_err());
+
+        let tolerant = validate_order_input(parse(mixed), None).unwrap();
+        let warnings: Vec<String> =
+            serde_wasm_bindgen::from_value(get(&tolerant, "warnings")).unwrap();
+        assert_eq!(warnings, ["note: unknown field"]);
+    }
+
+    #[wasm_bindgen_test]
+    fn test_large_nonce_round_trips_exactly() {
+        let nonce = (1u64 << 60) + 3;
+        let mut signer = WasmSigner::new(&WasmKeypair::new().unwrap());
+        let order = Order::limit("BTC-USD", true, 100000.0, 0.1, TimeInForce::Gtc);
+        let signed = signer
+            .inner
+            .sign(order.clone().into(), Some(nonce))
+            .unwrap();
+        let get = |v: &JsValue, key: &str| js_sys::Reflect::get(v, &key.into()).unwrap();
+
+        let js = to_js(&signed).unwrap();
+        assert_eq!(get(&js, "nonce").as_string().unwrap(), nonce.to_string());
+        assert!(get(&js, "actions").is_array());
+        assert!(signer.verify_signed_transaction(js, None).unwrap());
+
+        set_serialize_large_ints_as("bigint").unwrap();
+        let js = to_js(&signed).unwrap();
+        set_serialize_large_ints_as("string").unwrap();
+        assert_eq!(u64::try_from(get(&js, "nonce")).unwrap(), nonce);
+        assert!(signer.verify_signed_transaction(js, None).unwrap());
+        assert!(set_serialize_large_ints_as("number").is_err());
+
+        // Safe integers stay plain numbers.
+        let small = signer.inner.sign(order.into(), Some(42)).unwrap();
+        assert_eq!(get(&to_js(&small).unwrap(), "nonce").as_f64(), Some(42.0));
+    }
+
+    #[wasm_bindgen_test]
+    fn test_bigint_nonce_inputs() {
+        let nonce = (1u64 << 60) + 3;
+        let keypair = WasmKeypair::new().unwrap();
+        let mut signer = WasmSigner::new(&keypair);
+        let bigint = || Some(Nonce::from(JsValue::from(js_sys::BigInt::from(nonce))));
+        let order = || {
+            js_sys::JSON::parse(
+                r#"{"type":"order","symbol":"BTC-USD","isBuy":true,"price":100,"size":1}"#,
+            )
+            .unwrap()
+        };
+        let get = |v: &JsValue, key: &str| js_sys::Reflect::get(v, &key.into()).unwrap();
+
+        let signed = signer.sign(order(), bigint(), JsValue::UNDEFINED).unwrap();
+        assert_eq!(
+            get(&signed, "nonce").as_string().unwrap(),
+            nonce.to_string()
+        );
+        assert!(signer.verify_signed_transaction(signed, None).unwrap());
+
+        let options = js_sys::Object::new();
+        js_sys::Reflect::set(&options, &"account".into(), &keypair.pubkey().into()).unwrap();
+        js_sys::Reflect::set(
+            &options,
+            &"nonce".into(),
+            &js_sys::BigInt::from(nonce).into(),
+        )
+        .unwrap();
+        let prepared = wasm_prepare_order(order(), options.into()).unwrap();
+        assert_eq!(prepared.inner.nonce, nonce);
+
+        // Unsafe numbers are refused rather than rounded.
+        let unsafe_number = Some(Nonce::from(JsValue::from_f64(2f64.powi(60))));
+        assert!(signer
+            .sign(order(), unsafe_number, JsValue::UNDEFINED)
+            .is_err());
+        assert!(signer
+            .sign_faucet(Some(Nonce::from(JsValue::from_f64(-1.0))))
+            .is_err());
+    }
+
+    #[wasm_bindgen_test]
+    fn test_peek_and_reset_nonce() {
+        let keypair = WasmKeypair::new().unwrap();
+        let mut signer = WasmSigner::with_nonce_manager(&keypair, "counter").unwrap();
+        assert_eq!(signer.peek_next_nonce().unwrap().as_f64(), Some(0.0));
+        signer.sign_faucet(None).unwrap();
+        assert_eq!(signer.peek_next_nonce().unwrap().as_f64(), Some(1.0));
+        signer.reset_nonce();
+        assert_eq!(signer.peek_next_nonce().unwrap().as_f64(), Some(0.0));
+        assert!(WasmSigner::with_nonce_manager(&keypair, "random").is_ok());
+    }
+
+    #[wasm_bindgen_test]
+    fn test_signer_from_mnemonic() {
+        let abandon = format!("{}about", "abandon ".repeat(11));
+        let signer = WasmSigner::from_mnemonic(&abandon, "m/44'/501'/0'/0'", None).unwrap();
+        assert_eq!(
+            signer.pubkey(),
+            "HAgk14JpMQLgt6rVgv7cBQFJWFto5Dqxi472uT3DKpqk"
+        );
+        let counter =
+            WasmSigner::from_mnemonic(&abandon, "m/44'/501'/0'/0'", Some("counter".into()))
+                .unwrap();
+        assert_eq!(counter.pubkey(), signer.pubkey());
+        assert_eq!(counter.peek_next_nonce().unwrap().as_f64(), Some(0.0));
+        assert!(WasmSigner::from_mnemonic(&abandon, "m/44'/501'/0'/0'", Some("x".into())).is_err());
+        assert!(WasmSigner::from_mnemonic(&abandon, "m/44'/501'/0'/0", None).is_err());
+    }
+
+    #[wasm_bindgen_test]
+    fn test_notify_reconnect() {
+        let keypair = WasmKeypair::new().unwrap();
+        let signer = WasmSigner::with_nonce_manager(&keypair, "counter").unwrap();
+        let policy = js_sys::JSON::parse(r#"{"bumpNonceFloor":true}"#).unwrap();
+        let report = signer.notify_reconnect(policy).unwrap();
+        let floor = js_sys::Reflect::get(&report, &"nonceFloor".into()).unwrap();
+        assert!(!floor.is_undefined() && !floor.is_null());
+        assert_ne!(signer.peek_next_nonce().unwrap().as_f64(), Some(0.0));
+
+        let usage = signer.usage().unwrap();
+        let reconnects = js_sys::Reflect::get(&usage, &"reconnects".into()).unwrap();
+        assert_eq!(reconnects.as_f64(), Some(1.0));
+    }
+
+    #[wasm_bindgen_test]
+    fn test_secret_census_records_reasons() {
+        let census = |fingerprint: &str| {
+            let d = diagnostics().unwrap();
+            let entries =
+                js_sys::Array::from(&js_sys::Reflect::get(&d, &"secretCensus".into()).unwrap());
+            entries.iter().find(|e| {
+                js_sys::Reflect::get(e, &"fingerprint".into())
+                    .unwrap()
+                    .as_string()
+                    .as_deref()
+                    == Some(fingerprint)
+            })
+        };
+        let copies = |entry: &JsValue, reason: &str| {
+            let reasons = js_sys::Reflect::get(entry, &"reasons".into()).unwrap();
+            js_sys::Reflect::get(&reasons, &reason.into())
+                .unwrap()
+                .as_f64()
+        };
+
+        let keypair = WasmKeypair::new().unwrap();
+        let fingerprint = keypair.fingerprint();
+        let signer = WasmSigner::new(&keypair);
+        let entry = census(&fingerprint).unwrap();
+        assert_eq!(copies(&entry, "origin"), Some(1.0));
+        assert_eq!(copies(&entry, "WasmSigner::new"), Some(1.0));
+
+        drop(signer);
+        let entry = census(&fingerprint).unwrap();
+        assert_eq!(copies(&entry, "WasmSigner::new"), None);
+        drop(keypair);
+        assert!(census(&fingerprint).is_none());
+    }
+
+    #[wasm_bindgen_test]
+    fn test_diagnostics_track_live_objects() {
+        let parse = |json: &str| js_sys::JSON::parse(json).unwrap();
+        let live = || {
+            let d = diagnostics().unwrap();
+            let get = |key: &str| {
+                js_sys::Reflect::get(&d, &key.into())
+                    .unwrap()
+                    .as_f64()
+                    .unwrap()
+            };
+            [
+                get("liveKeypairs"),
+                get("liveSigners"),
+                get("livePreparedMessages"),
+                get("retainedBytes"),
+            ]
+        };
+        set_diagnostics_enabled(true);
+        let baseline = live();
+        let order =
+            || parse(r#"{"type":"order","symbol":"BTC-USD","isBuy":true,"price":100,"size":1}"#);
+
+        for i in 0..10 {
+            let keypair = WasmKeypair::new().unwrap();
+            let options = parse(&format!(
+                r#"{{"account":"{}","nonce":{}}}"#,
+                keypair.pubkey(),
+                i + 1
+            ));
+            let _signer = WasmSigner::new(&keypair);
+            let _prepared = wasm_prepare_order(order(), options).unwrap();
+        }
+        assert_eq!(live(), baseline);
+
+        let account = WasmKeypair::new().unwrap().pubkey();
+        let leaked: Vec<_> = (0..3)
+            .map(|i| {
+                let options = parse(&format!(r#"{{"account":"{account}","nonce":{i}}}"#));
+                wasm_prepare_order(order(), options).unwrap()
+            })
+            .collect();
+        let bytes: usize = leaked.iter().map(|p| p.inner.message_bytes.len()).sum();
+        let held = live();
+        assert_eq!(held[2], baseline[2] + 3.0);
+        assert_eq!(held[3], baseline[3] + bytes as f64);
+        drop(leaked);
+        assert_eq!(live()[2], baseline[2]);
+    }
+
+    #[wasm_bindgen_test]
+    fn test_strict_input_suggests_field() {
+        let parse = |json: &str| js_sys::JSON::parse(json).unwrap();
+        let order = || {
+            parse(
+                r#"{"type":"order","symbol":"BTC-USD","isBuy":true,"price":100,"size":1,"reduceOnIy":true,"orderType":{"type":"limit","tif":"GTC"}}"#,
+            )
+        };
+        let message = |err: JsError| {
+            let err = JsValue::from(err);
+            js_sys::Reflect::get(&err, &"message".into())
+                .unwrap()
+                .as_string()
+                .unwrap()
+        };
+
+        let mut signer = WasmSigner::new(&WasmKeypair::new().unwrap());
+        assert!(signer
+            .sign(order(), js_nonce(1.0), JsValue::UNDEFINED)
+            .is_ok());
+        signer.set_strict_input(true);
+        let err = message(
+            signer
+                .sign(order(), js_nonce(1.0), JsValue::UNDEFINED)
+                .unwrap_err(),
+        );
+        assert_eq!(
+            err,
+            "UnknownFields: unknown fields: reduceOnIy (did you mean reduceOnly?)"
+        );
+
+        let account = WasmKeypair::new().unwrap().pubkey();
+        let options = |strict: bool| {
+            parse(&format!(
+                r#"{{"account":"{account}","nonce":1,"strict":{strict}}}"#
+            ))
+        };
+        assert!(wasm_prepare_order(order(), options(false)).is_ok());
+        let err = message(wasm_prepare_order(order(), options(true)).err().unwrap());
+        assert!(err.contains("did you mean reduceOnly?"));
+    }
+
+    #[wasm_bindgen_test]
+    fn test_errors_name_their_variant() {
+        let parse = |json: &str| js_sys::JSON::parse(json).unwrap();
+        let message = |err: JsError| {
+            js_sys::Reflect::get(&JsValue::from(err), &"message".into())
+                .unwrap()
+                .as_string()
+                .unwrap()
+        };
+        let order =
+            || parse(r#"{"type":"order","symbol":"BTC-USD","isBuy":true,"price":100,"size":1}"#);
+
+        let err = message(WasmKeypair::from_base58("0OIl").err().unwrap());
+        assert!(err.starts_with("InvalidBase58: invalid base58"), "{err}");
+
+        let signer = WasmSigner::new(&WasmKeypair::new().unwrap());
+        let orders = js_sys::Array::of2(&order(), &order());
+        let max = Some(Nonce::from(JsValue::from(js_sys::BigInt::from(u64::MAX))));
+        let err = message(
+            signer
+                .sign_all(orders.into(), max, JsValue::UNDEFINED)
+                .unwrap_err(),
+        );
+        assert!(err.starts_with("NonceOverflow: "), "{err}");
+
+        let mut signer = WasmSigner::new(&WasmKeypair::new().unwrap());
+        let no_side = parse(r#"{"type":"order","symbol":"BTC-USD","price":100,"size":1}"#);
+        let err = message(
+            signer
+                .sign(no_side, js_nonce(1.0), JsValue::UNDEFINED)
+                .unwrap_err(),
+        );
+        assert_eq!(
+            err,
+            "InvalidOrderField: invalid order: order.isBuy is required"
+        );
+    }
+
+    #[wasm_bindgen_test]
+    fn test_sign_deadline_option() {
+        let parse = |json: &str| js_sys::JSON::parse(json).unwrap();
+        let order =
+            || parse(r#"{"type":"order","symbol":"BTC-USD","isBuy":true,"price":100,"size":1}"#);
+        let mut signer = WasmSigner::new(&WasmKeypair::new().unwrap());
+        let now = signer.inner.now_ms();
+        let late = parse(&format!(r#"{{"deadline":{}}}"#, now - 1_000));
+        assert!(signer.sign(order(), js_nonce(1.0), late).is_err());
+        let budget = parse(&format!(r#"{{"deadline":{}}}"#, now + 60_000));
+        assert!(signer.sign(order(), js_nonce(2.0), budget).is_ok());
+        // Seconds are refused before anything is signed or counted.
+        let seconds = parse(&format!(r#"{{"deadline":{}}}"#, now / 1000 + 60));
+        assert!(signer.sign(order(), js_nonce(3.0), seconds).is_err());
+        let usage = signer.usage().unwrap();
+        let count = |key: &str| js_sys::Reflect::get(&usage, &key.into()).unwrap().as_f64();
+        assert_eq!(
+            (count("signed"), count("deadlineRejections")),
+            (Some(1.0), Some(1.0))
+        );
+    }
+
+    #[wasm_bindgen_test]
+    fn test_unique_client_ids() {
+        let parse = |json: &str| js_sys::JSON::parse(json).unwrap();
+        let cid = Hash::from_bytes([3; 32]).to_base58();
+        let order = || {
+            parse(&format!(
+                r#"{{"type":"order","symbol":"BTC-USD","isBuy":true,"price":100,"size":1,"clientId":"{}"}}"#,
+                cid
+            ))
+        };
+        let mut signer = WasmSigner::new(&WasmKeypair::new().unwrap());
+        signer.set_unique_client_ids(true);
+        assert!(signer
+            .sign(order(), js_nonce(1.0), JsValue::UNDEFINED)
+            .is_ok());
+        assert!(signer
+            .sign(order(), js_nonce(2.0), JsValue::UNDEFINED)
+            .is_err());
+        let reuse = parse(r#"{"allowClientIdReuse":true}"#);
+        assert!(signer.sign(order(), js_nonce(3.0), reuse).is_ok());
+        assert!(signer.client_id_order(&cid).unwrap().is_some());
+    }
+
+    #[wasm_bindgen_test]
+    fn test_zero_and_empty_client_ids_refused() {
+        let parse = |json: &str| js_sys::JSON::parse(json).unwrap();
+        let order = |cid: &str| {
+            parse(&format!(
+                r#"{{"type":"order","symbol":"BTC-USD","isBuy":true,"price":100,"size":1,"clientId":"{}"}}"#,
+                cid
+            ))
+        };
+        let zero = Hash::from_bytes([0; 32]).to_base58();
+        let mut signer = WasmSigner::new(&WasmKeypair::new().unwrap());
+        assert!(signer
+            .sign(order(""), js_nonce(1.0), JsValue::UNDEFINED)
+            .is_err());
+        assert!(signer
+            .sign(order(&zero), js_nonce(1.0), JsValue::UNDEFINED)
+            .is_err());
+        signer.set_allow_zero_client_id(true);
+        assert!(signer
+            .sign(order(&zero), js_nonce(1.0), JsValue::UNDEFINED)
+            .is_ok());
+    }
+
+    #[wasm_bindgen_test]
+    fn test_sign_bytes_round_trip() {
+        let signer = WasmSigner::new(&WasmKeypair::new().unwrap());
+        let pubkey = signer.pubkey();
+        let sig = signer.sign_bytes(b"hello").unwrap();
+        assert_eq!(sig.len(), 64);
+        assert!(WasmSigner::verify_signature(&pubkey, b"hello", &sig).unwrap());
+        assert!(!WasmSigner::verify_signature(&pubkey, b"other", &sig).unwrap());
+        assert!(WasmSigner::verify_signature(&pubkey, b"hello", &sig[..63]).is_err());
+    }
+
+    #[wasm_bindgen_test]
+    fn test_verify_transaction() {
+        let mut signer = WasmSigner::new(&WasmKeypair::new().unwrap());
+        let order = js_sys::JSON::parse(
+            r#"{"type":"order","symbol":"BTC-USD","isBuy":true,"price":100,"size":1}"#,
+        )
+        .unwrap();
+        let tx = signer
+            .sign(order, js_nonce(1.0), JsValue::UNDEFINED)
+            .unwrap();
+        assert!(verify_transaction(tx.clone()).unwrap());
+        js_sys::Reflect::set(&tx, &"orderId".into(), &Hash::random().to_base58().into()).unwrap();
+        assert!(verify_transaction(tx).is_err());
+    }
+
+    #[wasm_bindgen_test]
+    fn test_sign_modify_order() {
+        let mut signer = WasmSigner::new(&WasmKeypair::new().unwrap());
+        let order_id = Hash::random().to_base58();
+        let tx = signer
+            .sign_modify_order(&order_id, "BTC-USD", 0.25, js_nonce(1.0))
+            .unwrap();
+        assert!(verify_transaction(tx).unwrap());
+        assert!(signer
+            .sign_modify_order("nope", "BTC-USD", 0.25, js_nonce(2.0))
+            .is_err());
+    }
+
+    #[wasm_bindgen_test]
+    fn test_cancel_by_client_id() {
+        let mut signer = WasmSigner::new(&WasmKeypair::new().unwrap());
+        signer.set_unique_client_ids(true);
+        let cid = Hash::random().to_base58();
+        let order = js_sys::JSON::parse(&format!(
+            r#"{{"type":"order","symbol":"BTC-USD","isBuy":true,"price":100,"size":1,"clientId":"{cid}"}}"#
+        ))
+        .unwrap();
+        let cancel = js_sys::JSON::parse(&format!(
+            r#"{{"type":"cancelByClientId","symbol":"BTC-USD","clientId":"{cid}"}}"#
+        ))
+        .unwrap();
+        let get = |v: &JsValue, k: &str| js_sys::Reflect::get(v, &k.into()).unwrap();
+        let placed = signer
+            .sign(order, js_nonce(1.0), JsValue::UNDEFINED)
+            .unwrap();
+        let tx = signer
+            .sign(cancel.clone(), js_nonce(2.0), JsValue::UNDEFINED)
+            .unwrap();
+        let actions = js_sys::Array::from(&get(&tx, "actions"));
+        let cx = get(&actions.get(0), "cx");
+        assert_eq!(
+            get(&cx, "oid").as_string(),
+            get(&placed, "orderId").as_string()
+        );
+        assert!(signer
+            .sign(cancel, js_nonce(3.0), JsValue::UNDEFINED)
+            .is_err());
+
+        let neither = js_sys::JSON::parse(r#"{"type":"cancel","symbol":"BTC-USD"}"#).unwrap();
+        assert!(signer
+            .sign(neither, js_nonce(4.0), JsValue::UNDEFINED)
+            .is_err());
+    }
+
+    #[wasm_bindgen_test]
+    fn test_deterministic_seed() {
+        let keypair = WasmKeypair::new().unwrap();
+        let mut a = WasmSigner::new(&keypair);
+        let mut b = WasmSigner::new(&keypair);
+        a.set_deterministic_seed(Some(9.0));
+        b.set_deterministic_seed(Some(9.0));
+        assert_eq!(a.random_client_id(), b.random_client_id());
+        a.set_deterministic_seed(None);
+        assert_ne!(a.random_client_id(), b.random_client_id());
+    }
+
+    #[wasm_bindgen_test]
+    fn test_plan_batch() {
+        let signer = WasmSigner::new(&WasmKeypair::new().unwrap());
+        let orders = js_sys::JSON::parse(
+            r#"[{"type":"order","symbol":"BTC-USD","isBuy":true,"price":100.004,"size":2},
+                {"type":"order","symbol":"BTC-USD","isBuy":true,"price":0,"size":1}]"#,
+        )
+        .unwrap();
+        let specs =
+            js_sys::JSON::parse(r#"{"BTC-USD":{"priceDecimals":2,"sizeDecimals":3}}"#).unwrap();
+        let plan = signer.plan_batch(orders, js_nonce(10.0), specs).unwrap();
+        let get = |v: &JsValue, k: &str| js_sys::Reflect::get(v, &k.into()).unwrap();
+        assert_eq!(get(&plan, "estimatedGroups").as_f64(), Some(1.0));
+        let rejections = js_sys::Array::from(&get(&plan, "rejections"));
+        assert_eq!(get(&rejections.get(0), "index").as_f64(), Some(1.0));
+        let adjustments = js_sys::Array::from(&get(&plan, "adjustments"));
+        assert_eq!(get(&adjustments.get(0), "to").as_f64(), Some(100.0));
+        assert_eq!(get(&get(&plan, "nonceRange"), "end").as_f64(), Some(12.0));
+    }
+
+    #[cfg(feature = "full")]
+    #[wasm_bindgen_test]
+    fn test_peek_order_ids() {
+        let signer = WasmSigner::new(&WasmKeypair::new().unwrap());
+        let orders = || {
+            js_sys::JSON::parse(
+                r#"[{"type":"order","symbol":"BTC-USD","isBuy":true,"price":100,"size":1},
+                    {"type":"cancelAll","symbols":["BTC-USD"]}]"#,
+            )
+            .unwrap()
+        };
+        let get = |v: &JsValue, k: &str| js_sys::Reflect::get(v, &k.into()).unwrap();
+        let peeked = signer.peek_order_ids(orders(), None).unwrap();
+        let ids = js_sys::Array::from(&get(&peeked, "orderIds"));
+        assert!(ids.get(1).is_null());
+        let reservation = get(&peeked, "reservation");
+
+        let txs = js_sys::Array::from(
+            &signer
+                .sign_all_reserved(orders(), reservation.clone())
+                .unwrap(),
+        );
+        assert_eq!(
+            get(&txs.get(0), "orderId").as_string(),
+            ids.get(0).as_string()
+        );
+        assert!(signer.sign_all_reserved(orders(), reservation).is_err());
+    }
+
+    #[wasm_bindgen_test]
+    fn test_capabilities_signed() {
+        let mut signer = WasmSigner::new(&WasmKeypair::new().unwrap());
+        signer.set_max_open_orders_per_symbol(Some(4));
+        signer.set_network(Some("testnet".to_string()));
+        let caps = signer.capabilities().unwrap();
+        let policy = js_sys::Reflect::get(&caps, &"policySummary".into()).unwrap();
+        assert_eq!(
+            js_sys::Reflect::get(&policy, &"maxOpenOrdersPerSymbol".into())
+                .unwrap()
+                .as_f64(),
+            Some(4.0)
+        );
+        let signed = signer.capabilities_signed().unwrap();
+        assert!(verify_capabilities(signed.clone()).unwrap());
+        js_sys::Reflect::set(&signed, &"payload".into(), &"{}".into()).unwrap();
+        assert!(verify_capabilities(signed).is_err());
+    }
+
+    #[cfg(feature = "full")]
+    #[wasm_bindgen_test]
+    fn test_diff_orders() {
+        let parse = |json: &str| js_sys::JSON::parse(json).unwrap();
+        let order = |is_buy: bool, price: f64| {
+            parse(&format!(
+                r#"{{"type":"order","symbol":"ETH-USD","isBuy":{is_buy},"price":{price},"size":1,"orderType":{{"type":"limit","tif":"GTC"}}}}"#
+            ))
+        };
+        let spec = parse(r#"{"priceDecimals":2,"sizeDecimals":2,"tickUnits":5}"#);
+        let diff = diff_orders(order(true, 2000.0), order(false, 2000.25), spec).unwrap();
+        let get = |key: &str| js_sys::Reflect::get(&diff, &key.into()).unwrap();
+        assert_eq!(get("priceDeltaTicks").as_f64(), Some(5.0));
+        assert_eq!(get("sideChanged").as_bool(), Some(true));
+        assert_eq!(get("tifChanged").as_bool(), Some(false));
+
+        let diff =
+            diff_orders(order(true, 2000.0), order(true, 2000.0), JsValue::UNDEFINED).unwrap();
+        assert!(js_sys::Reflect::get(&diff, &"priceDeltaTicks".into())
+            .unwrap()
+            .is_null());
+    }
+
+    #[cfg(feature = "full")]
+    #[wasm_bindgen_test]
+    fn test_sign_flatten() {
+        let signer = WasmSigner::new(&WasmKeypair::new().unwrap());
+        let parse = |json: &str| js_sys::JSON::parse(json).unwrap();
+        let specs = || {
+            parse(r#"{"ETH-USD":{"spec":{"priceDecimals":1,"sizeDecimals":2},"maxOrderSize":1}}"#)
+        };
+        let flatten = |size: f64| {
+            let position = parse(&format!(
+                r#"{{"symbol":"ETH-USD","size":{size},"markPrice":2000}}"#
+            ));
+            let signed = signer
+                .sign_flatten(
+                    position,
+                    parse(r#"{"type":"market"}"#),
+                    specs(),
+                    js_nonce(1.0),
+                )
+                .unwrap();
+            serde_wasm_bindgen::from_value::<Vec<SignedTransaction>>(signed).unwrap()
+        };
+
+        assert!(flatten(0.0).is_empty());
+        let txs = flatten(-2.5);
+        assert_eq!(txs.len(), 3);
+        let sizes: Vec<f64> = txs
+            .iter()
+            .map(|tx| tx.actions_json().unwrap()[0]["m"]["sz"].as_f64().unwrap())
+            .collect();
+        assert_eq!(sizes, [1.0, 1.0, 0.5]);
+        assert!(txs
+            .iter()
+            .all(|tx| tx.actions_json().unwrap()[0]["m"]["b"] == true));
+    }
+
+    #[cfg(feature = "full")]
+    #[wasm_bindgen_test]
+    fn test_remote_session_round_trip() {
+        let secret = [5u8; 32];
+        let keypair = WasmKeypair::new().unwrap();
+        let mut app = WasmSessionInitiator::new(&secret, None).unwrap();
+        let mut wallet = WasmSessionResponder::from_keypair(&secret, &keypair).unwrap();
+
+        let order = Order::limit("BTC-USD", true, 100000.0, 0.1, TimeInForce::Gtc);
+        let prepared =
+            prepare_message(order.into(), &keypair.inner.pubkey(), None, Some(1)).unwrap();
+        let request = app.request(&WasmPreparedMessage::new(prepared)).unwrap();
+        let response = wallet.handle_request(&request).unwrap();
+        assert_eq!(wallet.session_id().unwrap(), app.session_id());
+
+        let signed = app.handle_response(&response).unwrap();
+        let signed: SignedTransaction = serde_wasm_bindgen::from_value(signed).unwrap();
+        assert_eq!(signed.signer, keypair.inner.pubkey().to_base58());
+        assert_eq!(app.pending_count(), 0);
+        assert!(wallet.handle_request(&request).is_err());
+    }
 }
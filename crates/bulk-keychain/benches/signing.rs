@@ -1,12 +1,52 @@
 //! Benchmarks for signing performance.
 
-use bulk_keychain::{Keypair, Order, OrderItem, Signer, TimeInForce};
-use criterion::{black_box, criterion_group, criterion_main, Criterion, Throughput};
+use bulk_keychain::{
+    prepare_group, FramedBatchReader, FramedBatchWriter, Hash, Keypair, Order, OrderIdHasher,
+    OrderItem, Parallelism, SignedTransaction, Signer, TimeInForce,
+};
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
 use sha2::{Digest, Sha256};
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering};
 
 const BATCH_SIZE: usize = 256;
 const GROUP_SIZE: usize = 3;
+const LARGE_GROUP_SIZE: usize = 500;
 const SCALE_1E8: u64 = 100_000_000;
+const HASH_ITERS: usize = 1_000_000;
+
+/// Tracks live and peak heap bytes so benches can report peak allocation.
+struct CountingAlloc;
+
+static LIVE_BYTES: AtomicUsize = AtomicUsize::new(0);
+static PEAK_BYTES: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc(layout);
+        if !ptr.is_null() {
+            let live = LIVE_BYTES.fetch_add(layout.size(), Ordering::Relaxed) + layout.size();
+            PEAK_BYTES.fetch_max(live, Ordering::Relaxed);
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout);
+        LIVE_BYTES.fetch_sub(layout.size(), Ordering::Relaxed);
+    }
+}
+
+#[global_allocator]
+static ALLOC: CountingAlloc = CountingAlloc;
+
+/// Heap bytes allocated at the peak of `f`, above what was live before it.
+fn peak_allocation<T>(f: impl FnOnce() -> T) -> usize {
+    let base = LIVE_BYTES.load(Ordering::Relaxed);
+    PEAK_BYTES.store(base, Ordering::Relaxed);
+    black_box(f());
+    PEAK_BYTES.load(Ordering::Relaxed) - base
+}
 
 #[inline]
 fn make_order(i: usize) -> OrderItem {
@@ -80,6 +120,33 @@ fn bench_sign_all(c: &mut Criterion) {
     group.finish();
 }
 
+/// `sign_all` pinned serial vs on every core, for batches of 10, 100 and 1000.
+fn bench_sign_all_serial_vs_parallel(c: &mut Criterion) {
+    let keypair = Keypair::generate();
+    let serial = Signer::new(keypair.clone()).with_parallelism(Parallelism::Serial);
+    let parallel = Signer::new(keypair).with_parallelism(Parallelism::Auto);
+
+    let mut group = c.benchmark_group("sign_all_serial_vs_parallel");
+    for size in [10, 100, 1_000] {
+        let orders: Vec<OrderItem> = (0..size).map(make_order).collect();
+        let expected = serial.sign_all(orders.clone(), Some(1)).unwrap();
+        let got = parallel.sign_all(orders.clone(), Some(1)).unwrap();
+        assert_eq!(
+            serde_json::to_vec(&got).unwrap(),
+            serde_json::to_vec(&expected).unwrap(),
+            "parallel output differs at {size} orders"
+        );
+
+        group.throughput(Throughput::Elements(size as u64));
+        for (name, signer) in [("serial", &serial), ("parallel", &parallel)] {
+            group.bench_with_input(BenchmarkId::new(name, size), &orders, |b, orders| {
+                b.iter(|| black_box(signer.sign_all(black_box(orders.clone()), Some(1)).unwrap()))
+            });
+        }
+    }
+    group.finish();
+}
+
 fn bench_sign_group(c: &mut Criterion) {
     let bracket: Vec<OrderItem> = vec![
         Order::limit("BTC-USD", true, 100000.0, 0.1, TimeInForce::Gtc).into(),
@@ -114,6 +181,60 @@ fn bench_sign_group(c: &mut Criterion) {
     group.finish();
 }
 
+/// Hashing a 500-leg group payload an encoder hands over leg by leg: one-shot
+/// hashing has to collect the legs into one buffer first, `OrderIdHasher`
+/// takes them as they come.
+fn bench_large_group_hashing(c: &mut Criterion) {
+    let legs: Vec<OrderItem> = (0..LARGE_GROUP_SIZE).map(make_order).collect();
+    let account = Keypair::generate().pubkey();
+    let payload = prepare_group(legs.clone(), &account, None, Some(1))
+        .unwrap()
+        .message_bytes;
+    let pieces: Vec<&[u8]> = payload
+        .chunks(payload.len().div_ceil(LARGE_GROUP_SIZE))
+        .collect();
+
+    let one_shot = || {
+        let mut buffer = Vec::new();
+        for piece in &pieces {
+            buffer.extend_from_slice(piece);
+        }
+        Hash::from_wincode_bytes(&buffer)
+    };
+    let streaming = || {
+        let mut hasher = OrderIdHasher::new();
+        for piece in &pieces {
+            hasher.update(piece);
+        }
+        hasher.finalize()
+    };
+    assert_eq!(one_shot(), streaming());
+
+    let mut signer = Signer::new(Keypair::generate()).with_batch_order_ids();
+    eprintln!(
+        "{LARGE_GROUP_SIZE}-leg group ({} bytes) peak allocation: one-shot {} B, streaming {} B; \
+         sign_group with batch order IDs {} B",
+        payload.len(),
+        peak_allocation(one_shot),
+        peak_allocation(streaming),
+        peak_allocation(|| signer.sign_group(legs.clone(), Some(1)).unwrap()),
+    );
+
+    let mut group = c.benchmark_group("large_group_hashing");
+    group.throughput(Throughput::Bytes(payload.len() as u64));
+    group.bench_function("one_shot", |b| b.iter(|| black_box(one_shot())));
+    group.bench_function("streaming", |b| b.iter(|| black_box(streaming())));
+    group.finish();
+
+    let mut group = c.benchmark_group("sign_large_group");
+    group.sample_size(20);
+    group.throughput(Throughput::Elements(LARGE_GROUP_SIZE as u64));
+    group.bench_function("with_batch_order_ids", |b| {
+        b.iter(|| black_box(signer.sign_group(black_box(legs.clone()), Some(1)).unwrap()))
+    });
+    group.finish();
+}
+
 #[inline]
 fn parse_scaled_1e8(value: &str) -> u64 {
     let bytes = value.as_bytes();
@@ -275,11 +396,90 @@ fn bench_oid_john_vs_junbug(c: &mut Criterion) {
     group.finish();
 }
 
+fn bench_hash_construction(c: &mut Criterion) {
+    let bytes: [u8; 32] = Sha256::digest(b"bulk-keychain").into();
+    let encoded = Hash::from_bytes_fixed(bytes).to_base58();
+
+    let mut group = c.benchmark_group("hash_construction");
+    group.sample_size(10);
+    group.throughput(Throughput::Elements(HASH_ITERS as u64));
+
+    group.bench_function("from_base58", |b| {
+        b.iter(|| {
+            for _ in 0..HASH_ITERS {
+                black_box(Hash::from_base58(black_box(&encoded)).unwrap());
+            }
+        })
+    });
+
+    group.bench_function("from_bytes_fixed", |b| {
+        b.iter(|| {
+            for _ in 0..HASH_ITERS {
+                black_box(Hash::from_bytes_fixed(black_box(bytes)));
+            }
+        })
+    });
+
+    group.finish();
+}
+
+fn bench_framed_vs_json(c: &mut Criterion) {
+    let orders: Vec<OrderItem> = (0..BATCH_SIZE).map(make_order).collect();
+    let txs = Signer::new(Keypair::generate())
+        .sign_all(orders, Some(1000000))
+        .unwrap();
+
+    let mut writer = FramedBatchWriter::new(Vec::new());
+    for tx in &txs {
+        writer.write(tx).unwrap();
+    }
+    let framed = writer.into_inner().unwrap();
+    let json = serde_json::to_vec(&txs).unwrap();
+
+    let mut group = c.benchmark_group("encode_batch");
+    group.throughput(Throughput::Elements(BATCH_SIZE as u64));
+    group.bench_function("framed", |b| {
+        b.iter(|| {
+            let mut writer = FramedBatchWriter::new(Vec::with_capacity(framed.len()));
+            for tx in black_box(&txs) {
+                writer.write(tx).unwrap();
+            }
+            black_box(writer.into_inner().unwrap())
+        })
+    });
+    group.bench_function("json", |b| {
+        b.iter(|| black_box(serde_json::to_vec(black_box(&txs)).unwrap()))
+    });
+    group.finish();
+
+    let mut group = c.benchmark_group("decode_batch");
+    group.throughput(Throughput::Elements(BATCH_SIZE as u64));
+    group.bench_function("framed", |b| {
+        b.iter(|| {
+            let txs = FramedBatchReader::new(black_box(framed.as_slice()))
+                .collect::<Result<Vec<_>, _>>()
+                .unwrap();
+            black_box(txs)
+        })
+    });
+    group.bench_function("json", |b| {
+        b.iter(|| {
+            let txs: Vec<SignedTransaction> = serde_json::from_slice(black_box(&json)).unwrap();
+            black_box(txs)
+        })
+    });
+    group.finish();
+}
+
 criterion_group!(
     benches,
     bench_sign_single,
     bench_sign_all,
+    bench_sign_all_serial_vs_parallel,
     bench_sign_group,
-    bench_oid_john_vs_junbug
+    bench_large_group_hashing,
+    bench_oid_john_vs_junbug,
+    bench_hash_construction,
+    bench_framed_vs_json
 );
 criterion_main!(benches);
@@ -0,0 +1,53 @@
+//! Embeds the metadata reported by `bulk_keychain::build_info()`.
+
+use std::path::Path;
+use std::process::Command;
+
+fn git(args: &[&str]) -> Option<String> {
+    let out = Command::new("git").args(args).output().ok()?;
+    if !out.status.success() {
+        return None;
+    }
+    String::from_utf8(out.stdout)
+        .ok()
+        .map(|s| s.trim().to_string())
+}
+
+fn main() {
+    println!("cargo:rerun-if-env-changed=BULK_KEYCHAIN_GIT_SHA");
+    let sha = std::env::var("BULK_KEYCHAIN_GIT_SHA")
+        .ok()
+        .or_else(|| git(&["rev-parse", "HEAD"]))
+        .unwrap_or_else(|| "unknown".to_string());
+
+    // Rebuild when HEAD moves, but only when building from a checkout.
+    if let Some(git_dir) = git(&["rev-parse", "--absolute-git-dir"]) {
+        let git_dir = Path::new(&git_dir);
+        println!("cargo:rerun-if-changed={}", git_dir.join("HEAD").display());
+        if let Some(head_ref) = git(&["symbolic-ref", "-q", "HEAD"]) {
+            let ref_path = git_dir.join(head_ref);
+            if ref_path.exists() {
+                println!("cargo:rerun-if-changed={}", ref_path.display());
+            }
+        }
+    }
+
+    let mut features: Vec<String> = std::env::vars()
+        .filter_map(|(key, _)| {
+            key.strip_prefix("CARGO_FEATURE_")
+                .map(|f| f.to_lowercase().replace('_', "-"))
+        })
+        .filter(|f| f != "default")
+        .collect();
+    features.sort();
+
+    println!("cargo:rustc-env=BULK_KEYCHAIN_GIT_SHA={}", sha);
+    println!(
+        "cargo:rustc-env=BULK_KEYCHAIN_FEATURES={}",
+        features.join(",")
+    );
+    println!(
+        "cargo:rustc-env=BULK_KEYCHAIN_PROFILE={}",
+        std::env::var("PROFILE").unwrap_or_default()
+    );
+}
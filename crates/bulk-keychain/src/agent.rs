@@ -0,0 +1,135 @@
+//! Deterministic agent wallet derivation.
+//!
+//! Agent keys are derived with HKDF-SHA256 from a root seed, keyed by the trading
+//! account and an epoch (e.g. a week number), so a rotated agent never needs to
+//! be stored: whoever holds the root seed can re-derive it.
+
+use crate::prepare::{prepare_agent_wallet, PreparedMessage};
+use crate::types::Pubkey;
+use crate::{Keypair, Result};
+use hkdf::Hkdf;
+use sha2::Sha256;
+use zeroize::Zeroizing;
+
+/// HKDF salt; also separates agent keys from any other use of the root seed.
+const DOMAIN: &[u8] = b"bulk-keychain:agent-wallet:v1";
+
+/// Derives per-account, per-epoch agent keypairs from a root seed.
+pub struct AgentDeriver {
+    root_seed: Zeroizing<[u8; 32]>,
+}
+
+impl AgentDeriver {
+    /// Create a deriver. The seed is wiped from memory when the deriver is dropped.
+    pub fn new(root_seed: [u8; 32]) -> Self {
+        Self {
+            root_seed: Zeroizing::new(root_seed),
+        }
+    }
+
+    /// Derive the agent keypair for `account` at `epoch`.
+    pub fn derive(&self, account: &Pubkey, epoch: u64) -> Keypair {
+        let mut info = [0u8; 40];
+        info[..32].copy_from_slice(account.as_bytes());
+        info[32..].copy_from_slice(&epoch.to_le_bytes());
+
+        let mut secret = Zeroizing::new([0u8; 32]);
+        Hkdf::<Sha256>::new(Some(DOMAIN), self.root_seed.as_slice())
+            .expand(&info, secret.as_mut_slice())
+            .expect("32 bytes is a valid HKDF-SHA256 output length");
+        Keypair::from_secret_key(secret.as_slice()).expect("secret is 32 bytes")
+    }
+
+    /// Public key of the agent for `account` at `epoch`.
+    pub fn pubkey(&self, account: &Pubkey, epoch: u64) -> Pubkey {
+        self.derive(account, epoch).pubkey()
+    }
+
+    /// Public key of the agent for the epoch after `epoch`, for pre-authorization.
+    pub fn next_pubkey(&self, account: &Pubkey, epoch: u64) -> Pubkey {
+        self.pubkey(account, epoch + 1)
+    }
+
+    /// Prepare the agent wallet authorization for `epoch`, to be signed by `account`.
+    ///
+    /// ```rust
+    /// use bulk_keychain::{AgentDeriver, Keypair, Order, Signer};
+    ///
+    /// let account = Keypair::generate();
+    /// let deriver = AgentDeriver::new([7u8; 32]);
+    ///
+    /// // The account signs the authorization for this epoch's agent...
+    /// let auth = deriver.prepare_authorization(&account.pubkey(), 0, Some(1)).unwrap();
+    /// assert!(auth.sign_with_keypair(&account).unwrap().verify().unwrap());
+    ///
+    /// // ...which then trades for the account.
+    /// let agent = deriver.derive(&account.pubkey(), 0);
+    /// let mut signer = Signer::new(agent).with_account(account.pubkey());
+    /// let tx = signer.sign(Order::market("BTC-USD", true, 0.1).into(), Some(2)).unwrap();
+    /// assert_eq!(tx.account_pubkey().unwrap(), account.pubkey());
+    /// assert_eq!(tx.signer_pubkey().unwrap(), deriver.pubkey(&account.pubkey(), 0));
+    /// ```
+    pub fn prepare_authorization(
+        &self,
+        account: &Pubkey,
+        epoch: u64,
+        nonce: Option<u64>,
+    ) -> Result<PreparedMessage> {
+        prepare_agent_wallet(&self.pubkey(account, epoch), false, account, None, nonce)
+    }
+}
+
+impl std::fmt::Debug for AgentDeriver {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AgentDeriver").finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Order, Signer, TimeInForce};
+
+    #[test]
+    fn test_derivation_is_deterministic_and_separated() {
+        let deriver = AgentDeriver::new([9u8; 32]);
+        let a = Keypair::generate().pubkey();
+        let b = Keypair::generate().pubkey();
+
+        assert_eq!(deriver.pubkey(&a, 7), deriver.pubkey(&a, 7));
+        assert_eq!(
+            deriver.pubkey(&a, 7),
+            AgentDeriver::new([9u8; 32]).pubkey(&a, 7)
+        );
+        assert_ne!(deriver.pubkey(&a, 7), deriver.pubkey(&a, 8));
+        assert_ne!(deriver.pubkey(&a, 7), deriver.pubkey(&b, 7));
+        assert_ne!(
+            deriver.pubkey(&a, 7),
+            AgentDeriver::new([10u8; 32]).pubkey(&a, 7)
+        );
+        assert_eq!(deriver.next_pubkey(&a, 7), deriver.pubkey(&a, 8));
+    }
+
+    #[test]
+    fn test_derived_agent_signs_for_account() {
+        let main = Keypair::generate();
+        let account = main.pubkey();
+        let deriver = AgentDeriver::new([3u8; 32]);
+
+        let auth = deriver
+            .prepare_authorization(&account, 1, Some(10))
+            .unwrap();
+        assert_eq!(auth.signer, account.to_base58());
+        assert_eq!(
+            auth.actions[0]["agentWalletCreation"]["a"],
+            deriver.pubkey(&account, 1).to_base58()
+        );
+
+        let mut signer = Signer::new(deriver.derive(&account, 1)).with_account(account);
+        let order = Order::limit("BTC-USD", true, 100000.0, 0.1, TimeInForce::Gtc);
+        let signed = signer.sign(order.into(), Some(11)).unwrap();
+        assert_eq!(signed.account, account.to_base58());
+        assert_eq!(signed.signer, deriver.pubkey(&account, 1).to_base58());
+        assert!(signer.verify_signed_transaction(&signed, None).unwrap());
+    }
+}
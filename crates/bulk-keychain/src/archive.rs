@@ -0,0 +1,352 @@
+//! Verifying archived transaction dumps
+//!
+//! [`verify_archive`] streams a JSONL file of [`SignedTransaction`]s and
+//! checks every signature, holding one chunk of lines in memory at a time
+//! and verifying each chunk in parallel. A signature that verifies but comes
+//! from a key outside [`ArchiveVerifyOptions::keys_allowlist`] is counted
+//! under [`ArchiveReport::unknown_signers`], not as valid.
+//!
+//! Long runs can be resumed: [`verify_archive_with_checkpoint`] hands an
+//! [`ArchiveCheckpoint`] to a sink after each chunk, and
+//! [`resume_verify_archive`] continues from one and produces the same final
+//! report as an uninterrupted run.
+//!
+//! ```rust
+//! use bulk_keychain::{verify_archive, ArchiveVerifyOptions, Keypair, Order, Signer};
+//!
+//! let signer = Signer::new(Keypair::generate());
+//! let orders = vec![Order::market("BTC-USD", true, 0.1).into(); 3];
+//! let mut archive = String::new();
+//! for tx in signer.sign_all(orders, Some(1)).unwrap() {
+//!     archive += &serde_json::to_string(&tx).unwrap();
+//!     archive.push('\n');
+//! }
+//!
+//! let report = verify_archive(archive.as_bytes(), &ArchiveVerifyOptions::default()).unwrap();
+//! assert_eq!((report.total, report.valid), (3, 3));
+//! assert!(report.invalid.is_empty());
+//! ```
+
+use crate::compat::CompatVersion;
+use crate::parallel::{run_batch, Parallelism};
+use crate::{Error, Pubkey, Result, SignedTransaction};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+use std::io::{BufRead, Seek, SeekFrom};
+
+/// Lines verified per chunk unless [`ArchiveVerifyOptions::chunk_lines`] says otherwise.
+pub const DEFAULT_ARCHIVE_CHUNK_LINES: usize = 4096;
+
+/// How [`verify_archive`] checks each line.
+#[derive(Debug, Clone)]
+pub struct ArchiveVerifyOptions {
+    /// Only these signers count as valid; `None` accepts any key
+    pub keys_allowlist: Option<HashSet<Pubkey>>,
+    /// Rebuild messages the way this version did; `None` tries every
+    /// registered version (see [`SignedTransaction::verify_any_version`])
+    pub compat_version: Option<CompatVersion>,
+    pub parallelism: Parallelism,
+    /// Lines held in memory and verified together; also the checkpoint interval
+    pub chunk_lines: usize,
+}
+
+impl Default for ArchiveVerifyOptions {
+    fn default() -> Self {
+        Self {
+            keys_allowlist: None,
+            compat_version: None,
+            parallelism: Parallelism::Auto,
+            chunk_lines: DEFAULT_ARCHIVE_CHUNK_LINES,
+        }
+    }
+}
+
+/// A line that is not a validly signed transaction.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ArchiveFailure {
+    /// 1-based line number in the archive
+    pub line: u64,
+    pub reason: String,
+}
+
+/// Outcome of verifying an archive.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ArchiveReport {
+    /// Non-blank lines read
+    pub total: u64,
+    /// Lines whose signature verifies under an allowed key
+    pub valid: u64,
+    /// Malformed lines and signatures that do not verify, in line order
+    pub invalid: Vec<ArchiveFailure>,
+    /// Well-signed transactions from keys outside the allowlist, per signer
+    pub unknown_signers: BTreeMap<String, u64>,
+}
+
+/// Progress through an archive, emitted after each chunk.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ArchiveCheckpoint {
+    /// Lines consumed so far, blank ones included (the last verified line)
+    pub lines: u64,
+    /// Bytes consumed so far; where [`resume_verify_archive`] seeks to
+    pub offset: u64,
+    /// Report for the lines consumed so far
+    pub report: ArchiveReport,
+}
+
+impl ArchiveCheckpoint {
+    /// Serialize to JSON string
+    pub fn to_json(&self) -> Result<String> {
+        serde_json::to_string(self).map_err(Error::from)
+    }
+
+    /// Parse from JSON string
+    pub fn from_json(s: &str) -> Result<Self> {
+        serde_json::from_str(s).map_err(Error::from)
+    }
+}
+
+enum Outcome {
+    Valid,
+    Invalid(String),
+    Unknown(String),
+}
+
+/// Verify every line of a JSONL archive of signed transactions.
+pub fn verify_archive<R: BufRead>(
+    reader: R,
+    options: &ArchiveVerifyOptions,
+) -> Result<ArchiveReport> {
+    verify_archive_with_checkpoint(reader, options, |_| {})
+}
+
+/// [`verify_archive`], passing a checkpoint to `sink` after each chunk of
+/// [`chunk_lines`](ArchiveVerifyOptions::chunk_lines) lines.
+pub fn verify_archive_with_checkpoint<R, F>(
+    reader: R,
+    options: &ArchiveVerifyOptions,
+    sink: F,
+) -> Result<ArchiveReport>
+where
+    R: BufRead,
+    F: FnMut(&ArchiveCheckpoint),
+{
+    let start = ArchiveCheckpoint {
+        lines: 0,
+        offset: 0,
+        report: ArchiveReport::default(),
+    };
+    verify_from(reader, options, start, sink)
+}
+
+/// Continue an archive from `checkpoint`, returning the report for the
+/// whole archive.
+///
+/// `reader` must read the same archive the checkpoint was taken from, with
+/// the same options; it is sought to the checkpoint's offset.
+pub fn resume_verify_archive<R, F>(
+    mut reader: R,
+    options: &ArchiveVerifyOptions,
+    checkpoint: &ArchiveCheckpoint,
+    sink: F,
+) -> Result<ArchiveReport>
+where
+    R: BufRead + Seek,
+    F: FnMut(&ArchiveCheckpoint),
+{
+    let end = reader.seek(SeekFrom::End(0)).map_err(io_error)?;
+    if checkpoint.offset > end {
+        return Err(Error::Archive(format!(
+            "checkpoint offset {} is past the end of the archive ({} bytes)",
+            checkpoint.offset, end
+        )));
+    }
+    reader
+        .seek(SeekFrom::Start(checkpoint.offset))
+        .map_err(io_error)?;
+    verify_from(reader, options, checkpoint.clone(), sink)
+}
+
+fn verify_from<R, F>(
+    mut reader: R,
+    options: &ArchiveVerifyOptions,
+    mut progress: ArchiveCheckpoint,
+    mut sink: F,
+) -> Result<ArchiveReport>
+where
+    R: BufRead,
+    F: FnMut(&ArchiveCheckpoint),
+{
+    let chunk_lines = options.chunk_lines.max(1);
+    let mut chunk = Vec::with_capacity(chunk_lines);
+    let mut buf = Vec::new();
+    loop {
+        chunk.clear();
+        let mut consumed = 0;
+        while chunk.len() < chunk_lines {
+            buf.clear();
+            let n = reader.read_until(b'\n', &mut buf).map_err(io_error)?;
+            if n == 0 {
+                break;
+            }
+            consumed += n;
+            progress.lines += 1;
+            if !buf.trim_ascii().is_empty() {
+                chunk.push((progress.lines, std::mem::take(&mut buf)));
+            }
+        }
+        if consumed == 0 {
+            return Ok(progress.report);
+        }
+
+        let (outcomes, _) = run_batch(
+            options.parallelism,
+            2,
+            std::mem::take(&mut chunk),
+            |_, (line, bytes)| (line, verify_line(&bytes, options)),
+        );
+        let report = &mut progress.report;
+        for (line, outcome) in outcomes {
+            report.total += 1;
+            match outcome {
+                Outcome::Valid => report.valid += 1,
+                Outcome::Invalid(reason) => report.invalid.push(ArchiveFailure { line, reason }),
+                Outcome::Unknown(signer) => *report.unknown_signers.entry(signer).or_default() += 1,
+            }
+        }
+        progress.offset += consumed as u64;
+        sink(&progress);
+    }
+}
+
+fn verify_line(bytes: &[u8], options: &ArchiveVerifyOptions) -> Outcome {
+    let tx: SignedTransaction = match serde_json::from_slice(bytes) {
+        Ok(tx) => tx,
+        Err(e) => return Outcome::Invalid(format!("malformed transaction: {}", e)),
+    };
+    let verified = match options.compat_version {
+        Some(version) => tx.verify_with(version),
+        None => tx.verify_any_version().map(|v| v.is_some()),
+    };
+    match verified {
+        Ok(true) => {}
+        Ok(false) => return Outcome::Invalid("signature does not verify".to_string()),
+        Err(e) => return Outcome::Invalid(e.to_string()),
+    }
+    match (&options.keys_allowlist, tx.signer_pubkey()) {
+        (_, Err(e)) => Outcome::Invalid(e.to_string()),
+        (Some(allowed), Ok(signer)) if !allowed.contains(&signer) => Outcome::Unknown(tx.signer),
+        _ => Outcome::Valid,
+    }
+}
+
+fn io_error(e: std::io::Error) -> Error {
+    Error::Archive(e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Keypair, Order, Signer};
+    use std::io::Cursor;
+
+    /// 12 lines: bad signatures on lines 3 and 7, garbage on line 5, a blank
+    /// line 9 and two transactions from `other` on lines 10 and 11.
+    fn fixture(ours: &Keypair, other: &Keypair) -> String {
+        let sign = |keypair: &Keypair, nonce| {
+            let mut signer = Signer::new(keypair.clone());
+            signer
+                .sign(Order::market("BTC-USD", true, 0.1).into(), Some(nonce))
+                .unwrap()
+        };
+        (1..=12u64)
+            .map(|line| match line {
+                3 | 7 => {
+                    let mut tx = sign(ours, line);
+                    tx.nonce += 1;
+                    serde_json::to_string(&tx).unwrap()
+                }
+                5 => "{not json".to_string(),
+                9 => "   ".to_string(),
+                10 | 11 => serde_json::to_string(&sign(other, line)).unwrap(),
+                _ => serde_json::to_string(&sign(ours, line)).unwrap(),
+            })
+            .map(|line| line + "\n")
+            .collect()
+    }
+
+    fn options(chunk_lines: usize) -> ArchiveVerifyOptions {
+        ArchiveVerifyOptions {
+            parallelism: Parallelism::Threads(2),
+            chunk_lines,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_planted_failures_are_reported_by_line() {
+        let (ours, other) = (Keypair::generate(), Keypair::generate());
+        let archive = fixture(&ours, &other);
+        let report = verify_archive(archive.as_bytes(), &options(4)).unwrap();
+        assert_eq!(report.total, 11);
+        assert_eq!(report.valid, 8);
+        let lines: Vec<u64> = report.invalid.iter().map(|f| f.line).collect();
+        assert_eq!(lines, [3, 5, 7]);
+        assert_eq!(report.invalid[0].reason, "signature does not verify");
+        assert!(report.invalid[1]
+            .reason
+            .starts_with("malformed transaction"));
+        assert!(report.unknown_signers.is_empty());
+    }
+
+    #[test]
+    fn test_allowlist_moves_other_keys_to_unknown() {
+        let (ours, other) = (Keypair::generate(), Keypair::generate());
+        let archive = fixture(&ours, &other);
+        let options = ArchiveVerifyOptions {
+            keys_allowlist: Some(HashSet::from([ours.pubkey()])),
+            compat_version: Some(CompatVersion::V1),
+            ..options(5)
+        };
+        let report = verify_archive(archive.as_bytes(), &options).unwrap();
+        assert_eq!(report.valid, 6);
+        assert_eq!(report.invalid.len(), 3);
+        assert_eq!(
+            report.unknown_signers,
+            BTreeMap::from([(other.pubkey().to_base58(), 2)])
+        );
+    }
+
+    #[test]
+    fn test_resume_mid_file_gives_the_same_report() {
+        let (ours, other) = (Keypair::generate(), Keypair::generate());
+        let archive = fixture(&ours, &other);
+        let mut checkpoints = Vec::new();
+        let full = verify_archive_with_checkpoint(archive.as_bytes(), &options(4), |cp| {
+            checkpoints.push(cp.clone())
+        })
+        .unwrap();
+        assert_eq!(
+            checkpoints.iter().map(|cp| cp.lines).collect::<Vec<_>>(),
+            [4, 8, 12]
+        );
+
+        // Crash after the first chunk, persist, restart.
+        let saved = ArchiveCheckpoint::from_json(&checkpoints[0].to_json().unwrap()).unwrap();
+        assert_eq!(saved.report.invalid.len(), 1);
+        let resumed =
+            resume_verify_archive(Cursor::new(&archive), &options(4), &saved, |_| {}).unwrap();
+        assert_eq!(resumed, full);
+
+        let past_end = ArchiveCheckpoint {
+            offset: archive.len() as u64 + 1,
+            ..saved
+        };
+        assert!(matches!(
+            resume_verify_archive(Cursor::new(&archive), &options(4), &past_end, |_| {}),
+            Err(Error::Archive(_))
+        ));
+    }
+}
@@ -0,0 +1,155 @@
+//! Mixed-action batches.
+//!
+//! A [`BatchBuilder`] collects singles, atomic groups, and cancel batches and signs
+//! each entry as its own transaction, in insertion order.
+
+use crate::types::*;
+use crate::{Result, Signer};
+
+/// One transaction in a [`BatchBuilder`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum BatchEntry {
+    /// A single order item
+    Single(OrderItem),
+    /// Several items signed atomically in one transaction
+    Group(Vec<OrderItem>),
+    /// Several cancels signed in one transaction
+    CancelBatch(Vec<Cancel>),
+}
+
+/// Builder for a batch of mixed transactions.
+#[derive(Debug, Clone, Default)]
+pub struct BatchBuilder {
+    entries: Vec<BatchEntry>,
+}
+
+impl BatchBuilder {
+    /// Create an empty builder.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Append an entry.
+    pub fn push(&mut self, entry: BatchEntry) -> &mut Self {
+        self.entries.push(entry);
+        self
+    }
+
+    /// Append a single order item.
+    pub fn single(&mut self, item: impl Into<OrderItem>) -> &mut Self {
+        self.push(BatchEntry::Single(item.into()))
+    }
+
+    /// Append an atomic group.
+    pub fn group(&mut self, items: Vec<OrderItem>) -> &mut Self {
+        self.push(BatchEntry::Group(items))
+    }
+
+    /// Append a batch of cancels.
+    pub fn cancel_batch(&mut self, cancels: Vec<Cancel>) -> &mut Self {
+        self.push(BatchEntry::CancelBatch(cancels))
+    }
+
+    /// Number of pending entries.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Whether there are no pending entries.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Sign all entries in order, entry `i` using nonce `base + i`.
+    ///
+    /// Pending entries are consumed, leaving the builder empty for reuse.
+    ///
+    /// ```rust
+    /// use bulk_keychain::{BatchBuilder, Cancel, Hash, Keypair, Order, Signer};
+    ///
+    /// let mut signer = Signer::new(Keypair::generate());
+    /// let mut batch = BatchBuilder::new();
+    /// batch
+    ///     .single(Order::market("BTC-USD", true, 0.1))
+    ///     .cancel_batch(vec![Cancel::new("BTC-USD", Hash::random())]);
+    /// let txs = batch.build(&mut signer, Some(1)).unwrap();
+    /// assert_eq!((txs[0].nonce, txs[1].nonce), (1, 2));
+    /// assert!(txs.iter().all(|tx| tx.verify().unwrap()));
+    /// assert!(batch.is_empty());
+    /// ```
+    pub fn build(
+        &mut self,
+        signer: &mut Signer,
+        base_nonce: Option<u64>,
+    ) -> Result<Vec<SignedTransaction>> {
+        let base = signer.base_nonce_or_now(base_nonce, self.entries.len())?;
+        std::mem::take(&mut self.entries)
+            .into_iter()
+            .enumerate()
+            .map(|(i, entry)| {
+                let nonce = Some(base + i as u64);
+                match entry {
+                    BatchEntry::Single(item) => signer.sign(item, nonce),
+                    BatchEntry::Group(items) => signer.sign_group(items, nonce),
+                    BatchEntry::CancelBatch(cancels) => signer.sign_group_with(
+                        cancels.into_iter().map(OrderItem::Cancel).collect(),
+                        Some(GroupKind::Batch),
+                        nonce,
+                    ),
+                }
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Error, Keypair};
+
+    #[test]
+    fn test_build_mixed_batch_in_order() {
+        let mut signer = Signer::new(Keypair::generate());
+        let mut batch = BatchBuilder::new();
+        batch
+            .single(Order::limit(
+                "BTC-USD",
+                true,
+                100000.0,
+                0.1,
+                TimeInForce::Gtc,
+            ))
+            .group(vec![
+                Order::limit("ETH-USD", true, 3000.0, 1.0, TimeInForce::Gtc).into(),
+                Order::limit("ETH-USD", false, 3300.0, 1.0, TimeInForce::Gtc).into(),
+            ])
+            .cancel_batch(vec![
+                Cancel::new("BTC-USD", Hash::random()),
+                Cancel::new("SOL-USD", Hash::random()),
+            ]);
+        assert_eq!(batch.len(), 3);
+
+        let signed = batch.build(&mut signer, Some(100)).unwrap();
+        assert!(batch.is_empty());
+        assert_eq!(signed.len(), 3);
+        assert_eq!(
+            signed.iter().map(|tx| tx.nonce).collect::<Vec<_>>(),
+            vec![100, 101, 102]
+        );
+        assert!(signed[0].actions_json().unwrap()[0].get("l").is_some());
+        assert_eq!(signed[1].actions_json().unwrap().len(), 2);
+        assert_eq!(signed[2].actions_json().unwrap().len(), 2);
+        assert!(signed[2].actions_json().unwrap()[1].get("cx").is_some());
+        assert_eq!(signed[1].group_kind, None);
+        assert_eq!(signed[2].group_kind, Some(GroupKind::Batch));
+    }
+
+    #[test]
+    fn test_build_rejects_empty_group() {
+        let mut signer = Signer::new(Keypair::generate());
+        let mut batch = BatchBuilder::new();
+        batch.group(vec![]);
+        let result = batch.build(&mut signer, Some(1));
+        assert!(matches!(result, Err(Error::EmptyOrders)));
+    }
+}
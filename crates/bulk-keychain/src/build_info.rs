@@ -0,0 +1,61 @@
+//! Compile-time build metadata
+//!
+//! Bindings re-export [`build_info`] so every layer of a deployment can report
+//! exactly which core build it embeds.
+
+use serde::Serialize;
+
+/// Version, commit and configuration this crate was compiled with.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BuildInfo {
+    /// `bulk-keychain` crate version
+    pub crate_version: String,
+    /// Commit the crate was built from, or `"unknown"` outside a git checkout.
+    /// Override with the `BULK_KEYCHAIN_GIT_SHA` environment variable.
+    pub git_sha: String,
+    /// Enabled cargo features, sorted, without `default`
+    pub features: Vec<String>,
+    /// Cargo profile: `debug` or `release`
+    pub profile: String,
+}
+
+/// Metadata captured by the build script.
+pub fn build_info() -> BuildInfo {
+    let features = env!("BULK_KEYCHAIN_FEATURES");
+    BuildInfo {
+        crate_version: env!("CARGO_PKG_VERSION").to_string(),
+        git_sha: env!("BULK_KEYCHAIN_GIT_SHA").to_string(),
+        features: features
+            .split(',')
+            .filter(|f| !f.is_empty())
+            .map(str::to_string)
+            .collect(),
+        profile: env!("BULK_KEYCHAIN_PROFILE").to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_build_info_matches_compiled_features() {
+        let info = build_info();
+        assert_eq!(info.crate_version, env!("CARGO_PKG_VERSION"));
+        assert!(!info.git_sha.is_empty());
+        assert_eq!(
+            info.features.contains(&"batch".to_string()),
+            cfg!(feature = "batch")
+        );
+        assert_eq!(
+            info.features.contains(&"agent".to_string()),
+            cfg!(feature = "agent")
+        );
+        assert_eq!(
+            info.features.contains(&"pipeline".to_string()),
+            cfg!(feature = "pipeline")
+        );
+        assert!(!info.features.contains(&"default".to_string()));
+    }
+}
@@ -0,0 +1,197 @@
+//! What a signer is allowed to do, for service discovery
+//!
+//! [`Signer::capabilities`] describes the account, key, network, policy and
+//! scopes a signer currently runs under, and the crate features it was
+//! compiled with. The descriptor is computed on every call, so it follows
+//! runtime changes such as [`Signer::set_max_open_orders_per_symbol`].
+//!
+//! [`Signer::capabilities_signed`] signs the descriptor's JSON with the
+//! signer's key so another service can check where it came from:
+//!
+//! ```rust
+//! use bulk_keychain::{verify_signature, Keypair, Signer};
+//!
+//! let signer = Signer::new(Keypair::generate()).with_network("testnet");
+//! let signed = signer.capabilities_signed().unwrap();
+//! assert!(signed.verify().unwrap());
+//!
+//! // The same check with the standalone verifier.
+//! let descriptor = signed.descriptor().unwrap();
+//! assert_eq!(descriptor.network.as_deref(), Some("testnet"));
+//! assert!(verify_signature(signer.signer_pubkey(), &signed.message(), &signed.signature).unwrap());
+//! ```
+
+use crate::scope::ScopeConfig;
+use crate::types::{ActionKind, Pubkey};
+use crate::{build_info, verify_signature, Error, Result};
+use serde::{Deserialize, Serialize};
+
+/// Prefix of the message [`SignedCapabilities::signature`] covers, so the
+/// signature cannot be replayed as any other signed payload.
+pub const CAPABILITIES_DOMAIN: &[u8] = b"bulk-keychain:capabilities:v1\n";
+
+/// Signing policy in force, as far as it can be described.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PolicySummary {
+    /// Whether a pre-sign hook vets every item; what it checks is opaque
+    pub pre_sign_hook: bool,
+    pub max_open_orders_per_symbol: Option<usize>,
+    pub max_resign_age_ms: Option<u64>,
+    /// Nonce strategy (`Debug` form), or `None` when nonces are the current time
+    pub nonce_strategy: Option<String>,
+}
+
+/// What a signer may sign, and for whom.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CapabilityDescriptor {
+    /// Trading account (base58)
+    pub account: String,
+    /// Signing key (base58)
+    pub signer: String,
+    /// Network label set with [`Signer::with_network`](crate::Signer::with_network)
+    pub network: Option<String>,
+    pub policy_summary: PolicySummary,
+    /// Scopes the signer is confined to, outermost first; empty if unscoped
+    pub scopes: Vec<ScopeConfig>,
+    pub supported_actions: Vec<ActionKind>,
+    /// Crate features compiled in (see [`build_info`](crate::build_info()))
+    pub features: Vec<String>,
+}
+
+impl CapabilityDescriptor {
+    pub(crate) fn new(
+        account: &Pubkey,
+        signer: &Pubkey,
+        network: Option<String>,
+        policy_summary: PolicySummary,
+        scopes: Vec<ScopeConfig>,
+        supported_actions: Vec<ActionKind>,
+    ) -> Self {
+        Self {
+            account: account.to_base58(),
+            signer: signer.to_base58(),
+            network,
+            policy_summary,
+            scopes,
+            supported_actions,
+            features: build_info().features,
+        }
+    }
+
+    pub fn to_json(&self) -> Result<String> {
+        serde_json::to_string(self).map_err(Error::from)
+    }
+}
+
+/// A [`CapabilityDescriptor`] signed by the key it describes.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SignedCapabilities {
+    /// The descriptor's JSON, exactly as signed
+    pub payload: String,
+    /// Signing key (base58)
+    pub signer: String,
+    /// Signature over [`SignedCapabilities::message`] (base58)
+    pub signature: String,
+}
+
+impl SignedCapabilities {
+    /// The signed bytes: [`CAPABILITIES_DOMAIN`] followed by the payload.
+    pub fn message(&self) -> Vec<u8> {
+        capabilities_message(&self.payload)
+    }
+
+    pub fn descriptor(&self) -> Result<CapabilityDescriptor> {
+        serde_json::from_str(&self.payload).map_err(Error::from)
+    }
+
+    /// Whether the signature is valid and the payload names the key that
+    /// made it, so a descriptor cannot vouch for someone else's key.
+    pub fn verify(&self) -> Result<bool> {
+        let signer = Pubkey::from_base58(&self.signer)?;
+        if self.descriptor()?.signer != self.signer {
+            return Ok(false);
+        }
+        verify_signature(&signer, &self.message(), &self.signature)
+    }
+}
+
+pub(crate) fn capabilities_message(payload: &str) -> Vec<u8> {
+    let mut message = Vec::with_capacity(CAPABILITIES_DOMAIN.len() + payload.len());
+    message.extend_from_slice(CAPABILITIES_DOMAIN);
+    message.extend_from_slice(payload.as_bytes());
+    message
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Keypair, NonceManager, Signer};
+
+    #[test]
+    fn test_descriptor_follows_runtime_policy_changes() {
+        let mut signer = Signer::with_nonce_manager(Keypair::generate(), NonceManager::counter());
+        let before = signer.capabilities();
+        assert_eq!(before.policy_summary.max_open_orders_per_symbol, None);
+        assert_eq!(
+            before.policy_summary.nonce_strategy.as_deref(),
+            Some("Counter")
+        );
+        assert_eq!(before.supported_actions, ActionKind::ALL.to_vec());
+        assert!(before.scopes.is_empty());
+
+        signer.set_max_open_orders_per_symbol(Some(5));
+        signer.set_max_resign_age_ms(Some(30_000));
+        signer.set_pre_sign_hook(Some(std::sync::Arc::new(|_: &_, _| Ok(()))));
+        signer.set_network(Some("mainnet".to_string()));
+        let after = signer.capabilities();
+        assert_eq!(
+            after.policy_summary,
+            PolicySummary {
+                pre_sign_hook: true,
+                max_open_orders_per_symbol: Some(5),
+                max_resign_age_ms: Some(30_000),
+                nonce_strategy: Some("Counter".to_string()),
+            }
+        );
+        assert_eq!(after.network.as_deref(), Some("mainnet"));
+
+        let json: serde_json::Value = serde_json::from_str(&after.to_json().unwrap()).unwrap();
+        assert_eq!(json["policySummary"]["maxOpenOrdersPerSymbol"], 5);
+        assert_eq!(json["supportedActions"][0], "order");
+
+        let scoped = signer.scoped(ScopeConfig {
+            symbols: Some(vec!["BTC-USD".to_string()]),
+            ..ScopeConfig::default()
+        });
+        let descriptor = scoped.capabilities();
+        assert_eq!(descriptor.scopes, vec![scoped.config().clone()]);
+        assert_eq!(descriptor.supported_actions, vec![ActionKind::Order]);
+    }
+
+    #[test]
+    fn test_self_signature_verifies_and_detects_tampering() {
+        let signer = Signer::new(Keypair::generate()).with_account(Keypair::generate().pubkey());
+        let signed = signer.capabilities_signed().unwrap();
+        assert!(signed.verify().unwrap());
+        assert!(
+            verify_signature(signer.signer_pubkey(), &signed.message(), &signed.signature).unwrap()
+        );
+        assert_eq!(signed.descriptor().unwrap(), signer.capabilities());
+
+        let mut tampered = signed.clone();
+        tampered.payload = tampered
+            .payload
+            .replace("\"network\":null", "\"network\":\"x\"");
+        assert_ne!(tampered.payload, signed.payload);
+        assert!(!tampered.verify().unwrap());
+
+        // A valid signature from a key the payload does not name.
+        let other = Signer::new(Keypair::generate());
+        let mut forged = signed.clone();
+        forged.signer = other.signer_pubkey().to_base58();
+        forged.signature = other.sign_bytes(&signed.message());
+        assert!(!forged.verify().unwrap());
+    }
+}
@@ -0,0 +1,181 @@
+//! Counting live copies of secret keys (`secret-census` feature)
+//!
+//! Every [`Keypair`](crate::Keypair) built or cloned while the feature is on
+//! registers one copy of its secret under the key's fingerprint, and drops
+//! it again when dropped or zeroized. [`secret_census`] answers "how many copies of this
+//! key exist in the process right now", and why:
+//! [`Keypair::clone_tracked`](crate::Keypair::clone_tracked) records a
+//! purpose for the copy, plain clones record `"clone"` and newly built
+//! keypairs `"origin"`.
+//!
+//! Without the feature the token is zero-sized and nothing is counted.
+
+#[cfg(feature = "secret-census")]
+pub(crate) use enabled::CensusToken;
+#[cfg(feature = "secret-census")]
+pub use enabled::{secret_census, SecretCensusEntry};
+
+#[cfg(not(feature = "secret-census"))]
+pub(crate) use disabled::CensusToken;
+
+#[cfg(feature = "secret-census")]
+mod enabled {
+    use crate::Pubkey;
+    use serde::{Deserialize, Serialize};
+    use std::collections::{BTreeMap, HashMap};
+    use std::sync::{Arc, Mutex, MutexGuard, OnceLock};
+
+    /// Live copies of one secret key.
+    #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct SecretCensusEntry {
+        /// [`Keypair::fingerprint`](crate::Keypair::fingerprint) of the key
+        pub fingerprint: String,
+        pub copies: usize,
+        /// Copies per recorded purpose
+        pub reasons: BTreeMap<String, usize>,
+    }
+
+    type Counts = HashMap<Arc<str>, BTreeMap<Arc<str>, usize>>;
+
+    fn counts() -> MutexGuard<'static, Counts> {
+        static COUNTS: OnceLock<Mutex<Counts>> = OnceLock::new();
+        COUNTS
+            .get_or_init(Default::default)
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Live copies of every secret key in the process, by fingerprint.
+    pub fn secret_census() -> Vec<SecretCensusEntry> {
+        let mut entries: Vec<_> = counts()
+            .iter()
+            .map(|(fingerprint, reasons)| SecretCensusEntry {
+                fingerprint: fingerprint.to_string(),
+                copies: reasons.values().sum(),
+                reasons: reasons
+                    .iter()
+                    .map(|(reason, n)| (reason.to_string(), *n))
+                    .collect(),
+            })
+            .collect();
+        entries.sort_by(|a, b| a.fingerprint.cmp(&b.fingerprint));
+        entries
+    }
+
+    /// One counted copy of a secret, released on drop.
+    #[derive(Debug)]
+    pub(crate) struct CensusToken {
+        fingerprint: Arc<str>,
+        reason: Arc<str>,
+    }
+
+    impl CensusToken {
+        pub(crate) fn register(pubkey: &Pubkey, reason: &str) -> Self {
+            Self::count(pubkey.fingerprint().into(), reason.into())
+        }
+
+        /// Another copy of the same secret, held for `reason`.
+        pub(crate) fn copy(&self, reason: &str) -> Self {
+            Self::count(self.fingerprint.clone(), reason.into())
+        }
+
+        fn count(fingerprint: Arc<str>, reason: Arc<str>) -> Self {
+            *counts()
+                .entry(fingerprint.clone())
+                .or_default()
+                .entry(reason.clone())
+                .or_default() += 1;
+            Self {
+                fingerprint,
+                reason,
+            }
+        }
+    }
+
+    impl Clone for CensusToken {
+        fn clone(&self) -> Self {
+            self.copy("clone")
+        }
+    }
+
+    impl Drop for CensusToken {
+        fn drop(&mut self) {
+            let mut counts = counts();
+            let Some(reasons) = counts.get_mut(&self.fingerprint) else {
+                return;
+            };
+            if let Some(n) = reasons.get_mut(&self.reason) {
+                *n -= 1;
+                if *n == 0 {
+                    reasons.remove(&self.reason);
+                }
+            }
+            if reasons.is_empty() {
+                counts.remove(&self.fingerprint);
+            }
+        }
+    }
+}
+
+#[cfg(not(feature = "secret-census"))]
+mod disabled {
+    #[derive(Debug, Clone)]
+    pub(crate) struct CensusToken;
+
+    impl CensusToken {
+        pub(crate) fn register(_pubkey: &crate::Pubkey, _reason: &str) -> Self {
+            Self
+        }
+
+        pub(crate) fn copy(&self, _reason: &str) -> Self {
+            Self
+        }
+    }
+}
+
+#[cfg(all(test, feature = "secret-census"))]
+mod tests {
+    use super::*;
+    use crate::{Keypair, Signer};
+
+    fn copies(keypair: &Keypair) -> Option<SecretCensusEntry> {
+        let fingerprint = keypair.fingerprint();
+        secret_census()
+            .into_iter()
+            .find(|e| e.fingerprint == fingerprint)
+    }
+
+    #[test]
+    fn test_counts_rise_and_fall() {
+        let keypair = Keypair::generate();
+        assert_eq!(copies(&keypair).unwrap().copies, 1);
+
+        let signer = Signer::new(keypair.clone_tracked("signer"));
+        let clone = keypair.clone();
+        let entry = copies(&keypair).unwrap();
+        assert_eq!(entry.copies, 3);
+        assert_eq!(entry.reasons["origin"], 1);
+        assert_eq!(entry.reasons["signer"], 1);
+        assert_eq!(entry.reasons["clone"], 1);
+
+        drop(signer);
+        drop(clone);
+        let entry = copies(&keypair).unwrap();
+        assert_eq!(entry.copies, 1);
+        assert!(!entry.reasons.contains_key("signer"));
+
+        let fingerprint = keypair.fingerprint();
+        drop(keypair);
+        assert!(secret_census().iter().all(|e| e.fingerprint != fingerprint));
+    }
+
+    #[test]
+    fn test_same_key_built_twice_counts_twice() {
+        let keypair = Keypair::generate();
+        let again = Keypair::from_bytes(&keypair.to_bytes()).unwrap();
+        assert_eq!(copies(&keypair).unwrap().reasons["origin"], 2);
+        drop(again);
+        assert_eq!(copies(&keypair).unwrap().copies, 1);
+    }
+}
@@ -0,0 +1,48 @@
+//! Checkpoints for resuming long `sign_all` runs.
+//!
+//! Item `i` of a batch is always signed with `base_nonce + i`, so a batch can be
+//! resumed after a crash without burning a fresh nonce range.
+
+use crate::types::{Hash, Pubkey};
+use serde::{Deserialize, Serialize};
+
+/// Progress marker emitted by [`Signer::sign_all_with_checkpoint`](crate::Signer::sign_all_with_checkpoint).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BatchCheckpoint {
+    /// Digest of the full input list; resuming against edited input is rejected.
+    pub items_hash: Hash,
+    /// Key that signed the batch; resuming with another signer is rejected.
+    pub signer: Pubkey,
+    /// Account the batch was signed for; resuming for another is rejected.
+    pub account: Pubkey,
+    /// Nonce of the first item in the batch.
+    pub base_nonce: u64,
+    /// Number of items signed so far (index of the next item to sign).
+    pub completed: usize,
+}
+
+impl BatchCheckpoint {
+    /// Nonce the next unsigned item will use.
+    ///
+    /// Fails with [`Error::NonceOverflow`](crate::Error::NonceOverflow) if it
+    /// would pass `u64::MAX`, which only a hand-edited checkpoint can reach.
+    pub fn next_nonce(&self) -> crate::Result<u64> {
+        self.base_nonce
+            .checked_add(self.completed as u64)
+            .ok_or(crate::Error::NonceOverflow {
+                base: self.base_nonce,
+                count: self.completed,
+            })
+    }
+
+    /// Serialize to JSON string
+    pub fn to_json(&self) -> crate::Result<String> {
+        serde_json::to_string(self).map_err(crate::Error::from)
+    }
+
+    /// Parse from JSON string
+    pub fn from_json(s: &str) -> crate::Result<Self> {
+        serde_json::from_str(s).map_err(crate::Error::from)
+    }
+}
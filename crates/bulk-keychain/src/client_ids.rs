@@ -0,0 +1,455 @@
+//! Client-ID uniqueness across live orders, enforced at signing time
+//!
+//! The registry remembers the `client_id` of every order the signer has
+//! signed until that order is closed: by a cancel or cancel-all the signer
+//! signs, or by [`ClientIdRegistry::notify_closed`] for fills, expiries and
+//! server-side cancels. Signing another order with a remembered client ID
+//! fails with [`Error::ClientIdInUse`], unless the call asks for reuse with
+//! [`SignOptions::allow_client_id_reuse`](crate::SignOptions::allow_client_id_reuse).
+//!
+//! Orders without a client ID are not tracked. Batches claim their IDs in
+//! input order and keep them only if the whole batch signs, so of two
+//! orders sharing an ID the earlier one wins, and a refused batch can be
+//! resubmitted once fixed.
+//!
+//! The same registry lets a signer cancel by client ID: an
+//! [`OrderItem::CancelByClientId`] is resolved to a cancel of the live order
+//! holding that ID before it is signed, since the exchange only cancels by
+//! order ID.
+
+use crate::order_id::compute_order_item_id_at_index;
+use crate::types::{Cancel, Hash, OrderItem, Pubkey};
+use crate::{Error, Result};
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+#[derive(Debug, Clone)]
+struct LiveOrder {
+    order_id: Hash,
+    symbol: String,
+}
+
+/// What one [`ClientIdRegistry::apply`] changed: each touched client ID
+/// with the order it was held by before, oldest first.
+#[derive(Debug, Default)]
+pub(crate) struct Undo(Vec<(Hash, Option<LiveOrder>)>);
+
+/// Client-ID bookkeeping behind [`crate::Signer::with_unique_client_ids`].
+#[derive(Debug, Default)]
+pub struct ClientIdRegistry {
+    live: Mutex<HashMap<Hash, LiveOrder>>,
+}
+
+impl ClientIdRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Live order currently holding `client_id`.
+    pub fn order_for(&self, client_id: &Hash) -> Option<Hash> {
+        self.lock().get(client_id).map(|live| live.order_id)
+    }
+
+    /// Client IDs currently held by live orders.
+    pub fn live_count(&self) -> usize {
+        self.lock().len()
+    }
+
+    /// Release the client ID held by `order_id`; returns whether it held one.
+    pub fn notify_closed(&self, order_id: &Hash) -> bool {
+        let mut live = self.lock();
+        let before = live.len();
+        live.retain(|_, l| l.order_id != *order_id);
+        live.len() != before
+    }
+
+    /// Release every client ID; returns how many were held, and what `then`
+    /// returned. `then` runs with the registry still locked, as in
+    /// [`ClientIdRegistry::apply`].
+    pub(crate) fn clear_then<T>(&self, then: impl FnOnce() -> T) -> (usize, T) {
+        let mut live = self.lock();
+        let cleared = std::mem::take(&mut *live).len();
+        (cleared, then())
+    }
+
+    /// A registry starting from a copy of this one's state, for dry runs.
+    pub(crate) fn fork(&self) -> Self {
+        Self {
+            live: Mutex::new(self.lock().clone()),
+        }
+    }
+
+    /// Apply one transaction's items: cancels release first, then each order
+    /// leg claims its client ID. If any leg's ID is taken (by a live order or
+    /// an earlier leg) and `allow_reuse` is off, nothing changes; otherwise
+    /// the returned [`Undo`] takes the transaction back out.
+    ///
+    /// `then` runs with the registry locked and must succeed for the claims
+    /// to be kept, so a later check refusing the transaction leaves no IDs
+    /// behind.
+    pub(crate) fn apply<T>(
+        &self,
+        items: &[OrderItem],
+        nonce: u64,
+        account: &Pubkey,
+        allow_reuse: bool,
+        then: impl FnOnce() -> Result<T>,
+    ) -> Result<(Undo, T)> {
+        let mut live = self.lock();
+        let mut undo = Undo::default();
+        let result = Self::apply_to(&mut live, &mut undo, items, nonce, account, allow_reuse)
+            .and_then(|()| then());
+        match result {
+            Ok(value) => Ok((undo, value)),
+            Err(e) => {
+                Self::revert(&mut live, undo);
+                Err(e)
+            }
+        }
+    }
+
+    fn apply_to(
+        live: &mut HashMap<Hash, LiveOrder>,
+        undo: &mut Undo,
+        items: &[OrderItem],
+        nonce: u64,
+        account: &Pubkey,
+        allow_reuse: bool,
+    ) -> Result<()> {
+        for item in items {
+            let released: Vec<Hash> = match item {
+                OrderItem::Cancel(cancel) => live
+                    .iter()
+                    .filter(|(_, l)| l.order_id == cancel.order_id)
+                    .map(|(client_id, _)| *client_id)
+                    .collect(),
+                OrderItem::CancelAll(cxa) if cxa.symbols.is_empty() => {
+                    live.keys().copied().collect()
+                }
+                OrderItem::CancelAll(cxa) => live
+                    .iter()
+                    .filter(|(_, l)| cxa.symbols.contains(&l.symbol))
+                    .map(|(client_id, _)| *client_id)
+                    .collect(),
+                _ => continue,
+            };
+            for client_id in released {
+                let previous = live.remove(&client_id);
+                undo.0.push((client_id, previous));
+            }
+        }
+
+        for (index, item) in items.iter().enumerate() {
+            let OrderItem::Order(order) = item else {
+                continue;
+            };
+            let Some(client_id) = order.client_id else {
+                continue;
+            };
+            if !allow_reuse {
+                if let Some(existing) = live.get(&client_id) {
+                    return Err(Error::ClientIdInUse {
+                        client_id: client_id.to_base58(),
+                        existing_order_id: existing.order_id.to_base58(),
+                    });
+                }
+            }
+            if let Some(order_id) =
+                compute_order_item_id_at_index(item, index as u32, nonce, account)
+            {
+                let symbol = order.symbol.clone();
+                let previous = live.insert(client_id, LiveOrder { order_id, symbol });
+                undo.0.push((client_id, previous));
+            }
+        }
+        Ok(())
+    }
+
+    /// Take back what a [`ClientIdRegistry::apply`] did.
+    pub(crate) fn undo(&self, undo: Undo) {
+        Self::revert(&mut self.lock(), undo);
+    }
+
+    fn revert(live: &mut HashMap<Hash, LiveOrder>, undo: Undo) {
+        for (client_id, previous) in undo.0.into_iter().rev() {
+            match previous {
+                Some(order) => live.insert(client_id, order),
+                None => live.remove(&client_id),
+            };
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<Hash, LiveOrder>> {
+        self.live.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// Replace every [`OrderItem::CancelByClientId`] in `item`, nested actions
+/// included, with a cancel of the order `registry` holds for its client ID.
+pub(crate) fn resolve_cancel(
+    registry: Option<&ClientIdRegistry>,
+    item: OrderItem,
+) -> Result<OrderItem> {
+    match item {
+        OrderItem::CancelByClientId(c) => {
+            let client_id = c.client_id.to_base58();
+            let registry = registry.ok_or(Error::UnresolvedClientIdCancel(client_id.clone()))?;
+            let order_id = registry
+                .order_for(&c.client_id)
+                .ok_or(Error::ClientIdNotLive(client_id))?;
+            Ok(Cancel::new(c.symbol, order_id).into())
+        }
+        OrderItem::TriggerBasket(mut basket) => {
+            basket.actions = resolve_cancels(registry, basket.actions)?;
+            Ok(basket.into())
+        }
+        OrderItem::OnFill(mut of) => {
+            of.actions = resolve_cancels(registry, of.actions)?;
+            Ok(of.into())
+        }
+        other => Ok(other),
+    }
+}
+
+pub(crate) fn resolve_cancels(
+    registry: Option<&ClientIdRegistry>,
+    items: Vec<OrderItem>,
+) -> Result<Vec<OrderItem>> {
+    items
+        .into_iter()
+        .map(|item| resolve_cancel(registry, item))
+        .collect()
+}
+
+/// `items`, resolved with [`resolve_cancels`] only if any need it.
+pub(crate) fn resolve_cancels_in<'a>(
+    registry: Option<&ClientIdRegistry>,
+    items: &'a [OrderItem],
+) -> Result<Cow<'a, [OrderItem]>> {
+    fn needs(item: &OrderItem) -> bool {
+        match item {
+            OrderItem::CancelByClientId(_) => true,
+            OrderItem::TriggerBasket(b) => b.actions.iter().any(needs),
+            OrderItem::OnFill(of) => of.actions.iter().any(needs),
+            _ => false,
+        }
+    }
+    if items.iter().any(needs) {
+        Ok(Cow::Owned(resolve_cancels(registry, items.to_vec())?))
+    } else {
+        Ok(Cow::Borrowed(items))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        Cancel, Error, Hash, Keypair, Order, OrderItem, SignOptions, SignedTransaction, Signer,
+        TimeInForce,
+    };
+
+    fn with_cid(client_id: Hash) -> OrderItem {
+        Order::limit("BTC-USD", true, 100.0, 1.0, TimeInForce::Gtc)
+            .with_client_id(client_id)
+            .into()
+    }
+
+    fn oid(tx: &SignedTransaction) -> Hash {
+        Hash::from_base58(tx.order_id.as_ref().unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_reuse_blocked_until_closed() {
+        let mut signer = Signer::new(Keypair::generate()).with_unique_client_ids();
+        let cid = Hash::from_bytes([7; 32]);
+        let first = signer.sign(with_cid(cid), Some(1)).unwrap();
+
+        let err = signer.sign(with_cid(cid), Some(2)).unwrap_err();
+        match err {
+            Error::ClientIdInUse {
+                client_id,
+                existing_order_id,
+            } => {
+                assert_eq!(client_id, cid.to_base58());
+                assert_eq!(existing_order_id, first.order_id.clone().unwrap());
+            }
+            other => panic!("unexpected error: {other}"),
+        }
+
+        // Orders without a client ID are never tracked.
+        let plain = Order::limit("BTC-USD", true, 100.0, 1.0, TimeInForce::Gtc);
+        signer.sign(plain.clone().into(), Some(3)).unwrap();
+        signer.sign(plain.into(), Some(4)).unwrap();
+
+        assert!(signer.notify_closed(&oid(&first)));
+        assert!(!signer.notify_closed(&oid(&first)));
+        let second = signer.sign(with_cid(cid), Some(5)).unwrap();
+        assert_eq!(signer.client_id_order(&cid), Some(oid(&second)));
+    }
+
+    #[test]
+    fn test_signed_cancel_releases() {
+        let mut signer = Signer::new(Keypair::generate()).with_unique_client_ids();
+        let cid = Hash::from_bytes([1; 32]);
+        let live = signer.sign(with_cid(cid), Some(1)).unwrap();
+        // Cancel-replace under the same client ID in one transaction.
+        let replace = vec![Cancel::new("BTC-USD", oid(&live)).into(), with_cid(cid)];
+        signer.sign_group(replace, Some(2)).unwrap();
+        assert!(signer.sign(with_cid(cid), Some(3)).is_err());
+    }
+
+    #[test]
+    fn test_override_allows_reuse() {
+        let signer = Signer::new(Keypair::generate()).with_unique_client_ids();
+        let cid = Hash::from_bytes([2; 32]);
+        signer.sign_all(vec![with_cid(cid)], Some(1)).unwrap();
+        assert!(signer.sign_all(vec![with_cid(cid)], Some(2)).is_err());
+
+        let reuse = SignOptions::new().with_client_id_reuse();
+        let txs = signer
+            .sign_all_with_options(vec![with_cid(cid)], Some(3), &reuse)
+            .unwrap();
+        // The newest order now holds the ID.
+        assert_eq!(signer.client_id_order(&cid), Some(oid(&txs[0])));
+    }
+
+    #[test]
+    fn test_group_legs_checked_individually() {
+        let mut signer = Signer::new(Keypair::generate()).with_unique_client_ids();
+        let cid = Hash::from_bytes([3; 32]);
+        let err = signer
+            .sign_group(vec![with_cid(cid), with_cid(cid)], Some(1))
+            .unwrap_err();
+        assert!(matches!(err, Error::ClientIdInUse { .. }));
+        // A rejected group claims nothing.
+        assert_eq!(signer.client_id_order(&cid), None);
+
+        let other = Hash::from_bytes([4; 32]);
+        signer
+            .sign_group(vec![with_cid(cid), with_cid(other)], Some(2))
+            .unwrap();
+        assert!(signer.client_id_order(&cid).is_some());
+        assert!(signer.client_id_order(&other).is_some());
+    }
+
+    #[test]
+    fn test_open_order_cap_refusal_claims_nothing() {
+        let mut signer = Signer::new(Keypair::generate())
+            .with_unique_client_ids()
+            .with_max_open_orders_per_symbol(1);
+        signer
+            .sign(with_cid(Hash::from_bytes([5; 32])), Some(1))
+            .unwrap();
+        let cid = Hash::from_bytes([6; 32]);
+        assert!(matches!(
+            signer.sign(with_cid(cid), Some(2)),
+            Err(Error::OpenOrderCapExceeded { .. })
+        ));
+        assert_eq!(signer.client_id_order(&cid), None);
+    }
+
+    #[test]
+    fn test_cancel_by_client_id_resolves_to_live_order() {
+        let mut signer = Signer::new(Keypair::generate()).with_unique_client_ids();
+        let cid = Hash::from_bytes([8; 32]);
+        let placed = signer.sign(with_cid(cid), Some(1)).unwrap();
+
+        let by_cid = signer
+            .sign_cancel_by_client_id("BTC-USD", cid, Some(2))
+            .unwrap();
+        assert_eq!(by_cid.actions_json().unwrap()[0]["cx"]["c"], "BTC-USD");
+        assert_eq!(
+            by_cid.actions_json().unwrap()[0]["cx"]["oid"],
+            oid(&placed).to_base58()
+        );
+        assert!(by_cid.verify().unwrap());
+        // The released ID no longer resolves.
+        assert!(matches!(
+            signer.sign_cancel_by_client_id("BTC-USD", cid, Some(3)),
+            Err(Error::ClientIdNotLive(_))
+        ));
+
+        // Not the same message as a cancel naming the client ID as an order ID.
+        let literal = signer
+            .sign(Cancel::new("BTC-USD", cid).into(), Some(2))
+            .unwrap();
+        assert_ne!(literal.signature, by_cid.signature);
+    }
+
+    #[test]
+    fn test_cancel_by_client_id_in_batches_and_prepare() {
+        let mut signer = Signer::new(Keypair::generate()).with_unique_client_ids();
+        let (a, b) = (Hash::from_bytes([10; 32]), Hash::from_bytes([11; 32]));
+        let placed = signer
+            .sign_all(vec![with_cid(a), with_cid(b)], Some(1))
+            .unwrap();
+        let cancel = |cid| OrderItem::from(Cancel::by_client_id("BTC-USD", cid));
+
+        let account = *signer.account_pubkey();
+        assert!(matches!(
+            crate::prepare_all(vec![cancel(a)], &account, None, Some(5)),
+            Err(Error::UnresolvedClientIdCancel(_))
+        ));
+        let resolved = signer.resolve_client_id_cancels(vec![cancel(a)]).unwrap();
+        let prepared = crate::prepare_all(resolved, &account, None, Some(5)).unwrap();
+
+        let txs = signer.sign_all(vec![cancel(a)], Some(5)).unwrap();
+        assert_eq!(txs[0].actions_json().unwrap(), prepared[0].actions);
+        assert_eq!(
+            txs[0].actions_json().unwrap()[0]["cx"]["oid"],
+            oid(&placed[0]).to_base58()
+        );
+
+        let group = signer
+            .sign_group(vec![cancel(b), with_cid(b)], Some(6))
+            .unwrap();
+        assert_eq!(
+            group.actions_json().unwrap()[0]["cx"]["oid"],
+            oid(&placed[1]).to_base58()
+        );
+
+        let untracked = Signer::new(Keypair::generate()).sign_all(vec![cancel(a)], Some(1));
+        assert!(matches!(untracked, Err(Error::UnresolvedClientIdCancel(_))));
+    }
+
+    #[test]
+    fn test_random_client_ids_never_collide() {
+        let signer = Signer::new(Keypair::generate()).with_unique_client_ids();
+        let orders: Vec<OrderItem> = (0..200)
+            .map(|i| {
+                Order::limit("BTC-USD", true, 100.0 + i as f64, 1.0, TimeInForce::Gtc)
+                    .with_random_client_id()
+                    .into()
+            })
+            .collect();
+        signer.sign_all(orders, Some(1)).unwrap();
+        assert_eq!(signer.live_client_ids(), 200);
+    }
+
+    #[test]
+    fn test_refused_batch_can_be_resubmitted() {
+        let signer = Signer::new(Keypair::generate())
+            .with_unique_client_ids()
+            .with_parallelism(crate::Parallelism::Threads(4));
+        let mut cids: Vec<Hash> = (1..=12).map(|i| Hash::from_bytes([i; 32])).collect();
+        cids[11] = cids[0];
+        let batch = |cids: &[Hash]| cids.iter().map(|cid| with_cid(*cid)).collect();
+
+        // The earlier duplicate always wins, and the refusal releases the
+        // IDs the rest of the batch claimed.
+        let err = signer.sign_all(batch(&cids), Some(1)).unwrap_err();
+        let Error::ClientIdInUse {
+            existing_order_id, ..
+        } = err
+        else {
+            panic!("unexpected error: {err}");
+        };
+        assert_eq!(signer.live_client_ids(), 0);
+
+        cids[11] = Hash::from_bytes([12; 32]);
+        let txs = signer.sign_all(batch(&cids), Some(1)).unwrap();
+        assert_eq!(txs[0].order_id.as_ref(), Some(&existing_order_id));
+        assert_eq!(signer.live_client_ids(), 12);
+    }
+}
@@ -0,0 +1,64 @@
+//! Order commitments for commit/reveal workflows.
+//!
+//! A commitment is `SHA256(domain || bincode(action) || salt)`, where `action` is
+//! the same canonical encoding embedded in the signed message. Nonce and account
+//! are not covered, so a commitment can be published before either is known.
+
+use crate::sdk_compat::serialize_order_item;
+use crate::types::{Hash, OrderItem};
+use crate::Result;
+use sha2::{Digest, Sha256};
+
+/// Domain separator, so commitments can never collide with order IDs or messages.
+const DOMAIN: &[u8] = b"bulk-keychain:order-commitment:v1";
+
+fn commit_encoded(encoded: &[u8], salt: &[u8; 32]) -> Hash {
+    let mut hasher = Sha256::new();
+    hasher.update(DOMAIN);
+    hasher.update(encoded);
+    hasher.update(salt);
+    Hash::from_bytes(hasher.finalize().into())
+}
+
+impl OrderItem {
+    /// Commit to this item's terms under a secret `salt`.
+    ///
+    /// Fails only for items that cannot be encoded for signing.
+    pub fn commitment(&self, salt: &[u8; 32]) -> Result<Hash> {
+        let mut encoded = Vec::with_capacity(96);
+        serialize_order_item(self, &mut encoded)?;
+        Ok(commit_encoded(&encoded, salt))
+    }
+}
+
+/// Check that `item` and `salt` open `commitment`.
+pub fn verify_commitment(item: &OrderItem, salt: &[u8; 32], commitment: &Hash) -> bool {
+    item.commitment(salt).is_ok_and(|c| c == *commitment)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{prepare_message, Keypair, Order, TimeInForce};
+
+    #[test]
+    fn test_commitment_matches_prepared_message() {
+        let account = Keypair::generate().pubkey();
+        let salt = [7u8; 32];
+        let item: OrderItem = Order::limit("BTC-USD", true, 100000.0, 0.1, TimeInForce::Gtc).into();
+        let commitment = item.commitment(&salt).unwrap();
+
+        // Signed message: bincode(vec![action]) + nonce + account.
+        let prepared = prepare_message(item.clone(), &account, None, Some(5)).unwrap();
+        let bytes = &prepared.message_bytes;
+        assert_eq!(bytes[..8], 1u64.to_le_bytes());
+        let encoded = &bytes[8..bytes.len() - 8 - 32];
+        assert_eq!(commit_encoded(encoded, &salt), commitment);
+
+        assert!(verify_commitment(&item, &salt, &commitment));
+        assert!(!verify_commitment(&item, &[8u8; 32], &commitment));
+        let changed: OrderItem =
+            Order::limit("BTC-USD", true, 100001.0, 0.1, TimeInForce::Gtc).into();
+        assert!(!verify_commitment(&changed, &salt, &commitment));
+    }
+}
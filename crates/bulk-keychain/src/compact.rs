@@ -0,0 +1,834 @@
+//! The compact tagged action JSON carried by signed transactions.
+//!
+//! [`action_to_json`] renders a typed [`Action`] as the `actions` list of
+//! [`SignedTransaction`] and [`PreparedMessage`](crate::PreparedMessage), and
+//! the parsers here turn that wire JSON back into typed actions so the signed
+//! message can be rebuilt.
+
+use crate::sdk_compat::serialize_actions_for_sdk_signing;
+use crate::types::*;
+use crate::{Error, Result};
+use serde_json::{json, Value};
+
+fn invalid(message: impl Into<String>) -> Error {
+    Error::InvalidAction(message.into())
+}
+
+fn json_obj<'a>(value: &'a Value, ctx: &str) -> Result<&'a serde_json::Map<String, Value>> {
+    value
+        .as_object()
+        .ok_or_else(|| invalid(format!("{ctx} must be an object")))
+}
+
+fn json_str<'a>(obj: &'a serde_json::Map<String, Value>, key: &str) -> Result<&'a str> {
+    obj.get(key)
+        .and_then(Value::as_str)
+        .ok_or_else(|| invalid(format!("{key} is required")))
+}
+
+fn json_bool(obj: &serde_json::Map<String, Value>, key: &str, default: bool) -> Result<bool> {
+    Ok(obj.get(key).and_then(Value::as_bool).unwrap_or(default))
+}
+
+fn json_f64(obj: &serde_json::Map<String, Value>, key: &str) -> Result<f64> {
+    obj.get(key)
+        .and_then(Value::as_f64)
+        .ok_or_else(|| invalid(format!("{key} is required")))
+}
+
+fn json_u32(obj: &serde_json::Map<String, Value>, key: &str, default: Option<u32>) -> Result<u32> {
+    match obj.get(key).and_then(Value::as_u64) {
+        Some(v) => u32::try_from(v).map_err(|_| invalid(format!("{key} out of range"))),
+        None => default.ok_or_else(|| invalid(format!("{key} is required"))),
+    }
+}
+
+fn json_u64(obj: &serde_json::Map<String, Value>, key: &str, default: Option<u64>) -> Result<u64> {
+    match obj.get(key).and_then(Value::as_u64) {
+        Some(v) => Ok(v),
+        None => default.ok_or_else(|| invalid(format!("{key} is required"))),
+    }
+}
+
+fn json_pubkey(obj: &serde_json::Map<String, Value>, key: &str) -> Result<Pubkey> {
+    Pubkey::from_base58(json_str(obj, key)?)
+}
+
+fn json_hash(obj: &serde_json::Map<String, Value>, key: &str) -> Result<Hash> {
+    Hash::from_base58(json_str(obj, key)?)
+}
+
+/// Render `action` as the compact tagged JSON carried in `actions`, the
+/// inverse of [`action_from_json_list`].
+pub fn action_to_json(action: &Action) -> Result<Vec<serde_json::Value>> {
+    match action {
+        Action::Order { orders } => orders.iter().map(order_item_to_json).collect(),
+        Action::Faucet(faucet) => {
+            let mut faucet_obj = json!({ "u": faucet.user.to_base58() });
+            if let Some(amount) = faucet.amount {
+                faucet_obj["amount"] = json!(amount);
+            }
+            Ok(vec![json!({ "faucet": faucet_obj })])
+        }
+        Action::AgentWalletCreation(agent) => Ok(vec![json!({
+            "agentWalletCreation": {
+                "a": agent.agent.to_base58(),
+                "d": agent.delete
+            }
+        })]),
+        Action::UpdateUserSettings(settings) => {
+            let m: serde_json::Map<String, serde_json::Value> = settings
+                .max_leverage
+                .iter()
+                .map(|(symbol, lev)| (symbol.clone(), json!(lev)))
+                .collect();
+            Ok(vec![json!({ "updateUserSettings": { "m": m } })])
+        }
+        Action::Oracle { oracles } => Ok(oracles
+            .iter()
+            .map(|o| {
+                json!({
+                    "px": {
+                        "t": o.timestamp,
+                        "c": o.asset,
+                        "px": o.price
+                    }
+                })
+            })
+            .collect()),
+        Action::PythOracle { oracles } => {
+            let entries: Vec<_> = oracles
+                .iter()
+                .map(|o| {
+                    json!({
+                        "t": o.timestamp,
+                        "fi": o.feed_index,
+                        "px": o.price,
+                        "e": o.exponent
+                    })
+                })
+                .collect();
+            Ok(vec![json!({ "o": { "oracles": entries } })])
+        }
+        Action::WhitelistFaucet(action) => Ok(vec![json!({
+            "whitelistFaucet": {
+                "target": action.target.to_base58(),
+                "whitelist": action.whitelist
+            }
+        })]),
+        Action::CreateSubAccount(action) => {
+            let mut obj = json!({ "name": action.name });
+            if let Some(symbol) = &action.margin_symbol {
+                obj["marginSymbol"] = json!(symbol);
+            }
+            if let Some(amount) = action.margin_amount {
+                obj["marginAmount"] = json!(amount);
+            }
+            Ok(vec![json!({ "createSubAccount": obj })])
+        }
+        Action::RemoveSubAccount(action) => Ok(vec![json!({
+            "removeSubAccount": {
+                "toRemove": action.to_remove.to_base58()
+            }
+        })]),
+        Action::RenameSubAccount(action) => Ok(vec![json!({
+            "renameSubAccount": {
+                "account": action.account.to_base58(),
+                "name": action.name
+            }
+        })]),
+        Action::Transfer(transfer) => Ok(vec![json!({
+            "transfer": {
+                "k": match transfer.kind {
+                    TransferKind::Internal => "internal",
+                    TransferKind::External => "external",
+                },
+                "from": transfer.from.to_base58(),
+                "to": transfer.to.to_base58(),
+                "marginSymbol": transfer.margin_symbol,
+                "marginAmount": transfer.margin_amount,
+            }
+        })]),
+        Action::CreateMultisig(action) => Ok(vec![json!({
+            "createMultisig": {
+                "signers": action.signers.iter().map(Pubkey::to_base58).collect::<Vec<_>>(),
+                "threshold": action.threshold,
+                "timeLockSecs": action.time_lock_secs,
+                "proposalLifetimeSecs": action.proposal_lifetime_secs,
+            }
+        })]),
+        Action::MultisigPropose(action) => {
+            let mut inner_actions = Vec::new();
+            for inner in &action.actions {
+                inner_actions.extend(action_to_json(inner)?);
+            }
+            Ok(vec![json!({
+                "msp": {
+                    "m": action.multisig.to_base58(),
+                    "a": inner_actions,
+                }
+            })])
+        }
+        Action::MultisigApprove(action) => Ok(vec![json!({
+            "msa": {
+                "m": action.multisig.to_base58(),
+                "p": action.proposal_id,
+            }
+        })]),
+        Action::MultisigReject(action) => Ok(vec![json!({
+            "msr": {
+                "m": action.multisig.to_base58(),
+                "p": action.proposal_id,
+            }
+        })]),
+        Action::MultisigCancel(action) => Ok(vec![json!({
+            "msc": {
+                "m": action.multisig.to_base58(),
+                "p": action.proposal_id,
+            }
+        })]),
+        Action::MultisigExecute(action) => Ok(vec![json!({
+            "mse": {
+                "m": action.multisig.to_base58(),
+                "p": action.proposal_id,
+            }
+        })]),
+        Action::UpdateMultisigPolicy(action) => Ok(vec![json!({
+            "msu": {
+                "m": action.multisig.to_base58(),
+                "signers": action.signers.iter().map(Pubkey::to_base58).collect::<Vec<_>>(),
+                "threshold": action.threshold,
+                "timeLockSecs": action.time_lock_secs,
+                "proposalLifetimeSecs": action.proposal_lifetime_secs,
+            }
+        })]),
+    }
+}
+
+fn order_item_to_json(item: &OrderItem) -> Result<serde_json::Value> {
+    match item {
+        OrderItem::Order(order) => match &order.order_type {
+            OrderType::Limit { tif } => {
+                let tif_str = match tif {
+                    TimeInForce::Gtc => "GTC",
+                    TimeInForce::Ioc => "IOC",
+                    TimeInForce::Alo => "ALO",
+                };
+                Ok(json!({
+                    "l": {
+                        "c": order.symbol,
+                        "b": order.is_buy,
+                        "px": order.price,
+                        "sz": order.size,
+                        "tif": tif_str,
+                        "r": order.reduce_only,
+                        "i": order.iso
+                    }
+                }))
+            }
+            OrderType::Trigger {
+                is_market,
+                trigger_px: _,
+            } => {
+                if !is_market {
+                    return Err(Error::InvalidOrder(
+                        "trigger orders are not supported by BULK API; use market".to_string(),
+                    ));
+                }
+                Ok(json!({
+                    "m": {
+                        "c": order.symbol,
+                        "b": order.is_buy,
+                        "sz": order.size,
+                        "r": order.reduce_only,
+                        "i": order.iso
+                    }
+                }))
+            }
+        },
+        OrderItem::Modify(modify) => Ok(json!({
+            "mod": {
+                "oid": modify.order_id.to_base58(),
+                "c": modify.symbol,
+                "sz": modify.amount
+            }
+        })),
+        OrderItem::Cancel(cancel) => Ok(json!({
+            "cx": {
+                "c": cancel.symbol,
+                "oid": cancel.order_id.to_base58()
+            }
+        })),
+        OrderItem::CancelByClientId(cancel) => Err(Error::UnresolvedClientIdCancel(
+            cancel.client_id.to_base58(),
+        )),
+        OrderItem::CancelAll(cancel_all) => Ok(json!({
+            "cxa": {
+                "c": cancel_all.symbols
+            }
+        })),
+        OrderItem::Stop(stop) => Ok(json!({
+            "st": {
+                "c": stop.symbol,
+                "d": stop.is_buy,
+                "sz": stop.size,
+                "tr": stop.trigger_price,
+                "lim": stop.limit_price,
+                "i": stop.iso
+            }
+        })),
+        OrderItem::TakeProfit(tp) => Ok(json!({
+            "tp": {
+                "c": tp.symbol,
+                "d": tp.is_buy,
+                "sz": tp.size,
+                "tr": tp.trigger_price,
+                "lim": tp.limit_price,
+                "i": tp.iso
+            }
+        })),
+        OrderItem::RangeOco(rng) => Ok(json!({
+            "rng": {
+                "c": rng.symbol,
+                "d": rng.is_buy,
+                "sz": rng.size,
+                "pmin": rng.collar_min,
+                "pmax": rng.collar_max,
+                "lmin": rng.limit_min,
+                "lmax": rng.limit_max,
+                "i": rng.iso
+            }
+        })),
+        OrderItem::TriggerBasket(trig) => {
+            let nested: Result<Vec<_>> = trig.actions.iter().map(order_item_to_json).collect();
+            Ok(json!({
+                "trig": {
+                    "c": trig.symbol,
+                    "d": trig.is_buy,
+                    "tr": trig.trigger_price,
+                    "actions": nested?,
+                    "i": trig.iso
+                }
+            }))
+        }
+        OrderItem::OnFill(of) => {
+            let actions: Result<Vec<_>> = of.actions.iter().map(order_item_to_json).collect();
+            Ok(json!({
+                "of": {
+                    "p": of.p,
+                    "actions": actions?
+                }
+            }))
+        }
+        OrderItem::TrailingStop(trl) => Ok(json!({
+            "trl": {
+                "c": trl.symbol,
+                "b": trl.is_buy,
+                "sz": trl.size,
+                "trb": trl.trail_bps,
+                "stb": trl.step_bps,
+                "lim": trl.limit_price,
+                "i": trl.iso
+            }
+        })),
+    }
+}
+
+/// Parse a single compact order item (`{"l": {...}}`, `{"cx": {...}}`, ...).
+pub fn order_item_from_json(value: &Value) -> Result<OrderItem> {
+    let obj = json_obj(value, "order item")?;
+    let (tag, payload) = obj
+        .iter()
+        .next()
+        .ok_or_else(|| invalid("order item cannot be empty"))?;
+
+    if obj.len() != 1 {
+        return Err(invalid("order item must have exactly one top-level key"));
+    }
+
+    match tag.as_str() {
+        "l" => {
+            let p = json_obj(payload, "l")?;
+            let tif = match json_str(p, "tif")?.to_uppercase().as_str() {
+                "GTC" => TimeInForce::Gtc,
+                "IOC" => TimeInForce::Ioc,
+                "ALO" => TimeInForce::Alo,
+                other => return Err(invalid(format!("invalid tif: {other}"))),
+            };
+            Ok(OrderItem::Order(Order {
+                symbol: json_str(p, "c")?.to_string(),
+                is_buy: json_bool(p, "b", false)?,
+                price: json_f64(p, "px")?,
+                size: json_f64(p, "sz")?,
+                reduce_only: json_bool(p, "r", false)?,
+                iso: json_bool(p, "i", false)?,
+                order_type: OrderType::limit(tif),
+                client_id: p
+                    .get("cloid")
+                    .and_then(Value::as_str)
+                    .map(Hash::from_base58)
+                    .transpose()?,
+            }))
+        }
+        "m" => {
+            let p = json_obj(payload, "m")?;
+            Ok(OrderItem::Order(Order {
+                symbol: json_str(p, "c")?.to_string(),
+                is_buy: json_bool(p, "b", false)?,
+                price: 0.0,
+                size: json_f64(p, "sz")?,
+                reduce_only: json_bool(p, "r", false)?,
+                iso: json_bool(p, "i", false)?,
+                order_type: OrderType::market(),
+                client_id: None,
+            }))
+        }
+        "cx" => {
+            let p = json_obj(payload, "cx")?;
+            Ok(OrderItem::Cancel(Cancel::new(
+                json_str(p, "c")?.to_string(),
+                json_hash(p, "oid")?,
+            )))
+        }
+        "mod" => {
+            let p = json_obj(payload, "mod")?;
+            Ok(OrderItem::Modify(Modify::new(
+                json_hash(p, "oid")?,
+                json_str(p, "c")?.to_string(),
+                json_f64(p, "sz")?,
+            )))
+        }
+        "cxa" => {
+            let p = json_obj(payload, "cxa")?;
+            let symbols = p
+                .get("c")
+                .and_then(Value::as_array)
+                .ok_or_else(|| invalid("c is required"))?
+                .iter()
+                .map(|v| {
+                    v.as_str()
+                        .map(ToOwned::to_owned)
+                        .ok_or_else(|| invalid("symbol must be a string"))
+                })
+                .collect::<Result<Vec<_>>>()?;
+            Ok(OrderItem::CancelAll(CancelAll::for_symbols(symbols)))
+        }
+        "st" => {
+            let p = json_obj(payload, "st")?;
+            Ok(OrderItem::Stop(Stop {
+                symbol: json_str(p, "c")?.to_string(),
+                is_buy: json_bool(p, "d", false)?,
+                size: json_f64(p, "sz")?,
+                trigger_price: json_f64(p, "tr")?,
+                limit_price: p.get("lim").and_then(Value::as_f64).unwrap_or(f64::NAN),
+                iso: json_bool(p, "i", false)?,
+            }))
+        }
+        "tp" => {
+            let p = json_obj(payload, "tp")?;
+            Ok(OrderItem::TakeProfit(TakeProfit {
+                symbol: json_str(p, "c")?.to_string(),
+                is_buy: json_bool(p, "d", false)?,
+                size: json_f64(p, "sz")?,
+                trigger_price: json_f64(p, "tr")?,
+                limit_price: p.get("lim").and_then(Value::as_f64).unwrap_or(f64::NAN),
+                iso: json_bool(p, "i", false)?,
+            }))
+        }
+        "rng" => {
+            let p = json_obj(payload, "rng")?;
+            Ok(OrderItem::RangeOco(RangeOco {
+                symbol: json_str(p, "c")?.to_string(),
+                is_buy: json_bool(p, "d", false)?,
+                size: json_f64(p, "sz")?,
+                collar_min: json_f64(p, "pmin")?,
+                collar_max: json_f64(p, "pmax")?,
+                limit_min: p.get("lmin").and_then(Value::as_f64).unwrap_or(f64::NAN),
+                limit_max: p.get("lmax").and_then(Value::as_f64).unwrap_or(f64::NAN),
+                iso: json_bool(p, "i", false)?,
+            }))
+        }
+        "trig" => {
+            let p = json_obj(payload, "trig")?;
+            let nested = p
+                .get("actions")
+                .and_then(Value::as_array)
+                .ok_or_else(|| invalid("actions is required"))?
+                .iter()
+                .map(order_item_from_json)
+                .collect::<Result<Vec<_>>>()?;
+            Ok(OrderItem::TriggerBasket(TriggerBasket {
+                symbol: json_str(p, "c")?.to_string(),
+                is_buy: json_bool(p, "d", false)?,
+                trigger_price: json_f64(p, "tr")?,
+                actions: nested,
+                iso: json_bool(p, "i", false)?,
+            }))
+        }
+        "of" => {
+            let p = json_obj(payload, "of")?;
+            let nested = p
+                .get("actions")
+                .and_then(Value::as_array)
+                .ok_or_else(|| invalid("actions is required"))?
+                .iter()
+                .map(order_item_from_json)
+                .collect::<Result<Vec<_>>>()?;
+            Ok(OrderItem::OnFill(OnFill {
+                p: json_u32(p, "p", Some(0))?,
+                actions: nested,
+            }))
+        }
+        "trl" => {
+            let p = json_obj(payload, "trl")?;
+            Ok(OrderItem::TrailingStop(TrailingStop {
+                symbol: json_str(p, "c")?.to_string(),
+                is_buy: json_bool(p, "b", false)?,
+                size: json_f64(p, "sz")?,
+                trail_bps: json_u32(p, "trb", None)?,
+                step_bps: json_u32(p, "stb", None)?,
+                limit_price: p.get("lim").and_then(Value::as_f64),
+                iso: json_bool(p, "i", false)?,
+            }))
+        }
+        other => Err(invalid(format!("unsupported order item type: {other}"))),
+    }
+}
+
+/// Parse a single compact action.
+///
+/// Bare order item tags become a one-item [`Action::Order`].
+pub fn action_from_json(value: &Value) -> Result<Action> {
+    let obj = json_obj(value, "action")?;
+    let (tag, payload) = obj
+        .iter()
+        .next()
+        .ok_or_else(|| invalid("action cannot be empty"))?;
+
+    if obj.len() != 1 {
+        return Err(invalid("action must have exactly one top-level key"));
+    }
+
+    match tag.as_str() {
+        "l" | "m" | "cx" | "mod" | "cxa" | "st" | "tp" | "rng" | "trig" | "of" | "trl" => {
+            Ok(Action::Order {
+                orders: vec![order_item_from_json(value)?],
+            })
+        }
+        "order" => {
+            let orders = match payload {
+                Value::Array(items) => items
+                    .iter()
+                    .map(order_item_from_json)
+                    .collect::<Result<Vec<_>>>()?,
+                _ => vec![order_item_from_json(payload)?],
+            };
+            Ok(Action::Order { orders })
+        }
+        "faucet" => {
+            let p = json_obj(payload, "faucet")?;
+            let mut faucet = Faucet::new(json_pubkey(p, "u").or_else(|_| json_pubkey(p, "user"))?);
+            faucet.amount = p.get("amount").and_then(Value::as_f64);
+            Ok(Action::Faucet(faucet))
+        }
+        "agentWalletCreation" => {
+            let p = json_obj(payload, "agentWalletCreation")?;
+            Ok(Action::AgentWalletCreation(AgentWallet {
+                agent: json_pubkey(p, "a").or_else(|_| json_pubkey(p, "agent"))?,
+                delete: json_bool(p, "d", false).or_else(|_| json_bool(p, "delete", false))?,
+            }))
+        }
+        "updateUserSettings" => {
+            let p = json_obj(payload, "updateUserSettings")?;
+            let leverage_map = p
+                .get("m")
+                .or_else(|| p.get("maxLeverage"))
+                .and_then(Value::as_object)
+                .ok_or_else(|| invalid("m is required"))?;
+            let max_leverage = leverage_map
+                .iter()
+                .map(|(k, v)| {
+                    v.as_f64()
+                        .map(|lev| (k.clone(), lev))
+                        .ok_or_else(|| invalid("max leverage values must be numbers"))
+                })
+                .collect::<Result<Vec<_>>>()?;
+            Ok(Action::UpdateUserSettings(UserSettings::new(max_leverage)))
+        }
+        "px" => {
+            let p = json_obj(payload, "px")?;
+            Ok(Action::Oracle {
+                oracles: vec![OraclePrice {
+                    timestamp: json_u64(p, "t", None)?,
+                    asset: json_str(p, "c")?.to_string(),
+                    price: json_f64(p, "px")?,
+                }],
+            })
+        }
+        "o" => {
+            let p = json_obj(payload, "o")?;
+            let entries = p
+                .get("oracles")
+                .and_then(Value::as_array)
+                .ok_or_else(|| invalid("oracles is required"))?;
+            let oracles = entries
+                .iter()
+                .map(|entry| {
+                    let e = json_obj(entry, "oracle")?;
+                    Ok(PythOraclePrice {
+                        timestamp: json_u64(e, "t", None)?,
+                        feed_index: json_u64(e, "fi", None)?,
+                        price: json_u64(e, "px", None)?,
+                        exponent: e
+                            .get("e")
+                            .and_then(Value::as_i64)
+                            .and_then(|v| i16::try_from(v).ok())
+                            .ok_or_else(|| invalid("e is required"))?,
+                    })
+                })
+                .collect::<Result<Vec<_>>>()?;
+            Ok(Action::PythOracle { oracles })
+        }
+        "whitelistFaucet" => {
+            let p = json_obj(payload, "whitelistFaucet")?;
+            Ok(Action::WhitelistFaucet(WhitelistFaucet {
+                target: json_pubkey(p, "target")?,
+                whitelist: json_bool(p, "whitelist", false)?,
+            }))
+        }
+        "createSubAccount" => {
+            let p = json_obj(payload, "createSubAccount")?;
+            Ok(Action::CreateSubAccount(CreateSubAccount {
+                name: json_str(p, "name")?.to_string(),
+                margin_symbol: p
+                    .get("marginSymbol")
+                    .and_then(Value::as_str)
+                    .map(ToOwned::to_owned),
+                margin_amount: p.get("marginAmount").and_then(Value::as_f64),
+            }))
+        }
+        "removeSubAccount" => {
+            let p = json_obj(payload, "removeSubAccount")?;
+            Ok(Action::RemoveSubAccount(RemoveSubAccount {
+                to_remove: json_pubkey(p, "toRemove")?,
+            }))
+        }
+        "renameSubAccount" => {
+            let p = json_obj(payload, "renameSubAccount")?;
+            Ok(Action::RenameSubAccount(RenameSubAccount {
+                account: json_pubkey(p, "account")?,
+                name: json_str(p, "name")?.to_string(),
+            }))
+        }
+        "transfer" => {
+            let p = json_obj(payload, "transfer")?;
+            let kind = match p.get("k").and_then(Value::as_str).unwrap_or("internal") {
+                "internal" => TransferKind::Internal,
+                "external" => TransferKind::External,
+                other => return Err(invalid(format!("invalid transfer kind: {other}"))),
+            };
+            Ok(Action::Transfer(Transfer {
+                kind,
+                from: json_pubkey(p, "from")?,
+                to: json_pubkey(p, "to")?,
+                margin_symbol: json_str(p, "marginSymbol")?.to_string(),
+                margin_amount: json_f64(p, "marginAmount")?,
+            }))
+        }
+        "createMultisig" => {
+            let p = json_obj(payload, "createMultisig")?;
+            let signers = p
+                .get("signers")
+                .and_then(Value::as_array)
+                .ok_or_else(|| invalid("signers is required"))?
+                .iter()
+                .map(|v| {
+                    v.as_str()
+                        .ok_or_else(|| invalid("signer must be a string"))
+                        .and_then(Pubkey::from_base58)
+                })
+                .collect::<Result<Vec<_>>>()?;
+            Ok(Action::CreateMultisig(CreateMultisig {
+                signers,
+                threshold: json_u32(p, "threshold", None)?,
+                time_lock_secs: json_u32(p, "timeLockSecs", Some(0))?,
+                proposal_lifetime_secs: json_u32(p, "proposalLifetimeSecs", Some(7 * 24 * 3600))?,
+            }))
+        }
+        "msp" | "multisigPropose" => {
+            let p = json_obj(payload, tag)?;
+            let multisig = json_pubkey(p, "m").or_else(|_| json_pubkey(p, "multisig"))?;
+            let raw_actions = p
+                .get("a")
+                .or_else(|| p.get("actions"))
+                .and_then(Value::as_array)
+                .ok_or_else(|| invalid("actions is required"))?;
+            let actions = raw_actions
+                .iter()
+                .map(action_from_json)
+                .collect::<Result<Vec<_>>>()?;
+            Ok(Action::MultisigPropose(MultisigPropose::new(
+                multisig, actions,
+            )))
+        }
+        "msa" | "multisigApprove" => {
+            let p = json_obj(payload, tag)?;
+            Ok(Action::MultisigApprove(MultisigApprove::new(
+                json_pubkey(p, "m").or_else(|_| json_pubkey(p, "multisig"))?,
+                json_u64(p, "p", p.get("proposalId").and_then(Value::as_u64))?,
+            )))
+        }
+        "msr" | "multisigReject" => {
+            let p = json_obj(payload, tag)?;
+            Ok(Action::MultisigReject(MultisigReject::new(
+                json_pubkey(p, "m").or_else(|_| json_pubkey(p, "multisig"))?,
+                json_u64(p, "p", p.get("proposalId").and_then(Value::as_u64))?,
+            )))
+        }
+        "msc" | "multisigCancel" => {
+            let p = json_obj(payload, tag)?;
+            Ok(Action::MultisigCancel(MultisigCancel::new(
+                json_pubkey(p, "m").or_else(|_| json_pubkey(p, "multisig"))?,
+                json_u64(p, "p", p.get("proposalId").and_then(Value::as_u64))?,
+            )))
+        }
+        "mse" | "multisigExecute" => {
+            let p = json_obj(payload, tag)?;
+            Ok(Action::MultisigExecute(MultisigExecute::new(
+                json_pubkey(p, "m").or_else(|_| json_pubkey(p, "multisig"))?,
+                json_u64(p, "p", p.get("proposalId").and_then(Value::as_u64))?,
+            )))
+        }
+        "msu" | "updateMultisigPolicy" => {
+            let p = json_obj(payload, tag)?;
+            let signers = p
+                .get("signers")
+                .and_then(Value::as_array)
+                .ok_or_else(|| invalid("signers is required"))?
+                .iter()
+                .map(|v| {
+                    v.as_str()
+                        .ok_or_else(|| invalid("signer must be a string"))
+                        .and_then(Pubkey::from_base58)
+                })
+                .collect::<Result<Vec<_>>>()?;
+            Ok(Action::UpdateMultisigPolicy(UpdateMultisigPolicy {
+                multisig: json_pubkey(p, "m").or_else(|_| json_pubkey(p, "multisig"))?,
+                signers,
+                threshold: json_u32(p, "threshold", None)?,
+                time_lock_secs: json_u32(p, "timeLockSecs", Some(0))?,
+                proposal_lifetime_secs: json_u32(p, "proposalLifetimeSecs", Some(7 * 24 * 3600))?,
+            }))
+        }
+        _ => Err(invalid(format!("unsupported action type: {tag}"))),
+    }
+}
+
+/// Parse a list of compact actions, as found on a signed transaction.
+pub fn actions_from_json(values: &[Value]) -> Result<Vec<Action>> {
+    values.iter().map(action_from_json).collect()
+}
+
+/// Parse the actions of one transaction back into the single [`Action`] that was signed.
+///
+/// Order items are regrouped into one [`Action::Order`] and oracle prices into
+/// one [`Action::Oracle`]; any other action must stand alone.
+pub fn action_from_json_list(values: &[Value]) -> Result<Action> {
+    merge_actions(actions_from_json(values)?)
+}
+
+/// Regroup per-entry actions into the single [`Action`] they were split from.
+pub(crate) fn merge_actions(actions: Vec<Action>) -> Result<Action> {
+    let mut actions = actions.into_iter();
+    let mut merged = actions.next().ok_or(Error::EmptyOrders)?;
+    for next in actions {
+        match (&mut merged, next) {
+            (Action::Order { orders }, Action::Order { orders: more }) => orders.extend(more),
+            (Action::Oracle { oracles }, Action::Oracle { oracles: more }) => oracles.extend(more),
+            _ => {
+                return Err(invalid(
+                    "actions of different kinds cannot share a transaction",
+                ))
+            }
+        }
+    }
+    Ok(merged)
+}
+
+/// Rebuild the canonical message bytes that were signed for `actions`.
+pub fn message_bytes_from_json(values: &[Value], nonce: u64, account: &Pubkey) -> Result<Vec<u8>> {
+    let actions = actions_from_json(values)?;
+    let mut out = Vec::with_capacity(256);
+    serialize_actions_for_sdk_signing(&actions, nonce, account, &mut out)?;
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{prepare_action, Keypair};
+
+    #[test]
+    fn test_message_bytes_roundtrip() {
+        let account = Keypair::generate().pubkey();
+        let other = Keypair::generate().pubkey();
+        let stop = OrderItem::Stop(Stop {
+            symbol: "BTC-USD".to_string(),
+            is_buy: false,
+            size: 0.1,
+            trigger_price: 95000.0,
+            limit_price: f64::NAN,
+            iso: true,
+        });
+        let actions = vec![
+            Action::Order {
+                orders: vec![
+                    Order::limit("BTC-USD", true, 100000.0, 0.1, TimeInForce::Alo).into(),
+                    Order::market("ETH-USD", false, 2.0).into(),
+                    OrderItem::Modify(Modify::new(Hash::random(), "BTC-USD", 0.2)),
+                    OrderItem::CancelAll(CancelAll::for_symbols(vec!["SOL-USD".to_string()])),
+                    OrderItem::TriggerBasket(TriggerBasket {
+                        symbol: "BTC-USD".to_string(),
+                        is_buy: true,
+                        trigger_price: 90000.0,
+                        actions: vec![stop.clone()],
+                        iso: false,
+                    }),
+                    OrderItem::OnFill(OnFill {
+                        p: 0,
+                        actions: vec![stop],
+                    }),
+                    OrderItem::TrailingStop(TrailingStop {
+                        symbol: "BTC-USD".to_string(),
+                        is_buy: true,
+                        size: 0.1,
+                        trail_bps: 50,
+                        step_bps: 10,
+                        limit_price: Some(99000.0),
+                        iso: false,
+                    }),
+                ],
+            },
+            Action::Transfer(Transfer::internal(account, other, "USDC", 10.5)),
+            Action::MultisigPropose(MultisigPropose::new(
+                other,
+                vec![Action::AgentWalletCreation(AgentWallet {
+                    agent: other,
+                    delete: false,
+                })],
+            )),
+        ];
+
+        for action in &actions {
+            let prepared = prepare_action(action, &account, None, Some(77)).unwrap();
+            let rebuilt = message_bytes_from_json(&prepared.actions, 77, &account).unwrap();
+            assert_eq!(rebuilt, prepared.message_bytes);
+        }
+    }
+
+    #[test]
+    fn test_rejects_unknown_tags() {
+        let err = action_from_json(&serde_json::json!({ "nope": {} })).unwrap_err();
+        assert!(matches!(err, Error::InvalidAction(_)));
+    }
+}
@@ -0,0 +1,166 @@
+//! Historical serialization compatibility
+//!
+//! Verification rebuilds the signed message from a transaction's action,
+//! which deserializing a record parses from its compact `actions` JSON. Any
+//! change to how actions are encoded would make old records stop verifying,
+//! so each such change adds a [`CompatVersion`] and an entry to [`REGISTRY`]
+//! that keeps rebuilding bytes the old way, and the parser must keep
+//! accepting the old JSON; the fixtures in this module's tests pin every
+//! version's output.
+
+use crate::sdk_compat::serialize_for_sdk_signing;
+use crate::{Pubkey, Result, SignedTransaction};
+use ed25519_dalek::{Signature, VerifyingKey};
+
+/// Serialization behavior a transaction was signed under.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum CompatVersion {
+    /// Compact tagged actions, wincode body, `nonce` LE and account bytes
+    V1,
+}
+
+impl CompatVersion {
+    /// Behavior of transactions signed by this crate version.
+    pub const CURRENT: Self = Self::V1;
+
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            Self::V1 => "v1",
+        }
+    }
+
+    /// Registry entry describing this version.
+    pub fn entry(&self) -> &'static CompatEntry {
+        REGISTRY
+            .iter()
+            .find(|e| e.version == *self)
+            .expect("every version is registered")
+    }
+
+    /// Rebuild the message `tx` was signed over under this version.
+    pub fn message_bytes(&self, tx: &SignedTransaction) -> Result<Vec<u8>> {
+        (self.entry().message_bytes)(tx)
+    }
+}
+
+/// One historical serialization behavior.
+pub struct CompatEntry {
+    pub version: CompatVersion,
+    /// First crate release that signed this way
+    pub since: &'static str,
+    pub description: &'static str,
+    message_bytes: fn(&SignedTransaction) -> Result<Vec<u8>>,
+}
+
+impl std::fmt::Debug for CompatEntry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CompatEntry")
+            .field("version", &self.version)
+            .field("since", &self.since)
+            .field("description", &self.description)
+            .finish()
+    }
+}
+
+/// Every known serialization behavior, newest first.
+pub static REGISTRY: &[CompatEntry] = &[CompatEntry {
+    version: CompatVersion::V1,
+    since: "0.1.0",
+    description: "compact tagged actions serialized with the BULK SDK wincode layout",
+    message_bytes: v1_message_bytes,
+}];
+
+fn v1_message_bytes(tx: &SignedTransaction) -> Result<Vec<u8>> {
+    let account = Pubkey::from_base58(&tx.account)?;
+    let mut out = Vec::with_capacity(256);
+    serialize_for_sdk_signing(&tx.action, tx.nonce, &account, &mut out)?;
+    Ok(out)
+}
+
+/// Best guess at the version `tx` was signed under: the newest one whose
+/// action schema accepts every action. `None` if no version understands them.
+pub fn detect_compat_version(tx: &SignedTransaction) -> Option<CompatVersion> {
+    REGISTRY
+        .iter()
+        .find(|e| (e.message_bytes)(tx).is_ok())
+        .map(|e| e.version)
+}
+
+impl SignedTransaction {
+    /// Check the signature against `tx.signer` over the current serialization.
+    pub fn verify(&self) -> Result<bool> {
+        self.verify_with(CompatVersion::CURRENT)
+    }
+
+    /// Check the signature against `tx.signer`, rebuilding the message the
+    /// way `version` did.
+    pub fn verify_with(&self, version: CompatVersion) -> Result<bool> {
+        let message = version.message_bytes(self)?;
+        let signer = self.signer_pubkey()?;
+        let Ok(key) = VerifyingKey::from_bytes(signer.as_bytes()) else {
+            return Ok(false);
+        };
+        let bytes = self.signature_bytes()?;
+        Ok(key
+            .verify_strict(&message, &Signature::from_bytes(&bytes))
+            .is_ok())
+    }
+
+    /// Verify under the detected version, then every other registered one;
+    /// returns the version that verified, if any. For archived records whose
+    /// signing version is unknown.
+    pub fn verify_any_version(&self) -> Result<Option<CompatVersion>> {
+        let detected = detect_compat_version(self);
+        let candidates = detected.into_iter().chain(
+            REGISTRY
+                .iter()
+                .map(|e| e.version)
+                .filter(|v| Some(*v) != detected),
+        );
+        for version in candidates {
+            if matches!(self.verify_with(version), Ok(true)) {
+                return Ok(Some(version));
+            }
+        }
+        Ok(None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Signed by 0.1.15 with secret key `[7; 32]`. Never regenerate: this
+    /// pins what V1 rebuilds for records already in archives.
+    const V1_FIXTURE: &str = r#"{"actions":[{"l":{"b":true,"c":"BTC-USD","i":false,"px":100000.5,"r":false,"sz":0.1,"tif":"GTC"}},{"cx":{"c":"BTC-USD","oid":"4vJ9JU1bJJE96FWSJKvHsmmFADCg4gpZQff4P3bkLKi"}}],"nonce":1700000000000,"account":"GmaDrppBC7P5ARKV8g3djiwP89vz1jLK23V2GBjuAEGB","signer":"GmaDrppBC7P5ARKV8g3djiwP89vz1jLK23V2GBjuAEGB","signature":"3QWLmru1hsDV1zcmqA1QsMXRteTjaVPE4Y5EV4e4iscGh81bW1FUPBR5tYiTNQNEuALvGHS6hwZacDcUrj7GoTiy"}"#;
+
+    fn v1_fixture() -> SignedTransaction {
+        serde_json::from_str(V1_FIXTURE).unwrap()
+    }
+
+    #[test]
+    fn test_v1_fixture_verifies() {
+        let tx = v1_fixture();
+        assert_eq!(detect_compat_version(&tx), Some(CompatVersion::V1));
+        assert!(tx.verify_with(CompatVersion::V1).unwrap());
+        assert!(tx.verify().unwrap());
+        assert_eq!(tx.verify_any_version().unwrap(), Some(CompatVersion::V1));
+    }
+
+    #[test]
+    fn test_tampered_fixture_fails() {
+        let mut tx = v1_fixture();
+        tx.nonce += 1;
+        assert!(!tx.verify().unwrap());
+        assert_eq!(tx.verify_any_version().unwrap(), None);
+
+        let unknown = V1_FIXTURE.replacen(r#"{"cx":"#, r#"{"unknown":"#, 1);
+        assert!(serde_json::from_str::<SignedTransaction>(&unknown).is_err());
+    }
+
+    #[test]
+    fn test_registry_covers_every_version() {
+        assert_eq!(REGISTRY[0].version, CompatVersion::CURRENT);
+        assert_eq!(CompatVersion::V1.entry().since, "0.1.0");
+    }
+}
@@ -0,0 +1,142 @@
+//! What a cancel-replace would change
+//!
+//! [`diff_orders`] compares a live order with its proposed replacement so the
+//! change can be shown or logged before it is signed. A flipped side is almost
+//! always a bug, so [`OrderDiff::check`] refuses it unless forced; the
+//! cancel-replace helpers ([`Signer::sign_cancel_replace`],
+//! [`prepare_checked_cancel_replace`]) run that check for you.
+//!
+//! [`Signer::sign_cancel_replace`]: crate::Signer::sign_cancel_replace
+//! [`prepare_checked_cancel_replace`]: crate::prepare::prepare_checked_cancel_replace
+
+use crate::types::Order;
+use crate::{Error, Result, SymbolSpec};
+use serde::Serialize;
+
+/// Field-by-field difference between an order and its replacement.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OrderDiff {
+    /// Replacement price minus original price
+    pub price_delta: f64,
+    /// `price_delta` in whole ticks; `None` without a [`SymbolSpec`]
+    pub price_delta_ticks: Option<i64>,
+    /// Replacement size minus original size
+    pub size_delta: f64,
+    /// Order type or time-in-force differs
+    pub tif_changed: bool,
+    /// Buy became sell or vice versa
+    pub side_changed: bool,
+    /// Reduce-only or isolated-margin flag differs
+    pub flags_changed: bool,
+}
+
+impl OrderDiff {
+    /// True if the replacement is identical in every compared field.
+    pub fn is_empty(&self) -> bool {
+        self.price_delta == 0.0
+            && self.size_delta == 0.0
+            && !self.tif_changed
+            && !self.side_changed
+            && !self.flags_changed
+    }
+
+    /// Refuse a side change unless `force_side_change` is set.
+    pub fn check(&self, force_side_change: bool) -> Result<()> {
+        if self.side_changed && !force_side_change {
+            return Err(Error::InvalidOrder(
+                "replacement flips the order side; force the side change if intended".into(),
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Compare `original` with `replacement`.
+///
+/// With a `spec`, the price delta is also expressed in ticks: each price is
+/// rounded to its nearest tick, so an off-grid price still gives a whole count.
+///
+/// ```rust
+/// use bulk_keychain::{diff_orders, Order, SymbolSpec, TimeInForce};
+///
+/// let live = Order::limit("BTC-USD", true, 100000.0, 0.1, TimeInForce::Gtc);
+/// let next = Order::limit("BTC-USD", true, 100000.5, 0.2, TimeInForce::Gtc);
+/// let diff = diff_orders(&live, &next, Some(&SymbolSpec::new(1, 3)));
+/// assert_eq!(diff.price_delta_ticks, Some(5));
+/// assert!(!diff.side_changed && !diff.tif_changed);
+/// assert!(diff.check(false).is_ok());
+/// ```
+pub fn diff_orders(original: &Order, replacement: &Order, spec: Option<&SymbolSpec>) -> OrderDiff {
+    let price_delta_ticks = spec.map(|spec| {
+        let tick = spec.tick_units as f64 / 10f64.powi(spec.price_decimals as i32);
+        (replacement.price / tick).round() as i64 - (original.price / tick).round() as i64
+    });
+    OrderDiff {
+        price_delta: replacement.price - original.price,
+        price_delta_ticks,
+        size_delta: replacement.size - original.size,
+        tif_changed: replacement.order_type != original.order_type,
+        side_changed: replacement.is_buy != original.is_buy,
+        flags_changed: replacement.reduce_only != original.reduce_only
+            || replacement.iso != original.iso,
+    }
+}
+
+/// Diff and check a cancel-replace, also refusing a change of market.
+pub(crate) fn check_replacement(
+    original: &Order,
+    replacement: &Order,
+    force_side_change: bool,
+) -> Result<()> {
+    if original.symbol != replacement.symbol {
+        return Err(Error::InvalidOrder(format!(
+            "replacement is on {}, original on {}",
+            replacement.symbol, original.symbol
+        )));
+    }
+    diff_orders(original, replacement, None).check(force_side_change)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::TimeInForce;
+
+    fn limit(is_buy: bool, price: f64, size: f64) -> Order {
+        Order::limit("ETH-USD", is_buy, price, size, TimeInForce::Gtc)
+    }
+
+    #[test]
+    fn test_tick_math() {
+        let spec = SymbolSpec::new(2, 2).with_tick_units(5);
+        let diff = diff_orders(
+            &limit(true, 2000.0, 1.0),
+            &limit(true, 1999.85, 1.5),
+            Some(&spec),
+        );
+        assert_eq!(diff.price_delta_ticks, Some(-3));
+        assert!((diff.price_delta + 0.15).abs() < 1e-9);
+        assert_eq!(diff.size_delta, 0.5);
+        assert_eq!(
+            diff_orders(&limit(true, 2000.0, 1.0), &limit(true, 2000.0, 1.0), None)
+                .price_delta_ticks,
+            None
+        );
+    }
+
+    #[test]
+    fn test_side_and_flag_changes() {
+        let original = limit(true, 2000.0, 1.0);
+        assert!(diff_orders(&original, &original, None).is_empty());
+
+        let flipped = diff_orders(&original, &limit(false, 2000.0, 1.0), None);
+        assert!(flipped.side_changed && !flipped.is_empty());
+        assert!(flipped.check(false).is_err());
+        assert!(flipped.check(true).is_ok());
+
+        let ioc = Order::limit("ETH-USD", true, 2000.0, 1.0, TimeInForce::Ioc);
+        let diff = diff_orders(&original, &ioc.reduce_only(), None);
+        assert!(diff.tif_changed && diff.flags_changed && !diff.side_changed);
+    }
+}
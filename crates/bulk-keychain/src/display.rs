@@ -0,0 +1,236 @@
+//! Displayed vs signed prices
+//!
+//! Frontends often show prices with fewer decimals than get signed, so a
+//! confirmation screen can read `100,000` while the order signs `100000.05`.
+//! A [`DisplayPolicy`] says how many decimals each symbol shows;
+//! [`Order::describe`](crate::Order::describe) renders with it, and
+//! [`confirm_price_matches`] is the check to run right before asking the
+//! wallet for a signature: the displayed string must be the signed price, and
+//! the policy must not round the signed price away.
+
+use crate::format::{normalize, parse_grouped, round_decimal};
+use crate::types::{Order, OrderType};
+use std::collections::HashMap;
+use thiserror::Error;
+
+/// Decimals shown per symbol; symbols without an entry show the exact price.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DisplayPolicy {
+    pub max_decimals_per_symbol: HashMap<String, u32>,
+}
+
+impl DisplayPolicy {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_max_decimals(mut self, symbol: impl Into<String>, decimals: u32) -> Self {
+        self.max_decimals_per_symbol.insert(symbol.into(), decimals);
+        self
+    }
+
+    pub fn max_decimals(&self, symbol: &str) -> Option<u32> {
+        self.max_decimals_per_symbol.get(symbol).copied()
+    }
+
+    /// `price` as shown for `symbol`: rounded half away from zero to the
+    /// symbol's decimals, trailing zeros dropped.
+    pub fn format_price(&self, symbol: &str, price: f64) -> String {
+        let exact = price.to_string();
+        match self.max_decimals(symbol) {
+            Some(decimals) => round_decimal(&exact, decimals),
+            None => exact,
+        }
+    }
+}
+
+/// Why a displayed price cannot be confirmed against the signed order.
+#[derive(Debug, Clone, PartialEq, Error)]
+pub enum DisplayMismatch {
+    /// Not a plain decimal; only `,` as a thousands separator is accepted
+    #[error("cannot read displayed price {displayed:?}: {reason}")]
+    Unreadable { displayed: String, reason: String },
+    /// The displayed value is not the signed value
+    #[error("displayed price {displayed} on {symbol} but the order signs {signed}")]
+    Differs {
+        symbol: String,
+        displayed: String,
+        signed: String,
+    },
+    /// The policy would show the signed price as a different value
+    #[error(
+        "display policy shows {symbol} with {max_decimals} decimals, \
+         so signed price {signed} would be shown as {shown}"
+    )]
+    PolicyRounds {
+        symbol: String,
+        max_decimals: u32,
+        signed: String,
+        shown: String,
+    },
+}
+
+/// Check that `displayed` is exactly the price `order` signs, and that
+/// `policy` shows that price without rounding it.
+///
+/// `displayed` may use `,` thousands separators (`100,000.05`); a decimal
+/// comma (`100000,05`), spaces or other grouping are rejected rather than
+/// guessed at. Market orders must be displayed as `MARKET`.
+///
+/// ```rust
+/// use bulk_keychain::display::{confirm_price_matches, DisplayPolicy};
+/// use bulk_keychain::{Order, TimeInForce};
+///
+/// let order = Order::limit("BTC-USD", true, 100000.05, 0.1, TimeInForce::Gtc);
+/// let policy = DisplayPolicy::new();
+/// assert!(confirm_price_matches("100,000.05", &order, &policy).is_ok());
+/// assert!(confirm_price_matches("100,000", &order, &policy).is_err());
+///
+/// let coarse = DisplayPolicy::new().with_max_decimals("BTC-USD", 0);
+/// assert_eq!(order.describe(&coarse), "BUY 0.1 BTC-USD @ 100000 (GTC)");
+/// assert!(confirm_price_matches("100,000.05", &order, &coarse).is_err());
+/// ```
+pub fn confirm_price_matches(
+    displayed: &str,
+    order: &Order,
+    policy: &DisplayPolicy,
+) -> Result<(), DisplayMismatch> {
+    let symbol = order.symbol.clone();
+    if matches!(
+        order.order_type,
+        OrderType::Trigger {
+            is_market: true,
+            ..
+        }
+    ) {
+        if displayed.trim().eq_ignore_ascii_case("MARKET") {
+            return Ok(());
+        }
+        return Err(DisplayMismatch::Differs {
+            symbol,
+            displayed: displayed.to_string(),
+            signed: "MARKET".to_string(),
+        });
+    }
+
+    let shown = parse_displayed(displayed)?;
+    let signed = normalize(&order.price.to_string());
+    if shown != signed {
+        return Err(DisplayMismatch::Differs {
+            symbol,
+            displayed: displayed.to_string(),
+            signed,
+        });
+    }
+    if let Some(max_decimals) = policy.max_decimals(&symbol) {
+        let rendered = policy.format_price(&symbol, order.price);
+        if rendered != signed {
+            return Err(DisplayMismatch::PolicyRounds {
+                symbol,
+                max_decimals,
+                signed,
+                shown: rendered,
+            });
+        }
+    }
+    Ok(())
+}
+
+/// Canonical decimal of a displayed price, or why it is not one.
+fn parse_displayed(displayed: &str) -> Result<String, DisplayMismatch> {
+    parse_grouped(displayed.trim()).map_err(|reason| DisplayMismatch::Unreadable {
+        displayed: displayed.to_string(),
+        reason: reason.to_string(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::TimeInForce;
+
+    fn limit(price: f64) -> Order {
+        Order::limit("BTC-USD", true, price, 0.1, TimeInForce::Gtc)
+    }
+
+    #[test]
+    fn test_rounding_boundaries() {
+        let policy = DisplayPolicy::new().with_max_decimals("BTC-USD", 2);
+        assert_eq!(policy.format_price("BTC-USD", 2.675), "2.68");
+        assert_eq!(policy.format_price("BTC-USD", 2.674), "2.67");
+        assert_eq!(policy.format_price("BTC-USD", 99.995), "100");
+        assert_eq!(policy.format_price("BTC-USD", 0.004), "0");
+        assert_eq!(policy.format_price("BTC-USD", 1.5), "1.5");
+        assert_eq!(policy.format_price("ETH-USD", 2.675), "2.675");
+
+        let order = limit(2.675);
+        assert_eq!(order.describe(&policy), "BUY 0.1 BTC-USD @ 2.68 (GTC)");
+        assert!(confirm_price_matches("2.675", &order, &DisplayPolicy::new()).is_ok());
+        assert!(confirm_price_matches("2.67500", &order, &DisplayPolicy::new()).is_ok());
+        assert!(confirm_price_matches("2.68", &order, &DisplayPolicy::new()).is_err());
+        assert!(confirm_price_matches("2.68", &limit(2.68), &policy).is_ok());
+    }
+
+    #[test]
+    fn test_locale_formats() {
+        let order = limit(1234567.5);
+        let policy = DisplayPolicy::new();
+        assert!(confirm_price_matches("1,234,567.5", &order, &policy).is_ok());
+        assert!(confirm_price_matches(" 1234567.50 ", &order, &policy).is_ok());
+        for rejected in [
+            "1.234.567,5",
+            "1234567,5",
+            "12,34,567.5",
+            "1 234 567.5",
+            "1,234,567.",
+            "-5",
+        ] {
+            assert!(
+                matches!(
+                    confirm_price_matches(rejected, &order, &policy),
+                    Err(DisplayMismatch::Unreadable { .. })
+                ),
+                "{rejected}"
+            );
+        }
+        // Ambiguous in some locales, but a valid thousands grouping here.
+        assert!(matches!(
+            confirm_price_matches("1,234", &limit(1.234), &policy),
+            Err(DisplayMismatch::Differs { .. })
+        ));
+    }
+
+    #[test]
+    fn test_mismatch_content() {
+        let order = limit(100000.05);
+        let err = confirm_price_matches("100,000", &order, &DisplayPolicy::new()).unwrap_err();
+        assert_eq!(
+            err,
+            DisplayMismatch::Differs {
+                symbol: "BTC-USD".into(),
+                displayed: "100,000".into(),
+                signed: "100000.05".into(),
+            }
+        );
+        assert_eq!(
+            err.to_string(),
+            "displayed price 100,000 on BTC-USD but the order signs 100000.05"
+        );
+
+        let policy = DisplayPolicy::new().with_max_decimals("BTC-USD", 1);
+        let err = confirm_price_matches("100000.05", &order, &policy).unwrap_err();
+        assert_eq!(
+            err,
+            DisplayMismatch::PolicyRounds {
+                symbol: "BTC-USD".into(),
+                max_decimals: 1,
+                signed: "100000.05".into(),
+                shown: "100000.1".into(),
+            }
+        );
+
+        let market = Order::market("BTC-USD", true, 0.1);
+        assert!(confirm_price_matches("market", &market, &policy).is_ok());
+        assert!(confirm_price_matches("100000", &market, &policy).is_err());
+    }
+}
@@ -3,6 +3,9 @@
 use thiserror::Error;
 
 /// All errors that can occur in bulk-keychain
+///
+/// Messages name keys by [`Pubkey::fingerprint`](crate::Pubkey::fingerprint)
+/// rather than in full; the variants' fields keep the full base58 keys.
 #[derive(Debug, Error)]
 pub enum Error {
     /// Invalid base58 encoding
@@ -21,10 +24,26 @@ pub enum Error {
     #[error("invalid signature length: expected 64 bytes, got {0}")]
     InvalidSignatureLength(usize),
 
+    /// Malformed or unsupported JSON Web Key
+    #[error("invalid jwk: {0}")]
+    InvalidJwk(String),
+
+    /// Malformed BIP39 seed phrase (unknown word, bad checksum, wrong length)
+    #[error("invalid mnemonic: {0}")]
+    InvalidMnemonic(String),
+
+    /// Malformed or non-hardened SLIP-0010 derivation path or child index
+    #[error("invalid derivation path: {0}")]
+    InvalidDerivationPath(String),
+
     /// Signing failed
     #[error("signing failed: {0}")]
     SigningFailed(String),
 
+    /// Signing with a keypair wiped by [`Keypair::zeroize`](crate::Keypair::zeroize)
+    #[error("keypair has been zeroized")]
+    KeypairZeroized,
+
     /// Empty orders array
     #[error("orders array cannot be empty")]
     EmptyOrders,
@@ -33,18 +52,344 @@ pub enum Error {
     #[error("signature count mismatch: expected {expected}, got {got}")]
     SignatureMismatch { expected: usize, got: usize },
 
+    /// Keypair does not match the signer a message was prepared for
+    #[error(
+        "signer mismatch: prepared for {}, keypair is {}",
+        fingerprint(expected),
+        fingerprint(got)
+    )]
+    SignerMismatch { expected: String, got: String },
+
     /// Invalid order parameters
     #[error("invalid order: {0}")]
     InvalidOrder(String),
 
+    /// One field of an order intent is missing or malformed; `field` is its
+    /// path, e.g. `order.price`
+    #[error("invalid order: {field} {reason}")]
+    InvalidOrderField { field: String, reason: String },
+
+    /// Input has fields no schema knows (strict input mode); each entry
+    /// names one, with a suggestion when it looks like a typo
+    #[error("unknown fields: {}", .0.join(", "))]
+    UnknownFields(Vec<String>),
+
+    /// User-entered amount is malformed or ambiguous
+    #[error("invalid amount: {0}")]
+    InvalidAmount(String),
+
+    /// Symbol is not of the form `BASE-QUOTE`
+    #[error("invalid symbol: {0}")]
+    InvalidSymbol(String),
+
+    /// Malformed compact action JSON
+    #[error("invalid action: {0}")]
+    InvalidAction(String),
+
     /// Serialization error
     #[error("serialization error: {0}")]
     SerializationError(String),
 
+    /// Batch checkpoint does not match the input being resumed
+    #[error("checkpoint mismatch: {0}")]
+    CheckpointMismatch(String),
+
+    /// Order IDs carried on a transaction differ from the ones its signed
+    /// actions produce
+    #[error("order id mismatch: transaction carries {claimed}, actions give {computed}")]
+    OrderIdMismatch { claimed: String, computed: String },
+
+    /// A signature handed to finalization does not verify over the prepared
+    /// message; `message` is its order ID, or its nonce if it has none
+    #[error(
+        "signature does not verify against signer {} for message {message}",
+        fingerprint(signer)
+    )]
+    SignatureDoesNotVerify { signer: String, message: String },
+
+    /// A nonce reservation from `Signer::peek_order_ids` cannot be used
+    #[error("nonce reservation {id}: {reason}")]
+    Reservation { id: u64, reason: String },
+
+    /// Order ID scheme differs from the one reported by the server
+    #[error("order id scheme mismatch: local {local}, server {server}")]
+    IdSchemeMismatch { local: String, server: String },
+
+    /// Batch of `count` nonces from `base` runs past `u64::MAX`
+    #[error("nonce overflow: {count} nonces from {base} exceed u64")]
+    NonceOverflow { base: u64, count: usize },
+
+    /// Persistent nonce state could not be read or written
+    #[error("nonce store error: {0}")]
+    NonceStore(String),
+
+    /// Content-derived nonce asked for on an action that is not idempotent
+    #[error(
+        "content-derived nonce refused for {0}: the same content always signs to the same \
+         transaction, so a deliberate repeat (e.g. a second identical order) would be dropped \
+         as a replay. Use a timestamp or counter nonce; only agent wallet and user settings \
+         actions may derive their nonce from content"
+    )]
+    ContentDerivedNonce(&'static str),
+
+    /// Transaction is too old to be re-signed under a new nonce
+    #[error("transaction too old to resign: {age_ms}ms > {max_age_ms}ms")]
+    ResignTooOld { age_ms: u64, max_age_ms: u64 },
+
+    /// Order rejected by a signer's pre-sign hook
+    #[error("rejected by pre-sign hook: {0}")]
+    PreSignRejected(String),
+
+    /// Clock passed the caller's signing deadline; `signed` items of a
+    /// batch got through before it did, and none are returned
+    #[error("deadline exceeded by {overrun_ms}ms after {signed} signed")]
+    DeadlineExceeded { overrun_ms: u64, signed: usize },
+
+    /// Group refused in strict mode by a group-level lint finding
+    #[error("group rejected: {0}")]
+    GroupRejected(String),
+
+    /// Item or sub-scope outside a scoped signer's policy; `rule` names the
+    /// [`ScopeConfig`](crate::scope::ScopeConfig) rule that refused it
+    #[error("scope violation ({rule}): {detail}")]
+    ScopeViolation { rule: &'static str, detail: String },
+
+    /// Signing would exceed the per-symbol open-order cap
+    #[error("open order cap exceeded on {symbol}: {open} open, cap {cap}")]
+    OpenOrderCapExceeded {
+        symbol: String,
+        open: usize,
+        cap: usize,
+    },
+
+    /// Order reuses the client ID of a live order (client-ID enforcement on)
+    #[error("client id {client_id} in use by live order {existing_order_id}")]
+    ClientIdInUse {
+        client_id: String,
+        existing_order_id: String,
+    },
+
+    /// Order with the all-zero hash as its client ID, which is almost always
+    /// a defaulted field rather than a chosen ID
+    #[error("client id is the all-zero hash; omit it to send no client id")]
+    ZeroClientId,
+
+    /// Cancel by client ID that no live order tracked by the signer holds
+    #[error("no live order holds client id {0}")]
+    ClientIdNotLive(String),
+
+    /// Cancel by client ID encoded without being resolved to an order ID
+    #[error("cancel by client id {0} needs a signer tracking client ids to resolve its order id")]
+    UnresolvedClientIdCancel(String),
+
+    /// Millisecond timestamp small enough that it was almost certainly
+    /// given in seconds (see [`TimestampMs::looks_like_seconds`](crate::TimestampMs::looks_like_seconds))
+    #[error(
+        "timestamp {0} looks like seconds; expected milliseconds since the Unix epoch \
+         (values before 1973 in ms are refused as likely seconds)"
+    )]
+    TimestampLooksLikeSeconds(u64),
+
+    /// Malformed, truncated or corrupted binary frame
+    #[error("invalid frame: {0}")]
+    InvalidFrame(String),
+
+    /// Remote session envelope failed validation or was rejected
+    #[error("remote session error: {0}")]
+    RemoteSession(String),
+
+    /// Submission transport failed before a response was received
+    #[error("transport error: {0}")]
+    Transport(String),
+
+    /// Signing pipeline was shut down before the item was sent
+    #[error("signing pipeline is shut down")]
+    PipelineClosed,
+
+    /// No signer in the pool trades for this account
+    #[error("no signer for account {}", fingerprint(.0))]
+    UnknownAccount(String),
+
+    /// Outbox log could not be read or written, or an entry is not pending
+    #[error("outbox error: {0}")]
+    Outbox(String),
+
+    /// Escrow cancel-all cannot be timed as asked
+    #[error("escrow: {0}")]
+    Escrow(String),
+
+    /// Process-wide quick signer missing, already set up or misconfigured
+    #[error("quick signer: {0}")]
+    Quick(String),
+
+    /// Transaction archive could not be read, or a checkpoint does not fit it
+    #[error("archive error: {0}")]
+    Archive(String),
+
+    /// Intent stream could not be read, or a line is too long to buffer
+    #[error("intent stream error: {0}")]
+    IntentStream(String),
+
     /// JSON parsing error
     #[error("json error: {0}")]
     JsonError(#[from] serde_json::Error),
 }
 
+impl Error {
+    /// Name of the variant, stable across releases, e.g. `"InvalidBase58"`.
+    ///
+    /// The bindings pass it on to JS (`error.code` in Node, a
+    /// `"<code>: <message>"` prefix in WASM) so callers can tell errors apart
+    /// without matching on message text.
+    ///
+    /// ```rust
+    /// use bulk_keychain::Keypair;
+    ///
+    /// let err = Keypair::from_base58("0OIl").unwrap_err();
+    /// assert_eq!(err.code(), "InvalidBase58");
+    /// ```
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::InvalidBase58(..) => "InvalidBase58",
+            Self::InvalidKeyLength { .. } => "InvalidKeyLength",
+            Self::InvalidHashLength(..) => "InvalidHashLength",
+            Self::InvalidSignatureLength(..) => "InvalidSignatureLength",
+            Self::InvalidJwk(..) => "InvalidJwk",
+            Self::InvalidMnemonic(..) => "InvalidMnemonic",
+            Self::InvalidDerivationPath(..) => "InvalidDerivationPath",
+            Self::SigningFailed(..) => "SigningFailed",
+            Self::KeypairZeroized => "KeypairZeroized",
+            Self::EmptyOrders => "EmptyOrders",
+            Self::SignatureMismatch { .. } => "SignatureMismatch",
+            Self::SignerMismatch { .. } => "SignerMismatch",
+            Self::InvalidOrder(..) => "InvalidOrder",
+            Self::InvalidOrderField { .. } => "InvalidOrderField",
+            Self::UnknownFields(..) => "UnknownFields",
+            Self::InvalidAmount(..) => "InvalidAmount",
+            Self::InvalidSymbol(..) => "InvalidSymbol",
+            Self::InvalidAction(..) => "InvalidAction",
+            Self::SerializationError(..) => "SerializationError",
+            Self::CheckpointMismatch(..) => "CheckpointMismatch",
+            Self::OrderIdMismatch { .. } => "OrderIdMismatch",
+            Self::SignatureDoesNotVerify { .. } => "SignatureDoesNotVerify",
+            Self::Reservation { .. } => "Reservation",
+            Self::IdSchemeMismatch { .. } => "IdSchemeMismatch",
+            Self::NonceOverflow { .. } => "NonceOverflow",
+            Self::NonceStore(..) => "NonceStore",
+            Self::ContentDerivedNonce(..) => "ContentDerivedNonce",
+            Self::ResignTooOld { .. } => "ResignTooOld",
+            Self::PreSignRejected(..) => "PreSignRejected",
+            Self::DeadlineExceeded { .. } => "DeadlineExceeded",
+            Self::GroupRejected(..) => "GroupRejected",
+            Self::ScopeViolation { .. } => "ScopeViolation",
+            Self::OpenOrderCapExceeded { .. } => "OpenOrderCapExceeded",
+            Self::ClientIdInUse { .. } => "ClientIdInUse",
+            Self::ZeroClientId => "ZeroClientId",
+            Self::ClientIdNotLive(..) => "ClientIdNotLive",
+            Self::UnresolvedClientIdCancel(..) => "UnresolvedClientIdCancel",
+            Self::TimestampLooksLikeSeconds(..) => "TimestampLooksLikeSeconds",
+            Self::InvalidFrame(..) => "InvalidFrame",
+            Self::RemoteSession(..) => "RemoteSession",
+            Self::Transport(..) => "Transport",
+            Self::PipelineClosed => "PipelineClosed",
+            Self::UnknownAccount(..) => "UnknownAccount",
+            Self::Outbox(..) => "Outbox",
+            Self::Escrow(..) => "Escrow",
+            Self::Quick(..) => "Quick",
+            Self::Archive(..) => "Archive",
+            Self::IntentStream(..) => "IntentStream",
+            Self::JsonError(..) => "JsonError",
+        }
+    }
+}
+
+/// Fingerprint of a base58 pubkey for a message; anything that does not
+/// parse as one is shown as given.
+pub(crate) fn fingerprint(base58: &str) -> String {
+    crate::Pubkey::from_base58(base58)
+        .map(|pubkey| pubkey.fingerprint())
+        .unwrap_or_else(|_| base58.to_string())
+}
+
 /// Result type alias for bulk-keychain operations
 pub type Result<T> = std::result::Result<T, Error>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Keypair, Order, OrderItem, Signer};
+
+    #[test]
+    fn test_codes_name_the_variant() {
+        let signer = Signer::new(Keypair::generate());
+        let orders = || -> Vec<OrderItem> {
+            vec![
+                Order::market("BTC-USD", true, 0.1).into(),
+                Order::market("BTC-USD", false, 0.1).into(),
+            ]
+        };
+        let cases = [
+            (Keypair::from_base58("0OIl").unwrap_err(), "InvalidBase58"),
+            (
+                Keypair::from_bytes(&[1; 5]).unwrap_err(),
+                "InvalidKeyLength",
+            ),
+            (
+                signer.sign_all(orders(), Some(u64::MAX)).unwrap_err(),
+                "NonceOverflow",
+            ),
+            (
+                crate::prepare_all(
+                    orders(),
+                    &signer.account_pubkey().clone(),
+                    None,
+                    Some(u64::MAX),
+                )
+                .unwrap_err(),
+                "NonceOverflow",
+            ),
+            (
+                OrderItem::try_from(serde_json::json!({ "type": "order", "symbol": "BTC-USD" }))
+                    .unwrap_err(),
+                "InvalidOrderField",
+            ),
+            (crate::symbol::split("BTCUSD").unwrap_err(), "InvalidSymbol"),
+            (
+                crate::sdk_compat::deserialize_actions(&[0xff]).unwrap_err(),
+                "SerializationError",
+            ),
+            (Error::SigningFailed("hsm offline".into()), "SigningFailed"),
+        ];
+        for (err, code) in cases {
+            assert_eq!(err.code(), code, "{err}");
+        }
+
+        // The last nonce of a batch may be u64::MAX itself.
+        assert!(signer.sign_all(orders(), Some(u64::MAX - 1)).is_ok());
+    }
+
+    #[test]
+    fn test_messages_name_keys_by_fingerprint() {
+        let keypair = Keypair::generate();
+        let other = Keypair::generate();
+        let order = Order::market("BTC-USD", true, 0.1).into();
+        let prepared = crate::prepare_message(order, &keypair.pubkey(), None, Some(1)).unwrap();
+        let wrong = Signer::new(keypair.clone()).sign_bytes(b"other bytes");
+        let cases = [
+            prepared.sign_with_keypair(&other).unwrap_err(),
+            prepared.clone().finalize_verified(&wrong).unwrap_err(),
+            Error::UnknownAccount(keypair.pubkey().to_base58()),
+        ];
+        for err in cases {
+            let message = err.to_string();
+            assert!(message.contains(&keypair.fingerprint()), "{message}");
+            for key in [&keypair, &other] {
+                assert!(!message.contains(&key.pubkey().to_base58()), "{message}");
+            }
+        }
+        assert!(matches!(
+            prepared.sign_with_keypair(&other).unwrap_err(),
+            Error::SignerMismatch { got, .. } if got == other.pubkey().to_base58()
+        ));
+    }
+}
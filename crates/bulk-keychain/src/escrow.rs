@@ -0,0 +1,253 @@
+//! Pre-signed cancel-all kill switches held by a third party
+//!
+//! [`Signer::sign_escrow_cancel_all`](crate::Signer::sign_escrow_cancel_all)
+//! signs a cancel-all that someone without the keys (e.g. a risk officer)
+//! can submit later, but not before an agreed time.
+//!
+//! The only thing enforcing that time is the server's nonce window. The
+//! signed `cxa` action has no valid-from or expiry field, so the nonce is
+//! the only time the transaction carries: it is set to
+//! `not_before_ms + max_ahead_ms`, which the server refuses as too far in
+//! the future until `not_before_ms`, and as stale after
+//! `nonce + max_behind_ms`. The window is assumed to be
+//! [`NonceWindow::default`] unless one is passed; if the server's real
+//! window is wider ahead, the escrow becomes usable early, and a server that
+//! does not bound future nonces at all accepts it immediately.
+//!
+//! Only timestamp nonce strategies can carry a time. Under
+//! [`NonceStrategy::TimestampWithCounter`] the nonce is the millisecond
+//! timestamp times 1000.
+
+use crate::nonce::NonceWindow;
+use crate::types::{Pubkey, SignedTransaction};
+use crate::{Error, NonceStrategy, Result, TimestampMs};
+use std::fmt;
+
+/// When an escrow transaction is accepted, relative to some time.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EscrowStatus {
+    /// The server would still refuse the nonce as too far ahead
+    TooEarly {
+        wait_ms: u64,
+    },
+    Valid,
+    /// The server would refuse the nonce as stale
+    Expired {
+        since_ms: u64,
+    },
+}
+
+/// What an escrow transaction does and when it is accepted.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EscrowDescriptor {
+    /// Account whose orders are cancelled (base58)
+    pub account: String,
+    /// Key that signed it (base58)
+    pub signer: String,
+    /// Symbols cancelled; empty means every symbol
+    pub symbols: Vec<String>,
+    pub nonce: u64,
+    pub strategy: NonceStrategy,
+    pub window: NonceWindow,
+    /// First time (ms since the Unix epoch) the server accepts it
+    pub valid_from_ms: u64,
+    /// Last time (ms since the Unix epoch) the server accepts it
+    pub valid_until_ms: u64,
+}
+
+impl EscrowDescriptor {
+    pub(crate) fn new(
+        account: &Pubkey,
+        signer: &Pubkey,
+        symbols: Vec<String>,
+        not_before: TimestampMs,
+        strategy: NonceStrategy,
+        window: NonceWindow,
+    ) -> Result<Self> {
+        let not_before_ms = not_before.as_millis();
+        let overflow = || Error::Escrow(format!("not_before_ms {} is out of range", not_before_ms));
+        let nonce_ms = not_before_ms
+            .checked_add(window.max_ahead_ms.as_millis())
+            .ok_or_else(overflow)?;
+        let nonce = match strategy {
+            NonceStrategy::Timestamp => nonce_ms,
+            NonceStrategy::TimestampWithCounter => {
+                nonce_ms.checked_mul(1000).ok_or_else(overflow)?
+            }
+            NonceStrategy::Counter
+            | NonceStrategy::ContentDerived { .. }
+            | NonceStrategy::RandomWithTimestampFallback => {
+                return Err(Error::Escrow(format!(
+                    "{:?} nonces carry no time, so they cannot delay an escrow",
+                    strategy
+                )))
+            }
+        };
+        Ok(Self {
+            account: account.to_base58(),
+            signer: signer.to_base58(),
+            symbols,
+            nonce,
+            strategy,
+            window,
+            valid_from_ms: not_before_ms,
+            valid_until_ms: nonce_ms.saturating_add(window.max_behind_ms.as_millis()),
+        })
+    }
+
+    /// Whether the server would accept the transaction at `now`.
+    pub fn status_at(&self, now: impl Into<TimestampMs>) -> EscrowStatus {
+        let now_ms = now.into().as_millis();
+        if now_ms < self.valid_from_ms {
+            EscrowStatus::TooEarly {
+                wait_ms: self.valid_from_ms - now_ms,
+            }
+        } else if now_ms > self.valid_until_ms {
+            EscrowStatus::Expired {
+                since_ms: now_ms - self.valid_until_ms,
+            }
+        } else {
+            EscrowStatus::Valid
+        }
+    }
+}
+
+impl fmt::Display for EscrowDescriptor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let symbols = if self.symbols.is_empty() {
+            "all symbols".to_string()
+        } else {
+            self.symbols.join(", ")
+        };
+        write!(
+            f,
+            "cancel-all on {} for account {} (signed by {}), nonce {}; accepted from {} to {} \
+             (ms since the Unix epoch) if the server takes nonces up to {}ms ahead and {}ms \
+             behind its clock",
+            symbols,
+            self.account,
+            self.signer,
+            self.nonce,
+            self.valid_from_ms,
+            self.valid_until_ms,
+            self.window.max_ahead_ms.as_millis(),
+            self.window.max_behind_ms.as_millis()
+        )
+    }
+}
+
+/// A signed escrow cancel-all and its descriptor, ready to hand over.
+#[derive(Debug, Clone)]
+pub struct EscrowCancelAll {
+    pub tx: SignedTransaction,
+    pub descriptor: EscrowDescriptor,
+}
+
+/// For the holder: whether `escrow` is intact and the server would accept
+/// it at `clock()`.
+///
+/// Checks the signature and that the descriptor describes this transaction,
+/// then the timing under the descriptor's window.
+pub fn is_escrow_valid_now(escrow: &EscrowCancelAll, clock: impl Fn() -> u64) -> bool {
+    let descriptor = &escrow.descriptor;
+    let intact = escrow.tx.verify().unwrap_or(false)
+        && escrow.tx.nonce == descriptor.nonce
+        && escrow.tx.account == descriptor.account
+        && escrow.tx.signer == descriptor.signer;
+    intact && descriptor.status_at(clock()) == EscrowStatus::Valid
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::nonce::{SERVER_NONCE_MAX_AHEAD_MS, SERVER_NONCE_MAX_BEHIND_MS};
+    use crate::{DurationMs, Keypair, NonceManager, Signer};
+    use std::sync::atomic::{AtomicU64, Ordering};
+    use std::sync::Arc;
+
+    const NOT_BEFORE: u64 = 1_700_000_000_000;
+
+    fn signer_at(now: u64) -> Signer {
+        let clock = Arc::new(AtomicU64::new(now));
+        let manager = NonceManager::timestamp().with_clock(move || clock.load(Ordering::SeqCst));
+        Signer::with_nonce_manager(Keypair::generate(), manager)
+    }
+
+    #[test]
+    fn test_too_early_and_valid_window() {
+        let mut signer = signer_at(NOT_BEFORE - 3_600_000);
+        let escrow = signer
+            .sign_escrow_cancel_all(vec!["BTC-USD".into()], NOT_BEFORE, NonceStrategy::Timestamp)
+            .unwrap();
+        let d = &escrow.descriptor;
+        assert_eq!(escrow.tx.nonce, NOT_BEFORE + SERVER_NONCE_MAX_AHEAD_MS);
+        assert_eq!(
+            d.valid_until_ms,
+            NOT_BEFORE + SERVER_NONCE_MAX_AHEAD_MS + SERVER_NONCE_MAX_BEHIND_MS
+        );
+        assert!(escrow.tx.verify().unwrap());
+        assert_eq!(
+            escrow.tx.actions_json().unwrap()[0]["cxa"]["c"][0],
+            "BTC-USD"
+        );
+
+        // The server's `nonce - max_ahead` bound is exactly `not_before`.
+        assert!(!is_escrow_valid_now(&escrow, || NOT_BEFORE - 1));
+        assert_eq!(
+            d.status_at(NOT_BEFORE - 1_000),
+            EscrowStatus::TooEarly { wait_ms: 1_000 }
+        );
+        assert!(is_escrow_valid_now(&escrow, || NOT_BEFORE));
+        assert!(is_escrow_valid_now(&escrow, || d.valid_until_ms));
+        assert!(!is_escrow_valid_now(&escrow, || d.valid_until_ms + 1));
+        assert_eq!(
+            d.status_at(d.valid_until_ms + 5),
+            EscrowStatus::Expired { since_ms: 5 }
+        );
+
+        let mut tampered = escrow.clone();
+        tampered.tx.nonce = NOT_BEFORE;
+        assert!(!is_escrow_valid_now(&tampered, || NOT_BEFORE + 1));
+    }
+
+    #[test]
+    fn test_custom_window_and_strategies() {
+        let mut signer = signer_at(NOT_BEFORE - 60_000);
+        let window = NonceWindow {
+            max_ahead_ms: DurationMs(2_000),
+            max_behind_ms: DurationMs(5_000),
+        };
+        let escrow = signer
+            .sign_escrow_cancel_all_with_window(
+                vec![],
+                NOT_BEFORE,
+                NonceStrategy::TimestampWithCounter,
+                window,
+            )
+            .unwrap();
+        assert_eq!(escrow.tx.nonce, (NOT_BEFORE + 2_000) * 1000);
+        assert_eq!(escrow.descriptor.valid_until_ms, NOT_BEFORE + 7_000);
+        assert!(escrow
+            .descriptor
+            .to_string()
+            .starts_with("cancel-all on all symbols for account"));
+
+        for strategy in [
+            NonceStrategy::Counter,
+            NonceStrategy::ContentDerived { epoch: 1 },
+            NonceStrategy::RandomWithTimestampFallback,
+        ] {
+            assert!(matches!(
+                signer.sign_escrow_cancel_all(vec![], NOT_BEFORE, strategy),
+                Err(Error::Escrow(_))
+            ));
+        }
+
+        // A window that closed before signing is refused rather than signed stale.
+        let mut late = signer_at(NOT_BEFORE + 3_600_000);
+        assert!(matches!(
+            late.sign_escrow_cancel_all(vec![], NOT_BEFORE, NonceStrategy::Timestamp),
+            Err(Error::Escrow(_))
+        ));
+    }
+}
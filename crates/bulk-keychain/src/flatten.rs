@@ -0,0 +1,259 @@
+//! Reduce-only orders that close out a reported position
+//!
+//! [`flatten_position`] turns a position snapshot from the exchange API into
+//! the orders that take it to zero: a long is sold, a short is bought back.
+//! Orders are split so none exceeds the symbol's max order size. With a
+//! [`SymbolSpec`] the split is done in whole lots, so the order sizes add up
+//! to the position exactly.
+
+use crate::types::{Order, TimeInForce};
+use crate::{Error, Result, SymbolSpec};
+use std::collections::HashMap;
+
+/// A position as reported by the exchange.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PositionSnapshot {
+    pub symbol: String,
+    /// Signed size: positive when long, negative when short
+    pub size: f64,
+    pub mark_price: f64,
+}
+
+/// How flattening orders are priced.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FlattenStyle {
+    /// Market orders
+    Market,
+    /// IOC limits this many bps through the mark price (above it when buying)
+    LimitAtOffsetBps(f64),
+}
+
+/// Per-symbol granularity and order-size caps for [`flatten_position`].
+#[derive(Debug, Clone, Default)]
+pub struct SymbolSpecs {
+    specs: HashMap<String, SymbolSpec>,
+    max_order_sizes: HashMap<String, f64>,
+}
+
+impl SymbolSpecs {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Round sizes to `spec`'s lots and limit prices to its ticks.
+    pub fn with_spec(mut self, symbol: impl Into<String>, spec: SymbolSpec) -> Self {
+        self.specs.insert(symbol.into(), spec);
+        self
+    }
+
+    /// Split positions larger than `max` into several orders.
+    pub fn with_max_order_size(mut self, symbol: impl Into<String>, max: f64) -> Self {
+        self.max_order_sizes.insert(symbol.into(), max);
+        self
+    }
+
+    pub fn spec(&self, symbol: &str) -> Option<&SymbolSpec> {
+        self.specs.get(symbol)
+    }
+
+    pub fn max_order_size(&self, symbol: &str) -> Option<f64> {
+        self.max_order_sizes.get(symbol).copied()
+    }
+}
+
+fn invalid(message: String) -> Error {
+    Error::InvalidOrder(message)
+}
+
+/// Split `total` into pieces of at most `max`: full pieces, then the rest.
+fn split_lots(total: i64, max: Option<i64>) -> Vec<i64> {
+    let Some(max) = max else {
+        return vec![total];
+    };
+    let mut pieces = vec![max; (total / max) as usize];
+    if total % max != 0 {
+        pieces.push(total % max);
+    }
+    pieces
+}
+
+fn split_sizes(pos: &PositionSnapshot, specs: &SymbolSpecs) -> Result<Vec<f64>> {
+    let total = pos.size.abs();
+    let max = specs.max_order_size(&pos.symbol);
+    if let Some(max) = max {
+        if !(max.is_finite() && max > 0.0) {
+            return Err(invalid(format!(
+                "max order size of {} must be positive, got {}",
+                pos.symbol, max
+            )));
+        }
+    }
+    let Some(spec) = specs.spec(&pos.symbol) else {
+        let Some(max) = max else {
+            return Ok(vec![total]);
+        };
+        let full = (total / max).floor();
+        let mut sizes = vec![max; full as usize];
+        let rest = total - full * max;
+        if rest > 0.0 {
+            sizes.push(rest);
+        }
+        return Ok(sizes);
+    };
+
+    let lot = spec.lot_units as f64 / 10f64.powi(spec.size_decimals as i32);
+    let max_lots = match max {
+        Some(max) => {
+            let lots = (max / lot + 1e-9).floor() as i64;
+            if lots < 1 {
+                return Err(invalid(format!(
+                    "max order size {} of {} is below one lot",
+                    max, pos.symbol
+                )));
+            }
+            Some(lots)
+        }
+        None => None,
+    };
+    split_lots(spec.lots_from_size(total)?, max_lots)
+        .into_iter()
+        .map(|lots| spec.size_from_lots(lots))
+        .collect()
+}
+
+fn limit_price(pos: &PositionSnapshot, is_buy: bool, bps: f64, specs: &SymbolSpecs) -> Result<f64> {
+    if !(bps.is_finite() && (0.0..10_000.0).contains(&bps)) {
+        return Err(invalid(format!(
+            "flatten offset must be in [0, 10000) bps, got {}",
+            bps
+        )));
+    }
+    if !(pos.mark_price.is_finite() && pos.mark_price > 0.0) {
+        return Err(invalid(format!(
+            "mark price of {} must be positive, got {}",
+            pos.symbol, pos.mark_price
+        )));
+    }
+    let offset = if is_buy { bps } else { -bps };
+    let price = pos.mark_price * (1.0 + offset / 10_000.0);
+    let Some(spec) = specs.spec(&pos.symbol) else {
+        return Ok(price);
+    };
+    // Round away from the mark so the limit stays at least as aggressive.
+    let tick = spec.tick_units as f64 / 10f64.powi(spec.price_decimals as i32);
+    let ticks = if is_buy {
+        (price / tick - 1e-9).ceil()
+    } else {
+        (price / tick + 1e-9).floor()
+    };
+    spec.price_from_ticks((ticks as i64).max(1))
+}
+
+/// Reduce-only orders that flatten `pos`; empty for a flat position.
+///
+/// ```rust
+/// use bulk_keychain::flatten::{flatten_position, FlattenStyle, PositionSnapshot, SymbolSpecs};
+/// use bulk_keychain::SymbolSpec;
+///
+/// let pos = PositionSnapshot { symbol: "BTC-USD".into(), size: 2.5, mark_price: 100000.0 };
+/// let specs = SymbolSpecs::new()
+///     .with_spec("BTC-USD", SymbolSpec::new(1, 3))
+///     .with_max_order_size("BTC-USD", 1.0);
+/// let orders = flatten_position(&pos, FlattenStyle::Market, &specs).unwrap();
+/// let sizes: Vec<f64> = orders.iter().map(|o| o.size).collect();
+/// assert_eq!(sizes, [1.0, 1.0, 0.5]);
+/// assert!(orders.iter().all(|o| !o.is_buy && o.reduce_only));
+/// ```
+pub fn flatten_position(
+    pos: &PositionSnapshot,
+    style: FlattenStyle,
+    specs: &SymbolSpecs,
+) -> Result<Vec<Order>> {
+    if !pos.size.is_finite() {
+        return Err(invalid(format!(
+            "position size of {} must be finite, got {}",
+            pos.symbol, pos.size
+        )));
+    }
+    if pos.size == 0.0 {
+        return Ok(vec![]);
+    }
+    let is_buy = pos.size < 0.0;
+    let price = match style {
+        FlattenStyle::Market => None,
+        FlattenStyle::LimitAtOffsetBps(bps) => Some(limit_price(pos, is_buy, bps, specs)?),
+    };
+    let orders = split_sizes(pos, specs)?
+        .into_iter()
+        .map(|size| {
+            let order = match price {
+                Some(px) => Order::limit(pos.symbol.clone(), is_buy, px, size, TimeInForce::Ioc),
+                None => Order::market(pos.symbol.clone(), is_buy, size),
+            };
+            order.reduce_only()
+        })
+        .collect();
+    Ok(orders)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::OrderType;
+
+    fn pos(size: f64) -> PositionSnapshot {
+        PositionSnapshot {
+            symbol: "ETH-USD".into(),
+            size,
+            mark_price: 2000.0,
+        }
+    }
+
+    #[test]
+    fn test_long_sells_and_short_buys() {
+        let specs = SymbolSpecs::new().with_spec("ETH-USD", SymbolSpec::new(1, 2));
+        let style = FlattenStyle::LimitAtOffsetBps(25.0);
+
+        let long = flatten_position(&pos(1.5), style, &specs).unwrap();
+        assert_eq!(long.len(), 1);
+        assert!(!long[0].is_buy && long[0].reduce_only);
+        assert_eq!((long[0].price, long[0].size), (1995.0, 1.5));
+        assert_eq!(long[0].order_type, OrderType::limit(TimeInForce::Ioc));
+
+        let short = flatten_position(&pos(-1.5), style, &specs).unwrap();
+        assert!(short[0].is_buy && short[0].reduce_only);
+        assert_eq!((short[0].price, short[0].size), (2005.0, 1.5));
+
+        let market = flatten_position(&pos(-0.3), FlattenStyle::Market, &specs).unwrap();
+        assert!(market[0].is_buy && matches!(market[0].order_type, OrderType::Trigger { .. }));
+    }
+
+    #[test]
+    fn test_flat_position_yields_nothing() {
+        let specs = SymbolSpecs::new();
+        assert!(flatten_position(&pos(0.0), FlattenStyle::Market, &specs)
+            .unwrap()
+            .is_empty());
+        assert!(flatten_position(&pos(f64::NAN), FlattenStyle::Market, &specs).is_err());
+    }
+
+    #[test]
+    fn test_split_preserves_total_exactly() {
+        let spec = SymbolSpec::new(1, 2).with_lot_units(5);
+        let specs = SymbolSpecs::new()
+            .with_spec("ETH-USD", spec)
+            .with_max_order_size("ETH-USD", 0.7);
+        let orders = flatten_position(&pos(-2.35), FlattenStyle::Market, &specs).unwrap();
+        let sizes: Vec<f64> = orders.iter().map(|o| o.size).collect();
+        // 47 lots of 0.05: three full 14-lot pieces and a 5-lot remainder.
+        assert_eq!(sizes, [0.7, 0.7, 0.7, 0.25]);
+        let lots: i64 = sizes.iter().map(|s| spec.lots_from_size(*s).unwrap()).sum();
+        assert_eq!(lots, spec.lots_from_size(2.35).unwrap());
+        assert!(orders.iter().all(|o| o.is_buy && o.reduce_only));
+
+        let too_small = SymbolSpecs::new()
+            .with_spec("ETH-USD", spec)
+            .with_max_order_size("ETH-USD", 0.01);
+        assert!(flatten_position(&pos(1.0), FlattenStyle::Market, &too_small).is_err());
+    }
+}
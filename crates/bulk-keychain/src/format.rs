@@ -0,0 +1,305 @@
+//! Human formatting and parsing of prices and sizes
+//!
+//! One set of rules for confirmation text, audit exports and user input:
+//! `,` only ever groups thousands and `.` is the only decimal point. Input a
+//! locale could read two ways (`1.234,56`, `1234,5`) is refused with an
+//! error saying so, never guessed at.
+//!
+//! ```rust
+//! use bulk_keychain::format::{format_price, format_size, parse_amount};
+//! use bulk_keychain::SymbolSpec;
+//!
+//! let spec = SymbolSpec::new(2, 2);
+//! assert_eq!(format_price("BTC-USD", 100000.0, &spec), "100,000.00 USD");
+//! assert_eq!(format_size("BTC-USD", 0.1, &spec), "0.10 BTC");
+//! assert_eq!(parse_amount("1,250.5").unwrap().to_f64(), 1250.5);
+//! assert_eq!(parse_amount("0.5k").unwrap().as_str(), "500");
+//! assert!(parse_amount("1.234,56").is_err());
+//! ```
+
+use crate::spec::SymbolSpec;
+use crate::{symbol, Error, Result};
+use std::fmt;
+use std::str::FromStr;
+
+/// Exact non-negative decimal, kept as its canonical text: no exponent, no
+/// leading integer zeros, no trailing fraction zeros.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Decimal(String);
+
+impl Decimal {
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// The nearest `f64`.
+    pub fn to_f64(&self) -> f64 {
+        self.0.parse().expect("canonical decimal parses as f64")
+    }
+
+    /// Rounded half away from zero to `decimals` places.
+    pub fn round_dp(&self, decimals: u32) -> Decimal {
+        Decimal(round_decimal(&self.0, decimals))
+    }
+}
+
+impl fmt::Display for Decimal {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl FromStr for Decimal {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        parse_amount(s)
+    }
+}
+
+/// The shortest decimal that round-trips to `value`, sign dropped. Non-finite
+/// values, which no order can carry, become zero.
+impl From<f64> for Decimal {
+    fn from(value: f64) -> Self {
+        if !value.is_finite() {
+            return Decimal("0".to_string());
+        }
+        // `Display` prints the shortest round-tripping form, never an exponent.
+        Decimal(normalize(&value.abs().to_string()))
+    }
+}
+
+/// `value` rounded to `decimals` places, with exactly that many decimals and
+/// `,` thousands separators, e.g. `100,000.00`.
+pub fn format_amount(value: impl Into<Decimal>, decimals: u32) -> String {
+    let rounded = value.into().round_dp(decimals);
+    let (int, frac) = rounded.0.split_once('.').unwrap_or((&rounded.0, ""));
+    let mut out = group_thousands(int);
+    if decimals > 0 {
+        out.push('.');
+        out.push_str(frac);
+        out.extend(std::iter::repeat_n('0', decimals as usize - frac.len()));
+    }
+    out
+}
+
+/// Price in the symbol's quote asset at the spec's price decimals,
+/// e.g. `100,000.00 USD`.
+pub fn format_price(symbol: &str, price: impl Into<Decimal>, spec: &SymbolSpec) -> String {
+    let unit = symbol::quote_asset(symbol).unwrap_or(symbol);
+    format!("{} {}", format_amount(price, spec.price_decimals), unit)
+}
+
+/// Size in the symbol's base asset at the spec's size decimals, e.g. `0.10 BTC`.
+pub fn format_size(symbol: &str, size: impl Into<Decimal>, spec: &SymbolSpec) -> String {
+    let unit = symbol::base_asset(symbol).unwrap_or(symbol);
+    format!("{} {}", format_amount(size, spec.size_decimals), unit)
+}
+
+/// Read a user-entered amount: digits with an optional `.` fraction, `,`
+/// thousands separators, and an optional `k` (thousand) or `m` (million)
+/// suffix.
+///
+/// A comma used as the decimal point (`1.234,56`, `0,5`) is refused with an
+/// error naming the dot form, as are signs, spaces and other grouping.
+pub fn parse_amount(input: &str) -> Result<Decimal> {
+    let invalid = |reason: String| Error::InvalidAmount(format!("{:?}: {}", input, reason));
+    let text = input.trim();
+    let (text, shift) = match text.char_indices().last() {
+        Some((i, 'k' | 'K')) => (&text[..i], 3),
+        Some((i, 'm' | 'M')) => (&text[..i], 6),
+        _ => (text, 0),
+    };
+    if let Some(dot_form) = decimal_comma_reading(text) {
+        return Err(invalid(format!(
+            "',' is a thousands separator here, not a decimal point; write {}",
+            dot_form
+        )));
+    }
+    let decimal = parse_grouped(text).map_err(|reason| invalid(reason.to_string()))?;
+    Ok(Decimal(times_pow10(&decimal, shift)))
+}
+
+/// Canonical decimal of `digits[.digits]` with optional `,` thousands
+/// groups, or why it is not one.
+pub(crate) fn parse_grouped(text: &str) -> std::result::Result<String, &'static str> {
+    let (int, frac) = match text.split_once('.') {
+        Some((int, frac)) => (int, Some(frac)),
+        None => (text, None),
+    };
+    if int.is_empty() || frac.is_some_and(|f| f.is_empty()) {
+        return Err("expected digits on both sides of '.'");
+    }
+    if frac.is_some_and(|f| !f.bytes().all(|b| b.is_ascii_digit())) {
+        return Err("only digits may follow '.'");
+    }
+    let groups: Vec<&str> = int.split(',').collect();
+    let grouped = groups.len() > 1;
+    for (i, group) in groups.iter().enumerate() {
+        if group.is_empty() || !group.bytes().all(|b| b.is_ascii_digit()) {
+            return Err("only digits, ',' thousands separators and one '.' are accepted");
+        }
+        let bad_width = if i == 0 {
+            group.len() > 3
+        } else {
+            group.len() != 3
+        };
+        if grouped && bad_width {
+            return Err(
+                "',' must separate groups of three digits; decimal commas are not accepted",
+            );
+        }
+    }
+    let digits = groups.concat();
+    Ok(normalize(&match frac {
+        Some(frac) => format!("{}.{}", digits, frac),
+        None => digits,
+    }))
+}
+
+/// If `text` only makes sense with `,` as the decimal point, the amount it
+/// would then mean, written with a `.`.
+fn decimal_comma_reading(text: &str) -> Option<String> {
+    let (int, frac) = text.rsplit_once(',')?;
+    let frac_is_decimal = !frac.is_empty()
+        && frac.bytes().all(|b| b.is_ascii_digit())
+        && (frac.len() != 3 || int.contains('.'));
+    let int_digits: String = int.chars().filter(|c| *c != '.').collect();
+    let int_ok = !int_digits.is_empty() && int_digits.bytes().all(|b| b.is_ascii_digit());
+    (frac_is_decimal && int_ok).then(|| format!("{}.{}", int_digits, frac))
+}
+
+/// `decimal` times `10^places`.
+fn times_pow10(decimal: &str, places: usize) -> String {
+    if places == 0 {
+        return decimal.to_string();
+    }
+    let (int, frac) = decimal.split_once('.').unwrap_or((decimal, ""));
+    let mut frac = frac.to_string();
+    if frac.len() < places {
+        frac.extend(std::iter::repeat_n('0', places - frac.len()));
+    }
+    normalize(&format!("{}{}.{}", int, &frac[..places], &frac[places..]))
+}
+
+fn group_thousands(int: &str) -> String {
+    let mut out = String::with_capacity(int.len() + int.len() / 3);
+    for (i, c) in int.chars().enumerate() {
+        if i > 0 && (int.len() - i).is_multiple_of(3) {
+            out.push(',');
+        }
+        out.push(c);
+    }
+    out
+}
+
+/// Strip leading integer zeros and trailing fraction zeros.
+pub(crate) fn normalize(decimal: &str) -> String {
+    let (int, frac) = decimal.split_once('.').unwrap_or((decimal, ""));
+    let int = int.trim_start_matches('0');
+    let int = if int.is_empty() { "0" } else { int };
+    let frac = frac.trim_end_matches('0');
+    if frac.is_empty() {
+        int.to_string()
+    } else {
+        format!("{}.{}", int, frac)
+    }
+}
+
+/// Round a decimal string half away from zero.
+pub(crate) fn round_decimal(decimal: &str, decimals: u32) -> String {
+    if let Some(magnitude) = decimal.strip_prefix('-') {
+        return format!("-{}", round_decimal(magnitude, decimals));
+    }
+    let (int, frac) = decimal.split_once('.').unwrap_or((decimal, ""));
+    let decimals = decimals as usize;
+    if frac.len() <= decimals {
+        return normalize(decimal);
+    }
+    let round_up = frac.as_bytes()[decimals] >= b'5';
+    let mut digits: Vec<u8> = format!("{}{}", int, &frac[..decimals]).into_bytes();
+    if round_up {
+        let mut i = digits.len();
+        loop {
+            if i == 0 {
+                digits.insert(0, b'1');
+                break;
+            }
+            i -= 1;
+            if digits[i] == b'9' {
+                digits[i] = b'0';
+            } else {
+                digits[i] += 1;
+                break;
+            }
+        }
+    }
+    let digits = String::from_utf8(digits).expect("ascii digits");
+    let split = digits.len() - decimals;
+    normalize(&format!("{}.{}", &digits[..split], &digits[split..]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_table() {
+        let accepted = [
+            ("1,250.5", "1250.5"),
+            ("1250.50", "1250.5"),
+            (" 0.5k ", "500"),
+            ("1.25M", "1250000"),
+            ("2k", "2000"),
+            ("0.0001k", "0.1"),
+            ("1,234", "1234"),
+            ("1.234", "1.234"),
+            ("007", "7"),
+        ];
+        for (input, expected) in accepted {
+            assert_eq!(parse_amount(input).unwrap().as_str(), expected, "{input}");
+        }
+
+        let rejected = [
+            ("1.234,56", Some("write 1234.56")),
+            ("1234,5", Some("write 1234.5")),
+            ("0,5", Some("write 0.5")),
+            ("1.234.567,891", Some("write 1234567.891")),
+            ("12,34,567.5", None),
+            ("1 234", None),
+            ("1'234", None),
+            ("-5", None),
+            ("+5", None),
+            ("", None),
+            ("k", None),
+            ("1.", None),
+            ("1e5", None),
+            ("5kk", None),
+        ];
+        for (input, hint) in rejected {
+            let err = parse_amount(input).unwrap_err();
+            assert!(matches!(err, Error::InvalidAmount(_)), "{input}");
+            if let Some(hint) = hint {
+                assert!(err.to_string().contains(hint), "{input}: {err}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_format_round_trips_through_spec_decimals() {
+        let spec = SymbolSpec::new(2, 4);
+        assert_eq!(format_price("BTC-USD", 100000.0, &spec), "100,000.00 USD");
+        assert_eq!(format_price("BTC-USD", 99.995, &spec), "100.00 USD");
+        assert_eq!(format_size("BTC-USD", 1234.5, &spec), "1,234.5000 BTC");
+        assert_eq!(format_amount(0.5, 0), "1");
+        assert_eq!(format_amount(123456789.0, 0), "123,456,789");
+
+        for price in [0.01, 0.3, 1.5, 999.99, 1000.0, 100000.05, 1234567.89] {
+            let shown = format_amount(price, spec.price_decimals);
+            assert_eq!(parse_amount(&shown).unwrap().to_f64(), price, "{shown}");
+        }
+        // Values finer than the spec come back at the spec's precision.
+        let shown = format_amount(2.675, 2);
+        assert_eq!(parse_amount(&shown).unwrap(), Decimal::from(2.68));
+    }
+}
@@ -0,0 +1,515 @@
+//! Compact length-prefixed binary encoding of signed transactions
+//!
+//! A frame is `[body_len: u32 LE][body][crc32(body): u32 LE]`. The body is:
+//!
+//! | field        | encoding                                         |
+//! |--------------|--------------------------------------------------|
+//! | version      | `u8` (currently `1`)                             |
+//! | flags        | `u8`: bit 0 = order_id, bit 1 = order_ids        |
+//! | account      | 32 raw bytes                                     |
+//! | signer       | 32 raw bytes                                     |
+//! | signature    | 64 raw bytes                                     |
+//! | nonce        | LEB128 varint                                    |
+//! | actions      | varint length + wincode bytes (as signed)        |
+//! | order_id     | 32 raw bytes, if flagged                         |
+//! | order_ids    | varint count + 32 raw bytes each, if flagged     |
+//!
+//! Actions are stored in their signed wire form, so prices and sizes carry
+//! the wire's 1e-8 resolution when read back.
+//!
+//! Because every frame is length-prefixed, a reader that meets an unknown
+//! version reports [`Error::InvalidFrame`] but stays aligned on the next frame.
+
+use crate::sdk_compat::{deserialize_actions, serialize_actions};
+use crate::{compact, Error, Hash, Pubkey, Result, SignedTransaction};
+use std::io::{ErrorKind, Read, Write};
+
+/// Current body layout version.
+pub const FRAME_VERSION: u8 = 1;
+
+/// Largest body a reader will accept.
+pub const MAX_FRAME_LEN: usize = 64 * 1024;
+
+const FLAG_ORDER_ID: u8 = 1 << 0;
+const FLAG_ORDER_IDS: u8 = 1 << 1;
+const KNOWN_FLAGS: u8 = FLAG_ORDER_ID | FLAG_ORDER_IDS;
+
+fn invalid(msg: impl Into<String>) -> Error {
+    Error::InvalidFrame(msg.into())
+}
+
+fn io_error(e: std::io::Error) -> Error {
+    match e.kind() {
+        ErrorKind::UnexpectedEof => invalid("truncated frame"),
+        _ => invalid(e.to_string()),
+    }
+}
+
+fn put_varint(out: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        out.push((value as u8) | 0x80);
+        value >>= 7;
+    }
+    out.push(value as u8);
+}
+
+/// Cursor over a frame body; every read is bounds-checked.
+struct Body<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> Body<'a> {
+    fn take(&mut self, n: usize) -> Result<&'a [u8]> {
+        if n > self.bytes.len() {
+            return Err(invalid("truncated frame body"));
+        }
+        let (head, tail) = self.bytes.split_at(n);
+        self.bytes = tail;
+        Ok(head)
+    }
+
+    fn u8(&mut self) -> Result<u8> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn array<const N: usize>(&mut self) -> Result<[u8; N]> {
+        let mut out = [0u8; N];
+        out.copy_from_slice(self.take(N)?);
+        Ok(out)
+    }
+
+    fn varint(&mut self) -> Result<u64> {
+        let mut value = 0u64;
+        for shift in (0..64).step_by(7) {
+            let byte = self.u8()?;
+            let bits = u64::from(byte & 0x7f);
+            if shift == 63 && bits > 1 {
+                return Err(invalid("varint overflow"));
+            }
+            value |= bits << shift;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        Err(invalid("varint overflow"))
+    }
+
+    fn len(&mut self) -> Result<usize> {
+        let len = self.varint()?;
+        usize::try_from(len)
+            .ok()
+            .filter(|len| *len <= self.bytes.len())
+            .ok_or_else(|| invalid("length exceeds frame body"))
+    }
+}
+
+fn decode_signature(s: &str) -> Result<[u8; 64]> {
+    let bytes = bs58::decode(s)
+        .into_vec()
+        .map_err(|e| Error::InvalidBase58(e.to_string()))?;
+    let got = bytes.len();
+    bytes
+        .try_into()
+        .map_err(|_| Error::InvalidSignatureLength(got))
+}
+
+fn encode_body(tx: &SignedTransaction) -> Result<Vec<u8>> {
+    let account = Pubkey::from_base58(&tx.account)?;
+    let signer = Pubkey::from_base58(&tx.signer)?;
+    let signature = decode_signature(&tx.signature)?;
+    let mut action_bytes = Vec::with_capacity(64);
+    serialize_actions(std::slice::from_ref(&tx.action), &mut action_bytes)?;
+
+    let mut flags = 0;
+    if tx.order_id.is_some() {
+        flags |= FLAG_ORDER_ID;
+    }
+    if tx.order_ids.is_some() {
+        flags |= FLAG_ORDER_IDS;
+    }
+
+    let mut body = Vec::with_capacity(144 + action_bytes.len());
+    body.push(FRAME_VERSION);
+    body.push(flags);
+    body.extend_from_slice(account.as_bytes());
+    body.extend_from_slice(signer.as_bytes());
+    body.extend_from_slice(&signature);
+    put_varint(&mut body, tx.nonce);
+    put_varint(&mut body, action_bytes.len() as u64);
+    body.extend_from_slice(&action_bytes);
+    if let Some(order_id) = &tx.order_id {
+        body.extend_from_slice(Hash::from_base58(order_id)?.as_bytes());
+    }
+    if let Some(order_ids) = &tx.order_ids {
+        put_varint(&mut body, order_ids.len() as u64);
+        for id in order_ids {
+            body.extend_from_slice(Hash::from_base58(id)?.as_bytes());
+        }
+    }
+
+    if body.len() > MAX_FRAME_LEN {
+        return Err(invalid(format!(
+            "frame body of {} bytes exceeds {MAX_FRAME_LEN}",
+            body.len()
+        )));
+    }
+    Ok(body)
+}
+
+fn decode_body(bytes: &[u8]) -> Result<SignedTransaction> {
+    let mut body = Body { bytes };
+    let version = body.u8()?;
+    if version != FRAME_VERSION {
+        return Err(invalid(format!("unsupported frame version {version}")));
+    }
+    let flags = body.u8()?;
+    if flags & !KNOWN_FLAGS != 0 {
+        return Err(invalid(format!("unknown frame flags {flags:#04x}")));
+    }
+    let account = Pubkey::from_bytes(body.array()?);
+    let signer = Pubkey::from_bytes(body.array()?);
+    let signature: [u8; 64] = body.array()?;
+    let nonce = body.varint()?;
+    let action_len = body.len()?;
+    let action = compact::merge_actions(deserialize_actions(body.take(action_len)?)?)?;
+    let order_id = if flags & FLAG_ORDER_ID != 0 {
+        Some(Hash::from_bytes(body.array()?).to_base58())
+    } else {
+        None
+    };
+    let order_ids = if flags & FLAG_ORDER_IDS != 0 {
+        let count = body.varint()?;
+        if count > (body.bytes.len() / 32) as u64 {
+            return Err(invalid("order id count exceeds frame body"));
+        }
+        let ids = (0..count)
+            .map(|_| Ok(Hash::from_bytes(body.array()?).to_base58()))
+            .collect::<Result<Vec<_>>>()?;
+        Some(ids)
+    } else {
+        None
+    };
+    if !body.bytes.is_empty() {
+        return Err(invalid("trailing bytes in frame body"));
+    }
+
+    Ok(SignedTransaction {
+        action,
+        nonce,
+        account: account.to_base58(),
+        signer: signer.to_base58(),
+        signature: bs58::encode(signature).into_string(),
+        order_id,
+        order_ids,
+        id_scheme: Default::default(),
+        resign_count: 0,
+        group_kind: None,
+        group_warnings: Vec::new(),
+    })
+}
+
+/// Read the length prefix, or `None` on a clean end of stream.
+fn read_len(reader: &mut impl Read) -> Result<Option<usize>> {
+    let mut len = [0u8; 4];
+    let mut filled = 0;
+    while filled < len.len() {
+        match reader.read(&mut len[filled..]) {
+            Ok(0) if filled == 0 => return Ok(None),
+            Ok(0) => return Err(invalid("truncated frame length")),
+            Ok(n) => filled += n,
+            Err(e) if e.kind() == ErrorKind::Interrupted => {}
+            Err(e) => return Err(io_error(e)),
+        }
+    }
+    let len = u32::from_le_bytes(len) as usize;
+    if len > MAX_FRAME_LEN {
+        return Err(invalid(format!(
+            "frame length {len} exceeds {MAX_FRAME_LEN}"
+        )));
+    }
+    Ok(Some(len))
+}
+
+/// Why a frame could not be read, by whether the stream is still usable.
+enum FrameError {
+    /// Reading failed part-way (truncation or I/O), so the stream is no
+    /// longer aligned on a frame boundary.
+    Stream(Error),
+    /// The frame was read whole but is bad; the next frame follows it.
+    Frame(Error),
+}
+
+impl From<FrameError> for Error {
+    fn from(e: FrameError) -> Self {
+        match e {
+            FrameError::Stream(e) | FrameError::Frame(e) => e,
+        }
+    }
+}
+
+fn read_frame_after_len(
+    reader: &mut impl Read,
+    len: usize,
+) -> std::result::Result<SignedTransaction, FrameError> {
+    let stream = |e| FrameError::Stream(io_error(e));
+    let mut body = vec![0u8; len];
+    reader.read_exact(&mut body).map_err(stream)?;
+    let mut crc = [0u8; 4];
+    reader.read_exact(&mut crc).map_err(stream)?;
+    if u32::from_le_bytes(crc) != crc32fast::hash(&body) {
+        return Err(FrameError::Frame(invalid("checksum mismatch")));
+    }
+    decode_body(&body).map_err(FrameError::Frame)
+}
+
+impl SignedTransaction {
+    /// Write this transaction as a single frame.
+    pub fn write_framed(&self, writer: &mut impl Write) -> Result<()> {
+        let body = encode_body(self)?;
+        let mut frame = Vec::with_capacity(body.len() + 8);
+        frame.extend_from_slice(&(body.len() as u32).to_le_bytes());
+        frame.extend_from_slice(&body);
+        frame.extend_from_slice(&crc32fast::hash(&body).to_le_bytes());
+        writer.write_all(&frame).map_err(io_error)
+    }
+
+    /// Read a single frame written by [`SignedTransaction::write_framed`].
+    ///
+    /// `id_scheme`, `resign_count` and `group_kind` are not framed and come back as defaults.
+    pub fn read_framed(reader: &mut impl Read) -> Result<Self> {
+        let len = read_len(reader)?.ok_or_else(|| invalid("empty stream"))?;
+        Ok(read_frame_after_len(reader, len)?)
+    }
+
+    /// Encode this transaction as a single frame.
+    pub fn to_framed(&self) -> Result<Vec<u8>> {
+        let mut out = Vec::new();
+        self.write_framed(&mut out)?;
+        Ok(out)
+    }
+
+    /// Decode a buffer holding exactly one frame.
+    pub fn from_framed(mut bytes: &[u8]) -> Result<Self> {
+        let tx = Self::read_framed(&mut bytes)?;
+        if !bytes.is_empty() {
+            return Err(invalid("trailing bytes after frame"));
+        }
+        Ok(tx)
+    }
+}
+
+/// Writes a stream of framed transactions.
+#[derive(Debug)]
+pub struct FramedBatchWriter<W: Write> {
+    inner: W,
+    count: usize,
+}
+
+impl<W: Write> FramedBatchWriter<W> {
+    /// Wrap a writer.
+    pub fn new(inner: W) -> Self {
+        Self { inner, count: 0 }
+    }
+
+    /// Append one transaction.
+    pub fn write(&mut self, tx: &SignedTransaction) -> Result<()> {
+        tx.write_framed(&mut self.inner)?;
+        self.count += 1;
+        Ok(())
+    }
+
+    /// Number of frames written so far.
+    pub fn count(&self) -> usize {
+        self.count
+    }
+
+    /// Flush and return the underlying writer.
+    pub fn into_inner(mut self) -> Result<W> {
+        self.inner.flush().map_err(io_error)?;
+        Ok(self.inner)
+    }
+}
+
+/// Iterates the transactions in a stream written by [`FramedBatchWriter`].
+///
+/// Ends cleanly at a frame boundary. A frame with a bad checksum or an
+/// unknown version yields an error and the next call moves on to the
+/// following frame; a truncated or unreadable stream yields an error and
+/// then ends.
+#[derive(Debug)]
+pub struct FramedBatchReader<R: Read> {
+    inner: R,
+    done: bool,
+}
+
+impl<R: Read> FramedBatchReader<R> {
+    /// Wrap a reader.
+    pub fn new(inner: R) -> Self {
+        Self { inner, done: false }
+    }
+
+    /// Return the underlying reader.
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+}
+
+impl<R: Read> Iterator for FramedBatchReader<R> {
+    type Item = Result<SignedTransaction>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        let len = match read_len(&mut self.inner) {
+            Ok(Some(len)) => len,
+            Ok(None) => {
+                self.done = true;
+                return None;
+            }
+            Err(e) => {
+                self.done = true;
+                return Some(Err(e));
+            }
+        };
+        match read_frame_after_len(&mut self.inner, len) {
+            Ok(tx) => Some(Ok(tx)),
+            Err(FrameError::Frame(e)) => Some(Err(e)),
+            Err(FrameError::Stream(e)) => {
+                self.done = true;
+                Some(Err(e))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Cancel, Keypair, Order, OrderItem, Signer, TimeInForce};
+
+    fn sample_txs() -> Vec<SignedTransaction> {
+        let mut signer = Signer::new(Keypair::generate());
+        let order = Order::limit("BTC-USD", true, 100000.5, 0.25, TimeInForce::Gtc);
+        let cancel = Cancel::new("ETH-USD", Hash::random());
+        vec![
+            signer.sign(order.clone().into(), Some(1)).unwrap(),
+            signer
+                .sign_group(
+                    vec![order.into(), OrderItem::Cancel(cancel)],
+                    Some(u64::MAX),
+                )
+                .unwrap(),
+            signer.sign_faucet(None).unwrap(),
+        ]
+    }
+
+    #[test]
+    fn test_framed_round_trip() {
+        let txs = sample_txs();
+        let mut writer = FramedBatchWriter::new(Vec::new());
+        for tx in &txs {
+            writer.write(tx).unwrap();
+        }
+        assert_eq!(writer.count(), txs.len());
+        let bytes = writer.into_inner().unwrap();
+
+        let decoded = FramedBatchReader::new(bytes.as_slice())
+            .collect::<Result<Vec<_>>>()
+            .unwrap();
+        assert_eq!(decoded.len(), txs.len());
+        for (a, b) in txs.iter().zip(&decoded) {
+            assert_eq!(a.actions_json().unwrap(), b.actions_json().unwrap());
+            assert_eq!(a.nonce, b.nonce);
+            assert_eq!(a.account, b.account);
+            assert_eq!(a.signer, b.signer);
+            assert_eq!(a.signature, b.signature);
+            assert_eq!(a.order_id, b.order_id);
+            assert_eq!(a.order_ids, b.order_ids);
+        }
+
+        let single = SignedTransaction::from_framed(&txs[0].to_framed().unwrap()).unwrap();
+        assert_eq!(single.to_json().unwrap(), txs[0].to_json().unwrap());
+    }
+
+    #[test]
+    fn test_framed_rejects_truncated_and_corrupted() {
+        for tx in sample_txs() {
+            let frame = tx.to_framed().unwrap();
+            for end in 0..frame.len() {
+                assert!(SignedTransaction::from_framed(&frame[..end]).is_err());
+            }
+            for i in 0..frame.len() {
+                for bit in 0..8 {
+                    let mut corrupted = frame.clone();
+                    corrupted[i] ^= 1 << bit;
+                    assert!(SignedTransaction::from_framed(&corrupted).is_err());
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_framed_body_decoder_never_panics() {
+        // Bypass the checksum so the body parser sees arbitrary garbage.
+        let tx = &sample_txs()[1];
+        let body = encode_body(tx).unwrap();
+        let mut state = 0x9e37_79b9_7f4a_7c15u64;
+        for _ in 0..2000 {
+            let mut mutated = body.clone();
+            for _ in 0..4 {
+                state ^= state << 13;
+                state ^= state >> 7;
+                state ^= state << 17;
+                let i = (state as usize) % mutated.len();
+                mutated[i] = (state >> 32) as u8;
+            }
+            mutated.truncate((state >> 40) as usize % (body.len() + 1));
+            let _ = decode_body(&mutated);
+        }
+    }
+
+    #[test]
+    fn test_framed_reader_skips_unknown_version() {
+        let txs = sample_txs();
+        let mut future = encode_body(&txs[0]).unwrap();
+        future[0] = FRAME_VERSION + 1;
+        let mut stream = Vec::new();
+        stream.extend_from_slice(&(future.len() as u32).to_le_bytes());
+        stream.extend_from_slice(&future);
+        stream.extend_from_slice(&crc32fast::hash(&future).to_le_bytes());
+        txs[2].write_framed(&mut stream).unwrap();
+
+        let results: Vec<_> = FramedBatchReader::new(stream.as_slice()).collect();
+        assert_eq!(results.len(), 2);
+        assert!(matches!(&results[0], Err(Error::InvalidFrame(_))));
+        assert_eq!(results[1].as_ref().unwrap().signature, txs[2].signature);
+    }
+
+    #[test]
+    fn test_framed_reader_skips_short_body_but_stops_on_truncation() {
+        let txs = sample_txs();
+        // A whole frame whose body ends early: bad, but the stream stays aligned.
+        let short = [FRAME_VERSION, 0];
+        let mut stream = Vec::new();
+        stream.extend_from_slice(&(short.len() as u32).to_le_bytes());
+        stream.extend_from_slice(&short);
+        stream.extend_from_slice(&crc32fast::hash(&short).to_le_bytes());
+        txs[0].write_framed(&mut stream).unwrap();
+
+        let results: Vec<_> = FramedBatchReader::new(stream.as_slice()).collect();
+        assert_eq!(results.len(), 2);
+        assert!(matches!(&results[0], Err(Error::InvalidFrame(_))));
+        assert_eq!(results[1].as_ref().unwrap().signature, txs[0].signature);
+
+        // A stream cut off mid-body yields one error and then ends.
+        let mut cut = Vec::new();
+        txs[0].write_framed(&mut cut).unwrap();
+        txs[1].write_framed(&mut cut).unwrap();
+        cut.truncate(cut.len() - 6);
+        let results: Vec<_> = FramedBatchReader::new(cut.as_slice()).collect();
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].as_ref().unwrap().signature, txs[0].signature);
+        assert!(matches!(&results[1], Err(Error::InvalidFrame(_))));
+    }
+}
@@ -0,0 +1,85 @@
+//! Hardware-wallet payload encodings for prepared messages
+//!
+//! Both encodings wrap the raw message bytes unchanged, so the signature a
+//! device returns is over the same bytes [`crate::finalize_transaction`]
+//! expects.
+//!
+//! - **Ledger**: `[version: u8][message_len: u32 BE][message]`, ready to be
+//!   chunked into `signMessage` APDUs by the host.
+//! - **Trezor**: a `SolanaSignTx` wire frame,
+//!   `"##"[message_type: u16 BE][payload_len: u32 BE][protobuf payload]`,
+//!   where the payload carries the derivation path and the message as
+//!   `serialized_tx`.
+
+/// Version byte of the Ledger payload header.
+pub const LEDGER_PAYLOAD_VERSION: u8 = 0;
+
+/// Trezor `MessageType_SolanaSignTx`.
+pub const TREZOR_SOLANA_SIGN_TX: u16 = 904;
+
+/// Default Solana derivation path, `m/44'/501'/0'/0'`.
+pub const SOLANA_DEFAULT_PATH: [u32; 4] = [HARDENED | 44, HARDENED | 501, HARDENED, HARDENED];
+
+const HARDENED: u32 = 0x8000_0000;
+
+/// Wrap message bytes in the Ledger blind-signing header.
+pub fn encode_for_ledger(message: &[u8]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(5 + message.len());
+    out.push(LEDGER_PAYLOAD_VERSION);
+    out.extend_from_slice(&(message.len() as u32).to_be_bytes());
+    out.extend_from_slice(message);
+    out
+}
+
+/// Wrap message bytes in a Trezor `SolanaSignTx` frame signing with `path`.
+pub fn encode_for_trezor(message: &[u8], path: &[u32]) -> Vec<u8> {
+    let mut payload = Vec::with_capacity(path.len() * 6 + message.len() + 6);
+    for index in path {
+        // field 1 (address_n), varint
+        payload.push(0x08);
+        put_varint(&mut payload, *index as u64);
+    }
+    // field 2 (serialized_tx), length-delimited
+    payload.push(0x12);
+    put_varint(&mut payload, message.len() as u64);
+    payload.extend_from_slice(message);
+
+    let mut out = Vec::with_capacity(8 + payload.len());
+    out.extend_from_slice(b"##");
+    out.extend_from_slice(&TREZOR_SOLANA_SIGN_TX.to_be_bytes());
+    out.extend_from_slice(&(payload.len() as u32).to_be_bytes());
+    out.extend_from_slice(&payload);
+    out
+}
+
+fn put_varint(out: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        out.push((value as u8) | 0x80);
+        value >>= 7;
+    }
+    out.push(value as u8);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ledger_header() {
+        let out = encode_for_ledger(b"abc");
+        assert_eq!(out, [0, 0, 0, 0, 3, b'a', b'b', b'c']);
+    }
+
+    #[test]
+    fn test_trezor_frame() {
+        let out = encode_for_trezor(b"abc", &[HARDENED | 44, 0]);
+        let payload = [
+            0x08, 0xac, 0x80, 0x80, 0x80, 0x08, // address_n 44'
+            0x08, 0x00, // address_n 0
+            0x12, 0x03, b'a', b'b', b'c', // serialized_tx
+        ];
+        assert_eq!(&out[..4], &[b'#', b'#', 0x03, 0x88]);
+        assert_eq!(&out[4..8], &(payload.len() as u32).to_be_bytes());
+        assert_eq!(&out[8..], &payload);
+    }
+}
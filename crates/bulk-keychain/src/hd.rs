@@ -0,0 +1,222 @@
+//! SLIP-0010 hierarchical derivation for Ed25519 keys
+//!
+//! Children are derived with HMAC-SHA512 as in SLIP-0010. Ed25519 has no
+//! public-key derivation, so every step is hardened: paths are written
+//! `m/44'/501'/0'/0'` (or with `h`/`H` suffixes), and a non-hardened index
+//! is [`Error::InvalidDerivationPath`].
+//!
+//! A keypair not itself derived is treated as a SLIP-0010 seed: its 32-byte
+//! secret is the seed of the master node `m`. A derived keypair remembers
+//! its chain code, so deriving from it continues down the same tree.
+//!
+//! ```rust
+//! use bulk_keychain::Keypair;
+//!
+//! let root = Keypair::generate();
+//! let agent = root.from_derivation_path("m/44'/501'/0'/1'").unwrap();
+//! let stepwise = root
+//!     .derive_child(44, true)
+//!     .and_then(|k| k.derive_child(501, true))
+//!     .and_then(|k| k.derive_child(0, true))
+//!     .and_then(|k| k.derive_child(1, true))
+//!     .unwrap();
+//! assert_eq!(agent.pubkey(), stepwise.pubkey());
+//! assert!(root.derive_child(0, false).is_err());
+//! ```
+
+use crate::{Error, Keypair, Result};
+use hmac::{Hmac, Mac};
+use sha2::Sha512;
+use zeroize::Zeroizing;
+
+/// Offset of hardened child indexes.
+pub const HARDENED_OFFSET: u32 = 1 << 31;
+
+/// HMAC key of the master node, fixed by SLIP-0010 for Ed25519.
+const SEED_KEY: &[u8] = b"ed25519 seed";
+
+/// Secret key and chain code of one node of the tree.
+#[derive(Clone)]
+pub(crate) struct ExtendedKey {
+    pub(crate) secret: Zeroizing<[u8; 32]>,
+    pub(crate) chain_code: Zeroizing<[u8; 32]>,
+}
+
+impl ExtendedKey {
+    /// Master node `m` of `seed`.
+    pub(crate) fn master(seed: &[u8]) -> Self {
+        Self::from_hmac(&Self::hmac(SEED_KEY, &[seed]))
+    }
+
+    /// Hardened child at `index` (below [`HARDENED_OFFSET`]).
+    pub(crate) fn child(&self, index: u32) -> Self {
+        let index = (index | HARDENED_OFFSET).to_be_bytes();
+        Self::from_hmac(&Self::hmac(
+            self.chain_code.as_slice(),
+            &[&[0], self.secret.as_slice(), &index],
+        ))
+    }
+
+    /// Node at `path`, relative to this one.
+    pub(crate) fn derive_path(&self, path: &str) -> Result<Self> {
+        Ok(parse_path(path)?
+            .into_iter()
+            .fold(self.clone(), |node, index| node.child(index)))
+    }
+
+    fn hmac(key: &[u8], parts: &[&[u8]]) -> Zeroizing<[u8; 64]> {
+        let mut mac = Hmac::<Sha512>::new_from_slice(key).expect("HMAC takes keys of any length");
+        for part in parts {
+            mac.update(part);
+        }
+        Zeroizing::new(mac.finalize().into_bytes().into())
+    }
+
+    fn from_hmac(output: &[u8; 64]) -> Self {
+        let mut secret = Zeroizing::new([0u8; 32]);
+        let mut chain_code = Zeroizing::new([0u8; 32]);
+        secret.copy_from_slice(&output[..32]);
+        chain_code.copy_from_slice(&output[32..]);
+        Self { secret, chain_code }
+    }
+}
+
+/// Child indexes (without [`HARDENED_OFFSET`]) of a path like
+/// `m/44'/501'/0'/0'`.
+pub fn parse_path(path: &str) -> Result<Vec<u32>> {
+    let invalid = |reason: String| Error::InvalidDerivationPath(format!("{}: {}", path, reason));
+    let mut segments = path.trim().split('/');
+    if segments.next() != Some("m") {
+        return Err(invalid("must start with m".into()));
+    }
+    segments
+        .map(|segment| {
+            let index = segment.strip_suffix(['\'', 'h', 'H']).ok_or_else(|| {
+                invalid(format!(
+                    "{} is not hardened; Ed25519 derives hardened children only",
+                    segment
+                ))
+            })?;
+            let index: u32 = index
+                .parse()
+                .map_err(|_| invalid(format!("{} is not a child index", segment)))?;
+            check_index(index).map_err(|_| invalid(format!("{} is out of range", segment)))
+        })
+        .collect()
+}
+
+fn check_index(index: u32) -> Result<u32> {
+    if index >= HARDENED_OFFSET {
+        return Err(Error::InvalidDerivationPath(format!(
+            "child index {} is not below 2^31",
+            index
+        )));
+    }
+    Ok(index)
+}
+
+impl Keypair {
+    /// Derive the hardened child at `index` (see [`crate::hd`]).
+    ///
+    /// `hardened` must be `true`: Ed25519 under SLIP-0010 has no
+    /// non-hardened children, and asking for one is an error rather than a
+    /// silently hardened key.
+    pub fn derive_child(&self, index: u32, hardened: bool) -> Result<Keypair> {
+        if !hardened {
+            return Err(Error::InvalidDerivationPath(format!(
+                "child {} is not hardened; Ed25519 derives hardened children only",
+                index
+            )));
+        }
+        let child = self.extended_key().child(check_index(index)?);
+        Ok(Keypair::from_extended_key(&child))
+    }
+
+    /// Derive the key at a path like `m/44'/501'/0'/0'`, where `m` is this
+    /// keypair (see [`crate::hd`]).
+    pub fn from_derivation_path(&self, path: &str) -> Result<Keypair> {
+        let node = self.extended_key().derive_path(path)?;
+        Ok(Keypair::from_extended_key(&node))
+    }
+
+    fn extended_key(&self) -> ExtendedKey {
+        match self.chain_code() {
+            Some(chain_code) => ExtendedKey {
+                secret: Zeroizing::new(*self.secret_key()),
+                chain_code: Zeroizing::new(*chain_code),
+            },
+            None => ExtendedKey::master(self.secret_key()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hex32(node: &ExtendedKey) -> (String, String) {
+        (hex::encode(*node.secret), hex::encode(*node.chain_code))
+    }
+
+    #[test]
+    fn test_slip10_vector_1() {
+        let seed = hex::decode("000102030405060708090a0b0c0d0e0f").unwrap();
+        let master = ExtendedKey::master(&seed);
+        assert_eq!(
+            hex32(&master),
+            (
+                "2b4be7f19ee27bbf30c667b642d5f4aa69fd169872f8fc3059c08ebae2eb19e7".into(),
+                "90046a93de5380a72b5e45010748567d5ea02bbf6522f979e05c0d8d8ca9fffb".into()
+            )
+        );
+        assert_eq!(
+            hex32(&master.derive_path("m/0'/1'").unwrap()),
+            (
+                "b1d0bad404bf35da785a64ca1ac54b2617211d2777696fbffaf208f746ae84f2".into(),
+                "a320425f77d1b5c2505a6b1b27382b37368ee640e3557c315416801243552f14".into()
+            )
+        );
+    }
+
+    #[test]
+    fn test_same_path_same_key() {
+        let root = Keypair::generate();
+        let a = root.from_derivation_path("m/44'/501'/0'/0'").unwrap();
+        let b = root.from_derivation_path("m/44h/501h/0h/0H").unwrap();
+        assert_eq!(a.pubkey(), b.pubkey());
+        assert_eq!(
+            a.derive_child(7, true).unwrap().pubkey(),
+            root.from_derivation_path("m/44'/501'/0'/0'/7'")
+                .unwrap()
+                .pubkey()
+        );
+
+        let other = root.from_derivation_path("m/44'/501'/0'/1'").unwrap();
+        assert_ne!(a.pubkey(), other.pubkey());
+        assert_ne!(a.pubkey(), root.pubkey());
+        assert_eq!(root.from_derivation_path("m").unwrap().pubkey(), {
+            let master = ExtendedKey::master(root.secret_key());
+            Keypair::from_extended_key(&master).pubkey()
+        });
+    }
+
+    #[test]
+    fn test_rejects_unhardened_and_malformed_paths() {
+        let root = Keypair::generate();
+        for path in [
+            "m/44'/60'/0'/0/1",
+            "44'/0'",
+            "m/x'",
+            "m/2147483648'",
+            "m//0'",
+        ] {
+            let err = root.from_derivation_path(path).unwrap_err();
+            assert!(
+                matches!(&err, Error::InvalidDerivationPath(msg) if msg.starts_with(path)),
+                "{path}: {err}"
+            );
+        }
+        assert!(root.derive_child(0, false).is_err());
+        assert!(root.derive_child(HARDENED_OFFSET, true).is_err());
+    }
+}
@@ -0,0 +1,1085 @@
+//! camelCase order intents
+//!
+//! [`OrderSpec`] is the `{ "type": "order", "isBuy": true, ... }` shape the
+//! Node and WASM bindings accept, so services can take the same JSON in Rust:
+//!
+//! ```rust
+//! use bulk_keychain::{Keypair, OrderItem, Signer};
+//!
+//! let intents = serde_json::json!([
+//!     { "type": "order", "symbol": "BTC-USD", "isBuy": true, "price": 100000.0, "size": 0.1,
+//!       "orderType": { "type": "limit", "tif": "GTC" } },
+//!     { "type": "cancelAll", "symbols": ["ETH-USD"] },
+//! ]);
+//! let items = intents
+//!     .as_array()
+//!     .unwrap()
+//!     .iter()
+//!     .cloned()
+//!     .map(OrderItem::try_from)
+//!     .collect::<bulk_keychain::Result<Vec<_>>>()
+//!     .unwrap();
+//!
+//! let mut signer = Signer::new(Keypair::generate());
+//! let signed = signer.sign_all(items, None).unwrap();
+//! assert_eq!(signed.len(), 2);
+//! ```
+//!
+//! Field names may also be snake_case (`is_buy`, `order_type`), and unknown
+//! fields are dropped with a warning; see [`OrderSpec::from_json`].
+
+use crate::types::*;
+use crate::{Error, Result, SymbolSpec};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+/// One order intent in the bindings' camelCase input shape.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OrderSpec {
+    /// Item kind: `order`, `modify`, `cancel`, `cancelAll`, `stop`,
+    /// `takeProfit`, `range`, `trig`, `onFill` or `trailingStop`
+    #[serde(rename = "type")]
+    pub item_type: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub symbol: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub is_buy: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub price: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub size: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reduce_only: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub iso: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub order_type: Option<OrderTypeSpec>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub client_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub order_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub amount: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub symbols: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_age_ms: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub trigger_price: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub limit_price: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pmin: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pmax: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub lmin: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub lmax: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub trail_bps: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub step_bps: Option<u32>,
+    /// Parent seqno of an `onFill` item, default `0`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub p: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub actions: Option<Vec<OrderSpec>>,
+    /// Integer price ticks of `symbolSpec` (instead of `price`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub price_ticks: Option<i64>,
+    /// Integer size lots of `symbolSpec` (instead of `size`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub size_lots: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub symbol_spec: Option<SymbolSpecInput>,
+    /// Actions to run once this order fills; see [`OrderSpec::into_items`]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub on_fill: Option<OnFillSpec>,
+}
+
+/// `onFill` of an [`OrderSpec`].
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct OnFillSpec {
+    /// Parent seqno
+    pub p: u32,
+    pub actions: Vec<OrderSpec>,
+}
+
+/// `orderType` of an [`OrderSpec`].
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OrderTypeSpec {
+    /// `limit`, `trigger` or `market`
+    #[serde(rename = "type")]
+    pub type_name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tif: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub is_market: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub trigger_px: Option<f64>,
+}
+
+/// `symbolSpec` of an [`OrderSpec`]; units default to `1`.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SymbolSpecInput {
+    pub price_decimals: u32,
+    pub size_decimals: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tick_units: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub lot_units: Option<u64>,
+}
+
+impl From<SymbolSpecInput> for SymbolSpec {
+    fn from(s: SymbolSpecInput) -> Self {
+        SymbolSpec::new(s.price_decimals, s.size_decimals)
+            .with_tick_units(s.tick_units.unwrap_or(1))
+            .with_lot_units(s.lot_units.unwrap_or(1))
+    }
+}
+
+/// How strictly [`OrderSpec::from_json`] and [`check_fields`] read input.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum InputMode {
+    /// camelCase and snake_case names, even mixed; unknown fields are dropped
+    /// with a warning
+    #[default]
+    Tolerant,
+    /// One casing per input, and unknown fields are an error
+    Strict,
+}
+
+/// A field no input schema knows, such as a typo like `reduceOnIy`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnknownField {
+    /// Path to the field as given, e.g. `orderType.tiff`
+    pub path: String,
+    /// Nearest known field name, if close enough to be a typo
+    pub suggestion: Option<&'static str>,
+}
+
+impl std::fmt::Display for UnknownField {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.path)?;
+        if let Some(suggestion) = self.suggestion {
+            write!(f, " (did you mean {}?)", suggestion)?;
+        }
+        Ok(())
+    }
+}
+
+/// An [`OrderSpec`] read from JSON, plus the fields it ignored.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParsedOrderSpec {
+    pub spec: OrderSpec,
+    /// One `path: unknown field` entry per dropped field, with a suggestion
+    /// when the name looks like a typo
+    pub warnings: Vec<String>,
+}
+
+pub(crate) const ORDER_FIELDS: &[&str] = &[
+    "type",
+    "symbol",
+    "isBuy",
+    "price",
+    "size",
+    "reduceOnly",
+    "iso",
+    "orderType",
+    "clientId",
+    "orderId",
+    "amount",
+    "symbols",
+    "maxAgeMs",
+    "triggerPrice",
+    "limitPrice",
+    "pmin",
+    "pmax",
+    "lmin",
+    "lmax",
+    "trailBps",
+    "stepBps",
+    "p",
+    "actions",
+    "priceTicks",
+    "sizeLots",
+    "symbolSpec",
+    "onFill",
+];
+pub(crate) const ORDER_TYPE_FIELDS: &[&str] = &["type", "tif", "isMarket", "triggerPx"];
+pub(crate) const SYMBOL_SPEC_FIELDS: &[&str] =
+    &["priceDecimals", "sizeDecimals", "tickUnits", "lotUnits"];
+const ON_FILL_FIELDS: &[&str] = &["p", "actions"];
+
+fn camel_case(key: &str) -> String {
+    let mut out = String::with_capacity(key.len());
+    let mut upper = false;
+    for c in key.chars() {
+        if c == '_' {
+            upper = true;
+        } else if upper {
+            out.extend(c.to_uppercase());
+            upper = false;
+        } else {
+            out.push(c);
+        }
+    }
+    out
+}
+
+/// Levenshtein distance.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let above = row[j + 1];
+            row[j + 1] = if ca == *cb {
+                diagonal
+            } else {
+                1 + diagonal.min(above).min(row[j])
+            };
+            diagonal = above;
+        }
+    }
+    row[b.len()]
+}
+
+/// Nearest of `known` to `key`, ignoring case and casing convention, if it is
+/// close enough to be a typo.
+pub fn suggest_field(key: &str, known: &[&'static str]) -> Option<&'static str> {
+    let key = camel_case(key).to_ascii_lowercase();
+    let max_distance = (key.len() / 3).max(1);
+    known
+        .iter()
+        .map(|name| (edit_distance(&key, &name.to_ascii_lowercase()), *name))
+        .filter(|(distance, _)| *distance <= max_distance)
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, name)| name)
+}
+
+/// Integral floats as integers, so JS numbers read into integer fields.
+fn integral(n: &serde_json::Number) -> Option<serde_json::Number> {
+    const MAX_SAFE_INTEGER: f64 = 9_007_199_254_740_991.0;
+    let f = n.as_f64().filter(|_| n.is_f64())?;
+    (f.fract() == 0.0 && f.abs() <= MAX_SAFE_INTEGER).then(|| (f as i64).into())
+}
+
+#[derive(Default)]
+struct Normalizer {
+    unknown: Vec<UnknownField>,
+    camel: Option<String>,
+    snake: Option<String>,
+}
+
+impl Normalizer {
+    /// camelCase name of `key` if it is one of `fields`; otherwise record it.
+    fn known(&mut self, key: &str, path: &str, fields: &[&'static str]) -> Option<String> {
+        let name = if key.contains('_') {
+            camel_case(key)
+        } else {
+            key.to_string()
+        };
+        if !fields.contains(&name.as_str()) {
+            self.unknown.push(UnknownField {
+                path: format!("{}{}", path, key),
+                suggestion: suggest_field(key, fields),
+            });
+            return None;
+        }
+        if key != name {
+            self.snake.get_or_insert_with(|| key.to_string());
+        } else if name.contains(|c: char| c.is_ascii_uppercase()) {
+            self.camel.get_or_insert_with(|| key.to_string());
+        }
+        Some(name)
+    }
+
+    fn object(&mut self, value: &mut Value, path: &str, fields: &[&'static str]) -> Result<()> {
+        let Value::Object(map) = value else {
+            return Ok(());
+        };
+        let mut out = Map::new();
+        for (key, mut field) in std::mem::take(map) {
+            let Some(name) = self.known(&key, path, fields) else {
+                continue;
+            };
+            self.field(&mut field, &name, path)?;
+            if out.contains_key(&name) {
+                return Err(Error::InvalidOrder(format!(
+                    "{}{} is given more than once",
+                    path, name
+                )));
+            }
+            out.insert(name, field);
+        }
+        *map = out;
+        Ok(())
+    }
+
+    fn field(&mut self, value: &mut Value, name: &str, path: &str) -> Result<()> {
+        let nested = format!("{}{}.", path, name);
+        match (name, value) {
+            ("orderType", value) => self.object(value, &nested, ORDER_TYPE_FIELDS),
+            ("symbolSpec", value) => self.object(value, &nested, SYMBOL_SPEC_FIELDS),
+            ("onFill", value) => self.object(value, &nested, ON_FILL_FIELDS),
+            ("actions", Value::Array(actions)) => {
+                for (i, action) in actions.iter_mut().enumerate() {
+                    let nested = format!("{}actions[{}].", path, i);
+                    self.object(action, &nested, ORDER_FIELDS)?;
+                }
+                Ok(())
+            }
+            (_, Value::Number(n)) => {
+                if let Some(int) = integral(n) {
+                    *n = int;
+                }
+                Ok(())
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Warnings for the unknown fields, or the error `mode` calls for.
+    fn finish(self, mode: InputMode) -> Result<Vec<String>> {
+        if mode == InputMode::Strict {
+            if !self.unknown.is_empty() {
+                return Err(Error::UnknownFields(
+                    self.unknown.iter().map(|f| f.to_string()).collect(),
+                ));
+            }
+            if let (Some(camel), Some(snake)) = (&self.camel, &self.snake) {
+                return Err(Error::InvalidOrder(format!(
+                    "mixed field casing: {} and {}",
+                    camel, snake
+                )));
+            }
+        }
+        Ok(self
+            .unknown
+            .iter()
+            .map(|f| match f.suggestion {
+                Some(s) => format!("{}: unknown field (did you mean {}?)", f.path, s),
+                None => format!("{}: unknown field", f.path),
+            })
+            .collect())
+    }
+}
+
+/// Rewrite an intent's field names to camelCase in place and drop unknown
+/// fields, returning one warning per dropped field.
+///
+/// Errors if a field is given in both casings. Under [`InputMode::Strict`],
+/// unknown fields and mixed casing are errors too.
+pub fn normalize_order_json(value: &mut Value, mode: InputMode) -> Result<Vec<String>> {
+    let mut normalizer = Normalizer::default();
+    normalizer.object(value, "", ORDER_FIELDS)?;
+    normalizer.finish(mode)
+}
+
+/// Check a flat options object's field names against `known` (camelCase),
+/// the same way intents are checked. Nothing is rewritten.
+pub fn check_fields(value: &Value, known: &[&'static str], mode: InputMode) -> Result<Vec<String>> {
+    let mut normalizer = Normalizer::default();
+    if let Value::Object(map) = value {
+        for key in map.keys() {
+            normalizer.known(key, "", known);
+        }
+    }
+    normalizer.finish(mode)
+}
+
+/// Check that an intent reads and converts to items, without signing it.
+pub fn validate_order_input(value: Value, mode: InputMode) -> Result<ParsedOrderSpec> {
+    let parsed = OrderSpec::from_json(value, mode)?;
+    parsed.spec.clone().into_items()?;
+    Ok(parsed)
+}
+
+/// [`Error::InvalidOrderField`] for `kind.field`.
+fn field_error(kind: &str, field: &str, reason: impl Into<String>) -> Error {
+    Error::InvalidOrderField {
+        field: format!("{}.{}", kind, field),
+        reason: reason.into(),
+    }
+}
+
+fn required<T>(value: Option<T>, kind: &str, field: &str) -> Result<T> {
+    value.ok_or_else(|| field_error(kind, field, "is required"))
+}
+
+fn parse_hash(s: &str, kind: &str, field: &str) -> Result<Hash> {
+    Hash::from_base58(s).map_err(|e| field_error(kind, field, format!("is malformed: {}", e)))
+}
+
+/// [`parse_hash`] for a `clientId`, naming omission as the way to send none.
+fn parse_client_id(s: &str, kind: &str) -> Result<Hash> {
+    if s.is_empty() {
+        return Err(field_error(
+            kind,
+            "clientId",
+            "is empty; omit the field to send no client id",
+        ));
+    }
+    parse_hash(s, kind, "clientId")
+}
+
+fn finite(px: f64) -> Option<f64> {
+    (!px.is_nan()).then_some(px)
+}
+
+impl OrderSpec {
+    /// Read an intent with camelCase or snake_case field names.
+    pub fn from_json(mut value: Value, mode: InputMode) -> Result<ParsedOrderSpec> {
+        let warnings = normalize_order_json(&mut value, mode)?;
+        Ok(ParsedOrderSpec {
+            spec: OrderSpec::deserialize(value)?,
+            warnings,
+        })
+    }
+
+    /// The intent's item, followed by an `onFill` item if `onFill` is set.
+    ///
+    /// Converting with `OrderItem::try_from` ignores `onFill`.
+    pub fn into_items(mut self) -> Result<Vec<OrderItem>> {
+        let on_fill = self.on_fill.take();
+        let mut items = vec![OrderItem::try_from(self)?];
+        if let Some(of) = on_fill {
+            items.push(OrderItem::OnFill(OnFill {
+                p: of.p,
+                actions: of
+                    .actions
+                    .into_iter()
+                    .map(OrderItem::try_from)
+                    .collect::<Result<_>>()?,
+            }));
+        }
+        Ok(items)
+    }
+
+    /// Resolve `price`/`size`, or their exact tick/lot equivalents.
+    fn price_and_size(&self) -> Result<(f64, f64)> {
+        let spec = |steps_field: &str| {
+            self.symbol_spec.map(SymbolSpec::from).ok_or_else(|| {
+                Error::InvalidOrder(format!(
+                    "order.symbolSpec is required with order.{}",
+                    steps_field
+                ))
+            })
+        };
+        let price = match (self.price, self.price_ticks) {
+            (Some(_), Some(_)) => {
+                return Err(field_error(
+                    "order",
+                    "price",
+                    "and order.priceTicks are mutually exclusive",
+                ))
+            }
+            (Some(price), None) => price,
+            (None, Some(ticks)) => spec("priceTicks")?.price_from_ticks(ticks)?,
+            (None, None) => return Err(field_error("order", "price", "is required")),
+        };
+        let size = match (self.size, self.size_lots) {
+            (Some(_), Some(_)) => {
+                return Err(field_error(
+                    "order",
+                    "size",
+                    "and order.sizeLots are mutually exclusive",
+                ))
+            }
+            (Some(size), None) => size,
+            (None, Some(lots)) => spec("sizeLots")?.size_from_lots(lots)?,
+            (None, None) => return Err(field_error("order", "size", "is required")),
+        };
+        Ok((price, size))
+    }
+
+    fn nested(actions: Option<Vec<OrderSpec>>, kind: &str) -> Result<Vec<OrderItem>> {
+        required(actions, kind, "actions")?
+            .into_iter()
+            .map(OrderItem::try_from)
+            .collect()
+    }
+}
+
+impl TryFrom<OrderSpec> for OrderItem {
+    type Error = Error;
+
+    fn try_from(spec: OrderSpec) -> Result<Self> {
+        let iso = spec.iso.unwrap_or(false);
+        let item = match spec.item_type.as_str() {
+            "order" => {
+                let (price, size) = spec.price_and_size()?;
+                let order_type = match spec.order_type {
+                    Some(ot) => match ot.type_name.as_str() {
+                        "limit" => OrderType::limit(TimeInForce::try_from(
+                            ot.tif.as_deref().unwrap_or("GTC"),
+                        )?),
+                        "trigger" | "market" => OrderType::Trigger {
+                            is_market: ot.is_market.unwrap_or(true),
+                            trigger_px: ot.trigger_px.unwrap_or(0.0),
+                        },
+                        other => {
+                            return Err(Error::InvalidOrder(format!(
+                                "order.orderType.type: unknown type {}",
+                                other
+                            )))
+                        }
+                    },
+                    None => OrderType::limit(TimeInForce::Gtc),
+                };
+                let client_id = spec
+                    .client_id
+                    .as_deref()
+                    .map(|s| parse_client_id(s, "order"))
+                    .transpose()?;
+                OrderItem::Order(Order {
+                    symbol: required(spec.symbol, "order", "symbol")?,
+                    is_buy: required(spec.is_buy, "order", "isBuy")?,
+                    price,
+                    size,
+                    reduce_only: spec.reduce_only.unwrap_or(false),
+                    iso,
+                    order_type,
+                    client_id,
+                })
+            }
+            "cancel" => {
+                let symbol = required(spec.symbol, "cancel", "symbol")?;
+                match (spec.order_id, spec.client_id) {
+                    (Some(order_id), None) => OrderItem::Cancel(Cancel::new(
+                        symbol,
+                        parse_hash(&order_id, "cancel", "orderId")?,
+                    )),
+                    (None, Some(client_id)) => OrderItem::CancelByClientId(CancelByClientId::new(
+                        symbol,
+                        parse_client_id(&client_id, "cancel")?,
+                    )),
+                    (Some(_), Some(_)) => {
+                        return Err(field_error(
+                            "cancel",
+                            "orderId",
+                            "and cancel.clientId are mutually exclusive",
+                        ))
+                    }
+                    (None, None) => {
+                        return Err(field_error(
+                            "cancel",
+                            "orderId",
+                            "or cancel.clientId is required",
+                        ))
+                    }
+                }
+            }
+            "cancelByClientId" => {
+                let client_id = required(spec.client_id, "cancelByClientId", "clientId")?;
+                OrderItem::CancelByClientId(CancelByClientId::new(
+                    required(spec.symbol, "cancelByClientId", "symbol")?,
+                    parse_client_id(&client_id, "cancelByClientId")?,
+                ))
+            }
+            "modify" => {
+                // The exchange's `mod` action amends size only; refuse the
+                // other order fields rather than silently dropping them.
+                for (set, field) in [
+                    (spec.price.is_some(), "price"),
+                    (spec.reduce_only.is_some(), "reduceOnly"),
+                    (spec.order_type.is_some(), "orderType"),
+                ] {
+                    if set {
+                        return Err(field_error(
+                            "modify",
+                            field,
+                            "cannot be amended; cancel and re-place the order",
+                        ));
+                    }
+                }
+                let amount = match (spec.amount, spec.size) {
+                    (Some(_), Some(_)) => {
+                        return Err(field_error(
+                            "modify",
+                            "amount",
+                            "and modify.size are mutually exclusive",
+                        ))
+                    }
+                    (amount, size) => required(amount.or(size), "modify", "amount")?,
+                };
+                let order_id = required(spec.order_id, "modify", "orderId")?;
+                OrderItem::Modify(Modify::new(
+                    parse_hash(&order_id, "modify", "orderId")?,
+                    required(spec.symbol, "modify", "symbol")?,
+                    amount,
+                ))
+            }
+            "cancelAll" => OrderItem::CancelAll(CancelAll {
+                symbols: spec.symbols.unwrap_or_default(),
+                max_age_ms: spec.max_age_ms,
+            }),
+            "stop" | "st" => OrderItem::Stop(Stop {
+                symbol: required(spec.symbol, "stop", "symbol")?,
+                is_buy: required(spec.is_buy, "stop", "isBuy")?,
+                size: required(spec.size, "stop", "size")?,
+                trigger_price: required(spec.trigger_price, "stop", "triggerPrice")?,
+                limit_price: spec.limit_price.unwrap_or(f64::NAN),
+                iso,
+            }),
+            "takeProfit" | "tp" => OrderItem::TakeProfit(TakeProfit {
+                symbol: required(spec.symbol, "takeProfit", "symbol")?,
+                is_buy: required(spec.is_buy, "takeProfit", "isBuy")?,
+                size: required(spec.size, "takeProfit", "size")?,
+                trigger_price: required(spec.trigger_price, "takeProfit", "triggerPrice")?,
+                limit_price: spec.limit_price.unwrap_or(f64::NAN),
+                iso,
+            }),
+            "range" | "rng" => OrderItem::RangeOco(RangeOco {
+                symbol: required(spec.symbol, "range", "symbol")?,
+                is_buy: required(spec.is_buy, "range", "isBuy")?,
+                size: required(spec.size, "range", "size")?,
+                collar_min: required(spec.pmin, "range", "pmin")?,
+                collar_max: required(spec.pmax, "range", "pmax")?,
+                limit_min: spec.lmin.unwrap_or(f64::NAN),
+                limit_max: spec.lmax.unwrap_or(f64::NAN),
+                iso,
+            }),
+            "trig" => OrderItem::TriggerBasket(TriggerBasket {
+                symbol: required(spec.symbol, "trig", "symbol")?,
+                is_buy: required(spec.is_buy, "trig", "isBuy")?,
+                trigger_price: required(spec.trigger_price, "trig", "triggerPrice")?,
+                actions: OrderSpec::nested(spec.actions, "trig")?,
+                iso,
+            }),
+            "onFill" | "of" => OrderItem::OnFill(OnFill {
+                p: spec.p.unwrap_or(0),
+                actions: OrderSpec::nested(spec.actions, "onFill")?,
+            }),
+            "trailingStop" | "trl" => OrderItem::TrailingStop(TrailingStop {
+                symbol: required(spec.symbol, "trailingStop", "symbol")?,
+                is_buy: required(spec.is_buy, "trailingStop", "isBuy")?,
+                size: required(spec.size, "trailingStop", "size")?,
+                trail_bps: required(spec.trail_bps, "trailingStop", "trailBps")?,
+                step_bps: required(spec.step_bps, "trailingStop", "stepBps")?,
+                limit_price: spec.limit_price,
+                iso,
+            }),
+            other => {
+                return Err(Error::InvalidOrder(format!(
+                    "type: unknown item type {}",
+                    other
+                )))
+            }
+        };
+        Ok(item)
+    }
+}
+
+impl From<&OrderItem> for OrderSpec {
+    fn from(item: &OrderItem) -> Self {
+        let kind = |item_type: &str| OrderSpec {
+            item_type: item_type.to_string(),
+            ..Default::default()
+        };
+        match item {
+            OrderItem::Order(o) => OrderSpec {
+                symbol: Some(o.symbol.clone()),
+                is_buy: Some(o.is_buy),
+                price: Some(o.price),
+                size: Some(o.size),
+                reduce_only: Some(o.reduce_only),
+                iso: Some(o.iso),
+                order_type: Some(match o.order_type {
+                    OrderType::Limit { tif } => OrderTypeSpec {
+                        type_name: "limit".into(),
+                        tif: Some(tif.as_str().into()),
+                        ..Default::default()
+                    },
+                    OrderType::Trigger {
+                        is_market,
+                        trigger_px,
+                    } => OrderTypeSpec {
+                        type_name: "trigger".into(),
+                        is_market: Some(is_market),
+                        trigger_px: Some(trigger_px),
+                        ..Default::default()
+                    },
+                }),
+                client_id: o.client_id.map(|h| h.to_base58()),
+                ..kind("order")
+            },
+            OrderItem::Cancel(c) => OrderSpec {
+                symbol: Some(c.symbol.clone()),
+                order_id: Some(c.order_id.to_base58()),
+                ..kind("cancel")
+            },
+            OrderItem::CancelByClientId(c) => OrderSpec {
+                symbol: Some(c.symbol.clone()),
+                client_id: Some(c.client_id.to_base58()),
+                ..kind("cancelByClientId")
+            },
+            OrderItem::Modify(m) => OrderSpec {
+                symbol: Some(m.symbol.clone()),
+                order_id: Some(m.order_id.to_base58()),
+                amount: Some(m.amount),
+                ..kind("modify")
+            },
+            OrderItem::CancelAll(cxa) => OrderSpec {
+                symbols: Some(cxa.symbols.clone()),
+                max_age_ms: cxa.max_age_ms,
+                ..kind("cancelAll")
+            },
+            OrderItem::Stop(st) => OrderSpec {
+                symbol: Some(st.symbol.clone()),
+                is_buy: Some(st.is_buy),
+                size: Some(st.size),
+                trigger_price: Some(st.trigger_price),
+                limit_price: finite(st.limit_price),
+                iso: Some(st.iso),
+                ..kind("stop")
+            },
+            OrderItem::TakeProfit(tp) => OrderSpec {
+                symbol: Some(tp.symbol.clone()),
+                is_buy: Some(tp.is_buy),
+                size: Some(tp.size),
+                trigger_price: Some(tp.trigger_price),
+                limit_price: finite(tp.limit_price),
+                iso: Some(tp.iso),
+                ..kind("takeProfit")
+            },
+            OrderItem::RangeOco(r) => OrderSpec {
+                symbol: Some(r.symbol.clone()),
+                is_buy: Some(r.is_buy),
+                size: Some(r.size),
+                pmin: Some(r.collar_min),
+                pmax: Some(r.collar_max),
+                lmin: finite(r.limit_min),
+                lmax: finite(r.limit_max),
+                iso: Some(r.iso),
+                ..kind("range")
+            },
+            OrderItem::TriggerBasket(b) => OrderSpec {
+                symbol: Some(b.symbol.clone()),
+                is_buy: Some(b.is_buy),
+                trigger_price: Some(b.trigger_price),
+                actions: Some(b.actions.iter().map(OrderSpec::from).collect()),
+                iso: Some(b.iso),
+                ..kind("trig")
+            },
+            OrderItem::OnFill(of) => OrderSpec {
+                p: Some(of.p),
+                actions: Some(of.actions.iter().map(OrderSpec::from).collect()),
+                ..kind("onFill")
+            },
+            OrderItem::TrailingStop(t) => OrderSpec {
+                symbol: Some(t.symbol.clone()),
+                is_buy: Some(t.is_buy),
+                size: Some(t.size),
+                trail_bps: Some(t.trail_bps),
+                step_bps: Some(t.step_bps),
+                limit_price: t.limit_price,
+                iso: Some(t.iso),
+                ..kind("trailingStop")
+            },
+        }
+    }
+}
+
+impl TryFrom<Value> for OrderItem {
+    type Error = Error;
+
+    fn try_from(value: Value) -> Result<Self> {
+        OrderSpec::from_json(value, InputMode::Tolerant)?
+            .spec
+            .try_into()
+    }
+}
+
+impl std::str::FromStr for OrderItem {
+    type Err = Error;
+
+    /// Parse one intent from a JSON string.
+    fn from_str(s: &str) -> Result<Self> {
+        serde_json::from_str::<Value>(s)?.try_into()
+    }
+}
+
+impl OrderItem {
+    /// This item as camelCase intent JSON, the inverse of [`OrderItem::from_str`](std::str::FromStr).
+    ///
+    /// NaN limit prices (market-style fills) are omitted.
+    pub fn to_input_json(&self) -> serde_json::Value {
+        serde_json::to_value(OrderSpec::from(self)).expect("OrderSpec serializes")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn every_variant() -> Vec<OrderItem> {
+        let oid = Hash::from_bytes([3; 32]);
+        vec![
+            Order::limit("BTC-USD", true, 100000.5, 0.1, TimeInForce::Alo)
+                .reduce_only()
+                .with_client_id(Hash::from_bytes([9; 32]))
+                .into(),
+            Order::market("ETH-USD", false, 2.0).isolated().into(),
+            Cancel::new("BTC-USD", oid).into(),
+            Modify::new(oid, "BTC-USD", 0.5).into(),
+            CancelAll::for_symbols(vec!["SOL-USD".into()])
+                .with_max_age_ms(500)
+                .into(),
+            Stop {
+                symbol: "BTC-USD".into(),
+                is_buy: false,
+                size: 0.1,
+                trigger_price: 90000.0,
+                limit_price: 89900.0,
+                iso: false,
+            }
+            .into(),
+            TakeProfit {
+                symbol: "BTC-USD".into(),
+                is_buy: false,
+                size: 0.1,
+                trigger_price: 110000.0,
+                limit_price: 109900.0,
+                iso: true,
+            }
+            .into(),
+            RangeOco {
+                symbol: "BTC-USD".into(),
+                is_buy: true,
+                size: 0.1,
+                collar_min: 90000.0,
+                collar_max: 110000.0,
+                limit_min: 89000.0,
+                limit_max: 111000.0,
+                iso: false,
+            }
+            .into(),
+            TriggerCondition::new("ETH-USD", true, 4000.0)
+                .into_basket(vec![Order::market("BTC-USD", true, 0.1).into()])
+                .into(),
+            OnFill {
+                p: 1,
+                actions: vec![Cancel::new("BTC-USD", oid).into()],
+            }
+            .into(),
+            TrailingStop {
+                symbol: "BTC-USD".into(),
+                is_buy: true,
+                size: 0.1,
+                trail_bps: 50,
+                step_bps: 10,
+                limit_price: None,
+                iso: false,
+            }
+            .into(),
+        ]
+    }
+
+    #[test]
+    fn test_round_trip_every_variant() {
+        for item in every_variant() {
+            let value = item.to_input_json();
+            assert_eq!(OrderItem::try_from(value.clone()).unwrap(), item);
+            assert_eq!(value.to_string().parse::<OrderItem>().unwrap(), item);
+        }
+    }
+
+    #[test]
+    fn test_nan_limit_omitted_and_restored() {
+        let stop = OrderItem::try_from(json!({
+            "type": "st", "symbol": "BTC-USD", "isBuy": false, "size": 1.0, "triggerPrice": 9.0
+        }))
+        .unwrap();
+        let OrderItem::Stop(ref st) = stop else {
+            panic!("expected stop");
+        };
+        assert!(st.limit_price.is_nan());
+        assert!(stop.to_input_json().get("limitPrice").is_none());
+    }
+
+    #[test]
+    fn test_errors_name_the_field() {
+        let err = OrderItem::try_from(
+            json!({ "type": "order", "symbol": "BTC-USD", "price": 1.0, "size": 1.0 }),
+        )
+        .unwrap_err();
+        assert_eq!(err.to_string(), "invalid order: order.isBuy is required");
+
+        let err = r#"{"type":"cancel","symbol":"BTC-USD","orderId":"0OIl"}"#
+            .parse::<OrderItem>()
+            .unwrap_err();
+        assert!(err
+            .to_string()
+            .starts_with("invalid order: cancel.orderId is malformed: invalid base58"));
+        assert!(
+            matches!(&err, Error::InvalidOrderField { field, .. } if field == "cancel.orderId")
+        );
+
+        let err =
+            OrderItem::try_from(json!({ "type": "cancel", "symbol": "BTC-USD" })).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "invalid order: cancel.orderId or cancel.clientId is required"
+        );
+        let by_client_id = json!({ "type": "cancel", "symbol": "BTC-USD", "clientId": "11111111111111111111111111111112" });
+        assert!(matches!(
+            OrderItem::try_from(by_client_id).unwrap(),
+            OrderItem::CancelByClientId(_)
+        ));
+        let both =
+            json!({ "type": "cancel", "symbol": "BTC-USD", "orderId": "1", "clientId": "1" });
+        let err = OrderItem::try_from(both).unwrap_err();
+        assert!(err.to_string().contains("mutually exclusive"), "{err}");
+
+        let err = OrderItem::try_from(json!({
+            "type": "order", "symbol": "BTC-USD", "isBuy": true, "price": 1.0, "size": 1.0,
+            "clientId": ""
+        }))
+        .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "invalid order: order.clientId is empty; omit the field to send no client id"
+        );
+
+        let reprice = json!({
+            "type": "modify", "symbol": "BTC-USD", "orderId": "11111111111111111111111111111112",
+            "amount": 1.0, "price": 2.0
+        });
+        let err = OrderItem::try_from(reprice).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "invalid order: modify.price cannot be amended; cancel and re-place the order"
+        );
+
+        let err = OrderItem::try_from(json!({ "type": "swap" })).unwrap_err();
+        assert!(err.to_string().contains("unknown item type swap"));
+        assert!(OrderItem::try_from(json!({ "symbol": "BTC-USD" })).is_err());
+    }
+
+    #[test]
+    fn test_ticks_and_lots() {
+        let item = OrderItem::try_from(json!({
+            "type": "order", "symbol": "BTC-USD", "isBuy": true,
+            "priceTicks": 1000005, "sizeLots": 1000,
+            "symbolSpec": { "priceDecimals": 1, "sizeDecimals": 4 }
+        }))
+        .unwrap();
+        let OrderItem::Order(order) = item else {
+            panic!("expected order");
+        };
+        assert_eq!((order.price, order.size), (100000.5, 0.1));
+    }
+
+    #[test]
+    fn test_camel_snake_and_mixed_agree() {
+        let camel = json!({
+            "type": "order", "symbol": "BTC-USD", "isBuy": true, "price": 100.0, "size": 1.0,
+            "reduceOnly": true, "orderType": { "type": "trigger", "isMarket": false, "triggerPx": 99.0 }
+        });
+        let snake = json!({
+            "type": "order", "symbol": "BTC-USD", "is_buy": true, "price": 100.0, "size": 1.0,
+            "reduce_only": true, "order_type": { "type": "trigger", "is_market": false, "trigger_px": 99.0 }
+        });
+        let mixed = json!({
+            "type": "order", "symbol": "BTC-USD", "isBuy": true, "price": 100.0, "size": 1.0,
+            "reduce_only": true, "orderType": { "type": "trigger", "is_market": false, "triggerPx": 99.0 }
+        });
+        let expected = OrderItem::try_from(camel.clone()).unwrap();
+        assert_eq!(OrderItem::try_from(snake.clone()).unwrap(), expected);
+        assert_eq!(OrderItem::try_from(mixed.clone()).unwrap(), expected);
+
+        for value in [camel, snake] {
+            let parsed = OrderSpec::from_json(value, InputMode::Strict).unwrap();
+            assert_eq!(OrderItem::try_from(parsed.spec).unwrap(), expected);
+        }
+        let err = OrderSpec::from_json(mixed, InputMode::Strict).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "invalid order: mixed field casing: isBuy and is_market"
+        );
+
+        let twice = json!({ "type": "cancelAll", "maxAgeMs": 5, "max_age_ms": 5 });
+        let err = OrderItem::try_from(twice).unwrap_err();
+        assert!(err.to_string().contains("maxAgeMs is given more than once"));
+    }
+
+    #[test]
+    fn test_unknown_fields_warn() {
+        let value = json!({
+            "type": "onFill", "p": 1, "colour": "red",
+            "actions": [{ "type": "cancelAll", "symbols": [], "max_age": 3 }]
+        });
+        let parsed = validate_order_input(value, InputMode::Tolerant).unwrap();
+        assert_eq!(
+            parsed.warnings,
+            [
+                "actions[0].max_age: unknown field (did you mean maxAgeMs?)",
+                "colour: unknown field"
+            ]
+        );
+
+        let value = json!({
+            "type": "order", "symbol": "BTC-USD", "is_buy": true, "price": 1.0, "size": 1.0,
+            "on_fill": { "p": 0, "actions": [{ "type": "cancelAll", "symbols": [] }] }
+        });
+        let parsed = OrderSpec::from_json(value, InputMode::Tolerant).unwrap();
+        assert!(parsed.warnings.is_empty());
+        let items = parsed.spec.into_items().unwrap();
+        assert!(matches!(
+            items[..],
+            [OrderItem::Order(_), OrderItem::OnFill(_)]
+        ));
+        // Integral floats, as JS numbers arrive, read into integer fields.
+        assert!(OrderItem::try_from(json!({ "type": "cancelAll", "maxAgeMs": 500.0 })).is_ok());
+    }
+
+    #[test]
+    fn test_strict_rejects_typos_with_suggestion() {
+        let value = json!({
+            "type": "order", "symbol": "BTC-USD", "isBuy": true, "price": 1.0, "size": 1.0,
+            "reduceOnIy": true, "orderType": { "type": "limit", "tiff": "GTC" }
+        });
+        let err = validate_order_input(value.clone(), InputMode::Strict).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "unknown fields: orderType.tiff (did you mean tif?), reduceOnIy (did you mean reduceOnly?)"
+        );
+        let parsed = validate_order_input(value, InputMode::Tolerant).unwrap();
+        assert_eq!(
+            parsed.warnings[1],
+            "reduceOnIy: unknown field (did you mean reduceOnly?)"
+        );
+
+        assert_eq!(
+            suggest_field("reduce_only_", ORDER_FIELDS),
+            Some("reduceOnly")
+        );
+        assert_eq!(suggest_field("leverage", ORDER_FIELDS), None);
+        let options = json!({ "nonce": 1, "acount": "x" });
+        assert!(check_fields(&options, &["nonce", "account"], InputMode::Strict).is_err());
+        assert_eq!(
+            check_fields(&options, &["nonce", "account"], InputMode::Tolerant).unwrap(),
+            ["acount: unknown field (did you mean account?)"]
+        );
+    }
+
+    #[test]
+    fn test_scalar_from_str() {
+        assert_eq!(TimeInForce::try_from("ioc").unwrap(), TimeInForce::Ioc);
+        assert!(TimeInForce::try_from("FOK").is_err());
+        let hash = Hash::from_bytes([5; 32]);
+        assert_eq!(hash.to_base58().parse::<Hash>().unwrap(), hash);
+        let pubkey = Pubkey::from_bytes([6; 32]);
+        assert_eq!(pubkey.to_base58().parse::<Pubkey>().unwrap(), pubkey);
+        assert!("not-base58!".parse::<Pubkey>().is_err());
+    }
+}
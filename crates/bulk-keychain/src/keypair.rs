@@ -1,20 +1,77 @@
 //! Keypair management for Ed25519 signing
 
+use crate::census::CensusToken;
 use crate::{Error, Pubkey, Result};
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
 use ed25519_dalek::{SecretKey, SigningKey, VerifyingKey};
 use rand::rngs::OsRng;
+use serde::{Deserialize, Serialize};
+use std::sync::OnceLock;
+use zeroize::Zeroizing;
+
+/// Ed25519 private key as an RFC 8037 OKP JSON Web Key.
+#[derive(Serialize, Deserialize)]
+struct Jwk {
+    kty: String,
+    crv: String,
+    x: String,
+    d: Option<String>,
+}
 
 /// Ed25519 keypair for signing transactions
+///
+/// The secret key is wiped when the keypair is dropped, or earlier with
+/// [`zeroize`](Self::zeroize). Each clone is a separate copy with its own
+/// lifetime.
 #[derive(Clone)]
 pub struct Keypair {
     signing_key: SigningKey,
+    /// SLIP-0010 chain code of a key derived with [`crate::hd`]
+    chain_code: Option<Zeroizing<[u8; 32]>>,
+    /// `None` once zeroized
+    census: Option<CensusToken>,
+    /// Set by [`zeroize`](Self::zeroize); signing is refused from then on
+    zeroized: bool,
+}
+
+/// The all-zero key a zeroized keypair holds in place of its secret.
+fn zeroed_signing_key() -> SigningKey {
+    static ZEROED: OnceLock<SigningKey> = OnceLock::new();
+    ZEROED
+        .get_or_init(|| SigningKey::from_bytes(&[0u8; 32]))
+        .clone()
 }
 
 impl Keypair {
     /// Generate a new random keypair
     pub fn generate() -> Self {
-        let signing_key = SigningKey::generate(&mut OsRng);
-        Self { signing_key }
+        Self::generate_with_rng(&mut OsRng)
+    }
+
+    /// Generate a keypair from `rng`. A seeded `rng` gives the same key every
+    /// time: for tests and simulations only.
+    pub fn generate_with_rng<R: rand::CryptoRng + rand::RngCore>(rng: &mut R) -> Self {
+        Self::from_signing_key(SigningKey::generate(rng))
+    }
+
+    fn from_signing_key(signing_key: SigningKey) -> Self {
+        let pubkey = Pubkey::from_bytes(signing_key.verifying_key().to_bytes());
+        Self {
+            signing_key,
+            chain_code: None,
+            census: Some(CensusToken::register(&pubkey, "origin")),
+            zeroized: false,
+        }
+    }
+
+    pub(crate) fn from_extended_key(node: &crate::hd::ExtendedKey) -> Self {
+        let mut keypair = Self::from_signing_key(SigningKey::from_bytes(&node.secret));
+        keypair.chain_code = Some(node.chain_code.clone());
+        keypair
+    }
+
+    pub(crate) fn chain_code(&self) -> Option<&[u8; 32]> {
+        self.chain_code.as_deref()
     }
 
     /// Create from a 32-byte secret key
@@ -25,10 +82,9 @@ impl Keypair {
                 got: secret.len(),
             });
         }
-        let mut bytes = [0u8; 32];
+        let mut bytes = Zeroizing::new([0u8; 32]);
         bytes.copy_from_slice(secret);
-        let signing_key = SigningKey::from_bytes(&bytes);
-        Ok(Self { signing_key })
+        Ok(Self::from_signing_key(SigningKey::from_bytes(&bytes)))
     }
 
     /// Create from a 64-byte keypair (32-byte secret + 32-byte public)
@@ -50,12 +106,43 @@ impl Keypair {
 
     /// Create from base58-encoded secret key or keypair
     pub fn from_base58(s: &str) -> Result<Self> {
-        let bytes = bs58::decode(s)
-            .into_vec()
-            .map_err(|e| Error::InvalidBase58(e.to_string()))?;
+        let bytes = Zeroizing::new(
+            bs58::decode(s)
+                .into_vec()
+                .map_err(|e| Error::InvalidBase58(e.to_string()))?,
+        );
         Self::from_bytes(&bytes)
     }
 
+    /// Clone, recording `reason` as the copy's purpose in the
+    /// [`secret census`](crate::census) when the `secret-census` feature is on
+    pub fn clone_tracked(&self, reason: &str) -> Self {
+        Self {
+            signing_key: self.signing_key.clone(),
+            chain_code: self.chain_code.clone(),
+            census: self.census.as_ref().map(|census| census.copy(reason)),
+            zeroized: self.zeroized,
+        }
+    }
+
+    /// Wipe the secret key now instead of on drop.
+    ///
+    /// The keypair is left holding the all-zero key, whose secret is public,
+    /// so signing with it afterwards fails with [`Error::KeypairZeroized`].
+    /// Clones made earlier keep their copy.
+    pub fn zeroize(&mut self) {
+        // Replacing the key drops the old one, which wipes its bytes.
+        self.signing_key = zeroed_signing_key();
+        self.chain_code = None;
+        self.census = None;
+        self.zeroized = true;
+    }
+
+    /// Whether [`zeroize`](Self::zeroize) has run on this keypair
+    pub fn is_zeroized(&self) -> bool {
+        self.zeroized
+    }
+
     /// Get the public key
     pub fn pubkey(&self) -> Pubkey {
         let verifying_key = self.signing_key.verifying_key();
@@ -80,21 +167,75 @@ impl Keypair {
         bs58::encode(self.to_bytes()).into_string()
     }
 
-    /// Get the internal signing key reference (for direct signing)
-    pub(crate) fn signing_key(&self) -> &SigningKey {
-        &self.signing_key
+    /// Get the internal signing key reference (for direct signing), or
+    /// [`Error::KeypairZeroized`] once the secret has been wiped
+    pub(crate) fn signing_key(&self) -> Result<&SigningKey> {
+        if self.zeroized {
+            return Err(Error::KeypairZeroized);
+        }
+        Ok(&self.signing_key)
+    }
+
+    /// Short, display-safe identifier for this keypair. See [`Pubkey::fingerprint`].
+    pub fn fingerprint(&self) -> String {
+        self.pubkey().fingerprint()
     }
 
     /// Get the verifying key
     pub fn verifying_key(&self) -> VerifyingKey {
         self.signing_key.verifying_key()
     }
+
+    /// Export as a private JSON Web Key (RFC 8037: `kty: "OKP"`, `crv: "Ed25519"`),
+    /// importable with Web Crypto `crypto.subtle.importKey("jwk", ...)`.
+    pub fn to_jwk(&self) -> Result<String> {
+        let jwk = Jwk {
+            kty: "OKP".to_string(),
+            crv: "Ed25519".to_string(),
+            x: URL_SAFE_NO_PAD.encode(self.signing_key.verifying_key().as_bytes()),
+            d: Some(URL_SAFE_NO_PAD.encode(self.signing_key.as_bytes())),
+        };
+        serde_json::to_string(&jwk).map_err(Error::from)
+    }
+
+    /// Import a private Ed25519 JSON Web Key. Extra members (`kid`, `use`, ...)
+    /// are ignored; `x` must match the public key derived from `d`.
+    pub fn from_jwk(json: &str) -> Result<Self> {
+        let jwk: Jwk = serde_json::from_str(json)?;
+        if jwk.kty != "OKP" || jwk.crv != "Ed25519" {
+            return Err(Error::InvalidJwk(format!(
+                "expected OKP/Ed25519 key, got {}/{}",
+                jwk.kty, jwk.crv
+            )));
+        }
+        let d = jwk
+            .d
+            .ok_or_else(|| Error::InvalidJwk("missing private key member \"d\"".to_string()))?;
+        let decode = |field: &str, value: &str| {
+            URL_SAFE_NO_PAD
+                .decode(value)
+                .map_err(|e| Error::InvalidJwk(format!("{}: {}", field, e)))
+        };
+        let keypair = Self::from_secret_key(&Zeroizing::new(decode("d", &d)?))?;
+        if decode("x", &jwk.x)? != keypair.pubkey().as_bytes() {
+            return Err(Error::InvalidJwk(
+                "public key \"x\" does not match private key \"d\"".to_string(),
+            ));
+        }
+        Ok(keypair)
+    }
+}
+
+impl Drop for Keypair {
+    fn drop(&mut self) {
+        self.zeroize();
+    }
 }
 
 impl std::fmt::Debug for Keypair {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("Keypair")
-            .field("pubkey", &self.pubkey().to_base58())
+            .field("fingerprint", &self.fingerprint())
             .finish_non_exhaustive()
     }
 }
@@ -134,6 +275,133 @@ mod tests {
         assert_eq!(keypair.pubkey(), restored.pubkey());
     }
 
+    #[test]
+    fn test_fingerprint() {
+        let pubkey = Pubkey::from_bytes([1u8; 32]);
+        assert_eq!(pubkey.fingerprint(), "bk1:8j9ARGFv");
+        assert_ne!(
+            pubkey.fingerprint(),
+            Pubkey::from_bytes([2u8; 32]).fingerprint()
+        );
+
+        let keypair = Keypair::generate();
+        assert_eq!(keypair.fingerprint(), keypair.pubkey().fingerprint());
+        let debug = format!("{:?}", keypair);
+        assert!(debug.contains(&keypair.fingerprint()));
+        assert!(!debug.contains(&keypair.pubkey().to_base58()));
+    }
+
+    #[test]
+    fn test_jwk_rfc8037_vectors() {
+        use ed25519_dalek::Signer;
+
+        // RFC 8037 appendix A.1 / A.4
+        let jwk = r#"{"kty":"OKP","crv":"Ed25519",
+            "d":"nWGxne_9WmC6hEr0kuwsxERJxWl7MmkZcDusAxyuf2A",
+            "x":"11qYAYKxCrfVS_7TyWQHOg7hcvPapiMlrwIaaPcHURo"}"#;
+        let keypair = Keypair::from_jwk(jwk).unwrap();
+        assert_eq!(
+            URL_SAFE_NO_PAD.encode(keypair.pubkey().as_bytes()),
+            "11qYAYKxCrfVS_7TyWQHOg7hcvPapiMlrwIaaPcHURo"
+        );
+        let signature = keypair
+            .signing_key()
+            .unwrap()
+            .sign(b"eyJhbGciOiJFZERTQSJ9.RXhhbXBsZSBvZiBFZDI1NTE5IHNpZ25pbmc");
+        assert_eq!(
+            URL_SAFE_NO_PAD.encode(signature.to_bytes()),
+            "hgyY0il_MGCjP0JzlnLWG1PPOt7-09PGcvMg3AIbQR6dWbhijcNR4ki4iylGjg5BhVsPt9g7sVvpAr_MuM0KAg"
+        );
+
+        let exported: serde_json::Value = serde_json::from_str(&keypair.to_jwk().unwrap()).unwrap();
+        assert_eq!(exported["kty"], "OKP");
+        assert_eq!(exported["crv"], "Ed25519");
+        assert_eq!(exported["d"], "nWGxne_9WmC6hEr0kuwsxERJxWl7MmkZcDusAxyuf2A");
+        assert_eq!(exported["x"], "11qYAYKxCrfVS_7TyWQHOg7hcvPapiMlrwIaaPcHURo");
+
+        let generated = Keypair::generate();
+        let restored = Keypair::from_jwk(&generated.to_jwk().unwrap()).unwrap();
+        assert_eq!(restored.to_bytes(), generated.to_bytes());
+    }
+
+    #[test]
+    fn test_jwk_rejects_bad_keys() {
+        let x = "11qYAYKxCrfVS_7TyWQHOg7hcvPapiMlrwIaaPcHURo";
+        let d = "nWGxne_9WmC6hEr0kuwsxERJxWl7MmkZcDusAxyuf2A";
+        let jwk = |kty: &str, crv: &str, x: &str, d: Option<&str>| {
+            serde_json::json!({ "kty": kty, "crv": crv, "x": x, "d": d }).to_string()
+        };
+
+        for bad in [
+            jwk("EC", "Ed25519", x, Some(d)),
+            jwk("OKP", "X25519", x, Some(d)),
+            jwk("OKP", "Ed25519", x, None),
+            jwk("OKP", "Ed25519", x, Some("not*base64")),
+            jwk(
+                "OKP",
+                "Ed25519",
+                &URL_SAFE_NO_PAD.encode([1u8; 32]),
+                Some(d),
+            ),
+        ] {
+            assert!(
+                matches!(Keypair::from_jwk(&bad), Err(Error::InvalidJwk(_))),
+                "{bad}"
+            );
+        }
+        assert!(matches!(
+            Keypair::from_jwk(&jwk("OKP", "Ed25519", x, Some("AAAA"))),
+            Err(Error::InvalidKeyLength { .. })
+        ));
+    }
+
+    fn contains(haystack: &[u8], needle: &[u8]) -> bool {
+        haystack.windows(needle.len()).any(|w| w == needle)
+    }
+
+    #[test]
+    fn test_drop_wipes_secret_in_place() {
+        use std::alloc::{alloc_zeroed, dealloc, Layout};
+
+        let keypair = Keypair::generate();
+        let secret = *keypair.secret_key();
+        let layout = Layout::new::<Keypair>();
+        // Drop the keypair in memory we own, then inspect that memory before
+        // handing it back to the allocator.
+        unsafe {
+            let ptr = alloc_zeroed(layout) as *mut Keypair;
+            ptr.write(keypair);
+            let bytes = || std::slice::from_raw_parts(ptr as *const u8, layout.size()).to_vec();
+            assert!(contains(&bytes(), &secret));
+            std::ptr::drop_in_place(ptr);
+            assert!(!contains(&bytes(), &secret));
+            dealloc(ptr as *mut u8, layout);
+        }
+    }
+
+    #[test]
+    fn test_zeroize_on_demand() {
+        let mut keypair = Keypair::generate();
+        let copy = keypair.clone();
+        let secret = *keypair.secret_key();
+        assert!(!keypair.is_zeroized());
+
+        keypair.zeroize();
+        assert!(keypair.is_zeroized());
+        assert_eq!(keypair.secret_key(), &[0u8; 32]);
+        assert!(!contains(&keypair.to_bytes(), &secret));
+        assert_ne!(keypair.pubkey(), copy.pubkey());
+        assert_eq!(copy.secret_key(), &secret);
+        assert!(!copy.is_zeroized());
+        assert!(keypair.clone().is_zeroized());
+        assert!(matches!(keypair.signing_key(), Err(Error::KeypairZeroized)));
+
+        // The flag, not the key bytes, marks a wiped keypair.
+        let all_zero = Keypair::from_secret_key(&[0u8; 32]).unwrap();
+        assert!(!all_zero.is_zeroized());
+        assert!(all_zero.signing_key().is_ok());
+    }
+
     #[test]
     fn test_invalid_key_length() {
         let result = Keypair::from_bytes(&[0u8; 31]);
@@ -47,30 +47,135 @@
 //! let signed_txs = signer.sign_all(orders, None).unwrap();
 //! ```
 
+#[cfg(feature = "agent")]
+pub mod agent;
+pub mod archive;
+#[cfg(feature = "batch")]
+pub mod batch;
+pub mod build_info;
+pub mod capabilities;
+pub mod census;
+#[cfg(feature = "batch")]
+pub mod checkpoint;
+pub mod client_ids;
+pub mod commitment;
+pub mod compact;
+pub mod compat;
+pub mod diff;
+pub mod display;
 mod error;
+pub mod escrow;
+pub mod flatten;
+pub mod format;
+pub mod framed;
+pub mod hardware;
+pub mod hd;
+pub mod input;
 mod keypair;
+pub mod lint;
+#[cfg(feature = "bip39")]
+pub mod mnemonic;
 pub mod nonce;
+pub mod obfuscation;
+pub mod open_orders;
 pub mod order_id;
+#[cfg(feature = "outbox")]
+pub mod outbox;
+pub mod parallel;
+#[cfg(feature = "pipeline")]
+pub mod pipeline;
+pub mod plan;
+pub mod pool;
 pub mod prepare;
+#[cfg(feature = "quick")]
+pub mod quick;
+pub mod reconnect;
+pub mod remote_session;
+pub mod reservation;
+pub mod rng;
+pub mod schemas;
+pub mod scope;
 mod sdk_compat;
 mod sign;
+pub mod spec;
+pub mod stream;
+pub mod symbol;
+pub mod time;
+pub mod transport;
 pub mod types;
 
+#[cfg(feature = "agent")]
+pub use agent::AgentDeriver;
+pub use archive::{
+    resume_verify_archive, verify_archive, verify_archive_with_checkpoint, ArchiveCheckpoint,
+    ArchiveFailure, ArchiveReport, ArchiveVerifyOptions,
+};
+#[cfg(feature = "batch")]
+pub use batch::{BatchBuilder, BatchEntry};
+pub use build_info::{build_info, BuildInfo};
+pub use capabilities::{CapabilityDescriptor, PolicySummary, SignedCapabilities};
+#[cfg(feature = "secret-census")]
+pub use census::{secret_census, SecretCensusEntry};
+#[cfg(feature = "batch")]
+pub use checkpoint::BatchCheckpoint;
+pub use commitment::verify_commitment;
+pub use compat::{detect_compat_version, CompatVersion};
+pub use diff::{diff_orders, OrderDiff};
+pub use display::{confirm_price_matches, DisplayMismatch, DisplayPolicy};
 pub use error::{Error, Result};
+pub use flatten::{flatten_position, FlattenStyle, PositionSnapshot, SymbolSpecs};
+pub use framed::{FramedBatchReader, FramedBatchWriter};
+pub use input::{
+    validate_order_input, InputMode, OnFillSpec, OrderSpec, OrderTypeSpec, ParsedOrderSpec,
+    UnknownField,
+};
 pub use keypair::Keypair;
-pub use nonce::{NonceManager, NonceStrategy};
+pub use lint::{
+    lint_group, lint_order, GroupLintWarning, GroupOptions, LintCode, LintContext, LintMode,
+    LintSeverity, LintWarning,
+};
+pub use nonce::{
+    content_derived_nonce, AnomalyCallback, FileNonceStore, NonceAnomaly, NonceManager, NonceStore,
+    NonceStrategy, NonceWindow, PersistedNonce, SERVER_NONCE_MAX_AHEAD_MS,
+    SERVER_NONCE_MAX_BEHIND_MS,
+};
+pub use obfuscation::{jitter_sizes, jitter_sizes_with_specs, shuffle_batch};
 pub use order_id::{
     compute_limit_order_id, compute_market_order_id, compute_order_id, compute_order_item_id,
+    compute_order_item_id_at, verify_id_scheme, IdScheme, OrderIdHasher,
 };
+pub use parallel::{BatchMode, Parallelism};
+#[cfg(feature = "pipeline")]
+pub use pipeline::{PipelineConfig, PipelineEvent, PipelineResult, ShutdownMode, SigningPipeline};
+pub use pool::SignerPool;
 pub use prepare::{
     finalize_all, finalize_transaction, finalize_transaction_bytes, prepare_action,
-    prepare_agent_wallet, prepare_all, prepare_create_multisig, prepare_create_sub_account,
-    prepare_faucet, prepare_group, prepare_message, prepare_multisig_approve,
-    prepare_multisig_cancel, prepare_multisig_execute, prepare_multisig_propose,
-    prepare_multisig_reject, prepare_remove_sub_account, prepare_rename_sub_account,
-    prepare_transfer, prepare_update_multisig_policy, prepare_user_settings, PreparedMessage,
+    prepare_action_content_derived, prepare_agent_wallet, prepare_all, prepare_all_indexed,
+    prepare_cancel, prepare_cancel_all, prepare_cancel_replace, prepare_checked_cancel_replace,
+    prepare_create_multisig, prepare_create_sub_account, prepare_faucet, prepare_group,
+    prepare_group_with, prepare_group_with_options, prepare_message, prepare_modify_order,
+    prepare_multisig_approve, prepare_multisig_cancel, prepare_multisig_execute,
+    prepare_multisig_propose, prepare_multisig_reject, prepare_remove_sub_account,
+    prepare_rename_sub_account, prepare_transfer, prepare_update_multisig_policy,
+    prepare_user_settings, verify_batch, verify_transaction, IndexedPreparedMessage,
+    PreparedMessage,
+};
+pub use reconnect::{ReconnectPolicy, ReconnectReport};
+pub use remote_session::{
+    RemoteSession, ResponderBackend, SessionInitiator, SessionReply, SessionRequest,
+    SessionResponder, SessionResponse,
+};
+pub use reservation::{NoncePlan, NonceReservation, PeekedOrderIds};
+pub use rng::RngProvider;
+pub use scope::{ScopeConfig, ScopedSigner};
+pub use sign::{verify_signature, PreSignHook, SignOptions, Signer, SignerUsage};
+pub use spec::SymbolSpec;
+pub use stream::{BatchItemError, IntentStream, MAX_INTENT_LINE_LEN};
+pub use time::{DurationMs, TimestampMs};
+pub use transport::{
+    sign_and_submit, MockTransport, Reconciler, SubmitStatus, Submitter, Transport,
+    TransportResponse,
 };
-pub use sign::Signer;
 pub use types::*;
 
 /// Re-export for convenience
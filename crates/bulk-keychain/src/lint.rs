@@ -0,0 +1,738 @@
+//! Order-intent linting
+//!
+//! Validation rejects orders the exchange would refuse; linting flags orders
+//! it would happily accept but that are probably not what the trader meant,
+//! such as a resting limit priced far through the market. Lints need market
+//! context the keychain does not have, supplied through a [`LintContext`].
+//!
+//! Run [`lint_order`] standalone, or install [`LintContext::into_pre_sign_hook`]
+//! on a [`crate::Signer`] to warn on or block suspicious orders at sign time.
+//!
+//! [`lint_group`] checks the legs of one atomic group against each other and
+//! needs no context. `sign_group` and `prepare_group` run it and attach the
+//! findings to their output; [`GroupOptions::strict`] makes them refuse instead.
+
+use crate::sign::PreSignHook;
+use crate::types::{GroupKind, Order, OrderItem, OrderType, TimeInForce};
+use crate::{Error, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// Default distance through the reference at which a GTC limit is flagged
+/// as marketable (20%).
+pub const DEFAULT_MARKETABLE_BPS: u32 = 2_000;
+
+/// Default distance behind the reference at which an IOC is flagged as
+/// unlikely to fill (5%).
+pub const DEFAULT_FAR_IOC_BPS: u32 = 500;
+
+/// Which rule produced a [`LintWarning`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LintCode {
+    /// GTC limit priced far through the reference; it will fill as a taker
+    MarketableLimit,
+    /// Reduce-only order on a symbol with no known position
+    ReduceOnlyNoPosition,
+    /// IOC priced far behind the reference; it will almost certainly not fill
+    FarIoc,
+    /// Stop or take-profit trigger already crossed; it fires immediately
+    TriggerWrongSide,
+    /// Group leg repeating an earlier leg's symbol, side, price and size
+    DuplicateLeg,
+    /// Group stop or take-profit on the same side as the group's entry
+    ProtectionSameSide,
+    /// Group reduce-only leg larger than everything the group opens
+    OversizedReduceOnly,
+}
+
+impl LintCode {
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            Self::MarketableLimit => "marketable_limit",
+            Self::ReduceOnlyNoPosition => "reduce_only_no_position",
+            Self::FarIoc => "far_ioc",
+            Self::TriggerWrongSide => "trigger_wrong_side",
+            Self::DuplicateLeg => "duplicate_leg",
+            Self::ProtectionSameSide => "protection_same_side",
+            Self::OversizedReduceOnly => "oversized_reduce_only",
+        }
+    }
+}
+
+/// How suspicious a lint finding is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LintSeverity {
+    /// Likely harmless, e.g. an order that will simply not fill
+    Info,
+    /// Likely costly, e.g. paying taker fees on an intended maker order
+    Warning,
+    /// Almost certainly a mistake, e.g. a stop that fires on submission
+    Critical,
+}
+
+impl LintSeverity {
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            Self::Info => "info",
+            Self::Warning => "warning",
+            Self::Critical => "critical",
+        }
+    }
+}
+
+/// One lint finding.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LintWarning {
+    pub code: LintCode,
+    pub message: String,
+    pub severity: LintSeverity,
+}
+
+/// Market context for [`lint_order`].
+///
+/// Rules that need a reference price are skipped for symbols without one.
+#[derive(Debug, Clone)]
+pub struct LintContext {
+    /// Reference (mark or mid) price per symbol
+    pub reference_prices: HashMap<String, f64>,
+    /// Signed position size per symbol (positive = long)
+    pub position_hints: HashMap<String, f64>,
+    /// Distance through the reference that makes a GTC limit marketable
+    pub marketable_bps: u32,
+    /// Distance behind the reference that makes an IOC unfillable
+    pub far_ioc_bps: u32,
+}
+
+impl Default for LintContext {
+    fn default() -> Self {
+        Self {
+            reference_prices: HashMap::new(),
+            position_hints: HashMap::new(),
+            marketable_bps: DEFAULT_MARKETABLE_BPS,
+            far_ioc_bps: DEFAULT_FAR_IOC_BPS,
+        }
+    }
+}
+
+/// Whether [`LintContext::into_pre_sign_hook`] only reports or also refuses.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LintMode {
+    /// Report every finding and sign anyway
+    Warn,
+    /// Refuse to sign on any [`LintSeverity::Warning`] or worse
+    Block,
+}
+
+impl LintContext {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_reference_price(mut self, symbol: impl Into<String>, price: f64) -> Self {
+        self.reference_prices.insert(symbol.into(), price);
+        self
+    }
+
+    pub fn with_position(mut self, symbol: impl Into<String>, size: f64) -> Self {
+        self.position_hints.insert(symbol.into(), size);
+        self
+    }
+
+    /// Pre-sign hook that lints every item, passing each finding to
+    /// `on_warning`. In [`LintMode::Block`] a finding of
+    /// [`LintSeverity::Warning`] or worse aborts signing with
+    /// [`Error::PreSignRejected`].
+    pub fn into_pre_sign_hook(
+        self,
+        mode: LintMode,
+        on_warning: impl Fn(&OrderItem, &LintWarning) + Send + Sync + 'static,
+    ) -> Arc<PreSignHook> {
+        Arc::new(move |item: &OrderItem, _nonce: u64| -> Result<()> {
+            for warning in lint_order(item, &self) {
+                on_warning(item, &warning);
+                if mode == LintMode::Block && warning.severity >= LintSeverity::Warning {
+                    return Err(Error::PreSignRejected(format!(
+                        "{}: {}",
+                        warning.code.as_str(),
+                        warning.message
+                    )));
+                }
+            }
+            Ok(())
+        })
+    }
+}
+
+/// Flag economically suspicious but valid orders. Nested basket and
+/// on-fill actions are linted too.
+pub fn lint_order(item: &OrderItem, ctx: &LintContext) -> Vec<LintWarning> {
+    let mut out = Vec::new();
+    lint_into(item, ctx, &mut out);
+    out
+}
+
+fn lint_into(item: &OrderItem, ctx: &LintContext, out: &mut Vec<LintWarning>) {
+    match item {
+        OrderItem::Order(order) => lint_limit(order, ctx, out),
+        OrderItem::Stop(st) => lint_trigger(
+            "stop",
+            &st.symbol,
+            st.is_buy,
+            st.trigger_price,
+            false,
+            ctx,
+            out,
+        ),
+        OrderItem::TakeProfit(tp) => lint_trigger(
+            "take-profit",
+            &tp.symbol,
+            tp.is_buy,
+            tp.trigger_price,
+            true,
+            ctx,
+            out,
+        ),
+        OrderItem::TriggerBasket(basket) => {
+            basket.actions.iter().for_each(|a| lint_into(a, ctx, out))
+        }
+        OrderItem::OnFill(of) => of.actions.iter().for_each(|a| lint_into(a, ctx, out)),
+        _ => {}
+    }
+}
+
+fn lint_limit(order: &Order, ctx: &LintContext, out: &mut Vec<LintWarning>) {
+    if order.reduce_only
+        && ctx
+            .position_hints
+            .get(&order.symbol)
+            .is_none_or(|size| *size == 0.0)
+    {
+        out.push(LintWarning {
+            code: LintCode::ReduceOnlyNoPosition,
+            message: format!(
+                "reduce-only order on {} with no known position",
+                order.symbol
+            ),
+            severity: LintSeverity::Warning,
+        });
+    }
+
+    let OrderType::Limit { tif } = order.order_type else {
+        return;
+    };
+    let Some(reference) = reference_price(ctx, &order.symbol) else {
+        return;
+    };
+    // Positive when the price is on the aggressive (crossing) side.
+    let through_bps = if order.is_buy {
+        (order.price - reference) / reference * 10_000.0
+    } else {
+        (reference - order.price) / reference * 10_000.0
+    };
+    let side = if order.is_buy { "buy" } else { "sell" };
+    match tif {
+        TimeInForce::Gtc if through_bps >= ctx.marketable_bps as f64 => out.push(LintWarning {
+            code: LintCode::MarketableLimit,
+            message: format!(
+                "GTC {} at {} is {:.0} bps through reference {}; it will fill as a taker",
+                side, order.price, through_bps, reference
+            ),
+            severity: LintSeverity::Warning,
+        }),
+        TimeInForce::Ioc if -through_bps >= ctx.far_ioc_bps as f64 => out.push(LintWarning {
+            code: LintCode::FarIoc,
+            message: format!(
+                "IOC {} at {} is {:.0} bps behind reference {}; it will not fill",
+                side, order.price, -through_bps, reference
+            ),
+            severity: LintSeverity::Info,
+        }),
+        _ => {}
+    }
+}
+
+/// Stops fire when price moves against the position: a sell stop triggers
+/// below the reference. Take-profits fire the other way.
+fn lint_trigger(
+    kind: &str,
+    symbol: &str,
+    is_buy: bool,
+    trigger: f64,
+    take_profit: bool,
+    ctx: &LintContext,
+    out: &mut Vec<LintWarning>,
+) {
+    let Some(reference) = reference_price(ctx, symbol) else {
+        return;
+    };
+    let expect_above = is_buy != take_profit;
+    let crossed = if expect_above {
+        trigger <= reference
+    } else {
+        trigger >= reference
+    };
+    if crossed {
+        out.push(LintWarning {
+            code: LintCode::TriggerWrongSide,
+            message: format!(
+                "{} {} trigger {} should be {} reference {}; it fires immediately",
+                if is_buy { "buy" } else { "sell" },
+                kind,
+                trigger,
+                if expect_above { "above" } else { "below" },
+                reference
+            ),
+            severity: LintSeverity::Critical,
+        });
+    }
+}
+
+fn reference_price(ctx: &LintContext, symbol: &str) -> Option<f64> {
+    ctx.reference_prices
+        .get(symbol)
+        .copied()
+        .filter(|px| px.is_finite() && *px > 0.0)
+}
+
+/// A [`lint_group`] finding on the leg at `index`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GroupLintWarning {
+    pub index: usize,
+    #[serde(flatten)]
+    pub warning: LintWarning,
+}
+
+/// How a group is signed or prepared.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct GroupOptions {
+    /// Unsigned intent tag for the output
+    pub kind: Option<GroupKind>,
+    /// Refuse a group with a [`lint_group`] finding of
+    /// [`LintSeverity::Warning`] or worse, instead of attaching it
+    pub strict: bool,
+}
+
+impl GroupOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_kind(mut self, kind: GroupKind) -> Self {
+        self.kind = Some(kind);
+        self
+    }
+
+    pub fn strict(mut self) -> Self {
+        self.strict = true;
+        self
+    }
+
+    /// Lint `items`, failing with [`Error::GroupRejected`] in strict mode.
+    pub(crate) fn check(&self, items: &[OrderItem]) -> Result<Vec<GroupLintWarning>> {
+        let warnings = lint_group(items);
+        if self.strict {
+            if let Some(w) = warnings
+                .iter()
+                .find(|w| w.warning.severity >= LintSeverity::Warning)
+            {
+                return Err(Error::GroupRejected(format!(
+                    "leg {}: {}: {}",
+                    w.index,
+                    w.warning.code.as_str(),
+                    w.warning.message
+                )));
+            }
+        }
+        Ok(warnings)
+    }
+}
+
+/// The fields of a group leg the group rules compare.
+struct Leg<'a> {
+    symbol: &'a str,
+    is_buy: bool,
+    /// Limit price, or trigger price for a stop or take-profit
+    price: f64,
+    size: f64,
+    /// Stop or take-profit
+    protective: bool,
+    reduce_only: bool,
+}
+
+impl<'a> Leg<'a> {
+    fn of(item: &'a OrderItem) -> Option<Self> {
+        let (symbol, is_buy, price, size, protective, reduce_only) = match item {
+            OrderItem::Order(o) => (&o.symbol, o.is_buy, o.price, o.size, false, o.reduce_only),
+            OrderItem::Stop(st) => (&st.symbol, st.is_buy, st.trigger_price, st.size, true, true),
+            OrderItem::TakeProfit(tp) => {
+                (&tp.symbol, tp.is_buy, tp.trigger_price, tp.size, true, true)
+            }
+            _ => return None,
+        };
+        Some(Self {
+            symbol,
+            is_buy,
+            price,
+            size,
+            protective,
+            reduce_only,
+        })
+    }
+
+    /// Opens or adds to a position
+    fn is_entry(&self) -> bool {
+        !self.reduce_only
+    }
+
+    fn describe(&self) -> String {
+        let side = if self.is_buy { "buy" } else { "sell" };
+        let kind = if self.protective { " trigger" } else { "" };
+        format!(
+            "{} {} {}{} @ {}",
+            side, self.size, self.symbol, kind, self.price
+        )
+    }
+}
+
+/// Check the legs of one atomic group against each other.
+///
+/// Flags a leg that repeats an earlier one (a stop and a take-profit at the
+/// same trigger count as repeats), a stop or take-profit on the same side as
+/// an entry in the group, and a reduce-only leg larger than the total the
+/// group opens on its symbol. Findings are in leg order.
+///
+/// ```rust
+/// use bulk_keychain::{lint_group, LintCode, Order, TimeInForce};
+///
+/// let leg = Order::limit("BTC-USD", true, 100000.0, 0.1, TimeInForce::Gtc);
+/// let warnings = lint_group(&[leg.clone().into(), leg.into()]);
+/// assert_eq!(warnings.len(), 1);
+/// assert_eq!((warnings[0].index, warnings[0].warning.code), (1, LintCode::DuplicateLeg));
+/// ```
+pub fn lint_group(items: &[OrderItem]) -> Vec<GroupLintWarning> {
+    let legs: Vec<Option<Leg>> = items.iter().map(Leg::of).collect();
+    let mut out = Vec::new();
+    let mut push = |index, code, severity, message| {
+        out.push(GroupLintWarning {
+            index,
+            warning: LintWarning {
+                code,
+                message,
+                severity,
+            },
+        })
+    };
+
+    for (j, leg) in legs.iter().enumerate() {
+        let Some(leg) = leg else { continue };
+        let earlier = legs[..j]
+            .iter()
+            .enumerate()
+            .filter_map(|(i, l)| Some((i, l.as_ref()?)));
+        let mut same_symbol = earlier.filter(|(_, l)| l.symbol == leg.symbol);
+        if let Some((i, _)) = same_symbol.find(|(_, l)| {
+            l.is_buy == leg.is_buy
+                && l.price == leg.price
+                && l.size == leg.size
+                && l.protective == leg.protective
+        }) {
+            push(
+                j,
+                LintCode::DuplicateLeg,
+                LintSeverity::Warning,
+                format!("leg {} repeats leg {}: {}", j, i, leg.describe()),
+            );
+        }
+
+        let entries = || {
+            legs.iter()
+                .enumerate()
+                .filter_map(|(i, l)| Some((i, l.as_ref()?)))
+                .filter(|(_, l)| l.symbol == leg.symbol && l.is_entry())
+        };
+        if leg.protective {
+            if let Some((i, _)) = entries().find(|(_, l)| l.is_buy == leg.is_buy) {
+                push(
+                    j,
+                    LintCode::ProtectionSameSide,
+                    LintSeverity::Critical,
+                    format!(
+                        "{} is on the same side as entry leg {}; it adds to the position instead of closing it",
+                        leg.describe(),
+                        i
+                    ),
+                );
+            }
+        }
+        if leg.reduce_only {
+            let opened: f64 = entries().map(|(_, l)| l.size).sum();
+            if opened > 0.0 && leg.size > opened {
+                push(
+                    j,
+                    LintCode::OversizedReduceOnly,
+                    LintSeverity::Warning,
+                    format!(
+                        "reduce-only {} is larger than the {} the group opens",
+                        leg.describe(),
+                        opened
+                    ),
+                );
+            }
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Keypair, Signer, Stop, TakeProfit};
+
+    fn ctx() -> LintContext {
+        LintContext::new().with_reference_price("BTC-USD", 100_000.0)
+    }
+
+    fn codes(item: impl Into<OrderItem>, ctx: &LintContext) -> Vec<LintCode> {
+        lint_order(&item.into(), ctx)
+            .into_iter()
+            .map(|w| w.code)
+            .collect()
+    }
+
+    fn limit(is_buy: bool, price: f64, tif: TimeInForce) -> Order {
+        Order::limit("BTC-USD", is_buy, price, 0.1, tif)
+    }
+
+    #[test]
+    fn test_marketable_limit() {
+        let ctx = ctx();
+        let tif = TimeInForce::Gtc;
+        assert_eq!(
+            codes(limit(true, 120_001.0, tif), &ctx),
+            [LintCode::MarketableLimit]
+        );
+        assert!(codes(limit(true, 119_999.0, tif), &ctx).is_empty());
+        assert_eq!(
+            codes(limit(false, 79_999.0, tif), &ctx),
+            [LintCode::MarketableLimit]
+        );
+        assert!(codes(limit(false, 80_001.0, tif), &ctx).is_empty());
+        // ALO and IOC are not resting takers.
+        assert!(codes(limit(true, 150_000.0, TimeInForce::Alo), &ctx).is_empty());
+        // No reference, no lint.
+        assert!(codes(limit(true, 150_000.0, tif), &LintContext::new()).is_empty());
+    }
+
+    #[test]
+    fn test_far_ioc() {
+        let ctx = ctx();
+        let tif = TimeInForce::Ioc;
+        assert_eq!(codes(limit(true, 94_999.0, tif), &ctx), [LintCode::FarIoc]);
+        assert!(codes(limit(true, 95_001.0, tif), &ctx).is_empty());
+        assert_eq!(
+            codes(limit(false, 105_001.0, tif), &ctx),
+            [LintCode::FarIoc]
+        );
+        assert!(codes(limit(false, 104_999.0, tif), &ctx).is_empty());
+    }
+
+    #[test]
+    fn test_reduce_only_without_position() {
+        let order = limit(false, 100_000.0, TimeInForce::Gtc).reduce_only();
+        assert_eq!(
+            codes(order.clone(), &ctx()),
+            [LintCode::ReduceOnlyNoPosition]
+        );
+        assert_eq!(
+            codes(order.clone(), &ctx().with_position("BTC-USD", 0.0)),
+            [LintCode::ReduceOnlyNoPosition]
+        );
+        assert!(codes(order, &ctx().with_position("BTC-USD", 0.5)).is_empty());
+    }
+
+    #[test]
+    fn test_trigger_wrong_side() {
+        let ctx = ctx();
+        let stop = |is_buy, trigger_price| Stop {
+            symbol: "BTC-USD".into(),
+            is_buy,
+            size: 0.1,
+            trigger_price,
+            limit_price: f64::NAN,
+            iso: false,
+        };
+        let tp = |is_buy, trigger_price| TakeProfit {
+            symbol: "BTC-USD".into(),
+            is_buy,
+            size: 0.1,
+            trigger_price,
+            limit_price: f64::NAN,
+            iso: false,
+        };
+        let wrong = [LintCode::TriggerWrongSide];
+        assert!(codes(stop(false, 99_999.0), &ctx).is_empty());
+        assert_eq!(codes(stop(false, 100_000.0), &ctx), wrong);
+        assert!(codes(stop(true, 100_001.0), &ctx).is_empty());
+        assert_eq!(codes(stop(true, 100_000.0), &ctx), wrong);
+        assert!(codes(tp(false, 100_001.0), &ctx).is_empty());
+        assert_eq!(codes(tp(false, 100_000.0), &ctx), wrong);
+        assert!(codes(tp(true, 99_999.0), &ctx).is_empty());
+        assert_eq!(codes(tp(true, 100_000.0), &ctx), wrong);
+    }
+
+    #[test]
+    fn test_pre_sign_hook_modes() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let marketable = limit(true, 130_000.0, TimeInForce::Gtc);
+        let far_ioc = limit(true, 90_000.0, TimeInForce::Ioc);
+        let seen = Arc::new(AtomicUsize::new(0));
+
+        let mut signer = Signer::new(Keypair::generate());
+        let counter = seen.clone();
+        signer.set_pre_sign_hook(Some(ctx().into_pre_sign_hook(
+            LintMode::Warn,
+            move |_, _| {
+                counter.fetch_add(1, Ordering::SeqCst);
+            },
+        )));
+        assert!(signer.sign(marketable.clone().into(), Some(1)).is_ok());
+        assert_eq!(seen.load(Ordering::SeqCst), 1);
+
+        signer.set_pre_sign_hook(Some(ctx().into_pre_sign_hook(LintMode::Block, |_, _| {})));
+        assert!(matches!(
+            signer.sign(marketable.into(), Some(2)),
+            Err(Error::PreSignRejected(_))
+        ));
+        // Info findings are reported but never block.
+        assert!(signer.sign(far_ioc.into(), Some(3)).is_ok());
+    }
+
+    fn group_codes(items: Vec<OrderItem>) -> Vec<(usize, LintCode)> {
+        lint_group(&items)
+            .into_iter()
+            .map(|w| (w.index, w.warning.code))
+            .collect()
+    }
+
+    fn stop(is_buy: bool, trigger_price: f64) -> OrderItem {
+        Stop {
+            symbol: "BTC-USD".into(),
+            is_buy,
+            size: 0.1,
+            trigger_price,
+            limit_price: f64::NAN,
+            iso: false,
+        }
+        .into()
+    }
+
+    fn take_profit(is_buy: bool, trigger_price: f64) -> OrderItem {
+        TakeProfit {
+            symbol: "BTC-USD".into(),
+            is_buy,
+            size: 0.1,
+            trigger_price,
+            limit_price: f64::NAN,
+            iso: false,
+        }
+        .into()
+    }
+
+    fn entry() -> OrderItem {
+        limit(true, 100_000.0, TimeInForce::Gtc).into()
+    }
+
+    #[test]
+    fn test_group_duplicate_legs() {
+        assert_eq!(
+            group_codes(vec![entry(), entry()]),
+            [(1, LintCode::DuplicateLeg)]
+        );
+        // The reported bug: stop and take-profit both selling at one trigger.
+        assert_eq!(
+            group_codes(vec![
+                entry(),
+                take_profit(false, 95_000.0),
+                stop(false, 95_000.0)
+            ]),
+            [(2, LintCode::DuplicateLeg)]
+        );
+        let other_price = limit(true, 100_001.0, TimeInForce::Gtc).into();
+        let other_symbol = Order::limit("ETH-USD", true, 100_000.0, 0.1, TimeInForce::Gtc).into();
+        assert!(group_codes(vec![entry(), other_price, other_symbol]).is_empty());
+    }
+
+    #[test]
+    fn test_group_protection_same_side() {
+        assert_eq!(
+            group_codes(vec![
+                entry(),
+                take_profit(true, 110_000.0),
+                stop(false, 95_000.0)
+            ]),
+            [(1, LintCode::ProtectionSameSide)]
+        );
+        assert!(group_codes(vec![
+            entry(),
+            take_profit(false, 110_000.0),
+            stop(false, 95_000.0)
+        ])
+        .is_empty());
+        // Without an entry in the group there is no side to compare with.
+        assert!(group_codes(vec![take_profit(true, 110_000.0)]).is_empty());
+    }
+
+    #[test]
+    fn test_group_oversized_reduce_only() {
+        let close = |size| {
+            Order::limit("BTC-USD", false, 110_000.0, size, TimeInForce::Gtc)
+                .reduce_only()
+                .into()
+        };
+        assert_eq!(
+            group_codes(vec![entry(), close(0.2)]),
+            [(1, LintCode::OversizedReduceOnly)]
+        );
+        assert!(group_codes(vec![entry(), close(0.1)]).is_empty());
+        // Closing a position opened elsewhere is fine.
+        assert!(group_codes(vec![close(0.2)]).is_empty());
+    }
+
+    #[test]
+    fn test_strict_group_blocks_signing() {
+        let mut signer = Signer::new(Keypair::generate());
+        let bad = vec![entry(), take_profit(true, 110_000.0)];
+
+        let tx = signer.sign_group(bad.clone(), Some(1)).unwrap();
+        assert_eq!(tx.group_warnings.len(), 1);
+        assert_eq!(
+            tx.group_warnings[0].warning.severity,
+            LintSeverity::Critical
+        );
+
+        let strict = GroupOptions::new().strict();
+        let err = signer
+            .sign_group_with_options(bad.clone(), &strict, Some(2))
+            .unwrap_err();
+        assert!(err.to_string().contains("leg 1: protection_same_side"));
+        let account = Keypair::generate().pubkey();
+        assert!(matches!(
+            crate::prepare_group_with_options(bad, &strict, &account, None, Some(3)),
+            Err(Error::GroupRejected(_))
+        ));
+
+        let good = vec![
+            entry(),
+            take_profit(false, 110_000.0),
+            stop(false, 95_000.0),
+        ];
+        let tx = signer
+            .sign_group_with_options(good, &strict, Some(4))
+            .unwrap();
+        assert!(tx.group_warnings.is_empty());
+    }
+}
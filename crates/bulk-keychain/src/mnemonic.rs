@@ -0,0 +1,229 @@
+//! BIP39 seed phrases (`bip39` feature)
+//!
+//! A phrase and optional passphrase are turned into a 64-byte seed with
+//! BIP39's PBKDF2, and [`Keypair::from_mnemonic`] derives a key from that
+//! seed with SLIP-0010 (see [`crate::hd`]). With
+//! [`SOLANA_DERIVATION_PATH`] this is the first account Phantom, Solflare
+//! and `solana-keygen recover 'prompt://?key=0/0'` show for the phrase.
+//!
+//! [`Keypair::from_mnemonic_seed`] instead takes the first 32 bytes of the
+//! seed as the secret key, which is what `solana-keygen recover` gives
+//! without a derivation path.
+//!
+//! ```rust
+//! use bulk_keychain::mnemonic::SOLANA_DERIVATION_PATH;
+//! use bulk_keychain::Keypair;
+//!
+//! let (keypair, phrase) = Keypair::generate_mnemonic();
+//! assert_eq!(phrase.split_whitespace().count(), 24);
+//! let restored = Keypair::from_mnemonic(&phrase, "", SOLANA_DERIVATION_PATH).unwrap();
+//! assert_eq!(restored.pubkey(), keypair.pubkey());
+//! ```
+
+use crate::hd::ExtendedKey;
+use crate::{Error, Keypair, Result};
+use bip39::Mnemonic;
+use rand::rngs::OsRng;
+use rand::RngCore;
+use zeroize::Zeroizing;
+
+/// Path of the first account of a Solana wallet.
+pub const SOLANA_DERIVATION_PATH: &str = "m/44'/501'/0'/0'";
+
+/// Entropy for a 24-word phrase.
+const ENTROPY_BYTES: usize = 32;
+
+impl Keypair {
+    /// Generate a keypair along with the 24-word English phrase it is
+    /// derived from at [`SOLANA_DERIVATION_PATH`]. The phrase is the only
+    /// backup of the key.
+    pub fn generate_mnemonic() -> (Keypair, String) {
+        let mut entropy = Zeroizing::new([0u8; ENTROPY_BYTES]);
+        OsRng.fill_bytes(entropy.as_mut());
+        let mnemonic = Mnemonic::from_entropy(entropy.as_ref())
+            .expect("32 bytes is a valid BIP39 entropy length");
+        let keypair = Self::from_bip39(&mnemonic, "", SOLANA_DERIVATION_PATH)
+            .expect("SOLANA_DERIVATION_PATH is a valid path");
+        (keypair, mnemonic.to_string())
+    }
+
+    /// Derive the key at `derivation_path` (usually
+    /// [`SOLANA_DERIVATION_PATH`]) from a 12- or 24-word English BIP39
+    /// phrase and its passphrase (`""` if none).
+    ///
+    /// Case and spacing of the phrase are normalized. Other word counts,
+    /// unknown words and bad checksums are [`Error::InvalidMnemonic`]; a
+    /// bad path is [`Error::InvalidDerivationPath`].
+    pub fn from_mnemonic(phrase: &str, passphrase: &str, derivation_path: &str) -> Result<Keypair> {
+        let words = phrase.split_whitespace().count();
+        if words != 12 && words != 24 {
+            return Err(Error::InvalidMnemonic(format!(
+                "expected 12 or 24 words, got {}",
+                words
+            )));
+        }
+        Self::from_bip39(&parse(phrase)?, passphrase, derivation_path)
+    }
+
+    /// Keypair whose secret key is the first 32 bytes of the seed of an
+    /// English BIP39 phrase (12 to 24 words), as `solana-keygen recover`
+    /// without a derivation path.
+    pub fn from_mnemonic_seed(phrase: &str, passphrase: &str) -> Result<Keypair> {
+        let seed = Zeroizing::new(parse(phrase)?.to_seed(passphrase));
+        Self::from_secret_key(&seed[..32])
+    }
+
+    fn from_bip39(mnemonic: &Mnemonic, passphrase: &str, path: &str) -> Result<Keypair> {
+        let seed = Zeroizing::new(mnemonic.to_seed(passphrase));
+        let node = ExtendedKey::master(seed.as_ref()).derive_path(path)?;
+        Ok(Keypair::from_extended_key(&node))
+    }
+}
+
+fn parse(phrase: &str) -> Result<Mnemonic> {
+    let normalized = Zeroizing::new(
+        phrase
+            .split_whitespace()
+            .map(str::to_lowercase)
+            .collect::<Vec<_>>()
+            .join(" "),
+    );
+    Mnemonic::parse_normalized(&normalized).map_err(|e| Error::InvalidMnemonic(e.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // BIP39 test vectors: entropy 0x00 * 16 and 0x7f * 16.
+    const ABANDON: &str = "abandon abandon abandon abandon abandon abandon \
+                           abandon abandon abandon abandon abandon about";
+    const LEGAL: &str = "legal winner thank year wave sausage worth useful \
+                         legal winner thank yellow";
+
+    fn address(phrase: &str, passphrase: &str, path: &str) -> String {
+        Keypair::from_mnemonic(phrase, passphrase, path)
+            .unwrap()
+            .pubkey()
+            .to_base58()
+    }
+
+    #[test]
+    fn test_solana_wallet_addresses() {
+        // (phrase, passphrase, path, address), as shown by Phantom and
+        // `solana-keygen pubkey prompt://?key=...`.
+        for (phrase, passphrase, path, expected) in [
+            (
+                ABANDON,
+                "",
+                SOLANA_DERIVATION_PATH,
+                "HAgk14JpMQLgt6rVgv7cBQFJWFto5Dqxi472uT3DKpqk",
+            ),
+            (
+                ABANDON,
+                "",
+                "m/44'/501'/1'/0'",
+                "Hh8QwFUA6MtVu1qAoq12ucvFHNwCcVTV7hpWjeY1Hztb",
+            ),
+            (
+                ABANDON,
+                "",
+                "m/44'/501'/0'",
+                "GjJyeC1r2RgkuoCWMyPYkCWSGSGLcz266EaAkLA27AhL",
+            ),
+            (
+                ABANDON,
+                "",
+                "m/44'/501'",
+                "D2PPQSYFe83nDzk96FqGumVU8JA7J8vj2Rhjc2oXzEi5",
+            ),
+            (
+                ABANDON,
+                "TREZOR",
+                SOLANA_DERIVATION_PATH,
+                "7zSmbu6gKkb6HB7UDPtHYjwCWuBHU1D4TpNZFm4sndQe",
+            ),
+            (
+                LEGAL,
+                "",
+                SOLANA_DERIVATION_PATH,
+                "BLeUXTx9thHGT7VJUtF9vHEmfMDgW1nnKZ9UVer2CoLX",
+            ),
+            (
+                LEGAL,
+                "",
+                "m/44'/501'/1'/0'",
+                "EdjcxP8MmXP4yRHguEVoH75kbXVfZNFXPgNfL9NqcXXK",
+            ),
+            (
+                LEGAL,
+                "TREZOR",
+                SOLANA_DERIVATION_PATH,
+                "AzshxZEXxrMd9UHDsAQWbVYa1aMDchbh9BFrDa5KgiJN",
+            ),
+        ] {
+            assert_eq!(address(phrase, passphrase, path), expected, "{path}");
+        }
+        let shouty = ABANDON.to_uppercase().replace(' ', "  ");
+        assert_eq!(
+            address(&shouty, "", SOLANA_DERIVATION_PATH),
+            "HAgk14JpMQLgt6rVgv7cBQFJWFto5Dqxi472uT3DKpqk"
+        );
+    }
+
+    #[test]
+    fn test_derived_key_continues_down_the_tree() {
+        let account = Keypair::from_mnemonic(ABANDON, "", "m/44'/501'/0'").unwrap();
+        assert_eq!(
+            account.derive_child(0, true).unwrap().pubkey().to_base58(),
+            address(ABANDON, "", SOLANA_DERIVATION_PATH)
+        );
+    }
+
+    #[test]
+    fn test_seed_prefix_is_the_secret_key() {
+        let keypair = Keypair::from_mnemonic_seed(ABANDON, "").unwrap();
+        // First half of the vector's seed with an empty passphrase.
+        assert_eq!(
+            hex::encode(keypair.secret_key()),
+            "5eb00bbddcf069084889a8ab9155568165f5c453ccb85e70811aaed6f6da5fc1"
+        );
+        assert_eq!(
+            keypair.pubkey().to_base58(),
+            "EHqmfkN89RJ7Y33CXM6uCzhVeuywHoJXZZLszBHHZy7o"
+        );
+    }
+
+    #[test]
+    fn test_generate_round_trips() {
+        let (keypair, phrase) = Keypair::generate_mnemonic();
+        assert_eq!(phrase.split(' ').count(), 24);
+        let restored = Keypair::from_mnemonic(&phrase, "", SOLANA_DERIVATION_PATH).unwrap();
+        assert_eq!(restored.to_bytes(), keypair.to_bytes());
+        assert_ne!(Keypair::generate_mnemonic().1, phrase);
+    }
+
+    #[test]
+    fn test_rejects_bad_phrases() {
+        let bad_checksum = ABANDON.replace("about", "abandon");
+        // Valid 18-word phrase (entropy 0x00 * 24).
+        let eighteen = format!("{}agent", "abandon ".repeat(17));
+        for (phrase, reason) in [
+            ("", "expected 12 or 24 words, got 0"),
+            ("abandon", "expected 12 or 24 words, got 1"),
+            (eighteen.as_str(), "expected 12 or 24 words, got 18"),
+            ("notaword ".repeat(12).as_str(), "unknown word"),
+            (&bad_checksum, "checksum"),
+        ] {
+            match Keypair::from_mnemonic(phrase, "", SOLANA_DERIVATION_PATH) {
+                Err(Error::InvalidMnemonic(msg)) => assert!(msg.contains(reason), "{msg}"),
+                other => panic!("{phrase}: {:?}", other.map(|k| k.pubkey())),
+            }
+        }
+        assert!(Keypair::from_mnemonic_seed(&eighteen, "").is_ok());
+        assert!(matches!(
+            Keypair::from_mnemonic(ABANDON, "", "m/44'/501'/0'/0"),
+            Err(Error::InvalidDerivationPath(_))
+        ));
+    }
+}
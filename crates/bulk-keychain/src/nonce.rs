@@ -3,7 +3,16 @@
 //! The BULK exchange requires unique nonces for replay protection.
 //! This module provides helpers for generating and managing nonces.
 
-use std::sync::atomic::{AtomicU64, Ordering};
+use crate::sdk_compat::serialize_for_sdk_signing;
+use crate::{Action, DurationMs, Error, Pubkey, Result};
+use rand::rngs::OsRng;
+use rand::RngCore;
+use sha2::{Digest, Sha256};
+use std::fs::{File, OpenOptions};
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicI64, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
 use std::time::{SystemTime, UNIX_EPOCH};
 
 /// Strategy for generating nonces
@@ -15,46 +24,598 @@ pub enum NonceStrategy {
     Counter,
     /// Timestamp with sub-millisecond counter for high-frequency
     TimestampWithCounter,
+    /// Hash of the action content and `epoch` (see [`content_derived_nonce`]),
+    /// so retries sign to identical bytes the server can deduplicate.
+    ///
+    /// Only for idempotent actions ([`Action::is_idempotent`]); everything
+    /// else is refused, since repeating the same order on purpose would be
+    /// indistinguishable from a replay. Bump `epoch` to apply the same
+    /// content again.
+    ContentDerived { epoch: u64 },
+    /// A random 53-bit nonce (exact as a JS number) from the OS RNG, so
+    /// nonces reveal nothing about order flow; a [`Timestamp`](Self::Timestamp)
+    /// nonce whenever the RNG fails.
+    ///
+    /// Random nonces are unordered and carry no time, so this only suits a
+    /// server that accepts any unused nonce.
+    RandomWithTimestampFallback,
 }
 
+/// How far ahead of its clock the server is assumed to accept a timestamp
+/// nonce. The exchange does not publish its window; confirm this against
+/// the deployment and pass a [`NonceWindow`] where it differs.
+pub const SERVER_NONCE_MAX_AHEAD_MS: u64 = 10_000;
+
+/// How far behind its clock the server is assumed to accept a timestamp
+/// nonce; see [`SERVER_NONCE_MAX_AHEAD_MS`].
+pub const SERVER_NONCE_MAX_BEHIND_MS: u64 = 60_000;
+
+/// Range of timestamp nonces a server accepts, relative to its clock: a
+/// nonce at `n` ms is accepted while `n - max_ahead_ms <= now <= n + max_behind_ms`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NonceWindow {
+    pub max_ahead_ms: DurationMs,
+    pub max_behind_ms: DurationMs,
+}
+
+impl NonceWindow {
+    pub fn new(max_ahead: impl Into<DurationMs>, max_behind: impl Into<DurationMs>) -> Self {
+        Self {
+            max_ahead_ms: max_ahead.into(),
+            max_behind_ms: max_behind.into(),
+        }
+    }
+}
+
+impl Default for NonceWindow {
+    fn default() -> Self {
+        Self::new(SERVER_NONCE_MAX_AHEAD_MS, SERVER_NONCE_MAX_BEHIND_MS)
+    }
+}
+
+/// Nonce state as read from a [`NonceStore`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PersistedNonce {
+    /// Nothing persisted yet
+    Empty,
+    /// Last nonce handed out
+    Value(u64),
+    /// State failed its integrity check
+    Corrupt,
+}
+
+/// Shared backing store for nonce state.
+///
+/// `update` must run read-modify-write atomically with respect to every other
+/// handle on the same state, including handles in other processes.
+pub trait NonceStore: Send + Sync {
+    /// Replace the persisted nonce with `f(current)` and return the new value.
+    fn update(&self, f: &mut dyn FnMut(PersistedNonce) -> Result<u64>) -> Result<u64>;
+}
+
+/// File-backed [`NonceStore`] guarded by an advisory lock
+/// (`flock` on Unix, `LockFileEx` on Windows).
+///
+/// The file holds the last nonce (u64 LE) followed by an 8-byte SHA256 checksum.
+#[derive(Debug)]
+pub struct FileNonceStore {
+    path: PathBuf,
+    file: Mutex<File>,
+}
+
+impl FileNonceStore {
+    const LEN: usize = 16;
+
+    /// Open (or create) the state file at `path`.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(&path)
+            .map_err(|e| Error::NonceStore(format!("{}: {}", path.display(), e)))?;
+        Ok(Self {
+            path,
+            file: Mutex::new(file),
+        })
+    }
+
+    /// Path of the state file.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    fn checksum(value: u64) -> [u8; 8] {
+        let digest = Sha256::digest(value.to_le_bytes());
+        let mut out = [0u8; 8];
+        out.copy_from_slice(&digest[..8]);
+        out
+    }
+
+    fn read_state(file: &mut File) -> std::io::Result<PersistedNonce> {
+        let mut buf = Vec::with_capacity(Self::LEN);
+        file.seek(SeekFrom::Start(0))?;
+        file.read_to_end(&mut buf)?;
+        if buf.is_empty() {
+            return Ok(PersistedNonce::Empty);
+        }
+        if buf.len() != Self::LEN {
+            return Ok(PersistedNonce::Corrupt);
+        }
+        let mut value = [0u8; 8];
+        value.copy_from_slice(&buf[..8]);
+        let value = u64::from_le_bytes(value);
+        if buf[8..] != Self::checksum(value) {
+            return Ok(PersistedNonce::Corrupt);
+        }
+        Ok(PersistedNonce::Value(value))
+    }
+
+    fn write_state(file: &mut File, value: u64) -> std::io::Result<()> {
+        let mut buf = [0u8; Self::LEN];
+        buf[..8].copy_from_slice(&value.to_le_bytes());
+        buf[8..].copy_from_slice(&Self::checksum(value));
+        file.seek(SeekFrom::Start(0))?;
+        file.write_all(&buf)?;
+        file.set_len(Self::LEN as u64)?;
+        file.sync_data()
+    }
+}
+
+impl NonceStore for FileNonceStore {
+    fn update(&self, f: &mut dyn FnMut(PersistedNonce) -> Result<u64>) -> Result<u64> {
+        let io_err =
+            |e: std::io::Error| Error::NonceStore(format!("{}: {}", self.path.display(), e));
+        // The mutex serializes threads sharing this handle; the file lock serializes handles.
+        let mut file = self
+            .file
+            .lock()
+            .map_err(|_| Error::NonceStore("nonce store mutex poisoned".to_string()))?;
+        file.lock().map_err(io_err)?;
+
+        let result = Self::read_state(&mut file)
+            .map_err(io_err)
+            .and_then(f)
+            .and_then(|next| {
+                Self::write_state(&mut file, next)
+                    .map_err(io_err)
+                    .map(|_| next)
+            });
+
+        file.unlock().map_err(io_err)?;
+        result
+    }
+}
+
+/// Something unusual the nonce manager saw while issuing nonces
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NonceAnomaly {
+    /// The clock read `observed_ms`, more than the regression threshold
+    /// behind the last nonce issued (`last_nonce_ms`, in milliseconds).
+    /// Usually an NTP step backwards; nonces keep increasing regardless.
+    ClockRegression {
+        observed_ms: u64,
+        last_nonce_ms: u64,
+    },
+}
+
+/// Callback for [`NonceManager::set_on_anomaly`]
+pub type AnomalyCallback = Arc<dyn Fn(&NonceAnomaly) + Send + Sync>;
+
 /// Thread-safe nonce manager
+///
+/// Timestamp strategies are monotonic: a nonce is never below the previous
+/// one, even if the clock steps backwards. Regressions beyond
+/// [`NonceManager::with_regression_threshold_ms`] are recorded once per
+/// episode (until the clock catches up again) in
+/// [`NonceManager::anomalies`].
 pub struct NonceManager {
     strategy: NonceStrategy,
     counter: AtomicU64,
     last_timestamp: AtomicU64,
+    store: Option<Box<dyn NonceStore>>,
+    clock_offset_ms: AtomicI64,
+    clock: Option<Box<dyn Fn() -> u64 + Send + Sync>>,
+    regression_threshold_ms: u64,
+    in_regression: AtomicBool,
+    anomalies: Mutex<Vec<NonceAnomaly>>,
+    on_anomaly: Mutex<Option<AnomalyCallback>>,
+    random: Option<Box<dyn Fn() -> Option<u64> + Send + Sync>>,
+    /// Random nonce drawn by [`NonceManager::peek_next`], issued next
+    peeked_random: Mutex<Option<u64>>,
 }
 
 impl NonceManager {
+    /// Clock regressions smaller than this are absorbed silently.
+    pub const DEFAULT_REGRESSION_THRESHOLD_MS: u64 = 1_000;
+    /// Anomalies kept by [`NonceManager::anomalies`]; older ones are dropped.
+    const MAX_ANOMALIES: usize = 64;
+
     /// Create a new nonce manager with the specified strategy
+    ///
+    /// ```rust
+    /// use bulk_keychain::{NonceManager, NonceStrategy};
+    ///
+    /// let manager = NonceManager::new(NonceStrategy::Counter);
+    /// assert_eq!((manager.next(), manager.next()), (0, 1));
+    /// ```
     pub fn new(strategy: NonceStrategy) -> Self {
         Self {
             strategy,
             counter: AtomicU64::new(0),
             last_timestamp: AtomicU64::new(0),
+            store: None,
+            clock_offset_ms: AtomicI64::new(0),
+            clock: None,
+            regression_threshold_ms: Self::DEFAULT_REGRESSION_THRESHOLD_MS,
+            in_regression: AtomicBool::new(false),
+            anomalies: Mutex::new(Vec::new()),
+            on_anomaly: Mutex::new(None),
+            random: None,
+            peeked_random: Mutex::new(None),
+        }
+    }
+
+    /// Create a nonce manager whose state lives in `store`, shared with every
+    /// other manager (in any process) backed by the same state.
+    ///
+    /// Nonces are strictly increasing across all of them. If the state is corrupt,
+    /// timestamp strategies recover from the clock; `Counter` refuses to continue.
+    ///
+    /// ```rust
+    /// use bulk_keychain::{FileNonceStore, NonceManager, NonceStrategy};
+    ///
+    /// let path = std::env::temp_dir().join(format!("bulk-keychain-doc-{}", std::process::id()));
+    /// let store = FileNonceStore::open(&path).unwrap();
+    /// let first = NonceManager::persistent(NonceStrategy::Counter, store)
+    ///     .try_next()
+    ///     .unwrap();
+    ///
+    /// // A second manager on the same file (e.g. after a restart) continues from it.
+    /// let store = FileNonceStore::open(&path).unwrap();
+    /// let restarted = NonceManager::persistent(NonceStrategy::Counter, store);
+    /// assert_eq!(restarted.try_next().unwrap(), first + 1);
+    /// std::fs::remove_file(&path).unwrap();
+    /// ```
+    pub fn persistent(strategy: NonceStrategy, store: impl NonceStore + 'static) -> Self {
+        Self {
+            store: Some(Box::new(store)),
+            ..Self::new(strategy)
         }
     }
 
     /// Create a timestamp-based nonce manager
+    ///
+    /// ```rust
+    /// use bulk_keychain::nonce::current_timestamp_millis;
+    /// use bulk_keychain::{Keypair, NonceManager, Order, Signer};
+    ///
+    /// let before = current_timestamp_millis();
+    /// let mut signer = Signer::with_nonce_manager(Keypair::generate(), NonceManager::timestamp());
+    /// let first = signer.sign(Order::market("BTC-USD", true, 0.1).into(), None).unwrap();
+    /// let second = signer.sign(Order::market("BTC-USD", true, 0.1).into(), None).unwrap();
+    /// assert!(first.nonce >= before);
+    /// assert!(second.nonce > first.nonce);
+    /// ```
     pub fn timestamp() -> Self {
         Self::new(NonceStrategy::Timestamp)
     }
 
     /// Create a counter-based nonce manager
+    ///
+    /// ```rust
+    /// use bulk_keychain::NonceManager;
+    ///
+    /// let manager = NonceManager::counter();
+    /// assert_eq!((manager.next(), manager.next(), manager.next()), (0, 1, 2));
+    /// ```
     pub fn counter() -> Self {
         Self::new(NonceStrategy::Counter)
     }
 
     /// Create a high-frequency nonce manager (timestamp + counter)
+    ///
+    /// ```rust
+    /// use bulk_keychain::nonce::current_timestamp_millis;
+    /// use bulk_keychain::NonceManager;
+    ///
+    /// let manager = NonceManager::high_frequency();
+    /// let nonces: Vec<u64> = (0..100).map(|_| manager.next()).collect();
+    /// assert!(nonces.windows(2).all(|w| w[1] > w[0]));
+    /// // Microsecond-scale: the timestamp times 1000 plus a sequence.
+    /// assert!(nonces[0] >= current_timestamp_millis() * 1000 - 1_000_000);
+    /// ```
     pub fn high_frequency() -> Self {
         Self::new(NonceStrategy::TimestampWithCounter)
     }
 
+    /// Create a manager that derives nonces from action content under `epoch`
+    ///
+    /// ```rust
+    /// use bulk_keychain::{Keypair, NonceManager, Signer, UserSettings};
+    ///
+    /// let keypair = Keypair::generate();
+    /// let mut signer = Signer::with_nonce_manager(keypair, NonceManager::content_derived(1));
+    /// let settings = UserSettings::set_leverage("BTC-USD", 5.0);
+    /// let first = signer.sign_user_settings(settings.clone(), None).unwrap();
+    /// let retry = signer.sign_user_settings(settings, None).unwrap();
+    /// assert_eq!(first.signature, retry.signature);
+    /// ```
+    pub fn content_derived(epoch: u64) -> Self {
+        Self::new(NonceStrategy::ContentDerived { epoch })
+    }
+
+    /// Create a manager issuing random nonces, falling back to timestamps
+    ///
+    /// ```rust
+    /// use bulk_keychain::NonceManager;
+    ///
+    /// let manager = NonceManager::random();
+    /// let nonce = manager.next();
+    /// assert!(nonce < 1 << 53);
+    /// assert_ne!(manager.next(), nonce);
+    /// ```
+    pub fn random() -> Self {
+        Self::new(NonceStrategy::RandomWithTimestampFallback)
+    }
+
+    pub fn strategy(&self) -> NonceStrategy {
+        self.strategy
+    }
+
+    /// Shift timestamp nonces by `offset_ms`, e.g. the measured skew between
+    /// the local clock and the exchange's. `Counter` nonces are unaffected.
+    pub fn with_clock_offset_ms(self, offset_ms: i64) -> Self {
+        self.set_clock_offset_ms(offset_ms);
+        self
+    }
+
+    /// See [`NonceManager::with_clock_offset_ms`]. Takes effect for every
+    /// signer sharing this manager.
+    pub fn set_clock_offset_ms(&self, offset_ms: i64) {
+        self.clock_offset_ms.store(offset_ms, Ordering::Relaxed);
+    }
+
+    pub fn clock_offset_ms(&self) -> i64 {
+        self.clock_offset_ms.load(Ordering::Relaxed)
+    }
+
+    /// Read time from `clock` (milliseconds) instead of the system clock.
+    pub fn with_clock(mut self, clock: impl Fn() -> u64 + Send + Sync + 'static) -> Self {
+        self.clock = Some(Box::new(clock));
+        self
+    }
+
+    /// Draw random nonces from `source` instead of the OS RNG, e.g. a seeded
+    /// RNG for a replayable simulation. `None` from `source` falls back to a
+    /// timestamp nonce.
+    pub fn with_random_source(
+        mut self,
+        source: impl Fn() -> Option<u64> + Send + Sync + 'static,
+    ) -> Self {
+        self.random = Some(Box::new(source));
+        self
+    }
+
+    /// Record a [`NonceAnomaly::ClockRegression`] when the clock falls more
+    /// than `threshold_ms` behind the last nonce.
+    pub fn with_regression_threshold_ms(mut self, threshold_ms: u64) -> Self {
+        self.regression_threshold_ms = threshold_ms;
+        self
+    }
+
+    /// Call `callback` whenever an anomaly is recorded, e.g. to page someone.
+    /// It runs on the signing thread, so it should return quickly.
+    pub fn with_on_anomaly(self, callback: impl Fn(&NonceAnomaly) + Send + Sync + 'static) -> Self {
+        self.set_on_anomaly(Some(Arc::new(callback)));
+        self
+    }
+
+    /// See [`NonceManager::with_on_anomaly`]; `None` removes the callback.
+    pub fn set_on_anomaly(&self, callback: Option<AnomalyCallback>) {
+        *self.on_anomaly.lock().unwrap_or_else(|e| e.into_inner()) = callback;
+    }
+
+    /// Recently recorded anomalies, oldest first.
+    pub fn anomalies(&self) -> Vec<NonceAnomaly> {
+        self.anomalies
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+
+    /// Current time in milliseconds with the clock offset applied.
+    pub fn now_ms(&self) -> u64 {
+        let raw = self
+            .clock
+            .as_ref()
+            .map_or_else(current_timestamp_millis, |clock| clock());
+        raw.saturating_add_signed(self.clock_offset_ms())
+    }
+
     /// Get the next nonce
+    ///
+    /// # Panics
+    ///
+    /// Panics if a persistent store fails; use [`NonceManager::try_next`] to handle that.
     pub fn next(&self) -> u64 {
-        match self.strategy {
-            NonceStrategy::Timestamp => current_timestamp_millis(),
-            NonceStrategy::Counter => self.counter.fetch_add(1, Ordering::SeqCst),
-            NonceStrategy::TimestampWithCounter => self.next_hf(),
+        self.try_next().expect("nonce store failed")
+    }
+
+    /// Get the next nonce, surfacing persistent store failures.
+    ///
+    /// Fails under [`NonceStrategy::ContentDerived`], which needs the action;
+    /// use [`content_derived_nonce`].
+    pub fn try_next(&self) -> Result<u64> {
+        if let NonceStrategy::ContentDerived { .. } = self.strategy {
+            return Err(Error::ContentDerivedNonce("an action not known up front"));
+        }
+        match &self.store {
+            Some(store) => store.update(&mut |state| self.next_persisted(state)),
+            None => Ok(match self.strategy {
+                NonceStrategy::Timestamp => {
+                    let now = self.now_ms();
+                    self.next_monotonic(now, now)
+                }
+                NonceStrategy::Counter | NonceStrategy::ContentDerived { .. } => {
+                    self.counter.fetch_add(1, Ordering::SeqCst)
+                }
+                NonceStrategy::TimestampWithCounter => self.next_hf(),
+                NonceStrategy::RandomWithTimestampFallback => {
+                    let peeked = self.peeked_random().take();
+                    match peeked.or_else(|| self.draw_random()) {
+                        Some(nonce) => nonce,
+                        None => {
+                            let now = self.now_ms();
+                            self.next_monotonic(now, now)
+                        }
+                    }
+                }
+            }),
+        }
+    }
+
+    /// The nonce [`NonceManager::next`] would return now, without taking it;
+    /// another caller may still take it first.
+    ///
+    /// ```rust
+    /// use bulk_keychain::NonceManager;
+    ///
+    /// let manager = NonceManager::counter();
+    /// manager.next();
+    /// assert_eq!(manager.peek_next(), 1);
+    /// assert_eq!(manager.next(), 1);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics where [`NonceManager::try_peek_next`] fails.
+    pub fn peek_next(&self) -> u64 {
+        self.try_peek_next().expect("nonce cannot be peeked")
+    }
+
+    /// See [`NonceManager::peek_next`]. Fails under
+    /// [`NonceStrategy::ContentDerived`], and for a persistent manager, whose
+    /// next nonce another process may take at any time.
+    pub fn try_peek_next(&self) -> Result<u64> {
+        if let NonceStrategy::ContentDerived { .. } = self.strategy {
+            return Err(Error::ContentDerivedNonce("an action not known up front"));
+        }
+        if self.store.is_some() {
+            return Err(Error::NonceStore(
+                "the next persisted nonce cannot be peeked".to_string(),
+            ));
+        }
+        let after_last = |candidate: u64| match self.last_timestamp.load(Ordering::SeqCst) {
+            0 => candidate,
+            last => candidate.max(last + 1),
+        };
+        Ok(match self.strategy {
+            NonceStrategy::Timestamp => after_last(self.now_ms()),
+            NonceStrategy::Counter | NonceStrategy::ContentDerived { .. } => {
+                self.counter.load(Ordering::SeqCst)
+            }
+            NonceStrategy::TimestampWithCounter => {
+                after_last(self.now_ms() * 1000 + self.counter.load(Ordering::SeqCst))
+            }
+            NonceStrategy::RandomWithTimestampFallback => {
+                let mut peeked = self.peeked_random();
+                match *peeked {
+                    Some(nonce) => nonce,
+                    None => match self.draw_random() {
+                        Some(nonce) => *peeked.insert(nonce),
+                        None => after_last(self.now_ms()),
+                    },
+                }
+            }
+        })
+    }
+
+    /// Make every later nonce at least the current time, in the strategy's
+    /// units: milliseconds, or milliseconds times 1000 for
+    /// `TimestampWithCounter`. A `Counter` jumps to the millisecond
+    /// timestamp. Returns the floor, or `None` for random and
+    /// content-derived nonces, which have no order to raise.
+    pub fn bump_floor_to_now(&self) -> Result<Option<u64>> {
+        let now = self.now_ms();
+        let floor = match self.strategy {
+            NonceStrategy::Timestamp | NonceStrategy::Counter => now,
+            NonceStrategy::TimestampWithCounter => now * 1000,
+            NonceStrategy::RandomWithTimestampFallback | NonceStrategy::ContentDerived { .. } => {
+                return Ok(None)
+            }
+        };
+        // Nonces continue from one past the last value recorded.
+        let last = floor.saturating_sub(1);
+        match &self.store {
+            Some(store) => {
+                store.update(&mut |state| match state {
+                    PersistedNonce::Value(prev) => Ok(prev.max(last)),
+                    PersistedNonce::Corrupt if self.strategy == NonceStrategy::Counter => Err(
+                        Error::NonceStore("persisted counter state is corrupt".to_string()),
+                    ),
+                    PersistedNonce::Empty | PersistedNonce::Corrupt => Ok(last),
+                })?;
+            }
+            None if self.strategy == NonceStrategy::Counter => {
+                self.counter.fetch_max(floor, Ordering::SeqCst);
+            }
+            None => {
+                self.last_timestamp.fetch_max(last, Ordering::SeqCst);
+            }
+        }
+        Ok(Some(floor))
+    }
+
+    /// A random 53-bit nonce, or `None` if the RNG failed.
+    fn draw_random(&self) -> Option<u64> {
+        let raw = match &self.random {
+            Some(source) => source()?,
+            None => {
+                let mut bytes = [0u8; 8];
+                OsRng.try_fill_bytes(&mut bytes).ok()?;
+                u64::from_le_bytes(bytes)
+            }
+        };
+        Some(raw & ((1 << 53) - 1))
+    }
+
+    fn peeked_random(&self) -> std::sync::MutexGuard<'_, Option<u64>> {
+        self.peeked_random.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn next_persisted(&self, state: PersistedNonce) -> Result<u64> {
+        let now = self.now_ms();
+        let floor = match self.strategy {
+            NonceStrategy::RandomWithTimestampFallback => match self.draw_random() {
+                // Random nonces are unordered; the store only records them.
+                Some(nonce) => return Ok(nonce),
+                None => now,
+            },
+            NonceStrategy::Timestamp => now,
+            NonceStrategy::Counter | NonceStrategy::ContentDerived { .. } => 0,
+            NonceStrategy::TimestampWithCounter => now * 1000,
+        };
+        match state {
+            PersistedNonce::Empty => Ok(floor),
+            PersistedNonce::Value(last) => {
+                if self.strategy == NonceStrategy::Timestamp
+                    || self.strategy == NonceStrategy::TimestampWithCounter
+                {
+                    self.observe_clock(now, last);
+                }
+                let next = last.checked_add(1).ok_or(Error::NonceOverflow {
+                    base: last,
+                    count: 1,
+                })?;
+                Ok(floor.max(next))
+            }
+            PersistedNonce::Corrupt if self.strategy == NonceStrategy::Counter => Err(
+                Error::NonceStore("persisted counter state is corrupt".to_string()),
+            ),
+            PersistedNonce::Corrupt => Ok(floor),
         }
     }
 
@@ -63,15 +624,83 @@ impl NonceManager {
     fn next_hf(&self) -> u64 {
         // Simply use an atomic counter that combines timestamp with sequence
         // This guarantees uniqueness and strict ordering
-        let base = current_timestamp_millis() * 1000; // Leave room for 1000 nonces per millisecond
+        let now = self.now_ms();
+        let base = now * 1000; // Leave room for 1000 nonces per millisecond
         let seq = self.counter.fetch_add(1, Ordering::SeqCst);
-        base + seq
+        self.next_monotonic(base + seq, now)
+    }
+
+    /// Issue `candidate`, or one past the last nonce if the clock went back.
+    fn next_monotonic(&self, candidate: u64, now_ms: u64) -> u64 {
+        let mut last = self.last_timestamp.load(Ordering::SeqCst);
+        let next = loop {
+            let next = if last == 0 {
+                candidate
+            } else {
+                candidate.max(last + 1)
+            };
+            match self.last_timestamp.compare_exchange_weak(
+                last,
+                next,
+                Ordering::SeqCst,
+                Ordering::SeqCst,
+            ) {
+                Ok(_) => break next,
+                Err(current) => last = current,
+            }
+        };
+        if last != 0 {
+            self.observe_clock(now_ms, last);
+        }
+        next
+    }
+
+    /// Compare the clock against the last nonce issued (in strategy units)
+    /// and record a regression at the start of each episode.
+    fn observe_clock(&self, observed_ms: u64, last_nonce: u64) {
+        let last_nonce_ms = match self.strategy {
+            NonceStrategy::TimestampWithCounter => last_nonce / 1000,
+            _ => last_nonce,
+        };
+        if observed_ms >= last_nonce_ms {
+            self.in_regression.store(false, Ordering::SeqCst);
+            return;
+        }
+        if last_nonce_ms - observed_ms <= self.regression_threshold_ms
+            || self.in_regression.swap(true, Ordering::SeqCst)
+        {
+            return;
+        }
+        let anomaly = NonceAnomaly::ClockRegression {
+            observed_ms,
+            last_nonce_ms,
+        };
+        {
+            let mut anomalies = self.anomalies.lock().unwrap_or_else(|e| e.into_inner());
+            if anomalies.len() == Self::MAX_ANOMALIES {
+                anomalies.remove(0);
+            }
+            anomalies.push(anomaly);
+        }
+        let callback = self
+            .on_anomaly
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone();
+        if let Some(callback) = callback {
+            callback(&anomaly);
+        }
     }
 
     /// Reset the counter (useful for testing)
+    ///
+    /// `Counter` and `TimestampWithCounter` start again from zero; a random
+    /// nonce drawn by [`NonceManager::peek_next`] is dropped.
     pub fn reset(&self) {
         self.counter.store(0, Ordering::SeqCst);
+        self.peeked_random().take();
         self.last_timestamp.store(0, Ordering::SeqCst);
+        self.in_regression.store(false, Ordering::SeqCst);
     }
 }
 
@@ -81,6 +710,35 @@ impl Default for NonceManager {
     }
 }
 
+/// Nonce for `action` under [`NonceStrategy::ContentDerived`]: the first
+/// 53 bits (exact as a JS number) of SHA256 over `epoch` and the action's
+/// signing bytes for `account`, with the nonce field zeroed.
+///
+/// Fails with [`Error::ContentDerivedNonce`] unless the action is idempotent.
+pub fn content_derived_nonce(action: &Action, account: &Pubkey, epoch: u64) -> Result<u64> {
+    if !action.is_idempotent() {
+        return Err(Error::ContentDerivedNonce(action.type_str()));
+    }
+    let mut message = Vec::with_capacity(128);
+    serialize_for_sdk_signing(action, 0, account, &mut message)?;
+    let digest = Sha256::new()
+        .chain_update(epoch.to_le_bytes())
+        .chain_update(&message)
+        .finalize();
+    let mut head = [0u8; 8];
+    head.copy_from_slice(&digest[..8]);
+    Ok(u64::from_le_bytes(head) & ((1 << 53) - 1))
+}
+
+/// Check that the batch nonces `base..base + count` all fit in a `u64`,
+/// returning `base`, or fail with [`Error::NonceOverflow`].
+pub fn check_nonce_range(base: u64, count: usize) -> Result<u64> {
+    match base.checked_add((count as u64).saturating_sub(1)) {
+        Some(_) => Ok(base),
+        None => Err(Error::NonceOverflow { base, count }),
+    }
+}
+
 /// Get current timestamp in milliseconds
 #[inline]
 pub fn current_timestamp_millis() -> u64 {
@@ -117,6 +775,91 @@ mod tests {
         assert!(n2 >= n1);
     }
 
+    #[test]
+    fn test_clock_offset() {
+        let ahead = NonceManager::timestamp().with_clock_offset_ms(5_000);
+        let now = current_timestamp_millis();
+        let n = ahead.next();
+        assert!(n >= now + 5_000 && n < now + 6_000);
+
+        let behind = NonceManager::high_frequency();
+        behind.set_clock_offset_ms(-5_000);
+        assert_eq!(behind.clock_offset_ms(), -5_000);
+        assert!(behind.next() < current_timestamp_millis() * 1000);
+
+        let counter = NonceManager::counter().with_clock_offset_ms(5_000);
+        assert_eq!(counter.next(), 0);
+    }
+
+    fn mock_clock(start: u64) -> (Arc<AtomicU64>, impl Fn() -> u64 + Send + Sync) {
+        let now = Arc::new(AtomicU64::new(start));
+        let clock = now.clone();
+        (now, move || clock.load(Ordering::SeqCst))
+    }
+
+    #[test]
+    fn test_small_clock_regression_absorbed() {
+        let (now, clock) = mock_clock(1_000_000);
+        let manager = NonceManager::timestamp().with_clock(clock);
+        assert_eq!(manager.next(), 1_000_000);
+        now.store(999_500, Ordering::SeqCst);
+        assert_eq!(manager.next(), 1_000_001);
+        assert_eq!(manager.next(), 1_000_002);
+        assert!(manager.anomalies().is_empty());
+    }
+
+    #[test]
+    fn test_large_clock_regression_reported_once_per_episode() {
+        let (now, clock) = mock_clock(1_000_000);
+        let fired = Arc::new(AtomicU64::new(0));
+        let counter = fired.clone();
+        let manager = NonceManager::timestamp()
+            .with_clock(clock)
+            .with_on_anomaly(move |_| {
+                counter.fetch_add(1, Ordering::SeqCst);
+            });
+        manager.next();
+
+        now.store(900_000, Ordering::SeqCst);
+        let nonces: Vec<u64> = (0..5).map(|_| manager.next()).collect();
+        assert_eq!(nonces, (1_000_001..=1_000_005).collect::<Vec<_>>());
+        assert_eq!(fired.load(Ordering::SeqCst), 1);
+        assert_eq!(
+            manager.anomalies(),
+            vec![NonceAnomaly::ClockRegression {
+                observed_ms: 900_000,
+                last_nonce_ms: 1_000_000,
+            }]
+        );
+
+        // The clock catches up, ending the episode; the next step back is a new one.
+        now.store(1_000_010, Ordering::SeqCst);
+        assert_eq!(manager.next(), 1_000_010);
+        now.store(500_000, Ordering::SeqCst);
+        manager.next();
+        manager.next();
+        assert_eq!(fired.load(Ordering::SeqCst), 2);
+        assert_eq!(manager.anomalies().len(), 2);
+    }
+
+    #[test]
+    fn test_high_frequency_regression_stays_monotonic() {
+        let (now, clock) = mock_clock(1_000_000);
+        let manager = NonceManager::high_frequency()
+            .with_clock(clock)
+            .with_regression_threshold_ms(10);
+        let before = manager.next();
+        now.store(999_000, Ordering::SeqCst);
+        assert!(manager.next() > before);
+        assert!(matches!(
+            manager.anomalies()[..],
+            [NonceAnomaly::ClockRegression {
+                observed_ms: 999_000,
+                last_nonce_ms: 1_000_000,
+            }]
+        ));
+    }
+
     #[test]
     fn test_counter_nonce() {
         let manager = NonceManager::counter();
@@ -125,6 +868,128 @@ mod tests {
         assert_eq!(manager.next(), 2);
     }
 
+    #[test]
+    fn test_random_nonce_falls_back_to_timestamp() {
+        let draws = Arc::new(Mutex::new(vec![None, Some(u64::MAX), Some(7)]));
+        let source = draws.clone();
+        let manager = NonceManager::random()
+            .with_clock(|| 1_700_000_000_000)
+            .with_random_source(move || source.lock().unwrap().pop().flatten());
+
+        assert_eq!(manager.peek_next(), 7);
+        assert_eq!(manager.peek_next(), 7);
+        assert_eq!(manager.next(), 7);
+        // Masked to 53 bits.
+        assert_eq!(manager.next(), (1 << 53) - 1);
+        // The RNG failed: a timestamp, then timestamps stay increasing.
+        assert_eq!(manager.peek_next(), 1_700_000_000_000);
+        assert_eq!(manager.next(), 1_700_000_000_000);
+        assert_eq!(manager.next(), 1_700_000_000_001);
+
+        let os = NonceManager::random();
+        assert_ne!(os.next(), os.next());
+    }
+
+    #[test]
+    fn test_peek_next_and_reset() {
+        let counter = NonceManager::counter();
+        assert_eq!(counter.peek_next(), 0);
+        counter.next();
+        counter.next();
+        assert_eq!(counter.peek_next(), 2);
+        counter.reset();
+        assert_eq!(counter.next(), 0);
+
+        let hf = NonceManager::high_frequency().with_clock(|| 5);
+        assert_eq!(hf.peek_next(), 5_000);
+        assert_eq!(hf.next(), 5_000);
+        assert_eq!(hf.peek_next(), 5_001);
+        hf.reset();
+        assert_eq!(hf.next(), 5_000);
+
+        let timestamp = NonceManager::timestamp().with_clock(|| 9);
+        timestamp.next();
+        assert_eq!(timestamp.peek_next(), 10);
+
+        assert!(NonceManager::content_derived(1).try_peek_next().is_err());
+    }
+
+    fn temp_state_path(name: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!(
+            "bulk-keychain-{}-{}-{}",
+            name,
+            std::process::id(),
+            current_timestamp_micros()
+        ));
+        let _ = std::fs::remove_file(&path);
+        path
+    }
+
+    #[test]
+    fn test_file_store_unique_across_handles() {
+        let path = temp_state_path("nonce-shared");
+        let threads = 4;
+        let per_thread = 50;
+
+        let handles: Vec<_> = (0..threads)
+            .map(|_| {
+                let store = FileNonceStore::open(&path).unwrap();
+                std::thread::spawn(move || {
+                    let manager = NonceManager::persistent(NonceStrategy::Counter, store);
+                    (0..per_thread)
+                        .map(|_| manager.try_next().unwrap())
+                        .collect::<Vec<_>>()
+                })
+            })
+            .collect();
+
+        let mut all: Vec<u64> = handles
+            .into_iter()
+            .flat_map(|h| h.join().unwrap())
+            .collect();
+        all.sort_unstable();
+        // No duplicates and no lost updates: exactly 0..N.
+        assert_eq!(all, (0..(threads * per_thread) as u64).collect::<Vec<_>>());
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_file_store_corruption() {
+        let path = temp_state_path("nonce-corrupt");
+        std::fs::write(&path, b"garbage").unwrap();
+
+        let counter =
+            NonceManager::persistent(NonceStrategy::Counter, FileNonceStore::open(&path).unwrap());
+        assert!(matches!(counter.try_next(), Err(Error::NonceStore(_))));
+
+        let before = current_timestamp_millis();
+        let timestamp = NonceManager::persistent(
+            NonceStrategy::Timestamp,
+            FileNonceStore::open(&path).unwrap(),
+        );
+        let recovered = timestamp.try_next().unwrap();
+        assert!(recovered >= before);
+        assert!(timestamp.try_next().unwrap() > recovered);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_file_store_exhausted() {
+        let path = temp_state_path("nonce-exhausted");
+        let store = FileNonceStore::open(&path).unwrap();
+        FileNonceStore::write_state(&mut store.file.lock().unwrap(), u64::MAX).unwrap();
+
+        let counter = NonceManager::persistent(NonceStrategy::Counter, store);
+        assert!(matches!(
+            counter.try_next(),
+            Err(Error::NonceOverflow {
+                base: u64::MAX,
+                count: 1
+            })
+        ));
+        std::fs::remove_file(&path).unwrap();
+    }
+
     #[test]
     fn test_high_frequency_nonce() {
         let manager = NonceManager::high_frequency();
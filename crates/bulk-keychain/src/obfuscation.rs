@@ -0,0 +1,206 @@
+//! Batch obfuscation helpers
+//!
+//! A batch's order and exact sizes can leak how a strategy is built. These
+//! helpers shuffle signed batches and jitter sizes before signing without
+//! changing the economics: jitter moves size between pairs of orders on the
+//! same symbol and side, so each side's total is unchanged.
+//!
+//! Both are driven by a caller-supplied seed so a run can be replayed exactly.
+//! They are not meant to be cryptographically unpredictable.
+
+use crate::types::{Order, SignedTransaction};
+use crate::{Error, Result, SymbolSpec};
+use std::collections::HashMap;
+
+/// SplitMix64: tiny, seedable and stable across crate and `rand` versions.
+struct SeededRng(u64);
+
+impl SeededRng {
+    fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// Uniform in `0..n`.
+    fn below(&mut self, n: u64) -> u64 {
+        ((self.next_u64() as u128 * n as u128) >> 64) as u64
+    }
+
+    /// Uniform in `[-1, 1)`.
+    fn signed_unit(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 52) as f64 - 1.0
+    }
+}
+
+/// Shuffle signed transactions in place (Fisher–Yates). The same seed and
+/// input always give the same order.
+pub fn shuffle_batch(txs: &mut [SignedTransaction], seed: u64) {
+    let mut rng = SeededRng(seed);
+    for i in (1..txs.len()).rev() {
+        let j = rng.below(i as u64 + 1) as usize;
+        txs.swap(i, j);
+    }
+}
+
+/// Jitter order sizes by up to `pct` (e.g. `0.1` for ±10%) while keeping
+/// each symbol-and-side total unchanged.
+///
+/// Orders are paired in order of appearance within each symbol and side;
+/// one order of a pair gains what the other loses. An unpaired order keeps
+/// its size.
+pub fn jitter_sizes(orders: &mut [Order], pct: f64, seed: u64) -> Result<()> {
+    jitter_sizes_with_specs(orders, pct, seed, &HashMap::new())
+}
+
+/// [`jitter_sizes`] that moves whole lots for symbols in `specs`, so jittered
+/// sizes stay on the lot grid and totals are preserved exactly. Sizes of
+/// those symbols must already be whole lots.
+pub fn jitter_sizes_with_specs(
+    orders: &mut [Order],
+    pct: f64,
+    seed: u64,
+    specs: &HashMap<String, SymbolSpec>,
+) -> Result<()> {
+    if !(0.0..1.0).contains(&pct) {
+        return Err(Error::InvalidOrder(format!(
+            "jitter pct must be in [0, 1), got {}",
+            pct
+        )));
+    }
+
+    let mut groups: Vec<((&str, bool), Vec<usize>)> = Vec::new();
+    for (index, order) in orders.iter().enumerate() {
+        let key = (order.symbol.as_str(), order.is_buy);
+        match groups.iter_mut().find(|(k, _)| *k == key) {
+            Some((_, indices)) => indices.push(index),
+            None => groups.push((key, vec![index])),
+        }
+    }
+    let pairs: Vec<(usize, usize)> = groups
+        .iter()
+        .flat_map(|(_, indices)| indices.as_chunks::<2>().0.iter().map(|&[a, b]| (a, b)))
+        .collect();
+
+    let mut rng = SeededRng(seed);
+    for (a, b) in pairs {
+        let shift = rng.signed_unit() * pct;
+        let (size_a, size_b) = (orders[a].size, orders[b].size);
+        match specs.get(&orders[a].symbol) {
+            Some(spec) => {
+                let (lots_a, lots_b) = (spec.lots_from_size(size_a)?, spec.lots_from_size(size_b)?);
+                let delta = (shift * lots_a.min(lots_b) as f64).trunc() as i64;
+                orders[a].size = spec.size_from_lots(lots_a + delta)?;
+                orders[b].size = spec.size_from_lots(lots_b - delta)?;
+            }
+            None => {
+                let delta = shift * size_a.min(size_b);
+                orders[a].size = size_a + delta;
+                orders[b].size = size_b - delta;
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Keypair, Signer, TimeInForce};
+
+    fn ladder() -> Vec<Order> {
+        [0.5, 1.25, 0.75, 2.0, 1.0]
+            .iter()
+            .enumerate()
+            .map(|(i, &size)| {
+                Order::limit(
+                    "BTC-USD",
+                    i % 2 == 0,
+                    100.0 + i as f64,
+                    size,
+                    TimeInForce::Gtc,
+                )
+            })
+            .collect()
+    }
+
+    fn side_totals(orders: &[Order]) -> (f64, f64) {
+        orders.iter().fold((0.0, 0.0), |(buy, sell), o| {
+            if o.is_buy {
+                (buy + o.size, sell)
+            } else {
+                (buy, sell + o.size)
+            }
+        })
+    }
+
+    #[test]
+    fn test_deterministic_under_seed() {
+        let signer = Signer::new(Keypair::generate());
+        let items = ladder().into_iter().map(Into::into).collect();
+        let txs = signer.sign_all(items, Some(1)).unwrap();
+
+        let (mut first, mut second) = (txs.clone(), txs.clone());
+        shuffle_batch(&mut first, 42);
+        shuffle_batch(&mut second, 42);
+        let signatures = |txs: &[SignedTransaction]| -> Vec<String> {
+            txs.iter().map(|tx| tx.signature.clone()).collect()
+        };
+        assert_eq!(signatures(&first), signatures(&second));
+        assert_ne!(signatures(&first), signatures(&txs));
+        let mut nonces: Vec<u64> = first.iter().map(|tx| tx.nonce).collect();
+        nonces.sort_unstable();
+        assert_eq!(nonces, (1..=5).collect::<Vec<_>>());
+
+        let (mut a, mut b) = (ladder(), ladder());
+        jitter_sizes(&mut a, 0.2, 7).unwrap();
+        jitter_sizes(&mut b, 0.2, 7).unwrap();
+        assert_eq!(a, b);
+        assert_ne!(a, ladder());
+    }
+
+    #[test]
+    fn test_jitter_preserves_side_totals() {
+        let before = side_totals(&ladder());
+        for seed in 0..50 {
+            let mut orders = ladder();
+            jitter_sizes(&mut orders, 0.3, seed).unwrap();
+            let after = side_totals(&orders);
+            assert!((after.0 - before.0).abs() < 1e-9);
+            assert!((after.1 - before.1).abs() < 1e-9);
+            assert!(orders.iter().all(|o| o.size > 0.0));
+            // The unpaired fifth order is left alone.
+            assert_eq!(orders[4].size, 1.0);
+        }
+        assert!(jitter_sizes(&mut ladder(), 1.0, 0).is_err());
+    }
+
+    #[test]
+    fn test_jitter_respects_lot_sizes() {
+        let spec = SymbolSpec::new(2, 2).with_lot_units(25);
+        let specs = HashMap::from([("BTC-USD".to_string(), spec)]);
+        let lots = |orders: &[Order], is_buy: bool| -> i64 {
+            orders
+                .iter()
+                .filter(|o| o.is_buy == is_buy)
+                .map(|o| spec.lots_from_size(o.size).unwrap())
+                .sum()
+        };
+        let before = ladder();
+        let mut moved = false;
+        for seed in 0..50 {
+            let mut orders = ladder();
+            jitter_sizes_with_specs(&mut orders, 0.5, seed, &specs).unwrap();
+            assert_eq!(lots(&orders, true), lots(&before, true));
+            assert_eq!(lots(&orders, false), lots(&before, false));
+            moved |= orders != before;
+        }
+        assert!(moved);
+
+        let mut off_grid = ladder();
+        off_grid[0].size = 0.3;
+        assert!(jitter_sizes_with_specs(&mut off_grid, 0.5, 0, &specs).is_err());
+    }
+}
@@ -0,0 +1,288 @@
+//! Per-symbol open-order caps enforced at signing time
+//!
+//! The tracker counts resting orders (GTC and ALO limits) the signer has
+//! signed, minus those it has signed cancels for. Orders closed any other way
+//! (fills, expiries, server-side cancels) must be reported through
+//! [`OpenOrderTracker::notify_closed`], or they keep counting against the cap.
+//!
+//! Market and IOC orders never rest, and conditional orders are not counted.
+
+use crate::order_id::compute_order_item_id_at_index;
+use crate::types::{Hash, OrderItem, OrderType, Pubkey, TimeInForce};
+use crate::{Error, Result};
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+/// Open-order bookkeeping behind [`crate::Signer::with_max_open_orders_per_symbol`].
+#[derive(Debug)]
+pub struct OpenOrderTracker {
+    cap: usize,
+    open: Mutex<Open>,
+}
+
+/// Open orders by ID, with a running count per symbol.
+#[derive(Debug, Clone, Default)]
+struct Open {
+    orders: HashMap<Hash, String>,
+    per_symbol: HashMap<String, usize>,
+}
+
+impl Open {
+    fn count(&self, symbol: &str) -> usize {
+        self.per_symbol.get(symbol).copied().unwrap_or(0)
+    }
+
+    fn insert(&mut self, id: Hash, symbol: String) -> Option<String> {
+        *self.per_symbol.entry(symbol.clone()).or_insert(0) += 1;
+        let previous = self.orders.insert(id, symbol);
+        if let Some(symbol) = &previous {
+            self.uncount(symbol);
+        }
+        previous
+    }
+
+    fn remove(&mut self, id: &Hash) -> Option<String> {
+        let previous = self.orders.remove(id);
+        if let Some(symbol) = &previous {
+            self.uncount(symbol);
+        }
+        previous
+    }
+
+    fn uncount(&mut self, symbol: &str) {
+        if let Some(count) = self.per_symbol.get_mut(symbol) {
+            *count -= 1;
+            if *count == 0 {
+                self.per_symbol.remove(symbol);
+            }
+        }
+    }
+}
+
+/// What one [`OpenOrderTracker::apply`] changed: each touched order ID with
+/// the symbol it had before, oldest first.
+#[derive(Debug, Default)]
+pub(crate) struct Undo(Vec<(Hash, Option<String>)>);
+
+impl OpenOrderTracker {
+    pub fn new(cap: usize) -> Self {
+        Self {
+            cap,
+            open: Mutex::new(Open::default()),
+        }
+    }
+
+    pub fn cap(&self) -> usize {
+        self.cap
+    }
+
+    /// Orders currently counted as open on `symbol`.
+    pub fn open_count(&self, symbol: &str) -> usize {
+        self.lock().count(symbol)
+    }
+
+    /// Stop counting `order_id`; returns whether it was open.
+    pub fn notify_closed(&self, order_id: &Hash) -> bool {
+        self.lock().remove(order_id).is_some()
+    }
+
+    /// Stop counting every order; returns how many were open.
+    pub(crate) fn clear(&self) -> usize {
+        std::mem::take(&mut *self.lock()).orders.len()
+    }
+
+    /// A tracker starting from a copy of this one's state, for dry runs.
+    pub(crate) fn fork(&self) -> Self {
+        Self {
+            cap: self.cap,
+            open: Mutex::new(self.lock().clone()),
+        }
+    }
+
+    /// Apply one transaction's items: cancels first, then new resting orders.
+    /// Nothing changes if any symbol would exceed the cap; otherwise the
+    /// returned [`Undo`] takes the transaction back out.
+    pub(crate) fn apply(&self, items: &[OrderItem], nonce: u64, account: &Pubkey) -> Result<Undo> {
+        let mut open = self.lock();
+        let mut undo = Undo::default();
+        if let Err(e) = Self::apply_to(&mut open, &mut undo, self.cap, items, nonce, account) {
+            Self::revert(&mut open, undo);
+            return Err(e);
+        }
+        Ok(undo)
+    }
+
+    fn apply_to(
+        open: &mut Open,
+        undo: &mut Undo,
+        cap: usize,
+        items: &[OrderItem],
+        nonce: u64,
+        account: &Pubkey,
+    ) -> Result<()> {
+        for item in items {
+            let cancelled: Vec<Hash> = match item {
+                OrderItem::Cancel(cancel) => vec![cancel.order_id],
+                OrderItem::CancelAll(cxa) if cxa.symbols.is_empty() => {
+                    open.orders.keys().copied().collect()
+                }
+                OrderItem::CancelAll(cxa) => open
+                    .orders
+                    .iter()
+                    .filter(|(_, s)| cxa.symbols.contains(s))
+                    .map(|(id, _)| *id)
+                    .collect(),
+                _ => continue,
+            };
+            for id in cancelled {
+                if let Some(symbol) = open.remove(&id) {
+                    undo.0.push((id, Some(symbol)));
+                }
+            }
+        }
+
+        for (index, item) in items.iter().enumerate() {
+            let OrderItem::Order(order) = item else {
+                continue;
+            };
+            if !matches!(
+                order.order_type,
+                OrderType::Limit {
+                    tif: TimeInForce::Gtc | TimeInForce::Alo
+                }
+            ) {
+                continue;
+            }
+            let symbol = order.symbol.as_str();
+            let count = open.count(symbol);
+            if count >= cap {
+                return Err(Error::OpenOrderCapExceeded {
+                    symbol: symbol.to_string(),
+                    open: count,
+                    cap,
+                });
+            }
+            if let Some(id) = compute_order_item_id_at_index(item, index as u32, nonce, account) {
+                let previous = open.insert(id, symbol.to_string());
+                undo.0.push((id, previous));
+            }
+        }
+        Ok(())
+    }
+
+    /// Take back what an [`OpenOrderTracker::apply`] did.
+    pub(crate) fn undo(&self, undo: Undo) {
+        Self::revert(&mut self.lock(), undo);
+    }
+
+    fn revert(open: &mut Open, undo: Undo) {
+        for (id, previous) in undo.0.into_iter().rev() {
+            match previous {
+                Some(symbol) => {
+                    open.insert(id, symbol);
+                }
+                None => {
+                    open.remove(&id);
+                }
+            }
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Open> {
+        self.open.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Cancel, CancelAll, Error, Hash, Keypair, Order, OrderItem, Signer, TimeInForce};
+
+    fn limit(symbol: &str) -> OrderItem {
+        Order::limit(symbol, true, 100.0, 1.0, TimeInForce::Gtc).into()
+    }
+
+    fn oid(tx: &crate::SignedTransaction) -> Hash {
+        Hash::from_base58(tx.order_id.as_ref().unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_cap_boundary_and_notify_closed() {
+        let mut signer = Signer::new(Keypair::generate()).with_max_open_orders_per_symbol(2);
+        let first = signer.sign(limit("BTC-USD"), Some(1)).unwrap();
+        signer.sign(limit("BTC-USD"), Some(2)).unwrap();
+        // IOC and other symbols do not count.
+        let ioc = Order::limit("BTC-USD", true, 100.0, 1.0, TimeInForce::Ioc);
+        signer.sign(ioc.into(), Some(3)).unwrap();
+        signer.sign(limit("ETH-USD"), Some(4)).unwrap();
+
+        let err = signer.sign(limit("BTC-USD"), Some(5)).unwrap_err();
+        assert!(matches!(
+            err,
+            Error::OpenOrderCapExceeded { ref symbol, open: 2, cap: 2 } if symbol == "BTC-USD"
+        ));
+
+        assert!(signer.notify_closed(&oid(&first)));
+        assert!(!signer.notify_closed(&oid(&first)));
+        assert_eq!(signer.open_order_count("BTC-USD"), 1);
+        signer.sign(limit("BTC-USD"), Some(6)).unwrap();
+    }
+
+    #[test]
+    fn test_cancels_and_cancel_all_free_slots() {
+        let mut signer = Signer::new(Keypair::generate()).with_max_open_orders_per_symbol(1);
+        let btc = signer.sign(limit("BTC-USD"), Some(1)).unwrap();
+        signer.sign(limit("ETH-USD"), Some(2)).unwrap();
+
+        // Cancel-replace in one transaction stays within the cap.
+        let replace = vec![Cancel::new("BTC-USD", oid(&btc)).into(), limit("BTC-USD")];
+        signer.sign_group(replace, Some(3)).unwrap();
+        assert_eq!(signer.open_order_count("BTC-USD"), 1);
+
+        let cxa = CancelAll::for_symbols(vec!["BTC-USD".to_string()]);
+        signer.sign(cxa.into(), Some(4)).unwrap();
+        assert_eq!(signer.open_order_count("BTC-USD"), 0);
+        assert_eq!(signer.open_order_count("ETH-USD"), 1);
+        signer.sign(limit("BTC-USD"), Some(5)).unwrap();
+    }
+
+    #[test]
+    fn test_group_legs_counted_individually() {
+        let mut signer = Signer::new(Keypair::generate()).with_max_open_orders_per_symbol(2);
+        let legs = vec![limit("BTC-USD"), limit("BTC-USD"), limit("BTC-USD")];
+        let err = signer.sign_group(legs, Some(1)).unwrap_err();
+        assert!(matches!(err, Error::OpenOrderCapExceeded { open: 2, .. }));
+        // A rejected group leaves no partial count behind.
+        assert_eq!(signer.open_order_count("BTC-USD"), 0);
+
+        signer
+            .sign_group(vec![limit("BTC-USD"), limit("BTC-USD")], Some(2))
+            .unwrap();
+        assert_eq!(signer.open_order_count("BTC-USD"), 2);
+    }
+
+    #[test]
+    fn test_refused_batch_keeps_no_open_orders() {
+        let mut signer = Signer::new(Keypair::generate())
+            .with_max_open_orders_per_symbol(1)
+            .with_parallelism(crate::Parallelism::Threads(4));
+        // Large enough for the parallel path; tracked batches go in order.
+        let batch = (0..12).map(|_| limit("BTC-USD")).collect();
+        let err = signer.sign_all(batch, Some(1)).unwrap_err();
+        assert!(matches!(
+            err,
+            Error::OpenOrderCapExceeded {
+                open: 1,
+                cap: 1,
+                ..
+            }
+        ));
+        assert_eq!(
+            signer.usage().last_batch_mode,
+            Some(crate::BatchMode::Serial)
+        );
+
+        // The first order, signed before the refusal, was released with it.
+        assert_eq!(signer.open_order_count("BTC-USD"), 0);
+        signer.sign(limit("BTC-USD"), Some(13)).unwrap();
+    }
+}
@@ -12,13 +12,161 @@
 
 use crate::sdk_compat::compute_order_item_id_with_seqno;
 use crate::types::*;
+use crate::{Error, Result};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+/// Versioned order ID derivation scheme.
+///
+/// Every precomputed ID is derived through [`Hash::from_wincode_bytes_v`], so a
+/// server-side change only needs a new variant here.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum IdScheme {
+    /// SHA256 over `[seqno] + [bincode(action)] + [account] + [nonce]`.
+    #[default]
+    #[serde(rename = "v1-sha256-wincode")]
+    V1Sha256Wincode,
+}
+
+impl IdScheme {
+    /// Scheme used for all IDs computed by this crate.
+    pub const CURRENT: IdScheme = IdScheme::V1Sha256Wincode;
+
+    /// Wire identifier, as reported by the server.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            IdScheme::V1Sha256Wincode => "v1-sha256-wincode",
+        }
+    }
+}
+
+impl std::fmt::Display for IdScheme {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl std::str::FromStr for IdScheme {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "v1-sha256-wincode" => Ok(IdScheme::V1Sha256Wincode),
+            other => Err(Error::IdSchemeMismatch {
+                local: IdScheme::CURRENT.to_string(),
+                server: other.to_string(),
+            }),
+        }
+    }
+}
+
+/// Incremental order ID hashing, for preimages fed in pieces.
+///
+/// Hashing the concatenation of every `update` gives the same ID as
+/// [`Hash::from_wincode_bytes_v`] over the whole buffer, so custom encoders
+/// can write straight into the hasher (it is also an [`std::io::Write`])
+/// instead of materializing the preimage first. The crate's own order ID
+/// computation streams this way.
+///
+/// ```rust
+/// use bulk_keychain::{Hash, OrderIdHasher};
+///
+/// let mut hasher = OrderIdHasher::new();
+/// hasher.update(&0u32.to_le_bytes());
+/// hasher.update(b"encoded order");
+/// assert_eq!(hasher.finalize(), Hash::from_wincode_bytes(b"\0\0\0\0encoded order"));
+/// ```
+#[derive(Debug, Clone)]
+pub struct OrderIdHasher {
+    inner: Sha256,
+}
+
+impl OrderIdHasher {
+    /// A hasher for [`IdScheme::CURRENT`].
+    pub fn new() -> Self {
+        Self::with_scheme(IdScheme::CURRENT)
+    }
+
+    pub fn with_scheme(scheme: IdScheme) -> Self {
+        match scheme {
+            IdScheme::V1Sha256Wincode => Self {
+                inner: Sha256::new(),
+            },
+        }
+    }
+
+    #[inline]
+    pub fn update(&mut self, bytes: &[u8]) {
+        self.inner.update(bytes);
+    }
+
+    #[inline]
+    pub fn finalize(self) -> Hash {
+        Hash::from_bytes_fixed(self.inner.finalize().into())
+    }
+}
+
+impl Default for OrderIdHasher {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl std::io::Write for OrderIdHasher {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.update(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Check the server-reported ID scheme against the one this crate computes.
+///
+/// Run this against the exchange's capability response to fail fast instead of
+/// tracking orders under stale IDs.
+pub fn verify_id_scheme(server_reported: &str) -> Result<()> {
+    let server: IdScheme = server_reported.parse()?;
+    if server != IdScheme::CURRENT {
+        return Err(Error::IdSchemeMismatch {
+            local: IdScheme::CURRENT.to_string(),
+            server: server.to_string(),
+        });
+    }
+    Ok(())
+}
 
 /// Compute order ID for an order item, assuming `signer == owner`.
 ///
 /// Returns `Some(Hash)` only for `OrderItem::Order`, otherwise `None`.
 pub fn compute_order_item_id(item: &OrderItem, nonce: u64, owner: &Pubkey) -> Option<Hash> {
-    let mut scratch = Vec::with_capacity(96);
-    compute_order_item_id_at_index(item, 0, nonce, owner, &mut scratch)
+    compute_order_item_id_at_index(item, 0, nonce, owner)
+}
+
+/// Compute the ID of the item at `index` in a multi-order transaction, as
+/// reported in [`SignedTransaction::order_ids`](crate::SignedTransaction::order_ids).
+///
+/// ```rust
+/// use bulk_keychain::{compute_order_item_id_at, Keypair, Order, OrderItem, Signer};
+///
+/// let mut signer = Signer::new(Keypair::generate()).with_batch_order_ids();
+/// let legs: Vec<OrderItem> = vec![
+///     Order::market("BTC-USD", true, 0.1).into(),
+///     Order::market("ETH-USD", true, 1.0).into(),
+/// ];
+/// let tx = signer.sign_group(legs.clone(), Some(7)).unwrap();
+/// let second = compute_order_item_id_at(&legs[1], 1, 7, signer.account_pubkey()).unwrap();
+/// assert_eq!(tx.order_ids.unwrap()[1], second.to_base58());
+/// ```
+pub fn compute_order_item_id_at(
+    item: &OrderItem,
+    index: u32,
+    nonce: u64,
+    owner: &Pubkey,
+) -> Option<Hash> {
+    compute_order_item_id_at_index(item, index, nonce, owner)
 }
 
 #[inline]
@@ -27,9 +175,8 @@ pub(crate) fn compute_order_item_id_at_index(
     seqno: u32,
     nonce: u64,
     account: &Pubkey,
-    scratch: &mut Vec<u8>,
 ) -> Option<Hash> {
-    compute_order_item_id_with_seqno(item, seqno, nonce, account, scratch)
+    compute_order_item_id_with_seqno(item, seqno, nonce, account)
 }
 
 /// Compute order ID for a limit order.
@@ -105,8 +252,7 @@ fn compute_order_id_at_index(order: &Order, seqno: u32, nonce: u64, owner: &Pubk
     };
 
     let item = OrderItem::Order(normalized);
-    let mut scratch = Vec::with_capacity(96);
-    compute_order_item_id_with_seqno(&item, seqno, nonce, owner, &mut scratch)
+    compute_order_item_id_with_seqno(&item, seqno, nonce, owner)
         .unwrap_or_else(|| unreachable!("normalized order serialization should always succeed"))
 }
 
@@ -124,6 +270,54 @@ pub fn compute_order_id(order: &Order, nonce: u64, owner: &Pubkey) -> Hash {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_streaming_hash_matches_one_shot() {
+        let bytes: Vec<u8> = (0..1024u32).map(|i| (i * 31 % 251) as u8).collect();
+        let one_shot = Hash::from_wincode_bytes(&bytes);
+        for chunk in [1, 7, 64, 1000, 1024] {
+            let mut hasher = OrderIdHasher::new();
+            for piece in bytes.chunks(chunk) {
+                hasher.update(piece);
+            }
+            assert_eq!(hasher.finalize(), one_shot, "chunk size {chunk}");
+        }
+
+        let mut writer = OrderIdHasher::default();
+        std::io::Write::write_all(&mut writer, &bytes).unwrap();
+        assert_eq!(writer.finalize(), one_shot);
+        assert_eq!(
+            OrderIdHasher::new().finalize(),
+            Hash::from_wincode_bytes(b"")
+        );
+    }
+
+    #[test]
+    fn test_streamed_ids_match_buffered_preimages() {
+        use crate::sdk_compat::write_order_item_id_preimage;
+
+        let owner = Pubkey::from_bytes([9u8; 32]);
+        let legs: Vec<OrderItem> = (0..500)
+            .map(|i| {
+                Order::limit(
+                    "BTC-USD",
+                    i % 2 == 0,
+                    100.0 + i as f64,
+                    0.1,
+                    TimeInForce::Gtc,
+                )
+                .into()
+            })
+            .collect();
+        for (i, leg) in legs.iter().enumerate() {
+            let mut preimage = Vec::new();
+            write_order_item_id_preimage(leg, i as u32, 42, &owner, &mut preimage).unwrap();
+            assert_eq!(
+                compute_order_item_id_at(leg, i as u32, 42, &owner),
+                Some(Hash::from_wincode_bytes(&preimage))
+            );
+        }
+    }
+
     #[test]
     fn test_limit_order_id_deterministic() {
         let owner = Pubkey::from_bytes([1u8; 32]);
@@ -308,6 +502,31 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_id_scheme_v1_pinned() {
+        let bytes = b"wincode";
+        assert_eq!(
+            Hash::from_wincode_bytes_v(IdScheme::V1Sha256Wincode, bytes),
+            Hash::from_wincode_bytes(bytes)
+        );
+        assert_eq!(IdScheme::CURRENT, IdScheme::V1Sha256Wincode);
+        assert_eq!(
+            serde_json::to_string(&IdScheme::CURRENT).unwrap(),
+            "\"v1-sha256-wincode\""
+        );
+    }
+
+    #[test]
+    fn test_verify_id_scheme() {
+        assert!(verify_id_scheme("v1-sha256-wincode").is_ok());
+
+        let err = verify_id_scheme("v2-blake3-wincode").unwrap_err();
+        assert!(matches!(
+            err,
+            Error::IdSchemeMismatch { ref server, .. } if server == "v2-blake3-wincode"
+        ));
+    }
+
     #[test]
     fn test_compute_order_id_matches_same_owner_path() {
         let owner = Pubkey::from_bytes([1u8; 32]);
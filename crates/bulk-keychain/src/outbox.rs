@@ -0,0 +1,397 @@
+//! Crash-safe record of prepared messages awaiting an external signature
+//!
+//! In custody flows a message is prepared, sent to the custodian, and the
+//! signature comes back later. An [`Outbox`] keeps every message between
+//! [`Outbox::enqueue`] and [`Outbox::complete`] in an append-only JSONL log,
+//! synced to disk before each call returns, so after a crash
+//! [`Outbox::pending`] says exactly what is still outstanding.
+//!
+//! Each record is one line. A line torn by a crash mid-write can only be the
+//! last one; it is dropped on open, which undoes the call that was writing it.
+//! Opening also compacts the log to just the pending entries.
+
+use crate::prepare::{finalize_transaction, PreparedMessage};
+use crate::types::SignedTransaction;
+use crate::{Error, Result};
+use serde::{Deserialize, Serialize};
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "camelCase")]
+enum Record {
+    Enqueue {
+        key: String,
+        at_ms: u64,
+        prepared: Box<PreparedMessage>,
+    },
+    Complete {
+        key: String,
+    },
+    Expire {
+        key: String,
+    },
+}
+
+/// A prepared message still waiting for its signature.
+#[derive(Debug, Clone)]
+pub struct OutboxEntry {
+    /// See [`Outbox::key`]
+    pub key: String,
+    /// When it was enqueued (ms since the Unix epoch)
+    pub enqueued_at_ms: u64,
+    pub prepared: PreparedMessage,
+}
+
+struct State {
+    file: File,
+    entries: Vec<OutboxEntry>,
+}
+
+/// Append-only, fsynced log of prepared messages awaiting a signature.
+///
+/// The log file is locked while open, so one process owns it at a time.
+pub struct Outbox {
+    path: PathBuf,
+    state: Mutex<State>,
+}
+
+fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+impl Outbox {
+    /// Open (or create) the log at `path` and recover its pending entries.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let io_err = |e: std::io::Error| Error::Outbox(format!("{}: {}", path.display(), e));
+        let file = OpenOptions::new()
+            .read(true)
+            .append(true)
+            .create(true)
+            .open(&path)
+            .map_err(io_err)?;
+        file.try_lock()
+            .map_err(|_| Error::Outbox(format!("{} is open in another process", path.display())))?;
+        let entries = Self::replay(&path, &file)?;
+
+        // Rewrite as just the pending entries, then swap it in atomically.
+        // The rewritten file is locked before it replaces the old one, so the
+        // log is never unlocked while this handle owns it.
+        let tmp = path.with_extension("compact");
+        let mut compacted = File::create(&tmp).map_err(io_err)?;
+        compacted
+            .try_lock()
+            .map_err(|_| Error::Outbox(format!("{} is open in another process", tmp.display())))?;
+        for entry in &entries {
+            let record = Record::Enqueue {
+                key: entry.key.clone(),
+                at_ms: entry.enqueued_at_ms,
+                prepared: Box::new(entry.prepared.clone()),
+            };
+            writeln!(compacted, "{}", serde_json::to_string(&record)?).map_err(io_err)?;
+        }
+        compacted.sync_all().map_err(io_err)?;
+        std::fs::rename(&tmp, &path).map_err(io_err)?;
+        drop(file);
+
+        Ok(Self {
+            path,
+            state: Mutex::new(State {
+                file: compacted,
+                entries,
+            }),
+        })
+    }
+
+    fn replay(path: &Path, file: &File) -> Result<Vec<OutboxEntry>> {
+        let lines: Vec<String> = BufReader::new(file)
+            .lines()
+            .collect::<std::io::Result<_>>()
+            .map_err(|e| Error::Outbox(format!("{}: {}", path.display(), e)))?;
+        let mut entries: Vec<OutboxEntry> = Vec::new();
+        for (i, line) in lines.iter().enumerate() {
+            let record = match serde_json::from_str(line) {
+                Ok(record) => record,
+                // A torn final line is a write that never returned.
+                Err(_) if i + 1 == lines.len() => break,
+                Err(e) => {
+                    return Err(Error::Outbox(format!(
+                        "{} line {}: {}",
+                        path.display(),
+                        i + 1,
+                        e
+                    )))
+                }
+            };
+            match record {
+                Record::Enqueue {
+                    key,
+                    at_ms,
+                    prepared,
+                } => entries.push(OutboxEntry {
+                    key,
+                    enqueued_at_ms: at_ms,
+                    prepared: *prepared,
+                }),
+                Record::Complete { key } | Record::Expire { key } => {
+                    entries.retain(|e| e.key != key)
+                }
+            }
+        }
+        Ok(entries)
+    }
+
+    /// Path of the log file.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Key an entry is tracked under: its order ID, the first of its order
+    /// IDs, or else the base58 message.
+    pub fn key(prepared: &PreparedMessage) -> String {
+        prepared
+            .order_id
+            .clone()
+            .or_else(|| {
+                prepared
+                    .order_ids
+                    .as_ref()
+                    .and_then(|ids| ids.first().cloned())
+            })
+            .unwrap_or_else(|| prepared.message_base58())
+    }
+
+    fn append(&self, state: &mut State, record: &Record) -> Result<()> {
+        let io_err = |e: std::io::Error| Error::Outbox(format!("{}: {}", self.path.display(), e));
+        let mut line = serde_json::to_string(record)?;
+        line.push('\n');
+        state.file.write_all(line.as_bytes()).map_err(io_err)?;
+        state.file.sync_data().map_err(io_err)
+    }
+
+    fn lock(&self) -> Result<std::sync::MutexGuard<'_, State>> {
+        self.state
+            .lock()
+            .map_err(|_| Error::Outbox("outbox mutex poisoned".to_string()))
+    }
+
+    /// Record `prepared` as sent for signing; returns its key.
+    ///
+    /// Call before handing the message to the custodian.
+    pub fn enqueue(&self, prepared: PreparedMessage) -> Result<String> {
+        let key = Self::key(&prepared);
+        let mut state = self.lock()?;
+        if state.entries.iter().any(|e| e.key == key) {
+            return Err(Error::Outbox(format!("{} is already pending", key)));
+        }
+        let at_ms = now_ms();
+        self.append(
+            &mut state,
+            &Record::Enqueue {
+                key: key.clone(),
+                at_ms,
+                prepared: Box::new(prepared.clone()),
+            },
+        )?;
+        state.entries.push(OutboxEntry {
+            key: key.clone(),
+            enqueued_at_ms: at_ms,
+            prepared,
+        });
+        Ok(key)
+    }
+
+    /// Pending messages, oldest first.
+    pub fn pending(&self) -> Result<Vec<PreparedMessage>> {
+        Ok(self.entries()?.into_iter().map(|e| e.prepared).collect())
+    }
+
+    /// Pending entries with their keys and enqueue times, oldest first.
+    pub fn entries(&self) -> Result<Vec<OutboxEntry>> {
+        Ok(self.lock()?.entries.clone())
+    }
+
+    /// Finalize the entry under `key` with `signature` and remove it.
+    ///
+    /// The signature must verify; an unknown or already completed key is an
+    /// error, and nothing changes on error.
+    ///
+    /// ```rust
+    /// use bulk_keychain::outbox::Outbox;
+    /// use bulk_keychain::{prepare_message, Keypair, Order, TimeInForce};
+    ///
+    /// let path = std::env::temp_dir().join(format!("outbox-doc-{}.jsonl", std::process::id()));
+    /// let keypair = Keypair::generate();
+    /// let order = Order::limit("BTC-USD", true, 100000.0, 0.1, TimeInForce::Gtc);
+    /// let prepared = prepare_message(order.into(), &keypair.pubkey(), None, Some(1)).unwrap();
+    /// let signature = prepared.sign_with_keypair(&keypair).unwrap().signature;
+    ///
+    /// let outbox = Outbox::open(&path).unwrap();
+    /// let key = outbox.enqueue(prepared).unwrap();
+    /// drop(outbox); // crash
+    ///
+    /// let outbox = Outbox::open(&path).unwrap();
+    /// assert_eq!(outbox.pending().unwrap().len(), 1);
+    /// assert!(outbox.complete(&key, &signature).unwrap().verify().unwrap());
+    /// assert!(outbox.complete(&key, &signature).is_err());
+    /// # std::fs::remove_file(&path).unwrap();
+    /// ```
+    pub fn complete(&self, key: &str, signature: &str) -> Result<SignedTransaction> {
+        let mut state = self.lock()?;
+        let index = state
+            .entries
+            .iter()
+            .position(|e| e.key == key)
+            .ok_or_else(|| Error::Outbox(format!("{} is not pending", key)))?;
+        let tx = finalize_transaction(state.entries[index].prepared.clone(), signature)?;
+        if !tx.verify()? {
+            return Err(Error::Outbox(format!(
+                "signature for {} does not verify",
+                key
+            )));
+        }
+        self.append(
+            &mut state,
+            &Record::Complete {
+                key: key.to_string(),
+            },
+        )?;
+        state.entries.remove(index);
+        Ok(tx)
+    }
+
+    /// Drop entries enqueued more than `max_age_ms` ago; returns them.
+    pub fn expire_older_than(&self, max_age_ms: u64) -> Result<Vec<PreparedMessage>> {
+        self.expire_before(now_ms().saturating_sub(max_age_ms))
+    }
+
+    fn expire_before(&self, cutoff_ms: u64) -> Result<Vec<PreparedMessage>> {
+        let mut state = self.lock()?;
+        let expired: Vec<String> = state
+            .entries
+            .iter()
+            .filter(|e| e.enqueued_at_ms < cutoff_ms)
+            .map(|e| e.key.clone())
+            .collect();
+        let mut out = Vec::with_capacity(expired.len());
+        for key in expired {
+            self.append(&mut state, &Record::Expire { key: key.clone() })?;
+            let index = state
+                .entries
+                .iter()
+                .position(|e| e.key == key)
+                .expect("expired key is pending");
+            out.push(state.entries.remove(index).prepared);
+        }
+        Ok(out)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prepare::prepare_message;
+    use crate::types::{Order, TimeInForce};
+    use crate::Keypair;
+
+    fn temp_path(name: &str) -> PathBuf {
+        let path =
+            std::env::temp_dir().join(format!("bulk-outbox-{}-{}.jsonl", name, std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        path
+    }
+
+    fn prepared(keypair: &Keypair, nonce: u64) -> PreparedMessage {
+        let order = Order::limit("BTC-USD", true, 100000.0, 0.1, TimeInForce::Gtc);
+        prepare_message(order.into(), &keypair.pubkey(), None, Some(nonce)).unwrap()
+    }
+
+    #[test]
+    fn test_kill_and_recover() {
+        let path = temp_path("recover");
+        let keypair = Keypair::generate();
+        let (a, b, c) = (
+            prepared(&keypair, 1),
+            prepared(&keypair, 2),
+            prepared(&keypair, 3),
+        );
+        let sig_b = b.sign_with_keypair(&keypair).unwrap().signature;
+
+        let outbox = Outbox::open(&path).unwrap();
+        assert!(Outbox::open(&path).is_err());
+        outbox.enqueue(a.clone()).unwrap();
+        let key_b = outbox.enqueue(b).unwrap();
+        outbox.enqueue(c.clone()).unwrap();
+        outbox.complete(&key_b, &sig_b).unwrap();
+        drop(outbox);
+
+        // A crash mid-append leaves a torn last line.
+        let mut file = OpenOptions::new().append(true).open(&path).unwrap();
+        file.write_all(br#"{"op":"complete","key":"#).unwrap();
+        drop(file);
+
+        let outbox = Outbox::open(&path).unwrap();
+        let nonces: Vec<u64> = outbox.pending().unwrap().iter().map(|p| p.nonce).collect();
+        assert_eq!(nonces, [a.nonce, c.nonce]);
+        let sig_a = a.sign_with_keypair(&keypair).unwrap().signature;
+        outbox.complete(&Outbox::key(&a), &sig_a).unwrap();
+        drop(outbox);
+
+        // Reopening compacted the log down to the one pending entry.
+        let outbox = Outbox::open(&path).unwrap();
+        assert_eq!(outbox.pending().unwrap().len(), 1);
+        assert_eq!(std::fs::read_to_string(&path).unwrap().lines().count(), 1);
+        drop(outbox);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_double_complete_and_bad_signature_rejected() {
+        let path = temp_path("double");
+        let keypair = Keypair::generate();
+        let message = prepared(&keypair, 1);
+        let signature = message.sign_with_keypair(&keypair).unwrap().signature;
+        let wrong = prepared(&keypair, 2)
+            .sign_with_keypair(&keypair)
+            .unwrap()
+            .signature;
+
+        let outbox = Outbox::open(&path).unwrap();
+        let key = outbox.enqueue(message.clone()).unwrap();
+        assert!(outbox.enqueue(message).is_err());
+        assert!(outbox.complete(&key, &wrong).is_err());
+        assert_eq!(outbox.pending().unwrap().len(), 1);
+        assert!(outbox.complete(&key, &signature).is_ok());
+        let err = outbox.complete(&key, &signature).unwrap_err();
+        assert!(err.to_string().contains("not pending"));
+        drop(outbox);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_expiry_prunes_old_entries() {
+        let path = temp_path("expire");
+        let keypair = Keypair::generate();
+        let outbox = Outbox::open(&path).unwrap();
+        outbox.enqueue(prepared(&keypair, 1)).unwrap();
+        outbox.enqueue(prepared(&keypair, 2)).unwrap();
+        assert!(outbox.expire_older_than(60_000).unwrap().is_empty());
+
+        let expired = outbox.expire_before(now_ms() + 1).unwrap();
+        assert_eq!(expired.len(), 2);
+        assert!(outbox.pending().unwrap().is_empty());
+        drop(outbox);
+
+        let outbox = Outbox::open(&path).unwrap();
+        assert!(outbox.pending().unwrap().is_empty());
+        drop(outbox);
+        std::fs::remove_file(&path).unwrap();
+    }
+}
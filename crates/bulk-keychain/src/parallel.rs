@@ -0,0 +1,236 @@
+//! Serial or parallel batch signing
+//!
+//! Batch paths ([`Signer::sign_all`](crate::Signer::sign_all),
+//! [`prepare_all`](crate::prepare_all), [`SignerPool`](crate::SignerPool))
+//! sign on worker threads only when it can pay off: the batch is large
+//! enough, more than one core is available
+//! (`std::thread::available_parallelism`), and the worker threads could be
+//! spawned. Otherwise, including on single-vCPU containers, sandboxes that
+//! forbid spawning threads and `wasm32`, they sign serially on the calling
+//! thread. Output is identical either way; only the
+//! [`BatchMode`] reported by [`Signer::usage`](crate::Signer::usage)
+//! differs. Pin the choice with [`Signer::with_parallelism`](crate::Signer::with_parallelism).
+
+/// Smallest batch signed in parallel.
+#[cfg_attr(not(feature = "batch"), allow(dead_code))]
+pub(crate) const PARALLEL_THRESHOLD: usize = 10;
+
+/// How batch paths may use threads.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Parallelism {
+    /// One worker per available core, serial on a single core
+    #[default]
+    Auto,
+    /// Always sign on the calling thread
+    Serial,
+    /// This many workers; `0` and `1` mean serial
+    Threads(usize),
+}
+
+/// How a batch was actually signed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BatchMode {
+    Serial,
+    Parallel { threads: usize },
+}
+
+impl Parallelism {
+    /// Workers to use, before checking that they can be spawned.
+    #[cfg_attr(not(feature = "batch"), allow(dead_code))]
+    fn threads(self) -> usize {
+        match self {
+            Self::Auto => std::thread::available_parallelism().map_or(1, |n| n.get()),
+            Self::Serial => 1,
+            Self::Threads(n) => n,
+        }
+    }
+}
+
+/// `f(i, item)` for each item, in input order, on worker threads when
+/// `parallelism` and the batch size allow it and the workers can be spawned.
+///
+/// Batches shorter than `min_parallel` are always serial.
+pub(crate) fn run_batch<T, R>(
+    parallelism: Parallelism,
+    min_parallel: usize,
+    items: Vec<T>,
+    f: impl Fn(usize, T) -> R + Sync + Send,
+) -> (Vec<R>, BatchMode)
+where
+    T: Send,
+    R: Send,
+{
+    #[cfg(feature = "batch")]
+    if items.len() >= min_parallel {
+        let threads = parallelism.threads();
+        if let Some(pool) = pool::get(threads) {
+            use rayon::prelude::*;
+            let results = pool.install(|| {
+                items
+                    .into_par_iter()
+                    .enumerate()
+                    .map(|(i, item)| f(i, item))
+                    .collect()
+            });
+            return (results, BatchMode::Parallel { threads });
+        }
+    }
+    #[cfg(not(feature = "batch"))]
+    let _ = (parallelism, min_parallel);
+    let results = items
+        .into_iter()
+        .enumerate()
+        .map(|(i, item)| f(i, item))
+        .collect();
+    (results, BatchMode::Serial)
+}
+
+#[cfg(feature = "batch")]
+mod pool {
+    use rayon::{ThreadPool, ThreadPoolBuilder};
+    use std::collections::HashMap;
+    use std::sync::{Arc, Mutex, OnceLock};
+
+    #[cfg(test)]
+    thread_local! {
+        /// Make pool creation on this thread fail as if threads could not be spawned.
+        pub(crate) static FAIL_SPAWN: std::cell::Cell<bool> = const { std::cell::Cell::new(false) };
+    }
+
+    /// A pool of `threads` workers, built on first use and kept; `None` for
+    /// fewer than two workers or if they cannot be spawned. A failed build is
+    /// retried on the next batch.
+    pub(super) fn get(threads: usize) -> Option<Arc<ThreadPool>> {
+        if threads < 2 {
+            return None;
+        }
+        #[cfg(test)]
+        if FAIL_SPAWN.get() {
+            return build(threads, true);
+        }
+        static POOLS: OnceLock<Mutex<HashMap<usize, Arc<ThreadPool>>>> = OnceLock::new();
+        let mut pools = POOLS
+            .get_or_init(Default::default)
+            .lock()
+            .unwrap_or_else(|e| e.into_inner());
+        if let Some(pool) = pools.get(&threads) {
+            return Some(pool.clone());
+        }
+        let pool = build(threads, false)?;
+        pools.insert(threads, pool.clone());
+        Some(pool)
+    }
+
+    fn build(threads: usize, fail_spawn: bool) -> Option<Arc<ThreadPool>> {
+        ThreadPoolBuilder::new()
+            .num_threads(threads)
+            .thread_name(|i| format!("bulk-keychain-batch-{}", i))
+            .spawn_handler(move |thread| {
+                if fail_spawn {
+                    return Err(std::io::Error::other("thread spawning disabled"));
+                }
+                let mut builder = std::thread::Builder::new();
+                if let Some(name) = thread.name() {
+                    builder = builder.name(name.to_string());
+                }
+                builder.spawn(|| thread.run())?;
+                Ok(())
+            })
+            .build()
+            .ok()
+            .map(Arc::new)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Keypair, Order, OrderItem, SignedTransaction, Signer, TimeInForce};
+
+    fn json(txs: &[SignedTransaction]) -> String {
+        serde_json::to_string(txs).unwrap()
+    }
+
+    fn orders(n: usize) -> Vec<OrderItem> {
+        (0..n)
+            .map(|i| {
+                Order::limit(
+                    "BTC-USD",
+                    i % 2 == 0,
+                    100.0 + i as f64,
+                    1.0,
+                    TimeInForce::Gtc,
+                )
+            })
+            .map(Into::into)
+            .collect()
+    }
+
+    #[test]
+    fn test_mode_follows_config_and_outputs_match() {
+        let keypair = Keypair::generate();
+        let items = orders(PARALLEL_THRESHOLD * 2);
+        let sign = |parallelism| {
+            let signer = Signer::new(keypair.clone()).with_parallelism(parallelism);
+            let txs = signer.sign_all(items.clone(), Some(1)).unwrap();
+            (json(&txs), signer.usage().last_batch_mode)
+        };
+
+        let (serial, mode) = sign(Parallelism::Threads(1));
+        assert_eq!(mode, Some(BatchMode::Serial));
+        let (pinned, mode) = sign(Parallelism::Serial);
+        assert_eq!(mode, Some(BatchMode::Serial));
+        assert_eq!(pinned, serial);
+
+        #[cfg(feature = "batch")]
+        {
+            let (parallel, mode) = sign(Parallelism::Threads(3));
+            assert_eq!(mode, Some(BatchMode::Parallel { threads: 3 }));
+            assert_eq!(parallel, serial);
+        }
+
+        // Small batches never pay for threads.
+        let signer = Signer::new(keypair.clone()).with_parallelism(Parallelism::Threads(3));
+        signer.sign_all(orders(2), Some(1)).unwrap();
+        assert_eq!(signer.usage().last_batch_mode, Some(BatchMode::Serial));
+    }
+
+    #[cfg(feature = "batch")]
+    #[test]
+    fn test_parallel_output_is_byte_identical() {
+        let keypair = Keypair::generate();
+        let serial = Signer::new(keypair.clone()).with_parallelism(Parallelism::Serial);
+        let parallel = Signer::new(keypair).with_parallelism(Parallelism::Threads(4));
+        for n in [10, 100, 1_000] {
+            let expected = serial.sign_all(orders(n), Some(5)).unwrap();
+            let got = parallel.sign_all(orders(n), Some(5)).unwrap();
+            assert_eq!(json(&got), json(&expected), "{n} orders");
+            assert_eq!(
+                parallel.usage().last_batch_mode,
+                Some(BatchMode::Parallel { threads: 4 })
+            );
+        }
+    }
+
+    #[cfg(feature = "batch")]
+    #[test]
+    fn test_spawn_failure_degrades_to_serial() {
+        let keypair = Keypair::generate();
+        let items = orders(PARALLEL_THRESHOLD * 2);
+        let expected = Signer::new(keypair.clone())
+            .with_parallelism(Parallelism::Serial)
+            .sign_all(items.clone(), Some(7))
+            .unwrap();
+        let expected = json(&expected);
+
+        pool::FAIL_SPAWN.set(true);
+        let signer = Signer::new(keypair).with_parallelism(Parallelism::Threads(4));
+        let result = signer.sign_all(items.clone(), Some(7));
+        let prepared = crate::prepare_all(items, signer.account_pubkey(), None, Some(7));
+        pool::FAIL_SPAWN.set(false);
+
+        assert_eq!(json(&result.unwrap()), expected);
+        assert_eq!(signer.usage().last_batch_mode, Some(BatchMode::Serial));
+        assert_eq!(prepared.unwrap().len(), PARALLEL_THRESHOLD * 2);
+    }
+}
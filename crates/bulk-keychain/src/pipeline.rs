@@ -0,0 +1,543 @@
+//! Backpressure-aware signing pipeline (`pipeline` feature)
+//!
+//! [`SigningPipeline`] owns a [`Signer`] and a [`Transport`] and moves order
+//! intents through bounded queues on the current tokio runtime:
+//!
+//! - [`SigningPipeline::submit`] waits while the intake queue is full, so a
+//!   burst slows the producer down instead of growing memory.
+//! - Cancels go through a separate queue that is always served first.
+//! - Items that pile up are signed together with [`Signer::sign_all`].
+//! - At most `max_in_flight` transport calls run at once; each runs on the
+//!   blocking pool since [`Transport`] is synchronous.
+//!
+//! Nonces come from the signer ([`Signer::next_nonce`]) but never repeat
+//! within the pipeline, even when a batch of `n` items takes `n` of them.
+
+use crate::{Error, OrderItem, Result, SignedTransaction, Signer, Transport, TransportResponse};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::{mpsc, oneshot, Semaphore};
+use tokio::task::JoinHandle;
+
+/// Outcome delivered for one submitted item: the signed transaction and
+/// the transport's response, or why it was not sent.
+pub type PipelineResult = Result<(SignedTransaction, TransportResponse)>;
+
+/// Receives [`PipelineEvent`]s; must be cheap, it runs on the pipeline's tasks.
+pub type PipelineMetricsHook = dyn Fn(PipelineEvent) + Send + Sync;
+
+/// Measurement reported to [`PipelineConfig::metrics`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PipelineEvent {
+    /// Items still queued after the worker took its next batch
+    QueueDepth(usize),
+    /// Time from `submit` to the transport's response for one item
+    Latency(Duration),
+}
+
+/// What [`SigningPipeline::shutdown`] does with items still queued.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ShutdownMode {
+    /// Sign and submit everything already queued
+    #[default]
+    Drain,
+    /// Fail queued items with [`Error::PipelineClosed`] without signing them
+    Abort,
+}
+
+/// Sizing and behavior of a [`SigningPipeline`].
+#[derive(Clone)]
+pub struct PipelineConfig {
+    /// Capacity of each intake queue; `submit` waits when it is full
+    pub queue_depth: usize,
+    /// Most transport submissions running at once
+    pub max_in_flight: usize,
+    /// Extra attempts after a transport error, resending the same signed body
+    pub retry: u32,
+    /// Most queued items signed together as one batch
+    pub max_batch: usize,
+    pub shutdown: ShutdownMode,
+    pub metrics: Option<Arc<PipelineMetricsHook>>,
+}
+
+impl Default for PipelineConfig {
+    fn default() -> Self {
+        Self {
+            queue_depth: 1024,
+            max_in_flight: 16,
+            retry: 0,
+            max_batch: 64,
+            shutdown: ShutdownMode::Drain,
+            metrics: None,
+        }
+    }
+}
+
+impl std::fmt::Debug for PipelineConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PipelineConfig")
+            .field("queue_depth", &self.queue_depth)
+            .field("max_in_flight", &self.max_in_flight)
+            .field("retry", &self.retry)
+            .field("max_batch", &self.max_batch)
+            .field("shutdown", &self.shutdown)
+            .field("metrics", &self.metrics.is_some())
+            .finish()
+    }
+}
+
+struct Job {
+    item: OrderItem,
+    enqueued: Instant,
+    reply: oneshot::Sender<PipelineResult>,
+}
+
+/// Cancels jump ahead of everything else.
+fn is_urgent(item: &OrderItem) -> bool {
+    matches!(item, OrderItem::Cancel(_) | OrderItem::CancelAll(_))
+}
+
+/// Bounded sign-and-submit pipeline, see the [module docs](self).
+pub struct SigningPipeline {
+    urgent: mpsc::Sender<Job>,
+    normal: mpsc::Sender<Job>,
+    abort: Arc<AtomicBool>,
+    mode: ShutdownMode,
+    worker: JoinHandle<()>,
+}
+
+impl SigningPipeline {
+    /// Start the pipeline on the current tokio runtime.
+    ///
+    /// # Panics
+    ///
+    /// Outside a tokio runtime, or if any of `queue_depth`, `max_in_flight`
+    /// or `max_batch` is zero.
+    pub fn new<T>(signer: Signer, transport: T, config: PipelineConfig) -> Self
+    where
+        T: Transport + Send + Sync + 'static,
+    {
+        assert!(
+            config.queue_depth > 0 && config.max_in_flight > 0 && config.max_batch > 0,
+            "pipeline sizes must be non-zero"
+        );
+        let (urgent, urgent_rx) = mpsc::channel(config.queue_depth);
+        let (normal, normal_rx) = mpsc::channel(config.queue_depth);
+        let abort = Arc::new(AtomicBool::new(false));
+        let mode = config.shutdown;
+        let worker = Worker {
+            signer,
+            transport: Arc::new(transport),
+            in_flight: Arc::new(Semaphore::new(config.max_in_flight)),
+            abort: abort.clone(),
+            nonce_floor: 0,
+            config,
+        };
+        Self {
+            urgent,
+            normal,
+            abort,
+            mode,
+            worker: tokio::spawn(worker.run(urgent_rx, normal_rx)),
+        }
+    }
+
+    /// Queue `item`, waiting while its queue is full.
+    ///
+    /// The receiver resolves once the item has been submitted or has failed.
+    pub async fn submit(&self, item: OrderItem) -> Result<oneshot::Receiver<PipelineResult>> {
+        let (reply, rx) = oneshot::channel();
+        let queue = if is_urgent(&item) {
+            &self.urgent
+        } else {
+            &self.normal
+        };
+        let job = Job {
+            item,
+            enqueued: Instant::now(),
+            reply,
+        };
+        queue.send(job).await.map_err(|_| Error::PipelineClosed)?;
+        Ok(rx)
+    }
+
+    /// Items waiting in the intake queues.
+    pub fn queue_depth(&self) -> usize {
+        let used = |q: &mpsc::Sender<Job>| q.max_capacity() - q.capacity();
+        used(&self.urgent) + used(&self.normal)
+    }
+
+    /// Stop accepting items, handle the queued ones per
+    /// [`PipelineConfig::shutdown`], and wait for in-flight submissions.
+    pub async fn shutdown(self) {
+        if self.mode == ShutdownMode::Abort {
+            self.abort.store(true, Ordering::SeqCst);
+        }
+        drop(self.urgent);
+        drop(self.normal);
+        let _ = self.worker.await;
+    }
+}
+
+struct Worker<T> {
+    signer: Signer,
+    transport: Arc<T>,
+    in_flight: Arc<Semaphore>,
+    abort: Arc<AtomicBool>,
+    /// Lowest nonce not yet used by this pipeline
+    nonce_floor: u64,
+    config: PipelineConfig,
+}
+
+impl<T: Transport + Send + Sync + 'static> Worker<T> {
+    async fn run(mut self, mut urgent: mpsc::Receiver<Job>, mut normal: mpsc::Receiver<Job>) {
+        loop {
+            let first = tokio::select! {
+                biased;
+                Some(job) = urgent.recv() => job,
+                Some(job) = normal.recv() => job,
+                else => break,
+            };
+            let mut batch = vec![first];
+            while batch.len() < self.config.max_batch {
+                match urgent.try_recv().or_else(|_| normal.try_recv()) {
+                    Ok(job) => batch.push(job),
+                    Err(_) => break,
+                }
+            }
+            self.report(PipelineEvent::QueueDepth(urgent.len() + normal.len()));
+
+            if self.abort.load(Ordering::SeqCst) {
+                for job in batch {
+                    let _ = job.reply.send(Err(Error::PipelineClosed));
+                }
+                continue;
+            }
+            batch.sort_by_key(|job| !is_urgent(&job.item));
+            self.dispatch(batch).await;
+        }
+        // Wait for every in-flight submission to hand back its permit.
+        let _ = self
+            .in_flight
+            .acquire_many(self.config.max_in_flight as u32)
+            .await;
+    }
+
+    async fn dispatch(&mut self, batch: Vec<Job>) {
+        let (items, jobs): (Vec<_>, Vec<_>) = batch
+            .into_iter()
+            .map(|job| (job.item, (job.enqueued, job.reply)))
+            .unzip();
+
+        for (signed, (enqueued, reply)) in self.sign(items).into_iter().zip(jobs) {
+            let tx = match signed {
+                Ok(tx) => tx,
+                Err(e) => {
+                    let _ = reply.send(Err(e));
+                    continue;
+                }
+            };
+            let permit = self
+                .in_flight
+                .clone()
+                .acquire_owned()
+                .await
+                .expect("in-flight semaphore is never closed");
+            let transport = self.transport.clone();
+            let retry = self.config.retry;
+            let metrics = self.config.metrics.clone();
+            tokio::spawn(async move {
+                let result =
+                    tokio::task::spawn_blocking(move || submit_with_retry(&*transport, tx, retry))
+                        .await
+                        .unwrap_or_else(|e| Err(Error::Transport(e.to_string())));
+                if let Some(hook) = &metrics {
+                    hook(PipelineEvent::Latency(enqueued.elapsed()));
+                }
+                let _ = reply.send(result);
+                drop(permit);
+            });
+        }
+    }
+
+    /// Sign `items` under consecutive fresh nonces, one result per item.
+    ///
+    /// A batch that fails as a whole is re-signed item by item under the
+    /// same nonces, so each error reaches only the item that caused it.
+    fn sign(&mut self, items: Vec<OrderItem>) -> Vec<Result<SignedTransaction>> {
+        let n = items.len() as u64;
+        let base = match self.signer.next_nonce() {
+            Ok(nonce) => nonce.max(self.nonce_floor),
+            Err(e) => {
+                let reason = e.to_string();
+                return items
+                    .iter()
+                    .map(|_| Err(Error::SigningFailed(reason.clone())))
+                    .collect();
+            }
+        };
+        self.nonce_floor = base + n;
+
+        if items.len() > 1 {
+            if let Ok(txs) = self.signer.sign_all(items.clone(), Some(base)) {
+                return txs.into_iter().map(Ok).collect();
+            }
+        }
+        items
+            .into_iter()
+            .enumerate()
+            .map(|(i, item)| {
+                self.signer
+                    .sign_all(vec![item], Some(base + i as u64))
+                    .map(|mut txs| txs.remove(0))
+            })
+            .collect()
+    }
+
+    fn report(&self, event: PipelineEvent) {
+        if let Some(hook) = &self.config.metrics {
+            hook(event);
+        }
+    }
+}
+
+fn submit_with_retry<T: Transport + ?Sized>(
+    transport: &T,
+    tx: SignedTransaction,
+    retry: u32,
+) -> PipelineResult {
+    let body = tx.to_json()?;
+    let mut attempt = 0;
+    loop {
+        match transport.submit(&body) {
+            Ok(response) => return Ok((tx, response)),
+            Err(_) if attempt < retry => attempt += 1,
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Cancel, Hash, Keypair, MockTransport, Order, TimeInForce};
+    use std::sync::{Condvar, Mutex};
+
+    fn order(px: f64) -> OrderItem {
+        Order::limit("BTC-USD", true, px, 0.1, TimeInForce::Gtc).into()
+    }
+
+    /// Mock transport that blocks every submission until opened.
+    #[derive(Default)]
+    struct Gated {
+        mock: MockTransport,
+        open: Mutex<bool>,
+        cv: Condvar,
+    }
+
+    impl Gated {
+        fn open(&self) {
+            *self.open.lock().unwrap() = true;
+            self.cv.notify_all();
+        }
+    }
+
+    impl Transport for Gated {
+        fn submit(&self, body: &str) -> Result<TransportResponse> {
+            let mut open = self.open.lock().unwrap();
+            while !*open {
+                open = self.cv.wait(open).unwrap();
+            }
+            drop(open);
+            self.mock.submit(body)
+        }
+    }
+
+    fn serial(queue_depth: usize) -> PipelineConfig {
+        PipelineConfig {
+            queue_depth,
+            max_in_flight: 1,
+            max_batch: 1,
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_burst_beyond_queue_depth_applies_backpressure() {
+        let gated = Arc::new(Gated::default());
+        let signer = Signer::new(Keypair::generate());
+        let pipeline = SigningPipeline::new(signer, gated.clone(), serial(2));
+
+        let mut receivers = Vec::new();
+        let mut blocked = false;
+        for i in 0..10 {
+            let submit = pipeline.submit(order(100.0 + i as f64));
+            match tokio::time::timeout(Duration::from_millis(200), submit).await {
+                Ok(rx) => receivers.push(rx.unwrap()),
+                Err(_) => {
+                    blocked = true;
+                    break;
+                }
+            }
+        }
+        // Queue (2) + one waiting for a permit + one in flight.
+        assert!(blocked, "submit never waited");
+        assert!(receivers.len() <= 4, "accepted {}", receivers.len());
+
+        gated.open();
+        for rx in receivers {
+            let (_, response) = rx.await.unwrap().unwrap();
+            assert!(response.is_success());
+        }
+        pipeline.shutdown().await;
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_shutdown_drains_queue_and_reports_metrics() {
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let sink = events.clone();
+        let config = PipelineConfig {
+            queue_depth: 16,
+            max_in_flight: 4,
+            metrics: Some(Arc::new(move |e| sink.lock().unwrap().push(e))),
+            ..Default::default()
+        };
+        let transport = Arc::new(MockTransport::new());
+        let pipeline =
+            SigningPipeline::new(Signer::new(Keypair::generate()), transport.clone(), config);
+
+        let mut receivers = Vec::new();
+        for i in 0..10 {
+            receivers.push(pipeline.submit(order(100.0 + i as f64)).await.unwrap());
+        }
+        pipeline.shutdown().await;
+
+        let mut nonces = Vec::new();
+        for rx in receivers {
+            nonces.push(rx.await.unwrap().unwrap().0.nonce);
+        }
+        nonces.sort_unstable();
+        nonces.dedup();
+        assert_eq!(nonces.len(), 10);
+        assert_eq!(transport.requests().len(), 10);
+
+        let events = events.lock().unwrap();
+        let latencies = events
+            .iter()
+            .filter(|e| matches!(e, PipelineEvent::Latency(_)))
+            .count();
+        assert_eq!(latencies, 10);
+        assert!(events
+            .iter()
+            .any(|e| matches!(e, PipelineEvent::QueueDepth(_))));
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_abort_fails_queued_items() {
+        let gated = Arc::new(Gated::default());
+        let config = PipelineConfig {
+            shutdown: ShutdownMode::Abort,
+            ..serial(8)
+        };
+        let pipeline =
+            SigningPipeline::new(Signer::new(Keypair::generate()), gated.clone(), config);
+
+        let mut receivers = Vec::new();
+        for i in 0..5 {
+            receivers.push(pipeline.submit(order(100.0 + i as f64)).await.unwrap());
+        }
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        let shutdown = tokio::spawn(pipeline.shutdown());
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        gated.open();
+        shutdown.await.unwrap();
+
+        let results: Vec<_> = futures_results(receivers).await;
+        assert!(results[0].is_ok());
+        assert!(matches!(results[4], Err(Error::PipelineClosed)));
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_transport_failure_reaches_its_item() {
+        let transport = Arc::new(MockTransport::new());
+        transport
+            .push_response(TransportResponse::ok("{}"))
+            .push_error("connection reset")
+            .push_response(TransportResponse::ok("{}"));
+        let pipeline = SigningPipeline::new(
+            Signer::new(Keypair::generate()),
+            transport.clone(),
+            serial(8),
+        );
+
+        let mut receivers = Vec::new();
+        for i in 0..3 {
+            receivers.push(pipeline.submit(order(100.0 + i as f64)).await.unwrap());
+        }
+        let results = futures_results(receivers).await;
+        assert!(results[0].is_ok());
+        assert!(matches!(results[1], Err(Error::Transport(ref m)) if m == "connection reset"));
+        assert!(results[2].is_ok());
+        pipeline.shutdown().await;
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_retry_resends_same_body() {
+        let transport = Arc::new(MockTransport::new());
+        transport
+            .push_error("timeout")
+            .push_response(TransportResponse::ok("{}"));
+        let config = PipelineConfig {
+            retry: 1,
+            ..serial(8)
+        };
+        let pipeline =
+            SigningPipeline::new(Signer::new(Keypair::generate()), transport.clone(), config);
+
+        let rx = pipeline.submit(order(100.0)).await.unwrap();
+        assert!(rx.await.unwrap().is_ok());
+        let requests = transport.requests();
+        assert_eq!(requests.len(), 2);
+        assert_eq!(requests[0], requests[1]);
+        pipeline.shutdown().await;
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_cancels_jump_the_queue() {
+        let gated = Arc::new(Gated::default());
+        let pipeline =
+            SigningPipeline::new(Signer::new(Keypair::generate()), gated.clone(), serial(8));
+
+        let mut receivers = Vec::new();
+        for i in 0..4 {
+            receivers.push(pipeline.submit(order(100.0 + i as f64)).await.unwrap());
+        }
+        // Let the worker pick up the first two orders: one in flight, one
+        // waiting for a permit.
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        let cancel = Cancel::new("BTC-USD", Hash::from_bytes([1; 32]));
+        receivers.push(pipeline.submit(cancel.into()).await.unwrap());
+        gated.open();
+        futures_results(receivers).await;
+
+        let requests = gated.mock.requests();
+        assert_eq!(requests.len(), 5);
+        assert!(
+            requests[2].contains("\"cx\""),
+            "cancel not third: {}",
+            requests[2]
+        );
+        pipeline.shutdown().await;
+    }
+
+    async fn futures_results(
+        receivers: Vec<oneshot::Receiver<PipelineResult>>,
+    ) -> Vec<PipelineResult> {
+        let mut results = Vec::new();
+        for rx in receivers {
+            results.push(rx.await.unwrap());
+        }
+        results
+    }
+}
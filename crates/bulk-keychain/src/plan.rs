@@ -0,0 +1,223 @@
+//! Dry-run reports for batches, from [`Signer::plan_batch`](crate::Signer::plan_batch)
+//!
+//! Planning runs every check signing would (the pre-sign hook, open-order
+//! caps, client-ID uniqueness and encoding) against copies of the signer's
+//! state, in input order, and reports what would happen without signing,
+//! counting usage or taking nonces. The hook is user code and does run.
+
+use crate::flatten::SymbolSpecs;
+use crate::types::{OrderItem, OrderType};
+use crate::Error;
+use std::collections::BTreeMap;
+use std::ops::Range;
+
+/// What [`Signer::sign_all_partial`](crate::Signer::sign_all_partial) would
+/// do with a batch.
+#[derive(Debug)]
+pub struct BatchPlan {
+    /// `price * size` of the limit orders that would be signed, per symbol.
+    /// Market and conditional orders have no price to estimate with and are
+    /// left out.
+    pub per_symbol_notional: BTreeMap<String, f64>,
+    /// Items signing would refuse, by input index, in input order
+    pub rejections: Vec<(usize, Error)>,
+    /// Prices and sizes off their symbol's grid, with the values rounding to
+    /// the spec would give; only with [`Signer::plan_batch_with_specs`](crate::Signer::plan_batch_with_specs)
+    pub adjustments: Vec<(usize, Adjustment)>,
+    /// Nonces the batch would use, one per item, rejected items included
+    pub nonce_range: Range<u64>,
+    /// Transactions the batch would produce (one per item that passes)
+    pub estimated_groups: usize,
+}
+
+/// Which field an [`Adjustment`] rounds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AdjustedField {
+    Price,
+    Size,
+}
+
+/// A price or size rounded to the nearest tick or lot.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Adjustment {
+    pub field: AdjustedField,
+    pub from: f64,
+    pub to: f64,
+}
+
+/// Limit-order notional of `item`, if it has one.
+pub(crate) fn notional(item: &OrderItem) -> Option<(&str, f64)> {
+    match item {
+        OrderItem::Order(order) if matches!(order.order_type, OrderType::Limit { .. }) => {
+            Some((order.symbol.as_str(), order.price * order.size))
+        }
+        _ => None,
+    }
+}
+
+/// Roundings `specs` would apply to `item`'s price and size. Values that do
+/// not round to a positive number of ticks or lots are left for signing to
+/// judge.
+pub(crate) fn adjustments(item: &OrderItem, specs: &SymbolSpecs) -> Vec<Adjustment> {
+    let OrderItem::Order(order) = item else {
+        return Vec::new();
+    };
+    let Some(spec) = specs.spec(&order.symbol) else {
+        return Vec::new();
+    };
+    let round = |field, from: f64, step_units: u64, decimals: u32| {
+        let step = step_units as f64 / 10f64.powi(decimals as i32);
+        let steps = (from / step).round();
+        if !(steps >= 1.0 && steps <= i64::MAX as f64) {
+            return None;
+        }
+        let to = match field {
+            AdjustedField::Price => spec.price_from_ticks(steps as i64),
+            AdjustedField::Size => spec.size_from_lots(steps as i64),
+        }
+        .ok()?;
+        (to != from).then_some(Adjustment { field, from, to })
+    };
+
+    let mut out = Vec::new();
+    if matches!(order.order_type, OrderType::Limit { .. }) {
+        let price = round(
+            AdjustedField::Price,
+            order.price,
+            spec.tick_units,
+            spec.price_decimals,
+        );
+        out.extend(price);
+    }
+    out.extend(round(
+        AdjustedField::Size,
+        order.size,
+        spec.lot_units,
+        spec.size_decimals,
+    ));
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{CancelAll, Hash, Keypair, NonceManager, Order, Signer, SymbolSpec, TimeInForce};
+
+    fn limit(symbol: &str, price: f64, size: f64) -> OrderItem {
+        Order::limit(symbol, true, price, size, TimeInForce::Gtc).into()
+    }
+
+    #[test]
+    fn test_rejections_match_sign_all_partial() {
+        let signer = Signer::with_pre_sign_hook(Keypair::generate(), |item, _| match item {
+            OrderItem::Order(order) if order.size > 5.0 => {
+                Err(Error::InvalidOrder("size above desk limit".into()))
+            }
+            _ => Ok(()),
+        })
+        .with_max_open_orders_per_symbol(2)
+        .with_unique_client_ids();
+        let cid = Hash::from_bytes([1; 32]);
+        let with_cid = |price| {
+            Order::limit("ETH-USD", true, price, 1.0, TimeInForce::Gtc)
+                .with_client_id(cid)
+                .into()
+        };
+        let items: Vec<OrderItem> = vec![
+            limit("BTC-USD", 100.0, 1.0),
+            limit("BTC-USD", 101.0, 9.0), // hook
+            limit("BTC-USD", 102.0, 1.0),
+            limit("BTC-USD", 103.0, 1.0), // cap
+            with_cid(10.0),
+            with_cid(11.0), // client ID in use
+            CancelAll::for_symbol_older_than("BTC-USD", 1_000).into(), // encoding
+            Order::market("SOL-USD", true, 3.0).into(),
+        ];
+
+        let plan = signer.plan_batch(&items, Some(50)).unwrap();
+        assert_eq!(signer.open_order_count("BTC-USD"), 0);
+        assert_eq!(signer.live_client_ids(), 0);
+        assert_eq!(signer.usage().signed, 0);
+
+        let results = signer.sign_all_partial(items, Some(50)).unwrap();
+        let rejected: Vec<(usize, String)> = results
+            .iter()
+            .enumerate()
+            .filter_map(|(i, r)| r.as_ref().err().map(|e| (i, e.to_string())))
+            .collect();
+        let planned: Vec<(usize, String)> = plan
+            .rejections
+            .iter()
+            .map(|(i, e)| (*i, e.to_string()))
+            .collect();
+        assert_eq!(planned, rejected);
+        assert_eq!(
+            planned.iter().map(|(i, _)| *i).collect::<Vec<_>>(),
+            [1, 3, 5, 6]
+        );
+
+        assert_eq!(
+            plan.estimated_groups,
+            results.iter().filter(|r| r.is_ok()).count()
+        );
+        assert_eq!(plan.nonce_range, 50..58);
+        let nonces: Vec<u64> = results.iter().flatten().map(|tx| tx.nonce).collect();
+        assert_eq!(nonces, [50, 52, 54, 57]);
+        assert_eq!(plan.per_symbol_notional["BTC-USD"], 202.0);
+        assert_eq!(plan.per_symbol_notional["ETH-USD"], 10.0);
+        assert!(!plan.per_symbol_notional.contains_key("SOL-USD"));
+    }
+
+    #[test]
+    fn test_planning_takes_no_nonce() {
+        let signer = Signer::with_nonce_manager(Keypair::generate(), NonceManager::counter());
+        let first = Signer::with_nonce_manager(Keypair::generate(), NonceManager::counter())
+            .next_nonce()
+            .unwrap();
+        let items = vec![limit("BTC-USD", 100.0, 1.0); 3];
+        signer.plan_batch(&items, None).unwrap();
+        signer.plan_batch(&items, Some(7)).unwrap();
+        assert_eq!(signer.next_nonce().unwrap(), first);
+    }
+
+    #[test]
+    fn test_adjustments_from_specs() {
+        let signer = Signer::new(Keypair::generate());
+        let specs = SymbolSpecs::new().with_spec("BTC-USD", SymbolSpec::new(1, 2));
+        let items = vec![
+            limit("BTC-USD", 100.04, 0.5),
+            limit("BTC-USD", 100.5, 0.123),
+            limit("ETH-USD", 10.123, 0.5), // no spec
+            Order::market("BTC-USD", true, 0.5).into(),
+        ];
+        let plan = signer
+            .plan_batch_with_specs(&items, Some(1), &specs)
+            .unwrap();
+        assert_eq!(
+            plan.adjustments,
+            [
+                (
+                    0,
+                    Adjustment {
+                        field: AdjustedField::Price,
+                        from: 100.04,
+                        to: 100.0
+                    }
+                ),
+                (
+                    1,
+                    Adjustment {
+                        field: AdjustedField::Size,
+                        from: 0.123,
+                        to: 0.12
+                    }
+                ),
+            ]
+        );
+        assert!(signer
+            .plan_batch(&items, Some(1))
+            .unwrap()
+            .adjustments
+            .is_empty());
+    }
+}
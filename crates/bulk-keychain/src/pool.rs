@@ -0,0 +1,229 @@
+//! Signers for several accounts, used as one
+//!
+//! A [`SignerPool`] holds one [`Signer`] per account, typically a single agent
+//! key authorized for several main accounts. [`SignerPool::sign_mixed`] signs a
+//! batch whose items target different accounts, with a separate nonce sequence
+//! per account.
+
+use crate::parallel::{run_batch, Parallelism};
+use crate::types::{OrderItem, Pubkey, SignedTransaction};
+use crate::{Error, Keypair, Result, Signer};
+use std::collections::HashMap;
+
+/// One [`Signer`] per account.
+#[derive(Default)]
+pub struct SignerPool {
+    signers: HashMap<Pubkey, Signer>,
+}
+
+impl SignerPool {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Pool where `agent` signs for each of `accounts`.
+    pub fn for_agent(agent: Keypair, accounts: impl IntoIterator<Item = Pubkey>) -> Self {
+        let mut pool = Self::new();
+        for account in accounts {
+            pool.insert(
+                Signer::new(agent.clone_tracked("SignerPool::for_agent")).with_account(account),
+            );
+        }
+        pool
+    }
+
+    /// Add `signer` for its account, replacing any signer already there.
+    pub fn insert(&mut self, signer: Signer) -> Option<Signer> {
+        self.signers.insert(*signer.account_pubkey(), signer)
+    }
+
+    pub fn get(&self, account: &Pubkey) -> Option<&Signer> {
+        self.signers.get(account)
+    }
+
+    pub fn get_mut(&mut self, account: &Pubkey) -> Option<&mut Signer> {
+        self.signers.get_mut(account)
+    }
+
+    pub fn remove(&mut self, account: &Pubkey) -> Option<Signer> {
+        self.signers.remove(account)
+    }
+
+    pub fn accounts(&self) -> impl Iterator<Item = &Pubkey> {
+        self.signers.keys()
+    }
+
+    pub fn len(&self) -> usize {
+        self.signers.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.signers.is_empty()
+    }
+
+    /// Sign each `(account, item)` as its own transaction with that account's signer.
+    ///
+    /// Items of one account get nonces `base + 0, base + 1, ...` in input order,
+    /// where `base` comes from `base_nonces` or, failing that, the signer (as in
+    /// [`Signer::sign_all`]). Accounts are signed in parallel with the `batch`
+    /// feature, where that pays off (see [`crate::parallel`]). Results are in input order; an item for an account outside the
+    /// pool, or one a pre-sign hook rejects, fails alone with its error at its
+    /// index. The outer error is for nonces that cannot be assigned, in which
+    /// case nothing is signed.
+    ///
+    /// ```rust
+    /// use bulk_keychain::{Keypair, Order, SignerPool};
+    /// use std::collections::HashMap;
+    ///
+    /// let (a, b) = (Keypair::generate().pubkey(), Keypair::generate().pubkey());
+    /// let pool = SignerPool::for_agent(Keypair::generate(), [a, b]);
+    /// let order = || Order::market("BTC-USD", true, 0.1).into();
+    /// let bases = HashMap::from([(a, 10), (b, 20)]);
+    /// let results = pool.sign_mixed(vec![(a, order()), (b, order()), (a, order())], Some(bases)).unwrap();
+    /// let nonces: Vec<u64> = results.iter().map(|r| r.as_ref().unwrap().nonce).collect();
+    /// assert_eq!(nonces, [10, 20, 11]);
+    /// ```
+    pub fn sign_mixed(
+        &self,
+        batch: Vec<(Pubkey, OrderItem)>,
+        base_nonces: Option<HashMap<Pubkey, u64>>,
+    ) -> Result<Vec<Result<SignedTransaction>>> {
+        let base_nonces = base_nonces.unwrap_or_default();
+        let mut groups: HashMap<Pubkey, Vec<(usize, OrderItem)>> = HashMap::new();
+        for (index, (account, item)) in batch.into_iter().enumerate() {
+            groups.entry(account).or_default().push((index, item));
+        }
+
+        let mut results: Vec<Option<Result<SignedTransaction>>> = Vec::new();
+        let mut jobs = Vec::with_capacity(groups.len());
+        for (account, items) in groups {
+            results.extend(items.iter().map(|_| None));
+            let signer = self.signers.get(&account);
+            let base = match signer {
+                Some(signer) => {
+                    signer.base_nonce_or_now(base_nonces.get(&account).copied(), items.len())?
+                }
+                None => 0,
+            };
+            jobs.push(AccountJob {
+                account,
+                signer,
+                base,
+                items,
+            });
+        }
+
+        let (signed, _) = run_batch(Parallelism::Auto, 2, jobs, |_, job| job.sign());
+        for (index, result) in signed.into_iter().flatten() {
+            results[index] = Some(result);
+        }
+        Ok(results
+            .into_iter()
+            .map(|r| r.expect("every index is signed once"))
+            .collect())
+    }
+}
+
+/// One account's share of a [`SignerPool::sign_mixed`] batch.
+struct AccountJob<'a> {
+    account: Pubkey,
+    signer: Option<&'a Signer>,
+    base: u64,
+    /// Input index and item, in input order
+    items: Vec<(usize, OrderItem)>,
+}
+
+impl AccountJob<'_> {
+    fn sign(self) -> Vec<(usize, Result<SignedTransaction>)> {
+        let Self {
+            account,
+            signer,
+            base,
+            items,
+        } = self;
+        items
+            .into_iter()
+            .enumerate()
+            .map(|(i, (index, item))| {
+                let result = match signer {
+                    Some(signer) => signer.sign_single_item(item, base + i as u64, false),
+                    None => Err(Error::UnknownAccount(account.to_base58())),
+                };
+                (index, result)
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{Order, TimeInForce};
+    use crate::NonceManager;
+
+    fn order(price: f64) -> OrderItem {
+        Order::limit("BTC-USD", true, price, 0.1, TimeInForce::Gtc).into()
+    }
+
+    #[test]
+    fn test_interleaved_accounts_keep_nonces_and_order() {
+        let accounts: Vec<Pubkey> = (0..3).map(|_| Keypair::generate().pubkey()).collect();
+        let pool = SignerPool::for_agent(Keypair::generate(), accounts.clone());
+        let batch: Vec<_> = (0..30)
+            .map(|i| (accounts[i % 3], order(100000.0 + i as f64)))
+            .collect();
+        let bases = accounts
+            .iter()
+            .enumerate()
+            .map(|(i, a)| (*a, 1000 * i as u64))
+            .collect();
+
+        let results = pool.sign_mixed(batch, Some(bases)).unwrap();
+        assert_eq!(results.len(), 30);
+        for (i, result) in results.iter().enumerate() {
+            let tx = result.as_ref().unwrap();
+            assert_eq!(tx.account, accounts[i % 3].to_base58());
+            assert_eq!(tx.nonce, 1000 * (i % 3) as u64 + (i / 3) as u64);
+            assert_eq!(
+                tx.actions_json().unwrap()[0]["l"]["px"],
+                100000.0 + i as f64
+            );
+            assert!(tx.verify().unwrap());
+        }
+    }
+
+    #[test]
+    fn test_unknown_account_fails_alone() {
+        let known = Keypair::generate().pubkey();
+        let stranger = Keypair::generate().pubkey();
+        let pool = SignerPool::for_agent(Keypair::generate(), [known]);
+        let results = pool
+            .sign_mixed(
+                vec![
+                    (known, order(1.0)),
+                    (stranger, order(2.0)),
+                    (known, order(3.0)),
+                ],
+                None,
+            )
+            .unwrap();
+        assert!(results[0].is_ok() && results[2].is_ok());
+        assert!(matches!(&results[1], Err(Error::UnknownAccount(a)) if *a == stranger.to_base58()));
+        let nonces = (
+            results[0].as_ref().unwrap().nonce,
+            results[2].as_ref().unwrap().nonce,
+        );
+        assert_eq!(nonces.1, nonces.0 + 1);
+    }
+
+    #[test]
+    fn test_unassignable_nonce_signs_nothing() {
+        let account = Keypair::generate().pubkey();
+        let signer =
+            Signer::with_nonce_manager(Keypair::generate(), NonceManager::content_derived(0))
+                .with_account(account);
+        let mut pool = SignerPool::new();
+        pool.insert(signer);
+        assert!(pool.sign_mixed(vec![(account, order(1.0))], None).is_err());
+    }
+}
@@ -1,15 +1,16 @@
 //! Message preparation for external wallet signing.
 
-use crate::order_id::compute_order_item_id_at_index;
+use crate::compact::action_to_json;
+use crate::diff::check_replacement;
+use crate::hardware;
+use crate::lint::{GroupLintWarning, GroupOptions};
+use crate::order_id::{compute_order_item_id_at_index, IdScheme};
+use crate::parallel::{run_batch, Parallelism, PARALLEL_THRESHOLD};
 use crate::sdk_compat::serialize_for_sdk_signing;
 use crate::types::*;
-use crate::{Error, Result};
-use rayon::prelude::*;
+use crate::{Error, Keypair, Result};
+use ed25519_dalek::Signer as _;
 use serde::{Deserialize, Serialize};
-use serde_json::json;
-
-/// Threshold for switching to parallel preparation.
-const PARALLEL_THRESHOLD: usize = 10;
 
 /// Prepared message for external signing.
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -29,6 +30,25 @@ pub struct PreparedMessage {
     pub signer: String,
     /// Nonce.
     pub nonce: u64,
+    /// Scheme the pre-computed order IDs were derived with.
+    #[serde(default)]
+    pub id_scheme: IdScheme,
+    /// Intent tag for grouped transactions (unsigned metadata).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub group_kind: Option<GroupKind>,
+    /// Group-level lint findings (unsigned metadata); see [`crate::lint_group`].
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub group_warnings: Vec<GroupLintWarning>,
+}
+
+/// A prepared message tagged with the position of the input it was built from.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IndexedPreparedMessage {
+    /// Index of the source item in the batch input
+    pub index: usize,
+    /// The prepared message
+    #[serde(flatten)]
+    pub message: PreparedMessage,
 }
 
 impl PreparedMessage {
@@ -47,9 +67,149 @@ impl PreparedMessage {
     pub fn message_hex(&self) -> String {
         hex::encode(&self.message_bytes)
     }
+
+    /// Account the message acts on
+    pub fn account_pubkey(&self) -> Result<Pubkey> {
+        Pubkey::from_base58(&self.account)
+    }
+
+    /// Key expected to sign the message
+    pub fn signer_pubkey(&self) -> Result<Pubkey> {
+        Pubkey::from_base58(&self.signer)
+    }
+
+    /// Rebuild a prepared message from the fields it serializes to: the
+    /// message bytes and order IDs are derived again from `actions`, so a
+    /// copy that was edited or mangled in transit cannot carry stale ones.
+    /// Group metadata is not part of the message and starts empty.
+    ///
+    /// ```rust
+    /// use bulk_keychain::{prepare_message, Keypair, Order, PreparedMessage};
+    ///
+    /// let account = Keypair::generate().pubkey();
+    /// let prepared = prepare_message(Order::market("BTC-USD", true, 0.1).into(), &account, None, Some(1)).unwrap();
+    /// let rebuilt = PreparedMessage::from_parts(
+    ///     prepared.actions.clone(),
+    ///     &prepared.account,
+    ///     &prepared.signer,
+    ///     prepared.nonce,
+    /// )
+    /// .unwrap();
+    /// assert_eq!(rebuilt.message_bytes, prepared.message_bytes);
+    /// assert_eq!(rebuilt.order_id, prepared.order_id);
+    /// ```
+    pub fn from_parts(
+        actions: Vec<serde_json::Value>,
+        account: &str,
+        signer: &str,
+        nonce: u64,
+    ) -> Result<Self> {
+        let account = Pubkey::from_base58(account)?;
+        let signer = Pubkey::from_base58(signer)?;
+        let action = crate::compact::action_from_json_list(&actions)?;
+        prepare_action(&action, &account, Some(&signer), Some(nonce))
+    }
+
+    /// Message bytes behind a Ledger blind-signing header, see [`crate::hardware`].
+    pub fn encode_for_ledger(&self) -> Vec<u8> {
+        hardware::encode_for_ledger(&self.message_bytes)
+    }
+
+    /// Message bytes as a Trezor `SolanaSignTx` frame on the default
+    /// Solana path, see [`crate::hardware`].
+    pub fn encode_for_trezor(&self) -> Vec<u8> {
+        hardware::encode_for_trezor(&self.message_bytes, &hardware::SOLANA_DEFAULT_PATH)
+    }
+
+    /// Sign the message with `keypair` and finalize it.
+    ///
+    /// Fails with [`Error::SignerMismatch`] unless `keypair` is the prepared signer.
+    ///
+    /// ```rust
+    /// use bulk_keychain::{prepare_message, Error, Keypair, Order, TimeInForce};
+    ///
+    /// let keypair = Keypair::generate();
+    /// let prepared = prepare_message(Order::limit("BTC-USD", true, 100000.0, 0.1, TimeInForce::Gtc).into(), &keypair.pubkey(), None, Some(1)).unwrap();
+    /// let tx = prepared.sign_with_keypair(&keypair).unwrap();
+    /// assert!(tx.verify().unwrap());
+    ///
+    /// let err = prepared.sign_with_keypair(&Keypair::generate()).unwrap_err();
+    /// assert!(matches!(err, Error::SignerMismatch { .. }));
+    /// ```
+    pub fn sign_with_keypair(&self, keypair: &Keypair) -> Result<SignedTransaction> {
+        let pubkey = keypair.pubkey();
+        if pubkey != self.signer_pubkey()? {
+            return Err(Error::SignerMismatch {
+                expected: self.signer.clone(),
+                got: pubkey.to_base58(),
+            });
+        }
+        let signature = keypair.signing_key()?.sign(&self.message_bytes);
+        finalize_transaction_bytes(self.clone(), &signature.to_bytes())
+    }
+
+    /// [`finalize_transaction`], refusing a base58 `signature` that does not
+    /// verify over the message against the prepared signer, so a wallet
+    /// that signed the wrong bytes fails here rather than at the exchange.
+    ///
+    /// ```rust
+    /// use bulk_keychain::{prepare_message, Error, Keypair, Order, Signer};
+    ///
+    /// let wallet = Signer::new(Keypair::generate());
+    /// let prepared = prepare_message(Order::market("BTC-USD", true, 0.1).into(), wallet.account_pubkey(), None, Some(1)).unwrap();
+    /// let signature = wallet.sign_bytes(&prepared.message_bytes);
+    /// assert!(prepared.clone().finalize_verified(&signature).is_ok());
+    ///
+    /// let wrong = wallet.sign_bytes(b"other bytes");
+    /// let err = prepared.finalize_verified(&wrong).unwrap_err();
+    /// assert!(matches!(err, Error::SignatureDoesNotVerify { .. }));
+    /// ```
+    pub fn finalize_verified(self, signature: &str) -> Result<SignedTransaction> {
+        let bytes = bs58::decode(signature)
+            .into_vec()
+            .map_err(|e| Error::InvalidBase58(format!("signature: {}", e)))?;
+        let bytes: [u8; 64] = bytes
+            .as_slice()
+            .try_into()
+            .map_err(|_| Error::InvalidSignatureLength(bytes.len()))?;
+        if !crate::Signer::verify(&self.signer_pubkey()?, &self.message_bytes, &bytes) {
+            let message = match (&self.order_id, &self.order_ids) {
+                (Some(id), _) => id.clone(),
+                (None, Some(ids)) => ids.join(","),
+                (None, None) => format!("at nonce {}", self.nonce),
+            };
+            return Err(Error::SignatureDoesNotVerify {
+                signer: self.signer,
+                message,
+            });
+        }
+        finalize_transaction(self, signature)
+    }
 }
 
 /// Prepare a single order item transaction.
+///
+/// ```rust
+/// use bulk_keychain::{prepare_message, Keypair, Order, TimeInForce};
+///
+/// // An agent key signing for a separate trading account.
+/// let account = Keypair::generate();
+/// let agent = Keypair::generate();
+///
+/// // Arguments go account first, then the signer.
+/// let prepared =
+///     prepare_message(Order::limit("BTC-USD", true, 100000.0, 0.1, TimeInForce::Gtc).into(), &account.pubkey(), Some(&agent.pubkey()), Some(1)).unwrap();
+/// assert_eq!(prepared.account_pubkey().unwrap(), account.pubkey());
+/// assert_eq!(prepared.signer_pubkey().unwrap(), agent.pubkey());
+/// assert!(prepared.order_id.is_some());
+///
+/// let tx = prepared.sign_with_keypair(&agent).unwrap();
+/// assert_eq!(tx.account_pubkey().unwrap(), account.pubkey());
+/// assert_eq!(tx.signer_pubkey().unwrap(), agent.pubkey());
+/// assert!(tx.verify().unwrap());
+/// // The account key is not the prepared signer.
+/// assert!(prepared.sign_with_keypair(&account).is_err());
+/// ```
 pub fn prepare_message(
     item: OrderItem,
     account: &Pubkey,
@@ -61,20 +221,235 @@ pub fn prepare_message(
 }
 
 /// Prepare an atomic multi-item order transaction.
+///
+/// ```rust
+/// use bulk_keychain::{prepare_group, Keypair, Order, TimeInForce};
+///
+/// let keypair = Keypair::generate();
+/// let legs = vec![
+///     Order::limit("BTC-USD", true, 100000.0, 0.1, TimeInForce::Gtc).into(),
+///     Order::limit("ETH-USD", false, 3000.0, 1.0, TimeInForce::Gtc).into(),
+/// ];
+/// let prepared = prepare_group(legs, &keypair.pubkey(), None, Some(1)).unwrap();
+/// assert_eq!(prepared.order_ids.as_ref().map(Vec::len), Some(2));
+/// assert!(prepared.sign_with_keypair(&keypair).unwrap().verify().unwrap());
+/// assert!(prepare_group(vec![], &keypair.pubkey(), None, Some(1)).is_err());
+/// ```
 pub fn prepare_group(
     items: Vec<OrderItem>,
     account: &Pubkey,
     signer: Option<&Pubkey>,
     nonce: Option<u64>,
+) -> Result<PreparedMessage> {
+    prepare_group_with_options(items, &GroupOptions::default(), account, signer, nonce)
+}
+
+/// Prepare an atomic multi-item order transaction after checking the legs
+/// against each other with [`crate::lint_group`].
+///
+/// Findings land in `group_warnings`; with [`GroupOptions::strict`] a warning
+/// or worse fails with [`Error::GroupRejected`]. [`prepare_group`] is this with
+/// default options.
+///
+/// ```rust
+/// use bulk_keychain::{prepare_group_with_options, GroupOptions, Keypair, Order, TimeInForce};
+///
+/// let account = Keypair::generate().pubkey();
+/// let entry = Order::limit("BTC-USD", true, 100000.0, 0.1, TimeInForce::Gtc);
+/// let close = Order::limit("BTC-USD", false, 110000.0, 0.5, TimeInForce::Gtc).reduce_only();
+/// let legs = vec![entry.into(), close.into()];
+///
+/// let prepared =
+///     prepare_group_with_options(legs.clone(), &GroupOptions::new(), &account, None, Some(1)).unwrap();
+/// assert_eq!(prepared.group_warnings[0].warning.code.as_str(), "oversized_reduce_only");
+/// let strict = GroupOptions::new().strict();
+/// assert!(prepare_group_with_options(legs, &strict, &account, None, Some(1)).is_err());
+/// ```
+pub fn prepare_group_with_options(
+    items: Vec<OrderItem>,
+    options: &GroupOptions,
+    account: &Pubkey,
+    signer: Option<&Pubkey>,
+    nonce: Option<u64>,
 ) -> Result<PreparedMessage> {
     if items.is_empty() {
         return Err(Error::EmptyOrders);
     }
+    let warnings = options.check(&items)?;
     let action = Action::Order { orders: items };
-    prepare_action(&action, account, signer, nonce)
+    let mut prepared = prepare_action(&action, account, signer, nonce)?;
+    prepared.group_kind = options.kind.clone();
+    prepared.group_warnings = warnings;
+    Ok(prepared)
+}
+
+/// Prepare an atomic multi-item order transaction tagged with its intent.
+///
+/// The tag rides along as metadata; the message bytes match [`prepare_group`].
+///
+/// ```rust
+/// use bulk_keychain::{prepare_group, prepare_group_with, GroupKind, Keypair, Order, TimeInForce};
+///
+/// let account = Keypair::generate().pubkey();
+/// let legs = vec![Order::limit("BTC-USD", true, 100000.0, 0.1, TimeInForce::Gtc).into()];
+/// let tagged =
+///     prepare_group_with(legs.clone(), Some(GroupKind::Bracket), &account, None, Some(1)).unwrap();
+/// let plain = prepare_group(legs, &account, None, Some(1)).unwrap();
+/// assert_eq!(tagged.group_kind, Some(GroupKind::Bracket));
+/// assert_eq!(tagged.message_bytes, plain.message_bytes);
+/// ```
+pub fn prepare_group_with(
+    items: Vec<OrderItem>,
+    kind: Option<GroupKind>,
+    account: &Pubkey,
+    signer: Option<&Pubkey>,
+    nonce: Option<u64>,
+) -> Result<PreparedMessage> {
+    let options = GroupOptions {
+        kind,
+        ..GroupOptions::default()
+    };
+    prepare_group_with_options(items, &options, account, signer, nonce)
+}
+
+/// Prepare a single-order cancel transaction.
+///
+/// ```rust
+/// use bulk_keychain::{prepare_cancel, Hash, Keypair};
+///
+/// let keypair = Keypair::generate();
+/// let order_id = Hash::random();
+/// let prepared = prepare_cancel("BTC-USD", order_id, &keypair.pubkey(), None, Some(1)).unwrap();
+/// assert_eq!(prepared.actions[0]["cx"]["oid"], order_id.to_base58());
+/// assert!(prepared.sign_with_keypair(&keypair).unwrap().verify().unwrap());
+/// ```
+pub fn prepare_cancel(
+    symbol: &str,
+    order_id: Hash,
+    account: &Pubkey,
+    signer: Option<&Pubkey>,
+    nonce: Option<u64>,
+) -> Result<PreparedMessage> {
+    prepare_message(
+        OrderItem::Cancel(Cancel::new(symbol, order_id)),
+        account,
+        signer,
+        nonce,
+    )
+}
+
+/// Prepare a size amendment of a live order (see [`crate::Signer::sign_modify_order`]).
+///
+/// ```rust
+/// use bulk_keychain::{prepare_modify_order, Hash, Keypair, Modify};
+///
+/// let keypair = Keypair::generate();
+/// let modify = Modify::new(Hash::random(), "BTC-USD", 0.25);
+/// let prepared = prepare_modify_order(modify, &keypair.pubkey(), None, Some(1)).unwrap();
+/// assert_eq!(prepared.actions[0]["mod"]["sz"], 0.25);
+/// assert!(prepared.sign_with_keypair(&keypair).unwrap().verify().unwrap());
+/// ```
+pub fn prepare_modify_order(
+    modify: Modify,
+    account: &Pubkey,
+    signer: Option<&Pubkey>,
+    nonce: Option<u64>,
+) -> Result<PreparedMessage> {
+    prepare_message(OrderItem::Modify(modify), account, signer, nonce)
+}
+
+/// Prepare an atomic cancel + new order on the new order's market.
+///
+/// The replacement's order ID is the only entry in `order_ids`.
+///
+/// ```rust
+/// use bulk_keychain::{prepare_cancel_replace, Hash, Keypair, Order, TimeInForce};
+///
+/// let keypair = Keypair::generate();
+/// let prepared =
+///     prepare_cancel_replace(Hash::random(), Order::limit("BTC-USD", true, 100000.0, 0.1, TimeInForce::Gtc), &keypair.pubkey(), None, Some(1)).unwrap();
+/// assert_eq!(prepared.actions.len(), 2);
+/// assert_eq!(prepared.order_ids.as_ref().map(Vec::len), Some(1));
+/// assert!(prepared.sign_with_keypair(&keypair).unwrap().verify().unwrap());
+/// ```
+pub fn prepare_cancel_replace(
+    old_id: Hash,
+    new_order: Order,
+    account: &Pubkey,
+    signer: Option<&Pubkey>,
+    nonce: Option<u64>,
+) -> Result<PreparedMessage> {
+    let cancel = Cancel::new(new_order.symbol.clone(), old_id);
+    prepare_group(
+        vec![OrderItem::Cancel(cancel), OrderItem::Order(new_order)],
+        account,
+        signer,
+        nonce,
+    )
+}
+
+/// Like [`prepare_cancel_replace`], but refuses a replacement that moves to
+/// another market or flips side (unless `force_side_change`).
+///
+/// ```rust
+/// use bulk_keychain::prepare::prepare_checked_cancel_replace;
+/// use bulk_keychain::{Hash, Keypair, Order, TimeInForce};
+///
+/// let account = Keypair::generate().pubkey();
+/// let live = Order::limit("BTC-USD", true, 100000.0, 0.1, TimeInForce::Gtc);
+/// let flipped = Order::limit("BTC-USD", false, 100000.0, 0.1, TimeInForce::Gtc);
+/// let id = Hash::random();
+/// assert!(prepare_checked_cancel_replace(id, &live, flipped.clone(), false, &account, None, Some(1)).is_err());
+/// assert!(prepare_checked_cancel_replace(id, &live, flipped, true, &account, None, Some(1)).is_ok());
+/// ```
+#[allow(clippy::too_many_arguments)]
+pub fn prepare_checked_cancel_replace(
+    old_id: Hash,
+    original: &Order,
+    new_order: Order,
+    force_side_change: bool,
+    account: &Pubkey,
+    signer: Option<&Pubkey>,
+    nonce: Option<u64>,
+) -> Result<PreparedMessage> {
+    check_replacement(original, &new_order, force_side_change)?;
+    prepare_cancel_replace(old_id, new_order, account, signer, nonce)
+}
+
+/// Prepare a cancel-all transaction. Empty `symbols` cancels across all markets.
+///
+/// ```rust
+/// use bulk_keychain::{prepare_cancel_all, Keypair};
+///
+/// let keypair = Keypair::generate();
+/// let prepared = prepare_cancel_all(vec![], &keypair.pubkey(), None, Some(1)).unwrap();
+/// assert!(prepared.actions[0].get("cxa").is_some());
+/// assert!(prepared.sign_with_keypair(&keypair).unwrap().verify().unwrap());
+/// ```
+pub fn prepare_cancel_all(
+    symbols: Vec<String>,
+    account: &Pubkey,
+    signer: Option<&Pubkey>,
+    nonce: Option<u64>,
+) -> Result<PreparedMessage> {
+    prepare_message(
+        OrderItem::CancelAll(CancelAll::for_symbols(symbols)),
+        account,
+        signer,
+        nonce,
+    )
 }
 
 /// Prepare a faucet transaction.
+///
+/// ```rust
+/// use bulk_keychain::{prepare_faucet, Keypair};
+///
+/// let keypair = Keypair::generate();
+/// let prepared = prepare_faucet(&keypair.pubkey(), None, Some(1)).unwrap();
+/// assert_eq!(prepared.actions[0]["faucet"]["u"], keypair.pubkey().to_base58());
+/// assert!(prepared.sign_with_keypair(&keypair).unwrap().verify().unwrap());
+/// ```
 pub fn prepare_faucet(
     account: &Pubkey,
     signer: Option<&Pubkey>,
@@ -85,6 +460,17 @@ pub fn prepare_faucet(
 }
 
 /// Prepare an agent wallet creation/deletion transaction.
+///
+/// ```rust
+/// use bulk_keychain::{prepare_agent_wallet, Keypair};
+///
+/// // The account authorizes `agent`; the account key signs.
+/// let account = Keypair::generate();
+/// let agent = Keypair::generate().pubkey();
+/// let prepared = prepare_agent_wallet(&agent, false, &account.pubkey(), None, Some(1)).unwrap();
+/// assert_eq!(prepared.signer_pubkey().unwrap(), account.pubkey());
+/// assert!(prepared.sign_with_keypair(&account).unwrap().verify().unwrap());
+/// ```
 pub fn prepare_agent_wallet(
     agent: &Pubkey,
     delete: bool,
@@ -100,6 +486,15 @@ pub fn prepare_agent_wallet(
 }
 
 /// Prepare a user settings transaction.
+///
+/// ```rust
+/// use bulk_keychain::{prepare_user_settings, Keypair, UserSettings};
+///
+/// let keypair = Keypair::generate();
+/// let prepared = prepare_user_settings(UserSettings::set_leverage("BTC-USD", 5.0), &keypair.pubkey(), None, Some(1)).unwrap();
+/// assert_eq!(prepared.nonce, 1);
+/// assert!(prepared.sign_with_keypair(&keypair).unwrap().verify().unwrap());
+/// ```
 pub fn prepare_user_settings(
     settings: UserSettings,
     account: &Pubkey,
@@ -111,6 +506,15 @@ pub fn prepare_user_settings(
 }
 
 /// Prepare a sub-account creation transaction.
+///
+/// ```rust
+/// use bulk_keychain::{prepare_create_sub_account, CreateSubAccount, Keypair};
+///
+/// let keypair = Keypair::generate();
+/// let prepared = prepare_create_sub_account(CreateSubAccount::new("hedge"), &keypair.pubkey(), None, Some(1)).unwrap();
+/// assert_eq!(prepared.nonce, 1);
+/// assert!(prepared.sign_with_keypair(&keypair).unwrap().verify().unwrap());
+/// ```
 pub fn prepare_create_sub_account(
     sub_account: CreateSubAccount,
     account: &Pubkey,
@@ -122,6 +526,15 @@ pub fn prepare_create_sub_account(
 }
 
 /// Prepare a sub-account removal transaction.
+///
+/// ```rust
+/// use bulk_keychain::{prepare_remove_sub_account, Keypair};
+///
+/// let keypair = Keypair::generate();
+/// let prepared = prepare_remove_sub_account(Keypair::generate().pubkey(), &keypair.pubkey(), None, Some(1)).unwrap();
+/// assert_eq!(prepared.nonce, 1);
+/// assert!(prepared.sign_with_keypair(&keypair).unwrap().verify().unwrap());
+/// ```
 pub fn prepare_remove_sub_account(
     to_remove: Pubkey,
     account: &Pubkey,
@@ -133,6 +546,15 @@ pub fn prepare_remove_sub_account(
 }
 
 /// Prepare a sub-account rename transaction.
+///
+/// ```rust
+/// use bulk_keychain::{prepare_rename_sub_account, Keypair, RenameSubAccount};
+///
+/// let keypair = Keypair::generate();
+/// let prepared = prepare_rename_sub_account(RenameSubAccount::new(Keypair::generate().pubkey(), "mm"), &keypair.pubkey(), None, Some(1)).unwrap();
+/// assert_eq!(prepared.nonce, 1);
+/// assert!(prepared.sign_with_keypair(&keypair).unwrap().verify().unwrap());
+/// ```
 pub fn prepare_rename_sub_account(
     rename: RenameSubAccount,
     account: &Pubkey,
@@ -144,6 +566,15 @@ pub fn prepare_rename_sub_account(
 }
 
 /// Prepare a margin transfer transaction.
+///
+/// ```rust
+/// use bulk_keychain::{prepare_transfer, Keypair, Transfer};
+///
+/// let keypair = Keypair::generate();
+/// let prepared = prepare_transfer(Transfer::internal(keypair.pubkey(), Keypair::generate().pubkey(), "USDC", 25.0), &keypair.pubkey(), None, Some(1)).unwrap();
+/// assert_eq!(prepared.nonce, 1);
+/// assert!(prepared.sign_with_keypair(&keypair).unwrap().verify().unwrap());
+/// ```
 pub fn prepare_transfer(
     transfer: Transfer,
     account: &Pubkey,
@@ -155,6 +586,15 @@ pub fn prepare_transfer(
 }
 
 /// Prepare a multisig creation transaction.
+///
+/// ```rust
+/// use bulk_keychain::{prepare_create_multisig, CreateMultisig, Keypair};
+///
+/// let keypair = Keypair::generate();
+/// let prepared = prepare_create_multisig(CreateMultisig::new(vec![keypair.pubkey()], 1), &keypair.pubkey(), None, Some(1)).unwrap();
+/// assert_eq!(prepared.nonce, 1);
+/// assert!(prepared.sign_with_keypair(&keypair).unwrap().verify().unwrap());
+/// ```
 pub fn prepare_create_multisig(
     create_multisig: CreateMultisig,
     account: &Pubkey,
@@ -166,6 +606,16 @@ pub fn prepare_create_multisig(
 }
 
 /// Prepare a multisig proposal transaction.
+///
+/// ```rust
+/// use bulk_keychain::{prepare_multisig_propose, Keypair, MultisigPropose};
+///
+/// let keypair = Keypair::generate();
+/// let multisig = Keypair::generate().pubkey();
+/// let prepared = prepare_multisig_propose(MultisigPropose::new(multisig, vec![]), &keypair.pubkey(), None, Some(1)).unwrap();
+/// assert_eq!(prepared.nonce, 1);
+/// assert!(prepared.sign_with_keypair(&keypair).unwrap().verify().unwrap());
+/// ```
 pub fn prepare_multisig_propose(
     propose: MultisigPropose,
     account: &Pubkey,
@@ -177,6 +627,16 @@ pub fn prepare_multisig_propose(
 }
 
 /// Prepare a multisig approve transaction.
+///
+/// ```rust
+/// use bulk_keychain::{prepare_multisig_approve, Keypair, MultisigApprove};
+///
+/// let keypair = Keypair::generate();
+/// let multisig = Keypair::generate().pubkey();
+/// let prepared = prepare_multisig_approve(MultisigApprove::new(multisig, 1), &keypair.pubkey(), None, Some(1)).unwrap();
+/// assert_eq!(prepared.nonce, 1);
+/// assert!(prepared.sign_with_keypair(&keypair).unwrap().verify().unwrap());
+/// ```
 pub fn prepare_multisig_approve(
     approve: MultisigApprove,
     account: &Pubkey,
@@ -188,6 +648,16 @@ pub fn prepare_multisig_approve(
 }
 
 /// Prepare a multisig reject transaction.
+///
+/// ```rust
+/// use bulk_keychain::{prepare_multisig_reject, Keypair, MultisigReject};
+///
+/// let keypair = Keypair::generate();
+/// let multisig = Keypair::generate().pubkey();
+/// let prepared = prepare_multisig_reject(MultisigReject::new(multisig, 1), &keypair.pubkey(), None, Some(1)).unwrap();
+/// assert_eq!(prepared.nonce, 1);
+/// assert!(prepared.sign_with_keypair(&keypair).unwrap().verify().unwrap());
+/// ```
 pub fn prepare_multisig_reject(
     reject: MultisigReject,
     account: &Pubkey,
@@ -199,6 +669,16 @@ pub fn prepare_multisig_reject(
 }
 
 /// Prepare a multisig cancel transaction.
+///
+/// ```rust
+/// use bulk_keychain::{prepare_multisig_cancel, Keypair, MultisigCancel};
+///
+/// let keypair = Keypair::generate();
+/// let multisig = Keypair::generate().pubkey();
+/// let prepared = prepare_multisig_cancel(MultisigCancel::new(multisig, 1), &keypair.pubkey(), None, Some(1)).unwrap();
+/// assert_eq!(prepared.nonce, 1);
+/// assert!(prepared.sign_with_keypair(&keypair).unwrap().verify().unwrap());
+/// ```
 pub fn prepare_multisig_cancel(
     cancel: MultisigCancel,
     account: &Pubkey,
@@ -210,6 +690,16 @@ pub fn prepare_multisig_cancel(
 }
 
 /// Prepare a multisig execute transaction.
+///
+/// ```rust
+/// use bulk_keychain::{prepare_multisig_execute, Keypair, MultisigExecute};
+///
+/// let keypair = Keypair::generate();
+/// let multisig = Keypair::generate().pubkey();
+/// let prepared = prepare_multisig_execute(MultisigExecute::new(multisig, 1), &keypair.pubkey(), None, Some(1)).unwrap();
+/// assert_eq!(prepared.nonce, 1);
+/// assert!(prepared.sign_with_keypair(&keypair).unwrap().verify().unwrap());
+/// ```
 pub fn prepare_multisig_execute(
     execute: MultisigExecute,
     account: &Pubkey,
@@ -221,6 +711,16 @@ pub fn prepare_multisig_execute(
 }
 
 /// Prepare a multisig policy update transaction.
+///
+/// ```rust
+/// use bulk_keychain::{prepare_update_multisig_policy, Keypair, UpdateMultisigPolicy};
+///
+/// let keypair = Keypair::generate();
+/// let multisig = Keypair::generate().pubkey();
+/// let prepared = prepare_update_multisig_policy(UpdateMultisigPolicy::new(multisig, vec![keypair.pubkey()], 1), &keypair.pubkey(), None, Some(1)).unwrap();
+/// assert_eq!(prepared.nonce, 1);
+/// assert!(prepared.sign_with_keypair(&keypair).unwrap().verify().unwrap());
+/// ```
 pub fn prepare_update_multisig_policy(
     update: UpdateMultisigPolicy,
     account: &Pubkey,
@@ -232,6 +732,18 @@ pub fn prepare_update_multisig_policy(
 }
 
 /// Low-level action preparation.
+///
+/// ```rust
+/// use bulk_keychain::{prepare_action, Action, Keypair, Order};
+///
+/// let account = Keypair::generate();
+/// let action = Action::Order {
+///     orders: vec![Order::market("BTC-USD", true, 0.1).into()],
+/// };
+/// let prepared = prepare_action(&action, &account.pubkey(), None, Some(1)).unwrap();
+/// assert_eq!(prepared.signer, prepared.account);
+/// assert!(prepared.sign_with_keypair(&account).unwrap().verify().unwrap());
+/// ```
 pub fn prepare_action(
     action: &Action,
     account: &Pubkey,
@@ -256,15 +768,39 @@ pub fn prepare_action(
         account: account.to_base58(),
         signer: signer_pubkey.to_base58(),
         nonce,
+        id_scheme: IdScheme::CURRENT,
+        group_kind: None,
+        group_warnings: Vec::new(),
     })
 }
 
+/// Prepare `action` under a content-derived nonce (see
+/// [`content_derived_nonce`](crate::content_derived_nonce)), so every retry
+/// prepares identical bytes. Refused for non-idempotent actions such as orders.
+///
+/// ```rust
+/// use bulk_keychain::{prepare_action_content_derived, Action, Keypair, UserSettings};
+///
+/// let account = Keypair::generate().pubkey();
+/// let action = Action::UpdateUserSettings(UserSettings::set_leverage("BTC-USD", 5.0));
+/// let first = prepare_action_content_derived(&action, 1, &account, None).unwrap();
+/// let retry = prepare_action_content_derived(&action, 1, &account, None).unwrap();
+/// assert_eq!(first.message_bytes, retry.message_bytes);
+/// ```
+pub fn prepare_action_content_derived(
+    action: &Action,
+    epoch: u64,
+    account: &Pubkey,
+    signer: Option<&Pubkey>,
+) -> Result<PreparedMessage> {
+    let nonce = crate::nonce::content_derived_nonce(action, account, epoch)?;
+    prepare_action(action, account, signer, Some(nonce))
+}
+
 fn compute_action_order_id(action: &Action, nonce: u64, account: &Pubkey) -> Option<String> {
     match action {
         Action::Order { orders } if orders.len() == 1 => {
-            let mut scratch = Vec::with_capacity(96);
-            compute_order_item_id_at_index(&orders[0], 0, nonce, account, &mut scratch)
-                .map(|id| id.to_base58())
+            compute_order_item_id_at_index(&orders[0], 0, nonce, account).map(|id| id.to_base58())
         }
         _ => None,
     }
@@ -273,12 +809,9 @@ fn compute_action_order_id(action: &Action, nonce: u64, account: &Pubkey) -> Opt
 fn compute_action_order_ids(action: &Action, nonce: u64, account: &Pubkey) -> Option<Vec<String>> {
     match action {
         Action::Order { orders } if orders.len() > 1 => {
-            let mut scratch = Vec::with_capacity(96);
             let mut ids = Vec::with_capacity(orders.len());
             for (idx, item) in orders.iter().enumerate() {
-                if let Some(id) =
-                    compute_order_item_id_at_index(item, idx as u32, nonce, account, &mut scratch)
-                {
+                if let Some(id) = compute_order_item_id_at_index(item, idx as u32, nonce, account) {
                     ids.push(id.to_base58());
                 }
             }
@@ -293,6 +826,21 @@ fn compute_action_order_ids(action: &Action, nonce: u64, account: &Pubkey) -> Op
 }
 
 /// Prepare multiple independent order item transactions.
+///
+/// Output `i` always corresponds to input `i` and uses nonce `base + i`.
+///
+/// ```rust
+/// use bulk_keychain::{prepare_all, Keypair, Order};
+///
+/// let keypair = Keypair::generate();
+/// let orders = vec![
+///     Order::market("BTC-USD", true, 0.1).into(),
+///     Order::market("ETH-USD", true, 1.0).into(),
+/// ];
+/// let prepared = prepare_all(orders, &keypair.pubkey(), None, Some(10)).unwrap();
+/// assert_eq!((prepared[0].nonce, prepared[1].nonce), (10, 11));
+/// assert!(prepared.iter().all(|p| p.order_id.is_some()));
+/// ```
 pub fn prepare_all(
     items: Vec<OrderItem>,
     account: &Pubkey,
@@ -303,22 +851,42 @@ pub fn prepare_all(
         return Ok(vec![]);
     }
 
-    let base = base_nonce.unwrap_or_else(crate::nonce::current_timestamp_millis);
+    let base = crate::nonce::check_nonce_range(
+        base_nonce.unwrap_or_else(crate::nonce::current_timestamp_millis),
+        items.len(),
+    )?;
     let signer_pubkey = signer.unwrap_or(account);
 
-    if items.len() < PARALLEL_THRESHOLD {
-        items
-            .into_iter()
-            .enumerate()
-            .map(|(i, item)| prepare_single_item(item, account, signer_pubkey, base + i as u64))
-            .collect()
-    } else {
-        items
-            .into_par_iter()
-            .enumerate()
-            .map(|(i, item)| prepare_single_item(item, account, signer_pubkey, base + i as u64))
-            .collect()
-    }
+    let (results, _) = run_batch(Parallelism::Auto, PARALLEL_THRESHOLD, items, |i, item| {
+        prepare_single_item(item, account, signer_pubkey, base + i as u64)
+    });
+    results.into_iter().collect()
+}
+
+/// Like [`prepare_all`], but tags each message with its input index.
+///
+/// ```rust
+/// use bulk_keychain::{prepare_all_indexed, Keypair, Order};
+///
+/// let keypair = Keypair::generate();
+/// let orders = vec![
+///     Order::market("BTC-USD", true, 0.1).into(),
+///     Order::market("ETH-USD", true, 1.0).into(),
+/// ];
+/// let prepared = prepare_all_indexed(orders, &keypair.pubkey(), None, Some(10)).unwrap();
+/// assert_eq!((prepared[1].index, prepared[1].message.nonce), (1, 11));
+/// ```
+pub fn prepare_all_indexed(
+    items: Vec<OrderItem>,
+    account: &Pubkey,
+    signer: Option<&Pubkey>,
+    base_nonce: Option<u64>,
+) -> Result<Vec<IndexedPreparedMessage>> {
+    Ok(prepare_all(items, account, signer, base_nonce)?
+        .into_iter()
+        .enumerate()
+        .map(|(index, message)| IndexedPreparedMessage { index, message })
+        .collect())
 }
 
 fn prepare_single_item(
@@ -327,9 +895,8 @@ fn prepare_single_item(
     signer: &Pubkey,
     nonce: u64,
 ) -> Result<PreparedMessage> {
-    let mut scratch = Vec::with_capacity(96);
-    let order_id = compute_order_item_id_at_index(&item, 0, nonce, account, &mut scratch)
-        .map(|id| id.to_base58());
+    let order_id =
+        compute_order_item_id_at_index(&item, 0, nonce, account).map(|id| id.to_base58());
     let action = Action::Order { orders: vec![item] };
 
     let mut message_bytes = Vec::with_capacity(512);
@@ -344,32 +911,92 @@ fn prepare_single_item(
         account: account.to_base58(),
         signer: signer.to_base58(),
         nonce,
+        id_scheme: IdScheme::CURRENT,
+        group_kind: None,
+        group_warnings: Vec::new(),
     })
 }
 
 /// Finalize a prepared message with a base58 signature.
-pub fn finalize_transaction(prepared: PreparedMessage, signature: &str) -> SignedTransaction {
-    SignedTransaction {
-        actions: prepared.actions,
+///
+/// Fails with [`Error::InvalidAction`] if `prepared.actions` do not parse
+/// back into an action.
+///
+/// ```rust
+/// use bulk_keychain::{finalize_transaction, prepare_message, Keypair, Order, Signer, TimeInForce};
+///
+/// // `wallet` stands in for an external wallet that signs raw bytes.
+/// let wallet = Signer::new(Keypair::generate());
+/// let account = *wallet.account_pubkey();
+/// let prepared = prepare_message(Order::limit("BTC-USD", true, 100000.0, 0.1, TimeInForce::Gtc).into(), &account, None, Some(1)).unwrap();
+/// let signature = wallet.sign_bytes(&prepared.message_bytes);
+///
+/// let tx = finalize_transaction(prepared, &signature).unwrap();
+/// assert_eq!(tx.account_pubkey().unwrap(), account);
+/// assert!(tx.order_id_hash().unwrap().is_some());
+/// assert!(tx.verify().unwrap());
+/// ```
+pub fn finalize_transaction(
+    prepared: PreparedMessage,
+    signature: &str,
+) -> Result<SignedTransaction> {
+    Ok(SignedTransaction {
+        action: crate::compact::action_from_json_list(&prepared.actions)?,
         nonce: prepared.nonce,
         account: prepared.account,
         signer: prepared.signer,
         signature: signature.to_string(),
         order_id: prepared.order_id,
         order_ids: prepared.order_ids,
-    }
+        id_scheme: prepared.id_scheme,
+        resign_count: 0,
+        group_kind: prepared.group_kind,
+        group_warnings: prepared.group_warnings,
+    })
 }
 
 /// Finalize a prepared message with raw signature bytes.
+///
+/// ```rust
+/// use bulk_keychain::ed25519_dalek::{Signer as _, SigningKey};
+/// use bulk_keychain::{finalize_transaction_bytes, prepare_message, Keypair, Order, TimeInForce};
+///
+/// let keypair = Keypair::generate();
+/// let prepared = prepare_message(Order::limit("BTC-USD", true, 100000.0, 0.1, TimeInForce::Gtc).into(), &keypair.pubkey(), None, Some(1)).unwrap();
+/// let signing_key = SigningKey::from_bytes(keypair.secret_key());
+/// let signature = signing_key.sign(&prepared.message_bytes).to_bytes();
+///
+/// let tx = finalize_transaction_bytes(prepared, &signature).unwrap();
+/// assert_eq!(tx.signature_bytes().unwrap(), signature);
+/// assert!(tx.verify().unwrap());
+/// ```
 pub fn finalize_transaction_bytes(
     prepared: PreparedMessage,
     signature: &[u8],
-) -> SignedTransaction {
+) -> Result<SignedTransaction> {
     let signature_b58 = bs58::encode(signature).into_string();
     finalize_transaction(prepared, &signature_b58)
 }
 
 /// Finalize many prepared messages with aligned signatures.
+///
+/// ```rust
+/// use bulk_keychain::{finalize_all, prepare_all, Keypair, Order, Signer};
+///
+/// let wallet = Signer::new(Keypair::generate());
+/// let orders = vec![
+///     Order::market("BTC-USD", true, 0.1).into(),
+///     Order::market("ETH-USD", true, 1.0).into(),
+/// ];
+/// let prepared = prepare_all(orders, wallet.account_pubkey(), None, Some(1)).unwrap();
+/// let signatures: Vec<String> = prepared
+///     .iter()
+///     .map(|p| wallet.sign_bytes(&p.message_bytes))
+///     .collect();
+///
+/// let txs = finalize_all(prepared, signatures.iter().map(String::as_str).collect()).unwrap();
+/// assert!(txs.iter().all(|tx| tx.verify().unwrap()));
+/// ```
 pub fn finalize_all(
     prepared: Vec<PreparedMessage>,
     signatures: Vec<&str>,
@@ -381,288 +1008,88 @@ pub fn finalize_all(
         });
     }
 
-    Ok(prepared
+    prepared
         .into_iter()
         .zip(signatures)
         .map(|(p, sig)| finalize_transaction(p, sig))
-        .collect())
+        .collect()
 }
 
-fn action_to_json(action: &Action) -> Result<Vec<serde_json::Value>> {
-    match action {
-        Action::Order { orders } => orders.iter().map(order_item_to_json).collect(),
-        Action::Faucet(faucet) => {
-            let mut faucet_obj = json!({ "u": faucet.user.to_base58() });
-            if let Some(amount) = faucet.amount {
-                faucet_obj["amount"] = json!(amount);
-            }
-            Ok(vec![json!({ "faucet": faucet_obj })])
-        }
-        Action::AgentWalletCreation(agent) => Ok(vec![json!({
-            "agentWalletCreation": {
-                "a": agent.agent.to_base58(),
-                "d": agent.delete
-            }
-        })]),
-        Action::UpdateUserSettings(settings) => {
-            let m: serde_json::Map<String, serde_json::Value> = settings
-                .max_leverage
-                .iter()
-                .map(|(symbol, lev)| (symbol.clone(), json!(lev)))
-                .collect();
-            Ok(vec![json!({ "updateUserSettings": { "m": m } })])
-        }
-        Action::Oracle { oracles } => Ok(oracles
-            .iter()
-            .map(|o| {
-                json!({
-                    "px": {
-                        "t": o.timestamp,
-                        "c": o.asset,
-                        "px": o.price
-                    }
-                })
-            })
-            .collect()),
-        Action::PythOracle { oracles } => {
-            let entries: Vec<_> = oracles
-                .iter()
-                .map(|o| {
-                    json!({
-                        "t": o.timestamp,
-                        "fi": o.feed_index,
-                        "px": o.price,
-                        "e": o.exponent
-                    })
-                })
-                .collect();
-            Ok(vec![json!({ "o": { "oracles": entries } })])
-        }
-        Action::WhitelistFaucet(action) => Ok(vec![json!({
-            "whitelistFaucet": {
-                "target": action.target.to_base58(),
-                "whitelist": action.whitelist
-            }
-        })]),
-        Action::CreateSubAccount(action) => {
-            let mut obj = json!({ "name": action.name });
-            if let Some(symbol) = &action.margin_symbol {
-                obj["marginSymbol"] = json!(symbol);
-            }
-            if let Some(amount) = action.margin_amount {
-                obj["marginAmount"] = json!(amount);
-            }
-            Ok(vec![json!({ "createSubAccount": obj })])
-        }
-        Action::RemoveSubAccount(action) => Ok(vec![json!({
-            "removeSubAccount": {
-                "toRemove": action.to_remove.to_base58()
-            }
-        })]),
-        Action::RenameSubAccount(action) => Ok(vec![json!({
-            "renameSubAccount": {
-                "account": action.account.to_base58(),
-                "name": action.name
-            }
-        })]),
-        Action::Transfer(transfer) => Ok(vec![json!({
-            "transfer": {
-                "k": match transfer.kind {
-                    TransferKind::Internal => "internal",
-                    TransferKind::External => "external",
-                },
-                "from": transfer.from.to_base58(),
-                "to": transfer.to.to_base58(),
-                "marginSymbol": transfer.margin_symbol,
-                "marginAmount": transfer.margin_amount,
-            }
-        })]),
-        Action::CreateMultisig(action) => Ok(vec![json!({
-            "createMultisig": {
-                "signers": action.signers.iter().map(Pubkey::to_base58).collect::<Vec<_>>(),
-                "threshold": action.threshold,
-                "timeLockSecs": action.time_lock_secs,
-                "proposalLifetimeSecs": action.proposal_lifetime_secs,
-            }
-        })]),
-        Action::MultisigPropose(action) => {
-            let mut inner_actions = Vec::new();
-            for inner in &action.actions {
-                inner_actions.extend(action_to_json(inner)?);
-            }
-            Ok(vec![json!({
-                "msp": {
-                    "m": action.multisig.to_base58(),
-                    "a": inner_actions,
-                }
-            })])
-        }
-        Action::MultisigApprove(action) => Ok(vec![json!({
-            "msa": {
-                "m": action.multisig.to_base58(),
-                "p": action.proposal_id,
-            }
-        })]),
-        Action::MultisigReject(action) => Ok(vec![json!({
-            "msr": {
-                "m": action.multisig.to_base58(),
-                "p": action.proposal_id,
-            }
-        })]),
-        Action::MultisigCancel(action) => Ok(vec![json!({
-            "msc": {
-                "m": action.multisig.to_base58(),
-                "p": action.proposal_id,
-            }
-        })]),
-        Action::MultisigExecute(action) => Ok(vec![json!({
-            "mse": {
-                "m": action.multisig.to_base58(),
-                "p": action.proposal_id,
-            }
-        })]),
-        Action::UpdateMultisigPolicy(action) => Ok(vec![json!({
-            "msu": {
-                "m": action.multisig.to_base58(),
-                "signers": action.signers.iter().map(Pubkey::to_base58).collect::<Vec<_>>(),
-                "threshold": action.threshold,
-                "timeLockSecs": action.time_lock_secs,
-                "proposalLifetimeSecs": action.proposal_lifetime_secs,
-            }
-        })]),
-    }
-}
-
-fn order_item_to_json(item: &OrderItem) -> Result<serde_json::Value> {
-    match item {
-        OrderItem::Order(order) => match &order.order_type {
-            OrderType::Limit { tif } => {
-                let tif_str = match tif {
-                    TimeInForce::Gtc => "GTC",
-                    TimeInForce::Ioc => "IOC",
-                    TimeInForce::Alo => "ALO",
-                };
-                Ok(json!({
-                    "l": {
-                        "c": order.symbol,
-                        "b": order.is_buy,
-                        "px": order.price,
-                        "sz": order.size,
-                        "tif": tif_str,
-                        "r": order.reduce_only,
-                        "i": order.iso
-                    }
-                }))
-            }
-            OrderType::Trigger {
-                is_market,
-                trigger_px: _,
-            } => {
-                if !is_market {
-                    return Err(Error::InvalidOrder(
-                        "trigger orders are not supported by BULK API; use market".to_string(),
-                    ));
-                }
-                Ok(json!({
-                    "m": {
-                        "c": order.symbol,
-                        "b": order.is_buy,
-                        "sz": order.size,
-                        "r": order.reduce_only,
-                        "i": order.iso
-                    }
-                }))
-            }
-        },
-        OrderItem::Modify(modify) => Ok(json!({
-            "mod": {
-                "oid": modify.order_id.to_base58(),
-                "c": modify.symbol,
-                "sz": modify.amount
-            }
-        })),
-        OrderItem::Cancel(cancel) => Ok(json!({
-            "cx": {
-                "c": cancel.symbol,
-                "oid": cancel.order_id.to_base58()
-            }
-        })),
-        OrderItem::CancelAll(cancel_all) => Ok(json!({
-            "cxa": {
-                "c": cancel_all.symbols
-            }
-        })),
-        OrderItem::Stop(stop) => Ok(json!({
-            "st": {
-                "c": stop.symbol,
-                "d": stop.is_buy,
-                "sz": stop.size,
-                "tr": stop.trigger_price,
-                "lim": stop.limit_price,
-                "i": stop.iso
-            }
-        })),
-        OrderItem::TakeProfit(tp) => Ok(json!({
-            "tp": {
-                "c": tp.symbol,
-                "d": tp.is_buy,
-                "sz": tp.size,
-                "tr": tp.trigger_price,
-                "lim": tp.limit_price,
-                "i": tp.iso
-            }
-        })),
-        OrderItem::RangeOco(rng) => Ok(json!({
-            "rng": {
-                "c": rng.symbol,
-                "d": rng.is_buy,
-                "sz": rng.size,
-                "pmin": rng.collar_min,
-                "pmax": rng.collar_max,
-                "lmin": rng.limit_min,
-                "lmax": rng.limit_max,
-                "i": rng.iso
-            }
-        })),
-        OrderItem::TriggerBasket(trig) => {
-            let nested: Result<Vec<_>> = trig.actions.iter().map(order_item_to_json).collect();
-            Ok(json!({
-                "trig": {
-                    "c": trig.symbol,
-                    "d": trig.is_buy,
-                    "tr": trig.trigger_price,
-                    "actions": nested?,
-                    "i": trig.iso
-                }
-            }))
+/// Indices of the transactions whose signature does not verify, malformed
+/// ones included; empty if the whole batch is good.
+///
+/// ```rust
+/// use bulk_keychain::{verify_batch, Keypair, Order, Signer};
+///
+/// let signer = Signer::new(Keypair::generate());
+/// let orders = vec![Order::market("BTC-USD", true, 0.1).into(); 3];
+/// let mut txs = signer.sign_all(orders, Some(1)).unwrap();
+/// assert!(verify_batch(&txs).is_empty());
+/// txs[1].nonce += 1;
+/// assert_eq!(verify_batch(&txs), [1]);
+/// ```
+pub fn verify_batch(txs: &[SignedTransaction]) -> Vec<usize> {
+    txs.iter()
+        .enumerate()
+        .filter(|(_, tx)| !tx.verify().unwrap_or(false))
+        .map(|(i, _)| i)
+        .collect()
+}
+
+/// Check a transaction signed elsewhere before submitting it.
+///
+/// The canonical message is rebuilt from `tx.action`, `tx.nonce` and
+/// `tx.account`, and the signature verified against `tx.signer`. `Ok(false)`
+/// means the signature does not verify. Malformed fields are errors, as are
+/// `order_id` / `order_ids` that differ from the IDs the signed actions
+/// produce ([`Error::OrderIdMismatch`]): the signature does not cover them,
+/// but tracking an order under the wrong ID is as bad as a bad signature.
+/// Absent IDs are not checked.
+///
+/// ```rust
+/// use bulk_keychain::{verify_transaction, Error, Keypair, Order, Signer};
+///
+/// let mut signer = Signer::new(Keypair::generate());
+/// let mut tx = signer.sign(Order::market("BTC-USD", true, 0.1).into(), Some(1)).unwrap();
+/// assert!(verify_transaction(&tx).unwrap());
+///
+/// tx.order_id = Some(bulk_keychain::Hash::random().to_base58());
+/// assert!(matches!(verify_transaction(&tx), Err(Error::OrderIdMismatch { .. })));
+/// ```
+pub fn verify_transaction(tx: &SignedTransaction) -> Result<bool> {
+    if !tx.verify()? {
+        return Ok(false);
+    }
+    if tx.order_id.is_none() && tx.order_ids.is_none() {
+        return Ok(true);
+    }
+
+    let account = tx.account_pubkey()?;
+    let mismatch = |claimed: String, computed: Option<String>| Error::OrderIdMismatch {
+        claimed,
+        computed: computed.unwrap_or_else(|| "none".to_string()),
+    };
+    if let Some(claimed) = &tx.order_id {
+        let computed = compute_action_order_id(&tx.action, tx.nonce, &account);
+        if computed.as_ref() != Some(claimed) {
+            return Err(mismatch(claimed.clone(), computed));
         }
-        OrderItem::OnFill(of) => {
-            let actions: Result<Vec<_>> = of.actions.iter().map(order_item_to_json).collect();
-            Ok(json!({
-                "of": {
-                    "p": of.p,
-                    "actions": actions?
-                }
-            }))
+    }
+    if let Some(claimed) = &tx.order_ids {
+        let computed = compute_action_order_ids(&tx.action, tx.nonce, &account);
+        if computed.as_ref() != Some(claimed) {
+            return Err(mismatch(
+                claimed.join(","),
+                computed.map(|ids| ids.join(",")),
+            ));
         }
-        OrderItem::TrailingStop(trl) => Ok(json!({
-            "trl": {
-                "c": trl.symbol,
-                "b": trl.is_buy,
-                "sz": trl.size,
-                "trb": trl.trail_bps,
-                "stb": trl.step_bps,
-                "lim": trl.limit_price,
-                "i": trl.iso
-            }
-        })),
     }
+    Ok(true)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::Keypair;
 
     #[test]
     fn test_prepare_message() {
@@ -692,6 +1119,151 @@ mod tests {
         assert!(mod_obj.get("amount").is_none());
     }
 
+    #[test]
+    fn test_finalize_verified_rebuilt_from_parts() {
+        let wallet = crate::Signer::new(Keypair::generate());
+        let legs = vec![
+            Order::limit("BTC-USD", true, 100000.0, 0.1, TimeInForce::Gtc).into(),
+            Order::limit("ETH-USD", false, 3000.0, 1.0, TimeInForce::Gtc).into(),
+        ];
+        let prepared = prepare_group(legs, wallet.account_pubkey(), None, Some(9)).unwrap();
+        let rebuilt = PreparedMessage::from_parts(
+            prepared.actions.clone(),
+            &prepared.account,
+            &prepared.signer,
+            prepared.nonce,
+        )
+        .unwrap();
+        assert_eq!(rebuilt.message_bytes, prepared.message_bytes);
+        assert_eq!(rebuilt.order_ids, prepared.order_ids);
+
+        let raw = wallet.sign_bytes_raw(&prepared.message_bytes);
+        let good = bs58::encode(raw).into_string();
+        let tx = rebuilt.clone().finalize_verified(&good).unwrap();
+        assert!(tx.verify().unwrap());
+
+        // Hex, truncated, and over another nonce are all refused.
+        assert!(matches!(
+            rebuilt.clone().finalize_verified(&hex::encode(raw)),
+            Err(Error::InvalidBase58(_) | Error::InvalidSignatureLength(_))
+        ));
+        assert!(matches!(
+            rebuilt
+                .clone()
+                .finalize_verified(&bs58::encode(&raw[..63]).into_string()),
+            Err(Error::InvalidSignatureLength(63))
+        ));
+        let mut actions = prepared.actions.clone();
+        actions.pop();
+        let other =
+            PreparedMessage::from_parts(actions, &prepared.account, &prepared.signer, 9).unwrap();
+        let err = other.finalize_verified(&good).unwrap_err().to_string();
+        assert!(err.contains(&wallet.signer_pubkey().fingerprint()), "{err}");
+    }
+
+    #[test]
+    fn test_prepare_modify_order_matches_signer() {
+        let keypair = Keypair::generate();
+        let modify = Modify::new(Hash::random(), "BTC-USD", 0.25);
+        let prepared =
+            prepare_modify_order(modify.clone(), &keypair.pubkey(), None, Some(7)).unwrap();
+        let mut signer = crate::Signer::new(keypair.clone());
+        let direct = signer.sign_modify_order(modify, Some(7)).unwrap();
+        assert_eq!(
+            prepared.sign_with_keypair(&keypair).unwrap().signature,
+            direct.signature
+        );
+    }
+
+    #[test]
+    fn test_prepare_all_preserves_input_order() {
+        let account = Keypair::generate().pubkey();
+        for n in [PARALLEL_THRESHOLD - 1, PARALLEL_THRESHOLD * 4] {
+            let items: Vec<OrderItem> = (0..n)
+                .map(|i| {
+                    Order::limit("BTC-USD", true, 1000.0 + i as f64, 0.1, TimeInForce::Gtc).into()
+                })
+                .collect();
+
+            let prepared = prepare_all(items, &account, None, Some(500)).unwrap();
+            assert_eq!(prepared.len(), n);
+            for (i, p) in prepared.iter().enumerate() {
+                assert_eq!(p.actions[0]["l"]["px"].as_f64(), Some(1000.0 + i as f64));
+                assert_eq!(p.nonce, 500 + i as u64);
+            }
+        }
+    }
+
+    #[test]
+    fn test_prepare_cancel_and_replace() {
+        let account = Keypair::generate().pubkey();
+        let old_id = Hash::random();
+
+        let cancel = prepare_cancel("BTC-USD", old_id, &account, None, Some(7)).unwrap();
+        assert_eq!(cancel.actions[0]["cx"]["oid"], old_id.to_base58());
+
+        let order = Order::limit("BTC-USD", true, 100000.0, 0.1, TimeInForce::Gtc);
+        let replaced =
+            prepare_cancel_replace(old_id, order.clone(), &account, None, Some(7)).unwrap();
+        assert_eq!(replaced.actions.len(), 2);
+        assert_eq!(replaced.actions[0]["cx"]["c"], "BTC-USD");
+        assert!(replaced.actions[1].get("l").is_some());
+        assert_eq!(replaced.order_ids.as_ref().map(Vec::len), Some(1));
+
+        let expected = prepare_group(
+            vec![Cancel::new("BTC-USD", old_id).into(), order.into()],
+            &account,
+            None,
+            Some(7),
+        )
+        .unwrap();
+        assert_eq!(replaced.message_bytes, expected.message_bytes);
+    }
+
+    #[test]
+    fn test_prepare_cancel_all() {
+        let account = Keypair::generate().pubkey();
+        let prepared = prepare_cancel_all(
+            vec!["BTC-USD".to_string(), "ETH-USD".to_string()],
+            &account,
+            None,
+            Some(1234567890),
+        )
+        .unwrap();
+
+        let expected = prepare_message(
+            CancelAll::for_symbols(vec!["BTC-USD".to_string(), "ETH-USD".to_string()]).into(),
+            &account,
+            None,
+            Some(1234567890),
+        )
+        .unwrap();
+        assert_eq!(prepared.message_bytes, expected.message_bytes);
+        assert_eq!(prepared.actions[0]["cxa"]["c"][1], "ETH-USD");
+        assert!(prepared.order_id.is_none());
+    }
+
+    #[test]
+    fn test_prepare_user_settings_is_deterministic() {
+        let account = Keypair::generate().pubkey();
+        let leverage = vec![("BTC-USD".to_string(), 5.0), ("ETH-USD".to_string(), 3.0)];
+        let prepare = |max_leverage| {
+            prepare_user_settings(
+                UserSettings::new(max_leverage),
+                &account,
+                None,
+                Some(1234567890),
+            )
+            .unwrap()
+            .message_bytes
+        };
+        let first = prepare(leverage.clone());
+        for _ in 0..8 {
+            assert_eq!(prepare(leverage.clone()), first);
+        }
+        assert_eq!(prepare(leverage.into_iter().rev().collect()), first);
+    }
+
     #[test]
     fn test_prepare_group() {
         let keypair = Keypair::generate();
@@ -736,14 +1308,70 @@ mod tests {
         let account = keypair.pubkey();
         let order = Order::limit("BTC-USD", true, 100000.0, 0.1, TimeInForce::Gtc);
         let prepared = prepare_message(order.into(), &account, None, Some(1234567890)).unwrap();
-        let signed = finalize_transaction(prepared.clone(), "sig");
+        let signed = finalize_transaction(prepared.clone(), "sig").unwrap();
 
         assert_eq!(signed.nonce, prepared.nonce);
-        assert_eq!(signed.actions, prepared.actions);
+        assert_eq!(signed.actions_json().unwrap(), prepared.actions);
         assert_eq!(signed.signature, "sig");
         assert_eq!(signed.order_ids, prepared.order_ids);
     }
 
+    #[test]
+    fn test_sign_with_keypair() {
+        let keypair = Keypair::generate();
+        let order = Order::limit("BTC-USD", true, 100000.0, 0.1, TimeInForce::Gtc);
+        let prepared =
+            prepare_message(order.clone().into(), &keypair.pubkey(), None, Some(7)).unwrap();
+
+        let signed = prepared.sign_with_keypair(&keypair).unwrap();
+        let expected = crate::Signer::new(keypair.clone())
+            .sign(order.into(), Some(7))
+            .unwrap();
+        assert_eq!(signed.signature, expected.signature);
+        assert_eq!(signed.order_id, prepared.order_id);
+        assert!(signed.verify().unwrap());
+
+        let other = Keypair::generate();
+        let err = prepared.sign_with_keypair(&other).unwrap_err();
+        assert!(
+            matches!(err, Error::SignerMismatch { ref got, .. } if *got == other.pubkey().to_base58())
+        );
+    }
+
+    #[test]
+    fn test_verify_transaction() {
+        let keypair = Keypair::generate();
+        let account = keypair.pubkey();
+        let group = vec![
+            Cancel::new("BTC-USD", Hash::random()).into(),
+            Order::limit("BTC-USD", true, 100000.0, 0.1, TimeInForce::Gtc).into(),
+            Order::market("ETH-USD", false, 1.0).into(),
+        ];
+        let prepared = prepare_group(group, &account, None, Some(5)).unwrap();
+        let signed = prepared.sign_with_keypair(&keypair).unwrap();
+        assert_eq!(signed.order_ids.as_ref().map(Vec::len), Some(2));
+        assert!(verify_transaction(&signed).unwrap());
+
+        let mut bad_nonce = signed.clone();
+        bad_nonce.nonce += 1;
+        assert!(!verify_transaction(&bad_nonce).unwrap());
+
+        let mut bad_ids = signed.clone();
+        bad_ids.order_ids.as_mut().unwrap().swap(0, 1);
+        assert!(matches!(
+            verify_transaction(&bad_ids),
+            Err(Error::OrderIdMismatch { .. })
+        ));
+
+        let mut untracked = signed.clone();
+        untracked.order_ids = None;
+        assert!(verify_transaction(&untracked).unwrap());
+
+        let mut bad_signer = signed;
+        bad_signer.signer = "not-base58!".to_string();
+        assert!(verify_transaction(&bad_signer).is_err());
+    }
+
     #[test]
     fn test_prepare_rename_sub_account() {
         let keypair = Keypair::generate();
@@ -0,0 +1,285 @@
+//! Process-wide signer for scripts and notebooks
+//!
+//! **Not for production services.** One global signer behind a mutex, set up
+//! once from the environment, so a script can sign an intent in one line:
+//!
+//! ```rust,no_run
+//! bulk_keychain::quick::init_from_env().unwrap();
+//! let signed = bulk_keychain::quick::sign(
+//!     r#"{"type":"order","symbol":"BTC-USD","isBuy":true,"price":100000,"size":0.1,
+//!         "orderType":{"type":"limit","tif":"GTC"}}"#,
+//! ).unwrap();
+//! ```
+//!
+//! Services should own a [`Signer`] and pass it where it is needed: the global
+//! serializes every signature, and any code in the process can sign with it.
+//! [`shutdown`] drops the signer, which wipes its secret key.
+
+use crate::input::OrderSpec;
+use crate::types::{OrderItem, Pubkey};
+use crate::{prepare_group, prepare_message, Error, InputMode, Keypair, Result, Signer};
+use serde_json::Value;
+use std::sync::{Mutex, MutexGuard};
+use zeroize::Zeroizing;
+
+/// Base58 secret key (32-byte secret or 64-byte keypair)
+pub const SECRET_KEY_ENV: &str = "BULK_SECRET_KEY";
+/// Path to a key file holding a private JWK or a base58 secret
+pub const KEYSTORE_ENV: &str = "BULK_KEYSTORE";
+/// Passphrase for an encrypted key file
+pub const KEYSTORE_PASSPHRASE_ENV: &str = "BULK_KEYSTORE_PASSPHRASE";
+
+static SIGNER: Mutex<Option<Signer>> = Mutex::new(None);
+
+fn registry() -> MutexGuard<'static, Option<Signer>> {
+    // A panic mid-sign leaves nothing half-written worth refusing over.
+    SIGNER.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+fn quick_error(message: impl Into<String>) -> Error {
+    Error::Quick(message.into())
+}
+
+/// Install `keypair` as the process-wide signer; fails if one is already set.
+pub fn init(keypair: Keypair) -> Result<()> {
+    let mut signer = registry();
+    if signer.is_some() {
+        return Err(quick_error(
+            "already initialized; call quick::shutdown() first",
+        ));
+    }
+    *signer = Some(Signer::new(keypair));
+    Ok(())
+}
+
+/// Install the signer from [`SECRET_KEY_ENV`] or [`KEYSTORE_ENV`].
+///
+/// Exactly one must be set. Prints a warning to stderr, since a key in the
+/// environment is visible to child processes and often ends up in logs.
+pub fn init_from_env() -> Result<()> {
+    init_from_vars(|name| std::env::var(name).ok())
+}
+
+fn init_from_vars(var: impl Fn(&str) -> Option<String>) -> Result<()> {
+    let secret = var(SECRET_KEY_ENV).map(Zeroizing::new);
+    let keystore = var(KEYSTORE_ENV);
+    let (keypair, source) = match (secret, keystore) {
+        (Some(secret), None) => (Keypair::from_base58(secret.trim())?, SECRET_KEY_ENV),
+        (None, Some(path)) => {
+            if var(KEYSTORE_PASSPHRASE_ENV).is_some() {
+                return Err(quick_error(format!(
+                    "encrypted key files are not supported; unset {} and store a private JWK or base58 secret",
+                    KEYSTORE_PASSPHRASE_ENV
+                )));
+            }
+            let contents = Zeroizing::new(
+                std::fs::read_to_string(&path)
+                    .map_err(|e| quick_error(format!("cannot read {}: {}", path, e)))?,
+            );
+            let contents = contents.trim();
+            let keypair = if contents.starts_with('{') {
+                Keypair::from_jwk(contents)?
+            } else {
+                Keypair::from_base58(contents)?
+            };
+            (keypair, KEYSTORE_ENV)
+        }
+        (Some(_), Some(_)) => {
+            return Err(quick_error(format!(
+                "both {} and {} are set; keep one",
+                SECRET_KEY_ENV, KEYSTORE_ENV
+            )))
+        }
+        (None, None) => {
+            return Err(quick_error(format!(
+                "set {} or {}",
+                SECRET_KEY_ENV, KEYSTORE_ENV
+            )))
+        }
+    };
+    let fingerprint = keypair.fingerprint();
+    init(keypair)?;
+    eprintln!(
+        "WARNING: bulk_keychain::quick loaded signing key {} from {}; \
+         the quick signer is for scripts only, not production services",
+        fingerprint, source
+    );
+    Ok(())
+}
+
+/// True while a process-wide signer is installed.
+pub fn is_initialized() -> bool {
+    registry().is_some()
+}
+
+/// Public key of the process-wide signer.
+pub fn pubkey() -> Result<Pubkey> {
+    with_signer(|signer| Ok(*signer.signer_pubkey()))
+}
+
+fn with_signer<T>(f: impl FnOnce(&mut Signer) -> Result<T>) -> Result<T> {
+    let mut signer = registry();
+    let signer = signer
+        .as_mut()
+        .ok_or_else(|| quick_error("not initialized; call quick::init_from_env() first"))?;
+    f(signer)
+}
+
+/// One intent, or an array signed atomically as a group.
+fn parse_items(json: &str) -> Result<(Vec<OrderItem>, bool)> {
+    let parse = |value: Value| -> Result<OrderItem> {
+        OrderSpec::from_json(value, InputMode::Tolerant)?
+            .spec
+            .try_into()
+    };
+    match serde_json::from_str(json)? {
+        Value::Array(values) => {
+            let items = values.into_iter().map(parse).collect::<Result<_>>()?;
+            Ok((items, true))
+        }
+        value => Ok((vec![parse(value)?], false)),
+    }
+}
+
+/// Sign an order intent (or an array of them, atomically) with the
+/// process-wide signer; returns the signed transaction as JSON.
+pub fn sign(json: &str) -> Result<String> {
+    let (mut items, group) = parse_items(json)?;
+    let tx = with_signer(|signer| {
+        if group {
+            signer.sign_group(items, None)
+        } else {
+            signer.sign(items.remove(0), None)
+        }
+    })?;
+    Ok(serde_json::to_string(&tx)?)
+}
+
+/// Prepare an intent for `account` (base58), signed by the process-wide
+/// signer's key as an agent; returns the [`PreparedMessage`] as JSON.
+///
+/// [`PreparedMessage`]: crate::PreparedMessage
+pub fn prepare(json: &str, account: &str) -> Result<String> {
+    let (mut items, group) = parse_items(json)?;
+    let account = Pubkey::from_base58(account)?;
+    let signer = pubkey()?;
+    let prepared = if group {
+        prepare_group(items, &account, Some(&signer), None)?
+    } else {
+        prepare_message(items.remove(0), &account, Some(&signer), None)?
+    };
+    Ok(serde_json::to_string(&prepared)?)
+}
+
+/// Drop the process-wide signer, wiping its secret key; returns whether one
+/// was installed. [`init`] may be called again afterwards.
+pub fn shutdown() -> bool {
+    let signer = registry().take();
+    let installed = signer.is_some();
+    // The signing key zeroizes itself on drop.
+    drop(signer);
+    installed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+    use zeroize::ZeroizeOnDrop;
+
+    // The registry is process-wide; keep these tests from interleaving.
+    static SERIAL: Mutex<()> = Mutex::new(());
+
+    fn serial() -> MutexGuard<'static, ()> {
+        let guard = SERIAL.lock().unwrap_or_else(|e| e.into_inner());
+        shutdown();
+        guard
+    }
+
+    fn vars(pairs: &[(&str, &str)]) -> impl Fn(&str) -> Option<String> {
+        let map: HashMap<String, String> = pairs
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect();
+        move |name| map.get(name).cloned()
+    }
+
+    const ORDER: &str = r#"{"type":"order","symbol":"BTC-USD","isBuy":true,"price":100000,"size":0.1,"orderType":{"type":"limit","tif":"GTC"}}"#;
+
+    #[test]
+    fn test_init_from_secret_and_keystore() {
+        let _guard = serial();
+        let keypair = Keypair::generate();
+
+        init_from_vars(vars(&[(SECRET_KEY_ENV, &keypair.to_base58())])).unwrap();
+        assert_eq!(pubkey().unwrap(), keypair.pubkey());
+        let tx: crate::SignedTransaction = serde_json::from_str(&sign(ORDER).unwrap()).unwrap();
+        assert!(tx.verify().unwrap());
+        assert!(shutdown());
+
+        let path = std::env::temp_dir().join(format!("bulk-quick-{}.jwk", keypair.fingerprint()));
+        std::fs::write(&path, keypair.to_jwk().unwrap()).unwrap();
+        let keystore = path.to_str().unwrap();
+        init_from_vars(vars(&[(KEYSTORE_ENV, keystore)])).unwrap();
+        assert_eq!(pubkey().unwrap(), keypair.pubkey());
+        shutdown();
+
+        let encrypted = vars(&[(KEYSTORE_ENV, keystore), (KEYSTORE_PASSPHRASE_ENV, "pw")]);
+        assert!(init_from_vars(encrypted).is_err());
+        std::fs::remove_file(&path).unwrap();
+
+        let both = vars(&[(SECRET_KEY_ENV, "x"), (KEYSTORE_ENV, "y")]);
+        assert!(init_from_vars(both).is_err());
+        assert!(init_from_vars(vars(&[])).is_err());
+        assert!(!is_initialized());
+    }
+
+    #[test]
+    fn test_double_init_errors() {
+        let _guard = serial();
+        init(Keypair::generate()).unwrap();
+        let err = init(Keypair::generate()).unwrap_err();
+        assert!(err.to_string().contains("already initialized"));
+        shutdown();
+    }
+
+    #[test]
+    fn test_prepare_signs_as_agent() {
+        let _guard = serial();
+        let account = Keypair::generate().pubkey();
+        assert!(prepare(ORDER, &account.to_base58()).is_err());
+
+        let agent = Keypair::generate();
+        init(agent.clone()).unwrap();
+        let group = format!("[{},{}]", ORDER, ORDER);
+        let prepared: crate::PreparedMessage =
+            serde_json::from_str(&prepare(&group, &account.to_base58()).unwrap()).unwrap();
+        assert_eq!(prepared.actions.len(), 2);
+        assert_eq!(prepared.account, account.to_base58());
+        assert!(prepared
+            .sign_with_keypair(&agent)
+            .unwrap()
+            .verify()
+            .unwrap());
+        shutdown();
+    }
+
+    #[test]
+    fn test_shutdown_wipes_and_resets() {
+        fn zeroizes_on_drop<T: ZeroizeOnDrop>() {}
+        // `shutdown` relies on the key wiping itself when the signer drops.
+        zeroizes_on_drop::<ed25519_dalek::SigningKey>();
+
+        let _guard = serial();
+        assert!(!shutdown());
+        init(Keypair::generate()).unwrap();
+        assert!(shutdown());
+        assert!(!is_initialized());
+        assert!(sign(ORDER)
+            .unwrap_err()
+            .to_string()
+            .contains("not initialized"));
+        init(Keypair::generate()).unwrap();
+        shutdown();
+    }
+}
@@ -0,0 +1,124 @@
+//! Resetting signer state after a gateway reconnect
+//!
+//! After a reconnect the exchange may ask for recent transactions again.
+//! The signer's own bookkeeping would refuse some of those: orders still
+//! counted against the open-order cap, and client IDs still held by orders
+//! the exchange no longer has. [`Signer::notify_reconnect`](crate::Signer::notify_reconnect)
+//! clears what a [`ReconnectPolicy`] names and can raise the nonce floor so
+//! nothing signed afterwards reuses a nonce from before the reconnect.
+//!
+//! Both trackers are cleared under their locks at once, so a transaction
+//! signed concurrently is either cleared from both or from neither. The
+//! nonce floor is raised separately, before the clears.
+//!
+//! Each call is counted in [`SignerUsage::reconnects`](crate::SignerUsage::reconnects).
+
+use serde::{Deserialize, Serialize};
+
+/// What [`Signer::notify_reconnect`](crate::Signer::notify_reconnect) resets.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct ReconnectPolicy {
+    /// Forget the orders counted against the open-order cap, resetting
+    /// every symbol's count to zero, so resubmitted orders are not refused
+    /// as extra ones
+    pub clear_open_orders: bool,
+    /// Forget the client IDs held by live orders, so resubmitted orders can
+    /// carry theirs again
+    pub clear_dedupe: bool,
+    /// Raise auto nonces to at least the current time
+    /// (see [`NonceManager::bump_floor_to_now`](crate::NonceManager::bump_floor_to_now))
+    pub bump_nonce_floor: bool,
+}
+
+/// What a [`Signer::notify_reconnect`](crate::Signer::notify_reconnect) call changed.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReconnectReport {
+    /// Orders no longer counted against the open-order cap
+    pub open_orders_cleared: usize,
+    /// Client IDs released
+    pub client_ids_cleared: usize,
+    /// Lowest nonce the nonce manager issues from now on; `None` if it was
+    /// not bumped or the strategy has no order
+    pub nonce_floor: Option<u64>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Error, Hash, Keypair, NonceManager, Order, OrderItem, Signer, TimeInForce};
+
+    fn order(cid: u8) -> OrderItem {
+        Order::limit("BTC-USD", true, 100.0, 1.0, TimeInForce::Gtc)
+            .with_client_id(Hash::from_bytes([cid; 32]))
+            .into()
+    }
+
+    #[test]
+    fn test_resubmission_allowed_after_reconnect() {
+        let mut signer = Signer::new(Keypair::generate())
+            .with_unique_client_ids()
+            .with_max_open_orders_per_symbol(2);
+        signer.sign(order(1), Some(1)).unwrap();
+        signer.sign(order(2), Some(2)).unwrap();
+        assert!(matches!(
+            signer.sign(order(1), Some(3)),
+            Err(Error::ClientIdInUse { .. })
+        ));
+
+        // Clearing only the client IDs still leaves the cap full.
+        let policy = ReconnectPolicy {
+            clear_dedupe: true,
+            ..Default::default()
+        };
+        let report = signer.notify_reconnect(policy).unwrap();
+        assert_eq!(report.client_ids_cleared, 2);
+        assert_eq!(report.open_orders_cleared, 0);
+        assert!(signer.sign(order(1), Some(3)).is_err());
+
+        let policy = ReconnectPolicy {
+            clear_open_orders: true,
+            clear_dedupe: true,
+            bump_nonce_floor: false,
+        };
+        let report = signer.notify_reconnect(policy).unwrap();
+        assert_eq!(report.open_orders_cleared, 2);
+        assert_eq!(signer.open_order_count("BTC-USD"), 0);
+        signer.sign(order(1), Some(3)).unwrap();
+        signer.sign(order(2), Some(4)).unwrap();
+    }
+
+    #[test]
+    fn test_bump_nonce_floor() {
+        let now = 1_700_000_000_000;
+        let manager = NonceManager::counter().with_clock(move || now);
+        let mut signer = Signer::with_nonce_manager(Keypair::generate(), manager);
+        assert_eq!(signer.next_nonce().unwrap(), 0);
+
+        let policy = ReconnectPolicy {
+            bump_nonce_floor: true,
+            ..Default::default()
+        };
+        let report = signer.notify_reconnect(policy).unwrap();
+        assert_eq!(report.nonce_floor, Some(now));
+        let tx = signer.sign(order(1), None).unwrap();
+        assert_eq!(tx.nonce, now);
+        assert_eq!(signer.next_nonce().unwrap(), now + 1);
+
+        let hf = NonceManager::high_frequency().with_clock(move || now);
+        let signer = Signer::with_nonce_manager(Keypair::generate(), hf);
+        let report = signer.notify_reconnect(policy).unwrap();
+        assert_eq!(report.nonce_floor, Some(now * 1000));
+        assert!(signer.next_nonce().unwrap() >= now * 1000);
+    }
+
+    #[test]
+    fn test_reconnect_counted_once_per_call() {
+        let signer = Signer::new(Keypair::generate());
+        assert_eq!(signer.usage().reconnects, 0);
+        let report = signer.notify_reconnect(ReconnectPolicy::default()).unwrap();
+        assert_eq!(report, ReconnectReport::default());
+        assert_eq!(signer.usage().reconnects, 1);
+    }
+}
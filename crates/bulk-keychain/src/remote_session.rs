@@ -0,0 +1,607 @@
+//! Prepare/finalize over a string-only message channel.
+//!
+//! For setups where the app and the wallet live in separate contexts (iframes,
+//! processes) that can only exchange strings, e.g. via `postMessage`. The
+//! initiator wraps [`PreparedMessage`]s in [`SessionRequest`] envelopes; the
+//! responder signs them and answers with [`SessionResponse`]s.
+//!
+//! Every envelope carries the session id, a sequence number and an
+//! HMAC-SHA256 keyed by a shared session secret, so tampering, replays and
+//! reordering in transit are detected. Requests must be answered in order.
+
+use crate::compact::message_bytes_from_json;
+use crate::nonce::current_timestamp_millis;
+use crate::prepare::{finalize_transaction, PreparedMessage};
+use crate::types::{Pubkey, SignedTransaction};
+use crate::{DurationMs, Error, Result, Signer};
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use std::collections::VecDeque;
+
+/// MAC domain; separates session envelopes from any other use of the secret.
+const DOMAIN: &[u8] = b"bulk-keychain:remote-session:v1";
+const KIND_REQUEST: u8 = 0;
+const KIND_RESPONSE: u8 = 1;
+
+/// Shortest accepted session secret, in bytes.
+pub const MIN_SECRET_LEN: usize = 16;
+
+/// How long a request may stay unanswered before it is dropped.
+pub const DEFAULT_TIMEOUT_MS: u64 = 60_000;
+
+/// Initiator → responder envelope carrying a prepared message.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionRequest {
+    pub session_id: String,
+    pub seq: u64,
+    /// Initiator clock when the request was sent
+    pub issued_at_ms: u64,
+    /// JSON-encoded [`PreparedMessage`]
+    pub payload: String,
+    /// Hex HMAC-SHA256 over every other field
+    pub mac: String,
+}
+
+/// Responder → initiator envelope carrying a signature or a rejection.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionResponse {
+    pub session_id: String,
+    /// Sequence number of the request being answered
+    pub seq: u64,
+    /// JSON-encoded [`SessionReply`]
+    pub payload: String,
+    /// Hex HMAC-SHA256 over every other field
+    pub mac: String,
+}
+
+/// Outcome of a request, as carried in [`SessionResponse::payload`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "status", rename_all = "camelCase")]
+pub enum SessionReply {
+    /// Base58 signature over the prepared message bytes
+    Signed { signature: String },
+    /// The responder declined to sign
+    Rejected { reason: String },
+}
+
+macro_rules! envelope_json {
+    ($ty:ty) => {
+        impl $ty {
+            /// Encode for the message channel.
+            pub fn to_json(&self) -> Result<String> {
+                serde_json::to_string(self).map_err(Error::from)
+            }
+
+            /// Decode from the message channel. The MAC is not checked here.
+            pub fn from_json(s: &str) -> Result<Self> {
+                serde_json::from_str(s).map_err(|e| session_err(format!("malformed envelope: {e}")))
+            }
+        }
+    };
+}
+
+envelope_json!(SessionRequest);
+envelope_json!(SessionResponse);
+
+fn session_err(msg: impl Into<String>) -> Error {
+    Error::RemoteSession(msg.into())
+}
+
+fn new_mac(
+    secret: &[u8],
+    kind: u8,
+    session_id: &str,
+    seq: u64,
+    issued_at_ms: u64,
+    payload: &str,
+) -> Hmac<Sha256> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret).expect("HMAC accepts any key length");
+    mac.update(DOMAIN);
+    mac.update(&[kind]);
+    mac.update(&(session_id.len() as u64).to_le_bytes());
+    mac.update(session_id.as_bytes());
+    mac.update(&seq.to_le_bytes());
+    mac.update(&issued_at_ms.to_le_bytes());
+    mac.update(payload.as_bytes());
+    mac
+}
+
+fn sign_envelope(
+    secret: &[u8],
+    kind: u8,
+    session_id: &str,
+    seq: u64,
+    issued_at_ms: u64,
+    payload: &str,
+) -> String {
+    hex::encode(
+        new_mac(secret, kind, session_id, seq, issued_at_ms, payload)
+            .finalize()
+            .into_bytes(),
+    )
+}
+
+fn verify_envelope(
+    secret: &[u8],
+    kind: u8,
+    session_id: &str,
+    seq: u64,
+    issued_at_ms: u64,
+    payload: &str,
+    tag: &str,
+) -> Result<()> {
+    let tag = hex::decode(tag).map_err(|_| session_err("envelope MAC is not hex"))?;
+    new_mac(secret, kind, session_id, seq, issued_at_ms, payload)
+        .verify_slice(&tag)
+        .map_err(|_| session_err("envelope MAC mismatch"))
+}
+
+fn check_secret(secret: &[u8]) -> Result<Vec<u8>> {
+    if secret.len() < MIN_SECRET_LEN {
+        return Err(session_err(format!(
+            "session secret must be at least {MIN_SECRET_LEN} bytes, got {}",
+            secret.len()
+        )));
+    }
+    Ok(secret.to_vec())
+}
+
+/// Entry points for the two ends of a session.
+pub struct RemoteSession;
+
+impl RemoteSession {
+    /// Start the app side of a session, under a fresh random session id.
+    pub fn initiator(secret: &[u8]) -> Result<SessionInitiator> {
+        Ok(SessionInitiator {
+            secret: check_secret(secret)?,
+            session_id: hex::encode(rand::random::<[u8; 16]>()),
+            next_seq: 0,
+            timeout_ms: DEFAULT_TIMEOUT_MS,
+            pending: VecDeque::new(),
+        })
+    }
+
+    /// Start the wallet side of a session. It binds to the session id of the
+    /// first authentic request it sees.
+    pub fn responder(
+        secret: &[u8],
+        backend: impl Into<ResponderBackend>,
+    ) -> Result<SessionResponder> {
+        Ok(SessionResponder {
+            secret: check_secret(secret)?,
+            backend: backend.into(),
+            session_id: None,
+            next_seq: 0,
+            timeout_ms: DEFAULT_TIMEOUT_MS,
+        })
+    }
+}
+
+struct Pending {
+    seq: u64,
+    issued_at_ms: u64,
+    prepared: PreparedMessage,
+}
+
+/// App side: sends prepared messages, turns responses into signed transactions.
+pub struct SessionInitiator {
+    secret: Vec<u8>,
+    session_id: String,
+    next_seq: u64,
+    timeout_ms: u64,
+    pending: VecDeque<Pending>,
+}
+
+impl SessionInitiator {
+    /// Drop requests that go unanswered for longer than `timeout_ms`.
+    pub fn with_timeout_ms(mut self, timeout: impl Into<DurationMs>) -> Self {
+        self.timeout_ms = timeout.into().as_millis();
+        self
+    }
+
+    /// Id shared by every envelope of this session.
+    pub fn session_id(&self) -> &str {
+        &self.session_id
+    }
+
+    /// Number of requests awaiting a response.
+    pub fn pending_count(&self) -> usize {
+        self.pending.len()
+    }
+
+    /// Wrap `prepared` in a request envelope, ready for the channel.
+    pub fn request(&mut self, prepared: PreparedMessage) -> Result<String> {
+        let seq = self.next_seq;
+        let issued_at_ms = current_timestamp_millis();
+        let payload = serde_json::to_string(&prepared)?;
+        let mac = sign_envelope(
+            &self.secret,
+            KIND_REQUEST,
+            &self.session_id,
+            seq,
+            issued_at_ms,
+            &payload,
+        );
+        let envelope = SessionRequest {
+            session_id: self.session_id.clone(),
+            seq,
+            issued_at_ms,
+            payload,
+            mac,
+        }
+        .to_json()?;
+
+        self.next_seq += 1;
+        self.pending.push_back(Pending {
+            seq,
+            issued_at_ms,
+            prepared,
+        });
+        Ok(envelope)
+    }
+
+    /// Check a response envelope and finalize the request it answers.
+    ///
+    /// Responses must arrive in request order; a tampered, foreign, replayed or
+    /// out-of-order envelope is rejected and leaves the pending queue intact.
+    /// The returned signature is verified against the prepared signer.
+    pub fn handle_response(&mut self, envelope: &str) -> Result<SignedTransaction> {
+        let response = SessionResponse::from_json(envelope)?;
+        if response.session_id != self.session_id {
+            return Err(session_err("response belongs to another session"));
+        }
+        verify_envelope(
+            &self.secret,
+            KIND_RESPONSE,
+            &response.session_id,
+            response.seq,
+            0,
+            &response.payload,
+            &response.mac,
+        )?;
+
+        let expected =
+            self.pending.front().map(|p| p.seq).ok_or_else(|| {
+                session_err(format!("unexpected response for seq {}", response.seq))
+            })?;
+        if response.seq != expected {
+            return Err(if self.pending.iter().any(|p| p.seq == response.seq) {
+                session_err(format!(
+                    "out-of-order response: expected seq {expected}, got {}",
+                    response.seq
+                ))
+            } else {
+                session_err(format!("unexpected response for seq {}", response.seq))
+            });
+        }
+        let pending = self.pending.pop_front().expect("front checked above");
+
+        let age_ms = current_timestamp_millis().saturating_sub(pending.issued_at_ms);
+        if age_ms > self.timeout_ms {
+            return Err(session_err(format!(
+                "request {} timed out after {age_ms}ms",
+                pending.seq
+            )));
+        }
+
+        let reply: SessionReply = serde_json::from_str(&response.payload)?;
+        let signature = match reply {
+            SessionReply::Signed { signature } => signature,
+            SessionReply::Rejected { reason } => {
+                return Err(session_err(format!("rejected by responder: {reason}")))
+            }
+        };
+        verify_signature(&pending.prepared, &signature)?;
+        finalize_transaction(pending.prepared, &signature)
+    }
+
+    /// Drop requests older than the timeout as of `now_ms`; returns their sequence numbers.
+    /// Late responses to them are then rejected.
+    pub fn expire(&mut self, now_ms: u64) -> Vec<u64> {
+        let timeout_ms = self.timeout_ms;
+        let mut expired = Vec::new();
+        self.pending.retain(|p| {
+            let keep = now_ms.saturating_sub(p.issued_at_ms) <= timeout_ms;
+            if !keep {
+                expired.push(p.seq);
+            }
+            keep
+        });
+        expired
+    }
+}
+
+fn verify_signature(prepared: &PreparedMessage, signature: &str) -> Result<()> {
+    let signer = Pubkey::from_base58(&prepared.signer)?;
+    let bytes = bs58::decode(signature)
+        .into_vec()
+        .map_err(|e| Error::InvalidBase58(e.to_string()))?;
+    let bytes: [u8; 64] = bytes
+        .as_slice()
+        .try_into()
+        .map_err(|_| Error::InvalidSignatureLength(bytes.len()))?;
+    let key = ed25519_dalek::VerifyingKey::from_bytes(signer.as_bytes())
+        .map_err(|e| session_err(format!("invalid signer key: {e}")))?;
+    key.verify_strict(
+        &prepared.message_bytes,
+        &ed25519_dalek::Signature::from_bytes(&bytes),
+    )
+    .map_err(|_| session_err("signature does not verify against the prepared signer"))
+}
+
+/// Wallet-side signing callback: returns a base58 signature over
+/// `message_bytes`, or `Err` to reject the request.
+pub type SignCallback = dyn FnMut(&PreparedMessage) -> Result<String>;
+
+/// Signs requests on the wallet side: a local [`Signer`] or a callback
+/// returning a base58 signature (e.g. forwarding to a hardware wallet).
+pub enum ResponderBackend {
+    Signer(Box<Signer>),
+    Callback(Box<SignCallback>),
+}
+
+impl ResponderBackend {
+    /// Sign with `f`; an `Err` is sent back as a rejection.
+    pub fn callback(f: impl FnMut(&PreparedMessage) -> Result<String> + 'static) -> Self {
+        Self::Callback(Box::new(f))
+    }
+
+    fn sign(&mut self, prepared: &PreparedMessage) -> Result<String> {
+        match self {
+            Self::Signer(signer) => {
+                if prepared.signer != signer.signer_pubkey().to_base58() {
+                    return Err(session_err(format!(
+                        "request is for signer {}, not this wallet",
+                        crate::error::fingerprint(&prepared.signer)
+                    )));
+                }
+                signer.try_sign_bytes(&prepared.message_bytes)
+            }
+            Self::Callback(f) => f(prepared),
+        }
+    }
+}
+
+impl From<Signer> for ResponderBackend {
+    fn from(signer: Signer) -> Self {
+        Self::Signer(Box::new(signer))
+    }
+}
+
+/// Wallet side: checks request envelopes and answers each with a signature or rejection.
+pub struct SessionResponder {
+    secret: Vec<u8>,
+    backend: ResponderBackend,
+    session_id: Option<String>,
+    next_seq: u64,
+    timeout_ms: u64,
+}
+
+impl SessionResponder {
+    /// Refuse to sign requests issued more than `timeout_ms` ago.
+    pub fn with_timeout_ms(mut self, timeout: impl Into<DurationMs>) -> Self {
+        self.timeout_ms = timeout.into().as_millis();
+        self
+    }
+
+    /// Session id this responder is bound to, once the first request arrived.
+    pub fn session_id(&self) -> Option<&str> {
+        self.session_id.as_deref()
+    }
+
+    /// Check a request envelope and return the response envelope to send back.
+    ///
+    /// Tampered, foreign, replayed or out-of-order envelopes are `Err` and get
+    /// no response. Stale requests, messages whose bytes do not match their
+    /// actions, and backend failures are answered with a rejection.
+    pub fn handle_request(&mut self, envelope: &str) -> Result<String> {
+        let request = SessionRequest::from_json(envelope)?;
+        verify_envelope(
+            &self.secret,
+            KIND_REQUEST,
+            &request.session_id,
+            request.seq,
+            request.issued_at_ms,
+            &request.payload,
+            &request.mac,
+        )?;
+        match &self.session_id {
+            Some(id) if *id != request.session_id => {
+                return Err(session_err("request belongs to another session"))
+            }
+            Some(_) => {}
+            None => self.session_id = Some(request.session_id.clone()),
+        }
+        if request.seq != self.next_seq {
+            return Err(session_err(format!(
+                "out-of-order request: expected seq {}, got {}",
+                self.next_seq, request.seq
+            )));
+        }
+        self.next_seq += 1;
+
+        let reply = match self.sign_request(&request) {
+            Ok(signature) => SessionReply::Signed { signature },
+            Err(e) => SessionReply::Rejected {
+                reason: e.to_string(),
+            },
+        };
+        let payload = serde_json::to_string(&reply)?;
+        let mac = sign_envelope(
+            &self.secret,
+            KIND_RESPONSE,
+            &request.session_id,
+            request.seq,
+            0,
+            &payload,
+        );
+        SessionResponse {
+            session_id: request.session_id,
+            seq: request.seq,
+            payload,
+            mac,
+        }
+        .to_json()
+    }
+
+    fn sign_request(&mut self, request: &SessionRequest) -> Result<String> {
+        let age_ms = current_timestamp_millis().saturating_sub(request.issued_at_ms);
+        if age_ms > self.timeout_ms {
+            return Err(session_err(format!("request expired {age_ms}ms ago")));
+        }
+        let prepared: PreparedMessage = serde_json::from_str(&request.payload)?;
+        let account = Pubkey::from_base58(&prepared.account)?;
+        if message_bytes_from_json(&prepared.actions, prepared.nonce, &account)?
+            != prepared.message_bytes
+        {
+            return Err(session_err("message bytes do not match the listed actions"));
+        }
+        self.backend.sign(&prepared)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{prepare_message, Keypair, Order, TimeInForce};
+    use std::sync::mpsc;
+
+    const SECRET: &[u8] = b"0123456789abcdef-shared-secret";
+
+    fn prepared(keypair: &Keypair, nonce: u64) -> PreparedMessage {
+        let order = Order::limit("BTC-USD", true, 100000.0, 0.1, TimeInForce::Gtc);
+        prepare_message(order.into(), &keypair.pubkey(), None, Some(nonce)).unwrap()
+    }
+
+    #[test]
+    fn test_full_session_over_channel() {
+        let keypair = Keypair::generate();
+        let (to_wallet, wallet_rx) = mpsc::channel::<String>();
+        let (to_app, app_rx) = mpsc::channel::<String>();
+
+        let mut app = RemoteSession::initiator(SECRET).unwrap();
+        let mut wallet = RemoteSession::responder(SECRET, Signer::new(keypair.clone())).unwrap();
+
+        for nonce in [1, 2, 3] {
+            to_wallet
+                .send(app.request(prepared(&keypair, nonce)).unwrap())
+                .unwrap();
+        }
+        assert_eq!(app.pending_count(), 3);
+        for envelope in wallet_rx.try_iter() {
+            to_app
+                .send(wallet.handle_request(&envelope).unwrap())
+                .unwrap();
+        }
+        assert_eq!(wallet.session_id(), Some(app.session_id()));
+
+        let verifier = Signer::new(keypair);
+        let signed: Vec<_> = app_rx
+            .try_iter()
+            .map(|envelope| app.handle_response(&envelope).unwrap())
+            .collect();
+        assert_eq!(
+            signed.iter().map(|tx| tx.nonce).collect::<Vec<_>>(),
+            vec![1, 2, 3]
+        );
+        for tx in &signed {
+            assert!(verifier.verify_signed_transaction(tx, None).unwrap());
+        }
+        assert_eq!(app.pending_count(), 0);
+    }
+
+    #[test]
+    fn test_tampered_envelopes_are_rejected() {
+        let keypair = Keypair::generate();
+        let mut app = RemoteSession::initiator(SECRET).unwrap();
+        let mut wallet = RemoteSession::responder(SECRET, Signer::new(keypair.clone())).unwrap();
+
+        // Payload edited in transit: MAC no longer matches.
+        let envelope = app.request(prepared(&keypair, 1)).unwrap();
+        let mut request = SessionRequest::from_json(&envelope).unwrap();
+        request.payload = request.payload.replace("\"nonce\":1", "\"nonce\":9");
+        assert!(matches!(
+            wallet.handle_request(&request.to_json().unwrap()),
+            Err(Error::RemoteSession(_))
+        ));
+
+        // Wrong secret on the other end.
+        let mut stranger =
+            RemoteSession::responder(b"another-secret-of-16+", Signer::new(keypair.clone()))
+                .unwrap();
+        assert!(stranger.handle_request(&envelope).is_err());
+
+        // Untouched request still goes through; then tamper with the response.
+        let response = wallet.handle_request(&envelope).unwrap();
+        let mut tampered = SessionResponse::from_json(&response).unwrap();
+        tampered.payload = r#"{"status":"signed","signature":"1111"}"#.to_string();
+        assert!(app.handle_response(&tampered.to_json().unwrap()).is_err());
+        assert_eq!(app.pending_count(), 1);
+        assert!(app.handle_response(&response).is_ok());
+
+        // Replaying a handled request is refused.
+        assert!(wallet.handle_request(&envelope).is_err());
+    }
+
+    #[test]
+    fn test_out_of_order_response() {
+        let keypair = Keypair::generate();
+        let mut app = RemoteSession::initiator(SECRET).unwrap();
+        let mut wallet = RemoteSession::responder(SECRET, Signer::new(keypair.clone())).unwrap();
+
+        let first = wallet
+            .handle_request(&app.request(prepared(&keypair, 1)).unwrap())
+            .unwrap();
+        let second = wallet
+            .handle_request(&app.request(prepared(&keypair, 2)).unwrap())
+            .unwrap();
+
+        let err = app.handle_response(&second).unwrap_err().to_string();
+        assert!(err.contains("out-of-order"), "{err}");
+        assert_eq!(app.handle_response(&first).unwrap().nonce, 1);
+        assert_eq!(app.handle_response(&second).unwrap().nonce, 2);
+        assert!(app.handle_response(&second).is_err());
+    }
+
+    #[test]
+    fn test_rejections_and_timeouts() {
+        let keypair = Keypair::generate();
+        let mut app = RemoteSession::initiator(SECRET)
+            .unwrap()
+            .with_timeout_ms(1_000);
+        let mut wallet = RemoteSession::responder(
+            SECRET,
+            ResponderBackend::callback(|_| Err(Error::PreSignRejected("user declined".into()))),
+        )
+        .unwrap();
+
+        let response = wallet
+            .handle_request(&app.request(prepared(&keypair, 1)).unwrap())
+            .unwrap();
+        let err = app.handle_response(&response).unwrap_err().to_string();
+        assert!(err.contains("user declined"), "{err}");
+
+        // A request for another key is declined by a signer-backed wallet.
+        let mut other = RemoteSession::responder(SECRET, Signer::new(Keypair::generate())).unwrap();
+        let mut app2 = RemoteSession::initiator(SECRET).unwrap();
+        let response = other
+            .handle_request(&app2.request(prepared(&keypair, 1)).unwrap())
+            .unwrap();
+        let err = app2.handle_response(&response).unwrap_err().to_string();
+        assert!(err.contains(&keypair.fingerprint()), "{err}");
+        assert!(!err.contains(&keypair.pubkey().to_base58()), "{err}");
+
+        // Unanswered requests expire and late responses are refused.
+        let late = wallet
+            .handle_request(&app.request(prepared(&keypair, 2)).unwrap())
+            .unwrap();
+        assert_eq!(app.expire(current_timestamp_millis() + 1_001), vec![1]);
+        let err = app.handle_response(&late).unwrap_err().to_string();
+        assert!(err.contains("unexpected response"), "{err}");
+
+        assert!(RemoteSession::initiator(b"short").is_err());
+    }
+}
@@ -0,0 +1,245 @@
+//! Nonce reservations, so order IDs can be shown before signing
+//!
+//! [`Signer::peek_order_ids`](crate::Signer::peek_order_ids) with
+//! [`NoncePlan::Reserve`] sets aside one nonce per item and returns the order
+//! IDs the batch will have; [`Signer::sign_all_reserved`](crate::Signer::sign_all_reserved)
+//! later signs the same items at exactly those nonces, so the IDs match.
+//!
+//! Reservations made through one signer (and signers sharing its state)
+//! never overlap. Each is single-use and lapses after a TTL
+//! ([`DEFAULT_RESERVATION_TTL_MS`] unless set with
+//! [`Signer::with_reservation_ttl`](crate::Signer::with_reservation_ttl));
+//! lapsed nonces are not handed out again. Batches signed at the current
+//! time without a reservation are not kept out of reserved ranges, just as
+//! two such batches in the same millisecond are not kept apart.
+
+use crate::time::{DurationMs, TimestampMs};
+use crate::types::Hash;
+use crate::{Error, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+/// How long a reservation holds by default.
+pub const DEFAULT_RESERVATION_TTL_MS: u64 = 30_000;
+
+/// Nonces for [`Signer::peek_order_ids`](crate::Signer::peek_order_ids).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NoncePlan {
+    /// Item `i` signs at `base + i`; the caller keeps the nonces apart
+    Explicit(u64),
+    /// Reserve fresh nonces now, for [`Signer::sign_all_reserved`](crate::Signer::sign_all_reserved)
+    Reserve,
+}
+
+/// Nonces `base_nonce..base_nonce + count` set aside for one batch.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NonceReservation {
+    pub id: u64,
+    pub base_nonce: u64,
+    pub count: usize,
+    pub expires_at: TimestampMs,
+    /// Digest of the peeked items; signing other items is refused
+    pub items_hash: Hash,
+}
+
+/// Order IDs a batch will get, and the reservation that pins them.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PeekedOrderIds {
+    /// One per item; `None` for items without an order ID, such as cancels
+    pub order_ids: Vec<Option<Hash>>,
+    /// Present for [`NoncePlan::Reserve`]
+    pub reservation: Option<NonceReservation>,
+}
+
+#[derive(Debug, Default)]
+struct State {
+    next_id: u64,
+    /// Lowest nonce no reservation has covered
+    next_free: u64,
+    live: HashMap<u64, NonceReservation>,
+}
+
+/// Live reservations of a signer.
+#[derive(Debug, Default)]
+pub(crate) struct NonceReservations {
+    state: Mutex<State>,
+}
+
+impl NonceReservations {
+    /// Reserve `count` nonces at or above `floor` and above every earlier
+    /// reservation.
+    pub(crate) fn reserve(
+        &self,
+        floor: u64,
+        count: usize,
+        items_hash: Hash,
+        now: TimestampMs,
+        ttl: DurationMs,
+    ) -> NonceReservation {
+        let mut state = self.lock();
+        state.live.retain(|_, r| r.expires_at > now);
+        let base_nonce = floor.max(state.next_free);
+        state.next_free = base_nonce.saturating_add(count as u64);
+        state.next_id += 1;
+        let reservation = NonceReservation {
+            id: state.next_id,
+            base_nonce,
+            count,
+            expires_at: now + ttl,
+            items_hash,
+        };
+        state.live.insert(reservation.id, reservation.clone());
+        reservation
+    }
+
+    /// Use up `reservation` for signing `items_hash` at `now`.
+    pub(crate) fn take(
+        &self,
+        reservation: &NonceReservation,
+        items_hash: Hash,
+        now: TimestampMs,
+    ) -> Result<()> {
+        let refuse = |reason: &str| Error::Reservation {
+            id: reservation.id,
+            reason: reason.to_string(),
+        };
+        let mut state = self.lock();
+        match state.live.get(&reservation.id) {
+            None => return Err(refuse("unknown or already used")),
+            Some(live) if live != reservation => {
+                return Err(refuse("does not match the reservation issued"))
+            }
+            Some(live) if live.items_hash != items_hash => {
+                return Err(refuse("items differ from the ones peeked"))
+            }
+            Some(_) => {}
+        }
+        state.live.remove(&reservation.id);
+        if reservation.expires_at <= now {
+            return Err(refuse("expired"));
+        }
+        Ok(())
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, State> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Error, Keypair, NonceManager, Order, OrderItem, Signer};
+    use std::sync::atomic::{AtomicU64, Ordering};
+    use std::sync::Arc;
+
+    fn orders(n: usize) -> Vec<OrderItem> {
+        (0..n)
+            .map(|i| Order::market("BTC-USD", i % 2 == 0, 0.1 + i as f64).into())
+            .collect()
+    }
+
+    fn signed_ids(txs: &[crate::SignedTransaction]) -> Vec<Option<Hash>> {
+        txs.iter().map(|tx| tx.order_id_hash().unwrap()).collect()
+    }
+
+    #[test]
+    fn test_peek_then_sign_ids_match() {
+        let signer = Signer::new(Keypair::generate());
+        let mut items = orders(3);
+        items.push(crate::Cancel::new("BTC-USD", Hash::random()).into());
+
+        let peeked = signer.peek_order_ids(&items, NoncePlan::Reserve).unwrap();
+        let reservation = peeked.reservation.clone().unwrap();
+        assert_eq!(reservation.count, 4);
+        assert_eq!(peeked.order_ids[3], None);
+
+        let txs = signer
+            .sign_all_reserved(items.clone(), &reservation)
+            .unwrap();
+        assert_eq!(signed_ids(&txs), peeked.order_ids);
+        assert_eq!(txs[0].nonce, reservation.base_nonce);
+
+        // Single use.
+        assert!(matches!(
+            signer.sign_all_reserved(items.clone(), &reservation),
+            Err(Error::Reservation { .. })
+        ));
+
+        let explicit = signer
+            .peek_order_ids(&items, NoncePlan::Explicit(7))
+            .unwrap();
+        assert_eq!(explicit.reservation, None);
+        let txs = signer.sign_all(items, Some(7)).unwrap();
+        assert_eq!(signed_ids(&txs), explicit.order_ids);
+    }
+
+    #[test]
+    fn test_reservation_expires() {
+        let now = Arc::new(AtomicU64::new(1_700_000_000_000));
+        let clock = now.clone();
+        let manager = NonceManager::timestamp().with_clock(move || clock.load(Ordering::SeqCst));
+        let signer = Signer::with_nonce_manager(Keypair::generate(), manager)
+            .with_reservation_ttl(DurationMs(1_000));
+
+        let items = orders(2);
+        let fresh = signer.peek_order_ids(&items, NoncePlan::Reserve).unwrap();
+        let stale = signer.peek_order_ids(&items, NoncePlan::Reserve).unwrap();
+        now.fetch_add(999, Ordering::SeqCst);
+        signer
+            .sign_all_reserved(items.clone(), fresh.reservation.as_ref().unwrap())
+            .unwrap();
+        now.fetch_add(1, Ordering::SeqCst);
+        let err = signer
+            .sign_all_reserved(items.clone(), stale.reservation.as_ref().unwrap())
+            .unwrap_err();
+        assert!(err.to_string().contains("expired"), "{err}");
+
+        // Lapsed nonces are not reissued.
+        let next = signer.peek_order_ids(&items, NoncePlan::Reserve).unwrap();
+        assert!(
+            next.reservation.unwrap().base_nonce
+                >= stale.reservation.unwrap().base_nonce + items.len() as u64
+        );
+    }
+
+    #[test]
+    fn test_reservation_refuses_other_items() {
+        let signer = Signer::new(Keypair::generate());
+        let peeked = signer
+            .peek_order_ids(&orders(2), NoncePlan::Reserve)
+            .unwrap();
+        let err = signer
+            .sign_all_reserved(orders(3), peeked.reservation.as_ref().unwrap())
+            .unwrap_err();
+        assert!(err.to_string().contains("items differ"), "{err}");
+    }
+
+    #[test]
+    fn test_concurrent_reservations_do_not_overlap() {
+        let signer = Signer::new(Keypair::generate());
+        let items = orders(5);
+        let mut ranges: Vec<(u64, u64)> = std::thread::scope(|s| {
+            let handles: Vec<_> = (0..8)
+                .map(|_| {
+                    let (signer, items) = (&signer, &items);
+                    s.spawn(move || {
+                        let r = signer
+                            .peek_order_ids(items, NoncePlan::Reserve)
+                            .unwrap()
+                            .reservation
+                            .unwrap();
+                        (r.base_nonce, r.base_nonce + r.count as u64)
+                    })
+                })
+                .collect();
+            handles.into_iter().map(|h| h.join().unwrap()).collect()
+        });
+        ranges.sort();
+        for pair in ranges.windows(2) {
+            assert!(pair[0].1 <= pair[1].0, "{ranges:?}");
+        }
+    }
+}
@@ -0,0 +1,153 @@
+//! One source for the randomness a signer uses
+//!
+//! Client IDs from [`Signer::random_client_id`](crate::Signer::random_client_id)
+//! and the seeds behind [`Signer::shuffle_batch`](crate::Signer::shuffle_batch)
+//! and [`Signer::jitter_sizes`](crate::Signer::jitter_sizes) come from the
+//! signer's [`RngProvider`]. The default draws from the OS RNG; a seeded one
+//! (via [`Signer::with_rng`](crate::Signer::with_rng)) replays a simulation
+//! exactly.
+//!
+//! Keys are never generated from a provider: [`Keypair::generate`](crate::Keypair::generate)
+//! always uses the OS RNG, and a reproducible key takes an explicit
+//! [`Keypair::generate_with_rng`](crate::Keypair::generate_with_rng).
+//!
+//! ```rust
+//! use bulk_keychain::{Keypair, Signer};
+//! use rand::{rngs::StdRng, SeedableRng};
+//!
+//! let keypair = Keypair::generate();
+//! let a = Signer::new(keypair.clone()).with_rng(StdRng::seed_from_u64(7));
+//! let b = Signer::new(keypair).with_rng(StdRng::seed_from_u64(7));
+//! assert_eq!(a.random_client_id(), b.random_client_id());
+//! ```
+
+use crate::types::Hash;
+use rand::rngs::{OsRng, StdRng};
+use rand::{RngCore, SeedableRng};
+use std::fmt;
+use std::sync::{Arc, Mutex};
+
+/// Where a signer's randomness comes from. Clones share one stream.
+#[derive(Clone, Default)]
+pub struct RngProvider {
+    /// `None` is the OS RNG
+    seeded: Option<Arc<Mutex<dyn RngCore + Send>>>,
+}
+
+impl RngProvider {
+    /// The OS RNG, the default.
+    pub fn os() -> Self {
+        Self::default()
+    }
+
+    /// Draw from `rng`, e.g. a seeded `StdRng`. For tests and simulations.
+    pub fn from_rng(rng: impl RngCore + Send + 'static) -> Self {
+        Self {
+            seeded: Some(Arc::new(Mutex::new(rng))),
+        }
+    }
+
+    /// A `StdRng` seeded with `seed`.
+    pub fn seeded(seed: u64) -> Self {
+        Self::from_rng(StdRng::seed_from_u64(seed))
+    }
+
+    /// Whether this replaces the OS RNG.
+    pub fn is_deterministic(&self) -> bool {
+        self.seeded.is_some()
+    }
+
+    pub fn fill_bytes(&self, dest: &mut [u8]) {
+        match &self.seeded {
+            Some(rng) => rng
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .fill_bytes(dest),
+            None => OsRng.fill_bytes(dest),
+        }
+    }
+
+    pub fn next_u64(&self) -> u64 {
+        let mut bytes = [0u8; 8];
+        self.fill_bytes(&mut bytes);
+        u64::from_le_bytes(bytes)
+    }
+
+    /// A random 32-byte hash, e.g. a client order ID.
+    pub fn random_hash(&self) -> Hash {
+        let mut bytes = [0u8; 32];
+        self.fill_bytes(&mut bytes);
+        Hash::from_bytes(bytes)
+    }
+}
+
+impl fmt::Debug for RngProvider {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(if self.is_deterministic() {
+            "RngProvider(seeded)"
+        } else {
+            "RngProvider(os)"
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Keypair, Order, OrderItem, Signer, TimeInForce};
+
+    fn batch() -> Vec<Order> {
+        (0..8)
+            .map(|i| {
+                Order::limit(
+                    "BTC-USD",
+                    i % 2 == 0,
+                    100.0,
+                    1.0 + i as f64,
+                    TimeInForce::Gtc,
+                )
+            })
+            .collect()
+    }
+
+    fn run(signer: &Signer) -> (Vec<Hash>, Vec<f64>, Vec<String>) {
+        let ids = (0..4).map(|_| signer.random_client_id()).collect();
+        let mut orders = batch();
+        signer.jitter_sizes(&mut orders, 0.2).unwrap();
+        let sizes = orders.iter().map(|o| o.size).collect();
+        let items: Vec<OrderItem> = orders.into_iter().map(Into::into).collect();
+        let mut txs = signer.sign_all(items, Some(1)).unwrap();
+        signer.shuffle_batch(&mut txs);
+        (ids, sizes, txs.into_iter().map(|tx| tx.signature).collect())
+    }
+
+    #[test]
+    fn test_same_seed_same_choices() {
+        let keypair = Keypair::generate();
+        let a = Signer::new(keypair.clone()).with_rng(StdRng::seed_from_u64(42));
+        let b = Signer::new(keypair.clone()).with_rng(StdRng::seed_from_u64(42));
+        assert!(a.rng().is_deterministic());
+        assert_eq!(run(&a), run(&b));
+
+        let c = Signer::new(keypair).with_rng(StdRng::seed_from_u64(43));
+        assert_ne!(run(&c).0, run(&b).0);
+    }
+
+    #[test]
+    fn test_unseeded_uses_os_rng() {
+        let keypair = Keypair::generate();
+        let a = Signer::new(keypair.clone());
+        let b = Signer::new(keypair);
+        assert!(!a.rng().is_deterministic());
+        assert_ne!(run(&a).0, run(&b).0);
+        assert_ne!(Hash::random(), Hash::random());
+    }
+
+    #[test]
+    fn test_keypair_only_from_explicit_rng() {
+        let a = Keypair::generate_with_rng(&mut StdRng::seed_from_u64(1));
+        let b = Keypair::generate_with_rng(&mut StdRng::seed_from_u64(1));
+        assert_eq!(a.pubkey(), b.pubkey());
+        assert_ne!(Keypair::generate().pubkey(), Keypair::generate().pubkey());
+    }
+}
@@ -0,0 +1,509 @@
+//! JSON Schemas for the JSON the keychain reads and writes
+//!
+//! Non-JS clients building payloads (Go, Python, ...) can validate against
+//! these instead of reverse-engineering the TypeScript types. Each schema is
+//! a self-contained JSON Schema (draft 2020-12) document:
+//!
+//! ```rust
+//! use bulk_keychain::schemas::json_schema_for;
+//!
+//! let schema = json_schema_for("OrderSpec").unwrap();
+//! assert_eq!(schema["properties"]["isBuy"]["type"], "boolean");
+//! assert!(json_schema_for("Nope").is_none());
+//! ```
+//!
+//! Input schemas describe the canonical camelCase form; tolerant input mode
+//! also accepts snake_case names. [`bundle`] gathers every schema under
+//! [`SCHEMA_VERSION`]; `cargo run -p bulk-keychain --example schema_dump`
+//! writes it out. The bundle is committed at `schemas/bundle.json` next to
+//! shared test vectors in `schemas/vectors.json`.
+
+use crate::lint::LintCode;
+use serde_json::{json, Value};
+
+/// Bumped whenever a schema changes shape.
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// Names accepted by [`json_schema_for`].
+pub const SCHEMA_NAMES: &[&str] = &[
+    "OrderSpec",
+    "OrderTypeInput",
+    "PrepareOptions",
+    "UserSettings",
+    "SignedTransaction",
+    "PreparedMessage",
+];
+
+const DIALECT: &str = "https://json-schema.org/draft/2020-12/schema";
+
+/// Schema for `name` (one of [`SCHEMA_NAMES`]), or `None`.
+pub fn json_schema_for(name: &str) -> Option<Value> {
+    let body = match name {
+        "OrderSpec" => order_spec(),
+        "OrderTypeInput" => order_type_input(),
+        "PrepareOptions" => prepare_options(),
+        "UserSettings" => user_settings(),
+        "SignedTransaction" => signed_transaction(),
+        "PreparedMessage" => prepared_message(),
+        _ => return None,
+    };
+    let mut schema = json!({
+        "$schema": DIALECT,
+        "$id": format!("urn:bulk-keychain:schema:v{}:{}", SCHEMA_VERSION, name),
+        "title": name,
+    });
+    if let (Value::Object(schema), Value::Object(body)) = (&mut schema, body) {
+        schema.extend(body);
+    }
+    Some(schema)
+}
+
+/// Every schema, keyed by name, with the bundle's [`SCHEMA_VERSION`].
+pub fn bundle() -> Value {
+    let schemas: serde_json::Map<String, Value> = SCHEMA_NAMES
+        .iter()
+        .map(|name| (name.to_string(), json_schema_for(name).expect("known name")))
+        .collect();
+    json!({ "schemaVersion": SCHEMA_VERSION, "schemas": schemas })
+}
+
+fn base58(description: &str) -> Value {
+    json!({ "type": "string", "description": description })
+}
+
+fn uint() -> Value {
+    json!({ "type": "integer", "minimum": 0 })
+}
+
+fn order_type_input() -> Value {
+    json!({
+        "description": "orderType of an order intent; defaults to a GTC limit when absent",
+        "type": "object",
+        "properties": {
+            "type": { "enum": ["limit", "trigger", "market"] },
+            "tif": {
+                "description": "Time in force of a limit order, case-insensitive; default GTC",
+                "enum": ["GTC", "IOC", "ALO", "gtc", "ioc", "alo"]
+            },
+            "isMarket": { "type": "boolean" },
+            "triggerPx": { "type": "number" }
+        },
+        "required": ["type"],
+        "additionalProperties": false
+    })
+}
+
+fn order_spec() -> Value {
+    json!({
+        "description": "One order intent; which fields are required depends on type",
+        "type": "object",
+        "properties": {
+            "type": {
+                "enum": [
+                    "order", "modify", "cancel", "cancelByClientId", "cancelAll", "stop", "st",
+                    "takeProfit", "tp", "range", "rng", "trig", "onFill", "of", "trailingStop",
+                    "trl"
+                ]
+            },
+            "symbol": { "type": "string" },
+            "isBuy": { "type": "boolean" },
+            "price": { "type": "number" },
+            "size": { "type": "number" },
+            "reduceOnly": { "type": "boolean" },
+            "iso": { "type": "boolean" },
+            "orderType": { "$ref": "#/$defs/OrderTypeInput" },
+            "clientId": base58("32-byte client order ID (order, cancelByClientId)"),
+            "orderId": base58("32-byte order ID (cancel, modify)"),
+            "amount": { "type": "number" },
+            "symbols": { "type": "array", "items": { "type": "string" } },
+            "maxAgeMs": uint(),
+            "triggerPrice": { "type": "number" },
+            "limitPrice": { "type": "number" },
+            "pmin": { "type": "number" },
+            "pmax": { "type": "number" },
+            "lmin": { "type": "number" },
+            "lmax": { "type": "number" },
+            "trailBps": uint(),
+            "stepBps": uint(),
+            "p": uint(),
+            "actions": { "type": "array", "items": { "$ref": "#" } },
+            "priceTicks": { "type": "integer" },
+            "sizeLots": { "type": "integer" },
+            "symbolSpec": { "$ref": "#/$defs/SymbolSpecInput" },
+            "onFill": { "$ref": "#/$defs/OnFill" }
+        },
+        "required": ["type"],
+        "additionalProperties": false,
+        "$defs": {
+            "OrderTypeInput": order_type_input(),
+            "SymbolSpecInput": {
+                "type": "object",
+                "properties": {
+                    "priceDecimals": uint(),
+                    "sizeDecimals": uint(),
+                    "tickUnits": uint(),
+                    "lotUnits": uint()
+                },
+                "required": ["priceDecimals", "sizeDecimals"],
+                "additionalProperties": false
+            },
+            "OnFill": {
+                "type": "object",
+                "properties": {
+                    "p": uint(),
+                    "actions": { "type": "array", "items": { "$ref": "#" } }
+                },
+                "required": ["p", "actions"],
+                "additionalProperties": false
+            }
+        }
+    })
+}
+
+fn prepare_options() -> Value {
+    json!({
+        "description": "Options of the bindings' prepare functions",
+        "type": "object",
+        "properties": {
+            "account": base58("Trading account"),
+            "signer": base58("Signing key; defaults to account"),
+            "nonce": uint(),
+            "groupKind": { "type": "string" },
+            "strictGroup": { "type": "boolean" },
+            "strict": { "type": "boolean" }
+        },
+        "required": ["account"],
+        "additionalProperties": false
+    })
+}
+
+fn user_settings() -> Value {
+    json!({
+        "description": "User settings update: max leverage per symbol",
+        "type": "object",
+        "properties": {
+            "maxLeverage": {
+                "type": "array",
+                "items": {
+                    "anyOf": [
+                        {
+                            "type": "array",
+                            "prefixItems": [{ "type": "string" }, { "type": "number" }],
+                            "minItems": 2,
+                            "maxItems": 2
+                        },
+                        {
+                            "type": "object",
+                            "properties": {
+                                "symbol": { "type": "string" },
+                                "leverage": { "type": "number" }
+                            },
+                            "required": ["symbol", "leverage"],
+                            "additionalProperties": false
+                        }
+                    ]
+                }
+            }
+        },
+        "required": ["maxLeverage"],
+        "additionalProperties": false
+    })
+}
+
+fn actions() -> Value {
+    json!({
+        "description": "Compact tagged actions, e.g. { \"l\": { ... } }",
+        "type": "array",
+        "items": { "type": "object" }
+    })
+}
+
+fn signed_transaction() -> Value {
+    json!({
+        "description": "Signed transaction as submitted to the API",
+        "type": "object",
+        "properties": {
+            "actions": actions(),
+            "nonce": uint(),
+            "account": base58("Trading account"),
+            "signer": base58("Signing key"),
+            "signature": base58("64-byte ed25519 signature")
+        },
+        "required": ["actions", "nonce", "account", "signer", "signature"],
+        "additionalProperties": false
+    })
+}
+
+fn prepared_message() -> Value {
+    let codes: Vec<&str> = [
+        LintCode::MarketableLimit,
+        LintCode::ReduceOnlyNoPosition,
+        LintCode::FarIoc,
+        LintCode::TriggerWrongSide,
+        LintCode::DuplicateLeg,
+        LintCode::ProtectionSameSide,
+        LintCode::OversizedReduceOnly,
+    ]
+    .iter()
+    .map(LintCode::as_str)
+    .collect();
+    json!({
+        "description": "Prepared message awaiting an external signature (Rust serde form)",
+        "type": "object",
+        "properties": {
+            "message_bytes": {
+                "type": "array",
+                "items": { "type": "integer", "minimum": 0, "maximum": 255 }
+            },
+            "order_id": { "type": ["string", "null"] },
+            "order_ids": { "type": ["array", "null"], "items": { "type": "string" } },
+            "actions": actions(),
+            "account": base58("Trading account"),
+            "signer": base58("Signing key"),
+            "nonce": uint(),
+            "id_scheme": { "enum": ["v1-sha256-wincode"] },
+            "group_kind": { "type": "string" },
+            "group_warnings": {
+                "type": "array",
+                "items": {
+                    "type": "object",
+                    "properties": {
+                        "index": uint(),
+                        "code": { "enum": codes },
+                        "message": { "type": "string" },
+                        "severity": { "enum": ["info", "warning", "critical"] }
+                    },
+                    "required": ["index", "code", "message", "severity"],
+                    "additionalProperties": false
+                }
+            }
+        },
+        "required": ["message_bytes", "actions", "account", "signer", "nonce"],
+        "additionalProperties": false
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input::{ORDER_FIELDS, ORDER_TYPE_FIELDS, SYMBOL_SPEC_FIELDS};
+    use crate::{
+        prepare_group_with_options, GroupKind, GroupOptions, InputMode, Keypair, OrderSpec,
+        PreparedMessage, SignedTransaction,
+    };
+    use std::collections::BTreeSet;
+
+    /// Checks the keywords these schemas use; anything else is a test bug.
+    fn validate(root: &Value, schema: &Value, value: &Value, path: &str, errors: &mut Vec<String>) {
+        if let Some(reference) = schema.get("$ref").and_then(Value::as_str) {
+            let target = match reference.strip_prefix("#/$defs/") {
+                Some(def) => &root["$defs"][def],
+                None if reference == "#" => root,
+                None => panic!("unsupported $ref {}", reference),
+            };
+            return validate(root, target, value, path, errors);
+        }
+        if let Some(types) = schema.get("type") {
+            let types: Vec<&str> = match types {
+                Value::String(t) => vec![t.as_str()],
+                Value::Array(ts) => ts.iter().filter_map(Value::as_str).collect(),
+                _ => panic!("bad type keyword at {}", path),
+            };
+            let matches = |t: &str| match t {
+                "object" => value.is_object(),
+                "array" => value.is_array(),
+                "string" => value.is_string(),
+                "boolean" => value.is_boolean(),
+                "null" => value.is_null(),
+                "number" => value.is_number(),
+                "integer" => value.is_u64() || value.is_i64(),
+                other => panic!("unknown type {}", other),
+            };
+            if !types.iter().any(|t| matches(t)) {
+                errors.push(format!("{}: expected {:?}, got {}", path, types, value));
+                return;
+            }
+        }
+        if let Some(options) = schema.get("enum").and_then(Value::as_array) {
+            if !options.contains(value) {
+                errors.push(format!("{}: {} is not one of {:?}", path, value, options));
+            }
+        }
+        if let Some(any_of) = schema.get("anyOf").and_then(Value::as_array) {
+            let fits = any_of.iter().any(|s| {
+                let mut scratch = Vec::new();
+                validate(root, s, value, path, &mut scratch);
+                scratch.is_empty()
+            });
+            if !fits {
+                errors.push(format!("{}: matches no anyOf branch", path));
+            }
+        }
+        if let Some(n) = value.as_f64() {
+            let below = schema
+                .get("minimum")
+                .and_then(Value::as_f64)
+                .is_some_and(|m| n < m);
+            let above = schema
+                .get("maximum")
+                .and_then(Value::as_f64)
+                .is_some_and(|m| n > m);
+            if below || above {
+                errors.push(format!("{}: {} out of range", path, n));
+            }
+        }
+        if let Value::Array(items) = value {
+            let len = items.len() as u64;
+            let min = schema.get("minItems").and_then(Value::as_u64);
+            let max = schema.get("maxItems").and_then(Value::as_u64);
+            if min.is_some_and(|m| len < m) || max.is_some_and(|m| len > m) {
+                errors.push(format!("{}: {} items", path, len));
+            }
+            let prefix = schema.get("prefixItems").and_then(Value::as_array);
+            for (i, item) in items.iter().enumerate() {
+                let item_schema = prefix.and_then(|p| p.get(i)).or(schema.get("items"));
+                if let Some(item_schema) = item_schema {
+                    validate(root, item_schema, item, &format!("{}[{}]", path, i), errors);
+                }
+            }
+        }
+        if let Value::Object(map) = value {
+            let properties = schema.get("properties").and_then(Value::as_object);
+            for required in schema["required"].as_array().into_iter().flatten() {
+                if !map.contains_key(required.as_str().unwrap()) {
+                    errors.push(format!("{}: missing {}", path, required));
+                }
+            }
+            for (key, field) in map {
+                match properties.and_then(|p| p.get(key)) {
+                    Some(s) => validate(root, s, field, &format!("{}.{}", path, key), errors),
+                    None if schema["additionalProperties"] == false => {
+                        errors.push(format!("{}: unexpected field {}", path, key))
+                    }
+                    None => {}
+                }
+            }
+        }
+    }
+
+    fn check(name: &str, value: &Value) -> Vec<String> {
+        let schema = json_schema_for(name).unwrap();
+        let mut errors = Vec::new();
+        validate(&schema, &schema, value, name, &mut errors);
+        errors
+    }
+
+    fn property_names(schema: &Value) -> BTreeSet<String> {
+        schema["properties"]
+            .as_object()
+            .unwrap()
+            .keys()
+            .cloned()
+            .collect()
+    }
+
+    fn names(fields: &[&str]) -> BTreeSet<String> {
+        fields.iter().map(|f| f.to_string()).collect()
+    }
+
+    #[test]
+    fn test_bundle_matches_committed_snapshot() {
+        let committed: Value =
+            serde_json::from_str(include_str!("../schemas/bundle.json")).unwrap();
+        assert!(
+            committed == bundle(),
+            "schemas/bundle.json is stale; regenerate with \
+             `cargo run -p bulk-keychain --example schema_dump -- crates/bulk-keychain/schemas/bundle.json`"
+        );
+    }
+
+    #[test]
+    fn test_input_schemas_cover_known_fields() {
+        let order = json_schema_for("OrderSpec").unwrap();
+        assert_eq!(property_names(&order), names(ORDER_FIELDS));
+        assert_eq!(
+            property_names(&json_schema_for("OrderTypeInput").unwrap()),
+            names(ORDER_TYPE_FIELDS)
+        );
+        assert_eq!(
+            property_names(&order["$defs"]["SymbolSpecInput"]),
+            names(SYMBOL_SPEC_FIELDS)
+        );
+    }
+
+    #[test]
+    fn test_output_schemas_cover_every_field() {
+        let keypair = Keypair::from_bytes(&[7u8; 32]).unwrap();
+        let leg: crate::OrderItem =
+            crate::Order::limit("BTC-USD", true, 100000.0, 0.1, crate::TimeInForce::Gtc).into();
+        let options = GroupOptions::new().with_kind(GroupKind::Bracket);
+        let prepared = prepare_group_with_options(
+            vec![leg.clone(), leg],
+            &options,
+            &keypair.pubkey(),
+            None,
+            Some(1),
+        )
+        .unwrap();
+        let prepared_json = serde_json::to_value(&prepared).unwrap();
+        assert_eq!(
+            check("PreparedMessage", &prepared_json),
+            Vec::<String>::new()
+        );
+        let fields: BTreeSet<String> = prepared_json.as_object().unwrap().keys().cloned().collect();
+        assert_eq!(
+            fields,
+            property_names(&json_schema_for("PreparedMessage").unwrap())
+        );
+
+        let signed = serde_json::to_value(prepared.sign_with_keypair(&keypair).unwrap()).unwrap();
+        assert_eq!(check("SignedTransaction", &signed), Vec::<String>::new());
+        let fields: BTreeSet<String> = signed.as_object().unwrap().keys().cloned().collect();
+        assert_eq!(
+            fields,
+            property_names(&json_schema_for("SignedTransaction").unwrap())
+        );
+    }
+
+    #[test]
+    fn test_vectors_validate_and_round_trip() {
+        let vectors: Value = serde_json::from_str(include_str!("../schemas/vectors.json")).unwrap();
+        for name in SCHEMA_NAMES {
+            let cases = vectors[*name]
+                .as_array()
+                .unwrap_or_else(|| panic!("no {} vectors", name));
+            assert!(!cases.is_empty());
+            for case in cases {
+                assert_eq!(check(name, case), Vec::<String>::new(), "{}", case);
+                match *name {
+                    "OrderSpec" => {
+                        let parsed = OrderSpec::from_json(case.clone(), InputMode::Strict).unwrap();
+                        parsed.spec.into_items().unwrap();
+                    }
+                    "SignedTransaction" => {
+                        let tx: SignedTransaction = serde_json::from_value(case.clone()).unwrap();
+                        assert!(tx.verify().unwrap());
+                        assert_eq!(&serde_json::to_value(&tx).unwrap(), case);
+                    }
+                    "PreparedMessage" => {
+                        let prepared: PreparedMessage =
+                            serde_json::from_value(case.clone()).unwrap();
+                        assert_eq!(&serde_json::to_value(&prepared).unwrap(), case);
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        for (name, bad) in [
+            ("OrderSpec", json!({ "type": "order", "isBuy": "yes" })),
+            ("OrderSpec", json!({ "type": "order", "reduceOnIy": true })),
+            ("OrderTypeInput", json!({ "type": "limit", "tif": "FOK" })),
+            ("PrepareOptions", json!({ "nonce": 1 })),
+            ("UserSettings", json!({ "maxLeverage": [["BTC-USD"]] })),
+            ("SignedTransaction", json!({ "actions": [], "nonce": -1 })),
+        ] {
+            assert!(!check(name, &bad).is_empty(), "{} accepted {}", name, bad);
+        }
+    }
+}
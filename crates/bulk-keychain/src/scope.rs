@@ -0,0 +1,504 @@
+//! Restricted signer handles
+//!
+//! [`Signer::scoped`] lends less-trusted code (e.g. strategy plugins) a
+//! [`ScopedSigner`] that signs with the same key, account and nonce manager
+//! but refuses anything outside its [`ScopeConfig`] with
+//! [`Error::ScopeViolation`]. A scoped signer can hand out further scopes,
+//! but never one broader than itself, and signature budgets are shared up
+//! the chain: a sub-scope's signatures also count against its parent's.
+//!
+//! Nonce continuity between parent and scopes needs a [`crate::NonceManager`];
+//! without one every handle uses the current time.
+
+use crate::capabilities::{CapabilityDescriptor, SignedCapabilities};
+use crate::types::{ActionKind, OrderItem, Pubkey, SignedTransaction};
+use crate::{Error, Result, Signer};
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+/// What a [`ScopedSigner`] may sign. The default scope allows any order
+/// but no cancels.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScopeConfig {
+    /// Symbols that may be traded or cancelled; `None` allows any
+    pub symbols: Option<Vec<String>>,
+    /// Largest order size or modify amount
+    pub max_size: Option<f64>,
+    /// Only reduce-only orders; rules out modifies and conditional orders,
+    /// which carry no reduce-only flag
+    pub reduce_only: bool,
+    /// Allow cancels and cancel-alls within `symbols`
+    pub allow_cancels: bool,
+    /// Most transactions this scope and its sub-scopes may sign
+    pub max_signatures: Option<u64>,
+}
+
+fn violation(rule: &'static str, detail: String) -> Error {
+    Error::ScopeViolation { rule, detail }
+}
+
+impl ScopeConfig {
+    /// Check that `self` allows nothing `parent` forbids.
+    fn check_within(&self, parent: &ScopeConfig) -> Result<()> {
+        let escalation = |detail: &str| Err(violation("escalation", detail.to_string()));
+        if let Some(allowed) = &parent.symbols {
+            match &self.symbols {
+                Some(symbols) if symbols.iter().all(|s| allowed.contains(s)) => {}
+                _ => return escalation("symbols must be a subset of the parent's"),
+            }
+        }
+        if let Some(max) = parent.max_size {
+            if !self.max_size.is_some_and(|size| size <= max) {
+                return escalation("max_size must not exceed the parent's");
+            }
+        }
+        if parent.reduce_only && !self.reduce_only {
+            return escalation("parent scope is reduce-only");
+        }
+        if !parent.allow_cancels && self.allow_cancels {
+            return escalation("parent scope does not allow cancels");
+        }
+        if let Some(max) = parent.max_signatures {
+            if !self.max_signatures.is_some_and(|n| n <= max) {
+                return escalation("max_signatures must not exceed the parent's");
+            }
+        }
+        Ok(())
+    }
+
+    fn check_symbol(&self, symbol: &str) -> Result<()> {
+        match &self.symbols {
+            Some(symbols) if !symbols.iter().any(|s| s == symbol) => Err(violation(
+                "symbol",
+                format!("{} is outside the scope", symbol),
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    fn check_size(&self, size: f64) -> Result<()> {
+        match self.max_size {
+            // NaN sizes fail too.
+            Some(max) if size.partial_cmp(&max).is_none_or(|o| o.is_gt()) => Err(violation(
+                "max_size",
+                format!("size {} exceeds {}", size, max),
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    fn check_cancels(&self) -> Result<()> {
+        if self.allow_cancels {
+            Ok(())
+        } else {
+            Err(violation("cancels", "cancels are not allowed".to_string()))
+        }
+    }
+
+    fn check_not_reduce_only(&self, what: &str) -> Result<()> {
+        if self.reduce_only {
+            Err(violation(
+                "reduce_only",
+                format!("{} not allowed in a reduce-only scope", what),
+            ))
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Check one item, recursing into trigger baskets and on-fill actions.
+    pub fn check(&self, item: &OrderItem) -> Result<()> {
+        match item {
+            OrderItem::Order(o) => {
+                self.check_symbol(&o.symbol)?;
+                self.check_size(o.size)?;
+                if self.reduce_only && !o.reduce_only {
+                    return Err(violation(
+                        "reduce_only",
+                        format!("order on {} is not reduce-only", o.symbol),
+                    ));
+                }
+                Ok(())
+            }
+            OrderItem::Modify(m) => {
+                self.check_not_reduce_only("modifies")?;
+                self.check_symbol(&m.symbol)?;
+                self.check_size(m.amount)
+            }
+            OrderItem::Cancel(c) => {
+                self.check_cancels()?;
+                self.check_symbol(&c.symbol)
+            }
+            OrderItem::CancelByClientId(c) => {
+                self.check_cancels()?;
+                self.check_symbol(&c.symbol)
+            }
+            OrderItem::CancelAll(cxa) => {
+                self.check_cancels()?;
+                if cxa.symbols.is_empty() && self.symbols.is_some() {
+                    return Err(violation(
+                        "symbol",
+                        "cancel-all across every symbol".to_string(),
+                    ));
+                }
+                cxa.symbols.iter().try_for_each(|s| self.check_symbol(s))
+            }
+            OrderItem::Stop(st) => self.check_conditional(&st.symbol, st.size),
+            OrderItem::TakeProfit(tp) => self.check_conditional(&tp.symbol, tp.size),
+            OrderItem::RangeOco(r) => self.check_conditional(&r.symbol, r.size),
+            OrderItem::TrailingStop(t) => self.check_conditional(&t.symbol, t.size),
+            OrderItem::TriggerBasket(b) => b.actions.iter().try_for_each(|a| self.check(a)),
+            OrderItem::OnFill(of) => of.actions.iter().try_for_each(|a| self.check(a)),
+        }
+    }
+
+    fn check_conditional(&self, symbol: &str, size: f64) -> Result<()> {
+        self.check_not_reduce_only("conditional orders")?;
+        self.check_symbol(symbol)?;
+        self.check_size(size)
+    }
+}
+
+#[derive(Debug)]
+struct SignatureBudget {
+    limit: u64,
+    used: AtomicU64,
+}
+
+impl SignatureBudget {
+    fn remaining(&self) -> u64 {
+        self.limit.saturating_sub(self.used.load(Ordering::SeqCst))
+    }
+
+    fn try_take(&self, n: u64) -> bool {
+        let mut used = self.used.load(Ordering::SeqCst);
+        while used + n <= self.limit {
+            match self.used.compare_exchange_weak(
+                used,
+                used + n,
+                Ordering::SeqCst,
+                Ordering::SeqCst,
+            ) {
+                Ok(_) => return true,
+                Err(actual) => used = actual,
+            }
+        }
+        false
+    }
+
+    fn give_back(&self, n: u64) {
+        self.used.fetch_sub(n, Ordering::SeqCst);
+    }
+}
+
+/// Signer restricted to a [`ScopeConfig`], from [`Signer::scoped`].
+pub struct ScopedSigner {
+    inner: Signer,
+    config: ScopeConfig,
+    /// This scope's budget, if any, after every ancestor's
+    budgets: Vec<Arc<SignatureBudget>>,
+}
+
+impl ScopedSigner {
+    pub(crate) fn new(inner: Signer, config: ScopeConfig) -> Self {
+        Self::with_budgets(inner, config, Vec::new())
+    }
+
+    fn with_budgets(
+        inner: Signer,
+        config: ScopeConfig,
+        mut budgets: Vec<Arc<SignatureBudget>>,
+    ) -> Self {
+        if let Some(limit) = config.max_signatures {
+            budgets.push(Arc::new(SignatureBudget {
+                limit,
+                used: AtomicU64::new(0),
+            }));
+        }
+        Self {
+            inner,
+            config,
+            budgets,
+        }
+    }
+
+    /// Narrower scope on the same key; fails with an `escalation`
+    /// [`Error::ScopeViolation`] if `config` allows anything this scope does not.
+    pub fn scoped(&self, config: ScopeConfig) -> Result<ScopedSigner> {
+        config.check_within(&self.config)?;
+        Ok(Self::with_budgets(
+            self.inner.share(),
+            config,
+            self.budgets.clone(),
+        ))
+    }
+
+    pub fn config(&self) -> &ScopeConfig {
+        &self.config
+    }
+
+    /// Signatures left before this scope or an ancestor runs out (`None` if unlimited).
+    pub fn remaining_signatures(&self) -> Option<u64> {
+        self.budgets.iter().map(|b| b.remaining()).min()
+    }
+
+    pub fn signer_pubkey(&self) -> &Pubkey {
+        self.inner.signer_pubkey()
+    }
+
+    pub fn account_pubkey(&self) -> &Pubkey {
+        self.inner.account_pubkey()
+    }
+
+    /// Describe this scope (see [`Signer::capabilities`]); a scoped signer
+    /// signs only order actions.
+    pub fn capabilities(&self) -> CapabilityDescriptor {
+        self.inner
+            .capabilities_with(vec![self.config.clone()], vec![ActionKind::Order])
+    }
+
+    /// [`ScopedSigner::capabilities`], signed with the underlying key. Does
+    /// not count against the signature budget.
+    pub fn capabilities_signed(&self) -> Result<SignedCapabilities> {
+        self.inner.sign_capabilities(&self.capabilities())
+    }
+
+    /// Sign a single item if the scope allows it.
+    ///
+    /// ```rust
+    /// use bulk_keychain::{Keypair, Order, ScopeConfig, Signer};
+    ///
+    /// let signer = Signer::new(Keypair::generate());
+    /// let mut bot = signer.scoped(ScopeConfig {
+    ///     symbols: Some(vec!["BTC-USD".to_string()]),
+    ///     ..ScopeConfig::default()
+    /// });
+    /// let tx = bot.sign(Order::market("BTC-USD", true, 0.1).into(), Some(1)).unwrap();
+    /// assert_eq!(tx.signer_pubkey().unwrap(), *signer.signer_pubkey());
+    /// assert!(bot.sign(Order::market("ETH-USD", true, 1.0).into(), Some(2)).is_err());
+    /// ```
+    pub fn sign(&mut self, item: OrderItem, nonce: Option<u64>) -> Result<SignedTransaction> {
+        self.config.check(&item)?;
+        self.spend(1, |signer| signer.sign(item, nonce))
+    }
+
+    /// Sign several items as one transaction if the scope allows every one.
+    pub fn sign_group(
+        &mut self,
+        items: Vec<OrderItem>,
+        nonce: Option<u64>,
+    ) -> Result<SignedTransaction> {
+        items.iter().try_for_each(|item| self.config.check(item))?;
+        self.spend(1, |signer| signer.sign_group(items, nonce))
+    }
+
+    /// Sign independent items (see [`Signer::sign_all`]) if the scope allows every one.
+    pub fn sign_all(
+        &mut self,
+        items: Vec<OrderItem>,
+        base_nonce: Option<u64>,
+    ) -> Result<Vec<SignedTransaction>> {
+        items.iter().try_for_each(|item| self.config.check(item))?;
+        self.spend(items.len() as u64, |signer| {
+            signer.sign_all(items, base_nonce)
+        })
+    }
+
+    /// Take `n` signatures from every budget in the chain, refunding them if
+    /// signing fails.
+    fn spend<T>(&mut self, n: u64, sign: impl FnOnce(&mut Signer) -> Result<T>) -> Result<T> {
+        for (taken, budget) in self.budgets.iter().enumerate() {
+            if !budget.try_take(n) {
+                self.budgets[..taken].iter().for_each(|b| b.give_back(n));
+                return Err(violation(
+                    "max_signatures",
+                    format!(
+                        "{} signature(s) requested, {} left of {}",
+                        n,
+                        budget.remaining(),
+                        budget.limit
+                    ),
+                ));
+            }
+        }
+        let result = sign(&mut self.inner);
+        if result.is_err() {
+            self.budgets.iter().for_each(|b| b.give_back(n));
+        }
+        result
+    }
+}
+
+impl std::fmt::Debug for ScopedSigner {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ScopedSigner")
+            .field("signer", self.inner.signer_pubkey())
+            .field("config", &self.config)
+            .field("remaining_signatures", &self.remaining_signatures())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Cancel, CancelAll, Hash, Keypair, NonceManager, Order, Stop, TimeInForce};
+
+    fn btc_reduce_only() -> ScopeConfig {
+        ScopeConfig {
+            symbols: Some(vec!["BTC-USD".into()]),
+            max_size: Some(1.0),
+            reduce_only: true,
+            allow_cancels: true,
+            max_signatures: None,
+        }
+    }
+
+    fn order(symbol: &str, size: f64) -> Order {
+        Order::limit(symbol, false, 100.0, size, TimeInForce::Gtc)
+    }
+
+    fn rule(err: Error) -> &'static str {
+        match err {
+            Error::ScopeViolation { rule, .. } => rule,
+            other => panic!("expected scope violation, got {other}"),
+        }
+    }
+
+    #[test]
+    fn test_scope_rules() {
+        let parent = Signer::new(Keypair::generate());
+        let mut scoped = parent.scoped(btc_reduce_only());
+
+        assert!(scoped
+            .sign(order("BTC-USD", 0.5).reduce_only().into(), Some(1))
+            .is_ok());
+        let err = |r: Result<SignedTransaction>| rule(r.unwrap_err());
+        assert_eq!(
+            err(scoped.sign(order("ETH-USD", 0.5).reduce_only().into(), Some(2))),
+            "symbol"
+        );
+        assert_eq!(
+            err(scoped.sign(order("BTC-USD", 2.0).reduce_only().into(), Some(3))),
+            "max_size"
+        );
+        assert_eq!(
+            err(scoped.sign(order("BTC-USD", 0.5).into(), Some(4))),
+            "reduce_only"
+        );
+        let stop = Stop {
+            symbol: "BTC-USD".into(),
+            is_buy: false,
+            size: 0.5,
+            trigger_price: 90.0,
+            limit_price: f64::NAN,
+            iso: false,
+        };
+        assert_eq!(err(scoped.sign(stop.into(), Some(5))), "reduce_only");
+
+        let cancel = Cancel::new("BTC-USD", Hash::from_bytes([1; 32]));
+        assert!(scoped.sign(cancel.clone().into(), Some(6)).is_ok());
+        assert_eq!(err(scoped.sign(CancelAll::all().into(), Some(7))), "symbol");
+
+        let mut no_cancels = parent.scoped(ScopeConfig::default());
+        assert_eq!(err(no_cancels.sign(cancel.into(), Some(8))), "cancels");
+
+        // One bad leg rejects the whole group.
+        let group = vec![
+            order("BTC-USD", 0.5).reduce_only().into(),
+            order("ETH-USD", 0.5).reduce_only().into(),
+        ];
+        assert_eq!(err(scoped.sign_group(group, Some(9))), "symbol");
+    }
+
+    #[test]
+    fn test_signature_budget_shared_with_sub_scopes() {
+        let parent = Signer::new(Keypair::generate());
+        let mut scoped = parent.scoped(ScopeConfig {
+            max_signatures: Some(3),
+            ..Default::default()
+        });
+        let mut child = scoped
+            .scoped(ScopeConfig {
+                max_signatures: Some(3),
+                ..Default::default()
+            })
+            .unwrap();
+
+        child.sign(order("BTC-USD", 1.0).into(), Some(1)).unwrap();
+        scoped.sign(order("BTC-USD", 1.0).into(), Some(2)).unwrap();
+        assert_eq!(child.remaining_signatures(), Some(1));
+        assert_eq!(
+            rule(
+                child
+                    .sign_all(vec![order("BTC-USD", 1.0).into(); 2], Some(3))
+                    .unwrap_err()
+            ),
+            "max_signatures"
+        );
+        child.sign(order("BTC-USD", 1.0).into(), Some(3)).unwrap();
+        assert_eq!(scoped.remaining_signatures(), Some(0));
+        assert!(scoped.sign(order("BTC-USD", 1.0).into(), Some(4)).is_err());
+    }
+
+    #[test]
+    fn test_nonce_continuity_with_parent() {
+        let mut parent = Signer::with_nonce_manager(Keypair::generate(), NonceManager::counter());
+        let mut scoped = parent.scoped(ScopeConfig::default());
+        let item = || OrderItem::from(order("BTC-USD", 1.0));
+
+        assert_eq!(parent.sign(item(), None).unwrap().nonce, 0);
+        assert_eq!(scoped.sign(item(), None).unwrap().nonce, 1);
+        assert_eq!(parent.sign(item(), None).unwrap().nonce, 2);
+        assert_eq!(scoped.signer_pubkey(), parent.signer_pubkey());
+    }
+
+    #[test]
+    fn test_no_escalation() {
+        let parent = Signer::new(Keypair::generate());
+        let scoped = parent.scoped(btc_reduce_only());
+
+        let broader = [
+            ScopeConfig {
+                symbols: None,
+                ..btc_reduce_only()
+            },
+            ScopeConfig {
+                symbols: Some(vec!["BTC-USD".into(), "ETH-USD".into()]),
+                ..btc_reduce_only()
+            },
+            ScopeConfig {
+                max_size: Some(5.0),
+                ..btc_reduce_only()
+            },
+            ScopeConfig {
+                reduce_only: false,
+                ..btc_reduce_only()
+            },
+        ];
+        for config in broader {
+            assert_eq!(rule(scoped.scoped(config).unwrap_err()), "escalation");
+        }
+
+        let narrower = scoped
+            .scoped(ScopeConfig {
+                max_size: Some(0.5),
+                allow_cancels: false,
+                ..btc_reduce_only()
+            })
+            .unwrap();
+        assert_eq!(
+            rule(
+                narrower
+                    .scoped(ScopeConfig {
+                        allow_cancels: true,
+                        ..narrower.config().clone()
+                    })
+                    .unwrap_err()
+            ),
+            "escalation"
+        );
+    }
+}
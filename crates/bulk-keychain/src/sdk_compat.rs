@@ -1,11 +1,12 @@
 //! Canonical BULK-SDK-compatible serialization.
 
+use crate::order_id::OrderIdHasher;
 use crate::types::*;
 use crate::{Error, Result};
-use serde::Serialize;
-use serde::Serializer;
-use sha2::{Digest, Sha256};
-use std::collections::HashMap;
+use serde::de::Error as _;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::cell::Cell;
+use std::collections::BTreeMap;
 
 const SCALE: f64 = 1e8;
 
@@ -22,6 +23,16 @@ mod serde_hash {
             val.as_bytes().serialize(serializer)
         }
     }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> std::result::Result<Hash, D::Error> {
+        if deserializer.is_human_readable() {
+            Hash::from_base58(&String::deserialize(deserializer)?).map_err(D::Error::custom)
+        } else {
+            <[u8; 32]>::deserialize(deserializer).map(Hash::from_bytes)
+        }
+    }
 }
 
 mod serde_pubkey {
@@ -37,6 +48,16 @@ mod serde_pubkey {
             val.as_bytes().serialize(serializer)
         }
     }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> std::result::Result<Pubkey, D::Error> {
+        if deserializer.is_human_readable() {
+            Pubkey::from_base58(&String::deserialize(deserializer)?).map_err(D::Error::custom)
+        } else {
+            <[u8; 32]>::deserialize(deserializer).map(Pubkey::from_bytes)
+        }
+    }
 }
 
 mod serde_pubkey_vec {
@@ -58,6 +79,22 @@ mod serde_pubkey_vec {
                 .serialize(serializer)
         }
     }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> std::result::Result<Vec<Pubkey>, D::Error> {
+        if deserializer.is_human_readable() {
+            Vec::<String>::deserialize(deserializer)?
+                .iter()
+                .map(|s| Pubkey::from_base58(s).map_err(D::Error::custom))
+                .collect()
+        } else {
+            Ok(Vec::<[u8; 32]>::deserialize(deserializer)?
+                .into_iter()
+                .map(Pubkey::from_bytes)
+                .collect())
+        }
+    }
 }
 
 mod serde_safe_f64 {
@@ -74,6 +111,18 @@ mod serde_safe_f64 {
             serializer.serialize_u64(fixed)
         }
     }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> std::result::Result<f64, D::Error> {
+        if deserializer.is_human_readable() {
+            String::deserialize(deserializer)?
+                .parse()
+                .map_err(D::Error::custom)
+        } else {
+            Ok(u64::deserialize(deserializer)? as f64 / SCALE)
+        }
+    }
 }
 
 mod serde_opt_f64 {
@@ -95,9 +144,42 @@ mod serde_opt_f64 {
             }
         }
     }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> std::result::Result<Option<f64>, D::Error> {
+        if deserializer.is_human_readable() {
+            Option::<String>::deserialize(deserializer)?
+                .map(|s| s.parse().map_err(D::Error::custom))
+                .transpose()
+        } else {
+            Ok(Option::<u64>::deserialize(deserializer)?.map(|v| v as f64 / SCALE))
+        }
+    }
+}
+
+/// Maximum nesting of `trig`/`of`/`msp` actions accepted when decoding.
+const MAX_NESTING: u32 = 8;
+
+thread_local! {
+    static NESTING: Cell<u32> = const { Cell::new(0) };
+}
+
+/// Decode nested actions, refusing input nested deeper than [`MAX_NESTING`].
+fn deserialize_nested<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> std::result::Result<Vec<TxAction>, D::Error> {
+    let depth = NESTING.with(|n| n.get());
+    if depth >= MAX_NESTING {
+        return Err(D::Error::custom("actions nested too deeply"));
+    }
+    NESTING.with(|n| n.set(depth + 1));
+    let result = Vec::<TxAction>::deserialize(deserializer);
+    NESTING.with(|n| n.set(depth));
+    result
 }
 
-#[derive(Clone, Debug, Serialize)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 #[serde(rename_all = "UPPERCASE")]
 enum TxTimeInForce {
     Gtc,
@@ -116,7 +198,7 @@ impl From<TimeInForce> for TxTimeInForce {
     }
 }
 
-#[derive(Clone, Debug, Serialize)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 struct TxMarketOrder {
     #[serde(rename = "c")]
     symbol: String,
@@ -130,7 +212,7 @@ struct TxMarketOrder {
     iso: bool,
 }
 
-#[derive(Clone, Debug, Serialize)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 struct TxLimitOrder {
     #[serde(rename = "c")]
     symbol: String,
@@ -148,7 +230,7 @@ struct TxLimitOrder {
     iso: bool,
 }
 
-#[derive(Clone, Debug, Serialize)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 struct TxModifyOrder {
     #[serde(with = "serde_hash", rename = "oid")]
     order_id: Hash,
@@ -158,7 +240,7 @@ struct TxModifyOrder {
     amount: f64,
 }
 
-#[derive(Clone, Debug, Serialize)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 struct TxCancelOrder {
     #[serde(rename = "c")]
     symbol: String,
@@ -166,13 +248,13 @@ struct TxCancelOrder {
     oid: Hash,
 }
 
-#[derive(Clone, Debug, Serialize)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 struct TxCancelAll {
     #[serde(rename = "c")]
     symbols: Vec<String>,
 }
 
-#[derive(Clone, Debug, Serialize)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 struct TxPrice {
     #[serde(rename = "t")]
     timestamp: u64,
@@ -182,7 +264,7 @@ struct TxPrice {
     price: f64,
 }
 
-#[derive(Clone, Debug, Serialize)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 struct TxPythPrice {
     #[serde(rename = "t")]
     timestamp: u64,
@@ -194,12 +276,12 @@ struct TxPythPrice {
     exponent: i16,
 }
 
-#[derive(Clone, Debug, Serialize)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 struct TxPythOracle {
     oracles: Vec<TxPythPrice>,
 }
 
-#[derive(Clone, Debug, Serialize)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 struct TxStop {
     #[serde(rename = "c")]
     symbol: String,
@@ -215,7 +297,7 @@ struct TxStop {
     iso: bool,
 }
 
-#[derive(Clone, Debug, Serialize)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 struct TxTakeProfit {
     #[serde(rename = "c")]
     symbol: String,
@@ -231,7 +313,7 @@ struct TxTakeProfit {
     iso: bool,
 }
 
-#[derive(Clone, Debug, Serialize)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 struct TxRangeOco {
     #[serde(rename = "c")]
     symbol: String,
@@ -251,7 +333,7 @@ struct TxRangeOco {
     iso: bool,
 }
 
-#[derive(Clone, Debug, Serialize)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 struct TxTriggerBasket {
     #[serde(rename = "c")]
     symbol: String,
@@ -259,21 +341,21 @@ struct TxTriggerBasket {
     is_buy: bool,
     #[serde(rename = "tr", with = "serde_safe_f64")]
     trigger_price: f64,
-    #[serde(rename = "actions")]
+    #[serde(rename = "actions", deserialize_with = "deserialize_nested")]
     actions: Vec<TxAction>,
     #[serde(rename = "i", default)]
     iso: bool,
 }
 
-#[derive(Clone, Debug, Serialize)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 struct TxOnFill {
     #[serde(rename = "p")]
     parent_seqno: u32,
-    #[serde(rename = "actions")]
+    #[serde(rename = "actions", deserialize_with = "deserialize_nested")]
     actions: Vec<TxAction>,
 }
 
-#[derive(Clone, Debug, Serialize)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 struct TxTrailingStop {
     #[serde(rename = "c")]
     symbol: String,
@@ -291,14 +373,14 @@ struct TxTrailingStop {
     iso: bool,
 }
 
-#[derive(Clone, Debug, Serialize)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 struct TxFaucet {
     #[serde(with = "serde_pubkey", rename = "u")]
     user: Pubkey,
     amount: Option<f64>,
 }
 
-#[derive(Clone, Debug, Serialize)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 struct TxAgentWalletCreation {
     #[serde(with = "serde_pubkey", rename = "a")]
     agent: Pubkey,
@@ -306,13 +388,13 @@ struct TxAgentWalletCreation {
     delete: bool,
 }
 
-#[derive(Clone, Debug, Serialize)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 struct TxUpdateUserSettings {
     #[serde(rename = "m")]
-    max_leverage: HashMap<String, f64>,
+    max_leverage: BTreeMap<String, f64>,
 }
 
-#[derive(Clone, Debug, Serialize)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 struct TxWhitelistFaucet {
     #[serde(with = "serde_pubkey")]
@@ -320,7 +402,7 @@ struct TxWhitelistFaucet {
     whitelist: bool,
 }
 
-#[derive(Clone, Debug, Serialize)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 struct TxCreateSubAccount {
     name: String,
@@ -330,14 +412,14 @@ struct TxCreateSubAccount {
     margin_amount: Option<f64>,
 }
 
-#[derive(Clone, Debug, Serialize)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 struct TxRemoveSubAccount {
     #[serde(with = "serde_pubkey")]
     to_remove: Pubkey,
 }
 
-#[derive(Clone, Debug, Serialize)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 struct TxRenameSubAccount {
     #[serde(with = "serde_pubkey", rename = "a")]
@@ -346,7 +428,7 @@ struct TxRenameSubAccount {
     name: String,
 }
 
-#[derive(Clone, Debug, Serialize)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 enum TxTransferKind {
     #[serde(rename = "internal")]
@@ -365,7 +447,7 @@ impl From<TransferKind> for TxTransferKind {
     }
 }
 
-#[derive(Clone, Debug, Serialize)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 struct TxTransfer {
     #[serde(rename = "k")]
@@ -378,7 +460,7 @@ struct TxTransfer {
     margin_amount: f64,
 }
 
-#[derive(Clone, Debug, Serialize)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 struct TxCreateMultisig {
     #[serde(with = "serde_pubkey_vec")]
@@ -388,15 +470,15 @@ struct TxCreateMultisig {
     proposal_lifetime_secs: u32,
 }
 
-#[derive(Clone, Debug, Serialize)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 struct TxMultisigPropose {
     #[serde(with = "serde_pubkey", rename = "m")]
     multisig: Pubkey,
-    #[serde(rename = "a")]
+    #[serde(rename = "a", deserialize_with = "deserialize_nested")]
     actions: Vec<TxAction>,
 }
 
-#[derive(Clone, Debug, Serialize)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 struct TxMultisigProposalRef {
     #[serde(with = "serde_pubkey", rename = "m")]
     multisig: Pubkey,
@@ -404,7 +486,7 @@ struct TxMultisigProposalRef {
     proposal_id: u64,
 }
 
-#[derive(Clone, Debug, Serialize)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 struct TxUpdateMultisigPolicy {
     #[serde(with = "serde_pubkey", rename = "m")]
@@ -416,7 +498,7 @@ struct TxUpdateMultisigPolicy {
     proposal_lifetime_secs: u32,
 }
 
-#[derive(Clone, Debug, Serialize)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 enum TxAction {
     #[serde(rename = "m")]
     MarketOrder(TxMarketOrder),
@@ -541,9 +623,19 @@ fn order_item_to_tx_action(item: &OrderItem) -> Result<TxAction> {
             symbol: cancel.symbol.clone(),
             oid: cancel.order_id,
         })),
-        OrderItem::CancelAll(cancel_all) => Ok(TxAction::CancelAll(TxCancelAll {
-            symbols: cancel_all.symbols.clone(),
-        })),
+        OrderItem::CancelByClientId(cancel) => Err(Error::UnresolvedClientIdCancel(
+            cancel.client_id.to_base58(),
+        )),
+        OrderItem::CancelAll(cancel_all) => {
+            if cancel_all.max_age_ms.is_some() {
+                return Err(Error::InvalidOrder(
+                    "cancel-all max age is not supported by BULK API".to_string(),
+                ));
+            }
+            Ok(TxAction::CancelAll(TxCancelAll {
+                symbols: cancel_all.symbols.clone(),
+            }))
+        }
         OrderItem::Stop(stop) => Ok(TxAction::Stop(TxStop {
             symbol: stop.symbol.clone(),
             is_buy: stop.is_buy,
@@ -637,7 +729,7 @@ fn action_to_tx_actions(action: &Action) -> Result<Vec<TxAction>> {
             })])
         }
         Action::UpdateUserSettings(settings) => {
-            let mut max_leverage = HashMap::with_capacity(settings.max_leverage.len());
+            let mut max_leverage = BTreeMap::new();
             for (symbol, leverage) in &settings.max_leverage {
                 max_leverage.insert(symbol.clone(), *leverage);
             }
@@ -726,6 +818,242 @@ fn action_to_tx_actions(action: &Action) -> Result<Vec<TxAction>> {
     }
 }
 
+impl From<TxTimeInForce> for TimeInForce {
+    #[inline]
+    fn from(value: TxTimeInForce) -> Self {
+        match value {
+            TxTimeInForce::Gtc => Self::Gtc,
+            TxTimeInForce::Ioc => Self::Ioc,
+            TxTimeInForce::Alo => Self::Alo,
+        }
+    }
+}
+
+fn tx_actions_to_order_items(actions: Vec<TxAction>) -> Result<Vec<OrderItem>> {
+    actions
+        .into_iter()
+        .map(|action| match tx_action_to_action(action)? {
+            Action::Order { mut orders } if orders.len() == 1 => Ok(orders.remove(0)),
+            _ => Err(Error::InvalidAction(
+                "nested action must be an order item".to_string(),
+            )),
+        })
+        .collect()
+}
+
+/// Inverse of [`order_item_to_tx_action`] / [`action_to_tx_actions`] for one wire action.
+fn tx_action_to_action(action: TxAction) -> Result<Action> {
+    let item = match action {
+        TxAction::MarketOrder(m) => OrderItem::Order(Order {
+            symbol: m.symbol,
+            is_buy: m.is_buy,
+            price: 0.0,
+            size: m.size,
+            reduce_only: m.reduce_only,
+            iso: m.iso,
+            order_type: OrderType::market(),
+            client_id: None,
+        }),
+        TxAction::LimitOrder(l) => OrderItem::Order(Order {
+            symbol: l.symbol,
+            is_buy: l.is_buy,
+            price: l.price,
+            size: l.size,
+            reduce_only: l.reduce_only,
+            iso: l.iso,
+            order_type: OrderType::limit(l.tif.into()),
+            client_id: None,
+        }),
+        TxAction::ModifyOrder(m) => OrderItem::Modify(Modify::new(m.order_id, m.symbol, m.amount)),
+        TxAction::Cancel(c) => OrderItem::Cancel(Cancel::new(c.symbol, c.oid)),
+        TxAction::CancelAll(c) => OrderItem::CancelAll(CancelAll::for_symbols(c.symbols)),
+        TxAction::Stop(s) => OrderItem::Stop(Stop {
+            symbol: s.symbol,
+            is_buy: s.is_buy,
+            size: s.size,
+            trigger_price: s.trigger_price,
+            limit_price: s.limit_price.unwrap_or(f64::NAN),
+            iso: s.iso,
+        }),
+        TxAction::TakeProfit(tp) => OrderItem::TakeProfit(TakeProfit {
+            symbol: tp.symbol,
+            is_buy: tp.is_buy,
+            size: tp.size,
+            trigger_price: tp.trigger_price,
+            limit_price: tp.limit_price.unwrap_or(f64::NAN),
+            iso: tp.iso,
+        }),
+        TxAction::RangeOco(r) => OrderItem::RangeOco(RangeOco {
+            symbol: r.symbol,
+            is_buy: r.is_buy,
+            size: r.size,
+            collar_min: r.collar_min,
+            collar_max: r.collar_max,
+            limit_min: r.limit_min.unwrap_or(f64::NAN),
+            limit_max: r.limit_max.unwrap_or(f64::NAN),
+            iso: r.iso,
+        }),
+        TxAction::TriggerBasket(t) => OrderItem::TriggerBasket(TriggerBasket {
+            symbol: t.symbol,
+            is_buy: t.is_buy,
+            trigger_price: t.trigger_price,
+            actions: tx_actions_to_order_items(t.actions)?,
+            iso: t.iso,
+        }),
+        TxAction::TrailingStop(t) => OrderItem::TrailingStop(TrailingStop {
+            symbol: t.symbol,
+            is_buy: t.is_buy,
+            size: t.size,
+            trail_bps: t.trail_bps,
+            step_bps: t.step_bps,
+            limit_price: t.limit_price,
+            iso: t.iso,
+        }),
+        TxAction::OnFill(of) => OrderItem::OnFill(OnFill {
+            p: of.parent_seqno,
+            actions: tx_actions_to_order_items(of.actions)?,
+        }),
+        TxAction::Price(px) => {
+            return Ok(Action::Oracle {
+                oracles: vec![OraclePrice {
+                    timestamp: px.timestamp,
+                    asset: px.asset,
+                    price: px.price,
+                }],
+            })
+        }
+        TxAction::PythOracle(o) => {
+            return Ok(Action::PythOracle {
+                oracles: o
+                    .oracles
+                    .into_iter()
+                    .map(|p| PythOraclePrice {
+                        timestamp: p.timestamp,
+                        feed_index: p.id,
+                        price: p.px,
+                        exponent: p.exponent,
+                    })
+                    .collect(),
+            })
+        }
+        TxAction::Faucet(f) => {
+            let mut faucet = Faucet::new(f.user);
+            faucet.amount = f.amount;
+            return Ok(Action::Faucet(faucet));
+        }
+        TxAction::AgentWalletCreation(a) => {
+            return Ok(Action::AgentWalletCreation(AgentWallet {
+                agent: a.agent,
+                delete: a.delete,
+            }))
+        }
+        TxAction::UpdateUserSettings(s) => {
+            return Ok(Action::UpdateUserSettings(UserSettings::new(
+                s.max_leverage.into_iter().collect(),
+            )))
+        }
+        TxAction::WhitelistFaucet(w) => {
+            return Ok(Action::WhitelistFaucet(WhitelistFaucet {
+                target: w.target,
+                whitelist: w.whitelist,
+            }))
+        }
+        TxAction::CreateSubAccount(c) => {
+            return Ok(Action::CreateSubAccount(CreateSubAccount {
+                name: c.name,
+                margin_symbol: c.margin_symbol,
+                margin_amount: c.margin_amount,
+            }))
+        }
+        TxAction::RemoveSubAccount(r) => {
+            return Ok(Action::RemoveSubAccount(RemoveSubAccount::new(r.to_remove)))
+        }
+        TxAction::RenameSubAccount(r) => {
+            return Ok(Action::RenameSubAccount(RenameSubAccount::new(
+                r.account, r.name,
+            )))
+        }
+        TxAction::Transfer(t) => {
+            return Ok(Action::Transfer(Transfer {
+                kind: match t.kind {
+                    TxTransferKind::Internal => TransferKind::Internal,
+                    TxTransferKind::External => TransferKind::External,
+                },
+                from: t.from,
+                to: t.to,
+                margin_symbol: t.margin_symbol,
+                margin_amount: t.margin_amount,
+            }))
+        }
+        TxAction::CreateMultisig(c) => {
+            return Ok(Action::CreateMultisig(CreateMultisig {
+                signers: c.signers,
+                threshold: c.threshold,
+                time_lock_secs: c.time_lock_secs,
+                proposal_lifetime_secs: c.proposal_lifetime_secs,
+            }))
+        }
+        TxAction::MultisigPropose(p) => {
+            let actions = p
+                .actions
+                .into_iter()
+                .map(tx_action_to_action)
+                .collect::<Result<Vec<_>>>()?;
+            return Ok(Action::MultisigPropose(MultisigPropose::new(
+                p.multisig, actions,
+            )));
+        }
+        TxAction::MultisigApprove(r) => {
+            return Ok(Action::MultisigApprove(MultisigApprove::new(
+                r.multisig,
+                r.proposal_id,
+            )))
+        }
+        TxAction::MultisigReject(r) => {
+            return Ok(Action::MultisigReject(MultisigReject::new(
+                r.multisig,
+                r.proposal_id,
+            )))
+        }
+        TxAction::MultisigCancel(r) => {
+            return Ok(Action::MultisigCancel(MultisigCancel::new(
+                r.multisig,
+                r.proposal_id,
+            )))
+        }
+        TxAction::MultisigExecute(r) => {
+            return Ok(Action::MultisigExecute(MultisigExecute::new(
+                r.multisig,
+                r.proposal_id,
+            )))
+        }
+        TxAction::UpdateMultisigPolicy(u) => {
+            return Ok(Action::UpdateMultisigPolicy(UpdateMultisigPolicy {
+                multisig: u.multisig,
+                signers: u.signers,
+                threshold: u.threshold,
+                time_lock_secs: u.time_lock_secs,
+                proposal_lifetime_secs: u.proposal_lifetime_secs,
+            }))
+        }
+        TxAction::ReservedCorrs
+        | TxAction::ReservedBeacon
+        | TxAction::ReservedJoin
+        | TxAction::Reserved20
+        | TxAction::Reserved21
+        | TxAction::Reserved22
+        | TxAction::Reserved23
+        | TxAction::Reserved24
+        | TxAction::Reserved25
+        | TxAction::Reserved26 => {
+            return Err(Error::InvalidAction(
+                "reserved action discriminant".to_string(),
+            ))
+        }
+    };
+    Ok(Action::Order { orders: vec![item] })
+}
+
 #[inline]
 fn serialize_into_buffer<T: Serialize>(value: &T, buffer: &mut Vec<u8>) -> Result<()> {
     buffer.clear();
@@ -740,12 +1068,18 @@ pub(crate) fn serialize_for_sdk_signing(
     account: &Pubkey,
     out: &mut Vec<u8>,
 ) -> Result<()> {
-    let tx_actions = action_to_tx_actions(action)?;
-    if tx_actions.is_empty() {
-        return Err(Error::EmptyOrders);
-    }
+    serialize_actions_for_sdk_signing(std::slice::from_ref(action), nonce, account, out)
+}
 
-    serialize_into_buffer(&tx_actions, out)?;
+/// Like [`serialize_for_sdk_signing`], for a transaction carrying several actions.
+#[inline]
+pub(crate) fn serialize_actions_for_sdk_signing(
+    actions: &[Action],
+    nonce: u64,
+    account: &Pubkey,
+    out: &mut Vec<u8>,
+) -> Result<()> {
+    serialize_actions(actions, out)?;
     out.extend_from_slice(&nonce.to_le_bytes());
     out.extend_from_slice(account.as_bytes());
     Ok(())
@@ -765,17 +1099,71 @@ pub(crate) fn compute_order_item_id_with_seqno(
     seqno: u32,
     nonce: u64,
     account: &Pubkey,
-    scratch: &mut Vec<u8>,
 ) -> Option<Hash> {
+    // Encode straight into the hasher; the preimage is never buffered.
+    let mut hasher = OrderIdHasher::new();
+    write_order_item_id_preimage(item, seqno, nonce, account, &mut hasher)?;
+    Some(hasher.finalize())
+}
+
+/// Write the order ID preimage of `item` at `seqno` to `out`; `None` for
+/// items that are not orders.
+#[inline]
+pub(crate) fn write_order_item_id_preimage(
+    item: &OrderItem,
+    seqno: u32,
+    nonce: u64,
+    account: &Pubkey,
+    mut out: impl std::io::Write,
+) -> Option<()> {
     let action = order_item_to_order_action(item).ok()??;
+    out.write_all(&seqno.to_le_bytes()).ok()?;
+    bincode::serialize_into(&mut out, &action).ok()?;
+    out.write_all(account.as_bytes()).ok()?;
+    out.write_all(&nonce.to_le_bytes()).ok()
+}
 
-    serialize_into_buffer(&action, scratch).ok()?;
+/// Canonical wire encoding of a transaction's actions (the signed message minus nonce and account).
+pub(crate) fn serialize_actions(actions: &[Action], out: &mut Vec<u8>) -> Result<()> {
+    let mut tx_actions = Vec::with_capacity(actions.len());
+    for action in actions {
+        tx_actions.extend(action_to_tx_actions(action)?);
+    }
+    if tx_actions.is_empty() {
+        return Err(Error::EmptyOrders);
+    }
+    serialize_into_buffer(&tx_actions, out)
+}
+
+/// Decode bytes produced by [`serialize_actions`], one [`Action`] per wire action.
+pub(crate) fn deserialize_actions(bytes: &[u8]) -> Result<Vec<Action>> {
+    use bincode::Options;
 
+    let tx_actions: Vec<TxAction> = bincode::DefaultOptions::new()
+        .with_fixint_encoding()
+        .with_limit(bytes.len() as u64)
+        .deserialize(bytes)
+        .map_err(|e| Error::SerializationError(e.to_string()))?;
+    tx_actions.into_iter().map(tx_action_to_action).collect()
+}
+
+/// Canonical wire encoding of a single order item, as embedded in signed messages.
+pub(crate) fn serialize_order_item(item: &OrderItem, out: &mut Vec<u8>) -> Result<()> {
+    serialize_into_buffer(&order_item_to_tx_action(item)?, out)
+}
+
+/// Digest of a list of order items over their canonical wire encoding.
+pub(crate) fn order_items_digest(items: &[OrderItem]) -> Result<Hash> {
+    use sha2::{Digest, Sha256};
+
+    let mut scratch = Vec::with_capacity(96);
     let mut hasher = Sha256::new();
-    hasher.update(seqno.to_le_bytes());
-    hasher.update(&*scratch);
-    hasher.update(account.as_bytes());
-    hasher.update(nonce.to_le_bytes());
+    hasher.update((items.len() as u64).to_le_bytes());
+    for item in items {
+        serialize_into_buffer(&order_item_to_tx_action(item)?, &mut scratch)?;
+        hasher.update((scratch.len() as u32).to_le_bytes());
+        hasher.update(&scratch);
+    }
     let hash: [u8; 32] = hasher.finalize().into();
-    Some(Hash::from_bytes(hash))
+    Ok(Hash::from_bytes(hash))
 }
@@ -1,48 +1,418 @@
 //! Transaction signing.
 
-use crate::order_id::compute_order_item_id_at_index;
+use crate::capabilities::{
+    capabilities_message, CapabilityDescriptor, PolicySummary, SignedCapabilities,
+};
+use crate::client_ids::{resolve_cancel, resolve_cancels, resolve_cancels_in, ClientIdRegistry};
+use crate::compact::{action_from_json_list, message_bytes_from_json};
+use crate::diff::check_replacement;
+use crate::escrow::{EscrowCancelAll, EscrowDescriptor};
+use crate::flatten::{flatten_position, FlattenStyle, PositionSnapshot, SymbolSpecs};
+use crate::lint::GroupOptions;
+use crate::nonce::{AnomalyCallback, NonceAnomaly, NonceWindow};
+use crate::open_orders::OpenOrderTracker;
+use crate::order_id::{compute_order_item_id_at_index, IdScheme};
+use crate::parallel::{run_batch, BatchMode, Parallelism, PARALLEL_THRESHOLD};
+use crate::plan::BatchPlan;
+use crate::prepare::{finalize_transaction, PreparedMessage};
+use crate::reconnect::{ReconnectPolicy, ReconnectReport};
+use crate::reservation::{
+    NoncePlan, NonceReservation, NonceReservations, PeekedOrderIds, DEFAULT_RESERVATION_TTL_MS,
+};
+use crate::rng::RngProvider;
+use crate::scope::{ScopeConfig, ScopedSigner};
+use crate::sdk_compat::order_items_digest;
 use crate::sdk_compat::serialize_for_sdk_signing;
 use crate::types::*;
-use crate::{Error, Keypair, NonceManager, Result};
+#[cfg(feature = "batch")]
+use crate::BatchCheckpoint;
+use crate::{DurationMs, Error, Keypair, NonceManager, NonceStrategy, Result, TimestampMs};
 use ed25519_dalek::Signer as DalekSigner;
-use rayon::prelude::*;
-use serde_json::json;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
 
-/// Threshold for switching to parallel signing.
-const PARALLEL_THRESHOLD: usize = 10;
+/// Validation run on each order item, with the nonce it will be signed under,
+/// before anything is signed. Returning `Err` aborts signing.
+pub type PreSignHook = dyn Fn(&OrderItem, u64) -> Result<()> + Send + Sync;
+
+/// Per-call limits for [`Signer::sign_with_options`] and
+/// [`Signer::sign_all_with_options`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SignOptions {
+    /// Latest time, on the signer's clock, a transaction may be produced
+    /// at; later fails with [`Error::DeadlineExceeded`]
+    pub deadline_ms: Option<TimestampMs>,
+    /// Sign orders whose client ID a live order already holds (see
+    /// [`crate::client_ids`]); the new order takes the ID over
+    pub allow_client_id_reuse: bool,
+}
+
+impl SignOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_deadline_ms(mut self, deadline: impl Into<TimestampMs>) -> Self {
+        self.deadline_ms = Some(deadline.into());
+        self
+    }
+
+    pub fn with_client_id_reuse(mut self) -> Self {
+        self.allow_client_id_reuse = true;
+        self
+    }
+}
+
+/// Counts reported by [`Signer::usage`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SignerUsage {
+    /// Transactions signed, including any then discarded for a missed deadline
+    pub signed: u64,
+    /// Calls refused with [`Error::DeadlineExceeded`]
+    pub deadline_rejections: u64,
+    /// How the latest multi-item batch was signed (see [`crate::parallel`])
+    pub last_batch_mode: Option<BatchMode>,
+    /// [`Signer::notify_reconnect`] calls
+    pub reconnects: u64,
+}
+
+/// What [`Signer::check_pre_sign`] claimed in the trackers, for
+/// [`Signer::undo_pre_sign`].
+#[derive(Debug, Default)]
+struct PreSignUndo {
+    open_orders: Option<crate::open_orders::Undo>,
+    client_ids: Option<crate::client_ids::Undo>,
+}
+
+#[derive(Default)]
+struct UsageCounters {
+    signed: AtomicU64,
+    deadline_rejections: AtomicU64,
+    /// 0: no batch yet, 1: serial, `n + 1`: `n` threads
+    last_batch_mode: AtomicU64,
+    reconnects: AtomicU64,
+}
+
+impl UsageCounters {
+    fn record_batch_mode(&self, mode: BatchMode) {
+        let code = match mode {
+            BatchMode::Serial => 1,
+            BatchMode::Parallel { threads } => threads as u64 + 1,
+        };
+        self.last_batch_mode.store(code, Ordering::Relaxed);
+    }
+
+    fn last_batch_mode(&self) -> Option<BatchMode> {
+        match self.last_batch_mode.load(Ordering::Relaxed) {
+            0 => None,
+            1 => Some(BatchMode::Serial),
+            code => Some(BatchMode::Parallel {
+                threads: (code - 1) as usize,
+            }),
+        }
+    }
+}
 
 /// High-performance signer.
 pub struct Signer {
     keypair: Keypair,
-    nonce_manager: Option<NonceManager>,
+    signer: Pubkey,
+    account: Pubkey,
+    nonce_manager: Option<Arc<NonceManager>>,
     serializer: Vec<u8>,
     compute_order_id: bool,
     compute_batch_order_ids: bool,
+    max_resign_age_ms: Option<u64>,
+    pre_sign_hook: Option<Arc<PreSignHook>>,
+    open_orders: Option<Arc<OpenOrderTracker>>,
+    client_ids: Option<Arc<ClientIdRegistry>>,
+    usage: Arc<UsageCounters>,
+    network: Option<String>,
+    parallelism: Parallelism,
+    rng: RngProvider,
+    reservations: Arc<NonceReservations>,
+    reservation_ttl: DurationMs,
+    allow_zero_client_id: bool,
+}
+
+/// Verify a base58 signature over raw bytes against `pubkey`, without a
+/// [`Signer`]; the counterpart of [`Signer::sign_bytes`].
+///
+/// A malformed signature is an error; a well-formed one that does not verify,
+/// or a pubkey that is not a valid curve point, is `Ok(false)`.
+pub fn verify_signature(pubkey: &Pubkey, message: &[u8], signature: &str) -> Result<bool> {
+    let bytes = bs58::decode(signature)
+        .into_vec()
+        .map_err(|e| Error::InvalidBase58(e.to_string()))?;
+    let bytes: [u8; 64] = bytes
+        .as_slice()
+        .try_into()
+        .map_err(|_| Error::InvalidSignatureLength(bytes.len()))?;
+    Ok(Signer::verify(pubkey, message, &bytes))
 }
 
 impl Signer {
     /// Create a signer.
+    ///
+    /// ```rust
+    /// use bulk_keychain::{Keypair, Order, Signer, TimeInForce};
+    ///
+    /// let keypair = Keypair::generate();
+    /// let pubkey = keypair.pubkey();
+    /// let mut signer = Signer::new(keypair);
+    /// let tx = signer.sign(Order::limit("BTC-USD", true, 100000.0, 0.1, TimeInForce::Gtc).into(), Some(1)).unwrap();
+    /// // Without `with_account`, the signer trades for its own account.
+    /// assert_eq!(tx.signer_pubkey().unwrap(), pubkey);
+    /// assert_eq!(tx.account_pubkey().unwrap(), pubkey);
+    /// assert!(tx.verify().unwrap());
+    /// ```
     pub fn new(keypair: Keypair) -> Self {
+        let signer = keypair.pubkey();
         Self {
             keypair,
+            signer,
+            account: signer,
             nonce_manager: None,
             serializer: Vec::with_capacity(512),
             compute_order_id: true,
             compute_batch_order_ids: false,
+            max_resign_age_ms: None,
+            pre_sign_hook: None,
+            open_orders: None,
+            client_ids: None,
+            usage: Arc::default(),
+            network: None,
+            parallelism: Parallelism::Auto,
+            rng: RngProvider::default(),
+            reservations: Arc::default(),
+            reservation_ttl: DurationMs(DEFAULT_RESERVATION_TTL_MS),
+            allow_zero_client_id: false,
         }
     }
 
     /// Create a signer with nonce management.
+    ///
+    /// ```rust
+    /// use bulk_keychain::{Keypair, NonceManager, Order, Signer, TimeInForce};
+    ///
+    /// let mut signer = Signer::with_nonce_manager(Keypair::generate(), NonceManager::counter());
+    /// let order = Order::limit("BTC-USD", true, 100000.0, 0.1, TimeInForce::Gtc);
+    /// let first = signer.sign(order.clone().into(), None).unwrap();
+    /// let second = signer.sign(order.into(), None).unwrap();
+    /// assert_eq!((first.nonce, second.nonce), (0, 1));
+    /// ```
     pub fn with_nonce_manager(keypair: Keypair, nonce_manager: NonceManager) -> Self {
+        let mut signer = Self::new(keypair);
+        signer.nonce_manager = Some(Arc::new(nonce_manager));
+        signer
+    }
+
+    /// Create a signer that runs `hook` on every order item before signing it.
+    ///
+    /// ```rust
+    /// use bulk_keychain::{Error, Keypair, Order, Signer, TimeInForce};
+    ///
+    /// // RiskLimiter: reject anything larger than 10 contracts.
+    /// let max_size = 10.0;
+    /// let mut signer = Signer::with_pre_sign_hook(Keypair::generate(), move |item, _nonce| {
+    ///     match item {
+    ///         bulk_keychain::OrderItem::Order(order) if order.size > max_size => Err(
+    ///             Error::PreSignRejected(format!("size {} exceeds {}", order.size, max_size)),
+    ///         ),
+    ///         _ => Ok(()),
+    ///     }
+    /// });
+    ///
+    /// let small = Order::limit("BTC-USD", true, 100000.0, 1.0, TimeInForce::Gtc);
+    /// let large = Order::limit("BTC-USD", true, 100000.0, 50.0, TimeInForce::Gtc);
+    /// assert!(signer.sign(small.into(), None).is_ok());
+    /// assert!(signer.sign(large.into(), None).is_err());
+    /// ```
+    pub fn with_pre_sign_hook(
+        keypair: Keypair,
+        hook: impl Fn(&OrderItem, u64) -> Result<()> + Send + Sync + 'static,
+    ) -> Self {
+        let mut signer = Self::new(keypair);
+        signer.set_pre_sign_hook(Some(Arc::new(hook)));
+        signer
+    }
+
+    /// Install or remove the pre-sign hook (see [`Signer::with_pre_sign_hook`]).
+    pub fn set_pre_sign_hook(&mut self, hook: Option<Arc<PreSignHook>>) {
+        self.pre_sign_hook = hook;
+    }
+
+    /// Refuse to sign a resting order that would leave more than `cap` open
+    /// orders on its symbol, with [`Error::OpenOrderCapExceeded`].
+    ///
+    /// Counts are kept from what this signer signs; report orders closed by
+    /// fills or the exchange with [`Signer::notify_closed`]. See
+    /// [`crate::open_orders`] for what counts as open.
+    pub fn with_max_open_orders_per_symbol(mut self, cap: usize) -> Self {
+        self.set_max_open_orders_per_symbol(Some(cap));
+        self
+    }
+
+    /// Set or remove the per-symbol open-order cap. Changing it resets the counts.
+    pub fn set_max_open_orders_per_symbol(&mut self, cap: Option<usize>) {
+        self.open_orders = cap.map(|cap| Arc::new(OpenOrderTracker::new(cap)));
+    }
+
+    pub fn max_open_orders_per_symbol(&self) -> Option<usize> {
+        self.open_orders.as_ref().map(|t| t.cap())
+    }
+
+    /// Stop counting `order_id` as open and release its client ID; returns
+    /// whether either was tracked.
+    pub fn notify_closed(&self, order_id: &Hash) -> bool {
+        let counted = self
+            .open_orders
+            .as_ref()
+            .is_some_and(|t| t.notify_closed(order_id));
+        let held = self
+            .client_ids
+            .as_ref()
+            .is_some_and(|r| r.notify_closed(order_id));
+        counted || held
+    }
+
+    /// Orders counted as open on `symbol` (0 without a cap).
+    pub fn open_order_count(&self, symbol: &str) -> usize {
+        self.open_orders
+            .as_ref()
+            .map_or(0, |t| t.open_count(symbol))
+    }
+
+    /// Refuse to sign an order whose `client_id` a live order signed here
+    /// already holds, with [`Error::ClientIdInUse`].
+    ///
+    /// IDs are released by signed cancels and by [`Signer::notify_closed`];
+    /// [`SignOptions::allow_client_id_reuse`] lets one call reuse an ID on
+    /// purpose. See [`crate::client_ids`].
+    ///
+    /// ```rust
+    /// use bulk_keychain::{Error, Hash, Keypair, Order, Signer, TimeInForce};
+    ///
+    /// let mut signer = Signer::new(Keypair::generate()).with_unique_client_ids();
+    /// let template = Order::limit("BTC-USD", true, 100000.0, 0.1, TimeInForce::Gtc)
+    ///     .with_client_id(Hash::from_bytes([1; 32]));
+    /// let live = signer.sign(template.clone().into(), Some(1)).unwrap();
+    /// assert!(matches!(
+    ///     signer.sign(template.clone().into(), Some(2)),
+    ///     Err(Error::ClientIdInUse { .. })
+    /// ));
+    ///
+    /// signer.notify_closed(&live.order_id_hash().unwrap().unwrap());
+    /// assert!(signer.sign(template.into(), Some(3)).is_ok());
+    /// ```
+    pub fn with_unique_client_ids(mut self) -> Self {
+        self.set_unique_client_ids(true);
+        self
+    }
+
+    /// Turn client-ID enforcement on or off. Turning it off forgets the IDs
+    /// held.
+    pub fn set_unique_client_ids(&mut self, enabled: bool) {
+        if !enabled {
+            self.client_ids = None;
+        } else if self.client_ids.is_none() {
+            self.client_ids = Some(Arc::new(ClientIdRegistry::new()));
+        }
+    }
+
+    pub fn unique_client_ids(&self) -> bool {
+        self.client_ids.is_some()
+    }
+
+    /// Live order holding `client_id`, if enforcement is on and one does.
+    pub fn client_id_order(&self, client_id: &Hash) -> Option<Hash> {
+        self.client_ids.as_ref()?.order_for(client_id)
+    }
+
+    /// Client IDs held by live orders (0 without enforcement).
+    pub fn live_client_ids(&self) -> usize {
+        self.client_ids.as_ref().map_or(0, |r| r.live_count())
+    }
+
+    /// Replace each [`OrderItem::CancelByClientId`] in `items` with a cancel
+    /// of the live order holding its client ID, as signing does. Use it
+    /// before handing items to the [`prepare`](crate::prepare) API, which
+    /// has no registry to resolve them against.
+    ///
+    /// Fails with [`Error::UnresolvedClientIdCancel`] without client-ID
+    /// tracking, and [`Error::ClientIdNotLive`] if no live order holds one.
+    pub fn resolve_client_id_cancels(&self, items: Vec<OrderItem>) -> Result<Vec<OrderItem>> {
+        resolve_cancels(self.client_ids.as_deref(), items)
+    }
+
+    /// Restricted handle on this signer's keypair, see [`crate::scope`].
+    pub fn scoped(&self, config: ScopeConfig) -> ScopedSigner {
+        ScopedSigner::new(self.share(), config)
+    }
+
+    /// New signer with the same key, account, settings and hooks, drawing
+    /// from the same nonce manager and open-order counts, e.g. to sign on
+    /// another thread.
+    pub fn share(&self) -> Signer {
         Self {
-            keypair,
-            nonce_manager: Some(nonce_manager),
+            keypair: self.keypair.clone_tracked("Signer::share"),
+            signer: self.signer,
+            account: self.account,
+            nonce_manager: self.nonce_manager.clone(),
             serializer: Vec::with_capacity(512),
-            compute_order_id: true,
-            compute_batch_order_ids: false,
+            compute_order_id: self.compute_order_id,
+            compute_batch_order_ids: self.compute_batch_order_ids,
+            max_resign_age_ms: self.max_resign_age_ms,
+            pre_sign_hook: self.pre_sign_hook.clone(),
+            open_orders: self.open_orders.clone(),
+            client_ids: self.client_ids.clone(),
+            usage: self.usage.clone(),
+            network: self.network.clone(),
+            parallelism: self.parallelism,
+            rng: self.rng.clone(),
+            reservations: self.reservations.clone(),
+            reservation_ttl: self.reservation_ttl,
+            allow_zero_client_id: self.allow_zero_client_id,
         }
     }
 
+    /// Trade on behalf of `account` instead of the keypair's own pubkey.
+    ///
+    /// This is the agent-wallet pattern: the keypair signs, `account` owns the orders.
+    ///
+    /// ```rust
+    /// use bulk_keychain::{Keypair, Order, Signer, TimeInForce};
+    ///
+    /// let account = Keypair::generate().pubkey();
+    /// let agent = Keypair::generate();
+    /// let agent_pubkey = agent.pubkey();
+    /// let mut signer = Signer::new(agent).with_account(account);
+    ///
+    /// let tx = signer.sign(Order::limit("BTC-USD", true, 100000.0, 0.1, TimeInForce::Gtc).into(), Some(1)).unwrap();
+    /// assert_eq!(tx.account_pubkey().unwrap(), account);
+    /// assert_eq!(tx.signer_pubkey().unwrap(), agent_pubkey);
+    /// assert!(tx.verify().unwrap());
+    /// ```
+    pub fn with_account(mut self, account: Pubkey) -> Self {
+        self.account = account;
+        self
+    }
+
+    /// Sign orders whose client ID is the all-zero hash instead of failing
+    /// with [`Error::ZeroClientId`].
+    ///
+    /// A zero client ID is still a client ID: it serializes as `cloid` and is
+    /// held by client-ID enforcement, where `client_id: None` is neither.
+    pub fn allow_zero_client_id(mut self) -> Self {
+        self.set_allow_zero_client_id(true);
+        self
+    }
+
+    /// Turn [`Signer::allow_zero_client_id`] on or off.
+    pub fn set_allow_zero_client_id(&mut self, allowed: bool) {
+        self.allow_zero_client_id = allowed;
+    }
+
     /// Disable optional pre-computed order ID generation.
     pub fn without_order_id(mut self) -> Self {
         self.compute_order_id = false;
@@ -74,136 +444,1803 @@ impl Signer {
         self
     }
 
-    /// Disable optional pre-computed batch order IDs.
-    pub fn without_batch_order_ids(mut self) -> Self {
-        self.compute_batch_order_ids = false;
-        self
+    /// Disable optional pre-computed batch order IDs.
+    pub fn without_batch_order_ids(mut self) -> Self {
+        self.compute_batch_order_ids = false;
+        self
+    }
+
+    /// Check whether batch order ID generation is enabled.
+    pub fn computes_batch_order_ids(&self) -> bool {
+        self.compute_batch_order_ids
+    }
+
+    /// Set whether batch order ID generation is enabled.
+    pub fn set_batch_order_ids(&mut self, enabled: bool) {
+        self.compute_batch_order_ids = enabled;
+    }
+
+    /// Refuse to resign transactions whose nonce is more than `max_age_ms` in the past.
+    ///
+    /// The age is measured by reading the original nonce as a millisecond
+    /// timestamp, so only enable this with timestamp nonces.
+    pub fn with_max_resign_age_ms(mut self, max_age_ms: u64) -> Self {
+        self.max_resign_age_ms = Some(max_age_ms);
+        self
+    }
+
+    /// Set or clear the resign age limit (see [`Signer::with_max_resign_age_ms`]).
+    pub fn set_max_resign_age_ms(&mut self, max_age_ms: Option<u64>) {
+        self.max_resign_age_ms = max_age_ms;
+    }
+
+    /// Get signer pubkey.
+    #[deprecated(
+        since = "0.1.16",
+        note = "Use signer_pubkey() or account_pubkey() instead"
+    )]
+    pub fn pubkey(&self) -> Pubkey {
+        self.signer
+    }
+
+    /// Get the pubkey of the signing keypair.
+    pub fn signer_pubkey(&self) -> &Pubkey {
+        &self.signer
+    }
+
+    /// Get the trading account pubkey (equals the signer pubkey unless set via `with_account`).
+    pub fn account_pubkey(&self) -> &Pubkey {
+        &self.account
+    }
+
+    /// Label the network this signer is meant for (e.g. `"mainnet"`), for
+    /// [`Signer::capabilities`]. Informational only: signing is the same on
+    /// every network.
+    pub fn with_network(mut self, network: impl Into<String>) -> Self {
+        self.network = Some(network.into());
+        self
+    }
+
+    pub fn set_network(&mut self, network: Option<String>) {
+        self.network = network;
+    }
+
+    pub fn network(&self) -> Option<&str> {
+        self.network.as_deref()
+    }
+
+    /// Describe what this signer may sign (see [`crate::capabilities`]).
+    pub fn capabilities(&self) -> CapabilityDescriptor {
+        self.capabilities_with(Vec::new(), ActionKind::ALL.to_vec())
+    }
+
+    /// [`Signer::capabilities`], signed with this signer's key.
+    pub fn capabilities_signed(&self) -> Result<SignedCapabilities> {
+        self.sign_capabilities(&self.capabilities())
+    }
+
+    pub(crate) fn capabilities_with(
+        &self,
+        scopes: Vec<ScopeConfig>,
+        supported_actions: Vec<ActionKind>,
+    ) -> CapabilityDescriptor {
+        let policy = PolicySummary {
+            pre_sign_hook: self.pre_sign_hook.is_some(),
+            max_open_orders_per_symbol: self.max_open_orders_per_symbol(),
+            max_resign_age_ms: self.max_resign_age_ms,
+            nonce_strategy: self
+                .nonce_manager
+                .as_ref()
+                .map(|m| format!("{:?}", m.strategy())),
+        };
+        CapabilityDescriptor::new(
+            &self.account,
+            &self.signer,
+            self.network.clone(),
+            policy,
+            scopes,
+            supported_actions,
+        )
+    }
+
+    pub(crate) fn sign_capabilities(
+        &self,
+        descriptor: &CapabilityDescriptor,
+    ) -> Result<SignedCapabilities> {
+        let payload = descriptor.to_json()?;
+        let signature = self.try_sign_bytes(&capabilities_message(&payload))?;
+        Ok(SignedCapabilities {
+            payload,
+            signer: self.signer.to_base58(),
+            signature,
+        })
+    }
+
+    /// Sign raw bytes and return base58 signature.
+    ///
+    /// # Panics
+    ///
+    /// If the keypair was [zeroized](Keypair::zeroize); see
+    /// [`Signer::try_sign_bytes`].
+    ///
+    /// ```rust
+    /// use bulk_keychain::{Keypair, Signer};
+    ///
+    /// let signer = Signer::new(Keypair::generate());
+    /// let signature = signer.sign_bytes(b"hello");
+    /// assert_eq!(bulk_keychain::bs58::decode(&signature).into_vec().unwrap().len(), 64);
+    /// assert!(signer.verify_bytes(b"hello", &signature).unwrap());
+    /// assert!(!signer.verify_bytes(b"other", &signature).unwrap());
+    /// ```
+    pub fn sign_bytes(&self, message: &[u8]) -> String {
+        bs58::encode(self.sign_bytes_raw(message)).into_string()
+    }
+
+    /// Sign raw bytes and return the 64-byte Ed25519 signature.
+    ///
+    /// # Panics
+    ///
+    /// If the keypair was [zeroized](Keypair::zeroize); see
+    /// [`Signer::try_sign_bytes_raw`].
+    ///
+    /// ```rust
+    /// use bulk_keychain::{Keypair, Signer};
+    ///
+    /// let signer = Signer::new(Keypair::generate());
+    /// let signature = signer.sign_bytes_raw(b"hello");
+    /// assert!(Signer::verify(signer.signer_pubkey(), b"hello", &signature));
+    /// assert!(!Signer::verify(signer.signer_pubkey(), b"other", &signature));
+    /// ```
+    pub fn sign_bytes_raw(&self, message: &[u8]) -> [u8; 64] {
+        match self.try_sign_bytes_raw(message) {
+            Ok(signature) => signature,
+            Err(e) => panic!("sign_bytes_raw: {e}"),
+        }
+    }
+
+    /// Like [`Signer::sign_bytes`], but fails with
+    /// [`Error::KeypairZeroized`] instead of panicking.
+    ///
+    /// ```rust
+    /// use bulk_keychain::{Error, Keypair, Signer};
+    ///
+    /// let mut keypair = Keypair::generate();
+    /// keypair.zeroize();
+    /// let signer = Signer::new(keypair);
+    /// assert!(matches!(signer.try_sign_bytes(b"hello"), Err(Error::KeypairZeroized)));
+    /// ```
+    pub fn try_sign_bytes(&self, message: &[u8]) -> Result<String> {
+        Ok(bs58::encode(self.try_sign_bytes_raw(message)?).into_string())
+    }
+
+    /// Like [`Signer::sign_bytes_raw`], but fails with
+    /// [`Error::KeypairZeroized`] instead of panicking.
+    pub fn try_sign_bytes_raw(&self, message: &[u8]) -> Result<[u8; 64]> {
+        Ok(self.keypair.signing_key()?.sign(message).to_bytes())
+    }
+
+    /// Check a raw Ed25519 signature over `message` against `pubkey`.
+    ///
+    /// Verification is strict (no small-order keys or malleable signatures);
+    /// a pubkey that is not a valid curve point never verifies.
+    pub fn verify(pubkey: &Pubkey, message: &[u8], signature: &[u8; 64]) -> bool {
+        let Ok(key) = ed25519_dalek::VerifyingKey::from_bytes(pubkey.as_bytes()) else {
+            return false;
+        };
+        let signature = ed25519_dalek::Signature::from_bytes(signature);
+        key.verify_strict(message, &signature).is_ok()
+    }
+
+    /// Verify a base58 signature over raw bytes against this signer's key.
+    pub fn verify_bytes(&self, message: &[u8], signature: &str) -> Result<bool> {
+        verify_signature(&self.signer, message, signature)
+    }
+
+    /// Check that `tx` carries a valid signature from this signer.
+    ///
+    /// The signed message is rebuilt from `tx.action`, `tx.nonce` and `tx.account`
+    /// unless `message` is given. Returns `false` if `tx.signer` is another key.
+    ///
+    /// ```rust
+    /// use bulk_keychain::{Keypair, Order, Signer, TimeInForce};
+    ///
+    /// let mut signer = Signer::new(Keypair::generate());
+    /// let tx = signer.sign(Order::limit("BTC-USD", true, 100000.0, 0.1, TimeInForce::Gtc).into(), Some(1)).unwrap();
+    /// assert!(signer.verify_signed_transaction(&tx, None).unwrap());
+    ///
+    /// let other = Signer::new(Keypair::generate());
+    /// assert!(!other.verify_signed_transaction(&tx, None).unwrap());
+    /// ```
+    pub fn verify_signed_transaction(
+        &self,
+        tx: &SignedTransaction,
+        message: Option<&[u8]>,
+    ) -> Result<bool> {
+        if Pubkey::from_base58(&tx.signer)? != self.signer {
+            return Ok(false);
+        }
+        match message {
+            Some(message) => self.verify_bytes(message, &tx.signature),
+            None => {
+                let account = Pubkey::from_base58(&tx.account)?;
+                let mut message = Vec::with_capacity(256);
+                serialize_for_sdk_signing(&tx.action, tx.nonce, &account, &mut message)?;
+                self.verify_bytes(&message, &tx.signature)
+            }
+        }
+    }
+
+    /// Sign a message prepared for this signer's key, keeping the prepared
+    /// account, signer and pre-computed order IDs.
+    ///
+    /// This completes agent-wallet flows with a local key: prepare for the
+    /// main account with the agent as signer, then sign here. Fails with
+    /// [`Error::SignerMismatch`] unless `prepared.signer` is this signer's
+    /// key, and with [`Error::InvalidAction`] if `prepared.actions` do not
+    /// rebuild `prepared.message_bytes`. Orders go through the pre-sign hook,
+    /// open-order caps and client-ID checks like any other.
+    ///
+    /// ```rust
+    /// use bulk_keychain::{prepare_message, Error, Keypair, Order, Signer, TimeInForce};
+    ///
+    /// let account = Keypair::generate().pubkey();
+    /// let mut agent = Signer::new(Keypair::generate());
+    /// let order = Order::limit("BTC-USD", true, 100000.0, 0.1, TimeInForce::Gtc);
+    /// let prepared =
+    ///     prepare_message(order.into(), &account, Some(agent.signer_pubkey()), Some(1)).unwrap();
+    ///
+    /// let tx = agent.sign_prepared(prepared.clone()).unwrap();
+    /// assert_eq!(tx.account_pubkey().unwrap(), account);
+    /// assert_eq!(tx.signer_pubkey().unwrap(), *agent.signer_pubkey());
+    /// assert_eq!(tx.order_id, prepared.order_id);
+    /// assert!(tx.verify().unwrap());
+    ///
+    /// let mut other = Signer::new(Keypair::generate());
+    /// assert!(matches!(other.sign_prepared(prepared), Err(Error::SignerMismatch { .. })));
+    /// ```
+    pub fn sign_prepared(&mut self, prepared: PreparedMessage) -> Result<SignedTransaction> {
+        if prepared.signer_pubkey()? != self.signer {
+            return Err(Error::SignerMismatch {
+                expected: prepared.signer,
+                got: self.signer.to_base58(),
+            });
+        }
+        let account = prepared.account_pubkey()?;
+        let message = message_bytes_from_json(&prepared.actions, prepared.nonce, &account)?;
+        if message != prepared.message_bytes {
+            return Err(Error::InvalidAction(
+                "prepared actions do not match the message bytes".to_string(),
+            ));
+        }
+        let undo = match action_from_json_list(&prepared.actions)? {
+            Action::Order { orders } => {
+                self.check_pre_sign(&orders, prepared.nonce, &account, false)?
+            }
+            _ => PreSignUndo::default(),
+        };
+        let signed = self
+            .try_sign_bytes(&prepared.message_bytes)
+            .and_then(|signature| finalize_transaction(prepared, &signature));
+        match signed {
+            Ok(tx) => {
+                self.usage.signed.fetch_add(1, Ordering::Relaxed);
+                Ok(tx)
+            }
+            Err(e) => {
+                self.undo_pre_sign(undo);
+                Err(e)
+            }
+        }
+    }
+
+    /// Shift timestamp nonces by `offset_ms` (see [`NonceManager::with_clock_offset_ms`]).
+    ///
+    /// Installs a timestamp [`NonceManager`] if the signer has none.
+    pub fn set_clock_offset_ms(&mut self, offset_ms: i64) {
+        self.nonce_manager
+            .get_or_insert_with(|| Arc::new(NonceManager::timestamp()))
+            .set_clock_offset_ms(offset_ms);
+    }
+
+    pub fn clock_offset_ms(&self) -> i64 {
+        self.nonce_manager
+            .as_ref()
+            .map_or(0, |m| m.clock_offset_ms())
+    }
+
+    /// Clock anomalies seen by the nonce manager (see [`NonceManager::anomalies`]).
+    pub fn nonce_anomalies(&self) -> Vec<NonceAnomaly> {
+        self.nonce_manager
+            .as_ref()
+            .map_or_else(Vec::new, |m| m.anomalies())
+    }
+
+    /// Call `callback` on each nonce anomaly (see [`NonceManager::set_on_anomaly`]).
+    ///
+    /// Installs a timestamp [`NonceManager`] if the signer has none.
+    pub fn set_on_nonce_anomaly(&mut self, callback: Option<AnomalyCallback>) {
+        self.nonce_manager
+            .get_or_insert_with(|| Arc::new(NonceManager::timestamp()))
+            .set_on_anomaly(callback);
+    }
+
+    /// Current time in milliseconds with the clock offset applied; the default
+    /// base nonce for batch signing.
+    pub fn now_ms(&self) -> u64 {
+        self.nonce_manager
+            .as_ref()
+            .map_or_else(crate::nonce::current_timestamp_millis, |m| m.now_ms())
+    }
+
+    /// Take the nonce the next auto-nonced call would use.
+    pub fn next_nonce(&self) -> Result<u64> {
+        self.nonce_or_next(None)
+    }
+
+    /// The nonce the next auto-nonced call would use, without taking it
+    /// (see [`NonceManager::peek_next`]).
+    pub fn peek_next_nonce(&self) -> Result<u64> {
+        match &self.nonce_manager {
+            Some(manager) => manager.try_peek_next(),
+            None => Ok(self.now_ms()),
+        }
+    }
+
+    /// Reset state that would fight resubmission after a gateway reconnect;
+    /// see [`crate::reconnect`].
+    ///
+    /// [`ReconnectPolicy::clear_open_orders`] resets every symbol's
+    /// open-order count to zero, so the caps start over.
+    ///
+    /// ```rust
+    /// use bulk_keychain::{Hash, Keypair, Order, ReconnectPolicy, Signer, TimeInForce};
+    ///
+    /// let mut signer = Signer::new(Keypair::generate()).with_unique_client_ids();
+    /// let order = Order::limit("BTC-USD", true, 100.0, 1.0, TimeInForce::Gtc)
+    ///     .with_client_id(Hash::from_bytes([1; 32]));
+    /// signer.sign(order.clone().into(), Some(1)).unwrap();
+    /// assert!(signer.sign(order.clone().into(), Some(2)).is_err());
+    ///
+    /// let policy = ReconnectPolicy { clear_dedupe: true, ..Default::default() };
+    /// assert_eq!(signer.notify_reconnect(policy).unwrap().client_ids_cleared, 1);
+    /// assert!(signer.sign(order.into(), Some(2)).is_ok());
+    /// ```
+    pub fn notify_reconnect(&self, policy: ReconnectPolicy) -> Result<ReconnectReport> {
+        let mut report = ReconnectReport::default();
+        if policy.bump_nonce_floor {
+            if let Some(manager) = &self.nonce_manager {
+                report.nonce_floor = manager.bump_floor_to_now()?;
+            }
+        }
+        let clear_open_orders = || match (policy.clear_open_orders, &self.open_orders) {
+            (true, Some(tracker)) => tracker.clear(),
+            _ => 0,
+        };
+        // Signing takes the client-ID lock before the open-order one, so
+        // clearing both under it is atomic with respect to signing.
+        (report.client_ids_cleared, report.open_orders_cleared) =
+            match (policy.clear_dedupe, &self.client_ids) {
+                (true, Some(registry)) => registry.clear_then(clear_open_orders),
+                _ => (0, clear_open_orders()),
+            };
+        self.usage.reconnects.fetch_add(1, Ordering::Relaxed);
+        Ok(report)
+    }
+
+    /// Reset the nonce manager, if any (see [`NonceManager::reset`]).
+    pub fn reset_nonce(&self) {
+        if let Some(manager) = &self.nonce_manager {
+            manager.reset();
+        }
+    }
+
+    /// Signing counts since this signer was created, shared with signers
+    /// derived from it (e.g. by a [`SignerPool`](crate::SignerPool)).
+    pub fn usage(&self) -> SignerUsage {
+        SignerUsage {
+            signed: self.usage.signed.load(Ordering::Relaxed),
+            deadline_rejections: self.usage.deadline_rejections.load(Ordering::Relaxed),
+            last_batch_mode: self.usage.last_batch_mode(),
+            reconnects: self.usage.reconnects.load(Ordering::Relaxed),
+        }
+    }
+
+    /// Pin how batch signing uses threads (default [`Parallelism::Auto`]);
+    /// see [`crate::parallel`].
+    pub fn with_parallelism(mut self, parallelism: Parallelism) -> Self {
+        self.parallelism = parallelism;
+        self
+    }
+
+    pub fn set_parallelism(&mut self, parallelism: Parallelism) {
+        self.parallelism = parallelism;
+    }
+
+    /// Draw client IDs and obfuscation seeds from `rng` instead of the OS
+    /// RNG, e.g. `StdRng::seed_from_u64(seed)` for a reproducible
+    /// simulation; see [`crate::rng`]. Never use a seeded RNG in production.
+    pub fn with_rng(mut self, rng: impl rand::RngCore + Send + 'static) -> Self {
+        self.rng = RngProvider::from_rng(rng);
+        self
+    }
+
+    pub fn set_rng(&mut self, rng: RngProvider) {
+        self.rng = rng;
+    }
+
+    pub fn rng(&self) -> &RngProvider {
+        &self.rng
+    }
+
+    /// A fresh client order ID from this signer's RNG.
+    pub fn random_client_id(&self) -> Hash {
+        self.rng.random_hash()
+    }
+
+    /// [`crate::shuffle_batch`] seeded from this signer's RNG.
+    pub fn shuffle_batch(&self, txs: &mut [SignedTransaction]) {
+        crate::shuffle_batch(txs, self.rng.next_u64());
+    }
+
+    /// [`crate::jitter_sizes`] seeded from this signer's RNG.
+    pub fn jitter_sizes(&self, orders: &mut [Order], pct: f64) -> Result<()> {
+        crate::jitter_sizes(orders, pct, self.rng.next_u64())
+    }
+
+    pub fn parallelism(&self) -> Parallelism {
+        self.parallelism
+    }
+
+    /// Fail with [`Error::DeadlineExceeded`] once the clock is past
+    /// `options.deadline_ms`; `signed` is how many items got through.
+    fn check_deadline(&self, options: &SignOptions, signed: usize) -> Result<()> {
+        let Some(deadline) = options.deadline_ms else {
+            return Ok(());
+        };
+        let deadline_ms = deadline.as_millis();
+        let now = self.now_ms();
+        if now <= deadline_ms {
+            return Ok(());
+        }
+        self.usage
+            .deadline_rejections
+            .fetch_add(1, Ordering::Relaxed);
+        Err(Error::DeadlineExceeded {
+            overrun_ms: now - deadline_ms,
+            signed,
+        })
+    }
+
+    fn check_pre_sign(
+        &self,
+        items: &[OrderItem],
+        nonce: u64,
+        account: &Pubkey,
+        allow_client_id_reuse: bool,
+    ) -> Result<PreSignUndo> {
+        self.check_pre_sign_with(
+            self.open_orders.as_deref(),
+            self.client_ids.as_deref(),
+            items,
+            nonce,
+            account,
+            allow_client_id_reuse,
+        )
+    }
+
+    /// [`Signer::check_pre_sign`] against the given trackers, which are the
+    /// signer's own or dry-run forks of them.
+    fn check_pre_sign_with(
+        &self,
+        open_orders: Option<&OpenOrderTracker>,
+        client_ids: Option<&ClientIdRegistry>,
+        items: &[OrderItem],
+        nonce: u64,
+        account: &Pubkey,
+        allow_client_id_reuse: bool,
+    ) -> Result<PreSignUndo> {
+        fn zero(item: &OrderItem) -> bool {
+            match item {
+                OrderItem::Order(o) => o.client_id.is_some_and(|id| id.is_zero()),
+                OrderItem::TriggerBasket(b) => b.actions.iter().any(zero),
+                OrderItem::OnFill(of) => of.actions.iter().any(zero),
+                _ => false,
+            }
+        }
+        if !self.allow_zero_client_id && items.iter().any(zero) {
+            return Err(Error::ZeroClientId);
+        }
+        if let Some(hook) = &self.pre_sign_hook {
+            for item in items {
+                hook(item, nonce)?;
+            }
+        }
+        let open_orders = || match open_orders {
+            Some(tracker) => tracker.apply(items, nonce, account).map(Some),
+            None => Ok(None),
+        };
+        let (client_ids, open_orders) = match client_ids {
+            Some(registry) => {
+                let (undo, open_orders) =
+                    registry.apply(items, nonce, account, allow_client_id_reuse, open_orders)?;
+                (Some(undo), open_orders)
+            }
+            None => (None, open_orders()?),
+        };
+        Ok(PreSignUndo {
+            open_orders,
+            client_ids,
+        })
+    }
+
+    /// Take back what [`Signer::check_pre_sign`] claimed, for a transaction
+    /// that ended up not being signed or returned.
+    fn undo_pre_sign(&self, undo: PreSignUndo) {
+        if let (Some(tracker), Some(undo)) = (self.open_orders.as_deref(), undo.open_orders) {
+            tracker.undo(undo);
+        }
+        if let (Some(registry), Some(undo)) = (self.client_ids.as_deref(), undo.client_ids) {
+            registry.undo(undo);
+        }
+    }
+
+    fn nonce_or_next(&self, nonce: Option<u64>) -> Result<u64> {
+        match (nonce, &self.nonce_manager) {
+            (Some(nonce), _) => Ok(nonce),
+            (None, Some(manager)) => manager.try_next(),
+            (None, None) => Ok(self.now_ms()),
+        }
+    }
+
+    /// Base for `base + i` batch nonces of `count` items. Batches are
+    /// orders, so a content-derived manager refuses them.
+    pub(crate) fn base_nonce_or_now(&self, base_nonce: Option<u64>, count: usize) -> Result<u64> {
+        let base = match (
+            base_nonce,
+            self.nonce_manager.as_ref().map(|m| m.strategy()),
+        ) {
+            (Some(base), _) => base,
+            (None, Some(crate::NonceStrategy::ContentDerived { .. })) => {
+                return Err(Error::ContentDerivedNonce("order"))
+            }
+            (None, _) => self.now_ms(),
+        };
+        crate::nonce::check_nonce_range(base, count)
+    }
+
+    /// Like `nonce_or_next`, but derives the nonce from `action` under
+    /// [`NonceStrategy::ContentDerived`](crate::NonceStrategy::ContentDerived).
+    fn nonce_for(&self, nonce: Option<u64>, action: &Action) -> Result<u64> {
+        match (nonce, self.nonce_manager.as_ref().map(|m| m.strategy())) {
+            (None, Some(crate::NonceStrategy::ContentDerived { epoch })) => {
+                crate::nonce::content_derived_nonce(action, &self.account, epoch)
+            }
+            _ => self.nonce_or_next(nonce),
+        }
+    }
+
+    /// Low-level signing entrypoint.
+    ///
+    /// ```rust
+    /// use bulk_keychain::{Action, Keypair, Order, Signer};
+    ///
+    /// let mut signer = Signer::new(Keypair::generate());
+    /// let account = Keypair::generate().pubkey();
+    /// let action = Action::Order {
+    ///     orders: vec![Order::market("BTC-USD", true, 0.1).into()],
+    /// };
+    /// let tx = signer.sign_action(&action, 1, &account).unwrap();
+    /// assert_eq!(tx.account_pubkey().unwrap(), account);
+    /// assert_eq!(tx.signer_pubkey().unwrap(), *signer.signer_pubkey());
+    /// assert!(tx.verify().unwrap());
+    /// ```
+    pub fn sign_action(
+        &mut self,
+        action: &Action,
+        nonce: u64,
+        account: &Pubkey,
+    ) -> Result<SignedTransaction> {
+        self.sign_action_checked(action, nonce, account, false)
+    }
+
+    fn sign_action_checked(
+        &mut self,
+        action: &Action,
+        nonce: u64,
+        account: &Pubkey,
+        allow_client_id_reuse: bool,
+    ) -> Result<SignedTransaction> {
+        if let Action::Order { orders } = action {
+            let resolved = resolve_cancels_in(self.client_ids.as_deref(), orders)?;
+            if let std::borrow::Cow::Owned(orders) = resolved {
+                let action = Action::Order { orders };
+                return self.sign_action_checked(&action, nonce, account, allow_client_id_reuse);
+            }
+            let undo = self.check_pre_sign(orders, nonce, account, allow_client_id_reuse)?;
+            let signed = self.sign_action_unchecked(action, nonce, account);
+            if signed.is_err() {
+                self.undo_pre_sign(undo);
+            }
+            return signed;
+        }
+        self.sign_action_unchecked(action, nonce, account)
+    }
+
+    /// [`Signer::sign_action`] without the pre-sign hook or open-order tracking.
+    fn sign_action_unchecked(
+        &mut self,
+        action: &Action,
+        nonce: u64,
+        account: &Pubkey,
+    ) -> Result<SignedTransaction> {
+        let signer_pubkey = self.signer;
+        serialize_for_sdk_signing(action, nonce, account, &mut self.serializer)?;
+
+        let order_id = if self.compute_order_id {
+            self.compute_action_order_id(action, nonce, account)
+        } else {
+            None
+        };
+        let order_ids = if self.compute_batch_order_ids {
+            self.compute_action_order_ids(action, nonce, account)
+        } else {
+            None
+        };
+
+        let signature = self.try_sign_bytes(&self.serializer)?;
+        self.usage.signed.fetch_add(1, Ordering::Relaxed);
+
+        Ok(SignedTransaction {
+            action: action.clone(),
+            nonce,
+            account: account.to_base58(),
+            signer: signer_pubkey.to_base58(),
+            signature,
+            order_id,
+            order_ids,
+            id_scheme: IdScheme::CURRENT,
+            resign_count: 0,
+            group_kind: None,
+            group_warnings: Vec::new(),
+        })
+    }
+
+    /// Re-sign a rejected transaction's actions under the next nonce.
+    ///
+    /// The actions are taken from `tx.action` and re-signed unchanged for
+    /// `tx.account`; only the nonce, signature and order IDs change, and
+    /// `resign_count` is incremented.
+    ///
+    /// ```rust
+    /// use bulk_keychain::{Keypair, NonceManager, Order, Signer, TimeInForce};
+    ///
+    /// let mut signer = Signer::with_nonce_manager(Keypair::generate(), NonceManager::counter());
+    /// let tx = signer.sign(Order::limit("BTC-USD", true, 100000.0, 0.1, TimeInForce::Gtc).into(), None).unwrap();
+    /// let resigned = signer.resign_with_new_nonce(&tx).unwrap();
+    /// assert_eq!(resigned.action, tx.action);
+    /// assert_eq!(resigned.nonce, tx.nonce + 1);
+    /// assert_eq!(resigned.resign_count, 1);
+    /// assert_ne!(resigned.order_id, tx.order_id);
+    /// assert!(resigned.verify().unwrap());
+    /// ```
+    pub fn resign_with_new_nonce(&mut self, tx: &SignedTransaction) -> Result<SignedTransaction> {
+        if let Some(max_age_ms) = self.max_resign_age_ms {
+            let age_ms = self.now_ms().saturating_sub(tx.nonce);
+            if age_ms > max_age_ms {
+                return Err(Error::ResignTooOld { age_ms, max_age_ms });
+            }
+        }
+        let account = Pubkey::from_base58(&tx.account)?;
+        let nonce = self.nonce_for(None, &tx.action)?;
+
+        // The same orders again: their client IDs move to the new order IDs.
+        let mut resigned = self.sign_action_checked(&tx.action, nonce, &account, true)?;
+        resigned.resign_count = tx.resign_count + 1;
+        resigned.group_kind = tx.group_kind.clone();
+        resigned.group_warnings = tx.group_warnings.clone();
+        Ok(resigned)
+    }
+
+    /// Sign using the signer's configured account.
+    ///
+    /// ```rust
+    /// use bulk_keychain::{Action, Faucet, Keypair, Signer};
+    ///
+    /// let mut signer = Signer::new(Keypair::generate());
+    /// let action = Action::Faucet(Faucet::new(*signer.account_pubkey()));
+    /// let tx = signer.sign_action_self(&action, 7).unwrap();
+    /// assert_eq!(tx.account_pubkey().unwrap(), *signer.account_pubkey());
+    /// assert_eq!(tx.nonce, 7);
+    /// assert!(tx.verify().unwrap());
+    /// ```
+    pub fn sign_action_self(&mut self, action: &Action, nonce: u64) -> Result<SignedTransaction> {
+        let account = self.account;
+        self.sign_action(action, nonce, &account)
+    }
+
+    /// Sign a single order item.
+    ///
+    /// ```rust
+    /// use bulk_keychain::{compute_order_item_id, Keypair, Order, OrderItem, Signer, TimeInForce};
+    ///
+    /// let mut signer = Signer::new(Keypair::generate());
+    /// let order: OrderItem = Order::limit("BTC-USD", true, 100000.0, 0.1, TimeInForce::Gtc).into();
+    /// let tx = signer.sign(order.clone(), Some(1_700_000_000_000)).unwrap();
+    ///
+    /// assert_eq!(tx.signature_bytes().unwrap().len(), 64);
+    /// let expected = compute_order_item_id(&order, tx.nonce, signer.account_pubkey());
+    /// assert_eq!(tx.order_id_hash().unwrap(), expected);
+    /// assert!(tx.verify().unwrap());
+    /// ```
+    pub fn sign(&mut self, item: OrderItem, nonce: Option<u64>) -> Result<SignedTransaction> {
+        let item = resolve_cancel(self.client_ids.as_deref(), item)?;
+        let action = Action::Order { orders: vec![item] };
+        let nonce = self.nonce_for(nonce, &action)?;
+        self.sign_action_self(&action, nonce)
+    }
+
+    /// Like [`Signer::sign`], but the clock is checked against
+    /// `options.deadline_ms` before and after signing; past it, the call
+    /// fails with [`Error::DeadlineExceeded`] and no transaction is returned.
+    ///
+    /// ```rust
+    /// use bulk_keychain::{Error, Keypair, Order, SignOptions, Signer};
+    ///
+    /// let mut signer = Signer::new(Keypair::generate());
+    /// let order = Order::market("BTC-USD", true, 0.1);
+    /// let late = SignOptions::new().with_deadline_ms(signer.now_ms() - 5);
+    /// let err = signer.sign_with_options(order.clone().into(), None, &late).unwrap_err();
+    /// assert!(matches!(err, Error::DeadlineExceeded { overrun_ms, signed: 0 } if overrun_ms >= 5));
+    ///
+    /// let budget = SignOptions::new().with_deadline_ms(signer.now_ms() + 60_000);
+    /// assert!(signer.sign_with_options(order.into(), None, &budget).is_ok());
+    /// assert_eq!(signer.usage().deadline_rejections, 1);
+    /// ```
+    pub fn sign_with_options(
+        &mut self,
+        item: OrderItem,
+        nonce: Option<u64>,
+        options: &SignOptions,
+    ) -> Result<SignedTransaction> {
+        self.check_deadline(options, 0)?;
+        let item = resolve_cancel(self.client_ids.as_deref(), item)?;
+        let action = Action::Order { orders: vec![item] };
+        let nonce = self.nonce_for(nonce, &action)?;
+        let account = self.account;
+        let tx =
+            self.sign_action_checked(&action, nonce, &account, options.allow_client_id_reuse)?;
+        self.check_deadline(options, 0)?;
+        Ok(tx)
+    }
+
+    /// Sign multiple independent items, in parallel where it pays off (see
+    /// [`crate::parallel`]).
+    ///
+    /// Output `i` always corresponds to input `i` and uses nonce `base + i`,
+    /// on both the serial and the parallel path.
+    ///
+    /// With open-order caps or client-ID uniqueness on, the batch is signed
+    /// in input order on the calling thread and is all or nothing: if an
+    /// item is refused, the open orders and client IDs claimed by the items
+    /// before it are released again.
+    ///
+    /// ```rust
+    /// use bulk_keychain::{Keypair, Order, Signer, TimeInForce};
+    ///
+    /// let signer = Signer::new(Keypair::generate());
+    /// let orders = (0..3)
+    ///     .map(|i| Order::limit("BTC-USD", true, 100000.0 + i as f64, 0.1, TimeInForce::Gtc).into())
+    ///     .collect();
+    /// let txs = signer.sign_all(orders, Some(100)).unwrap();
+    /// let nonces: Vec<u64> = txs.iter().map(|tx| tx.nonce).collect();
+    /// assert_eq!(nonces, [100, 101, 102]);
+    /// assert!(txs.iter().all(|tx| tx.order_id.is_some() && tx.verify().unwrap()));
+    /// ```
+    pub fn sign_all(
+        &self,
+        items: Vec<OrderItem>,
+        base_nonce: Option<u64>,
+    ) -> Result<Vec<SignedTransaction>> {
+        self.sign_all_checked(items, base_nonce, false)
+    }
+
+    fn sign_all_checked(
+        &self,
+        items: Vec<OrderItem>,
+        base_nonce: Option<u64>,
+        allow_client_id_reuse: bool,
+    ) -> Result<Vec<SignedTransaction>> {
+        if items.is_empty() {
+            return Ok(vec![]);
+        }
+
+        let base = self.base_nonce_or_now(base_nonce, items.len())?;
+        if self.open_orders.is_some() || self.client_ids.is_some() {
+            return self.sign_all_in_order(items, |i, item| {
+                self.sign_single_item_tracked(item, base + i as u64, allow_client_id_reuse)
+            });
+        }
+        let (results, mode) = run_batch(self.parallelism, PARALLEL_THRESHOLD, items, |i, item| {
+            self.sign_single_item(item, base + i as u64, allow_client_id_reuse)
+        });
+        self.usage.record_batch_mode(mode);
+        results.into_iter().collect()
+    }
+
+    /// Sign `items` in input order on the calling thread, all or nothing:
+    /// if one fails, what the items before it claimed in the open-order
+    /// and client-ID trackers is taken back out, so the batch can be
+    /// resubmitted.
+    fn sign_all_in_order<T>(
+        &self,
+        items: Vec<T>,
+        mut sign: impl FnMut(usize, T) -> Result<(SignedTransaction, PreSignUndo)>,
+    ) -> Result<Vec<SignedTransaction>> {
+        self.usage.record_batch_mode(BatchMode::Serial);
+        let mut txs = Vec::with_capacity(items.len());
+        let mut undos = Vec::with_capacity(items.len());
+        for (i, item) in items.into_iter().enumerate() {
+            match sign(i, item) {
+                Ok((tx, undo)) => {
+                    txs.push(tx);
+                    undos.push(undo);
+                }
+                Err(e) => {
+                    for undo in undos.into_iter().rev() {
+                        self.undo_pre_sign(undo);
+                    }
+                    return Err(e);
+                }
+            }
+        }
+        Ok(txs)
+    }
+
+    /// Like [`Signer::sign_all`], but signs every item it can: output `i` is
+    /// item `i`'s transaction (nonce `base + i`) or the reason it was refused.
+    ///
+    /// With open-order caps or client-ID uniqueness on, whether an item
+    /// passes depends on the items before it, so the batch is signed in
+    /// input order on the calling thread.
+    ///
+    /// ```rust
+    /// use bulk_keychain::{Keypair, Order, Signer, TimeInForce};
+    ///
+    /// let signer = Signer::new(Keypair::generate()).with_max_open_orders_per_symbol(1);
+    /// let order = || Order::limit("BTC-USD", true, 100.0, 1.0, TimeInForce::Gtc).into();
+    /// let results = signer.sign_all_partial(vec![order(), order()], Some(1)).unwrap();
+    /// assert!(results[0].is_ok());
+    /// assert!(results[1].is_err());
+    /// ```
+    pub fn sign_all_partial(
+        &self,
+        items: Vec<OrderItem>,
+        base_nonce: Option<u64>,
+    ) -> Result<Vec<Result<SignedTransaction>>> {
+        if items.is_empty() {
+            return Ok(vec![]);
+        }
+        let base = self.base_nonce_or_now(base_nonce, items.len())?;
+        if self.open_orders.is_some() || self.client_ids.is_some() {
+            self.usage.record_batch_mode(BatchMode::Serial);
+            return Ok(items
+                .into_iter()
+                .enumerate()
+                .map(|(i, item)| self.sign_single_item(item, base + i as u64, false))
+                .collect());
+        }
+        let (results, mode) = run_batch(self.parallelism, PARALLEL_THRESHOLD, items, |i, item| {
+            self.sign_single_item(item, base + i as u64, false)
+        });
+        self.usage.record_batch_mode(mode);
+        Ok(results)
+    }
+
+    /// Like [`Signer::sign_all_partial`], but pairs each outcome with its input index.
+    ///
+    /// ```rust
+    /// use bulk_keychain::{Keypair, Order, Signer, TimeInForce};
+    ///
+    /// let signer = Signer::new(Keypair::generate()).with_max_open_orders_per_symbol(1);
+    /// let order = || Order::limit("BTC-USD", true, 100.0, 1.0, TimeInForce::Gtc).into();
+    /// let results = signer.sign_all_partial_indexed(vec![order(), order()], Some(1)).unwrap();
+    /// assert!(matches!(&results[0], (0, Ok(tx)) if tx.nonce == 1));
+    /// assert!(matches!(&results[1], (1, Err(_))));
+    /// ```
+    pub fn sign_all_partial_indexed(
+        &self,
+        items: Vec<OrderItem>,
+        base_nonce: Option<u64>,
+    ) -> Result<Vec<(usize, Result<SignedTransaction>)>> {
+        Ok(self
+            .sign_all_partial(items, base_nonce)?
+            .into_iter()
+            .enumerate()
+            .collect())
+    }
+
+    /// Report what [`Signer::sign_all_partial`] would do with `items`
+    /// without signing anything; see [`crate::plan`].
+    ///
+    /// Rejections come from the same checks signing runs, applied in input
+    /// order to copies of the open-order and client-ID state, so they match
+    /// a `sign_all_partial` call made right after with the same input and
+    /// base nonce. No nonce is taken and usage is not counted.
+    ///
+    /// ```rust
+    /// use bulk_keychain::{Keypair, Order, Signer, TimeInForce};
+    ///
+    /// let signer = Signer::new(Keypair::generate()).with_max_open_orders_per_symbol(1);
+    /// let orders = vec![
+    ///     Order::limit("BTC-USD", true, 100.0, 2.0, TimeInForce::Gtc).into(),
+    ///     Order::limit("BTC-USD", true, 101.0, 1.0, TimeInForce::Gtc).into(),
+    /// ];
+    /// let plan = signer.plan_batch(&orders, Some(10)).unwrap();
+    /// assert_eq!(plan.nonce_range, 10..12);
+    /// assert_eq!(plan.rejections.len(), 1);
+    /// assert_eq!(plan.rejections[0].0, 1);
+    /// assert_eq!(plan.per_symbol_notional["BTC-USD"], 200.0);
+    /// assert_eq!(plan.estimated_groups, 1);
+    /// assert_eq!(signer.open_order_count("BTC-USD"), 0);
+    /// ```
+    pub fn plan_batch(&self, items: &[OrderItem], base_nonce: Option<u64>) -> Result<BatchPlan> {
+        self.plan_batch_with_specs(items, base_nonce, &SymbolSpecs::new())
+    }
+
+    /// [`Signer::plan_batch`], also reporting prices and sizes that
+    /// rounding to `specs` would change.
+    pub fn plan_batch_with_specs(
+        &self,
+        items: &[OrderItem],
+        base_nonce: Option<u64>,
+        specs: &SymbolSpecs,
+    ) -> Result<BatchPlan> {
+        let base = if items.is_empty() {
+            base_nonce.unwrap_or_else(|| self.now_ms())
+        } else {
+            self.base_nonce_or_now(base_nonce, items.len())?
+        };
+        let open_orders = self.open_orders.as_deref().map(OpenOrderTracker::fork);
+        let client_ids = self.client_ids.as_deref().map(ClientIdRegistry::fork);
+
+        let mut plan = BatchPlan {
+            per_symbol_notional: Default::default(),
+            rejections: Vec::new(),
+            adjustments: Vec::new(),
+            nonce_range: base..base + items.len() as u64,
+            estimated_groups: 0,
+        };
+        let mut scratch = Vec::with_capacity(512);
+        for (i, item) in items.iter().enumerate() {
+            plan.adjustments.extend(
+                crate::plan::adjustments(item, specs)
+                    .into_iter()
+                    .map(|a| (i, a)),
+            );
+            let nonce = base + i as u64;
+            let checked = resolve_cancel(client_ids.as_ref(), item.clone())
+                .and_then(|item| {
+                    self.check_pre_sign_with(
+                        open_orders.as_ref(),
+                        client_ids.as_ref(),
+                        std::slice::from_ref(&item),
+                        nonce,
+                        &self.account,
+                        false,
+                    )?;
+                    Ok(item)
+                })
+                .and_then(|item| {
+                    let action = Action::Order { orders: vec![item] };
+                    serialize_for_sdk_signing(&action, nonce, &self.account, &mut scratch)
+                });
+            match checked {
+                Ok(()) => {
+                    plan.estimated_groups += 1;
+                    if let Some((symbol, notional)) = crate::plan::notional(item) {
+                        *plan
+                            .per_symbol_notional
+                            .entry(symbol.to_string())
+                            .or_insert(0.0) += notional;
+                    }
+                }
+                Err(e) => plan.rejections.push((i, e)),
+            }
+        }
+        Ok(plan)
+    }
+
+    /// Order IDs `items` will get, before signing them.
+    ///
+    /// With [`NoncePlan::Explicit`], item `i` is computed at `base + i`, as
+    /// [`Signer::sign_all`] with that base would sign it. With
+    /// [`NoncePlan::Reserve`], fresh nonces are set aside for the batch;
+    /// sign it with [`Signer::sign_all_reserved`] before the reservation
+    /// expires. See [`crate::reservation`].
+    ///
+    /// ```rust
+    /// use bulk_keychain::{Keypair, NoncePlan, Order, OrderItem, Signer, TimeInForce};
+    ///
+    /// let signer = Signer::new(Keypair::generate());
+    /// let orders: Vec<OrderItem> = (0..3)
+    ///     .map(|i| Order::limit("BTC-USD", true, 100000.0 + i as f64, 0.1, TimeInForce::Gtc).into())
+    ///     .collect();
+    /// let peeked = signer.peek_order_ids(&orders, NoncePlan::Reserve).unwrap();
+    /// let reservation = peeked.reservation.unwrap();
+    /// let txs = signer.sign_all_reserved(orders, &reservation).unwrap();
+    /// for (tx, id) in txs.iter().zip(&peeked.order_ids) {
+    ///     assert_eq!(tx.order_id_hash().unwrap(), *id);
+    /// }
+    /// ```
+    pub fn peek_order_ids(&self, items: &[OrderItem], plan: NoncePlan) -> Result<PeekedOrderIds> {
+        let items = resolve_cancels_in(self.client_ids.as_deref(), items)?;
+        let (base, reservation) = match plan {
+            NoncePlan::Explicit(base) => (base, None),
+            NoncePlan::Reserve => {
+                let floor = self.base_nonce_or_now(None, items.len())?;
+                let reservation = self.reservations.reserve(
+                    floor,
+                    items.len(),
+                    order_items_digest(&items)?,
+                    TimestampMs(self.now_ms()),
+                    self.reservation_ttl,
+                );
+                (reservation.base_nonce, Some(reservation))
+            }
+        };
+        let order_ids = items
+            .iter()
+            .enumerate()
+            .map(|(i, item)| {
+                compute_order_item_id_at_index(item, 0, base + i as u64, &self.account)
+            })
+            .collect();
+        Ok(PeekedOrderIds {
+            order_ids,
+            reservation,
+        })
+    }
+
+    /// Sign `items` at the nonces of `reservation`, which
+    /// [`Signer::peek_order_ids`] issued for exactly these items.
+    ///
+    /// The reservation is used up whether or not signing succeeds; an
+    /// unknown, reused, expired or mismatched one fails with
+    /// [`Error::Reservation`].
+    pub fn sign_all_reserved(
+        &self,
+        items: Vec<OrderItem>,
+        reservation: &NonceReservation,
+    ) -> Result<Vec<SignedTransaction>> {
+        let items = resolve_cancels(self.client_ids.as_deref(), items)?;
+        self.reservations.take(
+            reservation,
+            order_items_digest(&items)?,
+            TimestampMs(self.now_ms()),
+        )?;
+        self.sign_all(items, Some(reservation.base_nonce))
+    }
+
+    /// How long reservations from [`Signer::peek_order_ids`] hold (default
+    /// [`DEFAULT_RESERVATION_TTL_MS`]).
+    pub fn with_reservation_ttl(mut self, ttl: DurationMs) -> Self {
+        self.reservation_ttl = ttl;
+        self
+    }
+
+    /// Like [`Signer::sign_all`], but checks `options.deadline_ms` before
+    /// and after each item.
+    ///
+    /// With a deadline, items are signed in order on the calling thread
+    /// (reported as [`BatchMode::Serial`]) and
+    /// the batch stops at the first item to miss it: the call fails with
+    /// [`Error::DeadlineExceeded`], whose `signed` is the index it stopped
+    /// at, and none of the batch is returned or kept in the trackers.
+    pub fn sign_all_with_options(
+        &self,
+        items: Vec<OrderItem>,
+        base_nonce: Option<u64>,
+        options: &SignOptions,
+    ) -> Result<Vec<SignedTransaction>> {
+        if options.deadline_ms.is_none() {
+            return self.sign_all_checked(items, base_nonce, options.allow_client_id_reuse);
+        }
+        let base = self.base_nonce_or_now(base_nonce, items.len())?;
+        self.sign_all_in_order(items, |i, item| {
+            self.check_deadline(options, i)?;
+            let (tx, undo) = self.sign_single_item_tracked(
+                item,
+                base + i as u64,
+                options.allow_client_id_reuse,
+            )?;
+            if let Err(e) = self.check_deadline(options, i) {
+                self.undo_pre_sign(undo);
+                return Err(e);
+            }
+            Ok((tx, undo))
+        })
+    }
+
+    /// Sign a cancel-all for `symbols` (empty = every symbol) that the
+    /// server only accepts from `not_before`, to hand to a third party as
+    /// a kill switch. See the [`escrow`](crate::escrow) module for how the
+    /// timing is enforced and its limits.
+    ///
+    /// Assumes the server's nonce window is [`NonceWindow::default`]; use
+    /// [`Signer::sign_escrow_cancel_all_with_window`] if it differs. The
+    /// pre-sign hook runs, but open-order tracking is untouched since
+    /// nothing is cancelled until the holder submits.
+    ///
+    /// ```rust
+    /// use bulk_keychain::escrow::{is_escrow_valid_now, EscrowStatus};
+    /// use bulk_keychain::{Keypair, NonceStrategy, Signer};
+    ///
+    /// let mut signer = Signer::new(Keypair::generate());
+    /// let not_before = signer.now_ms() + 3_600_000;
+    /// let escrow = signer
+    ///     .sign_escrow_cancel_all(vec!["BTC-USD".into()], not_before, NonceStrategy::Timestamp)
+    ///     .unwrap();
+    /// assert!(!is_escrow_valid_now(&escrow, || not_before - 1));
+    /// assert!(is_escrow_valid_now(&escrow, || not_before));
+    /// assert_eq!(escrow.descriptor.status_at(not_before - 1), EscrowStatus::TooEarly { wait_ms: 1 });
+    /// ```
+    pub fn sign_escrow_cancel_all(
+        &mut self,
+        symbols: Vec<String>,
+        not_before: impl Into<TimestampMs>,
+        nonce_strategy: NonceStrategy,
+    ) -> Result<EscrowCancelAll> {
+        self.sign_escrow_cancel_all_with_window(
+            symbols,
+            not_before,
+            nonce_strategy,
+            NonceWindow::default(),
+        )
+    }
+
+    /// [`Signer::sign_escrow_cancel_all`] for a server with nonce window `window`.
+    pub fn sign_escrow_cancel_all_with_window(
+        &mut self,
+        symbols: Vec<String>,
+        not_before: impl Into<TimestampMs>,
+        nonce_strategy: NonceStrategy,
+        window: NonceWindow,
+    ) -> Result<EscrowCancelAll> {
+        let descriptor = EscrowDescriptor::new(
+            &self.account,
+            &self.signer,
+            symbols,
+            not_before.into(),
+            nonce_strategy,
+            window,
+        )?;
+        if descriptor.valid_until_ms < self.now_ms() {
+            return Err(Error::Escrow(format!(
+                "window closed at {}ms, before now",
+                descriptor.valid_until_ms
+            )));
+        }
+        let item = OrderItem::CancelAll(CancelAll::for_symbols(descriptor.symbols.clone()));
+        if let Some(hook) = &self.pre_sign_hook {
+            hook(&item, descriptor.nonce)?;
+        }
+        let action = Action::Order { orders: vec![item] };
+        let account = self.account;
+        let tx = self.sign_action_unchecked(&action, descriptor.nonce, &account)?;
+        Ok(EscrowCancelAll { tx, descriptor })
+    }
+
+    /// Sign the reduce-only orders that flatten `pos`, one transaction each,
+    /// with nonces `base + i` as in [`Signer::sign_all`].
+    ///
+    /// A flat position signs nothing. See [`flatten_position`].
+    ///
+    /// ```rust
+    /// use bulk_keychain::{FlattenStyle, Keypair, PositionSnapshot, Signer, SymbolSpecs};
+    ///
+    /// let signer = Signer::new(Keypair::generate());
+    /// let pos = PositionSnapshot { symbol: "BTC-USD".into(), size: -0.3, mark_price: 100000.0 };
+    /// let specs = SymbolSpecs::new().with_max_order_size("BTC-USD", 0.2);
+    /// let txs = signer
+    ///     .sign_flatten(&pos, FlattenStyle::LimitAtOffsetBps(50.0), &specs, Some(10))
+    ///     .unwrap();
+    /// assert_eq!(txs.len(), 2);
+    /// assert!(txs.iter().all(|tx| tx.verify().unwrap()));
+    /// ```
+    pub fn sign_flatten(
+        &self,
+        pos: &PositionSnapshot,
+        style: FlattenStyle,
+        specs: &SymbolSpecs,
+        base_nonce: Option<u64>,
+    ) -> Result<Vec<SignedTransaction>> {
+        let orders = flatten_position(pos, style, specs)?;
+        self.sign_all(
+            orders.into_iter().map(OrderItem::from).collect(),
+            base_nonce,
+        )
+    }
+
+    /// Like [`Signer::sign_all`], but calls `hook(index, &tx)` as soon as each
+    /// transaction is signed, e.g. to push it onto a socket.
+    ///
+    /// Signing is always sequential so the hook sees items in input order;
+    /// for large batches this trades peak throughput for first-result latency.
+    ///
+    /// ```rust
+    /// use bulk_keychain::{Keypair, Order, Signer};
+    ///
+    /// let signer = Signer::new(Keypair::generate());
+    /// let orders = vec![
+    ///     Order::market("BTC-USD", true, 0.1).into(),
+    ///     Order::market("ETH-USD", true, 1.0).into(),
+    /// ];
+    /// let mut sent = Vec::new();
+    /// let txs = signer
+    ///     .sign_all_with_post_hook(orders, Some(1), |i, tx| sent.push((i, tx.nonce)))
+    ///     .unwrap();
+    /// assert_eq!(sent, [(0, 1), (1, 2)]);
+    /// assert_eq!(txs.len(), 2);
+    /// ```
+    pub fn sign_all_with_post_hook<F>(
+        &self,
+        items: Vec<OrderItem>,
+        base_nonce: Option<u64>,
+        mut hook: F,
+    ) -> Result<Vec<SignedTransaction>>
+    where
+        F: FnMut(usize, &SignedTransaction),
+    {
+        let base = self.base_nonce_or_now(base_nonce, items.len())?;
+        items
+            .into_iter()
+            .enumerate()
+            .map(|(i, item)| {
+                let tx = self.sign_single_item(item, base + i as u64, false)?;
+                hook(i, &tx);
+                Ok(tx)
+            })
+            .collect()
+    }
+
+    /// Estimated fee of each item at `fee_bps`, without signing anything.
+    ///
+    /// See [`OrderItem::estimated_fee`] for how each item type is priced.
+    pub fn sign_all_cost_estimate(&self, items: &[OrderItem], fee_bps: f64) -> Vec<f64> {
+        items
+            .iter()
+            .map(|item| item.estimated_fee(fee_bps))
+            .collect()
+    }
+
+    /// Sum of [`Signer::sign_all_cost_estimate`].
+    pub fn sign_all_total_cost_estimate(&self, items: &[OrderItem], fee_bps: f64) -> f64 {
+        items.iter().map(|item| item.estimated_fee(fee_bps)).sum()
+    }
+
+    /// Like [`Signer::sign_all`], but tags each transaction with its input index.
+    ///
+    /// ```rust
+    /// use bulk_keychain::{Keypair, Order, Signer};
+    ///
+    /// let signer = Signer::new(Keypair::generate());
+    /// let orders = vec![
+    ///     Order::market("BTC-USD", true, 0.1).into(),
+    ///     Order::market("ETH-USD", true, 1.0).into(),
+    /// ];
+    /// let txs = signer.sign_all_indexed(orders, Some(1)).unwrap();
+    /// assert_eq!((txs[1].index, txs[1].tx.nonce), (1, 2));
+    /// assert!(txs[1].tx.verify().unwrap());
+    /// ```
+    pub fn sign_all_indexed(
+        &self,
+        items: Vec<OrderItem>,
+        base_nonce: Option<u64>,
+    ) -> Result<Vec<IndexedSignedTransaction>> {
+        Ok(index_from(0, self.sign_all(items, base_nonce)?))
+    }
+
+    /// Sign a large batch in chunks of `every_n` items, passing a checkpoint to `sink`
+    /// after each chunk.
+    ///
+    /// The output is identical to [`Signer::sign_all`] for the same items and base nonce.
+    ///
+    /// ```rust
+    /// use bulk_keychain::{Keypair, Order, OrderItem, Signer};
+    ///
+    /// let signer = Signer::new(Keypair::generate());
+    /// let orders: Vec<OrderItem> = (0..5)
+    ///     .map(|i| Order::market("BTC-USD", i % 2 == 0, 0.1).into())
+    ///     .collect();
+    /// let mut completed = Vec::new();
+    /// let txs = signer
+    ///     .sign_all_with_checkpoint(orders, Some(1), 2, |cp| completed.push(cp.completed))
+    ///     .unwrap();
+    /// assert_eq!(completed, [2, 4, 5]);
+    /// assert_eq!(txs.len(), 5);
+    /// ```
+    #[cfg(feature = "batch")]
+    pub fn sign_all_with_checkpoint<F>(
+        &self,
+        items: Vec<OrderItem>,
+        base_nonce: Option<u64>,
+        every_n: usize,
+        sink: F,
+    ) -> Result<Vec<SignedTransaction>>
+    where
+        F: FnMut(&BatchCheckpoint),
+    {
+        let checkpoint = BatchCheckpoint {
+            items_hash: order_items_digest(&items)?,
+            signer: self.signer,
+            account: self.account,
+            base_nonce: self.base_nonce_or_now(base_nonce, items.len())?,
+            completed: 0,
+        };
+        self.sign_from_checkpoint(items, checkpoint, every_n, sink)
+    }
+
+    /// Like [`Signer::sign_all_with_checkpoint`], but tags each transaction
+    /// with its input index.
+    #[cfg(feature = "batch")]
+    pub fn sign_all_with_checkpoint_indexed<F>(
+        &self,
+        items: Vec<OrderItem>,
+        base_nonce: Option<u64>,
+        every_n: usize,
+        sink: F,
+    ) -> Result<Vec<IndexedSignedTransaction>>
+    where
+        F: FnMut(&BatchCheckpoint),
+    {
+        Ok(index_from(
+            0,
+            self.sign_all_with_checkpoint(items, base_nonce, every_n, sink)?,
+        ))
+    }
+
+    /// Continue a checkpointed batch, returning transactions for the remaining items only.
+    ///
+    /// `items` must be the same full list the checkpoint was taken from, and
+    /// this signer the one (and account) that took it.
+    ///
+    /// ```rust
+    /// use bulk_keychain::{Keypair, Order, OrderItem, Signer};
+    ///
+    /// let signer = Signer::new(Keypair::generate());
+    /// let orders: Vec<OrderItem> = (0..5)
+    ///     .map(|i| Order::market("BTC-USD", i % 2 == 0, 0.1).into())
+    ///     .collect();
+    /// let mut first = None;
+    /// let all = signer
+    ///     .sign_all_with_checkpoint(orders.clone(), Some(1), 2, |cp| {
+    ///         first.get_or_insert_with(|| cp.clone());
+    ///     })
+    ///     .unwrap();
+    ///
+    /// // Pick up after the first chunk as if the process had crashed there.
+    /// let rest = signer.resume_sign_all(orders, &first.unwrap()).unwrap();
+    /// assert_eq!(rest.len(), 3);
+    /// assert_eq!(rest[0].signature, all[2].signature);
+    /// ```
+    #[cfg(feature = "batch")]
+    pub fn resume_sign_all(
+        &self,
+        items: Vec<OrderItem>,
+        checkpoint: &BatchCheckpoint,
+    ) -> Result<Vec<SignedTransaction>> {
+        if checkpoint.signer != self.signer || checkpoint.account != self.account {
+            return Err(Error::CheckpointMismatch(format!(
+                "checkpoint was taken by signer {} for account {}",
+                checkpoint.signer.fingerprint(),
+                checkpoint.account.fingerprint()
+            )));
+        }
+        if order_items_digest(&items)? != checkpoint.items_hash {
+            return Err(Error::CheckpointMismatch(
+                "input items differ from the checkpointed batch".to_string(),
+            ));
+        }
+        if checkpoint.completed > items.len() {
+            return Err(Error::CheckpointMismatch(format!(
+                "checkpoint covers {} items but input has {}",
+                checkpoint.completed,
+                items.len()
+            )));
+        }
+        let every_n = items.len();
+        self.sign_from_checkpoint(items, checkpoint.clone(), every_n, |_| {})
+    }
+
+    /// Like [`Signer::resume_sign_all`], but tags each transaction with its
+    /// index in the full `items` list, so the first one is `checkpoint.completed`.
+    ///
+    /// ```rust
+    /// use bulk_keychain::{Keypair, Order, OrderItem, Signer};
+    ///
+    /// let signer = Signer::new(Keypair::generate());
+    /// let orders: Vec<OrderItem> = (0..5)
+    ///     .map(|i| Order::market("BTC-USD", i % 2 == 0, 0.1).into())
+    ///     .collect();
+    /// let mut first = None;
+    /// signer
+    ///     .sign_all_with_checkpoint(orders.clone(), Some(1), 2, |cp| {
+    ///         first.get_or_insert_with(|| cp.clone());
+    ///     })
+    ///     .unwrap();
+    ///
+    /// let rest = signer.resume_sign_all_indexed(orders, &first.unwrap()).unwrap();
+    /// assert_eq!((rest[0].index, rest[0].tx.nonce), (2, 3));
+    /// ```
+    #[cfg(feature = "batch")]
+    pub fn resume_sign_all_indexed(
+        &self,
+        items: Vec<OrderItem>,
+        checkpoint: &BatchCheckpoint,
+    ) -> Result<Vec<IndexedSignedTransaction>> {
+        Ok(index_from(
+            checkpoint.completed,
+            self.resume_sign_all(items, checkpoint)?,
+        ))
     }
 
-    /// Check whether batch order ID generation is enabled.
-    pub fn computes_batch_order_ids(&self) -> bool {
-        self.compute_batch_order_ids
+    #[cfg(feature = "batch")]
+    fn sign_from_checkpoint<F>(
+        &self,
+        items: Vec<OrderItem>,
+        mut checkpoint: BatchCheckpoint,
+        every_n: usize,
+        mut sink: F,
+    ) -> Result<Vec<SignedTransaction>>
+    where
+        F: FnMut(&BatchCheckpoint),
+    {
+        let every_n = every_n.max(1);
+        let mut signed = Vec::with_capacity(items.len() - checkpoint.completed);
+        let mut remaining = items.into_iter().skip(checkpoint.completed);
+        loop {
+            let chunk: Vec<OrderItem> = remaining.by_ref().take(every_n).collect();
+            if chunk.is_empty() {
+                break;
+            }
+            let len = chunk.len();
+            signed.extend(self.sign_all(chunk, Some(checkpoint.next_nonce()?))?);
+            checkpoint.completed += len;
+            sink(&checkpoint);
+        }
+        Ok(signed)
     }
 
-    /// Set whether batch order ID generation is enabled.
-    pub fn set_batch_order_ids(&mut self, enabled: bool) {
-        self.compute_batch_order_ids = enabled;
+    /// Sign an order that only activates once `condition` is met.
+    ///
+    /// ```rust
+    /// use bulk_keychain::{Keypair, Order, Signer, TriggerCondition};
+    ///
+    /// let mut signer = Signer::new(Keypair::generate());
+    /// // Sell 0.1 BTC at market once BTC trades at or below 95k.
+    /// let condition = TriggerCondition::new("BTC-USD", false, 95000.0);
+    /// let tx = signer
+    ///     .sign_order_conditional(condition, Order::market("BTC-USD", false, 0.1), Some(1))
+    ///     .unwrap();
+    /// assert!(tx.actions_json().unwrap()[0].get("trig").is_some());
+    /// assert!(tx.verify().unwrap());
+    /// ```
+    pub fn sign_order_conditional(
+        &mut self,
+        condition: TriggerCondition,
+        order: Order,
+        nonce: Option<u64>,
+    ) -> Result<SignedTransaction> {
+        let basket = condition.into_basket(vec![OrderItem::Order(order)]);
+        self.sign(OrderItem::TriggerBasket(basket), nonce)
     }
 
-    /// Get signer pubkey.
-    pub fn pubkey(&self) -> Pubkey {
-        self.keypair.pubkey()
+    /// Sign multiple items atomically as one transaction.
+    ///
+    /// ```rust
+    /// use bulk_keychain::{Keypair, Order, Signer, TimeInForce};
+    ///
+    /// let mut signer = Signer::new(Keypair::generate()).with_batch_order_ids();
+    /// let legs = vec![
+    ///     Order::limit("BTC-USD", true, 100000.0, 0.1, TimeInForce::Gtc).into(),
+    ///     Order::limit("ETH-USD", false, 3000.0, 1.0, TimeInForce::Gtc).into(),
+    /// ];
+    /// let tx = signer.sign_group(legs, Some(1)).unwrap();
+    /// assert_eq!(tx.actions_json().unwrap().len(), 2);
+    /// assert_eq!(tx.order_ids.as_ref().map(Vec::len), Some(2));
+    /// assert!(tx.verify().unwrap());
+    /// ```
+    pub fn sign_group(
+        &mut self,
+        items: Vec<OrderItem>,
+        nonce: Option<u64>,
+    ) -> Result<SignedTransaction> {
+        self.sign_group_with_options(items, &GroupOptions::default(), nonce)
     }
 
-    /// Sign raw bytes and return base58 signature.
-    pub fn sign_bytes(&self, message: &[u8]) -> String {
-        let signature = self.keypair.signing_key().sign(message);
-        bs58::encode(signature.to_bytes()).into_string()
+    /// Sign multiple items atomically after checking the legs against each
+    /// other with [`crate::lint_group`].
+    ///
+    /// Findings land in `group_warnings`; with [`GroupOptions::strict`] a
+    /// warning or worse fails with [`Error::GroupRejected`] before a nonce is
+    /// taken. [`Signer::sign_group`] is this with default options.
+    ///
+    /// ```rust
+    /// use bulk_keychain::{Error, GroupOptions, Keypair, Order, Signer, TimeInForce};
+    ///
+    /// let mut signer = Signer::new(Keypair::generate());
+    /// let leg = Order::limit("BTC-USD", true, 100000.0, 0.1, TimeInForce::Gtc);
+    /// let doubled = vec![leg.clone().into(), leg.into()];
+    ///
+    /// let tx = signer.sign_group(doubled.clone(), Some(1)).unwrap();
+    /// assert_eq!(tx.group_warnings[0].index, 1);
+    /// let strict = GroupOptions::new().strict();
+    /// assert!(matches!(
+    ///     signer.sign_group_with_options(doubled, &strict, Some(2)),
+    ///     Err(Error::GroupRejected(_))
+    /// ));
+    /// ```
+    pub fn sign_group_with_options(
+        &mut self,
+        items: Vec<OrderItem>,
+        options: &GroupOptions,
+        nonce: Option<u64>,
+    ) -> Result<SignedTransaction> {
+        if items.is_empty() {
+            return Err(Error::EmptyOrders);
+        }
+        let items = resolve_cancels(self.client_ids.as_deref(), items)?;
+        let warnings = options.check(&items)?;
+        let nonce = self.nonce_or_next(nonce)?;
+        let action = Action::Order { orders: items };
+        let mut signed = self.sign_action_self(&action, nonce)?;
+        signed.group_kind = options.kind.clone();
+        signed.group_warnings = warnings;
+        Ok(signed)
     }
 
-    fn next_nonce(&self) -> u64 {
-        self.nonce_manager
-            .as_ref()
-            .map(|m| m.next())
-            .unwrap_or_else(crate::nonce::current_timestamp_millis)
+    /// Atomically cancel `old_id` and place `replacement` on the same market.
+    ///
+    /// `original` is the order being replaced; a replacement on another market
+    /// or on the other side is refused unless `force_side_change` is set (see
+    /// [`crate::diff_orders`]).
+    ///
+    /// ```rust
+    /// use bulk_keychain::{Hash, Keypair, Order, Signer, TimeInForce};
+    ///
+    /// let mut signer = Signer::new(Keypair::generate());
+    /// let live = Order::limit("BTC-USD", true, 100000.0, 0.1, TimeInForce::Gtc);
+    /// let tx = signer.sign(live.clone().into(), Some(1)).unwrap();
+    /// let old_id = Hash::from_base58(tx.order_id.as_deref().unwrap()).unwrap();
+    ///
+    /// let repriced = Order::limit("BTC-USD", true, 100010.0, 0.1, TimeInForce::Gtc);
+    /// let tx = signer.sign_cancel_replace(old_id, &live, repriced, false, Some(2)).unwrap();
+    /// assert_eq!(tx.actions_json().unwrap().len(), 2);
+    ///
+    /// let flipped = Order::limit("BTC-USD", false, 100010.0, 0.1, TimeInForce::Gtc);
+    /// assert!(signer.sign_cancel_replace(old_id, &live, flipped, false, Some(3)).is_err());
+    /// ```
+    pub fn sign_cancel_replace(
+        &mut self,
+        old_id: Hash,
+        original: &Order,
+        replacement: Order,
+        force_side_change: bool,
+        nonce: Option<u64>,
+    ) -> Result<SignedTransaction> {
+        check_replacement(original, &replacement, force_side_change)?;
+        let cancel = Cancel::new(replacement.symbol.clone(), old_id);
+        self.sign_group(vec![cancel.into(), replacement.into()], nonce)
     }
 
-    /// Low-level signing entrypoint.
-    pub fn sign_action(
+    /// Cancel the live order placed with `client_id`, e.g. one still in
+    /// flight whose order ID the caller has not recorded yet.
+    ///
+    /// Signs a plain cancel of the order ID the signer computed when it
+    /// signed that order, so it needs client-ID tracking
+    /// ([`Signer::with_unique_client_ids`]).
+    ///
+    /// ```rust
+    /// use bulk_keychain::{Hash, Keypair, Order, Signer, TimeInForce};
+    ///
+    /// let mut signer = Signer::new(Keypair::generate()).with_unique_client_ids();
+    /// let client_id = Hash::random();
+    /// let order = Order::limit("BTC-USD", true, 100000.0, 0.1, TimeInForce::Gtc).with_client_id(client_id);
+    /// let placed = signer.sign(order.into(), Some(1)).unwrap();
+    ///
+    /// let tx = signer.sign_cancel_by_client_id("BTC-USD", client_id, Some(2)).unwrap();
+    /// assert_eq!(tx.actions_json().unwrap()[0]["cx"]["oid"], placed.order_id.unwrap());
+    /// assert_eq!(signer.client_id_order(&client_id), None);
+    /// ```
+    pub fn sign_cancel_by_client_id(
         &mut self,
-        action: &Action,
-        nonce: u64,
-        account: &Pubkey,
+        symbol: impl Into<String>,
+        client_id: Hash,
+        nonce: Option<u64>,
     ) -> Result<SignedTransaction> {
-        let signer_pubkey = self.keypair.pubkey();
+        self.sign(Cancel::by_client_id(symbol, client_id).into(), nonce)
+    }
 
-        serialize_for_sdk_signing(action, nonce, account, &mut self.serializer)?;
+    /// Sign a size amendment of a live order, keeping its queue position
+    /// where the exchange allows it. The exchange's `mod` action carries only
+    /// the new size; a price change still goes through
+    /// [`Signer::sign_cancel_replace`].
+    ///
+    /// ```rust
+    /// use bulk_keychain::{Hash, Keypair, Modify, Signer};
+    ///
+    /// let mut signer = Signer::new(Keypair::generate());
+    /// let order_id = Hash::random();
+    /// let tx = signer.sign_modify_order(Modify::new(order_id, "BTC-USD", 0.25), Some(1)).unwrap();
+    /// assert_eq!(tx.actions_json().unwrap()[0]["mod"]["oid"], order_id.to_base58());
+    /// assert_eq!(tx.actions_json().unwrap()[0]["mod"]["sz"], 0.25);
+    /// ```
+    pub fn sign_modify_order(
+        &mut self,
+        modify: Modify,
+        nonce: Option<u64>,
+    ) -> Result<SignedTransaction> {
+        self.sign(modify.into(), nonce)
+    }
 
-        let order_id = if self.compute_order_id {
-            self.compute_action_order_id(action, nonce, account)
-        } else {
-            None
-        };
-        let order_ids = if self.compute_batch_order_ids {
-            self.compute_action_order_ids(action, nonce, account)
-        } else {
-            None
+    /// Sign multiple items atomically, tagging the output with their intent.
+    ///
+    /// The tag is unsigned metadata; the signature matches [`Signer::sign_group`].
+    ///
+    /// ```rust
+    /// use bulk_keychain::{GroupKind, Keypair, Order, Signer, TimeInForce};
+    ///
+    /// let mut signer = Signer::new(Keypair::generate());
+    /// let ladder: Vec<_> = (0..3)
+    ///     .map(|i| Order::limit("BTC-USD", true, 99000.0 - i as f64 * 100.0, 0.1, TimeInForce::Gtc).into())
+    ///     .collect();
+    /// let kind = GroupKind::Custom("ladder".to_string());
+    /// let tagged = signer.sign_group_with(ladder.clone(), Some(kind.clone()), Some(1)).unwrap();
+    /// let plain = signer.sign_group(ladder, Some(1)).unwrap();
+    /// assert_eq!(tagged.group_kind, Some(kind));
+    /// assert_eq!(tagged.signature, plain.signature);
+    /// ```
+    pub fn sign_group_with(
+        &mut self,
+        items: Vec<OrderItem>,
+        kind: Option<GroupKind>,
+        nonce: Option<u64>,
+    ) -> Result<SignedTransaction> {
+        let options = GroupOptions {
+            kind,
+            ..GroupOptions::default()
         };
+        self.sign_group_with_options(items, &options, nonce)
+    }
 
-        let signature = self.sign_bytes(&self.serializer);
-        let actions = self.action_to_json(action)?;
-
-        Ok(SignedTransaction {
-            actions,
+    /// Sign an entry order with its take-profit and stop-loss legs as one
+    /// [`GroupKind::Bracket`] transaction.
+    ///
+    /// ```rust
+    /// use bulk_keychain::{GroupKind, Keypair, Order, Signer, Stop, TakeProfit, TimeInForce};
+    ///
+    /// let mut signer = Signer::new(Keypair::generate());
+    /// let entry = Order::limit("BTC-USD", true, 100000.0, 0.1, TimeInForce::Gtc);
+    /// let take_profit = TakeProfit {
+    ///     symbol: "BTC-USD".to_string(),
+    ///     is_buy: false,
+    ///     size: 0.1,
+    ///     trigger_price: 110000.0,
+    ///     limit_price: f64::NAN,
+    ///     iso: false,
+    /// };
+    /// let stop_loss = Stop {
+    ///     symbol: "BTC-USD".to_string(),
+    ///     is_buy: false,
+    ///     size: 0.1,
+    ///     trigger_price: 95000.0,
+    ///     limit_price: f64::NAN,
+    ///     iso: false,
+    /// };
+    /// let tx = signer.sign_bracket(entry, take_profit, stop_loss, Some(1)).unwrap();
+    /// assert_eq!(tx.actions_json().unwrap().len(), 3);
+    /// assert_eq!(tx.group_kind, Some(GroupKind::Bracket));
+    /// assert!(tx.verify().unwrap());
+    /// ```
+    pub fn sign_bracket(
+        &mut self,
+        entry: Order,
+        take_profit: TakeProfit,
+        stop_loss: Stop,
+        nonce: Option<u64>,
+    ) -> Result<SignedTransaction> {
+        self.sign_group_with(
+            vec![entry.into(), take_profit.into(), stop_loss.into()],
+            Some(GroupKind::Bracket),
             nonce,
-            account: account.to_base58(),
-            signer: signer_pubkey.to_base58(),
-            signature,
-            order_id,
-            order_ids,
-        })
+        )
     }
 
-    /// Sign using signer pubkey as account.
-    pub fn sign_action_self(&mut self, action: &Action, nonce: u64) -> Result<SignedTransaction> {
-        let account = self.keypair.pubkey();
-        self.sign_action(action, nonce, &account)
+    /// Sign a take-profit and stop-loss pair as one [`GroupKind::Oco`] transaction.
+    ///
+    /// ```rust
+    /// use bulk_keychain::{GroupKind, Keypair, Signer, Stop, TakeProfit};
+    ///
+    /// let mut signer = Signer::new(Keypair::generate());
+    /// let take_profit = TakeProfit {
+    ///     symbol: "BTC-USD".to_string(),
+    ///     is_buy: false,
+    ///     size: 0.1,
+    ///     trigger_price: 110000.0,
+    ///     limit_price: f64::NAN,
+    ///     iso: false,
+    /// };
+    /// let stop_loss = Stop {
+    ///     symbol: "BTC-USD".to_string(),
+    ///     is_buy: false,
+    ///     size: 0.1,
+    ///     trigger_price: 95000.0,
+    ///     limit_price: f64::NAN,
+    ///     iso: false,
+    /// };
+    /// let tx = signer.sign_oco(take_profit, stop_loss, Some(1)).unwrap();
+    /// assert_eq!(tx.actions_json().unwrap().len(), 2);
+    /// assert_eq!(tx.group_kind, Some(GroupKind::Oco));
+    /// assert!(tx.verify().unwrap());
+    /// ```
+    pub fn sign_oco(
+        &mut self,
+        take_profit: TakeProfit,
+        stop_loss: Stop,
+        nonce: Option<u64>,
+    ) -> Result<SignedTransaction> {
+        self.sign_group_with(
+            vec![take_profit.into(), stop_loss.into()],
+            Some(GroupKind::Oco),
+            nonce,
+        )
     }
 
-    /// Sign a single order item.
-    pub fn sign(&mut self, item: OrderItem, nonce: Option<u64>) -> Result<SignedTransaction> {
-        let nonce = nonce.unwrap_or_else(|| self.next_nonce());
-        let action = Action::Order { orders: vec![item] };
-        self.sign_action_self(&action, nonce)
+    pub(crate) fn sign_single_item(
+        &self,
+        item: OrderItem,
+        nonce: u64,
+        allow_client_id_reuse: bool,
+    ) -> Result<SignedTransaction> {
+        self.sign_single_item_tracked(item, nonce, allow_client_id_reuse)
+            .map(|(tx, _)| tx)
     }
 
-    /// Sign multiple independent items in parallel.
-    pub fn sign_all(
+    /// [`Signer::sign_single_item`], also returning what it claimed in the
+    /// trackers.
+    fn sign_single_item_tracked(
         &self,
-        items: Vec<OrderItem>,
-        base_nonce: Option<u64>,
-    ) -> Result<Vec<SignedTransaction>> {
-        if items.is_empty() {
-            return Ok(vec![]);
-        }
-
-        let base = base_nonce.unwrap_or_else(crate::nonce::current_timestamp_millis);
-        if items.len() < PARALLEL_THRESHOLD {
-            items
-                .into_iter()
-                .enumerate()
-                .map(|(i, item)| self.sign_single_item(item, base + i as u64))
-                .collect()
-        } else {
-            items
-                .into_par_iter()
-                .enumerate()
-                .map(|(i, item)| self.sign_single_item(item, base + i as u64))
-                .collect()
+        item: OrderItem,
+        nonce: u64,
+        allow_client_id_reuse: bool,
+    ) -> Result<(SignedTransaction, PreSignUndo)> {
+        let item = resolve_cancel(self.client_ids.as_deref(), item)?;
+        let account = self.account;
+        let undo = self.check_pre_sign(
+            std::slice::from_ref(&item),
+            nonce,
+            &account,
+            allow_client_id_reuse,
+        )?;
+        match self.sign_item_unchecked(item, nonce, &account) {
+            Ok(tx) => Ok((tx, undo)),
+            Err(e) => {
+                self.undo_pre_sign(undo);
+                Err(e)
+            }
         }
     }
 
-    /// Sign multiple items atomically as one transaction.
-    pub fn sign_group(
-        &mut self,
-        items: Vec<OrderItem>,
-        nonce: Option<u64>,
+    fn sign_item_unchecked(
+        &self,
+        item: OrderItem,
+        nonce: u64,
+        account: &Pubkey,
     ) -> Result<SignedTransaction> {
-        if items.is_empty() {
-            return Err(Error::EmptyOrders);
-        }
-        let nonce = nonce.unwrap_or_else(|| self.next_nonce());
-        let action = Action::Order { orders: items };
-        self.sign_action_self(&action, nonce)
-    }
-
-    fn sign_single_item(&self, item: OrderItem, nonce: u64) -> Result<SignedTransaction> {
-        let account = self.keypair.pubkey();
-        let signer_pubkey = self.keypair.pubkey();
+        let account = *account;
+        let signer_pubkey = self.signer;
         let order_id = if self.compute_order_id {
-            let mut scratch = Vec::with_capacity(96);
-            compute_order_item_id_at_index(&item, 0, nonce, &account, &mut scratch)
-                .map(|id| id.to_base58())
+            compute_order_item_id_at_index(&item, 0, nonce, &account).map(|id| id.to_base58())
         } else {
             None
         };
@@ -212,51 +2249,100 @@ impl Signer {
         let mut serializer = Vec::with_capacity(512);
         serialize_for_sdk_signing(&action, nonce, &account, &mut serializer)?;
 
-        let signature = self.sign_bytes(&serializer);
-        let actions = self.action_to_json(&action)?;
+        let signature = self.try_sign_bytes(&serializer)?;
+        self.usage.signed.fetch_add(1, Ordering::Relaxed);
 
         Ok(SignedTransaction {
-            actions,
+            action,
             nonce,
             account: account.to_base58(),
             signer: signer_pubkey.to_base58(),
             signature,
             order_id,
             order_ids: None,
+            id_scheme: IdScheme::CURRENT,
+            resign_count: 0,
+            group_kind: None,
+            group_warnings: Vec::new(),
         })
     }
 
     /// Sign a faucet action.
+    ///
+    /// ```rust
+    /// use bulk_keychain::{Keypair, Signer};
+    ///
+    /// let mut signer = Signer::new(Keypair::generate());
+    /// let tx = signer.sign_faucet(Some(1)).unwrap();
+    /// assert_eq!(tx.actions_json().unwrap()[0]["faucet"]["u"], tx.account);
+    /// assert!(tx.verify().unwrap());
+    /// ```
     pub fn sign_faucet(&mut self, nonce: Option<u64>) -> Result<SignedTransaction> {
-        let nonce = nonce.unwrap_or_else(|| self.next_nonce());
-        let action = Action::Faucet(Faucet::new(self.keypair.pubkey()));
+        let nonce = self.nonce_or_next(nonce)?;
+        let action = Action::Faucet(Faucet::new(self.account));
         self.sign_action_self(&action, nonce)
     }
 
     /// Sign agent wallet creation/deletion.
+    ///
+    /// ```rust
+    /// use bulk_keychain::{Keypair, Signer};
+    ///
+    /// let mut owner = Signer::new(Keypair::generate());
+    /// let agent = Keypair::generate().pubkey();
+    /// let tx = owner.sign_agent_wallet(agent, false, Some(1)).unwrap();
+    /// assert_eq!(tx.actions_json().unwrap()[0]["agentWalletCreation"]["a"], agent.to_base58());
+    /// assert_eq!(tx.signer_pubkey().unwrap(), *owner.signer_pubkey());
+    /// assert!(tx.verify().unwrap());
+    /// ```
     pub fn sign_agent_wallet(
         &mut self,
         agent: Pubkey,
         delete: bool,
         nonce: Option<u64>,
     ) -> Result<SignedTransaction> {
-        let nonce = nonce.unwrap_or_else(|| self.next_nonce());
         let action = Action::AgentWalletCreation(AgentWallet { agent, delete });
+        let nonce = self.nonce_for(nonce, &action)?;
         self.sign_action_self(&action, nonce)
     }
 
     /// Sign user settings update.
+    ///
+    /// ```rust
+    /// use bulk_keychain::{Keypair, Signer, UserSettings};
+    ///
+    /// let mut signer = Signer::new(Keypair::generate());
+    /// let tx = signer
+    ///     .sign_user_settings(UserSettings::set_leverage("BTC-USD", 5.0), Some(1))
+    ///     .unwrap();
+    /// assert!(tx.actions_json().unwrap()[0].get("updateUserSettings").is_some());
+    /// assert!(tx.verify().unwrap());
+    /// ```
     pub fn sign_user_settings(
         &mut self,
         settings: UserSettings,
         nonce: Option<u64>,
     ) -> Result<SignedTransaction> {
-        let nonce = nonce.unwrap_or_else(|| self.next_nonce());
         let action = Action::UpdateUserSettings(settings);
+        let nonce = self.nonce_for(nonce, &action)?;
         self.sign_action_self(&action, nonce)
     }
 
     /// Sign one or more oracle price updates (`px` actions).
+    ///
+    /// ```rust
+    /// use bulk_keychain::{Keypair, OraclePrice, Signer};
+    ///
+    /// let mut signer = Signer::new(Keypair::generate());
+    /// let price = OraclePrice {
+    ///     timestamp: 1_700_000_000_000,
+    ///     asset: "BTC".to_string(),
+    ///     price: 100000.0,
+    /// };
+    /// let tx = signer.sign_oracle_prices(vec![price], Some(1)).unwrap();
+    /// assert!(tx.verify().unwrap());
+    /// assert!(signer.sign_oracle_prices(vec![], Some(2)).is_err());
+    /// ```
     pub fn sign_oracle_prices(
         &mut self,
         oracles: Vec<OraclePrice>,
@@ -267,12 +2353,26 @@ impl Signer {
                 "oracle prices array cannot be empty".to_string(),
             ));
         }
-        let nonce = nonce.unwrap_or_else(|| self.next_nonce());
+        let nonce = self.nonce_or_next(nonce)?;
         let action = Action::Oracle { oracles };
         self.sign_action_self(&action, nonce)
     }
 
     /// Sign a batch Pyth oracle update (`o` action).
+    ///
+    /// ```rust
+    /// use bulk_keychain::{Keypair, PythOraclePrice, Signer};
+    ///
+    /// let mut signer = Signer::new(Keypair::generate());
+    /// let price = PythOraclePrice {
+    ///     timestamp: 1_700_000_000_000,
+    ///     feed_index: 0,
+    ///     price: 10_000_000_000_000,
+    ///     exponent: -8,
+    /// };
+    /// let tx = signer.sign_pyth_oracle(vec![price], Some(1)).unwrap();
+    /// assert!(tx.verify().unwrap());
+    /// ```
     pub fn sign_pyth_oracle(
         &mut self,
         oracles: Vec<PythOraclePrice>,
@@ -283,147 +2383,280 @@ impl Signer {
                 "pyth oracle array cannot be empty".to_string(),
             ));
         }
-        let nonce = nonce.unwrap_or_else(|| self.next_nonce());
+        let nonce = self.nonce_or_next(nonce)?;
         let action = Action::PythOracle { oracles };
         self.sign_action_self(&action, nonce)
     }
 
     /// Sign whitelist faucet access update (admin).
+    ///
+    /// ```rust
+    /// use bulk_keychain::{Keypair, Signer};
+    ///
+    /// let mut admin = Signer::new(Keypair::generate());
+    /// let user = Keypair::generate().pubkey();
+    /// let tx = admin.sign_whitelist_faucet(user, true, Some(1)).unwrap();
+    /// assert!(tx.verify().unwrap());
+    /// ```
     pub fn sign_whitelist_faucet(
         &mut self,
         target: Pubkey,
         whitelist: bool,
         nonce: Option<u64>,
     ) -> Result<SignedTransaction> {
-        let nonce = nonce.unwrap_or_else(|| self.next_nonce());
+        let nonce = self.nonce_or_next(nonce)?;
         let action = Action::WhitelistFaucet(WhitelistFaucet { target, whitelist });
         self.sign_action_self(&action, nonce)
     }
 
     /// Sign a sub-account creation (optionally with initial margin transfer).
+    ///
+    /// ```rust
+    /// use bulk_keychain::{CreateSubAccount, Keypair, Signer};
+    ///
+    /// let mut signer = Signer::new(Keypair::generate());
+    /// let sub = CreateSubAccount::with_margin("hedge", "USDC", 100.0);
+    /// let tx = signer.sign_create_sub_account(sub, Some(1)).unwrap();
+    /// assert!(tx.verify().unwrap());
+    /// ```
     pub fn sign_create_sub_account(
         &mut self,
         sub_account: CreateSubAccount,
         nonce: Option<u64>,
     ) -> Result<SignedTransaction> {
-        let nonce = nonce.unwrap_or_else(|| self.next_nonce());
+        let nonce = self.nonce_or_next(nonce)?;
         let action = Action::CreateSubAccount(sub_account);
         self.sign_action_self(&action, nonce)
     }
 
     /// Sign a sub-account removal.
+    ///
+    /// ```rust
+    /// use bulk_keychain::{Keypair, Signer};
+    ///
+    /// let mut signer = Signer::new(Keypair::generate());
+    /// let sub_account = Keypair::generate().pubkey();
+    /// let tx = signer.sign_remove_sub_account(sub_account, Some(1)).unwrap();
+    /// assert!(tx.verify().unwrap());
+    /// ```
     pub fn sign_remove_sub_account(
         &mut self,
         to_remove: Pubkey,
         nonce: Option<u64>,
     ) -> Result<SignedTransaction> {
-        let nonce = nonce.unwrap_or_else(|| self.next_nonce());
+        let nonce = self.nonce_or_next(nonce)?;
         let action = Action::RemoveSubAccount(RemoveSubAccount { to_remove });
         self.sign_action_self(&action, nonce)
     }
 
     /// Sign a sub-account rename.
+    ///
+    /// ```rust
+    /// use bulk_keychain::{Keypair, RenameSubAccount, Signer};
+    ///
+    /// let mut signer = Signer::new(Keypair::generate());
+    /// let sub_account = Keypair::generate().pubkey();
+    /// let rename = RenameSubAccount::new(sub_account, "market-making");
+    /// let tx = signer.sign_rename_sub_account(rename, Some(1)).unwrap();
+    /// assert!(tx.verify().unwrap());
+    /// ```
     pub fn sign_rename_sub_account(
         &mut self,
         rename: RenameSubAccount,
         nonce: Option<u64>,
     ) -> Result<SignedTransaction> {
-        let nonce = nonce.unwrap_or_else(|| self.next_nonce());
+        let nonce = self.nonce_or_next(nonce)?;
         let action = Action::RenameSubAccount(rename);
         self.sign_action_self(&action, nonce)
     }
 
     /// Sign a margin transfer between accounts.
+    ///
+    /// ```rust
+    /// use bulk_keychain::{Keypair, Signer, Transfer};
+    ///
+    /// let mut signer = Signer::new(Keypair::generate());
+    /// let to = Keypair::generate().pubkey();
+    /// let transfer = Transfer::internal(*signer.account_pubkey(), to, "USDC", 25.0);
+    /// let tx = signer.sign_transfer(transfer, Some(1)).unwrap();
+    /// assert!(tx.verify().unwrap());
+    /// ```
     pub fn sign_transfer(
         &mut self,
         transfer: Transfer,
         nonce: Option<u64>,
     ) -> Result<SignedTransaction> {
-        let nonce = nonce.unwrap_or_else(|| self.next_nonce());
+        let nonce = self.nonce_or_next(nonce)?;
         let action = Action::Transfer(transfer);
         self.sign_action_self(&action, nonce)
     }
 
     /// Sign a multisig creation.
+    ///
+    /// ```rust
+    /// use bulk_keychain::{CreateMultisig, Keypair, Signer};
+    ///
+    /// let mut signer = Signer::new(Keypair::generate());
+    /// let members = vec![*signer.signer_pubkey(), Keypair::generate().pubkey()];
+    /// let tx = signer
+    ///     .sign_create_multisig(CreateMultisig::new(members, 2), Some(1))
+    ///     .unwrap();
+    /// assert!(tx.verify().unwrap());
+    /// ```
     pub fn sign_create_multisig(
         &mut self,
         create_multisig: CreateMultisig,
         nonce: Option<u64>,
     ) -> Result<SignedTransaction> {
-        let nonce = nonce.unwrap_or_else(|| self.next_nonce());
+        let nonce = self.nonce_or_next(nonce)?;
         let action = Action::CreateMultisig(create_multisig);
         self.sign_action_self(&action, nonce)
     }
 
     /// Sign a multisig proposal.
+    ///
+    /// ```rust
+    /// use bulk_keychain::{Action, Faucet, Keypair, MultisigPropose, Signer};
+    ///
+    /// let mut signer = Signer::new(Keypair::generate());
+    /// let multisig = Keypair::generate().pubkey();
+    /// let propose = MultisigPropose::new(multisig, vec![Action::Faucet(Faucet::new(multisig))]);
+    /// let tx = signer.sign_multisig_propose(propose, Some(1)).unwrap();
+    /// assert!(tx.verify().unwrap());
+    /// ```
     pub fn sign_multisig_propose(
         &mut self,
         propose: MultisigPropose,
         nonce: Option<u64>,
     ) -> Result<SignedTransaction> {
-        let nonce = nonce.unwrap_or_else(|| self.next_nonce());
+        let nonce = self.nonce_or_next(nonce)?;
         let action = Action::MultisigPropose(propose);
         self.sign_action_self(&action, nonce)
     }
 
     /// Sign a multisig approval.
+    ///
+    /// ```rust
+    /// use bulk_keychain::{Keypair, MultisigApprove, Signer};
+    ///
+    /// let mut signer = Signer::new(Keypair::generate());
+    /// let multisig = Keypair::generate().pubkey();
+    /// let tx = signer
+    ///     .sign_multisig_approve(MultisigApprove::new(multisig, 1), Some(1))
+    ///     .unwrap();
+    /// assert!(tx.verify().unwrap());
+    /// ```
     pub fn sign_multisig_approve(
         &mut self,
         approve: MultisigApprove,
         nonce: Option<u64>,
     ) -> Result<SignedTransaction> {
-        let nonce = nonce.unwrap_or_else(|| self.next_nonce());
+        let nonce = self.nonce_or_next(nonce)?;
         let action = Action::MultisigApprove(approve);
         self.sign_action_self(&action, nonce)
     }
 
     /// Sign a multisig rejection.
+    ///
+    /// ```rust
+    /// use bulk_keychain::{Keypair, MultisigReject, Signer};
+    ///
+    /// let mut signer = Signer::new(Keypair::generate());
+    /// let multisig = Keypair::generate().pubkey();
+    /// let tx = signer
+    ///     .sign_multisig_reject(MultisigReject::new(multisig, 1), Some(1))
+    ///     .unwrap();
+    /// assert!(tx.verify().unwrap());
+    /// ```
     pub fn sign_multisig_reject(
         &mut self,
         reject: MultisigReject,
         nonce: Option<u64>,
     ) -> Result<SignedTransaction> {
-        let nonce = nonce.unwrap_or_else(|| self.next_nonce());
+        let nonce = self.nonce_or_next(nonce)?;
         let action = Action::MultisigReject(reject);
         self.sign_action_self(&action, nonce)
     }
 
     /// Sign a multisig cancellation.
+    ///
+    /// ```rust
+    /// use bulk_keychain::{Keypair, MultisigCancel, Signer};
+    ///
+    /// let mut signer = Signer::new(Keypair::generate());
+    /// let multisig = Keypair::generate().pubkey();
+    /// let tx = signer
+    ///     .sign_multisig_cancel(MultisigCancel::new(multisig, 1), Some(1))
+    ///     .unwrap();
+    /// assert!(tx.verify().unwrap());
+    /// ```
     pub fn sign_multisig_cancel(
         &mut self,
         cancel: MultisigCancel,
         nonce: Option<u64>,
     ) -> Result<SignedTransaction> {
-        let nonce = nonce.unwrap_or_else(|| self.next_nonce());
+        let nonce = self.nonce_or_next(nonce)?;
         let action = Action::MultisigCancel(cancel);
         self.sign_action_self(&action, nonce)
     }
 
     /// Sign a multisig execution.
+    ///
+    /// ```rust
+    /// use bulk_keychain::{Keypair, MultisigExecute, Signer};
+    ///
+    /// let mut signer = Signer::new(Keypair::generate());
+    /// let multisig = Keypair::generate().pubkey();
+    /// let tx = signer
+    ///     .sign_multisig_execute(MultisigExecute::new(multisig, 1), Some(1))
+    ///     .unwrap();
+    /// assert!(tx.verify().unwrap());
+    /// ```
     pub fn sign_multisig_execute(
         &mut self,
         execute: MultisigExecute,
         nonce: Option<u64>,
     ) -> Result<SignedTransaction> {
-        let nonce = nonce.unwrap_or_else(|| self.next_nonce());
+        let nonce = self.nonce_or_next(nonce)?;
         let action = Action::MultisigExecute(execute);
         self.sign_action_self(&action, nonce)
     }
 
     /// Sign a multisig policy update.
+    ///
+    /// ```rust
+    /// use bulk_keychain::{Keypair, Signer, UpdateMultisigPolicy};
+    ///
+    /// let mut signer = Signer::new(Keypair::generate());
+    /// let multisig = Keypair::generate().pubkey();
+    /// let policy = UpdateMultisigPolicy::new(multisig, vec![*signer.signer_pubkey()], 1);
+    /// let tx = signer.sign_update_multisig_policy(policy, Some(1)).unwrap();
+    /// assert!(tx.verify().unwrap());
+    /// ```
     pub fn sign_update_multisig_policy(
         &mut self,
         update: UpdateMultisigPolicy,
         nonce: Option<u64>,
     ) -> Result<SignedTransaction> {
-        let nonce = nonce.unwrap_or_else(|| self.next_nonce());
+        let nonce = self.nonce_or_next(nonce)?;
         let action = Action::UpdateMultisigPolicy(update);
         self.sign_action_self(&action, nonce)
     }
 
     /// Deprecated compatibility alias.
+    ///
+    /// ```rust
+    /// # #![allow(deprecated)]
+    /// use bulk_keychain::{Keypair, Order, Signer};
+    ///
+    /// let mut signer = Signer::new(Keypair::generate());
+    /// let tx = signer
+    ///     .sign_order(vec![Order::market("BTC-USD", true, 0.1).into()], Some(1))
+    ///     .unwrap();
+    /// assert!(tx.verify().unwrap());
+    /// ```
     #[deprecated(
-        since = "0.2.0",
+        since = "0.1.16",
         note = "Use sign(), sign_all(), or sign_group() instead"
     )]
     pub fn sign_order(
@@ -435,7 +2668,22 @@ impl Signer {
     }
 
     /// Deprecated compatibility alias.
-    #[deprecated(since = "0.2.0", note = "Use sign_all() for simple batches")]
+    ///
+    /// Output `i` always corresponds to `order_batches[i]`.
+    ///
+    /// ```rust
+    /// # #![allow(deprecated)]
+    /// use bulk_keychain::{Keypair, Order, Signer};
+    ///
+    /// let signer = Signer::new(Keypair::generate());
+    /// let batches = vec![
+    ///     vec![Order::market("BTC-USD", true, 0.1).into()],
+    ///     vec![Order::market("ETH-USD", true, 1.0).into()],
+    /// ];
+    /// let txs = signer.sign_orders_batch(batches, Some(1)).unwrap();
+    /// assert_eq!((txs[0].nonce, txs[1].nonce), (1, 2));
+    /// ```
+    #[deprecated(since = "0.1.16", note = "Use sign_all() for simple batches")]
     pub fn sign_orders_batch(
         &self,
         order_batches: Vec<Vec<OrderItem>>,
@@ -445,46 +2693,73 @@ impl Signer {
             return Ok(vec![]);
         }
 
-        let base = base_nonce.unwrap_or_else(crate::nonce::current_timestamp_millis);
-        if order_batches.len() < PARALLEL_THRESHOLD {
-            order_batches
-                .into_iter()
-                .enumerate()
-                .map(|(i, orders)| self.sign_single_order_batch(orders, base + i as u64))
-                .collect()
-        } else {
-            order_batches
-                .into_par_iter()
-                .enumerate()
-                .map(|(i, orders)| self.sign_single_order_batch(orders, base + i as u64))
-                .collect()
+        let base = self.base_nonce_or_now(base_nonce, order_batches.len())?;
+        if self.open_orders.is_some() || self.client_ids.is_some() {
+            return self.sign_all_in_order(order_batches, |i, orders| {
+                self.sign_single_order_batch(orders, base + i as u64)
+            });
         }
+        let (results, mode) = run_batch(
+            self.parallelism,
+            PARALLEL_THRESHOLD,
+            order_batches,
+            |i, orders| self.sign_single_order_batch(orders, base + i as u64),
+        );
+        self.usage.record_batch_mode(mode);
+        results.into_iter().map(|r| r.map(|(tx, _)| tx)).collect()
+    }
+
+    /// Deprecated compatibility alias; like `sign_orders_batch`, but tags
+    /// each transaction with the index of its batch in `order_batches`.
+    #[deprecated(since = "0.1.16", note = "Use sign_all_indexed() for simple batches")]
+    #[allow(deprecated)]
+    pub fn sign_orders_batch_indexed(
+        &self,
+        order_batches: Vec<Vec<OrderItem>>,
+        base_nonce: Option<u64>,
+    ) -> Result<Vec<IndexedSignedTransaction>> {
+        Ok(index_from(
+            0,
+            self.sign_orders_batch(order_batches, base_nonce)?,
+        ))
     }
 
     fn sign_single_order_batch(
         &self,
         orders: Vec<OrderItem>,
         nonce: u64,
-    ) -> Result<SignedTransaction> {
+    ) -> Result<(SignedTransaction, PreSignUndo)> {
         if orders.is_empty() {
             return Err(Error::EmptyOrders);
         }
+        let account = self.account;
+        let undo = self.check_pre_sign(&orders, nonce, &account, false)?;
+        match self.sign_order_batch_unchecked(orders, nonce, &account) {
+            Ok(tx) => Ok((tx, undo)),
+            Err(e) => {
+                self.undo_pre_sign(undo);
+                Err(e)
+            }
+        }
+    }
 
-        let account = self.keypair.pubkey();
-        let signer_pubkey = self.keypair.pubkey();
+    fn sign_order_batch_unchecked(
+        &self,
+        orders: Vec<OrderItem>,
+        nonce: u64,
+        account: &Pubkey,
+    ) -> Result<SignedTransaction> {
+        let account = *account;
+        let signer_pubkey = self.signer;
         let order_id = if self.compute_order_id && orders.len() == 1 {
-            let mut scratch = Vec::with_capacity(96);
-            compute_order_item_id_at_index(&orders[0], 0, nonce, &account, &mut scratch)
-                .map(|id| id.to_base58())
+            compute_order_item_id_at_index(&orders[0], 0, nonce, &account).map(|id| id.to_base58())
         } else {
             None
         };
         let order_ids = if self.compute_batch_order_ids && orders.len() > 1 {
-            let mut scratch = Vec::with_capacity(96);
             let mut ids = Vec::with_capacity(orders.len());
             for (idx, item) in orders.iter().enumerate() {
-                if let Some(id) =
-                    compute_order_item_id_at_index(item, idx as u32, nonce, &account, &mut scratch)
+                if let Some(id) = compute_order_item_id_at_index(item, idx as u32, nonce, &account)
                 {
                     ids.push(id.to_base58());
                 }
@@ -501,17 +2776,21 @@ impl Signer {
         let action = Action::Order { orders };
         let mut serializer = Vec::with_capacity(512);
         serialize_for_sdk_signing(&action, nonce, &account, &mut serializer)?;
-        let signature = self.sign_bytes(&serializer);
-        let actions = self.action_to_json(&action)?;
+        let signature = self.try_sign_bytes(&serializer)?;
+        self.usage.signed.fetch_add(1, Ordering::Relaxed);
 
         Ok(SignedTransaction {
-            actions,
+            action,
             nonce,
             account: account.to_base58(),
             signer: signer_pubkey.to_base58(),
             signature,
             order_id,
             order_ids,
+            id_scheme: IdScheme::CURRENT,
+            resign_count: 0,
+            group_kind: None,
+            group_warnings: Vec::new(),
         })
     }
 
@@ -523,8 +2802,7 @@ impl Signer {
     ) -> Option<String> {
         match action {
             Action::Order { orders } if orders.len() == 1 => {
-                let mut scratch = Vec::with_capacity(96);
-                compute_order_item_id_at_index(&orders[0], 0, nonce, account, &mut scratch)
+                compute_order_item_id_at_index(&orders[0], 0, nonce, account)
                     .map(|id| id.to_base58())
             }
             _ => None,
@@ -537,318 +2815,179 @@ impl Signer {
         nonce: u64,
         account: &Pubkey,
     ) -> Option<Vec<String>> {
-        match action {
-            Action::Order { orders } if orders.len() > 1 => {
-                let mut scratch = Vec::with_capacity(96);
-                let mut ids = Vec::with_capacity(orders.len());
-                for (idx, item) in orders.iter().enumerate() {
-                    if let Some(id) = compute_order_item_id_at_index(
-                        item,
-                        idx as u32,
-                        nonce,
-                        account,
-                        &mut scratch,
-                    ) {
-                        ids.push(id.to_base58());
-                    }
-                }
-                if ids.is_empty() {
-                    None
-                } else {
-                    Some(ids)
-                }
-            }
-            _ => None,
-        }
-    }
-
-    fn action_to_json(&self, action: &Action) -> Result<Vec<serde_json::Value>> {
-        match action {
-            Action::Order { orders } => orders
-                .iter()
-                .map(|item| self.order_item_to_json(item))
-                .collect(),
-            Action::Faucet(faucet) => {
-                let mut faucet_obj = json!({ "u": faucet.user.to_base58() });
-                if let Some(amount) = faucet.amount {
-                    faucet_obj["amount"] = json!(amount);
-                }
-                Ok(vec![json!({ "faucet": faucet_obj })])
-            }
-            Action::AgentWalletCreation(agent) => Ok(vec![json!({
-                "agentWalletCreation": {
-                    "a": agent.agent.to_base58(),
-                    "d": agent.delete
-                }
-            })]),
-            Action::UpdateUserSettings(settings) => {
-                let leverage: serde_json::Map<String, serde_json::Value> = settings
-                    .max_leverage
-                    .iter()
-                    .map(|(symbol, lev)| (symbol.clone(), json!(lev)))
-                    .collect();
-                Ok(vec![json!({ "updateUserSettings": { "m": leverage } })])
-            }
-            Action::Oracle { oracles } => Ok(oracles
-                .iter()
-                .map(|o| {
-                    json!({
-                        "px": {
-                            "t": o.timestamp,
-                            "c": o.asset,
-                            "px": o.price
-                        }
-                    })
-                })
-                .collect()),
-            Action::PythOracle { oracles } => {
-                let entries: Vec<_> = oracles
-                    .iter()
-                    .map(|o| {
-                        json!({
-                            "t": o.timestamp,
-                            "fi": o.feed_index,
-                            "px": o.price,
-                            "e": o.exponent
-                        })
-                    })
-                    .collect();
-                Ok(vec![json!({ "o": { "oracles": entries } })])
-            }
-            Action::WhitelistFaucet(action) => Ok(vec![json!({
-                "whitelistFaucet": {
-                    "target": action.target.to_base58(),
-                    "whitelist": action.whitelist
-                }
-            })]),
-            Action::CreateSubAccount(action) => {
-                let mut obj = json!({ "name": action.name });
-                if let Some(symbol) = &action.margin_symbol {
-                    obj["marginSymbol"] = json!(symbol);
-                }
-                if let Some(amount) = action.margin_amount {
-                    obj["marginAmount"] = json!(amount);
-                }
-                Ok(vec![json!({ "createSubAccount": obj })])
-            }
-            Action::RemoveSubAccount(action) => Ok(vec![json!({
-                "removeSubAccount": {
-                    "toRemove": action.to_remove.to_base58()
-                }
-            })]),
-            Action::RenameSubAccount(action) => Ok(vec![json!({
-                "renameSubAccount": {
-                    "account": action.account.to_base58(),
-                    "name": action.name
-                }
-            })]),
-            Action::Transfer(transfer) => Ok(vec![json!({
-                "transfer": {
-                    "k": match transfer.kind {
-                        TransferKind::Internal => "internal",
-                        TransferKind::External => "external",
-                    },
-                    "from": transfer.from.to_base58(),
-                    "to": transfer.to.to_base58(),
-                    "marginSymbol": transfer.margin_symbol,
-                    "marginAmount": transfer.margin_amount,
-                }
-            })]),
-            Action::CreateMultisig(action) => Ok(vec![json!({
-                "createMultisig": {
-                    "signers": action.signers.iter().map(Pubkey::to_base58).collect::<Vec<_>>(),
-                    "threshold": action.threshold,
-                    "timeLockSecs": action.time_lock_secs,
-                    "proposalLifetimeSecs": action.proposal_lifetime_secs,
-                }
-            })]),
-            Action::MultisigPropose(action) => {
-                let mut inner_actions = Vec::new();
-                for inner in &action.actions {
-                    inner_actions.extend(self.action_to_json(inner)?);
-                }
-                Ok(vec![json!({
-                    "msp": {
-                        "m": action.multisig.to_base58(),
-                        "a": inner_actions,
-                    }
-                })])
-            }
-            Action::MultisigApprove(action) => Ok(vec![json!({
-                "msa": {
-                    "m": action.multisig.to_base58(),
-                    "p": action.proposal_id,
-                }
-            })]),
-            Action::MultisigReject(action) => Ok(vec![json!({
-                "msr": {
-                    "m": action.multisig.to_base58(),
-                    "p": action.proposal_id,
-                }
-            })]),
-            Action::MultisigCancel(action) => Ok(vec![json!({
-                "msc": {
-                    "m": action.multisig.to_base58(),
-                    "p": action.proposal_id,
-                }
-            })]),
-            Action::MultisigExecute(action) => Ok(vec![json!({
-                "mse": {
-                    "m": action.multisig.to_base58(),
-                    "p": action.proposal_id,
-                }
-            })]),
-            Action::UpdateMultisigPolicy(action) => Ok(vec![json!({
-                "msu": {
-                    "m": action.multisig.to_base58(),
-                    "signers": action.signers.iter().map(Pubkey::to_base58).collect::<Vec<_>>(),
-                    "threshold": action.threshold,
-                    "timeLockSecs": action.time_lock_secs,
-                    "proposalLifetimeSecs": action.proposal_lifetime_secs,
-                }
-            })]),
-        }
-    }
-
-    fn order_item_to_json(&self, item: &OrderItem) -> Result<serde_json::Value> {
-        match item {
-            OrderItem::Order(order) => match &order.order_type {
-                OrderType::Limit { tif } => {
-                    let tif_str = match tif {
-                        TimeInForce::Gtc => "GTC",
-                        TimeInForce::Ioc => "IOC",
-                        TimeInForce::Alo => "ALO",
-                    };
-                    Ok(json!({
-                        "l": {
-                            "c": order.symbol,
-                            "b": order.is_buy,
-                            "px": order.price,
-                            "sz": order.size,
-                            "tif": tif_str,
-                            "r": order.reduce_only,
-                            "i": order.iso
-                        }
-                    }))
-                }
-                OrderType::Trigger {
-                    is_market,
-                    trigger_px: _,
-                } => {
-                    if !is_market {
-                        return Err(Error::InvalidOrder(
-                            "trigger orders are not supported by BULK API; use market".to_string(),
-                        ));
+        match action {
+            Action::Order { orders } if orders.len() > 1 => {
+                let mut ids = Vec::with_capacity(orders.len());
+                for (idx, item) in orders.iter().enumerate() {
+                    if let Some(id) =
+                        compute_order_item_id_at_index(item, idx as u32, nonce, account)
+                    {
+                        ids.push(id.to_base58());
                     }
-                    Ok(json!({
-                        "m": {
-                            "c": order.symbol,
-                            "b": order.is_buy,
-                            "sz": order.size,
-                            "r": order.reduce_only,
-                            "i": order.iso
-                        }
-                    }))
-                }
-            },
-            OrderItem::Modify(modify) => Ok(json!({
-                "mod": {
-                    "oid": modify.order_id.to_base58(),
-                    "c": modify.symbol,
-                    "sz": modify.amount
-                }
-            })),
-            OrderItem::Cancel(cancel) => Ok(json!({
-                "cx": {
-                    "c": cancel.symbol,
-                    "oid": cancel.order_id.to_base58()
-                }
-            })),
-            OrderItem::CancelAll(cancel_all) => Ok(json!({
-                "cxa": {
-                    "c": cancel_all.symbols
                 }
-            })),
-            OrderItem::Stop(stop) => Ok(json!({
-                "st": {
-                    "c": stop.symbol,
-                    "d": stop.is_buy,
-                    "sz": stop.size,
-                    "tr": stop.trigger_price,
-                    "lim": stop.limit_price,
-                    "i": stop.iso
-                }
-            })),
-            OrderItem::TakeProfit(tp) => Ok(json!({
-                "tp": {
-                    "c": tp.symbol,
-                    "d": tp.is_buy,
-                    "sz": tp.size,
-                    "tr": tp.trigger_price,
-                    "lim": tp.limit_price,
-                    "i": tp.iso
-                }
-            })),
-            OrderItem::RangeOco(rng) => Ok(json!({
-                "rng": {
-                    "c": rng.symbol,
-                    "d": rng.is_buy,
-                    "sz": rng.size,
-                    "pmin": rng.collar_min,
-                    "pmax": rng.collar_max,
-                    "lmin": rng.limit_min,
-                    "lmax": rng.limit_max,
-                    "i": rng.iso
+                if ids.is_empty() {
+                    None
+                } else {
+                    Some(ids)
                 }
-            })),
-            OrderItem::TriggerBasket(trig) => {
-                let nested: Result<Vec<_>> = trig
-                    .actions
-                    .iter()
-                    .map(|a| self.order_item_to_json(a))
-                    .collect();
-                Ok(json!({
-                    "trig": {
-                        "c": trig.symbol,
-                        "d": trig.is_buy,
-                        "tr": trig.trigger_price,
-                        "actions": nested?,
-                        "i": trig.iso
-                    }
-                }))
             }
-            OrderItem::OnFill(of) => {
-                let actions: Result<Vec<_>> = of
-                    .actions
-                    .iter()
-                    .map(|a| self.order_item_to_json(a))
-                    .collect();
-                Ok(json!({
-                    "of": {
-                        "p": of.p,
-                        "actions": actions?
-                    }
-                }))
-            }
-            OrderItem::TrailingStop(trl) => Ok(json!({
-                "trl": {
-                    "c": trl.symbol,
-                    "b": trl.is_buy,
-                    "sz": trl.size,
-                    "trb": trl.trail_bps,
-                    "stb": trl.step_bps,
-                    "lim": trl.limit_price,
-                    "i": trl.iso
-                }
-            })),
+            _ => None,
         }
     }
 }
 
+/// Tag `txs` with their input positions, counting from `start`.
+fn index_from(start: usize, txs: Vec<SignedTransaction>) -> Vec<IndexedSignedTransaction> {
+    txs.into_iter()
+        .enumerate()
+        .map(|(i, tx)| IndexedSignedTransaction {
+            index: start + i,
+            tx,
+        })
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    /// Signer on a mock clock that every pre-sign check advances by `step_ms`.
+    fn slow_signer(start: u64, step_ms: u64) -> (Arc<AtomicU64>, Signer) {
+        let now = Arc::new(AtomicU64::new(start));
+        let clock = now.clone();
+        let manager = NonceManager::timestamp().with_clock(move || clock.load(Ordering::SeqCst));
+        let mut signer = Signer::with_nonce_manager(Keypair::generate(), manager);
+        let tick = now.clone();
+        signer.set_pre_sign_hook(Some(Arc::new(move |_, _| {
+            tick.fetch_add(step_ms, Ordering::SeqCst);
+            Ok(())
+        })));
+        (now, signer)
+    }
+
+    #[test]
+    fn test_deadline_pre_expired() {
+        let (_, mut signer) = slow_signer(1_000, 0);
+        let order: OrderItem = Order::market("BTC-USD", true, 0.1).into();
+        let late = SignOptions::new().with_deadline_ms(990);
+        let err = signer
+            .sign_with_options(order.clone(), None, &late)
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            Error::DeadlineExceeded {
+                overrun_ms: 10,
+                signed: 0
+            }
+        ));
+        assert_eq!(signer.usage().signed, 0);
+
+        // Exactly on the deadline still signs.
+        let on_time = SignOptions::new().with_deadline_ms(1_000);
+        assert!(signer.sign_with_options(order, None, &on_time).is_ok());
+        assert_eq!(
+            signer.usage(),
+            SignerUsage {
+                signed: 1,
+                deadline_rejections: 1,
+                last_batch_mode: None,
+                reconnects: 0,
+            }
+        );
+    }
+
+    #[test]
+    fn test_deadline_expires_mid_batch() {
+        let (now, signer) = slow_signer(1_000, 10);
+        let items: Vec<OrderItem> = (0..20)
+            .map(|_| Order::market("BTC-USD", true, 0.1).into())
+            .collect();
+        // Items 0..3 finish at 1_010, 1_020, 1_030; item 3 finishes at 1_040.
+        let options = SignOptions::new().with_deadline_ms(1_035);
+        let err = signer
+            .sign_all_with_options(items.clone(), Some(1), &options)
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            Error::DeadlineExceeded {
+                overrun_ms: 5,
+                signed: 3
+            }
+        ));
+        assert_eq!(now.load(Ordering::SeqCst), 1_040);
+        assert_eq!(
+            signer.usage(),
+            SignerUsage {
+                signed: 4,
+                deadline_rejections: 1,
+                last_batch_mode: Some(BatchMode::Serial),
+                reconnects: 0,
+            }
+        );
+
+        // A single slow sign is discarded after the fact too.
+        let mut single = signer.share();
+        let options = SignOptions::new().with_deadline_ms(1_045);
+        assert!(matches!(
+            single.sign_with_options(items[0].clone(), None, &options),
+            Err(Error::DeadlineExceeded { overrun_ms: 5, .. })
+        ));
+
+        // Other rejections are not deadline rejections.
+        single.set_pre_sign_hook(Some(Arc::new(|_, _| {
+            Err(Error::PreSignRejected("no".to_string()))
+        })));
+        assert!(single
+            .sign_with_options(items[0].clone(), None, &SignOptions::new())
+            .is_err());
+        assert_eq!(signer.usage().deadline_rejections, 2);
+
+        let txs = signer
+            .sign_all_with_options(items, Some(1), &SignOptions::new())
+            .unwrap();
+        assert_eq!(txs.len(), 20);
+    }
+
+    #[test]
+    fn test_pre_sign_hook() {
+        let seen = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let log = seen.clone();
+        let mut signer = Signer::with_pre_sign_hook(Keypair::generate(), move |item, nonce| {
+            log.lock().unwrap().push(nonce);
+            match item {
+                OrderItem::Order(order) if order.size > 1.0 => {
+                    Err(Error::PreSignRejected("too large".to_string()))
+                }
+                _ => Ok(()),
+            }
+        });
+        let small = Order::limit("BTC-USD", true, 100000.0, 0.5, TimeInForce::Gtc);
+        let large = Order::limit("BTC-USD", true, 100000.0, 2.0, TimeInForce::Gtc);
+
+        signer.sign(small.clone().into(), Some(7)).unwrap();
+        assert!(matches!(
+            signer.sign(large.clone().into(), Some(8)),
+            Err(Error::PreSignRejected(_))
+        ));
+        assert!(signer
+            .sign_group(vec![small.clone().into(), large.clone().into()], Some(9))
+            .is_err());
+        let items: Vec<OrderItem> = (0..12).map(|_| small.clone().into()).collect();
+        assert_eq!(signer.sign_all(items, Some(100)).unwrap().len(), 12);
+        assert!(signer
+            .sign_all(vec![small.into(), large.into()], Some(200))
+            .is_err());
+
+        let mut seen = seen.lock().unwrap().clone();
+        seen.sort_unstable();
+        assert_eq!(&seen[..4], &[7, 8, 9, 9]);
+        assert_eq!(&seen[4..16], &(100..112).collect::<Vec<_>>()[..]);
+
+        signer.set_pre_sign_hook(None);
+        let large = Order::limit("BTC-USD", true, 100000.0, 2.0, TimeInForce::Gtc);
+        assert!(signer.sign(large.into(), Some(300)).is_ok());
+    }
+
     #[test]
     fn test_sign_single() {
         let keypair = Keypair::generate();
@@ -857,8 +2996,8 @@ mod tests {
         let signed = signer.sign(order.into(), Some(1234567890)).unwrap();
 
         assert_eq!(signed.nonce, 1234567890);
-        assert_eq!(signed.actions.len(), 1);
-        assert!(signed.actions[0].get("l").is_some());
+        assert_eq!(signed.actions_json().unwrap().len(), 1);
+        assert!(signed.actions_json().unwrap()[0].get("l").is_some());
         assert!(!signed.signature.is_empty());
     }
 
@@ -871,13 +3010,39 @@ mod tests {
             .sign(OrderItem::Modify(modify), Some(1234567890))
             .unwrap();
 
-        let mod_obj = signed.actions[0].get("mod").unwrap();
+        let actions = signed.actions_json().unwrap();
+
+        let mod_obj = actions[0].get("mod").unwrap();
         assert!(mod_obj.get("c").is_some());
         assert!(mod_obj.get("sz").is_some());
         assert!(mod_obj.get("symbol").is_none());
         assert!(mod_obj.get("amount").is_none());
     }
 
+    #[test]
+    fn test_sign_modify_is_stable_across_spellings() {
+        let keypair = Keypair::generate();
+        let order_id = Hash::random().to_base58();
+        let via_amount: OrderItem = serde_json::json!({
+            "type": "modify", "symbol": "BTC-USD", "orderId": order_id, "amount": 0.25
+        })
+        .try_into()
+        .unwrap();
+        let via_size: OrderItem = serde_json::json!({
+            "type": "modify", "symbol": "BTC-USD", "order_id": order_id, "size": 0.25
+        })
+        .try_into()
+        .unwrap();
+        assert_eq!(via_amount, via_size);
+
+        let mut signer = Signer::new(keypair);
+        let a = signer.sign(via_amount, Some(1234567890)).unwrap();
+        let b = signer.sign(via_size, Some(1234567890)).unwrap();
+        assert_eq!(a.order_id, b.order_id);
+        assert_eq!(a.signature, b.signature);
+        assert_eq!(a.actions_json().unwrap(), b.actions_json().unwrap());
+    }
+
     #[test]
     fn test_sign_all_parallel_nonce_increment() {
         let keypair = Keypair::generate();
@@ -904,6 +3069,359 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_sign_all_preserves_input_order() {
+        let signer = Signer::new(Keypair::generate());
+        // Below and above PARALLEL_THRESHOLD to cover both paths.
+        for n in [PARALLEL_THRESHOLD - 1, PARALLEL_THRESHOLD * 4] {
+            let orders: Vec<OrderItem> = (0..n)
+                .map(|i| {
+                    Order::limit("BTC-USD", true, 1000.0 + i as f64, 0.1, TimeInForce::Gtc).into()
+                })
+                .collect();
+
+            let signed = signer.sign_all(orders.clone(), Some(500)).unwrap();
+            let indexed = signer.sign_all_indexed(orders, Some(500)).unwrap();
+            assert_eq!(signed.len(), n);
+            assert_eq!(indexed.len(), n);
+            for (i, (tx, itx)) in signed.iter().zip(&indexed).enumerate() {
+                assert_eq!(
+                    tx.actions_json().unwrap()[0]["l"]["px"].as_f64(),
+                    Some(1000.0 + i as f64)
+                );
+                assert_eq!(tx.nonce, 500 + i as u64);
+                assert_eq!(itx.index, i);
+                assert_eq!(itx.tx.signature, tx.signature);
+            }
+        }
+    }
+
+    #[test]
+    fn test_sign_all_with_post_hook() {
+        let signer = Signer::new(Keypair::generate());
+        let orders: Vec<OrderItem> = (0..PARALLEL_THRESHOLD * 2)
+            .map(|i| Order::limit("BTC-USD", true, 1000.0 + i as f64, 0.1, TimeInForce::Gtc).into())
+            .collect();
+
+        let mut seen = Vec::new();
+        let signed = signer
+            .sign_all_with_post_hook(orders.clone(), Some(500), |i, tx| {
+                seen.push((i, tx.signature.clone()))
+            })
+            .unwrap();
+        let expected = signer.sign_all(orders, Some(500)).unwrap();
+        assert_eq!(signed.len(), expected.len());
+        assert_eq!(seen.len(), expected.len());
+        for (i, ((idx, sig), tx)) in seen.iter().zip(&expected).enumerate() {
+            assert_eq!(*idx, i);
+            assert_eq!(sig, &tx.signature);
+            assert_eq!(signed[i].signature, tx.signature);
+        }
+    }
+
+    #[test]
+    fn test_sign_all_cost_estimate() {
+        let signer = Signer::new(Keypair::generate());
+        let items: Vec<OrderItem> = vec![
+            Order::limit("BTC-USD", true, 100000.0, 0.5, TimeInForce::Gtc).into(),
+            Cancel::new("BTC-USD", Hash::random()).into(),
+            Stop {
+                symbol: "BTC-USD".into(),
+                is_buy: false,
+                size: 1.0,
+                trigger_price: 90000.0,
+                limit_price: f64::NAN,
+                iso: false,
+            }
+            .into(),
+        ];
+        let fees = signer.sign_all_cost_estimate(&items, 2.5);
+        assert_eq!(fees, vec![12.5, 0.0, 22.5]);
+        assert_eq!(signer.sign_all_total_cost_estimate(&items, 2.5), 35.0);
+    }
+
+    #[test]
+    #[allow(deprecated)]
+    fn test_sign_orders_batch_preserves_input_order() {
+        let signer = Signer::new(Keypair::generate());
+        for n in [PARALLEL_THRESHOLD - 1, PARALLEL_THRESHOLD * 4] {
+            let batches: Vec<Vec<OrderItem>> = (0..n)
+                .map(|i| vec![Order::market("BTC-USD", true, 1.0 + i as f64).into()])
+                .collect();
+
+            let signed = signer.sign_orders_batch(batches, Some(500)).unwrap();
+            assert_eq!(signed.len(), n);
+            for (i, tx) in signed.iter().enumerate() {
+                assert_eq!(
+                    tx.actions_json().unwrap()[0]["m"]["sz"].as_f64(),
+                    Some(1.0 + i as f64)
+                );
+                assert_eq!(tx.nonce, 500 + i as u64);
+            }
+        }
+    }
+
+    /// Randomized: every indexed variant tags output `i` with index `i` and
+    /// nonce `base + i`, and the serial and threaded paths sign identically.
+    #[test]
+    #[allow(deprecated)]
+    fn test_indexed_variants_preserve_order_across_paths() {
+        use crate::prepare::prepare_all_indexed;
+        use rand::{rngs::StdRng, Rng, SeedableRng};
+
+        let mut rng = StdRng::seed_from_u64(0x1dec5);
+        let keypair = Keypair::generate();
+        let serial = Signer::new(keypair.clone()).with_parallelism(Parallelism::Serial);
+        let threaded = Signer::new(keypair).with_parallelism(Parallelism::Threads(4));
+        let account = serial.account;
+
+        for _ in 0..12 {
+            let n = rng.gen_range(1..PARALLEL_THRESHOLD * 3);
+            let base = rng.gen_range(1..1_000_000u64);
+            let items: Vec<OrderItem> = (0..n)
+                .map(|_| {
+                    let size = rng.gen_range(1..1000) as f64 / 100.0;
+                    Order::market("BTC-USD", rng.gen(), size).into()
+                })
+                .collect();
+            let check = |index: usize, i: usize, tx: &SignedTransaction| {
+                assert_eq!(index, i);
+                assert_eq!(tx.nonce, base + i as u64);
+            };
+
+            let a = serial.sign_all_indexed(items.clone(), Some(base)).unwrap();
+            let b = threaded
+                .sign_all_indexed(items.clone(), Some(base))
+                .unwrap();
+            assert_eq!(a.len(), n);
+            for (i, (a, b)) in a.iter().zip(&b).enumerate() {
+                check(a.index, i, &a.tx);
+                check(b.index, i, &b.tx);
+                assert_eq!(a.tx.signature, b.tx.signature);
+            }
+
+            let batches: Vec<Vec<OrderItem>> = items.iter().map(|o| vec![o.clone()]).collect();
+            let a = serial
+                .sign_orders_batch_indexed(batches.clone(), Some(base))
+                .unwrap();
+            let b = threaded
+                .sign_orders_batch_indexed(batches, Some(base))
+                .unwrap();
+            for (i, (a, b)) in a.iter().zip(&b).enumerate() {
+                check(a.index, i, &a.tx);
+                check(b.index, i, &b.tx);
+                assert_eq!(a.tx.signature, b.tx.signature);
+            }
+
+            let a = serial
+                .sign_all_partial_indexed(items.clone(), Some(base))
+                .unwrap();
+            let b = threaded
+                .sign_all_partial_indexed(items.clone(), Some(base))
+                .unwrap();
+            for (i, (a, b)) in a.iter().zip(&b).enumerate() {
+                let (a_tx, b_tx) = (a.1.as_ref().unwrap(), b.1.as_ref().unwrap());
+                check(a.0, i, a_tx);
+                check(b.0, i, b_tx);
+                assert_eq!(a_tx.signature, b_tx.signature);
+            }
+
+            #[cfg(feature = "batch")]
+            {
+                let every_n = rng.gen_range(1..=n);
+                let a = serial
+                    .sign_all_with_checkpoint_indexed(items.clone(), Some(base), every_n, |_| {})
+                    .unwrap();
+                let b = threaded
+                    .sign_all_with_checkpoint_indexed(items.clone(), Some(base), every_n, |_| {})
+                    .unwrap();
+                for (i, (a, b)) in a.iter().zip(&b).enumerate() {
+                    check(a.index, i, &a.tx);
+                    check(b.index, i, &b.tx);
+                    assert_eq!(a.tx.signature, b.tx.signature);
+                }
+
+                let checkpoint = BatchCheckpoint {
+                    items_hash: order_items_digest(&items).unwrap(),
+                    signer: threaded.signer,
+                    account,
+                    base_nonce: base,
+                    completed: rng.gen_range(0..=n),
+                };
+                let rest = threaded
+                    .resume_sign_all_indexed(items.clone(), &checkpoint)
+                    .unwrap();
+                assert_eq!(rest.len(), n - checkpoint.completed);
+                for (tx, full) in rest.iter().zip(&a[checkpoint.completed..]) {
+                    check(tx.index, full.index, &tx.tx);
+                    assert_eq!(tx.tx.signature, full.tx.signature);
+                }
+            }
+
+            let prepared = prepare_all_indexed(items, &account, None, Some(base)).unwrap();
+            for (i, (p, tx)) in prepared.iter().zip(&a).enumerate() {
+                assert_eq!(p.index, i);
+                assert_eq!(p.message.nonce, base + i as u64);
+                assert_eq!(p.message.order_id, tx.1.as_ref().unwrap().order_id);
+            }
+        }
+    }
+
+    #[cfg(feature = "batch")]
+    #[test]
+    fn test_resume_from_checkpoint_matches_full_run() {
+        let signer = Signer::new(Keypair::generate());
+        let orders: Vec<OrderItem> = (0..25)
+            .map(|i| Order::limit("BTC-USD", true, 1000.0 + i as f64, 0.1, TimeInForce::Gtc).into())
+            .collect();
+        let full = signer.sign_all(orders.clone(), Some(9000)).unwrap();
+
+        // Simulate a crash after the second chunk.
+        let mut checkpoints = Vec::new();
+        let chunked = signer
+            .sign_all_with_checkpoint(orders.clone(), Some(9000), 10, |cp| {
+                checkpoints.push(cp.clone())
+            })
+            .unwrap();
+        assert_eq!(checkpoints.len(), 3);
+        let saved = BatchCheckpoint::from_json(&checkpoints[1].to_json().unwrap()).unwrap();
+        assert_eq!(saved.completed, 20);
+
+        let mut resumed = chunked[..saved.completed].to_vec();
+        resumed.extend(signer.resume_sign_all(orders, &saved).unwrap());
+
+        assert_eq!(resumed.len(), full.len());
+        for (a, b) in resumed.iter().zip(&full) {
+            assert_eq!(a.nonce, b.nonce);
+            assert_eq!(a.signature, b.signature);
+        }
+    }
+
+    #[cfg(feature = "batch")]
+    #[test]
+    fn test_resume_rejects_edited_input() {
+        let signer = Signer::new(Keypair::generate());
+        let orders: Vec<OrderItem> = (0..5)
+            .map(|i| Order::market("BTC-USD", true, 1.0 + i as f64).into())
+            .collect();
+        let mut last = None;
+        signer
+            .sign_all_with_checkpoint(orders.clone(), Some(1), 2, |cp| last = Some(cp.clone()))
+            .unwrap();
+
+        let mut edited = orders;
+        edited[4] = Order::market("BTC-USD", false, 5.0).into();
+        let result = signer.resume_sign_all(edited, &last.unwrap());
+        assert!(matches!(result, Err(Error::CheckpointMismatch(_))));
+    }
+
+    #[cfg(feature = "batch")]
+    #[test]
+    fn test_resume_rejects_other_signer_or_account() {
+        let keypair = Keypair::generate();
+        let signer = Signer::new(keypair.clone());
+        let orders: Vec<OrderItem> = (0..5)
+            .map(|i| Order::market("BTC-USD", true, 1.0 + i as f64).into())
+            .collect();
+        let mut last = None;
+        signer
+            .sign_all_with_checkpoint(orders.clone(), Some(1), 2, |cp| last = Some(cp.clone()))
+            .unwrap();
+        let last = last.unwrap();
+        assert_eq!((last.signer, last.account), (signer.signer, signer.account));
+
+        let other_key = Signer::new(Keypair::generate());
+        let other_account = Signer::new(keypair).with_account(Keypair::generate().pubkey());
+        for other in [other_key, other_account] {
+            let Err(Error::CheckpointMismatch(message)) =
+                other.resume_sign_all(orders.clone(), &last)
+            else {
+                panic!("expected a checkpoint mismatch");
+            };
+            assert!(message.contains(&last.signer.fingerprint()), "{message}");
+            assert!(!message.contains(&last.signer.to_base58()), "{message}");
+            assert!(!message.contains(&last.account.to_base58()), "{message}");
+        }
+    }
+
+    #[cfg(feature = "batch")]
+    #[test]
+    fn test_checkpoint_next_nonce_overflow() {
+        let checkpoint = BatchCheckpoint {
+            items_hash: Hash::random(),
+            signer: Keypair::generate().pubkey(),
+            account: Keypair::generate().pubkey(),
+            base_nonce: u64::MAX - 1,
+            completed: 2,
+        };
+        assert!(matches!(
+            checkpoint.next_nonce(),
+            Err(Error::NonceOverflow { base, count: 2 }) if base == u64::MAX - 1
+        ));
+        let checkpoint = BatchCheckpoint {
+            completed: 1,
+            ..checkpoint
+        };
+        assert_eq!(checkpoint.next_nonce().unwrap(), u64::MAX);
+    }
+
+    #[test]
+    fn test_group_kind_tags() {
+        let mut signer = Signer::new(Keypair::generate());
+        let entry = Order::limit("BTC-USD", true, 100000.0, 0.1, TimeInForce::Gtc);
+        let tp = TakeProfit {
+            symbol: "BTC-USD".into(),
+            is_buy: false,
+            size: 0.1,
+            trigger_price: 110000.0,
+            limit_price: f64::NAN,
+            iso: false,
+        };
+        let sl = Stop {
+            symbol: "BTC-USD".into(),
+            is_buy: false,
+            size: 0.1,
+            trigger_price: 95000.0,
+            limit_price: f64::NAN,
+            iso: false,
+        };
+
+        let bracket = signer
+            .sign_bracket(entry.clone(), tp.clone(), sl.clone(), Some(1))
+            .unwrap();
+        assert_eq!(bracket.group_kind, Some(GroupKind::Bracket));
+        assert_eq!(bracket.actions_json().unwrap().len(), 3);
+        let oco = signer.sign_oco(tp.clone(), sl.clone(), Some(2)).unwrap();
+        assert_eq!(oco.group_kind, Some(GroupKind::Oco));
+
+        let items: Vec<OrderItem> = vec![entry.into(), tp.into(), sl.into()];
+        let custom = GroupKind::Custom("ladder".into());
+        let tagged = signer
+            .sign_group_with(items.clone(), Some(custom.clone()), Some(3))
+            .unwrap();
+        let untagged = signer.sign_group(items.clone(), Some(3)).unwrap();
+        assert_eq!(tagged.group_kind, Some(custom.clone()));
+        assert_eq!(untagged.group_kind, None);
+        // The tag is metadata only: same bytes, same signature.
+        assert_eq!(tagged.signature, untagged.signature);
+        assert!(!tagged.to_json().unwrap().contains("ladder"));
+
+        let account = *signer.account_pubkey();
+        let prepared =
+            crate::prepare_group_with(items.clone(), Some(custom.clone()), &account, None, Some(3))
+                .unwrap();
+        let plain = crate::prepare_group(items, &account, None, Some(3)).unwrap();
+        assert_eq!(prepared.message_bytes, plain.message_bytes);
+        let finalized = crate::finalize_transaction(prepared, &tagged.signature).unwrap();
+        assert_eq!(finalized.group_kind, Some(custom));
+
+        assert_eq!(GroupKind::from("oco"), GroupKind::Oco);
+        assert_eq!(
+            serde_json::to_string(&GroupKind::Bracket).unwrap(),
+            "\"bracket\""
+        );
+    }
+
     #[test]
     fn test_sign_group_atomic() {
         let keypair = Keypair::generate();
@@ -915,7 +3433,7 @@ mod tests {
         ];
 
         let signed = signer.sign_group(bracket, Some(1234567890)).unwrap();
-        assert_eq!(signed.actions.len(), 3);
+        assert_eq!(signed.actions_json().unwrap().len(), 3);
         assert_eq!(signed.nonce, 1234567890);
         assert!(signed.order_ids.is_none());
     }
@@ -925,8 +3443,8 @@ mod tests {
         let keypair = Keypair::generate();
         let mut signer = Signer::new(keypair);
         let signed = signer.sign_faucet(Some(1234567890)).unwrap();
-        assert_eq!(signed.actions.len(), 1);
-        assert!(signed.actions[0].get("faucet").is_some());
+        assert_eq!(signed.actions_json().unwrap().len(), 1);
+        assert!(signed.actions_json().unwrap()[0].get("faucet").is_some());
     }
 
     #[test]
@@ -937,7 +3455,62 @@ mod tests {
         let signed = signer
             .sign_agent_wallet(agent_keypair.pubkey(), false, Some(1234567890))
             .unwrap();
-        assert!(signed.actions[0].get("agentWalletCreation").is_some());
+        assert!(signed.actions_json().unwrap()[0]
+            .get("agentWalletCreation")
+            .is_some());
+    }
+
+    #[test]
+    fn test_content_derived_nonce() {
+        let keypair = Keypair::generate();
+        let agent = Keypair::generate().pubkey();
+        let signer_at = |epoch| {
+            Signer::with_nonce_manager(keypair.clone(), NonceManager::content_derived(epoch))
+        };
+
+        let mut signer = signer_at(7);
+        let first = signer.sign_agent_wallet(agent, false, None).unwrap();
+        let retry = signer_at(7).sign_agent_wallet(agent, false, None).unwrap();
+        assert_eq!(first.nonce, retry.nonce);
+        assert_eq!(first.signature, retry.signature);
+        assert!(first.verify().unwrap());
+        assert_eq!(
+            signer.resign_with_new_nonce(&first).unwrap().nonce,
+            first.nonce
+        );
+
+        let next_epoch = signer_at(8).sign_agent_wallet(agent, false, None).unwrap();
+        assert_ne!(next_epoch.nonce, first.nonce);
+        let deleted = signer.sign_agent_wallet(agent, true, None).unwrap();
+        assert_ne!(deleted.nonce, first.nonce);
+
+        let order: OrderItem = Order::market("BTC-USD", true, 0.1).into();
+        let err = signer.sign(order.clone(), None).unwrap_err();
+        assert!(matches!(err, Error::ContentDerivedNonce("order")));
+        assert!(err.to_string().contains("replay"));
+        assert!(signer.sign_all(vec![order.clone()], None).is_err());
+        assert!(signer.sign(order, Some(1)).is_ok());
+
+        let action = Action::Order { orders: vec![] };
+        let account = keypair.pubkey();
+        assert!(crate::prepare_action_content_derived(&action, 7, &account, None).is_err());
+    }
+
+    #[test]
+    fn test_sign_with_agent_account() {
+        let agent = Keypair::generate();
+        let account = Keypair::generate().pubkey();
+        let mut signer = Signer::new(agent.clone()).with_account(account);
+        assert_eq!(signer.account_pubkey(), &account);
+        assert_eq!(signer.signer_pubkey(), &agent.pubkey());
+
+        let order = Order::limit("BTC-USD", true, 100000.0, 0.1, TimeInForce::Gtc);
+        let signed = signer.sign(order.clone().into(), Some(1234567890)).unwrap();
+        assert_eq!(signed.account, account.to_base58());
+        assert_eq!(signed.signer, agent.pubkey().to_base58());
+
+        let batch = signer.sign_all(vec![order.into()], Some(1)).unwrap();
+        assert_eq!(batch[0].account, account.to_base58());
     }
 
     #[test]
@@ -962,8 +3535,8 @@ mod tests {
                 Some(1234567890),
             )
             .unwrap();
-        assert_eq!(signed.actions.len(), 1);
-        assert!(signed.actions[0].get("px").is_some());
+        assert_eq!(signed.actions_json().unwrap().len(), 1);
+        assert!(signed.actions_json().unwrap()[0].get("px").is_some());
     }
 
     #[test]
@@ -981,8 +3554,8 @@ mod tests {
                 Some(1234567890),
             )
             .unwrap();
-        assert_eq!(signed.actions.len(), 1);
-        assert!(signed.actions[0].get("o").is_some());
+        assert_eq!(signed.actions_json().unwrap().len(), 1);
+        assert!(signed.actions_json().unwrap()[0].get("o").is_some());
     }
 
     #[test]
@@ -995,7 +3568,8 @@ mod tests {
                 Some(1234567890),
             )
             .unwrap();
-        let obj = signed.actions[0].get("updateUserSettings").unwrap();
+        let actions = signed.actions_json().unwrap();
+        let obj = actions[0].get("updateUserSettings").unwrap();
         let leverage = obj.get("m").and_then(|v| v.as_object()).unwrap();
         assert_eq!(leverage.get("BTC").and_then(|v| v.as_f64()), Some(5.0));
         assert_eq!(leverage.get("ETH").and_then(|v| v.as_f64()), Some(3.0));
@@ -1008,8 +3582,9 @@ mod tests {
         let signed = signer
             .sign_create_sub_account(CreateSubAccount::new("desk-1"), Some(1234567890))
             .unwrap();
-        assert_eq!(signed.actions.len(), 1);
-        let obj = signed.actions[0].get("createSubAccount").unwrap();
+        assert_eq!(signed.actions_json().unwrap().len(), 1);
+        let actions = signed.actions_json().unwrap();
+        let obj = actions[0].get("createSubAccount").unwrap();
         assert_eq!(obj.get("name").and_then(|v| v.as_str()), Some("desk-1"));
         assert!(obj.get("marginSymbol").is_none());
         assert!(obj.get("marginAmount").is_none());
@@ -1025,7 +3600,8 @@ mod tests {
                 Some(1234567890),
             )
             .unwrap();
-        let obj = signed.actions[0].get("createSubAccount").unwrap();
+        let actions = signed.actions_json().unwrap();
+        let obj = actions[0].get("createSubAccount").unwrap();
         assert_eq!(
             obj.get("marginSymbol").and_then(|v| v.as_str()),
             Some("USDC")
@@ -1044,7 +3620,8 @@ mod tests {
         let signed = signer
             .sign_remove_sub_account(target, Some(1234567890))
             .unwrap();
-        let obj = signed.actions[0].get("removeSubAccount").unwrap();
+        let actions = signed.actions_json().unwrap();
+        let obj = actions[0].get("removeSubAccount").unwrap();
         assert_eq!(
             obj.get("toRemove").and_then(|v| v.as_str()),
             Some(target.to_base58().as_str())
@@ -1059,7 +3636,8 @@ mod tests {
         let signed = signer
             .sign_rename_sub_account(RenameSubAccount::new(target, "desk-2"), Some(1234567890))
             .unwrap();
-        let obj = signed.actions[0].get("renameSubAccount").unwrap();
+        let actions = signed.actions_json().unwrap();
+        let obj = actions[0].get("renameSubAccount").unwrap();
         assert_eq!(
             obj.get("account").and_then(|v| v.as_str()),
             Some(target.to_base58().as_str())
@@ -1076,7 +3654,8 @@ mod tests {
         let signed = signer
             .sign_transfer(Transfer::internal(from, to, "USDC", 10.0), Some(1234567890))
             .unwrap();
-        let obj = signed.actions[0].get("transfer").unwrap();
+        let actions = signed.actions_json().unwrap();
+        let obj = actions[0].get("transfer").unwrap();
         assert_eq!(obj.get("k").and_then(|v| v.as_str()), Some("internal"));
         assert_eq!(
             obj.get("marginSymbol").and_then(|v| v.as_str()),
@@ -1093,8 +3672,10 @@ mod tests {
         let signed = signer
             .sign_whitelist_faucet(target, true, Some(1234567890))
             .unwrap();
-        assert_eq!(signed.actions.len(), 1);
-        assert!(signed.actions[0].get("whitelistFaucet").is_some());
+        assert_eq!(signed.actions_json().unwrap().len(), 1);
+        assert!(signed.actions_json().unwrap()[0]
+            .get("whitelistFaucet")
+            .is_some());
     }
 
     #[test]
@@ -1106,6 +3687,56 @@ mod tests {
         assert!(signed.order_id.is_some());
     }
 
+    #[test]
+    fn test_zero_client_id_is_not_no_client_id() {
+        let order = Order::limit("BTC-USD", true, 100000.0, 0.1, TimeInForce::Gtc);
+        let zero = order.clone().with_client_id(Hash::from_bytes([0; 32]));
+        assert!(Hash::from_bytes([0; 32]).is_zero());
+        assert!(!Hash::from_bytes([1; 32]).is_zero());
+
+        let none_json = serde_json::to_value(&order).unwrap();
+        let zero_json = serde_json::to_value(&zero).unwrap();
+        assert!(none_json.get("cloid").is_none());
+        assert_eq!(zero_json["cloid"], "11111111111111111111111111111111");
+
+        let mut signer = Signer::new(Keypair::generate());
+        let err = signer.sign(zero.clone().into(), Some(1)).unwrap_err();
+        assert!(matches!(err, Error::ZeroClientId));
+        let err = signer
+            .sign_all(vec![order.clone().into(), zero.clone().into()], Some(1))
+            .unwrap_err();
+        assert!(matches!(err, Error::ZeroClientId));
+
+        // Nested in a trigger basket or an onFill, including the basket
+        // sign_order_conditional builds.
+        let condition = TriggerCondition::new("BTC-USD", true, 95000.0);
+        let err = signer
+            .sign_order_conditional(condition.clone(), zero.clone(), Some(1))
+            .unwrap_err();
+        assert!(matches!(err, Error::ZeroClientId));
+        let basket = condition.into_basket(vec![order.clone().into(), zero.clone().into()]);
+        let on_fill = OrderItem::OnFill(OnFill {
+            p: 0,
+            actions: vec![OrderItem::TriggerBasket(basket.clone())],
+        });
+        for nested in [OrderItem::TriggerBasket(basket), on_fill] {
+            let err = signer.sign(nested.clone(), Some(1)).unwrap_err();
+            assert!(matches!(err, Error::ZeroClientId));
+            let err = signer
+                .sign_group(vec![order.clone().into(), nested], Some(1))
+                .unwrap_err();
+            assert!(matches!(err, Error::ZeroClientId));
+        }
+
+        // The exchange wire layout carries no client ID, so with the opt-out
+        // both sign the same bytes; the difference stays client-side.
+        let mut signer = signer.allow_zero_client_id();
+        let with_zero = signer.sign(zero.into(), Some(1)).unwrap();
+        let without = signer.sign(order.into(), Some(1)).unwrap();
+        assert_eq!(with_zero.signature, without.signature);
+        assert_eq!(with_zero.order_id, without.order_id);
+    }
+
     #[test]
     fn test_order_id_disabled() {
         let keypair = Keypair::generate();
@@ -1142,7 +3773,7 @@ mod tests {
         let one = signer
             .sign_order(vec![order.clone().into()], Some(100))
             .unwrap();
-        assert_eq!(one.actions.len(), 1);
+        assert_eq!(one.actions_json().unwrap().len(), 1);
 
         let signer = Signer::new(keypair);
         let batches = vec![vec![order.into()]];
@@ -1164,4 +3795,304 @@ mod tests {
         assert_eq!(signed.len(), 1);
         assert_eq!(signed[0].order_ids.as_ref().map(Vec::len), Some(2));
     }
+
+    #[test]
+    fn test_verify_signed_transaction() {
+        let keypair = Keypair::generate();
+        let mut signer = Signer::new(keypair);
+        let group = vec![
+            Order::limit("BTC-USD", true, 100000.0, 0.1, TimeInForce::Gtc).into(),
+            OrderItem::Cancel(Cancel::new("ETH-USD", Hash::random())),
+            OrderItem::Stop(Stop {
+                symbol: "BTC-USD".to_string(),
+                is_buy: false,
+                size: 0.1,
+                trigger_price: 95000.0,
+                limit_price: f64::NAN,
+                iso: false,
+            }),
+        ];
+        let signed = signer.sign_group(group, Some(42)).unwrap();
+        assert!(signer.verify_signed_transaction(&signed, None).unwrap());
+
+        let propose = MultisigPropose::new(
+            Keypair::generate().pubkey(),
+            vec![Action::Faucet(Faucet::new(*signer.account_pubkey()))],
+        );
+        let signed_propose = signer.sign_multisig_propose(propose, Some(43)).unwrap();
+        assert!(signer
+            .verify_signed_transaction(&signed_propose, None)
+            .unwrap());
+
+        let mut tampered = signed.clone();
+        tampered.nonce += 1;
+        assert!(!signer.verify_signed_transaction(&tampered, None).unwrap());
+
+        let other = Signer::new(Keypair::generate());
+        assert!(!other.verify_signed_transaction(&signed, None).unwrap());
+    }
+
+    #[test]
+    fn test_verify_signed_transaction_with_message_bytes() {
+        let keypair = Keypair::generate();
+        let account = keypair.pubkey();
+        let mut signer = Signer::new(keypair);
+        let order: OrderItem = Order::market("BTC-USD", true, 0.5).into();
+        let prepared = crate::prepare_message(order.clone(), &account, None, Some(9)).unwrap();
+        let signed = signer.sign(order, Some(9)).unwrap();
+
+        assert!(signer
+            .verify_signed_transaction(&signed, Some(&prepared.message_bytes))
+            .unwrap());
+        assert!(!signer
+            .verify_signed_transaction(&signed, Some(b"not the message"))
+            .unwrap());
+
+        let mut bad = signed;
+        bad.signature = "not-base58!".to_string();
+        assert!(matches!(
+            signer.verify_signed_transaction(&bad, None),
+            Err(Error::InvalidBase58(_))
+        ));
+    }
+
+    #[test]
+    fn test_sign_bytes_raw_round_trip() {
+        let signer = Signer::new(Keypair::generate());
+        let pubkey = *signer.signer_pubkey();
+        let message = b"protocol message";
+        let signature = signer.sign_bytes_raw(message);
+
+        assert!(Signer::verify(&pubkey, message, &signature));
+        assert_eq!(
+            bs58::encode(signature).into_string(),
+            signer.sign_bytes(message)
+        );
+        assert!(verify_signature(&pubkey, message, &signer.sign_bytes(message)).unwrap());
+
+        assert!(!Signer::verify(&pubkey, b"protocol messagE", &signature));
+        assert!(!Signer::verify(
+            &Keypair::generate().pubkey(),
+            message,
+            &signature
+        ));
+        let mut tampered = signature;
+        tampered[10] ^= 1;
+        assert!(!Signer::verify(&pubkey, message, &tampered));
+    }
+
+    #[test]
+    fn test_zeroized_keypair_refuses_to_sign() {
+        let mut keypair = Keypair::generate();
+        keypair.zeroize();
+        let mut signer = Signer::new(keypair.clone());
+        let order = || -> OrderItem { Order::market("BTC-USD", true, 0.1).into() };
+        let zeroized = |r: Result<SignedTransaction>| matches!(r, Err(Error::KeypairZeroized));
+
+        assert!(zeroized(signer.sign(order(), Some(1))));
+        assert!(zeroized(signer.sign_group(vec![order(), order()], Some(2))));
+        assert!(matches!(
+            signer.sign_all(vec![order(); 3], Some(3)),
+            Err(Error::KeypairZeroized)
+        ));
+        assert!(matches!(
+            signer.try_sign_bytes(b"hello"),
+            Err(Error::KeypairZeroized)
+        ));
+        assert!(matches!(
+            signer.try_sign_bytes_raw(b"hello"),
+            Err(Error::KeypairZeroized)
+        ));
+        let sign_bytes = std::panic::AssertUnwindSafe(|| signer.sign_bytes_raw(b"hello"));
+        assert!(std::panic::catch_unwind(sign_bytes).is_err());
+
+        let prepared = crate::prepare_message(order(), &keypair.pubkey(), None, Some(4)).unwrap();
+        assert!(zeroized(prepared.sign_with_keypair(&keypair)));
+        assert!(zeroized(signer.sign_prepared(prepared)));
+        assert_eq!(signer.usage().signed, 0);
+    }
+
+    #[test]
+    fn test_cancel_all_max_age_is_rejected() {
+        let mut signer = Signer::new(Keypair::generate());
+        let sweep = CancelAll::for_symbol_older_than("BTC-USD", 30_000);
+        assert_eq!(sweep.symbols, vec!["BTC-USD".to_string()]);
+        assert_eq!(sweep.max_age_ms, Some(30_000));
+
+        let err = signer
+            .sign(OrderItem::CancelAll(sweep), Some(1))
+            .unwrap_err();
+        assert!(matches!(err, Error::InvalidOrder(msg) if msg.contains("max age")));
+        assert!(signer
+            .sign(OrderItem::CancelAll(CancelAll::all()), Some(2))
+            .is_ok());
+    }
+
+    #[test]
+    fn test_resign_with_new_nonce() {
+        let keypair = Keypair::generate();
+        let account = Keypair::generate().pubkey();
+        let mut signer =
+            Signer::with_nonce_manager(keypair, NonceManager::counter()).with_account(account);
+        let group = vec![
+            Order::limit("BTC-USD", true, 100000.0, 0.1, TimeInForce::Ioc).into(),
+            OrderItem::Cancel(Cancel::new("BTC-USD", Hash::random())),
+        ];
+        let original = signer.sign_group(group, Some(500)).unwrap();
+
+        let resigned = signer.resign_with_new_nonce(&original).unwrap();
+        assert_eq!(
+            resigned.actions_json().unwrap(),
+            original.actions_json().unwrap()
+        );
+        assert_eq!(resigned.account, original.account);
+        assert_eq!(resigned.signer, original.signer);
+        assert_eq!(resigned.nonce, 0);
+        assert_ne!(resigned.signature, original.signature);
+        assert_eq!(resigned.resign_count, 1);
+        assert!(signer.verify_signed_transaction(&resigned, None).unwrap());
+
+        let again = signer.resign_with_new_nonce(&resigned).unwrap();
+        assert_eq!(again.nonce, 1);
+        assert_eq!(again.resign_count, 2);
+    }
+
+    #[test]
+    fn test_resign_refuses_old_transactions() {
+        let mut signer = Signer::new(Keypair::generate()).with_max_resign_age_ms(5_000);
+        let order = Order::limit("BTC-USD", true, 100000.0, 0.1, TimeInForce::Gtc);
+        let now = crate::nonce::current_timestamp_millis();
+
+        let stale = signer
+            .sign(order.clone().into(), Some(now - 60_000))
+            .unwrap();
+        assert!(matches!(
+            signer.resign_with_new_nonce(&stale),
+            Err(Error::ResignTooOld {
+                max_age_ms: 5_000,
+                ..
+            })
+        ));
+
+        let fresh = signer.sign(order.into(), Some(now)).unwrap();
+        let resigned = signer.resign_with_new_nonce(&fresh).unwrap();
+        assert!(resigned.nonce >= now);
+    }
+
+    #[test]
+    fn test_sign_order_conditional() {
+        let mut signer = Signer::new(Keypair::generate());
+        let condition = TriggerCondition::new("BTC-USD", true, 45000.0);
+        let order = Order::limit("ETH-USD", true, 3000.0, 1.0, TimeInForce::Gtc);
+
+        let signed = signer
+            .sign_order_conditional(condition.clone(), order.clone(), Some(7))
+            .unwrap();
+        let actions = signed.actions_json().unwrap();
+        let trig = &actions[0]["trig"];
+        assert_eq!(trig["c"], "BTC-USD");
+        assert_eq!(trig["d"], true);
+        assert_eq!(trig["tr"].as_f64(), Some(45000.0));
+        assert_eq!(trig["actions"][0]["l"]["c"], "ETH-USD");
+
+        let basket = OrderItem::TriggerBasket(condition.into_basket(vec![order.into()]));
+        let expected = signer.sign(basket, Some(7)).unwrap();
+        assert_eq!(signed.signature, expected.signature);
+        assert!(signer.verify_signed_transaction(&signed, None).unwrap());
+    }
+
+    /// Pins the `trig` encoding of both crossing directions: `above` lands
+    /// in the basket's side flag `d`, buy for a rise and sell for a fall.
+    #[test]
+    fn test_trigger_condition_golden_encoding() {
+        let account = Pubkey::from_bytes([7; 32]);
+        // Identical but for the `d` byte after the symbol.
+        let cases = [
+            (
+                true,
+                concat!(
+                    "01000000000000000800000007000000000000004254432d555344",
+                    "01",
+                    "00c8e6bc1704000001000000000000000000000007000000000000004554482d",
+                    "5553440100e1f505000000000000000700000000000000070707070707070707",
+                    "0707070707070707070707070707070707070707070707",
+                ),
+            ),
+            (
+                false,
+                concat!(
+                    "01000000000000000800000007000000000000004254432d555344",
+                    "00",
+                    "00c8e6bc1704000001000000000000000000000007000000000000004554482d",
+                    "5553440100e1f505000000000000000700000000000000070707070707070707",
+                    "0707070707070707070707070707070707070707070707",
+                ),
+            ),
+        ];
+        for (above, golden) in cases {
+            let order = Order::market("ETH-USD", true, 1.0);
+            let basket =
+                TriggerCondition::new("BTC-USD", above, 45000.0).into_basket(vec![order.into()]);
+            let action = Action::Order {
+                orders: vec![OrderItem::TriggerBasket(basket)],
+            };
+
+            let json = crate::compact::action_to_json(&action).unwrap();
+            assert_eq!(
+                serde_json::Value::Array(json),
+                serde_json::json!([{
+                    "trig": {
+                        "c": "BTC-USD",
+                        "d": above,
+                        "tr": 45000.0,
+                        "actions": [{ "m": { "c": "ETH-USD", "b": true, "sz": 1.0, "r": false, "i": false } }],
+                        "i": false
+                    }
+                }])
+            );
+
+            let mut bytes = Vec::new();
+            serialize_for_sdk_signing(&action, 7, &account, &mut bytes).unwrap();
+            assert_eq!(hex::encode(&bytes), golden);
+        }
+    }
+
+    #[test]
+    fn test_sign_prepared_checks_policy_and_message() {
+        let account = Keypair::generate().pubkey();
+        let mut signer = Signer::with_pre_sign_hook(Keypair::generate(), |item, _| match item {
+            OrderItem::Order(order) if order.size > 1.0 => {
+                Err(Error::PreSignRejected("too large".to_string()))
+            }
+            _ => Ok(()),
+        });
+        let prepare = |size| {
+            crate::prepare_message(
+                Order::market("BTC-USD", true, size).into(),
+                &account,
+                Some(signer.signer_pubkey()),
+                Some(7),
+            )
+            .unwrap()
+        };
+        let large = prepare(5.0);
+        let small = prepare(0.5);
+
+        assert!(matches!(
+            signer.sign_prepared(large.clone()),
+            Err(Error::PreSignRejected(_))
+        ));
+        // Actions swapped under the message bytes are refused.
+        let mut tampered = large;
+        tampered.actions = small.actions.clone();
+        assert!(matches!(
+            signer.sign_prepared(tampered),
+            Err(Error::InvalidAction(_))
+        ));
+
+        let tx = signer.sign_prepared(small).unwrap();
+        assert_eq!(tx.account, account.to_base58());
+        assert!(signer.verify_signed_transaction(&tx, None).unwrap());
+        assert_eq!(signer.usage().signed, 1);
+    }
 }
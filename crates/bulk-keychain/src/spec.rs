@@ -0,0 +1,176 @@
+//! Per-symbol price/size granularity.
+//!
+//! A [`SymbolSpec`] lets callers work in integer ticks and lots so equality
+//! checks and risk math stay exact. Conversion goes through the exact decimal
+//! string, so the `f64` that gets signed is the one closest to that decimal and
+//! renders back to it unchanged (`3` ticks of `0.1` is `0.3`, not
+//! `0.30000000000000004`).
+
+use crate::{Error, Result};
+
+/// Most decimals a spec may use; keeps `10^decimals` well inside `u128`.
+pub const MAX_DECIMALS: u32 = 18;
+
+/// Tick and lot size of a symbol, as exact decimals.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SymbolSpec {
+    /// Price decimals; one price unit is `10^-price_decimals`
+    pub price_decimals: u32,
+    /// Price units per tick
+    pub tick_units: u64,
+    /// Size decimals; one size unit is `10^-size_decimals`
+    pub size_decimals: u32,
+    /// Size units per lot
+    pub lot_units: u64,
+}
+
+impl SymbolSpec {
+    /// Spec whose tick and lot are one unit of the last decimal
+    /// (e.g. `new(2, 4)`: tick `0.01`, lot `0.0001`).
+    pub fn new(price_decimals: u32, size_decimals: u32) -> Self {
+        Self {
+            price_decimals,
+            tick_units: 1,
+            size_decimals,
+            lot_units: 1,
+        }
+    }
+
+    /// Set the tick to `units` price units (e.g. `new(2, 4).with_tick_units(5)`: tick `0.05`).
+    pub fn with_tick_units(mut self, units: u64) -> Self {
+        self.tick_units = units;
+        self
+    }
+
+    /// Set the lot to `units` size units.
+    pub fn with_lot_units(mut self, units: u64) -> Self {
+        self.lot_units = units;
+        self
+    }
+
+    /// Exact decimal price of `ticks` ticks, e.g. `"100000.5"`.
+    pub fn price_string(&self, ticks: i64) -> Result<String> {
+        to_decimal("price", ticks, self.tick_units, self.price_decimals)
+    }
+
+    /// Exact decimal size of `lots` lots.
+    pub fn size_string(&self, lots: i64) -> Result<String> {
+        to_decimal("size", lots, self.lot_units, self.size_decimals)
+    }
+
+    /// Price of `ticks` ticks as the `f64` that renders exactly as [`SymbolSpec::price_string`].
+    pub fn price_from_ticks(&self, ticks: i64) -> Result<f64> {
+        to_exact_f64("price", &self.price_string(ticks)?)
+    }
+
+    /// Size of `lots` lots as the `f64` that renders exactly as [`SymbolSpec::size_string`].
+    pub fn size_from_lots(&self, lots: i64) -> Result<f64> {
+        to_exact_f64("size", &self.size_string(lots)?)
+    }
+
+    /// Whole lots in `size`; fails if `size` is not on the lot grid.
+    pub fn lots_from_size(&self, size: f64) -> Result<i64> {
+        let lot = self.lot_units as f64 / 10f64.powi(self.size_decimals as i32);
+        let lots = (size / lot).round();
+        if lots < 1.0 || lots > i64::MAX as f64 || self.size_from_lots(lots as i64)? != size {
+            return Err(Error::InvalidOrder(format!(
+                "size {} is not a whole number of lots",
+                size
+            )));
+        }
+        Ok(lots as i64)
+    }
+}
+
+fn to_decimal(what: &str, count: i64, step_units: u64, decimals: u32) -> Result<String> {
+    if count <= 0 {
+        return Err(Error::InvalidOrder(format!(
+            "{} must be positive, got {} steps",
+            what, count
+        )));
+    }
+    if step_units == 0 || decimals > MAX_DECIMALS {
+        return Err(Error::InvalidOrder(format!(
+            "invalid {} spec: {} units at {} decimals",
+            what, step_units, decimals
+        )));
+    }
+    let units = count as u128 * step_units as u128;
+    let scale = 10u128.pow(decimals);
+    let (int, frac) = (units / scale, units % scale);
+    if frac == 0 {
+        return Ok(int.to_string());
+    }
+    let frac = format!("{:0width$}", frac, width = decimals as usize);
+    Ok(format!("{}.{}", int, frac.trim_end_matches('0')))
+}
+
+fn to_exact_f64(what: &str, decimal: &str) -> Result<f64> {
+    let value: f64 = decimal.parse().expect("rendered decimal parses as f64");
+    // `Display` prints the shortest round-tripping form, never an exponent.
+    if value.to_string() != decimal {
+        return Err(Error::InvalidOrder(format!(
+            "{} {} has no exact f64 rendering",
+            what, decimal
+        )));
+    }
+    Ok(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ticks_render_exactly() {
+        let spec = SymbolSpec::new(1, 8);
+        // Naive float math drifts; the decimal route does not.
+        assert_ne!(3.0 * 0.1, 0.3);
+        assert_eq!(spec.price_from_ticks(3).unwrap(), 0.3);
+        assert_eq!(spec.price_from_ticks(3).unwrap().to_string(), "0.3");
+        assert_eq!(spec.size_string(10_000_001).unwrap(), "0.10000001");
+        assert_eq!(
+            spec.size_from_lots(10_000_001).unwrap().to_string(),
+            "0.10000001"
+        );
+
+        let spec = SymbolSpec::new(2, 3).with_tick_units(5).with_lot_units(250);
+        assert_eq!(spec.price_string(2_000_001).unwrap(), "100000.05");
+        assert_eq!(spec.price_string(20).unwrap(), "1");
+        assert_eq!(spec.size_string(3).unwrap(), "0.75");
+        assert_eq!(spec.lots_from_size(0.75).unwrap(), 3);
+        assert!(spec.lots_from_size(0.8).is_err());
+    }
+
+    #[test]
+    fn test_signed_action_carries_exact_decimals() {
+        use crate::{Keypair, Order, Signer, TimeInForce};
+
+        let spec = SymbolSpec::new(1, 2);
+        let order = Order::from_ticks("BTC-USD", true, 3, 7, &spec, TimeInForce::Gtc).unwrap();
+        let signed = Signer::new(Keypair::generate())
+            .sign(order.into(), Some(1))
+            .unwrap();
+        let json = serde_json::to_string(&signed.actions_json().unwrap()).unwrap();
+        assert!(json.contains(r#""px":0.3,"#), "{json}");
+        assert!(json.contains(r#""sz":0.07,"#), "{json}");
+
+        assert!(Order::from_ticks("BTC-USD", true, 0, 7, &spec, TimeInForce::Gtc).is_err());
+    }
+
+    #[test]
+    fn test_invalid_steps() {
+        let spec = SymbolSpec::new(2, 2);
+        assert!(spec.price_from_ticks(0).is_err());
+        assert!(spec.size_from_lots(-1).is_err());
+        assert!(SymbolSpec::new(19, 2).price_from_ticks(1).is_err());
+        assert!(SymbolSpec::new(2, 2)
+            .with_lot_units(0)
+            .size_from_lots(1)
+            .is_err());
+        // 19 significant digits cannot survive a trip through f64.
+        assert!(SymbolSpec::new(18, 0)
+            .price_from_ticks(1_234_567_890_123_456_789)
+            .is_err());
+    }
+}
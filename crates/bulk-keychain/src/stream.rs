@@ -0,0 +1,424 @@
+//! Signing newline-delimited order intents as they arrive
+//!
+//! [`IntentStream`] reads one [`OrderSpec`] JSON object per line from any
+//! [`BufRead`] and yields an [`OrderItem`] per line, or a [`BatchItemError`]
+//! naming the line and why it was refused; a bad line does not end the
+//! stream. Only one line is buffered at a time, and lines longer than
+//! [`MAX_INTENT_LINE_LEN`] are skipped without being held in memory.
+//!
+//! [`Signer::sign_stream`] signs each item as soon as it is read:
+//!
+//! ```rust
+//! use bulk_keychain::{IntentStream, Keypair, Signer};
+//!
+//! let feed = r#"{"type":"order","symbol":"BTC-USD","isBuy":true,"price":100000.0,"size":0.1,"orderType":{"type":"limit","tif":"GTC"}}
+//! {"type":"order","symbol":"BTC-USD","isBuy":true}
+//! {"type":"cancelAll","symbols":["BTC-USD"]}
+//! "#;
+//!
+//! let signer = Signer::new(Keypair::generate());
+//! let (mut signed, mut failed) = (Vec::new(), Vec::new());
+//! signer
+//!     .sign_stream(IntentStream::new(feed.as_bytes()), Some(1), |result| match result {
+//!         Ok(tx) => signed.push(tx.nonce),
+//!         Err(e) => failed.push(e.to_string()),
+//!     })
+//!     .unwrap();
+//! assert_eq!(signed, [1, 2]);
+//! assert_eq!(failed, ["line 2: invalid order: order.price is required"]);
+//! ```
+
+use crate::input::{InputMode, OrderSpec};
+use crate::{Error, OrderItem, Result, SignedTransaction, Signer};
+use std::io::{BufRead, Read};
+
+/// Longest line [`IntentStream`] reads unless
+/// [`IntentStream::with_max_line_len`] says otherwise.
+pub const MAX_INTENT_LINE_LEN: usize = 64 * 1024;
+
+/// A line of an intent stream that was not signed.
+#[derive(Debug)]
+pub struct BatchItemError {
+    /// 1-based line number in the stream
+    pub line: u64,
+    pub error: Error,
+}
+
+impl std::fmt::Display for BatchItemError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "line {}: {}", self.line, self.error)
+    }
+}
+
+impl std::error::Error for BatchItemError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&self.error)
+    }
+}
+
+/// Iterates the order intents of a JSONL stream, one [`OrderItem`] per
+/// non-blank line.
+///
+/// A line that is not a valid intent yields a [`BatchItemError`] and the
+/// next call moves on to the following line. A read error yields an
+/// [`Error::IntentStream`] and then ends the stream. `onFill` is refused:
+/// its item must be signed in a group with its parent.
+#[derive(Debug)]
+pub struct IntentStream<R: BufRead> {
+    reader: R,
+    mode: InputMode,
+    max_line_len: usize,
+    line: u64,
+    buf: Vec<u8>,
+    done: bool,
+}
+
+impl<R: BufRead> IntentStream<R> {
+    /// Read intents from `reader` in [`InputMode::Tolerant`].
+    pub fn new(reader: R) -> Self {
+        Self {
+            reader,
+            mode: InputMode::Tolerant,
+            max_line_len: MAX_INTENT_LINE_LEN,
+            line: 0,
+            buf: Vec::new(),
+            done: false,
+        }
+    }
+
+    /// Read each line in `mode` (see [`OrderSpec::from_json`]).
+    pub fn with_mode(mut self, mode: InputMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    /// Refuse lines longer than `len` bytes, not counting the newline.
+    pub fn with_max_line_len(mut self, len: usize) -> Self {
+        self.max_line_len = len;
+        self
+    }
+
+    /// Number of lines read so far, blank ones included.
+    pub fn line(&self) -> u64 {
+        self.line
+    }
+
+    /// Return the underlying reader.
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+
+    /// Read the next line into `buf`: `None` at the end of the stream,
+    /// `Some(false)` if it was too long and has been skipped.
+    fn read_line(&mut self) -> std::io::Result<Option<bool>> {
+        self.buf.clear();
+        let limit = self.max_line_len as u64 + 1;
+        let n = self
+            .reader
+            .by_ref()
+            .take(limit)
+            .read_until(b'\n', &mut self.buf)?;
+        if n == 0 {
+            return Ok(None);
+        }
+        self.line += 1;
+        if n as u64 == limit && self.buf.last() != Some(&b'\n') {
+            self.buf.clear();
+            skip_line(&mut self.reader)?;
+            return Ok(Some(false));
+        }
+        Ok(Some(true))
+    }
+
+    fn parse(&self) -> Result<OrderItem> {
+        let value = serde_json::from_slice(&self.buf)?;
+        let spec = OrderSpec::from_json(value, self.mode)?.spec;
+        if spec.on_fill.is_some() {
+            return Err(Error::InvalidOrderField {
+                field: "order.onFill".into(),
+                reason: "is not supported in a stream; sign it in a group with its parent".into(),
+            });
+        }
+        OrderItem::try_from(spec)
+    }
+}
+
+/// Consume the rest of the current line without buffering it.
+fn skip_line<R: BufRead>(reader: &mut R) -> std::io::Result<()> {
+    loop {
+        let available = reader.fill_buf()?;
+        if available.is_empty() {
+            return Ok(());
+        }
+        match available.iter().position(|&b| b == b'\n') {
+            Some(i) => {
+                reader.consume(i + 1);
+                return Ok(());
+            }
+            None => {
+                let n = available.len();
+                reader.consume(n);
+            }
+        }
+    }
+}
+
+impl<R: BufRead> IntentStream<R> {
+    /// The next non-blank line's number and item.
+    fn next_item(&mut self) -> Option<(u64, Result<OrderItem>)> {
+        while !self.done {
+            let item = match self.read_line() {
+                Ok(None) => break,
+                Ok(Some(true)) if self.buf.trim_ascii().is_empty() => continue,
+                Ok(Some(true)) => self.parse(),
+                Ok(Some(false)) => Err(Error::IntentStream(format!(
+                    "line is longer than {} bytes",
+                    self.max_line_len
+                ))),
+                Err(e) => {
+                    self.done = true;
+                    Err(Error::IntentStream(e.to_string()))
+                }
+            };
+            return Some((self.line, item));
+        }
+        self.done = true;
+        None
+    }
+}
+
+impl<R: BufRead> Iterator for IntentStream<R> {
+    type Item = std::result::Result<OrderItem, BatchItemError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (line, item) = self.next_item()?;
+        Some(item.map_err(|error| BatchItemError { line, error }))
+    }
+}
+
+impl Signer {
+    /// Sign each intent of `intents` as it is read, passing the transaction
+    /// or the line's error to `sink` in stream order.
+    ///
+    /// Items are checked like [`Signer::sign_all_partial`]: pre-sign hook,
+    /// open-order caps and client-ID uniqueness apply, and a refused item
+    /// does not stop the stream. The `k`-th valid intent gets nonce
+    /// `base + k`, refused or not; invalid lines take no nonce.
+    /// Fails up front only if no base nonce can be chosen.
+    pub fn sign_stream<R, F>(
+        &self,
+        mut intents: IntentStream<R>,
+        base_nonce: Option<u64>,
+        mut sink: F,
+    ) -> Result<()>
+    where
+        R: BufRead,
+        F: FnMut(std::result::Result<SignedTransaction, BatchItemError>),
+    {
+        let base = self.base_nonce_or_now(base_nonce, 1)?;
+        let mut parsed = 0;
+        while let Some((line, item)) = intents.next_item() {
+            let result = item.and_then(|item| {
+                parsed += 1;
+                crate::nonce::check_nonce_range(base, parsed)?;
+                self.sign_single_item(item, base + (parsed - 1) as u64, false)
+            });
+            sink(result.map_err(|error| BatchItemError { line, error }));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Action, Keypair};
+    use std::cell::Cell;
+    use std::io::{BufReader, Read};
+    use std::rc::Rc;
+
+    fn order_line(price: f64) -> String {
+        format!(
+            r#"{{"type":"order","symbol":"BTC-USD","isBuy":true,"price":{price},"size":0.1,"orderType":{{"type":"limit","tif":"GTC"}}}}"#
+        )
+    }
+
+    fn run(
+        signer: &Signer,
+        stream: IntentStream<impl BufRead>,
+    ) -> Vec<std::result::Result<SignedTransaction, BatchItemError>> {
+        let mut results = Vec::new();
+        signer
+            .sign_stream(stream, Some(100), |r| results.push(r))
+            .unwrap();
+        results
+    }
+
+    #[test]
+    fn test_interleaved_good_and_bad_lines() {
+        let feed = [
+            order_line(100.0),
+            "{not json".to_string(),
+            String::new(),
+            r#"{"type":"order","symbol":"BTC-USD","isBuy":true,"size":0.1}"#.to_string(),
+            order_line(101.0),
+            r#"{"type":"teleport"}"#.to_string(),
+            r#"{"type":"cancel","symbol":"BTC-USD","orderId":"0OIl"}"#.to_string(),
+            r#"{"type":"cancelAll","symbols":["ETH-USD"]}"#.to_string(),
+        ]
+        .join("\n");
+        let signer = Signer::new(Keypair::generate());
+        let results = run(&signer, IntentStream::new(feed.as_bytes()));
+
+        let errors: Vec<(u64, String)> = results
+            .iter()
+            .filter_map(|r| r.as_ref().err())
+            .map(|e| (e.line, e.error.to_string()))
+            .collect();
+        assert_eq!(errors.len(), 4);
+        assert_eq!(errors[0].0, 2);
+        assert!(errors[0].1.contains("line 1 column 2"), "{}", errors[0].1);
+        assert_eq!(
+            errors[1],
+            (4, "invalid order: order.price is required".into())
+        );
+        assert_eq!(errors[2].0, 6);
+        assert!(errors[2].1.contains("teleport"), "{}", errors[2].1);
+        assert_eq!(errors[3].0, 7);
+        assert!(errors[3]
+            .1
+            .starts_with("invalid order: cancel.orderId is malformed"));
+
+        // Nonces count the valid intents; every signature verifies.
+        let signed: Vec<&SignedTransaction> =
+            results.iter().filter_map(|r| r.as_ref().ok()).collect();
+        let nonces: Vec<u64> = signed.iter().map(|tx| tx.nonce).collect();
+        assert_eq!(nonces, [100, 101, 102]);
+        assert!(signed.iter().all(|tx| tx.verify().unwrap()));
+        assert!(matches!(
+            &signed[2].action,
+            Action::Order { orders } if matches!(orders[..], [OrderItem::CancelAll(_)])
+        ));
+    }
+
+    #[test]
+    fn test_policy_refusals_do_not_stop_the_stream() {
+        let feed = [order_line(100.0), order_line(101.0), order_line(102.0)].join("\n");
+        let signer = Signer::new(Keypair::generate()).with_max_open_orders_per_symbol(2);
+        let results = run(&signer, IntentStream::new(feed.as_bytes()));
+        assert!(results[0].is_ok() && results[1].is_ok());
+        let refused = results[2].as_ref().unwrap_err();
+        assert_eq!(refused.line, 3);
+        assert!(matches!(refused.error, Error::OpenOrderCapExceeded { .. }));
+        assert_eq!(signer.open_order_count("BTC-USD"), 2);
+    }
+
+    #[test]
+    fn test_strict_mode_and_on_fill() {
+        let typo = r#"{"type":"cancelAll","symbols":[],"reduceOnIy":true}"#;
+        let on_fill = order_line(100.0).replace(
+            r#""size":0.1"#,
+            r#""size":0.1,"onFill":{"p":0,"actions":[]}"#,
+        );
+        let feed = format!("{typo}\n{on_fill}\n");
+
+        let items: Vec<_> = IntentStream::new(feed.as_bytes()).collect();
+        assert!(items[0].is_ok());
+        assert_eq!(
+            items[1].as_ref().unwrap_err().to_string(),
+            "line 2: invalid order: order.onFill is not supported in a stream; \
+             sign it in a group with its parent"
+        );
+
+        let first = IntentStream::new(feed.as_bytes())
+            .with_mode(InputMode::Strict)
+            .next()
+            .unwrap()
+            .unwrap_err();
+        assert!(matches!(first.error, Error::UnknownFields(_)));
+    }
+
+    #[test]
+    fn test_nonce_overflow_is_per_line() {
+        let feed = [order_line(100.0), order_line(101.0)].join("\n");
+        let signer = Signer::new(Keypair::generate());
+        let mut results = Vec::new();
+        signer
+            .sign_stream(IntentStream::new(feed.as_bytes()), Some(u64::MAX), |r| {
+                results.push(r)
+            })
+            .unwrap();
+        assert_eq!(results[0].as_ref().unwrap().nonce, u64::MAX);
+        assert!(matches!(
+            results[1].as_ref().unwrap_err().error,
+            Error::NonceOverflow {
+                base: u64::MAX,
+                count: 2
+            }
+        ));
+    }
+
+    /// Produces `lines` intents on demand, counting the bytes handed out.
+    struct Feed {
+        lines: usize,
+        pending: Vec<u8>,
+        read: Rc<Cell<usize>>,
+    }
+
+    impl Read for Feed {
+        fn read(&mut self, out: &mut [u8]) -> std::io::Result<usize> {
+            if self.pending.is_empty() && self.lines > 0 {
+                self.lines -= 1;
+                let line = if self.lines == 500 {
+                    // One oversized line in the middle of the feed.
+                    format!("{}\n", "x".repeat(4 * MAX_INTENT_LINE_LEN))
+                } else {
+                    order_line(100.0 + self.lines as f64) + "\n"
+                };
+                self.pending = line.into_bytes();
+            }
+            let n = out.len().min(self.pending.len());
+            out[..n].copy_from_slice(&self.pending[..n]);
+            self.pending.drain(..n);
+            self.read.set(self.read.get() + n);
+            Ok(n)
+        }
+    }
+
+    #[test]
+    fn test_signs_as_lines_arrive_in_bounded_memory() {
+        let read = Rc::new(Cell::new(0));
+        let feed = Feed {
+            lines: 1_000,
+            pending: Vec::new(),
+            read: read.clone(),
+        };
+        let stream = IntentStream::new(BufReader::with_capacity(1024, feed));
+        let signer = Signer::new(Keypair::generate());
+        let (mut signed, mut last_read) = (0, 0);
+        let mut errors = Vec::new();
+        signer
+            .sign_stream(stream, Some(1), |result| {
+                // Each result is delivered before much more of the feed is read.
+                assert!(read.get() - last_read <= 4 * MAX_INTENT_LINE_LEN + 2048);
+                last_read = read.get();
+                match result {
+                    Ok(_) => signed += 1,
+                    Err(e) => errors.push(e),
+                }
+            })
+            .unwrap();
+        assert_eq!(signed, 999);
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].line, 500);
+        assert!(matches!(errors[0].error, Error::IntentStream(_)));
+
+        let lines = format!("{}\n{}\n", "y".repeat(10_000), order_line(1.0));
+        let mut stream = IntentStream::new(lines.as_bytes()).with_max_line_len(1_000);
+        assert!(stream.next().unwrap().is_err());
+        assert!(stream.buf.capacity() <= 2_048);
+        assert!(stream.next().unwrap().is_ok());
+        assert!(stream.next().is_none());
+        assert_eq!(stream.line(), 2);
+    }
+}
@@ -0,0 +1,73 @@
+//! Market symbol helpers
+//!
+//! BULK symbols are `BASE-QUOTE`, e.g. `BTC-USD`.
+
+use crate::{Error, Result};
+
+/// Split `pair` into its base and quote assets.
+pub fn split(pair: &str) -> Result<(&str, &str)> {
+    match pair.split_once('-') {
+        Some((base, quote)) if is_asset(base) && is_asset(quote) && !quote.contains('-') => {
+            Ok((base, quote))
+        }
+        _ => Err(Error::InvalidSymbol(format!(
+            "expected BASE-QUOTE, got {:?}",
+            pair
+        ))),
+    }
+}
+
+/// Base asset of `pair` (`"BTC"` for `"BTC-USD"`).
+pub fn base_asset(pair: &str) -> Result<&str> {
+    split(pair).map(|(base, _)| base)
+}
+
+/// Quote asset of `pair` (`"USD"` for `"BTC-USD"`).
+pub fn quote_asset(pair: &str) -> Result<&str> {
+    split(pair).map(|(_, quote)| quote)
+}
+
+/// Canonical symbol for `base` and `quote`, uppercased (`"BTC-USD"`).
+pub fn canonical(base: &str, quote: &str) -> String {
+    format!(
+        "{}-{}",
+        base.trim().to_ascii_uppercase(),
+        quote.trim().to_ascii_uppercase()
+    )
+}
+
+fn is_asset(s: &str) -> bool {
+    !s.is_empty() && s.chars().all(|c| c.is_ascii_alphanumeric())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_standard_pairs() {
+        assert_eq!(base_asset("BTC-USD").unwrap(), "BTC");
+        assert_eq!(quote_asset("BTC-USD").unwrap(), "USD");
+        assert_eq!(split("1000PEPE-USDC").unwrap(), ("1000PEPE", "USDC"));
+        assert_eq!(canonical("btc", " usd"), "BTC-USD");
+        assert_eq!(split(&canonical("eth", "usd")).unwrap(), ("ETH", "USD"));
+    }
+
+    #[test]
+    fn test_malformed_pairs() {
+        for bad in [
+            "",
+            "BTC",
+            "BTC-",
+            "-USD",
+            "BTC-USD-PERP",
+            "BTC USD",
+            "BTC-U$D",
+        ] {
+            assert!(
+                matches!(split(bad), Err(Error::InvalidSymbol(_))),
+                "{bad:?} should be rejected"
+            );
+        }
+    }
+}
@@ -0,0 +1,284 @@
+//! Millisecond timestamps and durations
+//!
+//! [`TimestampMs`] and [`DurationMs`] carry the unit in the type, so a
+//! deadline, expiry or timeout given in seconds cannot be mistaken for
+//! milliseconds. Both convert from `u64` milliseconds for existing callers
+//! and from [`SystemTime`] / [`Duration`]; arithmetic saturates instead of
+//! overflowing, and both serialize as plain integers.
+//!
+//! ```rust
+//! use bulk_keychain::{DurationMs, TimestampMs};
+//! use std::time::{Duration, UNIX_EPOCH};
+//!
+//! let at = TimestampMs::from(UNIX_EPOCH + Duration::from_secs(1_700_000_000));
+//! assert_eq!(at.as_millis(), 1_700_000_000_000);
+//! assert_eq!(at + DurationMs::from_secs(5), TimestampMs(1_700_000_005_000));
+//! assert_eq!(TimestampMs(u64::MAX) + DurationMs(1), TimestampMs(u64::MAX));
+//!
+//! // Seconds where milliseconds belong are refused.
+//! assert!(TimestampMs::from_millis_checked(1_700_000_000).is_err());
+//! ```
+
+use crate::{Error, Result};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::ops::{Add, Sub};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Timestamps below this many ms (early March 1973) are taken to be seconds
+/// by [`TimestampMs::looks_like_seconds`]; seconds since the epoch stay
+/// below it until the year 5138.
+pub const SECONDS_HEURISTIC_LIMIT_MS: u64 = 100_000_000_000;
+
+/// Milliseconds since the Unix epoch.
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize,
+)]
+#[serde(transparent)]
+pub struct TimestampMs(pub u64);
+
+/// A span of milliseconds.
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize,
+)]
+#[serde(transparent)]
+pub struct DurationMs(pub u64);
+
+impl TimestampMs {
+    /// The current time on the system clock.
+    pub fn now() -> Self {
+        SystemTime::now().into()
+    }
+
+    /// `ms`, refused with [`Error::TimestampLooksLikeSeconds`] if it
+    /// [looks like seconds](TimestampMs::looks_like_seconds).
+    pub fn from_millis_checked(ms: u64) -> Result<Self> {
+        let ts = Self(ms);
+        if ts.looks_like_seconds() {
+            return Err(Error::TimestampLooksLikeSeconds(ms));
+        }
+        Ok(ts)
+    }
+
+    /// Whether this is more plausibly a count of seconds (or not a real
+    /// time at all) than milliseconds since the epoch: anything before
+    /// [`SECONDS_HEURISTIC_LIMIT_MS`]. A heuristic for validating
+    /// expiries and deadlines, not for nonces, which may be counters.
+    pub fn looks_like_seconds(self) -> bool {
+        self.0 < SECONDS_HEURISTIC_LIMIT_MS
+    }
+
+    pub fn as_millis(self) -> u64 {
+        self.0
+    }
+
+    pub fn to_system_time(self) -> SystemTime {
+        UNIX_EPOCH + Duration::from_millis(self.0)
+    }
+
+    pub fn saturating_add(self, duration: DurationMs) -> Self {
+        Self(self.0.saturating_add(duration.0))
+    }
+
+    pub fn saturating_sub(self, duration: DurationMs) -> Self {
+        Self(self.0.saturating_sub(duration.0))
+    }
+
+    /// Time from `earlier` to `self`; zero if `earlier` is later.
+    pub fn saturating_duration_since(self, earlier: TimestampMs) -> DurationMs {
+        DurationMs(self.0.saturating_sub(earlier.0))
+    }
+}
+
+impl DurationMs {
+    pub fn from_secs(secs: u64) -> Self {
+        Self(secs.saturating_mul(1000))
+    }
+
+    pub fn as_millis(self) -> u64 {
+        self.0
+    }
+
+    pub fn to_duration(self) -> Duration {
+        Duration::from_millis(self.0)
+    }
+
+    pub fn saturating_add(self, other: DurationMs) -> Self {
+        Self(self.0.saturating_add(other.0))
+    }
+
+    pub fn saturating_sub(self, other: DurationMs) -> Self {
+        Self(self.0.saturating_sub(other.0))
+    }
+}
+
+impl From<u64> for TimestampMs {
+    fn from(ms: u64) -> Self {
+        Self(ms)
+    }
+}
+
+impl From<TimestampMs> for u64 {
+    fn from(ts: TimestampMs) -> Self {
+        ts.0
+    }
+}
+
+/// Times before the epoch clamp to 0, and past `u64::MAX` ms to the maximum.
+impl From<SystemTime> for TimestampMs {
+    fn from(time: SystemTime) -> Self {
+        let since_epoch = time.duration_since(UNIX_EPOCH).unwrap_or_default();
+        Self(DurationMs::from(since_epoch).0)
+    }
+}
+
+impl From<u64> for DurationMs {
+    fn from(ms: u64) -> Self {
+        Self(ms)
+    }
+}
+
+impl From<DurationMs> for u64 {
+    fn from(duration: DurationMs) -> Self {
+        duration.0
+    }
+}
+
+/// Sub-millisecond parts are truncated; spans past `u64::MAX` ms saturate.
+impl From<Duration> for DurationMs {
+    fn from(duration: Duration) -> Self {
+        Self(u64::try_from(duration.as_millis()).unwrap_or(u64::MAX))
+    }
+}
+
+impl From<DurationMs> for Duration {
+    fn from(duration: DurationMs) -> Self {
+        duration.to_duration()
+    }
+}
+
+impl Add<DurationMs> for TimestampMs {
+    type Output = TimestampMs;
+
+    fn add(self, rhs: DurationMs) -> TimestampMs {
+        self.saturating_add(rhs)
+    }
+}
+
+impl Sub<DurationMs> for TimestampMs {
+    type Output = TimestampMs;
+
+    fn sub(self, rhs: DurationMs) -> TimestampMs {
+        self.saturating_sub(rhs)
+    }
+}
+
+impl Sub for TimestampMs {
+    type Output = DurationMs;
+
+    fn sub(self, rhs: TimestampMs) -> DurationMs {
+        self.saturating_duration_since(rhs)
+    }
+}
+
+impl Add for DurationMs {
+    type Output = DurationMs;
+
+    fn add(self, rhs: DurationMs) -> DurationMs {
+        self.saturating_add(rhs)
+    }
+}
+
+impl Sub for DurationMs {
+    type Output = DurationMs;
+
+    fn sub(self, rhs: DurationMs) -> DurationMs {
+        self.saturating_sub(rhs)
+    }
+}
+
+impl fmt::Display for TimestampMs {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl fmt::Display for DurationMs {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}ms", self.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_conversions() {
+        let at = UNIX_EPOCH + Duration::from_millis(1_700_000_000_123);
+        assert_eq!(TimestampMs::from(at), TimestampMs(1_700_000_000_123));
+        assert_eq!(TimestampMs(1_700_000_000_123).to_system_time(), at);
+        assert_eq!(
+            TimestampMs::from(UNIX_EPOCH - Duration::from_secs(1)),
+            TimestampMs(0)
+        );
+
+        assert_eq!(
+            DurationMs::from(Duration::from_micros(2_500)),
+            DurationMs(2)
+        );
+        assert_eq!(DurationMs::from(Duration::MAX), DurationMs(u64::MAX));
+        assert_eq!(DurationMs::from_secs(u64::MAX), DurationMs(u64::MAX));
+        assert_eq!(
+            Duration::from(DurationMs(1_500)),
+            Duration::from_millis(1_500)
+        );
+        assert_eq!(u64::from(TimestampMs::from(42)), 42);
+    }
+
+    #[test]
+    fn test_saturating_arithmetic() {
+        let t = TimestampMs(1_000);
+        assert_eq!(t + DurationMs(500), TimestampMs(1_500));
+        assert_eq!(t - DurationMs(5_000), TimestampMs(0));
+        assert_eq!(TimestampMs(u64::MAX) + DurationMs(1), TimestampMs(u64::MAX));
+        assert_eq!(t - TimestampMs(400), DurationMs(600));
+        assert_eq!(TimestampMs(400) - t, DurationMs(0));
+        assert_eq!(DurationMs(u64::MAX) + DurationMs(1), DurationMs(u64::MAX));
+        assert_eq!(DurationMs(1) - DurationMs(2), DurationMs(0));
+    }
+
+    #[test]
+    fn test_seconds_heuristic() {
+        // Seconds since the epoch, today and far out.
+        assert!(TimestampMs(1_700_000_000).looks_like_seconds());
+        assert!(TimestampMs(32_503_680_000).looks_like_seconds()); // year 3000 in seconds
+        assert!(TimestampMs(0).looks_like_seconds());
+        // Milliseconds since the epoch.
+        assert!(!TimestampMs(1_700_000_000_000).looks_like_seconds());
+        assert!(!TimestampMs(SECONDS_HEURISTIC_LIMIT_MS).looks_like_seconds());
+        assert!(!TimestampMs::now().looks_like_seconds());
+
+        assert!(matches!(
+            TimestampMs::from_millis_checked(1_700_000_000),
+            Err(Error::TimestampLooksLikeSeconds(1_700_000_000))
+        ));
+        assert_eq!(
+            TimestampMs::from_millis_checked(1_700_000_000_000).unwrap(),
+            TimestampMs(1_700_000_000_000)
+        );
+    }
+
+    #[test]
+    fn test_serde_as_integers() {
+        assert_eq!(
+            serde_json::to_string(&TimestampMs(1_700_000_000_000)).unwrap(),
+            "1700000000000"
+        );
+        assert_eq!(serde_json::to_string(&DurationMs(30_000)).unwrap(), "30000");
+        let back: TimestampMs = serde_json::from_str("1700000000000").unwrap();
+        assert_eq!(back, TimestampMs(1_700_000_000_000));
+        let back: DurationMs = serde_json::from_str("30000").unwrap();
+        assert_eq!(back, DurationMs(30_000));
+    }
+}
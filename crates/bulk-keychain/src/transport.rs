@@ -0,0 +1,378 @@
+//! Pluggable submission transport
+//!
+//! The keychain never talks to the network itself. [`Transport`] is the seam
+//! where a caller plugs in its HTTP client; [`Submitter`], [`sign_and_submit`]
+//! and [`Reconciler`] are generic over it, and [`MockTransport`] stands in for
+//! the exchange in tests.
+//!
+//! Wiring it to `reqwest` (blocking) looks like:
+//!
+//! ```ignore
+//! struct Http(reqwest::blocking::Client);
+//!
+//! impl Transport for Http {
+//!     fn submit(&self, body: &str) -> bulk_keychain::Result<TransportResponse> {
+//!         let resp = self
+//!             .0
+//!             .post("https://exchange.bulk.trade/api/v1/order")
+//!             .header("content-type", "application/json")
+//!             .body(body.to_owned())
+//!             .send()
+//!             .map_err(|e| Error::Transport(e.to_string()))?;
+//!         let status = resp.status().as_u16();
+//!         let body = resp.text().map_err(|e| Error::Transport(e.to_string()))?;
+//!         Ok(TransportResponse::new(status, body))
+//!     }
+//! }
+//! ```
+
+use crate::{Error, OrderItem, Result, SignedTransaction, Signer};
+use std::collections::VecDeque;
+use std::sync::Mutex;
+
+/// Status and body returned by a transport for one request.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TransportResponse {
+    /// HTTP-style status code
+    pub status: u16,
+    /// Raw response body
+    pub body: String,
+}
+
+impl TransportResponse {
+    pub fn new(status: u16, body: impl Into<String>) -> Self {
+        Self {
+            status,
+            body: body.into(),
+        }
+    }
+
+    /// `200` response with `body`.
+    pub fn ok(body: impl Into<String>) -> Self {
+        Self::new(200, body)
+    }
+
+    /// Whether the status is `2xx`.
+    pub fn is_success(&self) -> bool {
+        (200..300).contains(&self.status)
+    }
+}
+
+/// Delivers signed transaction JSON to the exchange.
+///
+/// `Err` means the outcome is unknown (network failure); a response with a
+/// non-`2xx` status is a definite rejection.
+pub trait Transport {
+    /// Submit one JSON request body.
+    fn submit(&self, body: &str) -> Result<TransportResponse>;
+
+    /// Submit several bodies, one response per body in order.
+    ///
+    /// Defaults to calling [`Transport::submit`] for each body.
+    fn submit_batch(&self, bodies: &[String]) -> Result<Vec<TransportResponse>> {
+        bodies.iter().map(|body| self.submit(body)).collect()
+    }
+}
+
+impl<T: Transport + ?Sized> Transport for &T {
+    fn submit(&self, body: &str) -> Result<TransportResponse> {
+        (**self).submit(body)
+    }
+
+    fn submit_batch(&self, bodies: &[String]) -> Result<Vec<TransportResponse>> {
+        (**self).submit_batch(bodies)
+    }
+}
+
+impl<T: Transport + ?Sized> Transport for std::sync::Arc<T> {
+    fn submit(&self, body: &str) -> Result<TransportResponse> {
+        (**self).submit(body)
+    }
+
+    fn submit_batch(&self, bodies: &[String]) -> Result<Vec<TransportResponse>> {
+        (**self).submit_batch(bodies)
+    }
+}
+
+/// Serializes signed transactions and hands them to a [`Transport`].
+#[derive(Debug)]
+pub struct Submitter<T> {
+    transport: T,
+}
+
+impl<T: Transport> Submitter<T> {
+    pub fn new(transport: T) -> Self {
+        Self { transport }
+    }
+
+    pub fn transport(&self) -> &T {
+        &self.transport
+    }
+
+    /// Submit one transaction.
+    pub fn submit(&self, tx: &SignedTransaction) -> Result<TransportResponse> {
+        self.transport.submit(&tx.to_json()?)
+    }
+
+    /// Submit several transactions through [`Transport::submit_batch`].
+    pub fn submit_all(&self, txs: &[SignedTransaction]) -> Result<Vec<TransportResponse>> {
+        let bodies = txs
+            .iter()
+            .map(SignedTransaction::to_json)
+            .collect::<Result<Vec<_>>>()?;
+        let responses = self.transport.submit_batch(&bodies)?;
+        if responses.len() != txs.len() {
+            return Err(Error::Transport(format!(
+                "expected {} responses, got {}",
+                txs.len(),
+                responses.len()
+            )));
+        }
+        Ok(responses)
+    }
+}
+
+/// Sign `item` and submit it in one step.
+pub fn sign_and_submit<T: Transport>(
+    signer: &mut Signer,
+    item: OrderItem,
+    nonce: Option<u64>,
+    transport: &T,
+) -> Result<(SignedTransaction, TransportResponse)> {
+    let signed = signer.sign(item, nonce)?;
+    let response = transport.submit(&signed.to_json()?)?;
+    Ok((signed, response))
+}
+
+/// Where a submitted transaction stands.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SubmitStatus {
+    /// Not yet submitted, or the last attempt failed in transit
+    Pending,
+    /// Accepted with a `2xx` response
+    Acked,
+    /// Rejected; carries the response body
+    Rejected(String),
+}
+
+impl SubmitStatus {
+    fn from_response(response: &TransportResponse) -> Self {
+        if response.is_success() {
+            Self::Acked
+        } else {
+            Self::Rejected(response.body.clone())
+        }
+    }
+}
+
+/// Tracks submitted transactions until each is acked or rejected.
+///
+/// Transactions whose submission failed in transit stay
+/// [`SubmitStatus::Pending`] and are resent by [`Reconciler::retry_pending`]
+/// byte-for-byte, under the same nonce and signature.
+#[derive(Debug)]
+pub struct Reconciler<T> {
+    submitter: Submitter<T>,
+    entries: Vec<(SignedTransaction, SubmitStatus)>,
+}
+
+impl<T: Transport> Reconciler<T> {
+    pub fn new(transport: T) -> Self {
+        Self {
+            submitter: Submitter::new(transport),
+            entries: Vec::new(),
+        }
+    }
+
+    pub fn transport(&self) -> &T {
+        self.submitter.transport()
+    }
+
+    /// Track and submit one transaction.
+    ///
+    /// A transport error leaves it pending and is returned.
+    pub fn submit(&mut self, tx: SignedTransaction) -> Result<SubmitStatus> {
+        let result = self.submitter.submit(&tx);
+        let status = match &result {
+            Ok(response) => SubmitStatus::from_response(response),
+            Err(_) => SubmitStatus::Pending,
+        };
+        self.entries.push((tx, status.clone()));
+        result.map(|_| status)
+    }
+
+    /// Track and submit several transactions as one batch.
+    ///
+    /// A transport error leaves all of them pending and is returned.
+    pub fn submit_all(&mut self, txs: Vec<SignedTransaction>) -> Result<Vec<SubmitStatus>> {
+        let result = self.submitter.submit_all(&txs);
+        let statuses = match &result {
+            Ok(responses) => responses.iter().map(SubmitStatus::from_response).collect(),
+            Err(_) => vec![SubmitStatus::Pending; txs.len()],
+        };
+        self.entries
+            .extend(txs.into_iter().zip(statuses.iter().cloned()));
+        result.map(|_| statuses)
+    }
+
+    /// Resend every pending transaction; returns how many were resolved.
+    ///
+    /// Stops at the first transport error, leaving the rest pending.
+    pub fn retry_pending(&mut self) -> Result<usize> {
+        let mut resolved = 0;
+        for (tx, status) in &mut self.entries {
+            if *status != SubmitStatus::Pending {
+                continue;
+            }
+            let response = self.submitter.submit(tx)?;
+            *status = SubmitStatus::from_response(&response);
+            resolved += 1;
+        }
+        Ok(resolved)
+    }
+
+    /// Status of the transaction with `signature`, if tracked.
+    pub fn status(&self, signature: &str) -> Option<&SubmitStatus> {
+        self.entries
+            .iter()
+            .find(|(tx, _)| tx.signature == signature)
+            .map(|(_, status)| status)
+    }
+
+    /// All tracked transactions with their status, in submission order.
+    pub fn entries(&self) -> &[(SignedTransaction, SubmitStatus)] {
+        &self.entries
+    }
+
+    pub fn pending(&self) -> impl Iterator<Item = &SignedTransaction> {
+        self.with_status(|s| *s == SubmitStatus::Pending)
+    }
+
+    pub fn acked(&self) -> impl Iterator<Item = &SignedTransaction> {
+        self.with_status(|s| *s == SubmitStatus::Acked)
+    }
+
+    pub fn rejected(&self) -> impl Iterator<Item = (&SignedTransaction, &str)> {
+        self.entries.iter().filter_map(|(tx, status)| match status {
+            SubmitStatus::Rejected(reason) => Some((tx, reason.as_str())),
+            _ => None,
+        })
+    }
+
+    fn with_status(
+        &self,
+        pred: impl Fn(&SubmitStatus) -> bool,
+    ) -> impl Iterator<Item = &SignedTransaction> {
+        self.entries
+            .iter()
+            .filter(move |(_, status)| pred(status))
+            .map(|(tx, _)| tx)
+    }
+}
+
+/// In-memory [`Transport`] that records request bodies and replays
+/// scripted responses in order.
+///
+/// Once the script runs out every request gets `200` with an empty body.
+#[derive(Debug, Default)]
+pub struct MockTransport {
+    script: Mutex<VecDeque<std::result::Result<TransportResponse, String>>>,
+    requests: Mutex<Vec<String>>,
+}
+
+impl MockTransport {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Queue a response for the next request.
+    pub fn push_response(&self, response: TransportResponse) -> &Self {
+        self.script.lock().unwrap().push_back(Ok(response));
+        self
+    }
+
+    /// Queue a transport failure for the next request.
+    pub fn push_error(&self, message: impl Into<String>) -> &Self {
+        self.script.lock().unwrap().push_back(Err(message.into()));
+        self
+    }
+
+    /// Every request body received so far.
+    pub fn requests(&self) -> Vec<String> {
+        self.requests.lock().unwrap().clone()
+    }
+}
+
+impl Transport for MockTransport {
+    fn submit(&self, body: &str) -> Result<TransportResponse> {
+        self.requests.lock().unwrap().push(body.to_owned());
+        match self.script.lock().unwrap().pop_front() {
+            Some(Ok(response)) => Ok(response),
+            Some(Err(message)) => Err(Error::Transport(message)),
+            None => Ok(TransportResponse::ok("")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Keypair, Order, TimeInForce};
+
+    fn order(px: f64) -> OrderItem {
+        Order::limit("BTC-USD", true, px, 0.1, TimeInForce::Gtc).into()
+    }
+
+    #[test]
+    fn test_sign_and_submit_records_body() {
+        let mut signer = Signer::new(Keypair::generate());
+        let transport = MockTransport::new();
+        transport.push_response(TransportResponse::ok(r#"{"status":"ok"}"#));
+
+        let (signed, response) =
+            sign_and_submit(&mut signer, order(1.0), Some(1), &transport).unwrap();
+        assert!(response.is_success());
+        assert_eq!(transport.requests(), vec![signed.to_json().unwrap()]);
+    }
+
+    #[test]
+    fn test_sign_submit_reconcile_loop() {
+        let mut signer = Signer::new(Keypair::generate());
+        let transport = MockTransport::new();
+        transport
+            .push_response(TransportResponse::ok("{}"))
+            .push_response(TransportResponse::new(400, "insufficient margin"))
+            .push_error("connection reset")
+            .push_response(TransportResponse::ok("{}"));
+
+        let txs = signer
+            .sign_all(vec![order(1.0), order(2.0)], Some(10))
+            .unwrap();
+        let mut reconciler = Reconciler::new(&transport);
+        let statuses = reconciler.submit_all(txs).unwrap();
+        assert_eq!(
+            statuses,
+            vec![
+                SubmitStatus::Acked,
+                SubmitStatus::Rejected("insufficient margin".into())
+            ]
+        );
+
+        let third = signer.sign(order(3.0), Some(12)).unwrap();
+        let signature = third.signature.clone();
+        assert!(matches!(reconciler.submit(third), Err(Error::Transport(_))));
+        assert_eq!(reconciler.status(&signature), Some(&SubmitStatus::Pending));
+
+        assert_eq!(reconciler.retry_pending().unwrap(), 1);
+        assert_eq!(reconciler.status(&signature), Some(&SubmitStatus::Acked));
+        assert_eq!(reconciler.acked().count(), 2);
+        assert_eq!(reconciler.pending().count(), 0);
+        let rejected: Vec<_> = reconciler.rejected().map(|(_, why)| why).collect();
+        assert_eq!(rejected, vec!["insufficient margin"]);
+
+        // The retry resent the identical signed body.
+        let requests = transport.requests();
+        assert_eq!(requests.len(), 4);
+        assert_eq!(requests[2], requests[3]);
+    }
+}
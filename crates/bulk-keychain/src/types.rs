@@ -2,10 +2,12 @@
 //!
 //! These types match the BULK exchange API specification exactly.
 
+use crate::display::DisplayPolicy;
+use crate::spec::SymbolSpec;
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
 
 /// 32-byte public key (Ed25519)
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, std::hash::Hash)]
 pub struct Pubkey(pub [u8; 32]);
 
 impl Pubkey {
@@ -39,6 +41,13 @@ impl Pubkey {
     pub fn as_bytes(&self) -> &[u8; 32] {
         &self.0
     }
+
+    /// Short identifier for display and logs: `bk1:` + first 8 base58 chars of SHA256(pubkey).
+    pub fn fingerprint(&self) -> String {
+        use sha2::{Digest, Sha256};
+        let digest = bs58::encode(Sha256::digest(self.0)).into_string();
+        format!("bk1:{}", &digest[..8])
+    }
 }
 
 impl std::fmt::Display for Pubkey {
@@ -47,6 +56,14 @@ impl std::fmt::Display for Pubkey {
     }
 }
 
+impl std::str::FromStr for Pubkey {
+    type Err = crate::Error;
+
+    fn from_str(s: &str) -> crate::Result<Self> {
+        Self::from_base58(s)
+    }
+}
+
 impl Serialize for Pubkey {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
@@ -67,7 +84,7 @@ impl<'de> Deserialize<'de> for Pubkey {
 }
 
 /// 32-byte hash (used for order IDs, client IDs)
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, std::hash::Hash)]
 pub struct Hash(pub [u8; 32]);
 
 impl Hash {
@@ -89,6 +106,12 @@ impl Hash {
         Ok(Self(arr))
     }
 
+    /// Wrap a fixed-size array without validation or allocation
+    #[inline]
+    pub const fn from_bytes_fixed(bytes: [u8; 32]) -> Self {
+        Self(bytes)
+    }
+
     /// Encode to base58 string
     pub fn to_base58(&self) -> String {
         bs58::encode(&self.0).into_string()
@@ -99,11 +122,29 @@ impl Hash {
         &self.0
     }
 
+    /// Copy out the raw bytes
+    #[inline]
+    pub const fn to_bytes_fixed(&self) -> [u8; 32] {
+        self.0
+    }
+
+    /// All 32 bytes are zero. As a client ID this is not the same as no
+    /// client ID; signers refuse it unless
+    /// [`Signer::allow_zero_client_id`](crate::Signer::allow_zero_client_id).
+    #[inline]
+    pub fn is_zero(&self) -> bool {
+        self.0 == [0; 32]
+    }
+
     /// Generate a random hash (useful for client order IDs)
     pub fn random() -> Self {
-        use rand::Rng;
+        Self::random_with(&mut rand::thread_rng())
+    }
+
+    /// [`Hash::random`] drawing from `rng`, e.g. a seeded one.
+    pub fn random_with<R: rand::RngCore + ?Sized>(rng: &mut R) -> Self {
         let mut bytes = [0u8; 32];
-        rand::thread_rng().fill(&mut bytes);
+        rng.fill_bytes(&mut bytes);
         Self(bytes)
     }
 
@@ -111,8 +152,15 @@ impl Hash {
     #[inline]
     pub fn from_wincode_bytes(wincode_bytes: &[u8]) -> Self {
         use sha2::{Digest, Sha256};
-        let hash: [u8; 32] = Sha256::digest(wincode_bytes).into();
-        Self(hash)
+        Self::from_bytes_fixed(Sha256::digest(wincode_bytes).into())
+    }
+
+    /// Hash an order ID preimage using the given ID scheme.
+    #[inline]
+    pub fn from_wincode_bytes_v(scheme: crate::order_id::IdScheme, wincode_bytes: &[u8]) -> Self {
+        let mut hasher = crate::order_id::OrderIdHasher::with_scheme(scheme);
+        hasher.update(wincode_bytes);
+        hasher.finalize()
     }
 }
 
@@ -122,6 +170,14 @@ impl std::fmt::Display for Hash {
     }
 }
 
+impl std::str::FromStr for Hash {
+    type Err = crate::Error;
+
+    fn from_str(s: &str) -> crate::Result<Self> {
+        Self::from_base58(s)
+    }
+}
+
 impl Serialize for Hash {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
@@ -168,6 +224,20 @@ impl TimeInForce {
     }
 }
 
+impl TryFrom<&str> for TimeInForce {
+    type Error = crate::Error;
+
+    /// Case-insensitive `GTC`, `IOC` or `ALO`.
+    fn try_from(s: &str) -> crate::Result<Self> {
+        match s.to_ascii_uppercase().as_str() {
+            "GTC" => Ok(Self::Gtc),
+            "IOC" => Ok(Self::Ioc),
+            "ALO" => Ok(Self::Alo),
+            _ => Err(crate::Error::InvalidOrder(format!("invalid tif: {}", s))),
+        }
+    }
+}
+
 // ============================================================================
 // Order Types
 // ============================================================================
@@ -238,7 +308,9 @@ pub struct Order {
     /// Order type
     #[serde(rename = "t")]
     pub order_type: OrderType,
-    /// Client order ID (optional)
+    /// Client order ID (optional). `None` omits `cloid`; `Some` of the zero
+    /// hash serializes as `"cloid": "11111111111111111111111111111111"` and
+    /// is refused by signers (see [`Hash::is_zero`]).
     #[serde(rename = "cloid", skip_serializing_if = "Option::is_none")]
     pub client_id: Option<Hash>,
 }
@@ -264,6 +336,27 @@ impl Order {
         }
     }
 
+    /// Create a limit order from integer ticks and lots of `spec`.
+    ///
+    /// The price and size are the exact decimals the ticks and lots stand for,
+    /// with no float artifacts (see [`SymbolSpec`]).
+    pub fn from_ticks(
+        symbol: impl Into<String>,
+        is_buy: bool,
+        price_ticks: i64,
+        size_lots: i64,
+        spec: &SymbolSpec,
+        tif: TimeInForce,
+    ) -> crate::Result<Self> {
+        Ok(Self::limit(
+            symbol,
+            is_buy,
+            spec.price_from_ticks(price_ticks)?,
+            spec.size_from_lots(size_lots)?,
+            tif,
+        ))
+    }
+
     /// Create a market order
     pub fn market(symbol: impl Into<String>, is_buy: bool, size: f64) -> Self {
         Self {
@@ -301,6 +394,12 @@ impl Order {
         self.client_id = Some(Hash::random());
         self
     }
+
+    /// Set a client order ID drawn from `rng` (see [`crate::rng`]).
+    pub fn with_random_client_id_from(mut self, rng: &crate::RngProvider) -> Self {
+        self.client_id = Some(rng.random_hash());
+        self
+    }
 }
 
 // ============================================================================
@@ -326,6 +425,36 @@ impl Cancel {
             order_id,
         }
     }
+
+    /// Cancel the live order placed with `client_id`
+    pub fn by_client_id(symbol: impl Into<String>, client_id: Hash) -> CancelByClientId {
+        CancelByClientId::new(symbol, client_id)
+    }
+}
+
+/// Cancel the live order placed with a client ID
+///
+/// The exchange only cancels by order ID, so a signer resolves this to a
+/// [`Cancel`] of the order its client-ID registry holds for `client_id`
+/// (see [`Signer::with_unique_client_ids`](crate::Signer::with_unique_client_ids))
+/// before signing; it never reaches the wire as is.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CancelByClientId {
+    /// Market symbol
+    #[serde(rename = "c")]
+    pub symbol: String,
+    /// Client ID the order was placed with
+    #[serde(rename = "cloid")]
+    pub client_id: Hash,
+}
+
+impl CancelByClientId {
+    pub fn new(symbol: impl Into<String>, client_id: Hash) -> Self {
+        Self {
+            symbol: symbol.into(),
+            client_id,
+        }
+    }
 }
 
 /// Modify an existing order
@@ -356,22 +485,58 @@ impl Modify {
 // ============================================================================
 
 /// Cancel all orders (optionally filtered by symbols)
+///
+/// Non-exhaustive so filters can be added; build one with
+/// [`CancelAll::new`] and the `with_*` methods.
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[non_exhaustive]
 pub struct CancelAll {
     /// Symbols to cancel orders for (empty = all symbols)
     #[serde(rename = "c")]
     pub symbols: Vec<String>,
+    /// Only cancel orders older than this many milliseconds.
+    ///
+    /// The BULK API has no age filter on `cxa`, so signing a cancel-all with
+    /// this set fails with [`Error::InvalidOrder`](crate::Error::InvalidOrder).
+    #[serde(rename = "maxAgeMs", default, skip_serializing_if = "Option::is_none")]
+    pub max_age_ms: Option<u64>,
 }
 
 impl CancelAll {
+    /// Cancel all orders for `symbols` (empty = all symbols)
+    ///
+    /// ```rust
+    /// use bulk_keychain::CancelAll;
+    ///
+    /// let cxa = CancelAll::new(vec!["BTC-USD".to_string()]).with_max_age_ms(60_000);
+    /// assert_eq!((cxa.symbols.len(), cxa.max_age_ms), (1, Some(60_000)));
+    /// ```
+    pub fn new(symbols: Vec<String>) -> Self {
+        Self {
+            symbols,
+            max_age_ms: None,
+        }
+    }
+
     /// Cancel all orders across all symbols
     pub fn all() -> Self {
-        Self { symbols: vec![] }
+        Self::new(vec![])
     }
 
     /// Cancel all orders for specific symbols
     pub fn for_symbols(symbols: Vec<String>) -> Self {
-        Self { symbols }
+        Self::new(symbols)
+    }
+
+    /// Cancel orders for `symbol` older than `max_age_ms` (see [`CancelAll::max_age_ms`]).
+    pub fn for_symbol_older_than(symbol: &str, max_age_ms: u64) -> Self {
+        Self::for_symbols(vec![symbol.to_string()]).with_max_age_ms(max_age_ms)
+    }
+
+    /// Restrict to orders older than `max_age_ms` (see [`CancelAll::max_age_ms`]).
+    pub fn with_max_age_ms(mut self, max_age_ms: u64) -> Self {
+        self.max_age_ms = Some(max_age_ms);
+        self
     }
 }
 
@@ -432,7 +597,8 @@ pub struct RangeOco {
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct TriggerBasket {
     pub symbol: String,
-    /// true = buy/long side, false = sell/short side
+    /// Side flag, sent as `d` like a stop's. As for a stop, true (buy) fires
+    /// when price rises to `trigger_price` and false (sell) when it falls to it.
     pub is_buy: bool,
     pub trigger_price: f64,
     pub actions: Vec<OrderItem>,
@@ -441,6 +607,41 @@ pub struct TriggerBasket {
     pub iso: bool,
 }
 
+/// Price condition gating a conditional order.
+///
+/// Encoded as a [`TriggerBasket`] on `reference_symbol` with
+/// `is_buy = above`, since a buy-side basket fires on a rise and a
+/// sell-side one on a fall. The gated order's own side is unaffected.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TriggerCondition {
+    pub reference_symbol: String,
+    /// true = fire when price rises to the threshold, false = when it falls to it
+    pub above: bool,
+    pub threshold_price: f64,
+}
+
+impl TriggerCondition {
+    /// Condition on `reference_symbol` crossing `threshold_price`.
+    pub fn new(reference_symbol: impl Into<String>, above: bool, threshold_price: f64) -> Self {
+        Self {
+            reference_symbol: reference_symbol.into(),
+            above,
+            threshold_price,
+        }
+    }
+
+    /// Trigger basket firing `actions` once this condition is met.
+    pub fn into_basket(self, actions: Vec<OrderItem>) -> TriggerBasket {
+        TriggerBasket {
+            symbol: self.reference_symbol,
+            is_buy: self.above,
+            trigger_price: self.threshold_price,
+            actions,
+            iso: false,
+        }
+    }
+}
+
 /// Trailing stop: protective stop that follows price by a fixed distance in bps,
 /// resetting forward on favorable moves in increments of `step_bps`.
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -485,6 +686,8 @@ pub enum OrderItem {
     Modify(Modify),
     /// Cancel a specific order
     Cancel(Cancel),
+    /// Cancel a specific order by client ID; resolved to [`OrderItem::Cancel`] when signed
+    CancelByClientId(CancelByClientId),
     /// Cancel all orders
     CancelAll(CancelAll),
     /// Stop-loss conditional order
@@ -509,8 +712,9 @@ impl OrderItem {
                 OrderType::Limit { .. } => 1,   // l
                 OrderType::Trigger { .. } => 0, // m
             },
-            Self::Modify(_) => 2,        // mod
-            Self::Cancel(_) => 3,        // cx
+            Self::Modify(_) => 2, // mod
+            Self::Cancel(_) => 3, // cx
+            Self::CancelByClientId(_) => 3,
             Self::CancelAll(_) => 4,     // cxa
             Self::Stop(_) => 5,          // st
             Self::TakeProfit(_) => 6,    // tp
@@ -520,6 +724,39 @@ impl OrderItem {
             Self::OnFill(_) => 10,       // of
         }
     }
+
+    /// Estimated fee at `fee_bps`: `price * size * fee_bps / 10000`.
+    ///
+    /// Conditional orders use their limit price, falling back to the trigger
+    /// price for market-style fills; range orders use the higher collar.
+    /// Baskets and on-fill consequents sum their nested actions. Items with no
+    /// price (modifies, cancels, market orders, trailing stops without a
+    /// limit) estimate as `0.0`.
+    pub fn estimated_fee(&self, fee_bps: f64) -> f64 {
+        let notional = |price: f64, size: f64| {
+            if price.is_finite() {
+                price * size * fee_bps / 10_000.0
+            } else {
+                0.0
+            }
+        };
+        let limit_or = |limit: f64, trigger: f64| if limit.is_nan() { trigger } else { limit };
+        match self {
+            Self::Order(o) => notional(o.price, o.size),
+            Self::Stop(st) => notional(limit_or(st.limit_price, st.trigger_price), st.size),
+            Self::TakeProfit(tp) => notional(limit_or(tp.limit_price, tp.trigger_price), tp.size),
+            Self::RangeOco(r) => notional(
+                limit_or(r.limit_min, r.collar_min).max(limit_or(r.limit_max, r.collar_max)),
+                r.size,
+            ),
+            Self::TrailingStop(t) => t.limit_price.map_or(0.0, |px| notional(px, t.size)),
+            Self::TriggerBasket(b) => b.actions.iter().map(|a| a.estimated_fee(fee_bps)).sum(),
+            Self::OnFill(of) => of.actions.iter().map(|a| a.estimated_fee(fee_bps)).sum(),
+            Self::Modify(_) | Self::Cancel(_) | Self::CancelByClientId(_) | Self::CancelAll(_) => {
+                0.0
+            }
+        }
+    }
 }
 
 impl From<Order> for OrderItem {
@@ -534,6 +771,12 @@ impl From<Cancel> for OrderItem {
     }
 }
 
+impl From<CancelByClientId> for OrderItem {
+    fn from(cancel: CancelByClientId) -> Self {
+        Self::CancelByClientId(cancel)
+    }
+}
+
 impl From<Modify> for OrderItem {
     fn from(modify: Modify) -> Self {
         Self::Modify(modify)
@@ -582,6 +825,184 @@ impl From<TrailingStop> for OrderItem {
     }
 }
 
+// ============================================================================
+// Display strings
+// ============================================================================
+
+fn side_label(is_buy: bool) -> &'static str {
+    if is_buy {
+        "BUY"
+    } else {
+        "SELL"
+    }
+}
+
+/// Limit price, or `MARKET` for NaN (market-style fill)
+fn limit_label(price: f64) -> String {
+    if price.is_nan() {
+        "MARKET".to_string()
+    } else {
+        price.to_string()
+    }
+}
+
+fn nested_label(actions: &[OrderItem]) -> String {
+    let items: Vec<_> = actions.iter().map(OrderItem::to_display_string).collect();
+    format!("[{}]", items.join("; "))
+}
+
+impl TimeInForce {
+    /// Upper-case label as used in the API (`GTC`, `IOC`, `ALO`)
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            Self::Gtc => "GTC",
+            Self::Ioc => "IOC",
+            Self::Alo => "ALO",
+        }
+    }
+}
+
+impl Order {
+    /// Human-readable summary, e.g. `BUY 0.1 BTC-USD @ 50000 (GTC)`
+    pub fn to_display_string(&self) -> String {
+        self.describe(&DisplayPolicy::default())
+    }
+
+    /// Like [`Order::to_display_string`], with prices rounded per `policy`.
+    pub fn describe(&self, policy: &DisplayPolicy) -> String {
+        self.render(format!("{} {}", self.size, self.symbol), &|price| {
+            policy.format_price(&self.symbol, price)
+        })
+    }
+
+    /// Confirmation text at `spec`'s decimals with asset units, e.g.
+    /// `BUY 0.10 BTC @ 100,000.00 USD (GTC)`, formatted with
+    /// [`format_price`](crate::format::format_price) and
+    /// [`format_size`](crate::format::format_size).
+    ///
+    /// ```rust
+    /// use bulk_keychain::{Order, SymbolSpec, TimeInForce};
+    ///
+    /// let order = Order::limit("BTC-USD", true, 100000.0, 0.1, TimeInForce::Gtc);
+    /// assert_eq!(
+    ///     order.describe_with_spec(&SymbolSpec::new(2, 2)),
+    ///     "BUY 0.10 BTC @ 100,000.00 USD (GTC)"
+    /// );
+    /// ```
+    pub fn describe_with_spec(&self, spec: &SymbolSpec) -> String {
+        let size = crate::format::format_size(&self.symbol, self.size, spec);
+        self.render(size, &|price| {
+            crate::format::format_price(&self.symbol, price, spec)
+        })
+    }
+
+    fn render(&self, size: String, price_label: &dyn Fn(f64) -> String) -> String {
+        let mut flags = Vec::new();
+        let price = match self.order_type {
+            OrderType::Limit { tif } => {
+                flags.push(tif.as_str().to_string());
+                price_label(self.price)
+            }
+            OrderType::Trigger {
+                is_market: true, ..
+            } => "MARKET".to_string(),
+            OrderType::Trigger { trigger_px, .. } => {
+                flags.push(format!("trigger {}", price_label(trigger_px)));
+                price_label(self.price)
+            }
+        };
+        if self.reduce_only {
+            flags.push("reduce-only".to_string());
+        }
+        if self.iso {
+            flags.push("isolated".to_string());
+        }
+
+        let mut out = format!("{} {} @ {}", side_label(self.is_buy), size, price);
+        if !flags.is_empty() {
+            out.push_str(&format!(" ({})", flags.join(", ")));
+        }
+        out
+    }
+}
+
+impl Cancel {
+    /// Human-readable summary, e.g. `CANCEL BTC-USD order <oid>`
+    pub fn to_display_string(&self) -> String {
+        format!("CANCEL {} order {}", self.symbol, self.order_id)
+    }
+}
+
+impl OrderItem {
+    /// Human-readable summary of this item (nested actions included)
+    pub fn to_display_string(&self) -> String {
+        match self {
+            Self::Order(order) => order.to_display_string(),
+            Self::Cancel(cancel) => cancel.to_display_string(),
+            Self::CancelByClientId(c) => {
+                format!("CANCEL {} client id {}", c.symbol, c.client_id)
+            }
+            Self::Modify(m) => {
+                format!("MODIFY {} order {} size {}", m.symbol, m.order_id, m.amount)
+            }
+            Self::CancelAll(c) => {
+                let mut out = if c.symbols.is_empty() {
+                    "CANCEL ALL".to_string()
+                } else {
+                    format!("CANCEL ALL {}", c.symbols.join(", "))
+                };
+                if let Some(ms) = c.max_age_ms {
+                    out.push_str(&format!(" older than {ms}ms"));
+                }
+                out
+            }
+            Self::Stop(s) => format!(
+                "STOP {} {} {} trigger {} @ {}",
+                side_label(s.is_buy),
+                s.size,
+                s.symbol,
+                s.trigger_price,
+                limit_label(s.limit_price)
+            ),
+            Self::TakeProfit(tp) => format!(
+                "TAKE PROFIT {} {} {} trigger {} @ {}",
+                side_label(tp.is_buy),
+                tp.size,
+                tp.symbol,
+                tp.trigger_price,
+                limit_label(tp.limit_price)
+            ),
+            Self::RangeOco(r) => format!(
+                "RANGE {} {} {} collar {}-{} @ {}/{}",
+                side_label(r.is_buy),
+                r.size,
+                r.symbol,
+                r.collar_min,
+                r.collar_max,
+                limit_label(r.limit_min),
+                limit_label(r.limit_max)
+            ),
+            Self::TriggerBasket(t) => format!(
+                "TRIGGER {} {} {}: {}",
+                t.symbol,
+                if t.is_buy { "above" } else { "below" },
+                t.trigger_price,
+                nested_label(&t.actions)
+            ),
+            Self::OnFill(of) => format!("ON FILL #{}: {}", of.p, nested_label(&of.actions)),
+            Self::TrailingStop(t) => format!(
+                "TRAILING STOP {} {} {} trail {}bps step {}bps @ {}",
+                side_label(t.is_buy),
+                t.size,
+                t.symbol,
+                t.trail_bps,
+                t.step_bps,
+                limit_label(t.limit_price.unwrap_or(f64::NAN))
+            ),
+        }
+    }
+}
+
 // ============================================================================
 // Faucet
 // ============================================================================
@@ -998,7 +1419,102 @@ pub enum Action {
     UpdateMultisigPolicy(UpdateMultisigPolicy),
 }
 
+/// Kind of an [`Action`], without its payload.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ActionKind {
+    Order,
+    Oracle,
+    PythOracle,
+    Faucet,
+    UpdateUserSettings,
+    AgentWalletCreation,
+    WhitelistFaucet,
+    CreateSubAccount,
+    RemoveSubAccount,
+    RenameSubAccount,
+    Transfer,
+    CreateMultisig,
+    MultisigPropose,
+    MultisigApprove,
+    MultisigReject,
+    MultisigCancel,
+    MultisigExecute,
+    UpdateMultisigPolicy,
+}
+
+impl ActionKind {
+    /// Every kind, in [`Action`] declaration order.
+    pub const ALL: [ActionKind; 18] = [
+        Self::Order,
+        Self::Oracle,
+        Self::PythOracle,
+        Self::Faucet,
+        Self::UpdateUserSettings,
+        Self::AgentWalletCreation,
+        Self::WhitelistFaucet,
+        Self::CreateSubAccount,
+        Self::RemoveSubAccount,
+        Self::RenameSubAccount,
+        Self::Transfer,
+        Self::CreateMultisig,
+        Self::MultisigPropose,
+        Self::MultisigApprove,
+        Self::MultisigReject,
+        Self::MultisigCancel,
+        Self::MultisigExecute,
+        Self::UpdateMultisigPolicy,
+    ];
+
+    /// The serialized name, e.g. `"createSubAccount"`.
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Order => "order",
+            Self::Oracle => "oracle",
+            Self::PythOracle => "pythOracle",
+            Self::Faucet => "faucet",
+            Self::UpdateUserSettings => "updateUserSettings",
+            Self::AgentWalletCreation => "agentWalletCreation",
+            Self::WhitelistFaucet => "whitelistFaucet",
+            Self::CreateSubAccount => "createSubAccount",
+            Self::RemoveSubAccount => "removeSubAccount",
+            Self::RenameSubAccount => "renameSubAccount",
+            Self::Transfer => "transfer",
+            Self::CreateMultisig => "createMultisig",
+            Self::MultisigPropose => "multisigPropose",
+            Self::MultisigApprove => "multisigApprove",
+            Self::MultisigReject => "multisigReject",
+            Self::MultisigCancel => "multisigCancel",
+            Self::MultisigExecute => "multisigExecute",
+            Self::UpdateMultisigPolicy => "updateMultisigPolicy",
+        }
+    }
+}
+
 impl Action {
+    pub const fn kind(&self) -> ActionKind {
+        match self {
+            Self::Order { .. } => ActionKind::Order,
+            Self::Oracle { .. } => ActionKind::Oracle,
+            Self::PythOracle { .. } => ActionKind::PythOracle,
+            Self::Faucet(_) => ActionKind::Faucet,
+            Self::UpdateUserSettings(_) => ActionKind::UpdateUserSettings,
+            Self::AgentWalletCreation(_) => ActionKind::AgentWalletCreation,
+            Self::WhitelistFaucet(_) => ActionKind::WhitelistFaucet,
+            Self::CreateSubAccount(_) => ActionKind::CreateSubAccount,
+            Self::RemoveSubAccount(_) => ActionKind::RemoveSubAccount,
+            Self::RenameSubAccount(_) => ActionKind::RenameSubAccount,
+            Self::Transfer(_) => ActionKind::Transfer,
+            Self::CreateMultisig(_) => ActionKind::CreateMultisig,
+            Self::MultisigPropose(_) => ActionKind::MultisigPropose,
+            Self::MultisigApprove(_) => ActionKind::MultisigApprove,
+            Self::MultisigReject(_) => ActionKind::MultisigReject,
+            Self::MultisigCancel(_) => ActionKind::MultisigCancel,
+            Self::MultisigExecute(_) => ActionKind::MultisigExecute,
+            Self::UpdateMultisigPolicy(_) => ActionKind::UpdateMultisigPolicy,
+        }
+    }
+
     /// Get the discriminant for wincode serialization
     pub const fn discriminant(&self) -> u32 {
         match self {
@@ -1023,6 +1539,15 @@ impl Action {
         }
     }
 
+    /// Whether signing this action twice has the same effect as signing it
+    /// once, which makes it safe for [`NonceStrategy::ContentDerived`](crate::NonceStrategy::ContentDerived).
+    pub const fn is_idempotent(&self) -> bool {
+        matches!(
+            self,
+            Self::AgentWalletCreation(_) | Self::UpdateUserSettings(_)
+        )
+    }
+
     /// Get the action type string for JSON
     pub const fn type_str(&self) -> &'static str {
         match self {
@@ -1096,6 +1621,65 @@ impl From<UpdateMultisigPolicy> for Action {
     }
 }
 
+// ============================================================================
+// Group Kind
+// ============================================================================
+
+/// Intent of an atomic multi-item group, for downstream routing.
+///
+/// Carried as unsigned metadata only: the exchange schema has no slot for it,
+/// so tagging a group never changes the signed bytes.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(from = "String", into = "String")]
+pub enum GroupKind {
+    /// Entry order plus its take-profit and stop-loss legs
+    Bracket,
+    /// Take-profit and stop-loss legs, one-cancels-other
+    Oco,
+    /// Unrelated items (e.g. a batch of cancels) grouped for atomicity
+    Batch,
+    /// Caller-defined tag
+    Custom(String),
+}
+
+impl GroupKind {
+    /// Wire name: `bracket`, `oco`, `batch`, or the custom tag itself
+    pub fn as_str(&self) -> &str {
+        match self {
+            Self::Bracket => "bracket",
+            Self::Oco => "oco",
+            Self::Batch => "batch",
+            Self::Custom(tag) => tag,
+        }
+    }
+}
+
+impl From<&str> for GroupKind {
+    fn from(s: &str) -> Self {
+        match s {
+            "bracket" => Self::Bracket,
+            "oco" => Self::Oco,
+            "batch" => Self::Batch,
+            other => Self::Custom(other.to_string()),
+        }
+    }
+}
+
+impl From<String> for GroupKind {
+    fn from(s: String) -> Self {
+        Self::from(s.as_str())
+    }
+}
+
+impl From<GroupKind> for String {
+    fn from(kind: GroupKind) -> Self {
+        match kind {
+            GroupKind::Custom(tag) => tag,
+            other => other.as_str().to_string(),
+        }
+    }
+}
+
 // ============================================================================
 // Signed Transaction
 // ============================================================================
@@ -1103,8 +1687,11 @@ impl From<UpdateMultisigPolicy> for Action {
 /// A signed transaction ready to submit to the API
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SignedTransaction {
-    /// Actions to execute atomically (compact tagged format)
-    pub actions: Vec<serde_json::Value>,
+    /// The signed action. Serialized as `actions`, the compact tagged JSON
+    /// list the API takes (see [`crate::compact`]); a single action may
+    /// render as several entries, e.g. one per order item.
+    #[serde(rename = "actions", with = "compact_actions")]
+    pub action: Action,
     /// Transaction nonce
     pub nonce: u64,
     /// Account public key (base58)
@@ -1121,9 +1708,65 @@ pub struct SignedTransaction {
     /// This is not part of the API request payload.
     #[serde(skip_serializing, skip_deserializing, default)]
     pub order_ids: Option<Vec<String>>,
+    /// Scheme the pre-computed order IDs were derived with.
+    #[serde(skip_serializing, skip_deserializing, default)]
+    pub id_scheme: crate::order_id::IdScheme,
+    /// How many times these actions were re-signed under a fresh nonce.
+    #[serde(skip_serializing, skip_deserializing, default)]
+    pub resign_count: u32,
+    /// Intent tag for grouped transactions.
+    /// This is not part of the API request payload.
+    #[serde(skip_serializing, skip_deserializing, default)]
+    pub group_kind: Option<GroupKind>,
+    /// Group-level lint findings; see [`crate::lint_group`].
+    /// This is not part of the API request payload.
+    #[serde(skip_serializing, skip_deserializing, default)]
+    pub group_warnings: Vec<crate::lint::GroupLintWarning>,
+}
+
+/// `SignedTransaction::action` as the compact `actions` list.
+mod compact_actions {
+    use super::Action;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    pub fn serialize<S: Serializer>(action: &Action, serializer: S) -> Result<S::Ok, S::Error> {
+        crate::compact::action_to_json(action)
+            .map_err(serde::ser::Error::custom)?
+            .serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Action, D::Error> {
+        let values = Vec::<serde_json::Value>::deserialize(deserializer)?;
+        crate::compact::action_from_json_list(&values).map_err(serde::de::Error::custom)
+    }
 }
 
 impl SignedTransaction {
+    /// The compact tagged `actions` list sent to the API.
+    ///
+    /// ```rust
+    /// use bulk_keychain::{ActionKind, Keypair, Order, Signer};
+    ///
+    /// let mut signer = Signer::new(Keypair::generate());
+    /// let tx = signer.sign(Order::market("BTC-USD", true, 0.1).into(), Some(1)).unwrap();
+    /// assert_eq!(tx.action_type(), ActionKind::Order);
+    /// assert_eq!(tx.actions_json().unwrap()[0]["m"]["c"], "BTC-USD");
+    /// assert_eq!(tx.to_json_value().unwrap()["actions"][0], tx.actions_json().unwrap()[0]);
+    /// ```
+    pub fn actions_json(&self) -> crate::Result<Vec<serde_json::Value>> {
+        crate::compact::action_to_json(&self.action)
+    }
+
+    /// The request payload as a JSON value, as [`to_json`](Self::to_json) writes it.
+    pub fn to_json_value(&self) -> crate::Result<serde_json::Value> {
+        serde_json::to_value(self).map_err(crate::Error::from)
+    }
+
+    /// Kind of the signed action
+    pub fn action_type(&self) -> ActionKind {
+        self.action.kind()
+    }
+
     /// Serialize to JSON string
     pub fn to_json(&self) -> crate::Result<String> {
         serde_json::to_string(self).map_err(crate::Error::from)
@@ -1133,4 +1776,155 @@ impl SignedTransaction {
     pub fn to_json_bytes(&self) -> crate::Result<Vec<u8>> {
         serde_json::to_vec(self).map_err(crate::Error::from)
     }
+
+    /// Raw 64-byte ed25519 signature
+    pub fn signature_bytes(&self) -> crate::Result<[u8; 64]> {
+        let bytes = bs58::decode(&self.signature)
+            .into_vec()
+            .map_err(|e| crate::Error::InvalidBase58(e.to_string()))?;
+        bytes
+            .as_slice()
+            .try_into()
+            .map_err(|_| crate::Error::InvalidSignatureLength(bytes.len()))
+    }
+
+    /// Account the transaction acts on
+    pub fn account_pubkey(&self) -> crate::Result<Pubkey> {
+        Pubkey::from_base58(&self.account)
+    }
+
+    /// Key that produced the signature
+    pub fn signer_pubkey(&self) -> crate::Result<Pubkey> {
+        Pubkey::from_base58(&self.signer)
+    }
+
+    /// Pre-computed order ID of a single-order transaction, decoded
+    pub fn order_id_hash(&self) -> crate::Result<Option<Hash>> {
+        self.order_id.as_deref().map(Hash::from_base58).transpose()
+    }
+
+    /// Every pre-computed order ID, decoded: `order_ids` for a multi-order
+    /// transaction, else `order_id`; empty if none were computed.
+    pub fn order_id_hashes(&self) -> crate::Result<Vec<Hash>> {
+        match &self.order_ids {
+            Some(ids) => ids.iter().map(|id| Hash::from_base58(id)).collect(),
+            None => Ok(self.order_id_hash()?.into_iter().collect()),
+        }
+    }
+}
+
+/// A signed transaction tagged with the position of the input it was built from.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IndexedSignedTransaction {
+    /// Index of the source item in the batch input
+    pub index: usize,
+    /// The signed transaction
+    #[serde(flatten)]
+    pub tx: SignedTransaction,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_display_strings() {
+        let oid = Hash::from_bytes_fixed([1u8; 32]);
+        let limit = Order::limit("BTC-USD", true, 50000.0, 0.1, TimeInForce::Gtc);
+        let cases: Vec<(OrderItem, String)> = vec![
+            (limit.clone().into(), "BUY 0.1 BTC-USD @ 50000 (GTC)".into()),
+            (
+                Order::limit("ETH-USD", false, 3000.5, 2.0, TimeInForce::Alo)
+                    .reduce_only()
+                    .isolated()
+                    .into(),
+                "SELL 2 ETH-USD @ 3000.5 (ALO, reduce-only, isolated)".into(),
+            ),
+            (
+                Order::market("BTC-USD", false, 0.5).into(),
+                "SELL 0.5 BTC-USD @ MARKET".into(),
+            ),
+            (
+                Cancel::new("BTC-USD", oid).into(),
+                format!("CANCEL BTC-USD order {oid}"),
+            ),
+            (
+                Modify::new(oid, "BTC-USD", 0.2).into(),
+                format!("MODIFY BTC-USD order {oid} size 0.2"),
+            ),
+            (CancelAll::all().into(), "CANCEL ALL".into()),
+            (
+                CancelAll::for_symbol_older_than("BTC-USD", 5000).into(),
+                "CANCEL ALL BTC-USD older than 5000ms".into(),
+            ),
+            (
+                OrderItem::Stop(Stop {
+                    symbol: "BTC-USD".into(),
+                    is_buy: false,
+                    size: 0.1,
+                    trigger_price: 45000.0,
+                    limit_price: f64::NAN,
+                    iso: false,
+                }),
+                "STOP SELL 0.1 BTC-USD trigger 45000 @ MARKET".into(),
+            ),
+            (
+                OrderItem::TakeProfit(TakeProfit {
+                    symbol: "BTC-USD".into(),
+                    is_buy: false,
+                    size: 0.1,
+                    trigger_price: 55000.0,
+                    limit_price: 54900.0,
+                    iso: false,
+                }),
+                "TAKE PROFIT SELL 0.1 BTC-USD trigger 55000 @ 54900".into(),
+            ),
+            (
+                OrderItem::RangeOco(RangeOco {
+                    symbol: "BTC-USD".into(),
+                    is_buy: false,
+                    size: 0.1,
+                    collar_min: 45000.0,
+                    collar_max: 55000.0,
+                    limit_min: f64::NAN,
+                    limit_max: 55100.0,
+                    iso: false,
+                }),
+                "RANGE SELL 0.1 BTC-USD collar 45000-55000 @ MARKET/55100".into(),
+            ),
+            (
+                TriggerCondition::new("ETH-USD", false, 2500.0)
+                    .into_basket(vec![limit.clone().into()])
+                    .into(),
+                "TRIGGER ETH-USD below 2500: [BUY 0.1 BTC-USD @ 50000 (GTC)]".into(),
+            ),
+            (
+                OrderItem::OnFill(OnFill {
+                    p: 0,
+                    actions: vec![CancelAll::all().into(), Cancel::new("BTC-USD", oid).into()],
+                }),
+                format!("ON FILL #0: [CANCEL ALL; CANCEL BTC-USD order {oid}]"),
+            ),
+            (
+                OrderItem::TrailingStop(TrailingStop {
+                    symbol: "BTC-USD".into(),
+                    is_buy: true,
+                    size: 0.1,
+                    trail_bps: 50,
+                    step_bps: 10,
+                    limit_price: None,
+                    iso: false,
+                }),
+                "TRAILING STOP BUY 0.1 BTC-USD trail 50bps step 10bps @ MARKET".into(),
+            ),
+        ];
+
+        for (item, expected) in cases {
+            assert_eq!(item.to_display_string(), expected);
+        }
+        assert_eq!(
+            Cancel::new("BTC-USD", oid).to_display_string(),
+            format!("CANCEL BTC-USD order {oid}")
+        );
+    }
 }
@@ -0,0 +1,88 @@
+//! Agent onboarding: an external wallet authorizes an agent key, the agent
+//! trades for the wallet's account, and the wallet revokes it.
+//!
+//! Run: cargo run --example agent_flow
+//! Test: cargo test -p bulk-keychain --features examples-as-tests --example agent_flow
+
+use bulk_keychain::{
+    compute_order_item_id, finalize_transaction, prepare_agent_wallet, Keypair, Order, OrderItem,
+    Signer, TimeInForce,
+};
+
+fn main() -> bulk_keychain::Result<()> {
+    // `wallet` stands in for an external wallet (Phantom, Privy, ...) that
+    // only ever signs raw bytes handed to it.
+    let wallet = Signer::new(Keypair::generate());
+    let account = *wallet.account_pubkey();
+    let agent = Keypair::generate();
+    let agent_pubkey = agent.pubkey();
+
+    // 1. The account authorizes the agent. The account key must sign this
+    //    itself, so it goes through prepare/finalize.
+    let prepared = prepare_agent_wallet(&agent_pubkey, false, &account, None, Some(1_000))?;
+    assert_eq!(prepared.signer_pubkey()?, account);
+    let authorize = finalize_transaction(
+        prepared.clone(),
+        &wallet.sign_bytes(&prepared.message_bytes),
+    )?;
+    assert!(authorize.verify()?);
+    assert_eq!(authorize.account_pubkey()?, account);
+    assert_eq!(authorize.signer_pubkey()?, account);
+    let actions = authorize.actions_json()?;
+    let grant = &actions[0]["agentWalletCreation"];
+    assert_eq!(grant["a"], agent_pubkey.to_base58());
+    assert_eq!(grant["d"], false);
+    println!("authorized agent {} for {}", agent_pubkey, account);
+
+    // 2. The agent signs orders for the account. Its nonces must come after
+    //    the authorization so the server has seen the grant first.
+    let mut agent_signer = Signer::new(agent).with_account(account);
+    let order: OrderItem = Order::limit("BTC-USD", true, 100000.0, 0.1, TimeInForce::Gtc).into();
+    let tx = agent_signer.sign(order.clone(), Some(authorize.nonce + 1))?;
+    assert!(tx.nonce > authorize.nonce);
+    assert!(tx.verify()?);
+    assert_eq!(tx.account_pubkey()?, account);
+    assert_eq!(tx.signer_pubkey()?, agent_pubkey);
+    // Order IDs are derived from the account, not the agent key.
+    let expected_id = compute_order_item_id(&order, tx.nonce, &account).expect("orders have IDs");
+    assert_eq!(tx.order_id_hash()?, Some(expected_id));
+    println!("agent signed order {}", expected_id);
+
+    let batch = agent_signer.sign_all(vec![order.clone(); 3], Some(tx.nonce + 1))?;
+    for (i, tx) in batch.iter().enumerate() {
+        assert!(tx.verify()?);
+        assert_eq!(tx.signer_pubkey()?, agent_pubkey);
+        assert_eq!(tx.account_pubkey()?, account);
+        assert_eq!(
+            tx.order_id_hash()?,
+            compute_order_item_id(&order, tx.nonce, &account)
+        );
+        assert_eq!(tx.nonce, batch[0].nonce + i as u64);
+    }
+
+    // 3. The account revokes the agent, again signed by the wallet.
+    let revoke_nonce = batch.last().map_or(tx.nonce, |tx| tx.nonce) + 1;
+    let prepared = prepare_agent_wallet(&agent_pubkey, true, &account, None, Some(revoke_nonce))?;
+    let revoke = finalize_transaction(
+        prepared.clone(),
+        &wallet.sign_bytes(&prepared.message_bytes),
+    )?;
+    assert!(revoke.verify()?);
+    assert_eq!(revoke.signer_pubkey()?, account);
+    assert_eq!(revoke.actions_json()?[0]["agentWalletCreation"]["d"], true);
+
+    // An agent signature passed off as the wallet's does not verify.
+    let forged = finalize_transaction(
+        prepared.clone(),
+        &agent_signer.sign_bytes(&prepared.message_bytes),
+    )?;
+    assert!(!forged.verify()?);
+    println!("revoked agent {}", agent_pubkey);
+    Ok(())
+}
+
+#[cfg(all(test, feature = "examples-as-tests"))]
+#[test]
+fn agent_flow() {
+    main().unwrap();
+}
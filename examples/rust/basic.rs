@@ -21,7 +21,7 @@ fn main() -> bulk_keychain::Result<()> {
 
     // 2. Create a signer
     let mut signer = Signer::new(keypair).with_batch_order_ids();
-    println!("Signer pubkey: {}", signer.pubkey());
+    println!("Signer pubkey: {}", signer.signer_pubkey());
     println!();
 
     // 3. Sign a single limit order (using new simple API)
@@ -35,7 +35,7 @@ fn main() -> bulk_keychain::Result<()> {
     println!("--- Market Order ---");
     let market_order = Order::market("ETH-USD", false, 1.0);
     let signed_market = signer.sign(market_order.into(), None)?;
-    println!("Actions in tx: {}", signed_market.actions.len());
+    println!("Actions in tx: {}", signed_market.actions_json()?.len());
     println!();
 
     // 5. Sign multiple orders atomically (sign_group)
@@ -48,7 +48,7 @@ fn main() -> bulk_keychain::Result<()> {
     let signed_bracket = signer.sign_group(bracket, None)?;
     println!(
         "Bracket order: {} actions in 1 tx",
-        signed_bracket.actions.len()
+        signed_bracket.actions_json()?.len()
     );
     println!("Bracket order IDs: {:?}", signed_bracket.order_ids);
     println!();
@@ -103,28 +103,16 @@ fn main() -> bulk_keychain::Result<()> {
     // 9. Sign faucet request
     println!("--- Faucet Request ---");
     let signed_faucet = signer.sign_faucet(None)?;
-    let faucet_tag = signed_faucet
-        .actions
-        .first()
-        .and_then(|v| v.as_object())
-        .and_then(|obj| obj.keys().next())
-        .cloned()
-        .unwrap_or_else(|| "unknown".to_string());
-    println!("Faucet action tag: {}", faucet_tag);
+    let faucet_type = signed_faucet.action_type().as_str();
+    println!("Faucet action type: {}", faucet_type);
     println!();
 
     // 10. Sign user settings
     println!("--- User Settings (Leverage) ---");
     let settings = UserSettings::new(vec![("BTC-USD".into(), 5.0), ("ETH-USD".into(), 3.0)]);
     let signed_settings = signer.sign_user_settings(settings, None)?;
-    let settings_tag = signed_settings
-        .actions
-        .first()
-        .and_then(|v| v.as_object())
-        .and_then(|obj| obj.keys().next())
-        .cloned()
-        .unwrap_or_else(|| "unknown".to_string());
-    println!("Settings action tag: {}", settings_tag);
+    let settings_type = signed_settings.action_type().as_str();
+    println!("Settings action type: {}", settings_type);
     println!();
 
     // 11. Sign oracle price update(s)
@@ -144,14 +132,8 @@ fn main() -> bulk_keychain::Result<()> {
         ],
         None,
     )?;
-    let oracle_tag = signed_oracle
-        .actions
-        .first()
-        .and_then(|v| v.as_object())
-        .and_then(|obj| obj.keys().next())
-        .cloned()
-        .unwrap_or_else(|| "unknown".to_string());
-    println!("Oracle action tag: {}", oracle_tag);
+    let oracle_type = signed_oracle.action_type().as_str();
+    println!("Oracle action type: {}", oracle_type);
     println!();
 
     // 12. Sign Pyth oracle batch update
@@ -173,28 +155,16 @@ fn main() -> bulk_keychain::Result<()> {
         ],
         None,
     )?;
-    let pyth_tag = signed_pyth
-        .actions
-        .first()
-        .and_then(|v| v.as_object())
-        .and_then(|obj| obj.keys().next())
-        .cloned()
-        .unwrap_or_else(|| "unknown".to_string());
-    println!("Pyth action tag: {}", pyth_tag);
+    let pyth_type = signed_pyth.action_type().as_str();
+    println!("Pyth action type: {}", pyth_type);
     println!();
 
     // 13. Sign whitelist faucet admin action
     println!("--- Whitelist Faucet ---");
     let target = Keypair::generate().pubkey();
     let signed_whitelist = signer.sign_whitelist_faucet(target, true, None)?;
-    let whitelist_tag = signed_whitelist
-        .actions
-        .first()
-        .and_then(|v| v.as_object())
-        .and_then(|obj| obj.keys().next())
-        .cloned()
-        .unwrap_or_else(|| "unknown".to_string());
-    println!("Whitelist action tag: {}", whitelist_tag);
+    let whitelist_type = signed_whitelist.action_type().as_str();
+    println!("Whitelist action type: {}", whitelist_type);
 
     println!("\n=== Done ===");
     Ok(())
@@ -0,0 +1,88 @@
+//! Bracket trading: an entry with take-profit and stop-loss legs signed as
+//! one transaction, each leg's order ID tracked, and the group's resting
+//! orders cancelled together.
+//!
+//! Run: cargo run --example bracket_flow
+//! Test: cargo test -p bulk-keychain --features examples-as-tests --example bracket_flow
+
+use bulk_keychain::{
+    compute_order_item_id_at, Cancel, GroupKind, Keypair, Order, OrderItem, Signer, Stop,
+    TakeProfit, TimeInForce,
+};
+
+fn main() -> bulk_keychain::Result<()> {
+    let mut signer = Signer::new(Keypair::generate()).with_batch_order_ids();
+    let account = *signer.account_pubkey();
+
+    let entry = Order::limit("BTC-USD", true, 100000.0, 0.1, TimeInForce::Gtc);
+    let take_profit = TakeProfit {
+        symbol: "BTC-USD".to_string(),
+        is_buy: false,
+        size: 0.1,
+        trigger_price: 110000.0,
+        limit_price: f64::NAN,
+        iso: false,
+    };
+    let stop_loss = Stop {
+        symbol: "BTC-USD".to_string(),
+        is_buy: false,
+        size: 0.1,
+        trigger_price: 95000.0,
+        limit_price: f64::NAN,
+        iso: false,
+    };
+    let legs: Vec<OrderItem> = vec![
+        entry.clone().into(),
+        take_profit.clone().into(),
+        stop_loss.clone().into(),
+    ];
+
+    // 1. Sign the bracket atomically.
+    let bracket = signer.sign_bracket(entry, take_profit, stop_loss, Some(1_000))?;
+    assert!(bracket.verify()?);
+    assert_eq!(bracket.group_kind, Some(GroupKind::Bracket));
+    assert_eq!(bracket.actions_json()?.len(), 3);
+    assert_eq!(bracket.account_pubkey()?, account);
+    assert_eq!(bracket.signer_pubkey()?, account);
+
+    // 2. Each leg's ID comes from its position in the group. Trigger legs
+    //    have none until they fire, so `order_ids` lists only the legs
+    //    that rest on the book: here the entry.
+    let leg_ids: Vec<_> = legs
+        .iter()
+        .enumerate()
+        .map(|(index, leg)| compute_order_item_id_at(leg, index as u32, bracket.nonce, &account))
+        .collect();
+    assert!(leg_ids[0].is_some());
+    assert_eq!(leg_ids[1..], [None, None]);
+    let resting: Vec<_> = leg_ids.into_iter().flatten().collect();
+    assert_eq!(bracket.order_id_hashes()?, resting);
+    println!("entry order ID: {}", resting[0]);
+
+    // 3. Cancel everything the group left resting in one transaction.
+    let cancels: Vec<OrderItem> = resting
+        .iter()
+        .map(|id| Cancel::new("BTC-USD", *id).into())
+        .collect();
+    let cancel =
+        signer.sign_group_with(cancels, Some(GroupKind::Batch), Some(bracket.nonce + 1))?;
+    assert!(cancel.verify()?);
+    assert_eq!(cancel.group_kind, Some(GroupKind::Batch));
+    assert_eq!(cancel.actions_json()?.len(), resting.len());
+    for (action, id) in cancel.actions_json()?.iter().zip(&resting) {
+        assert_eq!(action["cx"]["oid"], id.to_base58());
+    }
+    // Cancels place nothing, so they carry no order IDs.
+    assert!(cancel.order_id_hashes()?.is_empty());
+    println!(
+        "cancelled {} resting leg(s) in one transaction",
+        resting.len()
+    );
+    Ok(())
+}
+
+#[cfg(all(test, feature = "examples-as-tests"))]
+#[test]
+fn bracket_flow() {
+    main().unwrap();
+}
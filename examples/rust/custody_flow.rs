@@ -0,0 +1,98 @@
+//! External custody: orders are prepared to a file, signed elsewhere by a
+//! custodian that holds the key, and finalized and verified on return.
+//!
+//! Run: cargo run --example custody_flow
+//! Test: cargo test -p bulk-keychain --features examples-as-tests --example custody_flow
+
+use bulk_keychain::{
+    compute_order_item_id, finalize_all, prepare_all, verify_batch, Keypair, Order, OrderItem,
+    PreparedMessage, Signer, TimeInForce,
+};
+use std::fs;
+use std::io::{BufRead, BufReader, Write};
+use std::path::Path;
+
+/// The custodian's side: read prepared messages, sign each message's bytes,
+/// write one base58 signature per line. Only the key holder runs this.
+fn remote_sign(
+    custodian: &Signer,
+    requests: &Path,
+    signatures: &Path,
+) -> bulk_keychain::Result<()> {
+    let mut out = fs::File::create(signatures).expect("create signature file");
+    for line in BufReader::new(fs::File::open(requests).expect("open requests")).lines() {
+        let prepared: PreparedMessage = serde_json::from_str(&line.expect("read request"))?;
+        assert_eq!(prepared.signer_pubkey()?, *custodian.signer_pubkey());
+        writeln!(out, "{}", custodian.sign_bytes(&prepared.message_bytes))
+            .expect("write signature");
+    }
+    Ok(())
+}
+
+fn main() -> bulk_keychain::Result<()> {
+    let custodian = Signer::new(Keypair::generate());
+    let account = *custodian.account_pubkey();
+    let dir = std::env::temp_dir().join(format!("bulk-keychain-custody-{}", std::process::id()));
+    fs::create_dir_all(&dir).expect("create work dir");
+    let requests = dir.join("prepared.jsonl");
+    let signatures = dir.join("signatures.txt");
+
+    // 1. Prepare without the key and hand the messages over as a file.
+    let orders: Vec<OrderItem> = (0..12)
+        .map(|i| {
+            Order::limit(
+                "ETH-USD",
+                i % 2 == 0,
+                3000.0 + i as f64,
+                1.0,
+                TimeInForce::Gtc,
+            )
+            .into()
+        })
+        .collect();
+    let prepared = prepare_all(orders.clone(), &account, None, Some(5_000))?;
+    let mut file = fs::File::create(&requests).expect("create request file");
+    for message in &prepared {
+        writeln!(file, "{}", serde_json::to_string(message)?).expect("write request");
+    }
+    drop(file);
+
+    // 2. The custodian signs remotely.
+    remote_sign(&custodian, &requests, &signatures)?;
+
+    // 3. Finalize with the returned signatures, in order, and verify.
+    let returned = fs::read_to_string(&signatures).expect("read signatures");
+    let returned: Vec<&str> = returned.lines().collect();
+    assert_eq!(returned.len(), prepared.len());
+    let txs = finalize_all(prepared, returned)?;
+    assert!(verify_batch(&txs).is_empty());
+    for (i, (tx, order)) in txs.iter().zip(&orders).enumerate() {
+        assert_eq!(tx.nonce, 5_000 + i as u64);
+        assert_eq!(tx.account_pubkey()?, account);
+        assert_eq!(tx.signer_pubkey()?, account);
+        assert_eq!(
+            tx.order_id_hash()?,
+            compute_order_item_id(order, tx.nonce, &account)
+        );
+    }
+    println!(
+        "finalized and verified {} custodian-signed orders",
+        txs.len()
+    );
+
+    // A signature returned against the wrong message is caught.
+    let mut swapped = txs.clone();
+    let first = swapped[0].signature.clone();
+    swapped[0].signature = swapped[1].signature.clone();
+    swapped[1].signature = first;
+    assert_eq!(verify_batch(&swapped), [0, 1]);
+
+    fs::remove_dir_all(&dir).expect("remove work dir");
+    Ok(())
+}
+
+#[cfg(all(test, feature = "examples-as-tests"))]
+#[test]
+fn custody_flow() {
+    main().unwrap();
+}
@@ -0,0 +1,24 @@
+//! Write the versioned JSON Schema bundle for every keychain JSON input and output
+//!
+//! Run: cargo run --example schema_dump [-- <path>]
+//!
+//! Prints to stdout without a path.
+
+use bulk_keychain::schemas;
+
+fn main() -> std::io::Result<()> {
+    let bundle = serde_json::to_string_pretty(&schemas::bundle())?;
+    match std::env::args().nth(1) {
+        Some(path) => {
+            std::fs::write(&path, bundle + "\n")?;
+            eprintln!(
+                "wrote schema bundle v{} ({} schemas) to {}",
+                schemas::SCHEMA_VERSION,
+                schemas::SCHEMA_NAMES.len(),
+                path
+            );
+        }
+        None => println!("{}", bundle),
+    }
+    Ok(())
+}
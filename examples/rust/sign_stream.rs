@@ -0,0 +1,66 @@
+//! Sign newline-delimited order intents from stdin as they arrive
+//!
+//! Run: BULK_SECRET_KEY=<base58> cargo run --release --example sign_stream -- --stdin [--strict] [--nonce <base>]
+//!
+//! Each line is an `OrderSpec` JSON object. Signed transactions are written
+//! to stdout as JSON lines, in input order; refused lines are reported on
+//! stderr as `line N: reason` and do not stop the stream. Exits 1 if any
+//! line was refused.
+
+use bulk_keychain::{InputMode, IntentStream, Keypair, Signer};
+use std::io::Write;
+
+fn main() -> bulk_keychain::Result<()> {
+    let mut stdin = false;
+    let mut mode = InputMode::Tolerant;
+    let mut nonce = None;
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--stdin" => stdin = true,
+            "--strict" => mode = InputMode::Strict,
+            "--nonce" => match args.next().and_then(|n| n.parse().ok()) {
+                Some(base) => nonce = Some(base),
+                None => {
+                    eprintln!("--nonce takes an integer");
+                    std::process::exit(2);
+                }
+            },
+            other => {
+                eprintln!("unknown argument {}", other);
+                std::process::exit(2);
+            }
+        }
+    }
+    if !stdin {
+        eprintln!("usage: sign_stream --stdin [--strict] [--nonce <base>]");
+        std::process::exit(2);
+    }
+    let Ok(secret) = std::env::var("BULK_SECRET_KEY") else {
+        eprintln!("BULK_SECRET_KEY is not set");
+        std::process::exit(2);
+    };
+
+    let signer = Signer::new(Keypair::from_base58(&secret)?);
+    let intents = IntentStream::new(std::io::stdin().lock()).with_mode(mode);
+    let mut out = std::io::stdout().lock();
+    let mut refused = 0;
+    signer.sign_stream(intents, nonce, |result| match result {
+        Ok(tx) => {
+            let json = tx.to_json().expect("signed transactions serialize");
+            // A closed stdout ends the run like any other filter.
+            if writeln!(out, "{}", json).and_then(|_| out.flush()).is_err() {
+                std::process::exit(0);
+            }
+        }
+        Err(e) => {
+            refused += 1;
+            eprintln!("{}", e);
+        }
+    })?;
+
+    if refused > 0 {
+        std::process::exit(1);
+    }
+    Ok(())
+}
@@ -0,0 +1,70 @@
+//! Verify a JSONL dump of signed transactions, resumably
+//!
+//! Run: cargo run --release --example verify_archive -- --archive <file.jsonl> [--resume] [--allow <pubkey>]...
+//!
+//! Progress is saved to `<file.jsonl>.checkpoint.json` after each chunk;
+//! `--resume` continues from it. Prints the report as JSON and exits 1 if any
+//! line is invalid or signed by a key outside the `--allow` list.
+
+use bulk_keychain::{
+    resume_verify_archive, verify_archive_with_checkpoint, ArchiveCheckpoint, ArchiveVerifyOptions,
+    Pubkey,
+};
+use std::collections::HashSet;
+use std::fs::File;
+use std::io::BufReader;
+
+fn main() -> bulk_keychain::Result<()> {
+    let mut archive = None;
+    let mut resume = false;
+    let mut allow = HashSet::new();
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--archive" => archive = args.next(),
+            "--resume" => resume = true,
+            "--allow" => {
+                let key = args.next().unwrap_or_default();
+                allow.insert(Pubkey::from_base58(&key)?);
+            }
+            other => {
+                eprintln!("unknown argument {}", other);
+                std::process::exit(2);
+            }
+        }
+    }
+    let Some(archive) = archive else {
+        eprintln!("usage: verify_archive --archive <file.jsonl> [--resume] [--allow <pubkey>]...");
+        std::process::exit(2);
+    };
+
+    let options = ArchiveVerifyOptions {
+        keys_allowlist: (!allow.is_empty()).then_some(allow),
+        ..Default::default()
+    };
+    let checkpoint_path = format!("{}.checkpoint.json", archive);
+    let save = |cp: &ArchiveCheckpoint| {
+        let json = cp.to_json().expect("checkpoint serializes");
+        if let Err(e) = std::fs::write(&checkpoint_path, json) {
+            eprintln!("could not save checkpoint: {}", e);
+        }
+        eprintln!("verified {} lines", cp.lines);
+    };
+
+    let io = |e: std::io::Error| bulk_keychain::Error::Archive(format!("{}: {}", archive, e));
+    let reader = BufReader::new(File::open(&archive).map_err(io)?);
+    let report = if resume {
+        let saved = std::fs::read_to_string(&checkpoint_path).map_err(io)?;
+        let checkpoint = ArchiveCheckpoint::from_json(&saved)?;
+        eprintln!("resuming after line {}", checkpoint.lines);
+        resume_verify_archive(reader, &options, &checkpoint, save)?
+    } else {
+        verify_archive_with_checkpoint(reader, &options, save)?
+    };
+
+    println!("{}", serde_json::to_string_pretty(&report)?);
+    if !report.invalid.is_empty() || !report.unknown_signers.is_empty() {
+        std::process::exit(1);
+    }
+    Ok(())
+}
@@ -3,17 +3,311 @@
 //! This module provides high-performance native bindings using NAPI-RS.
 //! It's significantly faster than pure JavaScript or WASM implementations.
 
+use bulk_keychain::nonce::check_nonce_range;
+use bulk_keychain::outbox::Outbox;
 use bulk_keychain::{
-    prepare_agent_wallet, prepare_all, prepare_create_sub_account, prepare_faucet, prepare_group,
-    prepare_message, prepare_remove_sub_account, prepare_rename_sub_account, prepare_transfer,
-    Cancel, CancelAll, CreateSubAccount, Hash, Keypair, Modify, NonceManager, NonceStrategy,
-    OnFill, OraclePrice, Order, OrderItem, OrderType, PreparedMessage, Pubkey, PythOraclePrice,
-    RangeOco, RenameSubAccount, Signer, Stop, TakeProfit, TimeInForce, TrailingStop, Transfer,
-    TransferKind, TriggerBasket, UserSettings,
+    flatten_position, prepare_agent_wallet, prepare_all, prepare_all_indexed, prepare_cancel,
+    prepare_cancel_all, prepare_cancel_replace, prepare_create_sub_account, prepare_faucet,
+    prepare_group, prepare_group_with_options, prepare_message, prepare_remove_sub_account,
+    prepare_rename_sub_account, prepare_transfer, prepare_user_settings, sign_and_submit,
+    verify_commitment, AgentDeriver, BatchBuilder, BatchEntry, BatchMode, Cancel, CreateSubAccount,
+    FlattenStyle, GroupKind, GroupLintWarning, GroupOptions, Hash, InputMode, Keypair, LintContext,
+    NonceAnomaly, NonceManager, NoncePlan, NonceReservation, NonceStrategy, OnFillSpec,
+    OraclePrice, OrderItem, OrderSpec, OrderTypeSpec, Parallelism, PositionSnapshot,
+    PreparedMessage, Pubkey, PythOraclePrice, ReconnectPolicy, RemoteSession, RenameSubAccount,
+    RngProvider, ScopeConfig, ScopedSigner, SessionResponder, SignOptions, Signer, SignerPool,
+    SymbolSpec, SymbolSpecs, TimestampMs, Transfer, TransferKind, Transport, TransportResponse,
+    TriggerCondition, UserSettings,
 };
 use napi::bindgen_prelude::*;
+use napi::threadsafe_function::{
+    ErrorStrategy, ThreadSafeCallContext, ThreadsafeFunction, ThreadsafeFunctionCallMode,
+};
+use napi::{JsFunction, JsUnknown, NapiRaw, NapiValue, Ref};
 use napi_derive::napi;
 use serde::Deserialize;
+use std::collections::HashMap;
+use std::ops::Deref;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Arc;
+
+// ============================================================================
+// Errors
+// ============================================================================
+
+/// `code` of errors thrown to JS: the variant name of the core error behind
+/// them (`'InvalidBase58'`, `'NonceOverflow'`, ...), or napi's status
+/// (`'GenericFailure'`) for errors raised by the binding itself.
+#[derive(Debug)]
+pub enum ErrorCode {
+    Keychain(&'static str),
+    Napi(Status),
+}
+
+impl AsRef<str> for ErrorCode {
+    fn as_ref(&self) -> &str {
+        match self {
+            Self::Keychain(code) => code,
+            Self::Napi(status) => status.as_ref(),
+        }
+    }
+}
+
+type Error = napi::Error<ErrorCode>;
+type Result<T> = napi::Result<T, ErrorCode>;
+
+/// `Error::from_reason` for the coded [`Error`].
+trait FromReason {
+    fn from_reason(reason: impl Into<String>) -> Self;
+}
+
+impl FromReason for Error {
+    fn from_reason(reason: impl Into<String>) -> Self {
+        Error::new(ErrorCode::Napi(Status::GenericFailure), reason.into())
+    }
+}
+
+/// Coded error for a core error.
+fn keychain_error(e: bulk_keychain::Error) -> Error {
+    Error::new(ErrorCode::Keychain(e.code()), e.to_string())
+}
+
+/// Coded error for a napi error.
+fn napi_error(e: napi::Error) -> Error {
+    Error::new(ErrorCode::Napi(e.status), e.reason)
+}
+
+/// Re-raise what a JS callback threw as is, rather than a copy of its
+/// message.
+fn rethrow(env: Env, e: napi::Error) -> Error {
+    unsafe { JsError::from(e).throw_into(env.raw()) };
+    Error::from_status(ErrorCode::Napi(Status::PendingException))
+}
+
+// ============================================================================
+// Diagnostics
+// ============================================================================
+
+static DIAGNOSTICS_ENABLED: AtomicBool = AtomicBool::new(false);
+static LIVE_KEYPAIRS: AtomicU64 = AtomicU64::new(0);
+static LIVE_SIGNERS: AtomicU64 = AtomicU64::new(0);
+static LIVE_PREPARED_MESSAGES: AtomicU64 = AtomicU64::new(0);
+static RETAINED_BYTES: AtomicU64 = AtomicU64::new(0);
+
+/// Counts one live native object, and the bytes it retains, until dropped.
+/// Only objects created while diagnostics are enabled are counted.
+#[derive(Debug)]
+struct Tracked {
+    live: &'static AtomicU64,
+    bytes: u64,
+}
+
+impl Tracked {
+    fn new(live: &'static AtomicU64, bytes: usize) -> Option<Self> {
+        if !DIAGNOSTICS_ENABLED.load(Ordering::Relaxed) {
+            return None;
+        }
+        live.fetch_add(1, Ordering::Relaxed);
+        RETAINED_BYTES.fetch_add(bytes as u64, Ordering::Relaxed);
+        Some(Self {
+            live,
+            bytes: bytes as u64,
+        })
+    }
+}
+
+impl Drop for Tracked {
+    fn drop(&mut self) {
+        self.live.fetch_sub(1, Ordering::Relaxed);
+        RETAINED_BYTES.fetch_sub(self.bytes, Ordering::Relaxed);
+    }
+}
+
+/// Live native objects, as counted since diagnostics were enabled
+#[napi(object)]
+pub struct DiagnosticsOutput {
+    pub enabled: bool,
+    pub live_keypairs: u32,
+    pub live_signers: u32,
+    /// Prepared messages whose `messageBytes` are still held by JS
+    pub live_prepared_messages: u32,
+    /// Native bytes held by counted objects' buffers
+    pub retained_bytes: f64,
+    /// Live copies of each secret key, counted whether or not diagnostics
+    /// are enabled
+    pub secret_census: Vec<SecretCensusOutput>,
+}
+
+/// Live copies of one secret key, and what holds them
+#[napi(object)]
+pub struct SecretCensusOutput {
+    pub fingerprint: String,
+    pub copies: u32,
+    /// Copies per purpose, such as "NativeSigner::new"
+    pub reasons: HashMap<String, u32>,
+}
+
+/// Start or stop counting native objects for `diagnostics`
+///
+/// Off by default. Objects created while it is off are never counted, so
+/// enable it before the workload you want to watch.
+#[napi]
+pub fn set_diagnostics_enabled(enabled: bool) {
+    DIAGNOSTICS_ENABLED.store(enabled, Ordering::Relaxed);
+}
+
+/// Counts of live keypairs, signers and prepared-message buffers
+///
+/// Objects are released when disposed or garbage collected, so counts that
+/// keep growing across steady-state work point at retained references.
+#[napi(js_name = "diagnostics")]
+pub fn get_diagnostics() -> DiagnosticsOutput {
+    let count = |c: &AtomicU64| c.load(Ordering::Relaxed) as u32;
+    DiagnosticsOutput {
+        enabled: DIAGNOSTICS_ENABLED.load(Ordering::Relaxed),
+        live_keypairs: count(&LIVE_KEYPAIRS),
+        live_signers: count(&LIVE_SIGNERS),
+        live_prepared_messages: count(&LIVE_PREPARED_MESSAGES),
+        retained_bytes: RETAINED_BYTES.load(Ordering::Relaxed) as f64,
+        secret_census: bulk_keychain::secret_census()
+            .into_iter()
+            .map(|entry| SecretCensusOutput {
+                fingerprint: entry.fingerprint,
+                copies: entry.copies as u32,
+                reasons: entry
+                    .reasons
+                    .into_iter()
+                    .map(|(reason, n)| (reason, n as u32))
+                    .collect(),
+            })
+            .collect(),
+    }
+}
+
+/// Message bytes handed to JS as an external `Buffer`, counted until the
+/// buffer is garbage collected
+#[derive(Debug)]
+pub struct MessageBuffer {
+    bytes: Vec<u8>,
+    _tracked: Option<Tracked>,
+}
+
+impl MessageBuffer {
+    fn new(bytes: Vec<u8>) -> Self {
+        let tracked = Tracked::new(&LIVE_PREPARED_MESSAGES, bytes.len());
+        Self {
+            bytes,
+            _tracked: tracked,
+        }
+    }
+}
+
+impl Deref for MessageBuffer {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        &self.bytes
+    }
+}
+
+impl TypeName for MessageBuffer {
+    fn type_name() -> &'static str {
+        "Buffer"
+    }
+
+    fn value_type() -> ValueType {
+        ValueType::Object
+    }
+}
+
+impl ToNapiValue for MessageBuffer {
+    unsafe fn to_napi_value(env: sys::napi_env, val: Self) -> napi::Result<sys::napi_value> {
+        unsafe extern "C" fn release(
+            _env: sys::napi_env,
+            _data: *mut std::ffi::c_void,
+            hint: *mut std::ffi::c_void,
+        ) {
+            drop(Box::from_raw(hint as *mut MessageBuffer));
+        }
+        if val.bytes.is_empty() {
+            return Buffer::to_napi_value(env, Buffer::from(Vec::new()));
+        }
+        let mut val = Box::new(val);
+        let (data, len) = (val.bytes.as_mut_ptr(), val.bytes.len());
+        let mut result = std::ptr::null_mut();
+        check_status!(sys::napi_create_external_buffer(
+            env,
+            len,
+            data.cast(),
+            Some(release),
+            Box::into_raw(val).cast(),
+            &mut result,
+        ))?;
+        Ok(result)
+    }
+}
+
+impl FromNapiValue for MessageBuffer {
+    unsafe fn from_napi_value(env: sys::napi_env, napi_val: sys::napi_value) -> napi::Result<Self> {
+        let buffer = Buffer::from_napi_value(env, napi_val)?;
+        Ok(Self {
+            bytes: buffer.to_vec(),
+            _tracked: None,
+        })
+    }
+}
+
+// ============================================================================
+// Nonces
+// ============================================================================
+
+/// `Number.MAX_SAFE_INTEGER`; larger numbers cannot hold every integer.
+const MAX_SAFE_INTEGER: u64 = (1 << 53) - 1;
+
+/// A nonce as JS sees it: a number up to `Number.MAX_SAFE_INTEGER`, or a
+/// BigInt for any u64.
+type JsNonce = Either<f64, BigInt>;
+
+fn nonce_in(nonce: JsNonce) -> Result<u64> {
+    match nonce {
+        Either::A(n) if n >= 0.0 && n.fract() == 0.0 && n <= MAX_SAFE_INTEGER as f64 => {
+            Ok(n as u64)
+        }
+        Either::A(n) => Err(Error::from_reason(format!(
+            "nonce {n} is not a safe integer; pass larger nonces as a BigInt"
+        ))),
+        Either::B(n) => match n.get_u64() {
+            (false, value, true) => Ok(value),
+            _ => Err(Error::from_reason("nonce BigInt must fit in a u64")),
+        },
+    }
+}
+
+fn nonce_opt(nonce: Option<JsNonce>) -> Result<Option<u64>> {
+    nonce.map(nonce_in).transpose()
+}
+
+/// Nonce strategy by its JS name.
+fn parse_nonce_strategy(strategy: &str) -> Result<NonceStrategy> {
+    match strategy {
+        "timestamp" => Ok(NonceStrategy::Timestamp),
+        "counter" => Ok(NonceStrategy::Counter),
+        "highFrequency" => Ok(NonceStrategy::TimestampWithCounter),
+        "random" => Ok(NonceStrategy::RandomWithTimestampFallback),
+        _ => Err(Error::from_reason(
+            "Invalid nonce strategy. Use 'timestamp', 'counter', 'highFrequency', or 'random'",
+        )),
+    }
+}
+
+/// A number when exact, a BigInt above `Number.MAX_SAFE_INTEGER`.
+fn nonce_out(nonce: u64) -> JsNonce {
+    if nonce <= MAX_SAFE_INTEGER {
+        Either::A(nonce as f64)
+    } else {
+        Either::B(BigInt::from(nonce))
+    }
+}
 
 // ============================================================================
 // Keypair
@@ -23,6 +317,31 @@ use serde::Deserialize;
 #[napi]
 pub struct NativeKeypair {
     inner: Keypair,
+    tracked: Option<Tracked>,
+}
+
+impl NativeKeypair {
+    fn from_keypair(inner: Keypair) -> Self {
+        Self {
+            inner,
+            tracked: Tracked::new(&LIVE_KEYPAIRS, 0),
+        }
+    }
+}
+
+/// `NativeKeypair.generateMnemonic` result
+#[napi(object)]
+pub struct GeneratedMnemonic {
+    pub keypair: ClassInstance<NativeKeypair>,
+    /// 24 words; the only backup of the key
+    pub mnemonic: String,
+}
+
+/// Wipe the secret as soon as the JS object is collected.
+impl Drop for NativeKeypair {
+    fn drop(&mut self) {
+        self.inner.zeroize();
+    }
 }
 
 #[napi]
@@ -30,23 +349,61 @@ impl NativeKeypair {
     /// Generate a new random keypair
     #[napi(constructor)]
     pub fn new() -> Self {
-        Self {
-            inner: Keypair::generate(),
-        }
+        Self::from_keypair(Keypair::generate())
     }
 
     /// Create from base58-encoded secret key or full keypair
     #[napi(factory)]
     pub fn from_base58(s: String) -> Result<Self> {
-        let inner = Keypair::from_base58(&s).map_err(|e| Error::from_reason(e.to_string()))?;
-        Ok(Self { inner })
+        let inner = Keypair::from_base58(&s).map_err(keychain_error)?;
+        Ok(Self::from_keypair(inner))
     }
 
     /// Create from raw bytes (32-byte secret or 64-byte full keypair)
     #[napi(factory)]
     pub fn from_bytes(bytes: Buffer) -> Result<Self> {
-        let inner = Keypair::from_bytes(&bytes).map_err(|e| Error::from_reason(e.to_string()))?;
-        Ok(Self { inner })
+        let inner = Keypair::from_bytes(&bytes).map_err(keychain_error)?;
+        Ok(Self::from_keypair(inner))
+    }
+
+    /// Derive from a 12- or 24-word English BIP39 seed phrase at
+    /// `derivationPath` (default `m/44'/501'/0'/0'`, Phantom's first account)
+    #[napi(factory)]
+    pub fn from_mnemonic(
+        mnemonic: String,
+        passphrase: Option<String>,
+        derivation_path: Option<String>,
+    ) -> Result<Self> {
+        let inner = Keypair::from_mnemonic(
+            &mnemonic,
+            passphrase.as_deref().unwrap_or(""),
+            derivation_path
+                .as_deref()
+                .unwrap_or(bulk_keychain::mnemonic::SOLANA_DERIVATION_PATH),
+        )
+        .map_err(keychain_error)?;
+        Ok(Self::from_keypair(inner))
+    }
+
+    /// Keypair whose secret is the first 32 bytes of a BIP39 phrase's seed,
+    /// as `solana-keygen recover` without a derivation path
+    #[napi(factory)]
+    pub fn from_mnemonic_seed(mnemonic: String, passphrase: Option<String>) -> Result<Self> {
+        let inner = Keypair::from_mnemonic_seed(&mnemonic, passphrase.as_deref().unwrap_or(""))
+            .map_err(keychain_error)?;
+        Ok(Self::from_keypair(inner))
+    }
+
+    /// Generate a keypair and the 24-word seed phrase it derives from
+    #[napi]
+    pub fn generate_mnemonic(env: Env) -> Result<GeneratedMnemonic> {
+        let (inner, mnemonic) = Keypair::generate_mnemonic();
+        Ok(GeneratedMnemonic {
+            keypair: Self::from_keypair(inner)
+                .into_instance(env)
+                .map_err(napi_error)?,
+            mnemonic,
+        })
     }
 
     /// Get the public key as base58 string
@@ -73,12 +430,44 @@ impl NativeKeypair {
         Buffer::from(self.inner.secret_key().to_vec())
     }
 
+    /// Short display-safe identifier (`bk1:...`)
+    #[napi]
+    pub fn fingerprint(&self) -> String {
+        self.inner.fingerprint()
+    }
+
+    /// Derive the SLIP-0010 child at `index`; `hardened` must be true
+    #[napi]
+    pub fn derive_child(&self, index: u32, hardened: bool) -> Result<Self> {
+        let inner = self
+            .inner
+            .derive_child(index, hardened)
+            .map_err(keychain_error)?;
+        Ok(Self::from_keypair(inner))
+    }
+
+    /// Derive the key at a hardened path like `m/44'/501'/0'/0'`, where `m`
+    /// is this keypair
+    #[napi]
+    pub fn from_derivation_path(&self, path: String) -> Result<Self> {
+        let inner = self
+            .inner
+            .from_derivation_path(&path)
+            .map_err(keychain_error)?;
+        Ok(Self::from_keypair(inner))
+    }
+
     /// Clone the keypair
     #[napi]
     pub fn clone_keypair(&self) -> Self {
-        Self {
-            inner: self.inner.clone(),
-        }
+        Self::from_keypair(self.inner.clone_tracked("NativeKeypair::cloneKeypair"))
+    }
+
+    /// Stop counting this keypair in `diagnostics`. The key itself is
+    /// freed when the object is garbage collected.
+    #[napi]
+    pub fn dispose(&mut self) {
+        self.tracked = None;
     }
 }
 
@@ -93,9 +482,68 @@ impl Default for NativeKeypair {
 // ============================================================================
 
 /// High-performance transaction signer
-#[napi]
+#[napi(custom_finalize)]
 pub struct NativeSigner {
     inner: Signer,
+    pre_sign_hook: Option<Ref<()>>,
+    input_mode: InputMode,
+    tracked: Option<Tracked>,
+}
+
+impl ObjectFinalize for NativeSigner {
+    fn finalize(mut self, env: Env) -> napi::Result<()> {
+        if let Some(mut hook) = self.pre_sign_hook.take() {
+            hook.unref(env)?;
+        }
+        Ok(())
+    }
+}
+
+impl NativeSigner {
+    /// Nonce to sign under, resolved up front when a hook needs to see it.
+    fn hooked_nonce(&self, nonce: Option<JsNonce>) -> Result<Option<u64>> {
+        match (nonce, &self.pre_sign_hook) {
+            (Some(n), _) => Ok(Some(nonce_in(n)?)),
+            (None, Some(_)) => self.inner.next_nonce().map(Some).map_err(keychain_error),
+            (None, None) => Ok(None),
+        }
+    }
+
+    fn hooked_base_nonce(&self, base_nonce: Option<JsNonce>) -> Result<Option<u64>> {
+        Ok(match (base_nonce, &self.pre_sign_hook) {
+            (Some(n), _) => Some(nonce_in(n)?),
+            (None, Some(_)) => Some(self.inner.now_ms()),
+            (None, None) => None,
+        })
+    }
+
+    /// Call the JS hook with `(order, nonce)`; a throw aborts signing.
+    fn run_pre_sign_hook(&self, env: Env, order: &OrderInput, nonce: u64) -> Result<()> {
+        let Some(hook) = &self.pre_sign_hook else {
+            return Ok(());
+        };
+        let hook: JsFunction = env.get_reference_value(hook).map_err(napi_error)?;
+        let order = unsafe {
+            let raw = OrderInput::to_napi_value(env.raw(), order.clone()).map_err(napi_error)?;
+            JsUnknown::from_raw_unchecked(env.raw(), raw)
+        };
+        let nonce = unsafe {
+            let raw = JsNonce::to_napi_value(env.raw(), nonce_out(nonce)).map_err(napi_error)?;
+            JsUnknown::from_raw_unchecked(env.raw(), raw)
+        };
+        hook.call(None, &[order, nonce])
+            .map_err(|e| rethrow(env, e))?;
+        Ok(())
+    }
+
+    /// [`Self::run_pre_sign_hook`] for each order of a batch from `base`.
+    fn run_pre_sign_hook_all(&self, env: Env, orders: &[OrderInput], base: u64) -> Result<()> {
+        check_nonce_range(base, orders.len()).map_err(keychain_error)?;
+        for (i, order) in orders.iter().enumerate() {
+            self.run_pre_sign_hook(env, order, base + i as u64)?;
+        }
+        Ok(())
+    }
 }
 
 #[napi]
@@ -104,436 +552,1993 @@ impl NativeSigner {
     #[napi(constructor)]
     pub fn new(keypair: &NativeKeypair) -> Self {
         Self {
-            inner: Signer::new(keypair.inner.clone()),
+            inner: Signer::new(keypair.inner.clone_tracked("NativeSigner::new")),
+            pre_sign_hook: None,
+            input_mode: InputMode::Tolerant,
+            tracked: Tracked::new(&LIVE_SIGNERS, 0),
         }
     }
 
     /// Create a signer from base58-encoded secret key
     #[napi(factory)]
     pub fn from_base58(s: String) -> Result<Self> {
-        let keypair = Keypair::from_base58(&s).map_err(|e| Error::from_reason(e.to_string()))?;
+        let keypair = Keypair::from_base58(&s).map_err(keychain_error)?;
         Ok(Self {
             inner: Signer::new(keypair),
+            pre_sign_hook: None,
+            input_mode: InputMode::Tolerant,
+            tracked: Tracked::new(&LIVE_SIGNERS, 0),
+        })
+    }
+
+    /// Create a signer that trades on behalf of `account` (agent wallet pattern)
+    #[napi(factory)]
+    pub fn from_keypair_and_account(keypair: &NativeKeypair, account: String) -> Result<Self> {
+        let account = Pubkey::from_base58(&account).map_err(keychain_error)?;
+        Ok(Self {
+            inner: Signer::new(keypair.inner.clone_tracked("NativeSigner::for_account"))
+                .with_account(account),
+            pre_sign_hook: None,
+            input_mode: InputMode::Tolerant,
+            tracked: Tracked::new(&LIVE_SIGNERS, 0),
         })
     }
 
     /// Create a signer with nonce management
     #[napi(factory)]
     pub fn with_nonce_manager(keypair: &NativeKeypair, strategy: String) -> Result<Self> {
-        let nonce_strategy = match strategy.as_str() {
-            "timestamp" => NonceStrategy::Timestamp,
-            "counter" => NonceStrategy::Counter,
-            "highFrequency" => NonceStrategy::TimestampWithCounter,
-            _ => {
-                return Err(Error::from_reason(
-                    "Invalid nonce strategy. Use 'timestamp', 'counter', or 'highFrequency'",
-                ))
-            }
+        let nonce_manager = NonceManager::new(parse_nonce_strategy(&strategy)?);
+        Ok(Self {
+            inner: Signer::with_nonce_manager(
+                keypair
+                    .inner
+                    .clone_tracked("NativeSigner::withNonceManager"),
+                nonce_manager,
+            ),
+            pre_sign_hook: None,
+            input_mode: InputMode::Tolerant,
+            tracked: Tracked::new(&LIVE_SIGNERS, 0),
+        })
+    }
+
+    /// Create a signer from a 12- or 24-word BIP39 phrase, derived at
+    /// `derivationPath` (e.g. `m/44'/501'/0'/0'`), with nonce management if
+    /// `strategy` is given
+    #[napi(factory)]
+    pub fn from_mnemonic(
+        phrase: String,
+        derivation_path: String,
+        strategy: Option<String>,
+    ) -> Result<Self> {
+        let keypair =
+            Keypair::from_mnemonic(&phrase, "", &derivation_path).map_err(keychain_error)?;
+        let inner = match strategy {
+            Some(strategy) => Signer::with_nonce_manager(
+                keypair,
+                NonceManager::new(parse_nonce_strategy(&strategy)?),
+            ),
+            None => Signer::new(keypair),
         };
-        let nonce_manager = NonceManager::new(nonce_strategy);
         Ok(Self {
-            inner: Signer::with_nonce_manager(keypair.inner.clone(), nonce_manager),
+            inner,
+            pre_sign_hook: None,
+            input_mode: InputMode::Tolerant,
+            tracked: Tracked::new(&LIVE_SIGNERS, 0),
         })
     }
 
-    /// Get the signer's public key
+    /// Get the signer's public key (alias for signerPubkey)
     #[napi(getter)]
     pub fn pubkey(&self) -> String {
-        self.inner.pubkey().to_base58()
+        self.signer_pubkey()
     }
 
-    /// Enable/disable single-order ID computation.
-    #[napi(js_name = setComputeOrderId)]
-    pub fn set_compute_order_id(&mut self, enabled: bool) {
-        self.inner.set_order_id(enabled);
-    }
-
-    /// Enable/disable batch order ID computation for multi-order transactions.
-    #[napi(js_name = setComputeBatchOrderIds)]
-    pub fn set_compute_batch_order_ids(&mut self, enabled: bool) {
-        self.inner.set_batch_order_ids(enabled);
+    /// Public key of the signing keypair
+    #[napi]
+    pub fn signer_pubkey(&self) -> String {
+        self.inner.signer_pubkey().to_base58()
     }
 
-    /// Whether single-order ID computation is enabled.
-    #[napi(js_name = computesOrderId)]
-    pub fn computes_order_id(&self) -> bool {
-        self.inner.computes_order_id()
+    /// Public key of the trading account
+    #[napi]
+    pub fn account_pubkey(&self) -> String {
+        self.inner.account_pubkey().to_base58()
     }
 
-    /// Whether batch order ID computation is enabled.
-    #[napi(js_name = computesBatchOrderIds)]
-    pub fn computes_batch_order_ids(&self) -> bool {
-        self.inner.computes_batch_order_ids()
+    /// Short display-safe identifier (`bk1:...`) of the signing key
+    #[napi]
+    pub fn fingerprint(&self) -> String {
+        self.inner.signer_pubkey().fingerprint()
     }
 
-    // ========================================================================
-    // Simplified API
-    // ========================================================================
-
-    /// Sign a single order/cancel/cancelAll
+    /// Derive a restricted signer sharing this signer's key and nonces
     ///
-    /// Most common use case - returns a single signed transaction.
+    /// The scoped signer rejects anything outside `config`, and its own
+    /// `scoped()` can only narrow it further. The pre-sign hook is not carried
+    /// over.
     ///
     /// @example
     /// ```typescript
-    /// const signed = signer.sign({ type: 'order', symbol: 'BTC-USD', isBuy: true, price: 100000, size: 0.1 });
+    /// const bot = signer.scoped({ symbols: ['BTC-USD'], maxSize: 0.5, maxSignatures: 100 });
+    /// bot.sign(order);
     /// ```
     #[napi]
-    pub fn sign(
-        &mut self,
-        order: OrderInput,
-        nonce: Option<f64>,
-    ) -> Result<SignedTransactionOutput> {
-        let order_item: OrderItem = order.try_into()?;
-        let nonce_val = nonce.map(|n| n as u64);
+    pub fn scoped(&self, config: ScopeConfigInput) -> NativeScopedSigner {
+        NativeScopedSigner {
+            inner: self.inner.scoped(config.into()),
+        }
+    }
 
-        let signed = self
-            .inner
-            .sign(order_item, nonce_val)
-            .map_err(|e| Error::from_reason(e.to_string()))?;
+    /// Shift timestamp nonces by `offsetMs`, e.g. the measured skew between the
+    /// local clock and the exchange's
+    ///
+    /// Every timestamp nonce becomes `Date.now() + offsetMs`; counter nonces
+    /// are unaffected.
+    #[napi]
+    pub fn set_clock_offset_ms(&mut self, offset_ms: i64) {
+        self.inner.set_clock_offset_ms(offset_ms);
+    }
 
-        Ok(signed.into())
+    /// Current clock offset applied to timestamp nonces
+    #[napi]
+    pub fn get_clock_offset_ms(&self) -> i64 {
+        self.inner.clock_offset_ms()
     }
 
-    /// Sign multiple orders - each becomes its own transaction (parallel)
+    /// Call `callback(anomaly)` when the nonce clock misbehaves, e.g. an NTP
+    /// step backwards larger than a second; pass null to remove it
     ///
-    /// each order gets independent confirmation/rejection.
-    /// Automatically parallelizes when > 10 orders.
+    /// Fires once per regression episode, asynchronously on the event loop.
+    /// Signing keeps working: nonces stay monotonic regardless.
     ///
     /// @example
     /// ```typescript
-    /// const orders = [order1, order2, order3];
-    /// const signedTxs = signer.signAll(orders); // Returns SignedTransaction[]
+    /// signer.onNonceAnomaly((a) => pager.alert(`clock went back to ${a.observedMs}`));
     /// ```
     #[napi]
-    pub fn sign_all(
-        &self,
-        orders: Vec<OrderInput>,
-        base_nonce: Option<f64>,
-    ) -> Result<Vec<SignedTransactionOutput>> {
-        let order_items: Result<Vec<OrderItem>> =
-            orders.into_iter().map(|o| o.try_into()).collect();
-        let order_items = order_items?;
+    pub fn on_nonce_anomaly(&mut self, env: Env, callback: Option<JsFunction>) -> Result<()> {
+        let Some(callback) = callback else {
+            self.inner.set_on_nonce_anomaly(None);
+            return Ok(());
+        };
+        let mut tsfn: ThreadsafeFunction<NonceAnomaly, ErrorStrategy::Fatal> = callback
+            .create_threadsafe_function(0, |ctx: ThreadSafeCallContext<NonceAnomaly>| {
+                Ok(vec![NonceAnomalyOutput::from(ctx.value)])
+            })
+            .map_err(napi_error)?;
+        // Don't keep the process alive just to deliver alerts.
+        tsfn.unref(&env).map_err(napi_error)?;
+        self.inner
+            .set_on_nonce_anomaly(Some(Arc::new(move |anomaly: &NonceAnomaly| {
+                tsfn.call(*anomaly, ThreadsafeFunctionCallMode::NonBlocking);
+            })));
+        Ok(())
+    }
 
-        let base = base_nonce.map(|n| n as u64);
-        let signed = self
-            .inner
-            .sign_all(order_items, base)
-            .map_err(|e| Error::from_reason(e.to_string()))?;
+    /// Clock anomalies recorded by the nonce manager, oldest first
+    #[napi]
+    pub fn nonce_anomalies(&self) -> Vec<NonceAnomalyOutput> {
+        self.inner
+            .nonce_anomalies()
+            .into_iter()
+            .map(Into::into)
+            .collect()
+    }
 
-        Ok(signed.into_iter().map(Into::into).collect())
+    /// The nonce the next auto-nonced call would use, without taking it
+    #[napi(ts_return_type = "number | bigint")]
+    pub fn peek_next_nonce(&self) -> Result<JsNonce> {
+        self.inner
+            .peek_next_nonce()
+            .map(nonce_out)
+            .map_err(keychain_error)
     }
 
-    /// Sign multiple orders atomically in ONE transaction
+    /// Reset the nonce manager; `counter` and `highFrequency` start from zero
+    #[napi]
+    pub fn reset_nonce(&self) {
+        self.inner.reset_nonce();
+    }
+
+    /// Cap resting orders per symbol (`undefined` removes the cap and resets counts)
     ///
-    /// Use for bracket orders (entry + stop loss + take profit) where
-    /// all orders must succeed or fail together.
+    /// Signing past the cap throws. Report fills and exchange-side cancels
+    /// with `notifyClosed`.
+    #[napi]
+    pub fn set_max_open_orders_per_symbol(&mut self, cap: Option<u32>) {
+        self.inner
+            .set_max_open_orders_per_symbol(cap.map(|c| c as usize));
+    }
+
+    /// Refuse orders whose `clientId` a live order signed here already holds
     ///
-    /// @example
-    /// ```typescript
-    /// const bracket = [entryOrder, stopLoss, takeProfit];
-    /// const signed = signer.signGroup(bracket); // Returns single SignedTransaction
-    /// ```
+    /// Signing one throws `client id <id> in use by live order <orderId>`.
+    /// Signed cancels and `notifyClosed` release IDs; pass
+    /// `{ allowClientIdReuse: true }` to reuse one on purpose.
     #[napi]
-    pub fn sign_group(
-        &mut self,
-        orders: Vec<OrderInput>,
-        nonce: Option<f64>,
+    pub fn set_unique_client_ids(&mut self, enabled: bool) {
+        self.inner.set_unique_client_ids(enabled);
+    }
+
+    #[napi]
+    pub fn unique_client_ids(&self) -> bool {
+        self.inner.unique_client_ids()
+    }
+
+    /// Sign orders whose `clientId` is the all-zero hash
+    /// (`11111111111111111111111111111111`); refused by default, since it is
+    /// usually a defaulted field rather than a chosen ID
+    #[napi]
+    pub fn set_allow_zero_client_id(&mut self, allowed: bool) {
+        self.inner.set_allow_zero_client_id(allowed);
+    }
+
+    /// Live order (base58 ID) holding `clientId`, if any
+    #[napi]
+    pub fn client_id_order(&self, client_id: String) -> Result<Option<String>> {
+        let client_id = Hash::from_base58(&client_id).map_err(keychain_error)?;
+        Ok(self
+            .inner
+            .client_id_order(&client_id)
+            .map(|id| id.to_base58()))
+    }
+
+    /// Stop counting an order (base58 ID) as open and release its client ID;
+    /// returns whether either was tracked
+    #[napi]
+    pub fn notify_closed(&self, order_id: String) -> Result<bool> {
+        let order_id = Hash::from_base58(&order_id).map_err(keychain_error)?;
+        Ok(self.inner.notify_closed(&order_id))
+    }
+
+    /// Orders counted as open on `symbol`
+    #[napi]
+    pub fn open_order_count(&self, symbol: String) -> u32 {
+        self.inner.open_order_count(&symbol) as u32
+    }
+
+    /// Enable/disable single-order ID computation.
+    #[napi(js_name = setComputeOrderId)]
+    pub fn set_compute_order_id(&mut self, enabled: bool) {
+        self.inner.set_order_id(enabled);
+    }
+
+    /// Enable/disable batch order ID computation for multi-order transactions.
+    #[napi(js_name = setComputeBatchOrderIds)]
+    pub fn set_compute_batch_order_ids(&mut self, enabled: bool) {
+        self.inner.set_batch_order_ids(enabled);
+    }
+
+    /// Whether single-order ID computation is enabled.
+    #[napi(js_name = computesOrderId)]
+    pub fn computes_order_id(&self) -> bool {
+        self.inner.computes_order_id()
+    }
+
+    /// Whether batch order ID computation is enabled.
+    #[napi(js_name = computesBatchOrderIds)]
+    pub fn computes_batch_order_ids(&self) -> bool {
+        self.inner.computes_batch_order_ids()
+    }
+
+    /// Label the network this signer is meant for (`undefined` clears it);
+    /// reported by `capabilities()` only
+    #[napi]
+    pub fn set_network(&mut self, network: Option<String>) {
+        self.inner.set_network(network);
+    }
+
+    /// What this signer may sign: account, signer, network, policySummary,
+    /// scopes, supportedActions and features
+    #[napi]
+    pub fn capabilities(&self) -> Result<serde_json::Value> {
+        capabilities_json(&self.inner.capabilities())
+    }
+
+    /// `capabilities()` as JSON signed with this signer's key; check it with
+    /// `verifyCapabilities`
+    #[napi]
+    pub fn capabilities_signed(&self) -> Result<SignedCapabilitiesOutput> {
+        self.inner
+            .capabilities_signed()
+            .map(Into::into)
+            .map_err(keychain_error)
+    }
+
+    /// Transactions signed and deadline rejections since the signer was
+    /// created, and how the latest batch was signed
+    #[napi]
+    pub fn usage(&self) -> SignerUsageOutput {
+        let usage = self.inner.usage();
+        let (last_batch_mode, last_batch_threads) = match usage.last_batch_mode {
+            None => (None, None),
+            Some(BatchMode::Serial) => (Some("serial".to_string()), Some(1)),
+            Some(BatchMode::Parallel { threads }) => {
+                (Some("parallel".to_string()), Some(threads as u32))
+            }
+        };
+        SignerUsageOutput {
+            signed: usage.signed as f64,
+            deadline_rejections: usage.deadline_rejections as f64,
+            last_batch_mode,
+            last_batch_threads,
+            reconnects: usage.reconnects as f64,
+        }
+    }
+
+    /// Reset state that would refuse resubmitted transactions after a
+    /// gateway reconnect, counted in `usage().reconnects`
+    ///
+    /// `clearOpenOrders` forgets the orders counted against the open-order
+    /// cap, resetting it for every symbol, `clearDedupe` releases held client IDs, and `bumpNonceFloor`
+    /// raises auto nonces to at least the current time.
+    #[napi]
+    pub fn notify_reconnect(&self, policy: ReconnectPolicyInput) -> Result<ReconnectReportOutput> {
+        let policy = ReconnectPolicy {
+            clear_open_orders: policy.clear_open_orders.unwrap_or(false),
+            clear_dedupe: policy.clear_dedupe.unwrap_or(false),
+            bump_nonce_floor: policy.bump_nonce_floor.unwrap_or(false),
+        };
+        let report = self
+            .inner
+            .notify_reconnect(policy)
+            .map_err(keychain_error)?;
+        Ok(ReconnectReportOutput {
+            open_orders_cleared: report.open_orders_cleared as u32,
+            client_ids_cleared: report.client_ids_cleared as u32,
+            nonce_floor: report.nonce_floor.map(nonce_out),
+        })
+    }
+
+    /// Pin how batches use threads: `undefined` picks by available cores,
+    /// `1` always signs serially, `n` uses `n` workers. Batches fall back to
+    /// serial if worker threads cannot be spawned.
+    #[napi]
+    pub fn set_parallelism(&mut self, threads: Option<u32>) {
+        self.inner.set_parallelism(match threads {
+            None => Parallelism::Auto,
+            Some(n) => Parallelism::Threads(n as usize),
+        });
+    }
+
+    /// TEST ONLY: draw `randomClientId()` from a generator seeded with
+    /// `seed`, so a simulation replays exactly; `undefined` goes back to the
+    /// OS RNG. Never call this on a signer that trades for real. Keys are
+    /// not affected.
+    #[napi]
+    pub fn set_deterministic_seed(&mut self, seed: Option<f64>) {
+        self.inner.set_rng(match seed {
+            Some(seed) => RngProvider::seeded(seed as u64),
+            None => RngProvider::os(),
+        });
+    }
+
+    /// A fresh client order ID (base58) from this signer's RNG
+    #[napi]
+    pub fn random_client_id(&self) -> String {
+        self.inner.random_client_id().to_base58()
+    }
+
+    // ========================================================================
+    // Simplified API
+    // ========================================================================
+
+    /// Sign a single order/cancel/cancelAll
+    ///
+    /// Most common use case - returns a single signed transaction.
+    ///
+    /// @example
+    /// ```typescript
+    /// const signed = signer.sign({ type: 'order', symbol: 'BTC-USD', isBuy: true, price: 100000, size: 0.1 });
+    /// ```
+    #[napi]
+    pub fn sign(
+        &mut self,
+        env: Env,
+        #[napi(ts_arg_type = "OrderInput")] order: serde_json::Value,
+        nonce: Option<JsNonce>,
+        options: Option<SignOptionsInput>,
+    ) -> Result<SignedTransactionOutput> {
+        let order = parse_order_input(order, self.input_mode)?;
+        let nonce_val = self.hooked_nonce(nonce)?;
+        if let Some(n) = nonce_val {
+            self.run_pre_sign_hook(env, &order, n)?;
+        }
+        let order_item: OrderItem = order.try_into()?;
+
+        let signed = self
+            .inner
+            .sign_with_options(order_item, nonce_val, &sign_options(options)?)
+            .map_err(keychain_error)?;
+
+        Ok(signed.into())
+    }
+
+    /// Sign a single order and submit it through a callback transport
+    ///
+    /// `submit` receives the request JSON and returns `{ status, body }`;
+    /// if it throws, the error is rethrown as a transport error.
+    ///
+    /// @example
+    /// ```typescript
+    /// const { status } = signer.signAndSubmit(order, undefined, (body) => mock.post(body));
+    /// ```
+    #[napi]
+    pub fn sign_and_submit(
+        &mut self,
+        env: Env,
+        #[napi(ts_arg_type = "OrderInput")] order: serde_json::Value,
+        nonce: Option<JsNonce>,
+        submit: JsFunction,
+    ) -> Result<SubmitOutput> {
+        let order = parse_order_input(order, self.input_mode)?;
+        let nonce_val = self.hooked_nonce(nonce)?;
+        if let Some(n) = nonce_val {
+            self.run_pre_sign_hook(env, &order, n)?;
+        }
+        let order_item: OrderItem = order.try_into()?;
+
+        let transport = CallbackTransport { env, submit };
+        let (signed, response) =
+            sign_and_submit(&mut self.inner, order_item, nonce_val, &transport)
+                .map_err(keychain_error)?;
+        Ok(SubmitOutput {
+            transaction: signed.into(),
+            status: response.status as u32,
+            body: response.body,
+        })
+    }
+
+    /// Sign multiple orders - each becomes its own transaction (parallel)
+    ///
+    /// each order gets independent confirmation/rejection.
+    /// Automatically parallelizes when > 10 orders.
+    ///
+    /// @example
+    /// ```typescript
+    /// const orders = [order1, order2, order3];
+    /// const signedTxs = signer.signAll(orders); // Returns SignedTransaction[]
+    /// ```
+    ///
+    /// With `options.deadline`, orders are signed in sequence and the call
+    /// throws as soon as the deadline passes, naming how many were signed.
+    #[napi]
+    pub fn sign_all(
+        &self,
+        env: Env,
+        #[napi(ts_arg_type = "Array<OrderInput>")] orders: Vec<serde_json::Value>,
+        base_nonce: Option<JsNonce>,
+        options: Option<SignOptionsInput>,
+    ) -> Result<Vec<SignedTransactionOutput>> {
+        let orders = parse_order_inputs(orders, self.input_mode)?;
+        let base = self.hooked_base_nonce(base_nonce)?;
+        if let Some(base) = base {
+            self.run_pre_sign_hook_all(env, &orders, base)?;
+        }
+        let order_items: Result<Vec<OrderItem>> =
+            orders.into_iter().map(|o| o.try_into()).collect();
+        let order_items = order_items?;
+
+        let signed = self
+            .inner
+            .sign_all_with_options(order_items, base, &sign_options(options)?)
+            .map_err(keychain_error)?;
+
+        Ok(signed.into_iter().map(Into::into).collect())
+    }
+
+    /// `signAll` off the JS thread: signing runs on the libuv threadpool and
+    /// the promise resolves with the transactions
+    ///
+    /// Nonces `baseNonce + i` (the current time if omitted) and the pre-sign
+    /// hook are settled before this returns, so batches queued one after
+    /// another keep their order. Prefer `signAll` for small batches.
+    ///
+    /// @example
+    /// ```typescript
+    /// const signedTxs = await signer.signAllAsync(orders);
+    /// ```
+    #[napi(ts_return_type = "Promise<Array<SignedTransactionOutput>>")]
+    pub fn sign_all_async(
+        &self,
+        env: Env,
+        #[napi(ts_arg_type = "Array<OrderInput>")] orders: Vec<serde_json::Value>,
+        base_nonce: Option<JsNonce>,
+        options: Option<SignOptionsInput>,
+    ) -> Result<AsyncTask<SignAllTask>> {
+        let orders = parse_order_inputs(orders, self.input_mode)?;
+        let base = match nonce_opt(base_nonce)? {
+            Some(base) => base,
+            None => self.inner.now_ms(),
+        };
+        self.run_pre_sign_hook_all(env, &orders, base)?;
+        let items = orders
+            .into_iter()
+            .map(|o| o.try_into())
+            .collect::<Result<Vec<OrderItem>>>()?;
+        Ok(AsyncTask::new(SignAllTask {
+            signer: self.inner.share(),
+            items,
+            base,
+            options: sign_options(options)?,
+            error_code: None,
+        }))
+    }
+
+    /// Sign the reduce-only orders that flatten a reported position
+    ///
+    /// Each order is its own transaction with nonce `baseNonce + i`; a flat
+    /// position signs nothing. `style` is `{ type: 'market' }` or
+    /// `{ type: 'limit', offsetBps }`, and `specs` maps symbols to
+    /// `{ spec?, maxOrderSize? }`.
+    ///
+    /// @example
+    /// ```typescript
+    /// const position = { symbol: 'BTC-USD', size: -2.5, markPrice: 100000 };
+    /// const specs = { 'BTC-USD': { spec: { priceDecimals: 1, sizeDecimals: 3 }, maxOrderSize: 1 } };
+    /// const txs = signer.signFlatten(position, { type: 'limit', offsetBps: 20 }, specs);
+    /// ```
+    #[napi]
+    pub fn sign_flatten(
+        &self,
+        env: Env,
+        position: PositionInput,
+        style: FlattenStyleInput,
+        specs: Option<HashMap<String, FlattenSpecInput>>,
+        base_nonce: Option<JsNonce>,
+    ) -> Result<Vec<SignedTransactionOutput>> {
+        let orders = flatten_position(&position.into(), style.try_into()?, &flatten_specs(specs))
+            .map_err(keychain_error)?;
+        let items: Vec<OrderItem> = orders.into_iter().map(OrderItem::from).collect();
+        let base = self.hooked_base_nonce(base_nonce)?;
+        if let Some(base) = base {
+            check_nonce_range(base, items.len()).map_err(keychain_error)?;
+            for (i, item) in items.iter().enumerate() {
+                let order: OrderInput = serde_json::from_value(item.to_input_json())
+                    .map_err(|e| Error::from_reason(e.to_string()))?;
+                self.run_pre_sign_hook(env, &order, base + i as u64)?;
+            }
+        }
+
+        let signed = self.inner.sign_all(items, base).map_err(keychain_error)?;
+
+        Ok(signed.into_iter().map(Into::into).collect())
+    }
+
+    /// Like signAll, but calls `onSigned(index, tx)` as soon as each order is signed
+    ///
+    /// Orders are signed one at a time, in input order, so the first transaction
+    /// can go out on the wire before the rest are signed. For raw throughput on
+    /// large batches prefer signAll, which signs in parallel. If `onSigned` throws,
+    /// it is not called again and the error is rethrown once signing finishes.
+    ///
+    /// @example
+    /// ```typescript
+    /// signer.signAllWithPostHook(orders, undefined, (i, tx) => ws.send(JSON.stringify(tx)));
+    /// ```
+    #[napi]
+    pub fn sign_all_with_post_hook(
+        &self,
+        env: Env,
+        #[napi(ts_arg_type = "Array<OrderInput>")] orders: Vec<serde_json::Value>,
+        base_nonce: Option<JsNonce>,
+        on_signed: JsFunction,
+    ) -> Result<Vec<SignedTransactionOutput>> {
+        let orders = parse_order_inputs(orders, self.input_mode)?;
+        let base = self.hooked_base_nonce(base_nonce)?;
+        if let Some(base) = base {
+            self.run_pre_sign_hook_all(env, &orders, base)?;
+        }
+        let order_items: Result<Vec<OrderItem>> =
+            orders.into_iter().map(|o| o.try_into()).collect();
+        let order_items = order_items?;
+
+        let mut hook_error = None;
+        let signed = self
+            .inner
+            .sign_all_with_post_hook(order_items, base, |i, tx| {
+                if hook_error.is_some() {
+                    return;
+                }
+                let result = (|| {
+                    let index = env.create_uint32(i as u32)?.into_unknown();
+                    let tx = unsafe {
+                        let raw = SignedTransactionOutput::to_napi_value(
+                            env.raw(),
+                            SignedTransactionOutput::from(tx.clone()),
+                        )?;
+                        JsUnknown::from_raw_unchecked(env.raw(), raw)
+                    };
+                    on_signed.call(None, &[index, tx]).map(|_| ())
+                })();
+                hook_error = result.err();
+            })
+            .map_err(keychain_error)?;
+        if let Some(e) = hook_error {
+            return Err(rethrow(env, e));
+        }
+
+        Ok(signed.into_iter().map(Into::into).collect())
+    }
+
+    /// Like signAll, but each result carries the `index` of its input order
+    #[napi]
+    pub fn sign_all_indexed(
+        &self,
+        env: Env,
+        #[napi(ts_arg_type = "Array<OrderInput>")] orders: Vec<serde_json::Value>,
+        base_nonce: Option<JsNonce>,
+    ) -> Result<Vec<IndexedSignedTransactionOutput>> {
+        let orders = parse_order_inputs(orders, self.input_mode)?;
+        let base = self.hooked_base_nonce(base_nonce)?;
+        if let Some(base) = base {
+            self.run_pre_sign_hook_all(env, &orders, base)?;
+        }
+        let order_items: Result<Vec<OrderItem>> =
+            orders.into_iter().map(|o| o.try_into()).collect();
+        let order_items = order_items?;
+
+        let signed = self
+            .inner
+            .sign_all_indexed(order_items, base)
+            .map_err(keychain_error)?;
+
+        Ok(signed.into_iter().map(Into::into).collect())
+    }
+
+    /// Like signAllIndexed, but signs every order it can: result `i` has
+    /// either `transaction` or, if order `i` was refused, `error` and `code`
+    #[napi]
+    pub fn sign_all_partial_indexed(
+        &self,
+        env: Env,
+        #[napi(ts_arg_type = "Array<OrderInput>")] orders: Vec<serde_json::Value>,
+        base_nonce: Option<JsNonce>,
+    ) -> Result<Vec<PartialSignResultOutput>> {
+        let orders = parse_order_inputs(orders, self.input_mode)?;
+        let base = self.hooked_base_nonce(base_nonce)?;
+        if let Some(base) = base {
+            self.run_pre_sign_hook_all(env, &orders, base)?;
+        }
+        let order_items: Result<Vec<OrderItem>> =
+            orders.into_iter().map(|o| o.try_into()).collect();
+        let order_items = order_items?;
+
+        let results = self
+            .inner
+            .sign_all_partial_indexed(order_items, base)
+            .map_err(keychain_error)?;
+
+        Ok(results.into_iter().map(Into::into).collect())
+    }
+
+    /// Dry-run `orders` as `signAll` would: the rejections, limit-order
+    /// notional per symbol, nonces and transaction count, without signing
+    /// or taking nonces. With `specs` (symbol to spec), also the prices and
+    /// sizes rounding to the grid would change.
+    ///
+    /// The pre-sign hook set from JS is not called.
+    #[napi]
+    pub fn plan_batch(
+        &self,
+        #[napi(ts_arg_type = "Array<OrderInput>")] orders: Vec<serde_json::Value>,
+        base_nonce: Option<JsNonce>,
+        specs: Option<HashMap<String, SymbolSpecInput>>,
+    ) -> Result<BatchPlanOutput> {
+        let orders = parse_order_inputs(orders, self.input_mode)?;
+        let order_items: Result<Vec<OrderItem>> =
+            orders.into_iter().map(|o| o.try_into()).collect();
+        let specs = specs
+            .unwrap_or_default()
+            .iter()
+            .fold(SymbolSpecs::new(), |out, (symbol, spec)| {
+                out.with_spec(symbol.clone(), SymbolSpec::from(spec))
+            });
+        let plan = self
+            .inner
+            .plan_batch_with_specs(&order_items?, nonce_opt(base_nonce)?, &specs)
+            .map_err(keychain_error)?;
+        Ok(plan.into())
+    }
+
+    /// Order IDs `orders` will get, before signing them (`null` for
+    /// cancels). With `baseNonce`, order `i` is computed at `baseNonce + i`;
+    /// without one, fresh nonces are reserved and returned as
+    /// `reservation`, to pass to `signAllReserved` before it expires.
+    ///
+    /// @example
+    /// ```typescript
+    /// const { orderIds, reservation } = signer.peekOrderIds(orders);
+    /// showPending(orderIds);
+    /// const txs = signer.signAllReserved(orders, reservation);
+    /// ```
+    #[napi]
+    pub fn peek_order_ids(
+        &self,
+        #[napi(ts_arg_type = "Array<OrderInput>")] orders: Vec<serde_json::Value>,
+        base_nonce: Option<JsNonce>,
+    ) -> Result<PeekOrderIdsOutput> {
+        let orders = parse_order_inputs(orders, self.input_mode)?;
+        let order_items: Result<Vec<OrderItem>> =
+            orders.into_iter().map(|o| o.try_into()).collect();
+        let plan = nonce_opt(base_nonce)?.map_or(NoncePlan::Reserve, NoncePlan::Explicit);
+        let peeked = self
+            .inner
+            .peek_order_ids(&order_items?, plan)
+            .map_err(keychain_error)?;
+        Ok(PeekOrderIdsOutput {
+            order_ids: peeked
+                .order_ids
+                .iter()
+                .map(|id| id.map(|id| id.to_base58()))
+                .collect(),
+            reservation: peeked.reservation.map(Into::into),
+        })
+    }
+
+    /// Sign `orders` at the nonces `peekOrderIds` reserved for them. The
+    /// reservation is single-use; an expired one, or different orders,
+    /// throw.
+    #[napi]
+    pub fn sign_all_reserved(
+        &self,
+        env: Env,
+        #[napi(ts_arg_type = "Array<OrderInput>")] orders: Vec<serde_json::Value>,
+        reservation: NonceReservationOutput,
+    ) -> Result<Vec<SignedTransactionOutput>> {
+        let reservation = NonceReservation::try_from(reservation)?;
+        let orders = parse_order_inputs(orders, self.input_mode)?;
+        for (i, order) in orders.iter().enumerate() {
+            self.run_pre_sign_hook(env, order, reservation.base_nonce + i as u64)?;
+        }
+        let order_items: Result<Vec<OrderItem>> =
+            orders.into_iter().map(|o| o.try_into()).collect();
+        let signed = self
+            .inner
+            .sign_all_reserved(order_items?, &reservation)
+            .map_err(keychain_error)?;
+        Ok(signed.into_iter().map(Into::into).collect())
+    }
+
+    /// Sign raw bytes with this signer's key; returns the 64-byte signature.
+    /// Throws `KeypairZeroized` if the key was wiped.
+    #[napi]
+    pub fn sign_bytes(&self, bytes: Buffer) -> Result<Buffer> {
+        let signature = self
+            .inner
+            .try_sign_bytes_raw(&bytes)
+            .map_err(keychain_error)?;
+        Ok(Buffer::from(signature.to_vec()))
+    }
+
+    /// Check a 64-byte Ed25519 signature over `message` against `pubkey` (base58).
+    /// Throws if `signature` is not 64 bytes.
+    #[napi]
+    pub fn verify_signature(pubkey: String, message: Buffer, signature: Buffer) -> Result<bool> {
+        let pubkey = Pubkey::from_base58(&pubkey).map_err(keychain_error)?;
+        let signature: [u8; 64] = signature.as_ref().try_into().map_err(|_| {
+            Error::from_reason(
+                bulk_keychain::Error::InvalidSignatureLength(signature.len()).to_string(),
+            )
+        })?;
+        Ok(Signer::verify(&pubkey, &message, &signature))
+    }
+
+    /// Check a signed transaction's signature against this signer's key.
+    /// Pass `messageBytes` to skip rebuilding the signed message from `tx.actions`.
+    #[napi]
+    pub fn verify_signed_transaction(
+        &self,
+        tx: SignedTransactionOutput,
+        message_bytes: Option<Buffer>,
+    ) -> Result<bool> {
+        let tx: bulk_keychain::SignedTransaction = tx.try_into()?;
+        self.inner
+            .verify_signed_transaction(&tx, message_bytes.as_deref())
+            .map_err(keychain_error)
+    }
+
+    /// Sign a message prepared for this signer's key, keeping its account
+    ///
+    /// Completes agent-wallet flows with a local key: prepare with
+    /// `{ account: mainAccount, signer: agentPubkey }`, then sign here.
+    /// Throws if the message was prepared for another signer.
+    #[napi]
+    pub fn sign_prepared(
+        &mut self,
+        prepared: PreparedMessageOutput,
+    ) -> Result<SignedTransactionOutput> {
+        let prepared = PreparedMessage::try_from(prepared)?;
+        self.inner
+            .sign_prepared(prepared)
+            .map(Into::into)
+            .map_err(keychain_error)
+    }
+
+    /// Re-sign a transaction rejected for a stale nonce under the next nonce
+    ///
+    /// Actions are preserved exactly; only nonce, signature and order IDs change.
+    #[napi]
+    pub fn resign_with_new_nonce(
+        &mut self,
+        tx: SignedTransactionOutput,
+    ) -> Result<SignedTransactionOutput> {
+        let tx: bulk_keychain::SignedTransaction = tx.try_into()?;
+        let resigned = self
+            .inner
+            .resign_with_new_nonce(&tx)
+            .map_err(keychain_error)?;
+        Ok(resigned.into())
+    }
+
+    /// Run `hook(order, nonce)` before signing each order; throwing from it aborts signing.
+    /// Pass null to remove the hook.
+    ///
+    /// Applies to sign, signAll, signAllIndexed, signAllPartialIndexed, signGroup and signConditionalOrder.
+    ///
+    /// @example
+    /// ```typescript
+    /// signer.setPreSignHook((order, nonce) => {
+    ///   if (order.size > 10) throw new Error(`size ${order.size} over risk limit`);
+    /// });
+    /// ```
+    #[napi]
+    pub fn set_pre_sign_hook(&mut self, env: Env, hook: Option<JsFunction>) -> Result<()> {
+        if let Some(mut old) = self.pre_sign_hook.take() {
+            old.unref(env).map_err(napi_error)?;
+        }
+        self.pre_sign_hook = hook
+            .map(|f| env.create_reference(f))
+            .transpose()
+            .map_err(napi_error)?;
+        Ok(())
+    }
+
+    /// Release the pre-sign hook and stop counting this signer in
+    /// `diagnostics`, without waiting for garbage collection.
+    #[napi]
+    pub fn dispose(&mut self, env: Env) -> Result<()> {
+        self.tracked = None;
+        self.set_pre_sign_hook(env, None)
+    }
+
+    /// Reject unknown fields (suggesting the nearest valid name) and mixed
+    /// camelCase/snake_case in order and settings inputs instead of ignoring
+    /// them.
+    #[napi]
+    pub fn set_strict_input(&mut self, strict: bool) {
+        self.input_mode = if strict {
+            InputMode::Strict
+        } else {
+            InputMode::Tolerant
+        };
+    }
+
+    /// Refuse to resign transactions whose timestamp nonce is older than this (null = no limit)
+    #[napi]
+    pub fn set_max_resign_age_ms(&mut self, max_age_ms: Option<f64>) {
+        self.inner
+            .set_max_resign_age_ms(max_age_ms.map(|ms| ms as u64));
+    }
+
+    /// Sign an order that only activates once a price condition is met
+    #[napi]
+    pub fn sign_conditional_order(
+        &mut self,
+        env: Env,
+        condition: ConditionInput,
+        #[napi(ts_arg_type = "OrderInput")] order: serde_json::Value,
+        nonce: Option<JsNonce>,
+    ) -> Result<SignedTransactionOutput> {
+        let order = parse_order_input(order, self.input_mode)?;
+        let nonce = self.hooked_nonce(nonce)?;
+        if let Some(n) = nonce {
+            self.run_pre_sign_hook(env, &order, n)?;
+        }
+        let OrderItem::Order(order) = order.try_into()? else {
+            return Err(Error::from_reason(
+                "conditional order must be of type 'order'",
+            ));
+        };
+        let condition = TriggerCondition::new(condition.symbol, condition.above, condition.price);
+        let signed = self
+            .inner
+            .sign_order_conditional(condition, order, nonce)
+            .map_err(keychain_error)?;
+        Ok(signed.into())
+    }
+
+    /// Sign multiple orders atomically in ONE transaction
+    ///
+    /// Use for bracket orders (entry + stop loss + take profit) where
+    /// all orders must succeed or fail together.
+    ///
+    /// @example
+    /// ```typescript
+    /// const bracket = [entryOrder, stopLoss, takeProfit];
+    /// const signed = signer.signGroup(bracket); // Returns single SignedTransaction
+    /// // Tag the intent for downstream routing (unsigned metadata)
+    /// const tagged = signer.signGroup(bracket, undefined, 'bracket');
+    /// tagged.groupKind; // 'bracket'
+    /// // Conflicting legs come back as `groupWarnings`; pass strictGroup to refuse them
+    /// signer.signGroup(bracket, undefined, 'bracket', true);
+    /// ```
+    #[napi]
+    pub fn sign_group(
+        &mut self,
+        env: Env,
+        #[napi(ts_arg_type = "Array<OrderInput>")] orders: Vec<serde_json::Value>,
+        nonce: Option<JsNonce>,
+        group_kind: Option<String>,
+        strict_group: Option<bool>,
+    ) -> Result<SignedTransactionOutput> {
+        let orders = parse_order_inputs(orders, self.input_mode)?;
+        let nonce_val = self.hooked_nonce(nonce)?;
+        if let Some(n) = nonce_val {
+            for order in &orders {
+                self.run_pre_sign_hook(env, order, n)?;
+            }
+        }
+        let order_items: Result<Vec<OrderItem>> =
+            orders.into_iter().map(|o| o.try_into()).collect();
+        let order_items = order_items?;
+
+        let options = GroupOptions {
+            kind: group_kind.map(GroupKind::from),
+            strict: strict_group == Some(true),
+        };
+        let signed = self
+            .inner
+            .sign_group_with_options(order_items, &options, nonce_val)
+            .map_err(keychain_error)?;
+
+        Ok(signed.into())
+    }
+
+    /// Sign a new size for a live order; same as `sign` with a `modify` item,
+    /// so the pre-sign hook runs. A price change needs a cancel-replace.
+    #[napi]
+    pub fn sign_modify_order(
+        &mut self,
+        env: Env,
+        order_id: String,
+        symbol: String,
+        amount: f64,
+        nonce: Option<JsNonce>,
+    ) -> Result<SignedTransactionOutput> {
+        let modify = serde_json::json!({
+            "type": "modify",
+            "orderId": order_id,
+            "symbol": symbol,
+            "amount": amount,
+        });
+        self.sign(env, modify, nonce, None)
+    }
+
+    // ========================================================================
+    // Other signing methods
+    // ========================================================================
+
+    /// Sign a faucet request (testnet only)
+    #[napi]
+    pub fn sign_faucet(&mut self, nonce: Option<JsNonce>) -> Result<SignedTransactionOutput> {
+        let nonce_val = nonce_opt(nonce)?;
+        let signed = self.inner.sign_faucet(nonce_val).map_err(keychain_error)?;
+
+        Ok(signed.into())
+    }
+
+    /// Sign agent wallet creation/deletion
+    #[napi]
+    pub fn sign_agent_wallet(
+        &mut self,
+        agent_pubkey: String,
+        delete: bool,
+        nonce: Option<JsNonce>,
+    ) -> Result<SignedTransactionOutput> {
+        let agent = Pubkey::from_base58(&agent_pubkey).map_err(keychain_error)?;
+        let nonce_val = nonce_opt(nonce)?;
+
+        let signed = self
+            .inner
+            .sign_agent_wallet(agent, delete, nonce_val)
+            .map_err(keychain_error)?;
+
+        Ok(signed.into())
+    }
+
+    /// Sign user settings update
+    ///
+    /// `settings` is `{ maxLeverage: [{ symbol, leverage }] }`; a bare
+    /// array of leverage settings is also accepted.
+    #[napi]
+    pub fn sign_user_settings(
+        &mut self,
+        #[napi(ts_arg_type = "UserSettingsInput | Array<LeverageSetting>")]
+        settings: serde_json::Value,
+        nonce: Option<JsNonce>,
+    ) -> Result<SignedTransactionOutput> {
+        let user_settings = parse_user_settings(settings, self.input_mode)?;
+        let nonce_val = nonce_opt(nonce)?;
+
+        let signed = self
+            .inner
+            .sign_user_settings(user_settings, nonce_val)
+            .map_err(keychain_error)?;
+
+        Ok(signed.into())
+    }
+
+    /// Sign one or more oracle price updates (`px`)
+    #[napi]
+    pub fn sign_oracle_prices(
+        &mut self,
+        oracles: Vec<OraclePriceInput>,
+        nonce: Option<JsNonce>,
+    ) -> Result<SignedTransactionOutput> {
+        let oracle_prices: Vec<OraclePrice> = oracles
+            .into_iter()
+            .map(|o| OraclePrice {
+                timestamp: o.timestamp as u64,
+                asset: o.asset,
+                price: o.price,
+            })
+            .collect();
+        let nonce_val = nonce_opt(nonce)?;
+
+        let signed = self
+            .inner
+            .sign_oracle_prices(oracle_prices, nonce_val)
+            .map_err(keychain_error)?;
+
+        Ok(signed.into())
+    }
+
+    /// Sign a batch Pyth oracle update (`o`)
+    #[napi]
+    pub fn sign_pyth_oracle(
+        &mut self,
+        oracles: Vec<PythOraclePriceInput>,
+        nonce: Option<JsNonce>,
+    ) -> Result<SignedTransactionOutput> {
+        let pyth_oracles: Vec<PythOraclePrice> = oracles
+            .into_iter()
+            .map(|o| PythOraclePrice {
+                timestamp: o.timestamp as u64,
+                feed_index: o.feed_index as u64,
+                price: o.price as u64,
+                exponent: o.exponent as i16,
+            })
+            .collect();
+        let nonce_val = nonce_opt(nonce)?;
+
+        let signed = self
+            .inner
+            .sign_pyth_oracle(pyth_oracles, nonce_val)
+            .map_err(keychain_error)?;
+
+        Ok(signed.into())
+    }
+
+    /// Sign a margin transfer between accounts
+    #[napi]
+    pub fn sign_transfer(
+        &mut self,
+        from_pubkey: String,
+        to_pubkey: String,
+        margin_symbol: String,
+        margin_amount: f64,
+        kind: Option<String>,
+        nonce: Option<JsNonce>,
+    ) -> Result<SignedTransactionOutput> {
+        let from = Pubkey::from_base58(&from_pubkey).map_err(keychain_error)?;
+        let to = Pubkey::from_base58(&to_pubkey).map_err(keychain_error)?;
+        let kind = parse_transfer_kind(kind.as_deref())?;
+        let nonce_val = nonce_opt(nonce)?;
+
+        let transfer = Transfer {
+            kind,
+            from,
+            to,
+            margin_symbol,
+            margin_amount,
+        };
+
+        let signed = self
+            .inner
+            .sign_transfer(transfer, nonce_val)
+            .map_err(keychain_error)?;
+
+        Ok(signed.into())
+    }
+
+    /// Sign a sub-account creation (optional initial margin transfer)
+    #[napi]
+    pub fn sign_create_sub_account(
+        &mut self,
+        name: String,
+        margin_symbol: Option<String>,
+        margin_amount: Option<f64>,
+        nonce: Option<JsNonce>,
+    ) -> Result<SignedTransactionOutput> {
+        let nonce_val = nonce_opt(nonce)?;
+        let sub_account = CreateSubAccount {
+            name,
+            margin_symbol,
+            margin_amount,
+        };
+
+        let signed = self
+            .inner
+            .sign_create_sub_account(sub_account, nonce_val)
+            .map_err(keychain_error)?;
+
+        Ok(signed.into())
+    }
+
+    /// Sign a sub-account removal
+    #[napi]
+    pub fn sign_remove_sub_account(
+        &mut self,
+        to_remove: String,
+        nonce: Option<JsNonce>,
+    ) -> Result<SignedTransactionOutput> {
+        let target = Pubkey::from_base58(&to_remove).map_err(keychain_error)?;
+        let nonce_val = nonce_opt(nonce)?;
+
+        let signed = self
+            .inner
+            .sign_remove_sub_account(target, nonce_val)
+            .map_err(keychain_error)?;
+
+        Ok(signed.into())
+    }
+
+    /// Sign a sub-account rename
+    #[napi]
+    pub fn sign_rename_sub_account(
+        &mut self,
+        subaccount: String,
+        name: String,
+        nonce: Option<JsNonce>,
+    ) -> Result<SignedTransactionOutput> {
+        let account = Pubkey::from_base58(&subaccount).map_err(keychain_error)?;
+        let nonce_val = nonce_opt(nonce)?;
+
+        let signed = self
+            .inner
+            .sign_rename_sub_account(RenameSubAccount { account, name }, nonce_val)
+            .map_err(keychain_error)?;
+
+        Ok(signed.into())
+    }
+
+    /// Sign whitelist/un-whitelist faucet access (`whitelistFaucet`)
+    #[napi]
+    pub fn sign_whitelist_faucet(
+        &mut self,
+        target_pubkey: String,
+        whitelist: bool,
+        nonce: Option<JsNonce>,
     ) -> Result<SignedTransactionOutput> {
-        let order_items: Result<Vec<OrderItem>> =
-            orders.into_iter().map(|o| o.try_into()).collect();
-        let order_items = order_items?;
+        let target = Pubkey::from_base58(&target_pubkey).map_err(keychain_error)?;
+        let nonce_val = nonce_opt(nonce)?;
 
-        let nonce_val = nonce.map(|n| n as u64);
         let signed = self
             .inner
-            .sign_group(order_items, nonce_val)
-            .map_err(|e| Error::from_reason(e.to_string()))?;
+            .sign_whitelist_faucet(target, whitelist, nonce_val)
+            .map_err(keychain_error)?;
 
         Ok(signed.into())
     }
 
     // ========================================================================
-    // Other signing methods
+    // Legacy methods (deprecated, kept for backward compatibility)
     // ========================================================================
 
-    /// Sign a faucet request (testnet only)
+    /// @deprecated Use sign() for single items, signAll() for batches, signGroup() for atomic
     #[napi]
-    pub fn sign_faucet(&mut self, nonce: Option<f64>) -> Result<SignedTransactionOutput> {
-        let nonce_val = nonce.map(|n| n as u64);
-        let signed = self
-            .inner
-            .sign_faucet(nonce_val)
-            .map_err(|e| Error::from_reason(e.to_string()))?;
+    pub fn sign_order(
+        &mut self,
+        env: Env,
+        #[napi(ts_arg_type = "Array<OrderInput>")] orders: Vec<serde_json::Value>,
+        nonce: Option<JsNonce>,
+    ) -> Result<SignedTransactionOutput> {
+        // Delegates to sign_group for backward compatibility
+        self.sign_group(env, orders, nonce, None, None)
+    }
 
-        Ok(signed.into())
+    /// @deprecated Use signAll() instead
+    #[napi]
+    pub fn sign_orders_batch(
+        &self,
+        #[napi(ts_arg_type = "Array<Array<OrderInput>>")] batches: Vec<Vec<serde_json::Value>>,
+        base_nonce: Option<JsNonce>,
+    ) -> Result<Vec<SignedTransactionOutput>> {
+        let batches = batches
+            .into_iter()
+            .map(|batch| parse_order_inputs(batch, self.input_mode))
+            .collect::<Result<Vec<_>>>()?;
+        #[allow(deprecated)]
+        {
+            let order_batches: Result<Vec<Vec<OrderItem>>> = batches
+                .into_iter()
+                .map(|batch| batch.into_iter().map(|o| o.try_into()).collect())
+                .collect();
+            let order_batches = order_batches?;
+
+            let base = nonce_opt(base_nonce)?;
+            let signed = self
+                .inner
+                .sign_orders_batch(order_batches, base)
+                .map_err(keychain_error)?;
+
+            Ok(signed.into_iter().map(Into::into).collect())
+        }
     }
 
-    /// Sign agent wallet creation/deletion
+    /// @deprecated Use signAllIndexed() instead
     #[napi]
-    pub fn sign_agent_wallet(
+    pub fn sign_orders_batch_indexed(
+        &self,
+        #[napi(ts_arg_type = "Array<Array<OrderInput>>")] batches: Vec<Vec<serde_json::Value>>,
+        base_nonce: Option<JsNonce>,
+    ) -> Result<Vec<IndexedSignedTransactionOutput>> {
+        let batches = batches
+            .into_iter()
+            .map(|batch| parse_order_inputs(batch, self.input_mode))
+            .collect::<Result<Vec<_>>>()?;
+        #[allow(deprecated)]
+        {
+            let order_batches: Result<Vec<Vec<OrderItem>>> = batches
+                .into_iter()
+                .map(|batch| batch.into_iter().map(|o| o.try_into()).collect())
+                .collect();
+            let order_batches = order_batches?;
+
+            let base = nonce_opt(base_nonce)?;
+            let signed = self
+                .inner
+                .sign_orders_batch_indexed(order_batches, base)
+                .map_err(keychain_error)?;
+
+            Ok(signed.into_iter().map(Into::into).collect())
+        }
+    }
+}
+
+// ============================================================================
+// Scoped Signer
+// ============================================================================
+
+/// Signer restricted by a scope, created with `NativeSigner.scoped()`
+#[napi]
+pub struct NativeScopedSigner {
+    inner: ScopedSigner,
+}
+
+#[napi]
+impl NativeScopedSigner {
+    /// Narrow this scope further; fails if `config` would widen it
+    #[napi]
+    pub fn scoped(&self, config: ScopeConfigInput) -> Result<NativeScopedSigner> {
+        let inner = self.inner.scoped(config.into()).map_err(keychain_error)?;
+        Ok(NativeScopedSigner { inner })
+    }
+
+    /// Signatures left before the tightest budget in the chain runs out
+    #[napi]
+    pub fn remaining_signatures(&self) -> Option<f64> {
+        self.inner.remaining_signatures().map(|n| n as f64)
+    }
+
+    /// Public key of the signing keypair
+    #[napi]
+    pub fn signer_pubkey(&self) -> String {
+        self.inner.signer_pubkey().to_base58()
+    }
+
+    /// Public key of the trading account
+    #[napi]
+    pub fn account_pubkey(&self) -> String {
+        self.inner.account_pubkey().to_base58()
+    }
+
+    /// What this scope may sign; `scopes` holds its config
+    #[napi]
+    pub fn capabilities(&self) -> Result<serde_json::Value> {
+        capabilities_json(&self.inner.capabilities())
+    }
+
+    /// `capabilities()` signed with the underlying key
+    #[napi]
+    pub fn capabilities_signed(&self) -> Result<SignedCapabilitiesOutput> {
+        self.inner
+            .capabilities_signed()
+            .map(Into::into)
+            .map_err(keychain_error)
+    }
+
+    /// Sign a single order if the scope allows it
+    #[napi]
+    pub fn sign(
         &mut self,
-        agent_pubkey: String,
-        delete: bool,
-        nonce: Option<f64>,
+        order: OrderInput,
+        nonce: Option<JsNonce>,
     ) -> Result<SignedTransactionOutput> {
-        let agent =
-            Pubkey::from_base58(&agent_pubkey).map_err(|e| Error::from_reason(e.to_string()))?;
-        let nonce_val = nonce.map(|n| n as u64);
-
+        let order_item: OrderItem = order.try_into()?;
         let signed = self
             .inner
-            .sign_agent_wallet(agent, delete, nonce_val)
-            .map_err(|e| Error::from_reason(e.to_string()))?;
-
+            .sign(order_item, nonce_opt(nonce)?)
+            .map_err(keychain_error)?;
         Ok(signed.into())
     }
 
-    /// Sign user settings update
+    /// Sign orders as one atomic transaction if the scope allows every one
     #[napi]
-    pub fn sign_user_settings(
+    pub fn sign_group(
         &mut self,
-        max_leverage: Vec<LeverageSetting>,
-        nonce: Option<f64>,
+        orders: Vec<OrderInput>,
+        nonce: Option<JsNonce>,
     ) -> Result<SignedTransactionOutput> {
-        let leverage_vec: Vec<(String, f64)> = max_leverage
-            .into_iter()
-            .map(|l| (l.symbol, l.leverage))
-            .collect();
-        let user_settings = UserSettings::new(leverage_vec);
-        let nonce_val = nonce.map(|n| n as u64);
-
+        let order_items: Result<Vec<OrderItem>> =
+            orders.into_iter().map(|o| o.try_into()).collect();
         let signed = self
             .inner
-            .sign_user_settings(user_settings, nonce_val)
-            .map_err(|e| Error::from_reason(e.to_string()))?;
-
+            .sign_group(order_items?, nonce_opt(nonce)?)
+            .map_err(keychain_error)?;
         Ok(signed.into())
     }
 
-    /// Sign one or more oracle price updates (`px`)
+    /// Sign orders as independent transactions if the scope allows every one
     #[napi]
-    pub fn sign_oracle_prices(
+    pub fn sign_all(
         &mut self,
-        oracles: Vec<OraclePriceInput>,
-        nonce: Option<f64>,
-    ) -> Result<SignedTransactionOutput> {
-        let oracle_prices: Vec<OraclePrice> = oracles
-            .into_iter()
-            .map(|o| OraclePrice {
-                timestamp: o.timestamp as u64,
-                asset: o.asset,
-                price: o.price,
+        orders: Vec<OrderInput>,
+        base_nonce: Option<JsNonce>,
+    ) -> Result<Vec<SignedTransactionOutput>> {
+        let order_items: Result<Vec<OrderItem>> =
+            orders.into_iter().map(|o| o.try_into()).collect();
+        let signed = self
+            .inner
+            .sign_all(order_items?, nonce_opt(base_nonce)?)
+            .map_err(keychain_error)?;
+        Ok(signed.into_iter().map(Into::into).collect())
+    }
+}
+
+/// A `signAllAsync` batch, signed on the libuv threadpool
+pub struct SignAllTask {
+    signer: Signer,
+    items: Vec<OrderItem>,
+    base: u64,
+    options: SignOptions,
+    /// `code` for the rejection, set when signing fails
+    error_code: Option<&'static str>,
+}
+
+impl Task for SignAllTask {
+    type Output = Vec<bulk_keychain::SignedTransaction>;
+    type JsValue = Vec<SignedTransactionOutput>;
+
+    fn compute(&mut self) -> napi::Result<Self::Output> {
+        self.signer
+            .sign_all_with_options(
+                std::mem::take(&mut self.items),
+                Some(self.base),
+                &self.options,
+            )
+            .map_err(|e| {
+                self.error_code = Some(e.code());
+                napi::Error::from_reason(e.to_string())
             })
-            .collect();
-        let nonce_val = nonce.map(|n| n as u64);
+    }
 
-        let signed = self
+    fn resolve(&mut self, _env: Env, output: Self::Output) -> napi::Result<Self::JsValue> {
+        Ok(output.into_iter().map(Into::into).collect())
+    }
+
+    fn reject(&mut self, env: Env, err: napi::Error) -> napi::Result<Self::JsValue> {
+        let mut error = env.create_error(err)?;
+        if let Some(code) = self.error_code {
+            error.set_named_property("code", env.create_string(code)?)?;
+        }
+        Err(napi::Error::from(error.into_unknown()))
+    }
+}
+
+// ============================================================================
+// Signer Pool
+// ============================================================================
+
+/// Per-call options for `sign` and `signAll`
+#[napi(object)]
+#[derive(Default)]
+pub struct SignOptionsInput {
+    /// Latest time (ms since the Unix epoch, on the signer's clock) to sign at;
+    /// past it the call throws `deadline exceeded by <n>ms after <k> signed`.
+    /// Values that look like seconds are refused.
+    pub deadline: Option<f64>,
+    /// Sign even if a live order holds the same `clientId` (see `setUniqueClientIds`)
+    pub allow_client_id_reuse: Option<bool>,
+}
+
+fn sign_options(input: Option<SignOptionsInput>) -> Result<SignOptions> {
+    let input = input.unwrap_or_default();
+    let deadline_ms = input
+        .deadline
+        .map(|ms| TimestampMs::from_millis_checked(ms as u64))
+        .transpose()
+        .map_err(keychain_error)?;
+    Ok(SignOptions {
+        deadline_ms,
+        allow_client_id_reuse: input.allow_client_id_reuse.unwrap_or(false),
+    })
+}
+
+/// Capability descriptor JSON and its signature, from `capabilitiesSigned()`
+#[napi(object)]
+pub struct SignedCapabilitiesOutput {
+    /// Descriptor JSON, exactly as signed
+    pub payload: String,
+    /// Signing key (base58)
+    pub signer: String,
+    /// Signature (base58)
+    pub signature: String,
+}
+
+impl From<bulk_keychain::SignedCapabilities> for SignedCapabilitiesOutput {
+    fn from(signed: bulk_keychain::SignedCapabilities) -> Self {
+        Self {
+            payload: signed.payload,
+            signer: signed.signer,
+            signature: signed.signature,
+        }
+    }
+}
+
+fn capabilities_json(
+    descriptor: &bulk_keychain::CapabilityDescriptor,
+) -> Result<serde_json::Value> {
+    serde_json::to_value(descriptor).map_err(|e| Error::from_reason(e.to_string()))
+}
+
+/// Counts returned by `usage()`
+#[napi(object)]
+pub struct SignerUsageOutput {
+    pub signed: f64,
+    pub deadline_rejections: f64,
+    /// "serial" or "parallel"; undefined before the first batch
+    #[napi(ts_type = "'serial' | 'parallel'")]
+    pub last_batch_mode: Option<String>,
+    /// Threads the latest batch used
+    pub last_batch_threads: Option<u32>,
+    /// `notifyReconnect` calls
+    pub reconnects: f64,
+}
+
+/// What `notifyReconnect` resets; every field defaults to false
+#[napi(object)]
+pub struct ReconnectPolicyInput {
+    pub clear_open_orders: Option<bool>,
+    pub clear_dedupe: Option<bool>,
+    pub bump_nonce_floor: Option<bool>,
+}
+
+/// What a `notifyReconnect` call changed
+#[napi(object)]
+pub struct ReconnectReportOutput {
+    pub open_orders_cleared: u32,
+    pub client_ids_cleared: u32,
+    /// Lowest auto nonce from now on, if bumped
+    pub nonce_floor: Option<JsNonce>,
+}
+
+/// One item of a `signMixed` batch
+#[napi(object)]
+pub struct MixedOrderInput {
+    /// Account the order trades for (base58)
+    pub account: String,
+    pub order: OrderInput,
+}
+
+/// One result of a `signMixed` batch: `tx` on success, `error` otherwise
+#[napi(object)]
+pub struct MixedSignOutput {
+    /// Index of the source item in the input array
+    pub index: u32,
+    pub account: String,
+    pub tx: Option<SignedTransactionOutput>,
+    pub error: Option<String>,
+}
+
+/// One agent key signing for several accounts
+///
+/// @example
+/// ```typescript
+/// const pool = new NativeSignerPool(agent, [accountA, accountB]);
+/// const results = pool.signMixed([{ account: accountA, order }, { account: accountB, order }]);
+/// ```
+#[napi]
+pub struct NativeSignerPool {
+    inner: SignerPool,
+}
+
+#[napi]
+impl NativeSignerPool {
+    /// Pool where `agent` signs for each of `accounts` (base58)
+    #[napi(constructor)]
+    pub fn new(agent: &NativeKeypair, accounts: Vec<String>) -> Result<Self> {
+        let accounts = accounts
+            .iter()
+            .map(|a| Pubkey::from_base58(a))
+            .collect::<bulk_keychain::Result<Vec<_>>>()
+            .map_err(keychain_error)?;
+        Ok(Self {
+            inner: SignerPool::for_agent(
+                agent.inner.clone_tracked("NativeSignerPool::forAgent"),
+                accounts,
+            ),
+        })
+    }
+
+    /// Accounts this pool signs for (base58)
+    #[napi]
+    pub fn accounts(&self) -> Vec<String> {
+        self.inner.accounts().map(Pubkey::to_base58).collect()
+    }
+
+    /// Sign each item for its account, one transaction per item
+    ///
+    /// Each account's items get nonces `base + 0, base + 1, ...` in input
+    /// order, with `base` from `baseNonces[account]` or the current time.
+    /// Results are in input order; an item for an account outside the pool
+    /// carries an `error` instead of a `tx`.
+    #[napi]
+    pub fn sign_mixed(
+        &self,
+        items: Vec<MixedOrderInput>,
+        base_nonces: Option<HashMap<String, JsNonce>>,
+    ) -> Result<Vec<MixedSignOutput>> {
+        let to_pubkey = |s: &str| Pubkey::from_base58(s).map_err(keychain_error);
+        let base_nonces = base_nonces
+            .unwrap_or_default()
+            .into_iter()
+            .map(|(account, nonce)| Ok((to_pubkey(&account)?, nonce_in(nonce)?)))
+            .collect::<Result<HashMap<_, _>>>()?;
+        let mut accounts = Vec::with_capacity(items.len());
+        let mut batch = Vec::with_capacity(items.len());
+        for item in items {
+            batch.push((to_pubkey(&item.account)?, item.order.try_into()?));
+            accounts.push(item.account);
+        }
+
+        let results = self
             .inner
-            .sign_oracle_prices(oracle_prices, nonce_val)
-            .map_err(|e| Error::from_reason(e.to_string()))?;
+            .sign_mixed(batch, Some(base_nonces))
+            .map_err(keychain_error)?;
+        Ok(results
+            .into_iter()
+            .zip(accounts)
+            .enumerate()
+            .map(|(index, (result, account))| {
+                let (tx, error) = match result {
+                    Ok(tx) => (Some(tx.into()), None),
+                    Err(e) => (None, Some(e.to_string())),
+                };
+                MixedSignOutput {
+                    index: index as u32,
+                    account,
+                    tx,
+                    error,
+                }
+            })
+            .collect())
+    }
+}
+
+// ============================================================================
+// Outbox
+// ============================================================================
+
+/// Crash-safe log of prepared messages awaiting an external signature
+///
+/// Enqueue before sending a message to the custodian and complete it when the
+/// signature comes back; after a restart, `pending()` lists what is still
+/// outstanding.
+///
+/// @example
+/// ```typescript
+/// const outbox = new NativeOutbox('/var/lib/bot/outbox.jsonl');
+/// const key = outbox.enqueue(prepared);
+/// const signed = outbox.complete(key, await custodian.sign(prepared.messageBytes));
+/// ```
+#[napi]
+pub struct NativeOutbox {
+    inner: Option<Outbox>,
+}
+
+#[napi]
+impl NativeOutbox {
+    /// Open (or create) the log at `path`; fails if another process has it open
+    #[napi(constructor)]
+    pub fn new(path: String) -> Result<Self> {
+        let inner = Outbox::open(&path).map_err(keychain_error)?;
+        Ok(Self { inner: Some(inner) })
+    }
+
+    fn outbox(&self) -> Result<&Outbox> {
+        self.inner
+            .as_ref()
+            .ok_or_else(|| Error::from_reason("outbox has been closed"))
+    }
+
+    /// Release the log file so it can be reopened; the outbox cannot be used afterwards
+    #[napi]
+    pub fn close(&mut self) {
+        self.inner = None;
+    }
+
+    /// Record a prepared message as sent for signing; returns its key
+    /// (the order ID when there is one)
+    #[napi]
+    pub fn enqueue(&self, prepared: PreparedMessageOutput) -> Result<String> {
+        self.outbox()?
+            .enqueue(prepared.try_into()?)
+            .map_err(keychain_error)
+    }
+
+    /// Messages still waiting for a signature, oldest first
+    #[napi]
+    pub fn pending(&self) -> Result<Vec<PreparedMessageOutput>> {
+        let pending = self.outbox()?.pending().map_err(keychain_error)?;
+        Ok(pending.into_iter().map(Into::into).collect())
+    }
+
+    /// Finalize the message under `key` with a base58 signature and remove it
+    ///
+    /// Throws if the signature does not verify or `key` is not pending.
+    #[napi]
+    pub fn complete(&self, key: String, signature: String) -> Result<SignedTransactionOutput> {
+        self.outbox()?
+            .complete(&key, &signature)
+            .map(Into::into)
+            .map_err(keychain_error)
+    }
+
+    /// Drop messages enqueued more than `maxAgeMs` ago; returns them
+    #[napi]
+    pub fn expire_older_than(&self, max_age_ms: f64) -> Result<Vec<PreparedMessageOutput>> {
+        let expired = self
+            .outbox()?
+            .expire_older_than(max_age_ms as u64)
+            .map_err(keychain_error)?;
+        Ok(expired.into_iter().map(Into::into).collect())
+    }
+}
+
+// ============================================================================
+// Batch Builder
+// ============================================================================
+
+/// Builder for a batch of mixed transactions (singles, atomic groups, cancel batches)
+///
+/// @example
+/// ```typescript
+/// const batch = new NativeBatchBuilder();
+/// batch.addSingle(order);
+/// batch.addGroup([entry, stopLoss]);
+/// batch.addCancelBatch([{ symbol: 'BTC-USD', orderId }]);
+/// const signedTxs = batch.build(signer); // one transaction per entry, in order
+/// ```
+#[napi]
+pub struct NativeBatchBuilder {
+    inner: BatchBuilder,
+}
+
+#[napi]
+impl NativeBatchBuilder {
+    /// Create an empty batch
+    #[napi(constructor)]
+    pub fn new() -> Self {
+        Self {
+            inner: BatchBuilder::new(),
+        }
+    }
 
-        Ok(signed.into())
+    /// Append a single order/cancel/cancelAll
+    #[napi]
+    pub fn add_single(&mut self, order: OrderInput) -> Result<()> {
+        let item: OrderItem = order.try_into()?;
+        self.inner.push(BatchEntry::Single(item));
+        Ok(())
     }
 
-    /// Sign a batch Pyth oracle update (`o`)
+    /// Append orders to be signed atomically in one transaction
     #[napi]
-    pub fn sign_pyth_oracle(
-        &mut self,
-        oracles: Vec<PythOraclePriceInput>,
-        nonce: Option<f64>,
-    ) -> Result<SignedTransactionOutput> {
-        let pyth_oracles: Vec<PythOraclePrice> = oracles
+    pub fn add_group(&mut self, orders: Vec<OrderInput>) -> Result<()> {
+        let items: Result<Vec<OrderItem>> = orders.into_iter().map(|o| o.try_into()).collect();
+        self.inner.push(BatchEntry::Group(items?));
+        Ok(())
+    }
+
+    /// Append cancels to be signed together in one transaction
+    #[napi]
+    pub fn add_cancel_batch(&mut self, cancels: Vec<CancelInput>) -> Result<()> {
+        let cancels: Result<Vec<Cancel>> = cancels
             .into_iter()
-            .map(|o| PythOraclePrice {
-                timestamp: o.timestamp as u64,
-                feed_index: o.feed_index as u64,
-                price: o.price as u64,
-                exponent: o.exponent as i16,
+            .map(|c| {
+                let order_id = Hash::from_base58(&c.order_id)
+                    .map_err(|e| Error::from_reason(format!("Invalid orderId: {}", e)))?;
+                Ok(Cancel::new(c.symbol, order_id))
             })
             .collect();
-        let nonce_val = nonce.map(|n| n as u64);
-
-        let signed = self
-            .inner
-            .sign_pyth_oracle(pyth_oracles, nonce_val)
-            .map_err(|e| Error::from_reason(e.to_string()))?;
+        self.inner.push(BatchEntry::CancelBatch(cancels?));
+        Ok(())
+    }
 
-        Ok(signed.into())
+    /// Number of pending entries
+    #[napi(getter)]
+    pub fn length(&self) -> u32 {
+        self.inner.len() as u32
     }
 
-    /// Sign a margin transfer between accounts
+    /// Sign all entries in order (entry i uses nonce base + i); the builder is emptied
     #[napi]
-    pub fn sign_transfer(
+    pub fn build(
         &mut self,
-        from_pubkey: String,
-        to_pubkey: String,
-        margin_symbol: String,
-        margin_amount: f64,
-        kind: Option<String>,
-        nonce: Option<f64>,
-    ) -> Result<SignedTransactionOutput> {
-        let from =
-            Pubkey::from_base58(&from_pubkey).map_err(|e| Error::from_reason(e.to_string()))?;
-        let to = Pubkey::from_base58(&to_pubkey).map_err(|e| Error::from_reason(e.to_string()))?;
-        let kind = parse_transfer_kind(kind.as_deref())?;
-        let nonce_val = nonce.map(|n| n as u64);
-
-        let transfer = Transfer {
-            kind,
-            from,
-            to,
-            margin_symbol,
-            margin_amount,
-        };
-
+        signer: &mut NativeSigner,
+        base_nonce: Option<JsNonce>,
+    ) -> Result<Vec<SignedTransactionOutput>> {
+        let base = nonce_opt(base_nonce)?;
         let signed = self
             .inner
-            .sign_transfer(transfer, nonce_val)
-            .map_err(|e| Error::from_reason(e.to_string()))?;
+            .build(&mut signer.inner, base)
+            .map_err(keychain_error)?;
 
-        Ok(signed.into())
+        Ok(signed.into_iter().map(Into::into).collect())
     }
+}
 
-    /// Sign a sub-account creation (optional initial margin transfer)
-    #[napi]
-    pub fn sign_create_sub_account(
-        &mut self,
-        name: String,
-        margin_symbol: Option<String>,
-        margin_amount: Option<f64>,
-        nonce: Option<f64>,
-    ) -> Result<SignedTransactionOutput> {
-        let nonce_val = nonce.map(|n| n as u64);
-        let sub_account = CreateSubAccount {
-            name,
-            margin_symbol,
-            margin_amount,
-        };
+impl Default for NativeBatchBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
 
-        let signed = self
-            .inner
-            .sign_create_sub_account(sub_account, nonce_val)
-            .map_err(|e| Error::from_reason(e.to_string()))?;
+// ============================================================================
+// Agent Derivation
+// ============================================================================
 
-        Ok(signed.into())
-    }
+/// Derives per-account, per-epoch agent keypairs from a 32-byte root seed
+///
+/// @example
+/// ```typescript
+/// const deriver = new NativeAgentDeriver(rootSeed);
+/// const agent = deriver.derive(account, week);
+/// const signer = NativeSigner.fromKeypairAndAccount(agent, account);
+/// deriver.dispose(); // wipes the root seed
+/// ```
+#[napi]
+pub struct NativeAgentDeriver {
+    inner: Option<AgentDeriver>,
+}
 
-    /// Sign a sub-account removal
-    #[napi]
-    pub fn sign_remove_sub_account(
-        &mut self,
-        to_remove: String,
-        nonce: Option<f64>,
-    ) -> Result<SignedTransactionOutput> {
-        let target =
-            Pubkey::from_base58(&to_remove).map_err(|e| Error::from_reason(e.to_string()))?;
-        let nonce_val = nonce.map(|n| n as u64);
+#[napi]
+impl NativeAgentDeriver {
+    /// Create a deriver from a 32-byte root seed
+    #[napi(constructor)]
+    pub fn new(root_seed: Buffer) -> Result<Self> {
+        let seed: [u8; 32] = root_seed.as_ref().try_into().map_err(|_| {
+            Error::from_reason(format!(
+                "root seed must be 32 bytes, got {}",
+                root_seed.len()
+            ))
+        })?;
+        Ok(Self {
+            inner: Some(AgentDeriver::new(seed)),
+        })
+    }
 
-        let signed = self
-            .inner
-            .sign_remove_sub_account(target, nonce_val)
-            .map_err(|e| Error::from_reason(e.to_string()))?;
+    fn deriver(&self) -> Result<&AgentDeriver> {
+        self.inner
+            .as_ref()
+            .ok_or_else(|| Error::from_reason("agent deriver has been disposed"))
+    }
 
-        Ok(signed.into())
+    /// Derive the agent keypair for an account at an epoch
+    #[napi]
+    pub fn derive(&self, account: String, epoch: f64) -> Result<NativeKeypair> {
+        let account = Pubkey::from_base58(&account).map_err(keychain_error)?;
+        Ok(NativeKeypair::from_keypair(
+            self.deriver()?.derive(&account, epoch as u64),
+        ))
     }
 
-    /// Sign a sub-account rename
+    /// Public key of the agent for an account at an epoch
     #[napi]
-    pub fn sign_rename_sub_account(
-        &mut self,
-        subaccount: String,
-        name: String,
-        nonce: Option<f64>,
-    ) -> Result<SignedTransactionOutput> {
-        let account =
-            Pubkey::from_base58(&subaccount).map_err(|e| Error::from_reason(e.to_string()))?;
-        let nonce_val = nonce.map(|n| n as u64);
+    pub fn pubkey(&self, account: String, epoch: f64) -> Result<String> {
+        let account = Pubkey::from_base58(&account).map_err(keychain_error)?;
+        Ok(self.deriver()?.pubkey(&account, epoch as u64).to_base58())
+    }
 
-        let signed = self
-            .inner
-            .sign_rename_sub_account(RenameSubAccount { account, name }, nonce_val)
-            .map_err(|e| Error::from_reason(e.to_string()))?;
+    /// Public key of the next epoch's agent, for pre-authorization
+    #[napi]
+    pub fn next_pubkey(&self, account: String, epoch: f64) -> Result<String> {
+        let account = Pubkey::from_base58(&account).map_err(keychain_error)?;
+        Ok(self
+            .deriver()?
+            .next_pubkey(&account, epoch as u64)
+            .to_base58())
+    }
 
-        Ok(signed.into())
+    /// Prepare the agent wallet authorization for an epoch, to be signed by the account
+    #[napi]
+    pub fn prepare_authorization(
+        &self,
+        account: String,
+        epoch: f64,
+        nonce: Option<JsNonce>,
+    ) -> Result<PreparedMessageOutput> {
+        let account = Pubkey::from_base58(&account).map_err(keychain_error)?;
+        let prepared = self
+            .deriver()?
+            .prepare_authorization(&account, epoch as u64, nonce_opt(nonce)?)
+            .map_err(keychain_error)?;
+        Ok(prepared.into())
     }
 
-    /// Sign whitelist/un-whitelist faucet access (`whitelistFaucet`)
+    /// Wipe the root seed; the deriver cannot be used afterwards
     #[napi]
-    pub fn sign_whitelist_faucet(
-        &mut self,
-        target_pubkey: String,
-        whitelist: bool,
-        nonce: Option<f64>,
-    ) -> Result<SignedTransactionOutput> {
-        let target =
-            Pubkey::from_base58(&target_pubkey).map_err(|e| Error::from_reason(e.to_string()))?;
-        let nonce_val = nonce.map(|n| n as u64);
+    pub fn dispose(&mut self) {
+        self.inner = None;
+    }
+}
 
-        let signed = self
-            .inner
-            .sign_whitelist_faucet(target, whitelist, nonce_val)
-            .map_err(|e| Error::from_reason(e.to_string()))?;
+// ============================================================================
+// Remote Session
+// ============================================================================
 
-        Ok(signed.into())
+/// Wallet side of a remote prepare/finalize session, for server-custody setups
+///
+/// The app side (WasmSessionInitiator) posts request envelopes; each is checked
+/// against the shared secret and answered with a signed response envelope.
+///
+/// @example
+/// ```typescript
+/// const responder = new NativeSessionResponder(sharedSecret, custodyKeypair);
+/// socket.on('message', (envelope) => socket.send(responder.handleRequest(envelope)));
+/// ```
+#[napi]
+pub struct NativeSessionResponder {
+    inner: SessionResponder,
+}
+
+#[napi]
+impl NativeSessionResponder {
+    /// Create a responder keyed by a shared secret (at least 16 bytes) that signs with `keypair`
+    #[napi(constructor)]
+    pub fn new(secret: Buffer, keypair: &NativeKeypair, timeout_ms: Option<f64>) -> Result<Self> {
+        let mut inner = RemoteSession::responder(
+            &secret,
+            Signer::new(
+                keypair
+                    .inner
+                    .clone_tracked("NativeRemoteSession::responder"),
+            ),
+        )
+        .map_err(keychain_error)?;
+        if let Some(ms) = timeout_ms {
+            inner = inner.with_timeout_ms(ms as u64);
+        }
+        Ok(Self { inner })
     }
 
-    // ========================================================================
-    // Legacy methods (deprecated, kept for backward compatibility)
-    // ========================================================================
+    /// Session id, once the first request arrived
+    #[napi(getter)]
+    pub fn session_id(&self) -> Option<String> {
+        self.inner.session_id().map(str::to_string)
+    }
 
-    /// @deprecated Use sign() for single items, signAll() for batches, signGroup() for atomic
+    /// Check a request envelope and return the response envelope to send back
     #[napi]
-    pub fn sign_order(
-        &mut self,
-        orders: Vec<OrderInput>,
-        nonce: Option<f64>,
-    ) -> Result<SignedTransactionOutput> {
-        // Delegates to sign_group for backward compatibility
-        self.sign_group(orders, nonce)
+    pub fn handle_request(&mut self, envelope: String) -> Result<String> {
+        self.inner.handle_request(&envelope).map_err(keychain_error)
     }
+}
 
-    /// @deprecated Use signAll() instead
-    #[napi]
-    pub fn sign_orders_batch(
-        &self,
-        batches: Vec<Vec<OrderInput>>,
-        base_nonce: Option<f64>,
-    ) -> Result<Vec<SignedTransactionOutput>> {
-        #[allow(deprecated)]
-        {
-            let order_batches: Result<Vec<Vec<OrderItem>>> = batches
-                .into_iter()
-                .map(|batch| batch.into_iter().map(|o| o.try_into()).collect())
-                .collect();
-            let order_batches = order_batches?;
+// ============================================================================
+// Input/Output types for JS interop
+// ============================================================================
 
-            let base = base_nonce.map(|n| n as u64);
-            let signed = self
-                .inner
-                .sign_orders_batch(order_batches, base)
-                .map_err(|e| Error::from_reason(e.to_string()))?;
+/// Price condition for `signConditionalOrder`
+#[napi(object)]
+#[derive(Debug)]
+pub struct ConditionInput {
+    /// Reference symbol whose price is watched
+    pub symbol: String,
+    /// true = fire when price rises to `price`, false = when it falls to it
+    pub above: bool,
+    pub price: f64,
+}
 
-            Ok(signed.into_iter().map(Into::into).collect())
+/// Nonce clock anomaly reported by `onNonceAnomaly` / `nonceAnomalies`
+#[napi(object)]
+#[derive(Debug)]
+pub struct NonceAnomalyOutput {
+    /// Always "clockRegression" for now
+    pub kind: String,
+    /// Clock reading that triggered the report
+    pub observed_ms: f64,
+    /// Last nonce issued before it, in milliseconds
+    pub last_nonce_ms: f64,
+}
+
+impl From<NonceAnomaly> for NonceAnomalyOutput {
+    fn from(anomaly: NonceAnomaly) -> Self {
+        match anomaly {
+            NonceAnomaly::ClockRegression {
+                observed_ms,
+                last_nonce_ms,
+            } => Self {
+                kind: "clockRegression".to_string(),
+                observed_ms: observed_ms as f64,
+                last_nonce_ms: last_nonce_ms as f64,
+            },
         }
     }
 }
 
-// ============================================================================
-// Input/Output types for JS interop
-// ============================================================================
+/// Restrictions for `NativeSigner.scoped()`; omitted fields are unrestricted
+#[napi(object)]
+#[derive(Debug)]
+pub struct ScopeConfigInput {
+    /// Only these symbols may be traded or cancelled
+    pub symbols: Option<Vec<String>>,
+    /// Largest order size allowed
+    pub max_size: Option<f64>,
+    /// Only reduce-only orders (default false)
+    pub reduce_only: Option<bool>,
+    /// Whether cancels are allowed (default false)
+    pub allow_cancels: Option<bool>,
+    /// Total signatures this scope may produce
+    pub max_signatures: Option<f64>,
+}
+
+impl From<ScopeConfigInput> for ScopeConfig {
+    fn from(input: ScopeConfigInput) -> Self {
+        Self {
+            symbols: input.symbols,
+            max_size: input.max_size,
+            reduce_only: input.reduce_only.unwrap_or(false),
+            allow_cancels: input.allow_cancels.unwrap_or(false),
+            max_signatures: input.max_signatures.map(|n| n as u64),
+        }
+    }
+}
 
 #[napi(object)]
-#[derive(Debug, Deserialize)]
+#[derive(Debug)]
+pub struct CancelInput {
+    pub symbol: String,
+    pub order_id: String,
+}
+
+#[napi(object)]
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub struct OnFillInput {
     pub p: u32,
     pub actions: Vec<OrderInput>,
 }
 
-#[napi(object)]
-#[derive(Debug, Deserialize)]
+/// Order intent; field names may be camelCase or snake_case
+#[napi(object, object_from_js = false)]
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub struct OrderInput {
     #[napi(js_name = "type")]
+    #[serde(rename = "type")]
     pub item_type: String,
     pub symbol: Option<String>,
     pub is_buy: Option<bool>,
@@ -556,12 +2561,234 @@ pub struct OrderInput {
     pub on_fill: Option<OnFillInput>,
     pub trail_bps: Option<u32>,
     pub step_bps: Option<u32>,
+    /// Parent seqno of an `onFill` item, default 0
+    pub p: Option<u32>,
+    pub max_age_ms: Option<f64>,
+    /// Integer price ticks of `symbolSpec` (instead of `price`)
+    pub price_ticks: Option<i64>,
+    /// Integer size lots of `symbolSpec` (instead of `size`)
+    pub size_lots: Option<i64>,
+    pub symbol_spec: Option<SymbolSpecInput>,
+}
+
+/// Position snapshot for `signFlatten`
+#[napi(object)]
+pub struct PositionInput {
+    pub symbol: String,
+    /// Signed size: positive when long, negative when short
+    pub size: f64,
+    pub mark_price: f64,
+}
+
+impl From<PositionInput> for PositionSnapshot {
+    fn from(p: PositionInput) -> Self {
+        Self {
+            symbol: p.symbol,
+            size: p.size,
+            mark_price: p.mark_price,
+        }
+    }
+}
+
+/// How `signFlatten` prices its orders
+#[napi(object)]
+pub struct FlattenStyleInput {
+    /// `market` or `limit`
+    #[napi(js_name = "type")]
+    pub style_type: String,
+    /// Limit offset through the mark price, required for `limit`
+    pub offset_bps: Option<f64>,
+}
+
+impl TryFrom<FlattenStyleInput> for FlattenStyle {
+    type Error = Error;
+
+    fn try_from(style: FlattenStyleInput) -> Result<Self> {
+        match style.style_type.as_str() {
+            "market" => Ok(FlattenStyle::Market),
+            "limit" => style
+                .offset_bps
+                .map(FlattenStyle::LimitAtOffsetBps)
+                .ok_or_else(|| Error::from_reason("style.offsetBps is required")),
+            other => Err(Error::from_reason(format!(
+                "style.type: unknown flatten style {}",
+                other
+            ))),
+        }
+    }
+}
+
+/// Per-symbol granularity and order-size cap for `signFlatten`
+#[napi(object)]
+pub struct FlattenSpecInput {
+    pub spec: Option<SymbolSpecInput>,
+    pub max_order_size: Option<f64>,
+}
+
+fn flatten_specs(specs: Option<HashMap<String, FlattenSpecInput>>) -> SymbolSpecs {
+    let mut out = SymbolSpecs::new();
+    for (symbol, input) in specs.unwrap_or_default() {
+        if let Some(spec) = &input.spec {
+            out = out.with_spec(symbol.clone(), SymbolSpec::from(spec));
+        }
+        if let Some(max) = input.max_order_size {
+            out = out.with_max_order_size(symbol, max);
+        }
+    }
+    out
+}
+
+/// Response returned by a `signAndSubmit` transport callback
+#[napi(object)]
+pub struct TransportResponseInput {
+    pub status: u32,
+    pub body: String,
+}
+
+/// Result of `signAndSubmit`
+#[napi(object)]
+pub struct SubmitOutput {
+    pub transaction: SignedTransactionOutput,
+    pub status: u32,
+    pub body: String,
+}
+
+/// [`Transport`] backed by a synchronous JS callback.
+struct CallbackTransport {
+    env: Env,
+    submit: JsFunction,
+}
+
+impl Transport for CallbackTransport {
+    fn submit(&self, body: &str) -> bulk_keychain::Result<TransportResponse> {
+        let call = || -> napi::Result<TransportResponse> {
+            let body = self.env.create_string(body)?.into_unknown();
+            let ret = self.submit.call(None, &[body])?;
+            let ret =
+                unsafe { TransportResponseInput::from_napi_value(self.env.raw(), ret.raw())? };
+            Ok(TransportResponse::new(ret.status as u16, ret.body))
+        };
+        call().map_err(|e| bulk_keychain::Error::Transport(e.reason))
+    }
+}
+
+/// Tick/lot granularity for `priceTicks`/`sizeLots`
+#[napi(object)]
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SymbolSpecInput {
+    pub price_decimals: u32,
+    pub size_decimals: u32,
+    /// Price units (10^-priceDecimals) per tick, default 1
+    pub tick_units: Option<u32>,
+    /// Size units (10^-sizeDecimals) per lot, default 1
+    pub lot_units: Option<u32>,
+}
+
+impl From<&SymbolSpecInput> for SymbolSpec {
+    fn from(s: &SymbolSpecInput) -> Self {
+        SymbolSpec::new(s.price_decimals, s.size_decimals)
+            .with_tick_units(s.tick_units.unwrap_or(1) as u64)
+            .with_lot_units(s.lot_units.unwrap_or(1) as u64)
+    }
+}
+
+impl FromNapiValue for OrderInput {
+    /// Read through the core's casing-tolerant parser, so snake_case field
+    /// names and unknown-field handling match the WASM binding.
+    unsafe fn from_napi_value(env: sys::napi_env, napi_val: sys::napi_value) -> napi::Result<Self> {
+        let value = serde_json::Value::from_napi_value(env, napi_val)?;
+        parse_order_input(value, InputMode::Tolerant)
+            .map_err(|e| napi::Error::from_reason(e.reason))
+    }
+}
+
+/// Read an intent; under [`InputMode::Strict`] unknown fields are an error
+fn parse_order_input(mut value: serde_json::Value, mode: InputMode) -> Result<OrderInput> {
+    bulk_keychain::input::normalize_order_json(&mut value, mode).map_err(keychain_error)?;
+    serde_json::from_value(value).map_err(|e| Error::from_reason(e.to_string()))
+}
+
+fn parse_order_inputs(values: Vec<serde_json::Value>, mode: InputMode) -> Result<Vec<OrderInput>> {
+    values
+        .into_iter()
+        .map(|value| parse_order_input(value, mode))
+        .collect()
+}
+
+/// Read a flat input object after checking its field names against `known`
+fn parse_checked<T: serde::de::DeserializeOwned>(
+    value: serde_json::Value,
+    known: &[&'static str],
+    mode: InputMode,
+) -> Result<T> {
+    bulk_keychain::input::check_fields(&value, known, mode).map_err(keychain_error)?;
+    serde_json::from_value(value).map_err(|e| Error::from_reason(e.to_string()))
+}
+
+impl From<OrderTypeInput> for OrderTypeSpec {
+    fn from(ot: OrderTypeInput) -> Self {
+        Self {
+            type_name: ot.type_name,
+            tif: ot.tif,
+            is_market: ot.is_market,
+            trigger_px: ot.trigger_px,
+        }
+    }
+}
+
+impl From<OrderInput> for OrderSpec {
+    fn from(input: OrderInput) -> Self {
+        Self {
+            item_type: input.item_type,
+            symbol: input.symbol,
+            is_buy: input.is_buy,
+            price: input.price,
+            size: input.size,
+            reduce_only: input.reduce_only,
+            iso: input.iso,
+            order_type: input.order_type.map(OrderTypeSpec::from),
+            client_id: input.client_id,
+            order_id: input.order_id,
+            amount: input.amount,
+            symbols: input.symbols,
+            max_age_ms: input.max_age_ms.map(|ms| ms as u64),
+            trigger_price: input.trigger_price,
+            limit_price: input.limit_price,
+            pmin: input.pmin,
+            pmax: input.pmax,
+            lmin: input.lmin,
+            lmax: input.lmax,
+            trail_bps: input.trail_bps,
+            step_bps: input.step_bps,
+            p: input.p,
+            actions: input
+                .actions
+                .map(|actions| actions.into_iter().map(OrderSpec::from).collect()),
+            price_ticks: input.price_ticks,
+            size_lots: input.size_lots,
+            symbol_spec: input
+                .symbol_spec
+                .map(|s| bulk_keychain::input::SymbolSpecInput {
+                    price_decimals: s.price_decimals,
+                    size_decimals: s.size_decimals,
+                    tick_units: s.tick_units.map(u64::from),
+                    lot_units: s.lot_units.map(u64::from),
+                }),
+            on_fill: input.on_fill.map(|of| OnFillSpec {
+                p: of.p,
+                actions: of.actions.into_iter().map(OrderSpec::from).collect(),
+            }),
+        }
+    }
 }
 
 #[napi(object)]
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub struct OrderTypeInput {
     #[napi(js_name = "type")]
+    #[serde(rename = "type")]
     pub type_name: String,
     pub tif: Option<String>,
     pub is_market: Option<bool>,
@@ -569,12 +2796,51 @@ pub struct OrderTypeInput {
 }
 
 #[napi(object)]
-#[derive(Debug)]
+#[derive(Debug, Deserialize)]
 pub struct LeverageSetting {
     pub symbol: String,
     pub leverage: f64,
 }
 
+/// User settings, the same shape the WASM binding takes
+#[napi(object)]
+pub struct UserSettingsInput {
+    /// Entries may also be `[symbol, leverage]` pairs
+    pub max_leverage: Vec<LeverageSetting>,
+}
+
+/// `{ maxLeverage: [...] }` or a bare array of entries, each
+/// `{ symbol, leverage }` or a `[symbol, leverage]` pair.
+fn parse_user_settings(settings: serde_json::Value, mode: InputMode) -> Result<UserSettings> {
+    let entries = match settings {
+        serde_json::Value::Array(entries) => entries,
+        settings => {
+            bulk_keychain::input::check_fields(&settings, &["maxLeverage"], mode)
+                .map_err(keychain_error)?;
+            match settings.get("maxLeverage") {
+                Some(serde_json::Value::Array(entries)) => entries.clone(),
+                _ => {
+                    return Err(Error::from_reason(
+                        "settings.maxLeverage must be an array".to_string(),
+                    ))
+                }
+            }
+        }
+    };
+    let leverage = entries
+        .into_iter()
+        .map(|entry| match entry {
+            serde_json::Value::Array(_) => serde_json::from_value::<(String, f64)>(entry)
+                .map_err(|e| Error::from_reason(e.to_string())),
+            entry => {
+                let l: LeverageSetting = parse_checked(entry, &["symbol", "leverage"], mode)?;
+                Ok((l.symbol, l.leverage))
+            }
+        })
+        .collect::<Result<Vec<_>>>()?;
+    Ok(UserSettings::new(leverage))
+}
+
 #[napi(object)]
 #[derive(Debug)]
 pub struct OraclePriceInput {
@@ -596,10 +2862,13 @@ pub struct PythOraclePriceInput {
 #[napi(object)]
 #[derive(Debug)]
 pub struct SignedTransactionOutput {
-    /// Actions JSON as string
-    pub actions: String,
+    /// Actions (compact tagged format); a JSON string of them is also accepted as input
+    #[napi(ts_type = "Array<Record<string, unknown>>")]
+    pub actions: serde_json::Value,
+    /// Action kind ('order' | 'faucet' | 'agentWalletCreation' | ...); ignored on input
+    pub action_type: Option<String>,
     /// Nonce
-    pub nonce: f64,
+    pub nonce: JsNonce,
     /// Account public key (base58)
     pub account: String,
     /// Signer public key (base58)
@@ -611,13 +2880,83 @@ pub struct SignedTransactionOutput {
     pub order_id: Option<String>,
     /// Optional pre-computed order IDs for multi-order transactions.
     pub order_ids: Option<Vec<String>>,
+    /// Times these actions were re-signed under a fresh nonce (absent if never)
+    pub resign_count: Option<u32>,
+    /// Group intent tag ('bracket' | 'oco' | 'batch' | custom), not signed
+    pub group_kind: Option<String>,
+    /// Conflicting-leg findings from `signGroup` (absent if none)
+    pub group_warnings: Option<Vec<GroupLintWarningOutput>>,
 }
 
 impl From<bulk_keychain::SignedTransaction> for SignedTransactionOutput {
     fn from(tx: bulk_keychain::SignedTransaction) -> Self {
+        // Signing already rejected the actions that have no JSON form.
+        let actions = tx.actions_json().expect("signed actions render as JSON");
+        Self {
+            actions: actions.into(),
+            action_type: Some(tx.action_type().as_str().to_string()),
+            nonce: nonce_out(tx.nonce),
+            account: tx.account,
+            signer: tx.signer,
+            signature: tx.signature,
+            order_id: tx.order_id,
+            order_ids: tx.order_ids,
+            resign_count: (tx.resign_count > 0).then_some(tx.resign_count),
+            group_kind: tx.group_kind.map(String::from),
+            group_warnings: group_warnings_out(tx.group_warnings),
+        }
+    }
+}
+
+impl TryFrom<SignedTransactionOutput> for bulk_keychain::SignedTransaction {
+    type Error = Error;
+
+    fn try_from(tx: SignedTransactionOutput) -> Result<Self> {
+        Ok(Self {
+            action: action_in(tx.actions)?,
+            nonce: nonce_in(tx.nonce)?,
+            account: tx.account,
+            signer: tx.signer,
+            signature: tx.signature,
+            order_id: tx.order_id,
+            order_ids: tx.order_ids,
+            id_scheme: Default::default(),
+            resign_count: tx.resign_count.unwrap_or(0),
+            group_kind: tx.group_kind.map(GroupKind::from),
+            group_warnings: group_warnings_in(tx.group_warnings),
+        })
+    }
+}
+
+#[napi(object)]
+#[derive(Debug)]
+pub struct IndexedSignedTransactionOutput {
+    /// Index of the source order in the input array
+    pub index: u32,
+    /// Actions (compact tagged format)
+    #[napi(ts_type = "Array<Record<string, unknown>>")]
+    pub actions: serde_json::Value,
+    /// Nonce
+    pub nonce: JsNonce,
+    /// Account public key (base58)
+    pub account: String,
+    /// Signer public key (base58)
+    pub signer: String,
+    /// Signature (base58)
+    pub signature: String,
+    /// Pre-computed order ID for single-order transactions (base58).
+    pub order_id: Option<String>,
+    /// Optional pre-computed order IDs for multi-order transactions.
+    pub order_ids: Option<Vec<String>>,
+}
+
+impl From<bulk_keychain::IndexedSignedTransaction> for IndexedSignedTransactionOutput {
+    fn from(indexed: bulk_keychain::IndexedSignedTransaction) -> Self {
+        let tx = SignedTransactionOutput::from(indexed.tx);
         Self {
-            actions: serde_json::to_string(&tx.actions).unwrap_or_default(),
-            nonce: tx.nonce as f64,
+            index: indexed.index as u32,
+            actions: tx.actions,
+            nonce: tx.nonce,
             account: tx.account,
             signer: tx.signer,
             signature: tx.signature,
@@ -627,289 +2966,686 @@ impl From<bulk_keychain::SignedTransaction> for SignedTransactionOutput {
     }
 }
 
+/// Result of `signAllPartialIndexed` for one order
+#[napi(object)]
+pub struct PartialSignResultOutput {
+    /// Index of the source order in the input array
+    pub index: u32,
+    /// The signed transaction, if the order was signed
+    pub transaction: Option<SignedTransactionOutput>,
+    /// Why the order was refused
+    pub error: Option<String>,
+    /// Error code of the refusal, as on thrown errors
+    pub code: Option<String>,
+}
+
+impl
+    From<(
+        usize,
+        bulk_keychain::Result<bulk_keychain::SignedTransaction>,
+    )> for PartialSignResultOutput
+{
+    fn from(
+        (index, result): (
+            usize,
+            bulk_keychain::Result<bulk_keychain::SignedTransaction>,
+        ),
+    ) -> Self {
+        let (transaction, error, code) = match result {
+            Ok(tx) => (Some(tx.into()), None, None),
+            Err(e) => (None, Some(e.to_string()), Some(e.code().to_string())),
+        };
+        Self {
+            index: index as u32,
+            transaction,
+            error,
+            code,
+        }
+    }
+}
+
+/// Result of `planBatch`
+#[napi(object)]
+pub struct BatchPlanOutput {
+    /// Limit-order `price * size` per symbol, over the orders that would sign
+    pub per_symbol_notional: HashMap<String, f64>,
+    pub rejections: Vec<PlanRejectionOutput>,
+    pub adjustments: Vec<PlanAdjustmentOutput>,
+    /// Nonces the batch would use, `end` exclusive
+    pub nonce_range: NonceRangeOutput,
+    pub estimated_groups: u32,
+}
+
+#[napi(object)]
+pub struct PlanRejectionOutput {
+    /// Index of the order in the input array
+    pub index: u32,
+    pub error: String,
+}
+
+#[napi(object)]
+pub struct PlanAdjustmentOutput {
+    pub index: u32,
+    /// "price" or "size"
+    pub field: String,
+    pub from: f64,
+    pub to: f64,
+}
+
+#[napi(object)]
+pub struct NonceRangeOutput {
+    pub start: JsNonce,
+    pub end: JsNonce,
+}
+
+impl From<bulk_keychain::plan::BatchPlan> for BatchPlanOutput {
+    fn from(plan: bulk_keychain::plan::BatchPlan) -> Self {
+        Self {
+            per_symbol_notional: plan.per_symbol_notional.into_iter().collect(),
+            rejections: plan
+                .rejections
+                .into_iter()
+                .map(|(index, e)| PlanRejectionOutput {
+                    index: index as u32,
+                    error: e.to_string(),
+                })
+                .collect(),
+            adjustments: plan
+                .adjustments
+                .into_iter()
+                .map(|(index, a)| PlanAdjustmentOutput {
+                    index: index as u32,
+                    field: match a.field {
+                        bulk_keychain::plan::AdjustedField::Price => "price",
+                        bulk_keychain::plan::AdjustedField::Size => "size",
+                    }
+                    .to_string(),
+                    from: a.from,
+                    to: a.to,
+                })
+                .collect(),
+            nonce_range: NonceRangeOutput {
+                start: nonce_out(plan.nonce_range.start),
+                end: nonce_out(plan.nonce_range.end),
+            },
+            estimated_groups: plan.estimated_groups as u32,
+        }
+    }
+}
+
+/// Result of `peekOrderIds`
+#[napi(object)]
+pub struct PeekOrderIdsOutput {
+    pub order_ids: Vec<Option<String>>,
+    /// Present when no `baseNonce` was given
+    pub reservation: Option<NonceReservationOutput>,
+}
+
+/// Nonces `baseNonce..baseNonce + count` held for one batch until `expiresAt`
+#[napi(object)]
+pub struct NonceReservationOutput {
+    pub id: f64,
+    pub base_nonce: JsNonce,
+    pub count: u32,
+    pub expires_at: f64,
+    pub items_hash: String,
+}
+
+impl From<NonceReservation> for NonceReservationOutput {
+    fn from(r: NonceReservation) -> Self {
+        Self {
+            id: r.id as f64,
+            base_nonce: nonce_out(r.base_nonce),
+            count: r.count as u32,
+            expires_at: r.expires_at.as_millis() as f64,
+            items_hash: r.items_hash.to_base58(),
+        }
+    }
+}
+
+impl TryFrom<NonceReservationOutput> for NonceReservation {
+    type Error = Error;
+
+    fn try_from(r: NonceReservationOutput) -> Result<Self> {
+        Ok(Self {
+            id: r.id as u64,
+            base_nonce: nonce_in(r.base_nonce)?,
+            count: r.count as usize,
+            expires_at: TimestampMs(r.expires_at as u64),
+            items_hash: Hash::from_base58(&r.items_hash).map_err(keychain_error)?,
+        })
+    }
+}
+
 impl TryFrom<OrderInput> for OrderItem {
     type Error = Error;
 
     fn try_from(input: OrderInput) -> Result<Self> {
-        match input.item_type.as_str() {
-            "order" => {
-                let symbol = input
-                    .symbol
-                    .ok_or_else(|| Error::from_reason("order.symbol is required"))?;
-                let is_buy = input
-                    .is_buy
-                    .ok_or_else(|| Error::from_reason("order.isBuy is required"))?;
-                let price = input
-                    .price
-                    .ok_or_else(|| Error::from_reason("order.price is required"))?;
-                let size = input
-                    .size
-                    .ok_or_else(|| Error::from_reason("order.size is required"))?;
-                let reduce_only = input.reduce_only.unwrap_or(false);
-                let iso = input.iso.unwrap_or(false);
-
-                let order_type = match input.order_type {
-                    Some(ot) => match ot.type_name.as_str() {
-                        "limit" => {
-                            let tif_str = ot.tif.as_deref().unwrap_or("GTC");
-                            let tif = match tif_str.to_uppercase().as_str() {
-                                "GTC" => TimeInForce::Gtc,
-                                "IOC" => TimeInForce::Ioc,
-                                "ALO" => TimeInForce::Alo,
-                                _ => {
-                                    return Err(Error::from_reason(format!(
-                                        "Invalid tif: {}",
-                                        tif_str
-                                    )))
-                                }
-                            };
-                            OrderType::limit(tif)
-                        }
-                        "trigger" | "market" => OrderType::Trigger {
-                            is_market: ot.is_market.unwrap_or(true),
-                            trigger_px: ot.trigger_px.unwrap_or(0.0),
-                        },
-                        _ => {
-                            return Err(Error::from_reason(format!(
-                                "Invalid orderType: {}",
-                                ot.type_name
-                            )))
-                        }
-                    },
-                    None => OrderType::limit(TimeInForce::Gtc),
-                };
+        OrderItem::try_from(OrderSpec::from(input)).map_err(keychain_error)
+    }
+}
 
-                let client_id = input
-                    .client_id
-                    .map(|s| Hash::from_base58(&s))
-                    .transpose()
-                    .map_err(|e| Error::from_reason(format!("Invalid clientId: {}", e)))?;
-
-                let mut order = Order {
-                    symbol,
-                    is_buy,
-                    price,
-                    size,
-                    reduce_only,
-                    iso,
-                    order_type,
-                    client_id: None,
-                };
-                if let Some(cid) = client_id {
-                    order.client_id = Some(cid);
-                }
+// ============================================================================
+// Utility functions
+// ============================================================================
 
-                Ok(OrderItem::Order(order))
-            }
-            "cancel" => {
-                let symbol = input
-                    .symbol
-                    .ok_or_else(|| Error::from_reason("cancel.symbol is required"))?;
-                let order_id_str = input
-                    .order_id
-                    .ok_or_else(|| Error::from_reason("cancel.orderId is required"))?;
-                let order_id = Hash::from_base58(&order_id_str)
-                    .map_err(|e| Error::from_reason(format!("Invalid orderId: {}", e)))?;
+/// Generate a random hash (for client order IDs)
+#[napi]
+pub fn random_hash() -> String {
+    Hash::random().to_base58()
+}
+
+/// Get current timestamp in milliseconds
+#[napi]
+pub fn current_timestamp() -> f64 {
+    bulk_keychain::nonce::current_timestamp_millis() as f64
+}
+
+/// Shuffle signed transactions; the same seed always gives the same order
+///
+/// @example
+/// ```typescript
+/// const submitted = shuffleBatch(signer.signAll(orders), seed);
+/// ```
+#[napi]
+pub fn shuffle_batch(
+    txs: Vec<SignedTransactionOutput>,
+    seed: f64,
+) -> Result<Vec<SignedTransactionOutput>> {
+    let mut txs = txs
+        .into_iter()
+        .map(bulk_keychain::SignedTransaction::try_from)
+        .collect::<Result<Vec<_>>>()?;
+    bulk_keychain::shuffle_batch(&mut txs, seed as u64);
+    Ok(txs.into_iter().map(Into::into).collect())
+}
+
+/// Jitter order sizes by up to `pct` (0.1 = ±10%) before signing, keeping
+/// each symbol-and-side total unchanged
+///
+/// Only plain orders are jittered; other items pass through. With `specs`,
+/// sizes of those symbols move by whole lots. Jittered orders come back with
+/// `size` set and `sizeLots` cleared.
+#[napi]
+pub fn jitter_sizes(
+    mut orders: Vec<OrderInput>,
+    pct: f64,
+    seed: f64,
+    specs: Option<HashMap<String, SymbolSpecInput>>,
+) -> Result<Vec<OrderInput>> {
+    let mut indices = Vec::new();
+    let mut plain = Vec::new();
+    for (index, input) in orders.iter().enumerate() {
+        if let OrderItem::Order(order) = OrderItem::try_from(input.clone())? {
+            indices.push(index);
+            plain.push(order);
+        }
+    }
+    let specs: HashMap<String, SymbolSpec> = specs
+        .unwrap_or_default()
+        .iter()
+        .map(|(symbol, spec)| (symbol.clone(), SymbolSpec::from(spec)))
+        .collect();
+    bulk_keychain::jitter_sizes_with_specs(&mut plain, pct, seed as u64, &specs)
+        .map_err(keychain_error)?;
+    for (index, order) in indices.into_iter().zip(plain) {
+        orders[index].size = Some(order.size);
+        orders[index].size_lots = None;
+    }
+    Ok(orders)
+}
+
+/// Build metadata of the embedded core crate and of this addon
+#[napi(object)]
+pub struct BuildInfoOutput {
+    /// `bulk-keychain` core crate version
+    pub crate_version: String,
+    /// Core commit, or "unknown"
+    pub git_sha: String,
+    /// Core cargo features compiled in
+    pub features: Vec<String>,
+    /// Cargo profile: "debug" or "release"
+    pub profile: String,
+    /// Version of this addon
+    pub binding_version: String,
+}
+
+/// Which core build this addon embeds
+#[napi]
+pub fn build_info() -> BuildInfoOutput {
+    let info = bulk_keychain::build_info();
+    BuildInfoOutput {
+        crate_version: info.crate_version,
+        git_sha: info.git_sha,
+        features: info.features,
+        profile: info.profile,
+        binding_version: env!("CARGO_PKG_VERSION").to_string(),
+    }
+}
+
+/// JSON Schema (draft 2020-12) for one of the keychain's JSON shapes
+///
+/// Names: `OrderSpec`, `OrderTypeInput`, `PrepareOptions`, `UserSettings`,
+/// `SignedTransaction`, `PreparedMessage`.
+///
+/// @example
+/// ```typescript
+/// const schema = getJsonSchema('OrderSpec');
+/// ```
+#[napi]
+pub fn get_json_schema(name: String) -> Result<serde_json::Value> {
+    bulk_keychain::schemas::json_schema_for(&name).ok_or_else(|| {
+        Error::from_reason(format!(
+            "unknown schema {}; expected one of {}",
+            name,
+            bulk_keychain::schemas::SCHEMA_NAMES.join(", ")
+        ))
+    })
+}
+
+/// Check a signed transaction from anywhere (a wallet, another process)
+/// before submitting it: `false` if the signature does not verify against
+/// `tx.signer`. Throws on malformed fields, or if `orderId` / `orderIds`
+/// differ from the IDs the signed actions produce.
+#[napi]
+pub fn verify_transaction(tx: SignedTransactionOutput) -> Result<bool> {
+    let tx: bulk_keychain::SignedTransaction = tx.try_into()?;
+    bulk_keychain::verify_transaction(&tx).map_err(keychain_error)
+}
+
+/// Whether a `capabilitiesSigned()` result is intact and was signed by the
+/// key its descriptor names
+#[napi]
+pub fn verify_capabilities(signed: SignedCapabilitiesOutput) -> Result<bool> {
+    bulk_keychain::SignedCapabilities {
+        payload: signed.payload,
+        signer: signed.signer,
+        signature: signed.signature,
+    }
+    .verify()
+    .map_err(keychain_error)
+}
+
+/// Validate a base58-encoded public key
+#[napi]
+pub fn validate_pubkey(s: String) -> bool {
+    Pubkey::from_base58(&s).is_ok()
+}
+
+/// Short display-safe identifier (`bk1:...`) for a base58-encoded public key
+#[napi]
+pub fn pubkey_fingerprint(pubkey: String) -> Result<String> {
+    let pubkey = Pubkey::from_base58(&pubkey).map_err(keychain_error)?;
+    Ok(pubkey.fingerprint())
+}
+
+/// Validate a base58-encoded hash
+#[napi]
+pub fn validate_hash(s: String) -> bool {
+    Hash::from_base58(&s).is_ok()
+}
+
+/// Compute SHA256 hash from raw bytes.
+///
+/// This is a raw utility and does not apply BULK order-ID canonicalization.
+#[napi]
+pub fn compute_order_id(wincode_bytes: Buffer) -> String {
+    Hash::from_wincode_bytes(&wincode_bytes).to_base58()
+}
+
+fn commitment_salt(salt: &[u8]) -> Result<[u8; 32]> {
+    salt.try_into()
+        .map_err(|_| Error::from_reason(format!("salt must be 32 bytes, got {}", salt.len())))
+}
+
+/// Result of `validateOrderInput`
+#[napi(object)]
+pub struct OrderValidationOutput {
+    /// The intent with camelCase field names
+    pub order: serde_json::Value,
+    /// A `path: unknown field` entry per field that was dropped
+    pub warnings: Vec<String>,
+}
+
+/// Check an order intent without signing it
+///
+/// Field names may be camelCase or snake_case; with `strict`, one intent must
+/// not mix the two.
+#[napi]
+pub fn validate_order_input(
+    #[napi(ts_arg_type = "OrderInput")] order: serde_json::Value,
+    strict: Option<bool>,
+) -> Result<OrderValidationOutput> {
+    let casing = if strict.unwrap_or(false) {
+        InputMode::Strict
+    } else {
+        InputMode::Tolerant
+    };
+    let parsed = bulk_keychain::validate_order_input(order, casing).map_err(keychain_error)?;
+    Ok(OrderValidationOutput {
+        order: serde_json::to_value(parsed.spec).map_err(|e| Error::from_reason(e.to_string()))?,
+        warnings: parsed.warnings,
+    })
+}
+
+/// Human-readable summary of an order, e.g. `BUY 0.1 BTC-USD @ 50000 (GTC)`
+#[napi]
+pub fn order_to_display_string(order: OrderInput) -> Result<String> {
+    let item: OrderItem = order.try_into()?;
+    Ok(item.to_display_string())
+}
+
+/// Throw unless `displayed` is exactly the price `order` signs
+///
+/// Run right before requesting the wallet signature. `displayed` may use `,`
+/// thousands separators; decimal commas are rejected. With `maxDecimals`, also
+/// throw if showing the price with that many decimals would round it.
+///
+/// @example
+/// ```typescript
+/// confirmPriceMatches('100,000.05', order, 2);
+/// ```
+#[napi]
+pub fn confirm_price_matches(
+    displayed: String,
+    order: OrderInput,
+    max_decimals: Option<u32>,
+) -> Result<()> {
+    let order = match order.try_into()? {
+        OrderItem::Order(order) => order,
+        _ => return Err(Error::from_reason("order must have type 'order'")),
+    };
+    let mut policy = bulk_keychain::DisplayPolicy::new();
+    if let Some(decimals) = max_decimals {
+        policy = policy.with_max_decimals(order.symbol.clone(), decimals);
+    }
+    bulk_keychain::confirm_price_matches(&displayed, &order, &policy)
+        .map_err(|e| Error::from_reason(e.to_string()))
+}
+
+/// Price in the symbol's quote asset at the spec's price decimals
+///
+/// @example
+/// ```typescript
+/// formatPrice('BTC-USD', 100000, { priceDecimals: 2, sizeDecimals: 4 }); // '100,000.00 USD'
+/// ```
+#[napi]
+pub fn format_price(symbol: String, price: f64, spec: SymbolSpecInput) -> String {
+    bulk_keychain::format::format_price(&symbol, price, &SymbolSpec::from(&spec))
+}
+
+/// Size in the symbol's base asset at the spec's size decimals, e.g. `0.10 BTC`
+#[napi]
+pub fn format_size(symbol: String, size: f64, spec: SymbolSpecInput) -> String {
+    bulk_keychain::format::format_size(&symbol, size, &SymbolSpec::from(&spec))
+}
+
+/// Parse a user-entered amount into an exact decimal string
+///
+/// Accepts `,` thousands separators and a `k`/`m` suffix; throws on a decimal
+/// comma (`1.234,56`) with the dot form to use instead.
+///
+/// @example
+/// ```typescript
+/// parseAmount('0.5k'); // '500'
+/// ```
+#[napi]
+pub fn parse_amount(input: String) -> Result<String> {
+    bulk_keychain::format::parse_amount(&input)
+        .map(|amount| amount.to_string())
+        .map_err(keychain_error)
+}
+
+/// Base and quote assets of a symbol
+#[napi(object)]
+pub struct SymbolParts {
+    pub base: String,
+    pub quote: String,
+}
+
+/// Split a `BASE-QUOTE` symbol, e.g. `BTC-USD` into `{ base: 'BTC', quote: 'USD' }`
+#[napi]
+pub fn parse_symbol(pair: String) -> Result<SymbolParts> {
+    let (base, quote) = bulk_keychain::symbol::split(&pair).map_err(keychain_error)?;
+    Ok(SymbolParts {
+        base: base.to_string(),
+        quote: quote.to_string(),
+    })
+}
+
+/// Canonical `BASE-QUOTE` symbol, uppercased
+#[napi]
+pub fn make_symbol(base: String, quote: String) -> String {
+    bulk_keychain::symbol::canonical(&base, &quote)
+}
+
+/// Market context for `lintOrder`
+#[napi(object)]
+pub struct LintContextInput {
+    /// Reference (mark or mid) price per symbol
+    pub reference_prices: Option<HashMap<String, f64>>,
+    /// Signed position size per symbol (positive = long)
+    pub position_hints: Option<HashMap<String, f64>>,
+    /// Distance through the reference that flags a GTC limit (default 2000)
+    pub marketable_bps: Option<u32>,
+    /// Distance behind the reference that flags an IOC (default 500)
+    pub far_ioc_bps: Option<u32>,
+}
+
+impl From<LintContextInput> for LintContext {
+    fn from(input: LintContextInput) -> Self {
+        let defaults = LintContext::default();
+        Self {
+            reference_prices: input.reference_prices.unwrap_or_default(),
+            position_hints: input.position_hints.unwrap_or_default(),
+            marketable_bps: input.marketable_bps.unwrap_or(defaults.marketable_bps),
+            far_ioc_bps: input.far_ioc_bps.unwrap_or(defaults.far_ioc_bps),
+        }
+    }
+}
+
+/// One `lintOrder` finding
+#[napi(object)]
+pub struct LintWarningOutput {
+    /// e.g. `marketable_limit`, `reduce_only_no_position`, `far_ioc`, `trigger_wrong_side`
+    pub code: String,
+    pub message: String,
+    /// `info`, `warning` or `critical`
+    pub severity: String,
+}
+
+/// One conflicting-leg finding on a signed or prepared group
+#[napi(object)]
+#[derive(Debug)]
+pub struct GroupLintWarningOutput {
+    /// Position of the leg in the group
+    pub index: u32,
+    /// `duplicate_leg`, `protection_same_side` or `oversized_reduce_only`
+    pub code: String,
+    pub message: String,
+    /// `info`, `warning` or `critical`
+    pub severity: String,
+}
+
+fn group_warnings_out(warnings: Vec<GroupLintWarning>) -> Option<Vec<GroupLintWarningOutput>> {
+    if warnings.is_empty() {
+        return None;
+    }
+    Some(
+        warnings
+            .into_iter()
+            .map(|w| GroupLintWarningOutput {
+                index: w.index as u32,
+                code: w.warning.code.as_str().to_string(),
+                message: w.warning.message,
+                severity: w.warning.severity.as_str().to_string(),
+            })
+            .collect(),
+    )
+}
 
-                Ok(OrderItem::Cancel(Cancel::new(symbol, order_id)))
-            }
-            "modify" => {
-                let symbol = input
-                    .symbol
-                    .ok_or_else(|| Error::from_reason("modify.symbol is required"))?;
-                let order_id_str = input
-                    .order_id
-                    .ok_or_else(|| Error::from_reason("modify.orderId is required"))?;
-                let amount = input
-                    .amount
-                    .ok_or_else(|| Error::from_reason("modify.amount is required"))?;
-                let order_id = Hash::from_base58(&order_id_str)
-                    .map_err(|e| Error::from_reason(format!("Invalid orderId: {}", e)))?;
-                Ok(OrderItem::Modify(Modify::new(order_id, symbol, amount)))
-            }
-            "cancelAll" => {
-                let symbols = input.symbols.unwrap_or_default();
-                Ok(OrderItem::CancelAll(CancelAll::for_symbols(symbols)))
-            }
-            "stop" | "st" => {
-                let symbol = input
-                    .symbol
-                    .ok_or_else(|| Error::from_reason("stop.symbol is required"))?;
-                let is_buy = input
-                    .is_buy
-                    .ok_or_else(|| Error::from_reason("stop.isBuy is required"))?;
-                let size = input
-                    .size
-                    .ok_or_else(|| Error::from_reason("stop.size is required"))?;
-                let trigger_price = input
-                    .trigger_price
-                    .ok_or_else(|| Error::from_reason("stop.triggerPrice is required"))?;
-                let limit_price = input.limit_price.unwrap_or(f64::NAN);
-                Ok(OrderItem::Stop(Stop {
-                    symbol,
-                    is_buy,
-                    size,
-                    trigger_price,
-                    limit_price,
-                    iso: input.iso.unwrap_or(false),
-                }))
-            }
-            "takeProfit" | "tp" => {
-                let symbol = input
-                    .symbol
-                    .ok_or_else(|| Error::from_reason("takeProfit.symbol is required"))?;
-                let is_buy = input
-                    .is_buy
-                    .ok_or_else(|| Error::from_reason("takeProfit.isBuy is required"))?;
-                let size = input
-                    .size
-                    .ok_or_else(|| Error::from_reason("takeProfit.size is required"))?;
-                let trigger_price = input
-                    .trigger_price
-                    .ok_or_else(|| Error::from_reason("takeProfit.triggerPrice is required"))?;
-                let limit_price = input.limit_price.unwrap_or(f64::NAN);
-                Ok(OrderItem::TakeProfit(TakeProfit {
-                    symbol,
-                    is_buy,
-                    size,
-                    trigger_price,
-                    limit_price,
-                    iso: input.iso.unwrap_or(false),
-                }))
-            }
-            "range" | "rng" => {
-                let symbol = input
-                    .symbol
-                    .ok_or_else(|| Error::from_reason("range.symbol is required"))?;
-                let is_buy = input
-                    .is_buy
-                    .ok_or_else(|| Error::from_reason("range.isBuy is required"))?;
-                let size = input
-                    .size
-                    .ok_or_else(|| Error::from_reason("range.size is required"))?;
-                let collar_min = input
-                    .pmin
-                    .ok_or_else(|| Error::from_reason("range.pmin is required"))?;
-                let collar_max = input
-                    .pmax
-                    .ok_or_else(|| Error::from_reason("range.pmax is required"))?;
-                let limit_min = input.lmin.unwrap_or(f64::NAN);
-                let limit_max = input.lmax.unwrap_or(f64::NAN);
-                Ok(OrderItem::RangeOco(RangeOco {
-                    symbol,
-                    is_buy,
-                    size,
-                    collar_min,
-                    collar_max,
-                    limit_min,
-                    limit_max,
-                    iso: input.iso.unwrap_or(false),
-                }))
-            }
-            "trig" => {
-                let symbol = input
-                    .symbol
-                    .ok_or_else(|| Error::from_reason("trig.symbol is required"))?;
-                let is_buy = input
-                    .is_buy
-                    .ok_or_else(|| Error::from_reason("trig.isBuy is required"))?;
-                let trigger_price = input
-                    .trigger_price
-                    .ok_or_else(|| Error::from_reason("trig.triggerPrice is required"))?;
-                let raw_actions = input
-                    .actions
-                    .ok_or_else(|| Error::from_reason("trig.actions is required"))?;
-                let iso = input.iso.unwrap_or(false);
-                let actions: Result<Vec<OrderItem>> =
-                    raw_actions.into_iter().map(|a| a.try_into()).collect();
-                Ok(OrderItem::TriggerBasket(TriggerBasket {
-                    symbol,
-                    is_buy,
-                    trigger_price,
-                    actions: actions?,
-                    iso,
-                }))
-            }
-            "onFill" | "of" => {
-                let raw_actions = input
-                    .actions
-                    .ok_or_else(|| Error::from_reason("onFill.actions is required"))?;
-                let actions: Result<Vec<OrderItem>> =
-                    raw_actions.into_iter().map(|a| a.try_into()).collect();
-                Ok(OrderItem::OnFill(OnFill {
-                    p: 0,
-                    actions: actions?,
-                }))
-            }
-            "trailingStop" | "trl" => {
-                let symbol = input
-                    .symbol
-                    .ok_or_else(|| Error::from_reason("trl.symbol is required"))?;
-                let is_buy = input
-                    .is_buy
-                    .ok_or_else(|| Error::from_reason("trl.isBuy is required"))?;
-                let size = input
-                    .size
-                    .ok_or_else(|| Error::from_reason("trl.size is required"))?;
-                let trail_bps = input
-                    .trail_bps
-                    .ok_or_else(|| Error::from_reason("trl.trailBps is required"))?;
-                let step_bps = input
-                    .step_bps
-                    .ok_or_else(|| Error::from_reason("trl.stepBps is required"))?;
-                let limit_price = input.limit_price;
-                Ok(OrderItem::TrailingStop(TrailingStop {
-                    symbol,
-                    is_buy,
-                    size,
-                    trail_bps,
-                    step_bps,
-                    limit_price,
-                    iso: input.iso.unwrap_or(false),
-                }))
-            }
-            _ => Err(Error::from_reason(format!(
-                "Invalid item type: {}",
-                input.item_type
-            ))),
-        }
+/// Read `actions` back from an output: an array, or the JSON string older
+/// versions produced.
+fn actions_in(actions: serde_json::Value) -> Result<Vec<serde_json::Value>> {
+    match actions {
+        serde_json::Value::String(json) => serde_json::from_str(&json),
+        actions => serde_json::from_value(actions),
     }
+    .map_err(|e| Error::from_reason(format!("invalid actions: {}", e)))
 }
 
-// ============================================================================
-// Utility functions
-// ============================================================================
+fn action_in(actions: serde_json::Value) -> Result<bulk_keychain::Action> {
+    bulk_keychain::compact::action_from_json_list(&actions_in(actions)?)
+        .map_err(|e| Error::from_reason(format!("invalid actions: {}", e)))
+}
 
-/// Generate a random hash (for client order IDs)
+/// Read findings back from an output; entries with unknown codes are dropped.
+fn group_warnings_in(warnings: Option<Vec<GroupLintWarningOutput>>) -> Vec<GroupLintWarning> {
+    fn parse<T: serde::de::DeserializeOwned>(s: String) -> Option<T> {
+        serde_json::from_value(serde_json::Value::String(s)).ok()
+    }
+    warnings
+        .unwrap_or_default()
+        .into_iter()
+        .filter_map(|w| {
+            Some(GroupLintWarning {
+                index: w.index as usize,
+                warning: bulk_keychain::LintWarning {
+                    code: parse(w.code)?,
+                    message: w.message,
+                    severity: parse(w.severity)?,
+                },
+            })
+        })
+        .collect()
+}
+
+/// Flag economically suspicious but valid orders
+///
+/// @example
+/// ```typescript
+/// const warnings = lintOrder(order, { referencePrices: { 'BTC-USD': 100000 } });
+/// ```
 #[napi]
-pub fn random_hash() -> String {
-    Hash::random().to_base58()
+pub fn lint_order(order: OrderInput, context: LintContextInput) -> Result<Vec<LintWarningOutput>> {
+    let item: OrderItem = order.try_into()?;
+    Ok(bulk_keychain::lint_order(&item, &context.into())
+        .into_iter()
+        .map(|w| LintWarningOutput {
+            code: w.code.as_str().to_string(),
+            message: w.message,
+            severity: w.severity.as_str().to_string(),
+        })
+        .collect())
 }
 
-/// Get current timestamp in milliseconds
+/// What `diffOrders` found between an order and its replacement
+#[napi(object)]
+pub struct OrderDiffOutput {
+    pub price_delta: f64,
+    /// `priceDelta` in whole ticks; null without a spec
+    pub price_delta_ticks: Option<i64>,
+    pub size_delta: f64,
+    pub tif_changed: bool,
+    /// Almost always a bug in a cancel-replace
+    pub side_changed: bool,
+    /// Reduce-only or isolated-margin flag differs
+    pub flags_changed: bool,
+}
+
+/// What replacing `original` with `replacement` would change
+///
+/// Both must have type `'order'`.
+///
+/// @example
+/// ```typescript
+/// const diff = diffOrders(live, next, { priceDecimals: 1, sizeDecimals: 3 });
+/// if (diff.sideChanged) throw new Error('replacement flips side');
+/// ```
 #[napi]
-pub fn current_timestamp() -> f64 {
-    bulk_keychain::nonce::current_timestamp_millis() as f64
+pub fn diff_orders(
+    original: OrderInput,
+    replacement: OrderInput,
+    spec: Option<SymbolSpecInput>,
+) -> Result<OrderDiffOutput> {
+    let order = |input: OrderInput, name: &str| match input.try_into()? {
+        OrderItem::Order(order) => Ok(order),
+        _ => Err(Error::from_reason(format!(
+            "{} must have type 'order'",
+            name
+        ))),
+    };
+    let original = order(original, "original")?;
+    let replacement = order(replacement, "replacement")?;
+    let spec = spec.as_ref().map(SymbolSpec::from);
+    let diff = bulk_keychain::diff_orders(&original, &replacement, spec.as_ref());
+    Ok(OrderDiffOutput {
+        price_delta: diff.price_delta,
+        price_delta_ticks: diff.price_delta_ticks,
+        size_delta: diff.size_delta,
+        tif_changed: diff.tif_changed,
+        side_changed: diff.side_changed,
+        flags_changed: diff.flags_changed,
+    })
 }
 
-/// Validate a base58-encoded public key
+/// Estimated fee of each order at `feeBps` (`price * size * feeBps / 10000`)
+///
+/// Nothing is signed. Cancels, modifies and market orders estimate as 0.
 #[napi]
-pub fn validate_pubkey(s: String) -> bool {
-    Pubkey::from_base58(&s).is_ok()
+pub fn estimate_batch_fees(orders: Vec<OrderInput>, fee_bps: f64) -> Result<Vec<f64>> {
+    let items: Result<Vec<OrderItem>> = orders.into_iter().map(|o| o.try_into()).collect();
+    Ok(items?
+        .iter()
+        .map(|item| item.estimated_fee(fee_bps))
+        .collect())
 }
 
-/// Validate a base58-encoded hash
+/// Sum of `estimateBatchFees`
 #[napi]
-pub fn validate_hash(s: String) -> bool {
-    Hash::from_base58(&s).is_ok()
+pub fn estimate_total_batch_fee(orders: Vec<OrderInput>, fee_bps: f64) -> Result<f64> {
+    Ok(estimate_batch_fees(orders, fee_bps)?.iter().sum())
 }
 
-/// Compute SHA256 hash from raw bytes.
+/// Commit to an order's terms under a 32-byte secret salt (base58 hash).
 ///
-/// This is a raw utility and does not apply BULK order-ID canonicalization.
+/// Nonce and account are not covered, so the commitment can be published
+/// before the order is signed.
 #[napi]
-pub fn compute_order_id(wincode_bytes: Buffer) -> String {
-    Hash::from_wincode_bytes(&wincode_bytes).to_base58()
+pub fn order_commitment(order: OrderInput, salt: Buffer) -> Result<String> {
+    let item: OrderItem = order.try_into()?;
+    let salt = commitment_salt(&salt)?;
+    item.commitment(&salt)
+        .map(|c| c.to_base58())
+        .map_err(keychain_error)
+}
+
+/// Check that an order and salt open a commitment from `orderCommitment`
+#[napi]
+pub fn verify_order_commitment(
+    order: OrderInput,
+    salt: Buffer,
+    commitment: String,
+) -> Result<bool> {
+    let item: OrderItem = order.try_into()?;
+    let salt = commitment_salt(&salt)?;
+    let commitment = Hash::from_base58(&commitment).map_err(keychain_error)?;
+    Ok(verify_commitment(&item, &salt, &commitment))
+}
+
+/// Encode a signed transaction as a compact binary frame
+#[napi]
+pub fn to_framed(tx: SignedTransactionOutput) -> Result<Buffer> {
+    let tx: bulk_keychain::SignedTransaction = tx.try_into()?;
+    tx.to_framed().map(Buffer::from).map_err(keychain_error)
+}
+
+/// Decode a binary frame produced by `toFramed`
+#[napi]
+pub fn from_framed(buf: Buffer) -> Result<SignedTransactionOutput> {
+    bulk_keychain::SignedTransaction::from_framed(&buf)
+        .map(Into::into)
+        .map_err(keychain_error)
 }
 
 // ============================================================================
@@ -918,14 +3654,74 @@ pub fn compute_order_id(wincode_bytes: Buffer) -> String {
 
 /// Options for preparing a message
 #[napi(object)]
-#[derive(Debug)]
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub struct PrepareOptions {
     /// Account public key (base58) - the trading account
     pub account: String,
     /// Signer public key (base58) - defaults to account if not provided
     pub signer: Option<String>,
     /// Nonce - defaults to current timestamp if not provided
-    pub nonce: Option<f64>,
+    #[serde(skip)]
+    pub nonce: Option<JsNonce>,
+    /// Group intent tag (prepareGroup only): 'bracket' | 'oco' | 'batch' | custom
+    pub group_kind: Option<String>,
+    /// Refuse conflicting legs instead of reporting them (prepareGroup only)
+    pub strict_group: Option<bool>,
+    /// Reject unknown fields in the orders and these options (order prepare
+    /// functions only)
+    pub strict: Option<bool>,
+}
+
+const PREPARE_OPTION_FIELDS: &[&str] = &[
+    "account",
+    "signer",
+    "nonce",
+    "groupKind",
+    "strictGroup",
+    "strict",
+];
+
+impl PrepareOptions {
+    fn input_mode(&self) -> InputMode {
+        if self.strict == Some(true) {
+            InputMode::Strict
+        } else {
+            InputMode::Tolerant
+        }
+    }
+}
+
+/// Read options for a prepare function that honors `strict`
+fn parse_prepare_options(object: Object) -> Result<PrepareOptions> {
+    // The nonce may be a BigInt, which has no JSON form; read it separately.
+    let mut fields = serde_json::Map::new();
+    for key in Object::keys(&object).map_err(napi_error)? {
+        let value = if key == "nonce" {
+            Some(serde_json::Value::Null)
+        } else {
+            object
+                .get::<_, serde_json::Value>(&key)
+                .map_err(napi_error)?
+        };
+        if let Some(value) = value {
+            fields.insert(key, value);
+        }
+    }
+    let value = serde_json::Value::Object(fields);
+    let mut options: PrepareOptions =
+        serde_json::from_value(value.clone()).map_err(|e| Error::from_reason(e.to_string()))?;
+    bulk_keychain::input::check_fields(&value, PREPARE_OPTION_FIELDS, options.input_mode())
+        .map_err(keychain_error)?;
+    options.nonce = match object
+        .get::<_, Either3<f64, BigInt, Null>>("nonce")
+        .map_err(napi_error)?
+    {
+        Some(Either3::A(n)) => Some(Either::A(n)),
+        Some(Either3::B(n)) => Some(Either::B(n)),
+        Some(Either3::C(_)) | None => None,
+    };
+    Ok(options)
 }
 
 /// Options for preparing a sub-account creation
@@ -937,7 +3733,7 @@ pub struct CreateSubAccountOptions {
     /// Signer public key (base58) - defaults to account if not provided
     pub signer: Option<String>,
     /// Nonce - defaults to current timestamp if not provided
-    pub nonce: Option<f64>,
+    pub nonce: Option<JsNonce>,
     /// Optional margin asset symbol to transfer. Required when marginAmount is non-zero.
     pub margin_symbol: Option<String>,
     /// Optional initial margin amount. Default 0.0
@@ -948,7 +3744,8 @@ pub struct CreateSubAccountOptions {
 #[napi(object)]
 pub struct PreparedMessageOutput {
     /// Raw message bytes to sign (pass to wallet.signMessage())
-    pub message_bytes: Buffer,
+    #[napi(ts_type = "Buffer")]
+    pub message_bytes: MessageBuffer,
     /// Message as base58 string
     pub message_base58: String,
     /// Message as base64 string
@@ -959,33 +3756,63 @@ pub struct PreparedMessageOutput {
     pub order_id: Option<String>,
     /// Optional pre-computed order IDs for multi-order transactions.
     pub order_ids: Option<Vec<String>>,
-    /// Actions JSON as string
-    pub actions: String,
+    /// Actions (compact tagged format); a JSON string of them is also accepted as input
+    #[napi(ts_type = "Array<Record<string, unknown>>")]
+    pub actions: serde_json::Value,
     /// Account public key (base58)
     pub account: String,
     /// Signer public key (base58)
     pub signer: String,
     /// Nonce used for this transaction
-    pub nonce: f64,
+    pub nonce: JsNonce,
+    /// Group intent tag, carried through to the finalized transaction
+    pub group_kind: Option<String>,
+    /// Conflicting-leg findings from `prepareGroup` (absent if none)
+    pub group_warnings: Option<Vec<GroupLintWarningOutput>>,
+    /// Index of the source order in the input array (`prepareAllIndexed` only)
+    pub index: Option<u32>,
 }
 
 impl From<PreparedMessage> for PreparedMessageOutput {
     fn from(p: PreparedMessage) -> Self {
         Self {
-            message_bytes: Buffer::from(p.message_bytes.clone()),
+            message_bytes: MessageBuffer::new(p.message_bytes.clone()),
             message_base58: p.message_base58(),
             message_base64: p.message_base64(),
             message_hex: p.message_hex(),
             order_id: p.order_id,
             order_ids: p.order_ids,
-            actions: serde_json::to_string(&p.actions).unwrap_or_default(),
+            actions: p.actions.into(),
             account: p.account,
             signer: p.signer,
-            nonce: p.nonce as f64,
+            nonce: nonce_out(p.nonce),
+            group_kind: p.group_kind.map(String::from),
+            group_warnings: group_warnings_out(p.group_warnings),
+            index: None,
         }
     }
 }
 
+impl TryFrom<PreparedMessageOutput> for PreparedMessage {
+    type Error = Error;
+
+    fn try_from(prepared: PreparedMessageOutput) -> Result<Self> {
+        let actions = actions_in(prepared.actions)?;
+        Ok(PreparedMessage {
+            message_bytes: prepared.message_bytes.to_vec(),
+            order_id: prepared.order_id,
+            order_ids: prepared.order_ids,
+            actions,
+            account: prepared.account,
+            signer: prepared.signer,
+            nonce: nonce_in(prepared.nonce)?,
+            id_scheme: Default::default(),
+            group_kind: prepared.group_kind.map(GroupKind::from),
+            group_warnings: group_warnings_in(prepared.group_warnings),
+        })
+    }
+}
+
 /// Prepare a single order for external wallet signing
 ///
 /// Use this when you don't have access to the private key and need
@@ -998,38 +3825,30 @@ impl From<PreparedMessage> for PreparedMessageOutput {
 /// const signed = finalizeTransaction(prepared, signature);
 /// ```
 #[napi]
-pub fn prepare_order(order: OrderInput, options: PrepareOptions) -> Result<PreparedMessageOutput> {
-    let account =
-        Pubkey::from_base58(&options.account).map_err(|e| Error::from_reason(e.to_string()))?;
+pub fn prepare_order(
+    #[napi(ts_arg_type = "OrderInput")] order: serde_json::Value,
+    #[napi(ts_arg_type = "PrepareOptions")] options: Object,
+) -> Result<PreparedMessageOutput> {
+    let options = parse_prepare_options(options)?;
+    let order = parse_order_input(order, options.input_mode())?;
+    let account = Pubkey::from_base58(&options.account).map_err(keychain_error)?;
     let signer = options
         .signer
         .map(|s| Pubkey::from_base58(&s))
         .transpose()
-        .map_err(|e| Error::from_reason(e.to_string()))?;
-    let nonce = options.nonce.map(|n| n as u64);
+        .map_err(keychain_error)?;
+    let nonce = nonce_opt(options.nonce)?;
 
     // If onFill is present, emit parent + OnFill as an atomic group
-    let on_fill_input = order.on_fill;
-    let order_no_fill = OrderInput {
-        on_fill: None,
-        ..order
-    };
-
-    let prepared = if let Some(of) = on_fill_input {
-        let parent: OrderItem = order_no_fill.try_into()?;
-        let consequents: Result<Vec<OrderItem>> =
-            of.actions.into_iter().map(|a| a.try_into()).collect();
-        let of_item = OrderItem::OnFill(OnFill {
-            p: of.p,
-            actions: consequents?,
-        });
-        prepare_group(vec![parent, of_item], &account, signer.as_ref(), nonce)
-            .map_err(|e| Error::from_reason(e.to_string()))?
+    let mut items = OrderSpec::from(order)
+        .into_items()
+        .map_err(keychain_error)?;
+    let prepared = if items.len() > 1 {
+        prepare_group(items, &account, signer.as_ref(), nonce)
     } else {
-        let order_item: OrderItem = order_no_fill.try_into()?;
-        prepare_message(order_item, &account, signer.as_ref(), nonce)
-            .map_err(|e| Error::from_reason(e.to_string()))?
-    };
+        prepare_message(items.remove(0), &account, signer.as_ref(), nonce)
+    }
+    .map_err(keychain_error)?;
 
     Ok(prepared.into())
 }
@@ -1041,32 +3860,64 @@ pub fn prepare_order(order: OrderInput, options: PrepareOptions) -> Result<Prepa
 /// @example
 /// ```typescript
 /// const orders = [order1, order2, order3];
-/// const prepared = prepareAllOrders(orders, { account: myPubkey });
+/// const prepared = prepareAll(orders, { account: myPubkey });
 /// // Sign each with wallet, then finalize
 /// ```
-#[napi]
+#[napi(js_name = "prepareAll")]
 pub fn prepare_all_orders(
-    orders: Vec<OrderInput>,
-    options: PrepareOptions,
+    #[napi(ts_arg_type = "Array<OrderInput>")] orders: Vec<serde_json::Value>,
+    #[napi(ts_arg_type = "PrepareOptions")] options: Object,
 ) -> Result<Vec<PreparedMessageOutput>> {
+    let options = parse_prepare_options(options)?;
+    let orders = parse_order_inputs(orders, options.input_mode())?;
     let order_items: Result<Vec<OrderItem>> = orders.into_iter().map(|o| o.try_into()).collect();
     let order_items = order_items?;
 
-    let account =
-        Pubkey::from_base58(&options.account).map_err(|e| Error::from_reason(e.to_string()))?;
+    let account = Pubkey::from_base58(&options.account).map_err(keychain_error)?;
     let signer = options
         .signer
         .map(|s| Pubkey::from_base58(&s))
         .transpose()
-        .map_err(|e| Error::from_reason(e.to_string()))?;
-    let base_nonce = options.nonce.map(|n| n as u64);
+        .map_err(keychain_error)?;
+    let base_nonce = nonce_opt(options.nonce)?;
 
-    let prepared = prepare_all(order_items, &account, signer.as_ref(), base_nonce)
-        .map_err(|e| Error::from_reason(e.to_string()))?;
+    let prepared =
+        prepare_all(order_items, &account, signer.as_ref(), base_nonce).map_err(keychain_error)?;
 
     Ok(prepared.into_iter().map(Into::into).collect())
 }
 
+/// Like prepareAll, but each prepared message carries the `index` of its input order
+#[napi(js_name = "prepareAllIndexed")]
+pub fn prepare_all_orders_indexed(
+    #[napi(ts_arg_type = "Array<OrderInput>")] orders: Vec<serde_json::Value>,
+    #[napi(ts_arg_type = "PrepareOptions")] options: Object,
+) -> Result<Vec<PreparedMessageOutput>> {
+    let options = parse_prepare_options(options)?;
+    let orders = parse_order_inputs(orders, options.input_mode())?;
+    let order_items: Result<Vec<OrderItem>> = orders.into_iter().map(|o| o.try_into()).collect();
+    let order_items = order_items?;
+
+    let account = Pubkey::from_base58(&options.account).map_err(keychain_error)?;
+    let signer = options
+        .signer
+        .map(|s| Pubkey::from_base58(&s))
+        .transpose()
+        .map_err(keychain_error)?;
+    let base_nonce = nonce_opt(options.nonce)?;
+
+    let prepared = prepare_all_indexed(order_items, &account, signer.as_ref(), base_nonce)
+        .map_err(keychain_error)?;
+
+    Ok(prepared
+        .into_iter()
+        .map(|indexed| PreparedMessageOutput {
+            index: Some(indexed.index as u32),
+            ..indexed.message.into()
+        })
+        .collect())
+}
+
 /// Prepare multiple orders as ONE atomic transaction
 ///
 /// Use for bracket orders (entry + stop loss + take profit).
@@ -1074,29 +3925,40 @@ pub fn prepare_all_orders(
 /// @example
 /// ```typescript
 /// const bracket = [entryOrder, stopLoss, takeProfit];
-/// const prepared = prepareOrderGroup(bracket, { account: myPubkey });
+/// const prepared = prepareGroup(bracket, { account: myPubkey });
 /// const signature = await wallet.signMessage(prepared.messageBytes);
 /// const signed = finalizeTransaction(prepared, signature);
 /// ```
-#[napi]
+#[napi(js_name = "prepareGroup")]
 pub fn prepare_order_group(
-    orders: Vec<OrderInput>,
-    options: PrepareOptions,
+    #[napi(ts_arg_type = "Array<OrderInput>")] orders: Vec<serde_json::Value>,
+    #[napi(ts_arg_type = "PrepareOptions")] options: Object,
 ) -> Result<PreparedMessageOutput> {
+    let options = parse_prepare_options(options)?;
+    let orders = parse_order_inputs(orders, options.input_mode())?;
     let order_items: Result<Vec<OrderItem>> = orders.into_iter().map(|o| o.try_into()).collect();
     let order_items = order_items?;
 
-    let account =
-        Pubkey::from_base58(&options.account).map_err(|e| Error::from_reason(e.to_string()))?;
+    let account = Pubkey::from_base58(&options.account).map_err(keychain_error)?;
     let signer = options
         .signer
         .map(|s| Pubkey::from_base58(&s))
         .transpose()
-        .map_err(|e| Error::from_reason(e.to_string()))?;
-    let nonce = options.nonce.map(|n| n as u64);
+        .map_err(keychain_error)?;
+    let nonce = nonce_opt(options.nonce)?;
 
-    let prepared = prepare_group(order_items, &account, signer.as_ref(), nonce)
-        .map_err(|e| Error::from_reason(e.to_string()))?;
+    let group_options = GroupOptions {
+        kind: options.group_kind.map(GroupKind::from),
+        strict: options.strict_group == Some(true),
+    };
+    let prepared = prepare_group_with_options(
+        order_items,
+        &group_options,
+        &account,
+        signer.as_ref(),
+        nonce,
+    )
+    .map_err(keychain_error)?;
 
     Ok(prepared.into())
 }
@@ -1105,47 +3967,122 @@ pub fn prepare_order_group(
 ///
 /// @example
 /// ```typescript
-/// const prepared = prepareAgentWalletAuth(agentPubkey, false, { account: myPubkey });
+/// const prepared = prepareAgentWallet(agentPubkey, false, { account: myPubkey });
 /// const signature = await wallet.signMessage(prepared.messageBytes);
 /// const signed = finalizeTransaction(prepared, signature);
 /// ```
-#[napi]
+#[napi(js_name = "prepareAgentWallet")]
 pub fn prepare_agent_wallet_auth(
     agent_pubkey: String,
     delete: bool,
     options: PrepareOptions,
 ) -> Result<PreparedMessageOutput> {
-    let agent =
-        Pubkey::from_base58(&agent_pubkey).map_err(|e| Error::from_reason(e.to_string()))?;
-    let account =
-        Pubkey::from_base58(&options.account).map_err(|e| Error::from_reason(e.to_string()))?;
+    let agent = Pubkey::from_base58(&agent_pubkey).map_err(keychain_error)?;
+    let account = Pubkey::from_base58(&options.account).map_err(keychain_error)?;
     let signer = options
         .signer
         .map(|s| Pubkey::from_base58(&s))
         .transpose()
-        .map_err(|e| Error::from_reason(e.to_string()))?;
-    let nonce = options.nonce.map(|n| n as u64);
+        .map_err(keychain_error)?;
+    let nonce = nonce_opt(options.nonce)?;
 
     let prepared = prepare_agent_wallet(&agent, delete, &account, signer.as_ref(), nonce)
-        .map_err(|e| Error::from_reason(e.to_string()))?;
+        .map_err(keychain_error)?;
 
     Ok(prepared.into())
 }
 
 /// Prepare faucet request for external signing
-#[napi]
+#[napi(js_name = "prepareFaucet")]
 pub fn prepare_faucet_request(options: PrepareOptions) -> Result<PreparedMessageOutput> {
-    let account =
-        Pubkey::from_base58(&options.account).map_err(|e| Error::from_reason(e.to_string()))?;
+    let account = Pubkey::from_base58(&options.account).map_err(keychain_error)?;
+    let signer = options
+        .signer
+        .map(|s| Pubkey::from_base58(&s))
+        .transpose()
+        .map_err(keychain_error)?;
+    let nonce = nonce_opt(options.nonce)?;
+
+    let prepared = prepare_faucet(&account, signer.as_ref(), nonce).map_err(keychain_error)?;
+
+    Ok(prepared.into())
+}
+
+/// Prepare a single-order cancel for external signing
+#[napi(js_name = "prepareCancel")]
+pub fn prepare_cancel_tx(
+    symbol: String,
+    order_id: String,
+    options: PrepareOptions,
+) -> Result<PreparedMessageOutput> {
+    let order_id = Hash::from_base58(&order_id)
+        .map_err(|e| Error::from_reason(format!("Invalid orderId: {}", e)))?;
+    let account = Pubkey::from_base58(&options.account).map_err(keychain_error)?;
+    let signer = options
+        .signer
+        .map(|s| Pubkey::from_base58(&s))
+        .transpose()
+        .map_err(keychain_error)?;
+    let nonce = nonce_opt(options.nonce)?;
+
+    let prepared = prepare_cancel(&symbol, order_id, &account, signer.as_ref(), nonce)
+        .map_err(keychain_error)?;
+
+    Ok(prepared.into())
+}
+
+/// Prepare a cancel-all for external signing (empty symbols = all markets)
+#[napi(js_name = "prepareCancelAll")]
+pub fn prepare_cancel_all_tx(
+    symbols: Vec<String>,
+    options: PrepareOptions,
+) -> Result<PreparedMessageOutput> {
+    let account = Pubkey::from_base58(&options.account).map_err(keychain_error)?;
+    let signer = options
+        .signer
+        .map(|s| Pubkey::from_base58(&s))
+        .transpose()
+        .map_err(keychain_error)?;
+    let nonce = nonce_opt(options.nonce)?;
+
+    let prepared =
+        prepare_cancel_all(symbols, &account, signer.as_ref(), nonce).map_err(keychain_error)?;
+
+    Ok(prepared.into())
+}
+
+/// Prepare an atomic cancel + replacement order for external signing
+///
+/// @example
+/// ```typescript
+/// const prepared = prepareCancelReplace(oldOrderId, newOrder, { account: myPubkey });
+/// const signature = await wallet.signMessage(prepared.messageBytes);
+/// const signed = finalizeTransaction(prepared, signature);
+/// ```
+#[napi(js_name = "prepareCancelReplace")]
+pub fn prepare_cancel_replace_tx(
+    old_order_id: String,
+    #[napi(ts_arg_type = "OrderInput")] new_order: serde_json::Value,
+    #[napi(ts_arg_type = "PrepareOptions")] options: Object,
+) -> Result<PreparedMessageOutput> {
+    let options = parse_prepare_options(options)?;
+    let new_order = parse_order_input(new_order, options.input_mode())?;
+    let old_id = Hash::from_base58(&old_order_id)
+        .map_err(|e| Error::from_reason(format!("Invalid orderId: {}", e)))?;
+    let new_order = match new_order.try_into()? {
+        OrderItem::Order(order) => order,
+        _ => return Err(Error::from_reason("newOrder must have type 'order'")),
+    };
+    let account = Pubkey::from_base58(&options.account).map_err(keychain_error)?;
     let signer = options
         .signer
         .map(|s| Pubkey::from_base58(&s))
         .transpose()
-        .map_err(|e| Error::from_reason(e.to_string()))?;
-    let nonce = options.nonce.map(|n| n as u64);
+        .map_err(keychain_error)?;
+    let nonce = nonce_opt(options.nonce)?;
 
-    let prepared = prepare_faucet(&account, signer.as_ref(), nonce)
-        .map_err(|e| Error::from_reason(e.to_string()))?;
+    let prepared = prepare_cancel_replace(old_id, new_order, &account, signer.as_ref(), nonce)
+        .map_err(keychain_error)?;
 
     Ok(prepared.into())
 }
@@ -1170,13 +4107,13 @@ pub struct TransferOptions {
     /// Signer public key (base58) - defaults to account if not provided
     pub signer: Option<String>,
     /// Nonce - defaults to current timestamp if not provided
-    pub nonce: Option<f64>,
+    pub nonce: Option<JsNonce>,
     /// "internal" (default) or "external"
     pub kind: Option<String>,
 }
 
 /// Prepare a margin transfer for external signing
-#[napi]
+#[napi(js_name = "prepareTransfer")]
 pub fn prepare_transfer_tx(
     from_pubkey: String,
     to_pubkey: String,
@@ -1184,16 +4121,15 @@ pub fn prepare_transfer_tx(
     margin_amount: f64,
     options: TransferOptions,
 ) -> Result<PreparedMessageOutput> {
-    let from = Pubkey::from_base58(&from_pubkey).map_err(|e| Error::from_reason(e.to_string()))?;
-    let to = Pubkey::from_base58(&to_pubkey).map_err(|e| Error::from_reason(e.to_string()))?;
-    let account =
-        Pubkey::from_base58(&options.account).map_err(|e| Error::from_reason(e.to_string()))?;
+    let from = Pubkey::from_base58(&from_pubkey).map_err(keychain_error)?;
+    let to = Pubkey::from_base58(&to_pubkey).map_err(keychain_error)?;
+    let account = Pubkey::from_base58(&options.account).map_err(keychain_error)?;
     let signer = options
         .signer
         .map(|s| Pubkey::from_base58(&s))
         .transpose()
-        .map_err(|e| Error::from_reason(e.to_string()))?;
-    let nonce = options.nonce.map(|n| n as u64);
+        .map_err(keychain_error)?;
+    let nonce = nonce_opt(options.nonce)?;
     let kind = parse_transfer_kind(options.kind.as_deref())?;
 
     let transfer = Transfer {
@@ -1204,51 +4140,48 @@ pub fn prepare_transfer_tx(
         margin_amount,
     };
 
-    let prepared = prepare_transfer(transfer, &account, signer.as_ref(), nonce)
-        .map_err(|e| Error::from_reason(e.to_string()))?;
+    let prepared =
+        prepare_transfer(transfer, &account, signer.as_ref(), nonce).map_err(keychain_error)?;
 
     Ok(prepared.into())
 }
 
 /// Prepare a sub-account removal for external signing
-#[napi]
+#[napi(js_name = "prepareRemoveSubAccount")]
 pub fn prepare_remove_sub_account_tx(
     to_remove: String,
     options: PrepareOptions,
 ) -> Result<PreparedMessageOutput> {
-    let target = Pubkey::from_base58(&to_remove).map_err(|e| Error::from_reason(e.to_string()))?;
-    let account =
-        Pubkey::from_base58(&options.account).map_err(|e| Error::from_reason(e.to_string()))?;
+    let target = Pubkey::from_base58(&to_remove).map_err(keychain_error)?;
+    let account = Pubkey::from_base58(&options.account).map_err(keychain_error)?;
     let signer = options
         .signer
         .map(|s| Pubkey::from_base58(&s))
         .transpose()
-        .map_err(|e| Error::from_reason(e.to_string()))?;
-    let nonce = options.nonce.map(|n| n as u64);
+        .map_err(keychain_error)?;
+    let nonce = nonce_opt(options.nonce)?;
 
     let prepared = prepare_remove_sub_account(target, &account, signer.as_ref(), nonce)
-        .map_err(|e| Error::from_reason(e.to_string()))?;
+        .map_err(keychain_error)?;
 
     Ok(prepared.into())
 }
 
 /// Prepare a sub-account rename for external signing
-#[napi]
+#[napi(js_name = "prepareRenameSubAccount")]
 pub fn prepare_rename_sub_account_tx(
     subaccount: String,
     name: String,
     options: PrepareOptions,
 ) -> Result<PreparedMessageOutput> {
-    let subaccount =
-        Pubkey::from_base58(&subaccount).map_err(|e| Error::from_reason(e.to_string()))?;
-    let account =
-        Pubkey::from_base58(&options.account).map_err(|e| Error::from_reason(e.to_string()))?;
+    let subaccount = Pubkey::from_base58(&subaccount).map_err(keychain_error)?;
+    let account = Pubkey::from_base58(&options.account).map_err(keychain_error)?;
     let signer = options
         .signer
         .map(|s| Pubkey::from_base58(&s))
         .transpose()
-        .map_err(|e| Error::from_reason(e.to_string()))?;
-    let nonce = options.nonce.map(|n| n as u64);
+        .map_err(keychain_error)?;
+    let nonce = nonce_opt(options.nonce)?;
 
     let prepared = prepare_rename_sub_account(
         RenameSubAccount {
@@ -1259,25 +4192,24 @@ pub fn prepare_rename_sub_account_tx(
         signer.as_ref(),
         nonce,
     )
-    .map_err(|e| Error::from_reason(e.to_string()))?;
+    .map_err(keychain_error)?;
 
     Ok(prepared.into())
 }
 
 /// Prepare a sub-account creation for external signing
-#[napi]
+#[napi(js_name = "prepareCreateSubAccount")]
 pub fn prepare_create_sub_account_tx(
     name: String,
     options: CreateSubAccountOptions,
 ) -> Result<PreparedMessageOutput> {
-    let account =
-        Pubkey::from_base58(&options.account).map_err(|e| Error::from_reason(e.to_string()))?;
+    let account = Pubkey::from_base58(&options.account).map_err(keychain_error)?;
     let signer = options
         .signer
         .map(|s| Pubkey::from_base58(&s))
         .transpose()
-        .map_err(|e| Error::from_reason(e.to_string()))?;
-    let nonce = options.nonce.map(|n| n as u64);
+        .map_err(keychain_error)?;
+    let nonce = nonce_opt(options.nonce)?;
 
     let sub_account = CreateSubAccount {
         name,
@@ -1286,13 +4218,27 @@ pub fn prepare_create_sub_account_tx(
     };
 
     let prepared = prepare_create_sub_account(sub_account, &account, signer.as_ref(), nonce)
-        .map_err(|e| Error::from_reason(e.to_string()))?;
+        .map_err(keychain_error)?;
 
     Ok(prepared.into())
 }
 
+/// Options for `finalizeTransaction` and its bytes/hex variants
+#[napi(object)]
+#[derive(Default)]
+pub struct FinalizeOptions {
+    /// Attach the signature without checking it (saves one Ed25519 verify)
+    pub skip_verify: Option<bool>,
+}
+
 /// Finalize a prepared message with a signature from an external wallet
 ///
+/// The message is rebuilt from `prepared.actions`, `account`, `signer` and
+/// `nonce`, and the signature must verify over it against `signer`; a wallet
+/// that signed other bytes fails here with `signature does not verify
+/// against signer <pubkey> for message <orderId>`. Pass `{ skipVerify: true }`
+/// to attach the signature unchecked.
+///
 /// @param prepared - The prepared message from prepare* functions
 /// @param signature - Base58-encoded signature from wallet.signMessage()
 ///
@@ -1303,22 +4249,260 @@ pub fn prepare_create_sub_account_tx(
 /// const signed = finalizeTransaction(prepared, signature);
 /// // Now submit `signed` to the API
 /// ```
-#[napi]
+#[napi(js_name = "finalizeTransaction")]
 pub fn finalize_prepared_transaction(
     prepared: PreparedMessageOutput,
     signature: String,
-) -> SignedTransactionOutput {
-    // Reconstruct the PreparedMessage (we only need the fields for finalization)
-    let actions: Vec<serde_json::Value> =
-        serde_json::from_str(&prepared.actions).unwrap_or_default();
+    options: Option<FinalizeOptions>,
+) -> Result<SignedTransactionOutput> {
+    if options.unwrap_or_default().skip_verify != Some(true) {
+        let mut rebuilt = PreparedMessage::from_parts(
+            actions_in(prepared.actions)?,
+            &prepared.account,
+            &prepared.signer,
+            nonce_in(prepared.nonce)?,
+        )
+        .map_err(keychain_error)?;
+        rebuilt.group_kind = prepared.group_kind.map(GroupKind::from);
+        rebuilt.group_warnings = group_warnings_in(prepared.group_warnings);
+        let signed = rebuilt
+            .finalize_verified(&signature)
+            .map_err(keychain_error)?;
+        return Ok(signed.into());
+    }
     let signed = bulk_keychain::SignedTransaction {
-        actions,
-        nonce: prepared.nonce as u64,
+        action: action_in(prepared.actions)?,
+        nonce: nonce_in(prepared.nonce)?,
         account: prepared.account,
         signer: prepared.signer,
         signature,
         order_id: prepared.order_id,
         order_ids: prepared.order_ids,
+        id_scheme: Default::default(),
+        resign_count: 0,
+        group_kind: prepared.group_kind.map(GroupKind::from),
+        group_warnings: group_warnings_in(prepared.group_warnings),
     };
-    signed.into()
+    Ok(signed.into())
+}
+
+/// Finalize a prepared message with a raw 64-byte signature
+///
+/// For wallet adapters and Web Crypto `sign()` calls that return a
+/// `Buffer`/`Uint8Array` instead of a base58 string.
+#[napi]
+pub fn finalize_prepared_transaction_bytes(
+    prepared: PreparedMessageOutput,
+    signature_bytes: Buffer,
+    options: Option<FinalizeOptions>,
+) -> Result<SignedTransactionOutput> {
+    let signature = signature_to_base58(&signature_bytes)?;
+    finalize_prepared_transaction(prepared, signature, options)
+}
+
+/// Finalize a prepared message with a hex-encoded signature (optional `0x` prefix)
+#[napi]
+pub fn finalize_prepared_transaction_hex(
+    prepared: PreparedMessageOutput,
+    signature_hex: String,
+    options: Option<FinalizeOptions>,
+) -> Result<SignedTransactionOutput> {
+    let hex_str = signature_hex.trim_start_matches("0x");
+    let bytes = hex::decode(hex_str)
+        .map_err(|e| Error::from_reason(format!("invalid signature hex: {}", e)))?;
+    let signature = signature_to_base58(&bytes)?;
+    finalize_prepared_transaction(prepared, signature, options)
+}
+
+/// Prepare a user settings update for external signing
+///
+/// `settings` takes the same shapes as `NativeSigner.signUserSettings`.
+#[napi]
+pub fn prepare_update_user_settings(
+    #[napi(ts_arg_type = "UserSettingsInput | Array<LeverageSetting>")] settings: serde_json::Value,
+    #[napi(ts_arg_type = "PrepareOptions")] options: Object,
+) -> Result<PreparedMessageOutput> {
+    let options = parse_prepare_options(options)?;
+    let settings = parse_user_settings(settings, options.input_mode())?;
+    let account = Pubkey::from_base58(&options.account).map_err(keychain_error)?;
+    let signer = options
+        .signer
+        .map(|s| Pubkey::from_base58(&s))
+        .transpose()
+        .map_err(keychain_error)?;
+    let nonce = nonce_opt(options.nonce)?;
+
+    let prepared = prepare_user_settings(settings, &account, signer.as_ref(), nonce)
+        .map_err(keychain_error)?;
+
+    Ok(prepared.into())
+}
+
+/// Sign a prepared message with a local keypair and finalize it
+///
+/// Throws if `keypair` is not the signer the message was prepared for.
+#[napi]
+pub fn sign_prepared_with_keypair(
+    prepared: PreparedMessageOutput,
+    keypair: &NativeKeypair,
+) -> Result<SignedTransactionOutput> {
+    PreparedMessage::try_from(prepared)?
+        .sign_with_keypair(&keypair.inner)
+        .map(Into::into)
+        .map_err(keychain_error)
+}
+
+/// Message bytes behind a Ledger blind-signing header
+/// (`[version u8][length u32 BE][message]`)
+#[napi]
+pub fn encode_for_ledger(prepared: PreparedMessageOutput) -> Buffer {
+    bulk_keychain::hardware::encode_for_ledger(&prepared.message_bytes).into()
+}
+
+/// Message bytes as a Trezor `SolanaSignTx` frame on `m/44'/501'/0'/0'`
+#[napi]
+pub fn encode_for_trezor(prepared: PreparedMessageOutput) -> Buffer {
+    bulk_keychain::hardware::encode_for_trezor(
+        &prepared.message_bytes,
+        &bulk_keychain::hardware::SOLANA_DEFAULT_PATH,
+    )
+    .into()
+}
+
+fn signature_to_base58(bytes: &[u8]) -> Result<String> {
+    if bytes.len() != 64 {
+        return Err(Error::from_reason(
+            bulk_keychain::Error::InvalidSignatureLength(bytes.len()).to_string(),
+        ));
+    }
+    Ok(bulk_keychain::bs58::encode(bytes).into_string())
+}
+
+// ============================================================================
+// Deprecated names
+// ============================================================================
+
+/// Deprecated: use `prepareAll`, the name shared with the WASM binding
+#[napi(js_name = "prepareAllOrders")]
+pub fn legacy_prepare_all_orders(
+    #[napi(ts_arg_type = "Array<OrderInput>")] orders: Vec<serde_json::Value>,
+    #[napi(ts_arg_type = "PrepareOptions")] options: Object,
+) -> Result<Vec<PreparedMessageOutput>> {
+    prepare_all_orders(orders, options)
+}
+
+/// Deprecated: use `prepareGroup`, the name shared with the WASM binding
+#[napi(js_name = "prepareOrderGroup")]
+pub fn legacy_prepare_order_group(
+    #[napi(ts_arg_type = "Array<OrderInput>")] orders: Vec<serde_json::Value>,
+    #[napi(ts_arg_type = "PrepareOptions")] options: Object,
+) -> Result<PreparedMessageOutput> {
+    prepare_order_group(orders, options)
+}
+
+/// Deprecated: use `prepareAgentWallet`, the name shared with the WASM binding
+#[napi(js_name = "prepareAgentWalletAuth")]
+pub fn legacy_prepare_agent_wallet_auth(
+    agent_pubkey: String,
+    delete: bool,
+    options: PrepareOptions,
+) -> Result<PreparedMessageOutput> {
+    prepare_agent_wallet_auth(agent_pubkey, delete, options)
+}
+
+/// Deprecated: use `prepareFaucet`, the name shared with the WASM binding
+#[napi(js_name = "prepareFaucetRequest")]
+pub fn legacy_prepare_faucet_request(options: PrepareOptions) -> Result<PreparedMessageOutput> {
+    prepare_faucet_request(options)
+}
+
+/// Deprecated: use `prepareCancel`, the name shared with the WASM binding
+#[napi(js_name = "prepareCancelTx")]
+pub fn legacy_prepare_cancel_tx(
+    symbol: String,
+    order_id: String,
+    options: PrepareOptions,
+) -> Result<PreparedMessageOutput> {
+    prepare_cancel_tx(symbol, order_id, options)
+}
+
+/// Deprecated: use `prepareCancelAll`, the name shared with the WASM binding
+#[napi(js_name = "prepareCancelAllTx")]
+pub fn legacy_prepare_cancel_all_tx(
+    symbols: Vec<String>,
+    options: PrepareOptions,
+) -> Result<PreparedMessageOutput> {
+    prepare_cancel_all_tx(symbols, options)
+}
+
+/// Deprecated: use `prepareCancelReplace`, the name shared with the WASM binding
+#[napi(js_name = "prepareCancelReplaceTx")]
+pub fn legacy_prepare_cancel_replace_tx(
+    old_order_id: String,
+    #[napi(ts_arg_type = "OrderInput")] new_order: serde_json::Value,
+    #[napi(ts_arg_type = "PrepareOptions")] options: Object,
+) -> Result<PreparedMessageOutput> {
+    prepare_cancel_replace_tx(old_order_id, new_order, options)
+}
+
+/// Deprecated: use `prepareTransfer`, the name shared with the WASM binding
+#[napi(js_name = "prepareTransferTx")]
+pub fn legacy_prepare_transfer_tx(
+    from_pubkey: String,
+    to_pubkey: String,
+    margin_symbol: String,
+    margin_amount: f64,
+    options: TransferOptions,
+) -> Result<PreparedMessageOutput> {
+    prepare_transfer_tx(
+        from_pubkey,
+        to_pubkey,
+        margin_symbol,
+        margin_amount,
+        options,
+    )
+}
+
+/// Deprecated: use `prepareRemoveSubAccount`, the name shared with the WASM binding
+#[napi(js_name = "prepareRemoveSubAccountTx")]
+pub fn legacy_prepare_remove_sub_account_tx(
+    to_remove: String,
+    options: PrepareOptions,
+) -> Result<PreparedMessageOutput> {
+    prepare_remove_sub_account_tx(to_remove, options)
+}
+
+/// Deprecated: use `prepareRenameSubAccount`, the name shared with the WASM binding
+#[napi(js_name = "prepareRenameSubAccountTx")]
+pub fn legacy_prepare_rename_sub_account_tx(
+    subaccount: String,
+    name: String,
+    options: PrepareOptions,
+) -> Result<PreparedMessageOutput> {
+    prepare_rename_sub_account_tx(subaccount, name, options)
+}
+
+/// Deprecated: use `prepareCreateSubAccount`, the name shared with the WASM binding
+#[napi(js_name = "prepareCreateSubAccountTx")]
+pub fn legacy_prepare_create_sub_account_tx(
+    name: String,
+    options: CreateSubAccountOptions,
+) -> Result<PreparedMessageOutput> {
+    prepare_create_sub_account_tx(name, options)
+}
+
+/// Deprecated: use `finalizeTransaction`, the name shared with the WASM binding
+#[napi(js_name = "finalizePreparedTransaction")]
+pub fn legacy_finalize_prepared_transaction(
+    prepared: PreparedMessageOutput,
+    signature: String,
+    options: Option<FinalizeOptions>,
+) -> Result<SignedTransactionOutput> {
+    finalize_prepared_transaction(prepared, signature, options)
+}
+
+/// Deprecated: use `diagnostics`, the name shared with the WASM binding
+#[napi(js_name = "getDiagnostics")]
+pub fn legacy_get_diagnostics() -> DiagnosticsOutput {
+    get_diagnostics()
 }
@@ -0,0 +1,27 @@
+const test = require('node:test');
+const assert = require('node:assert');
+const { NativeKeypair, NativeSigner } = require('../index.js');
+
+const orders = Array.from({ length: 20 }, (_, i) => ({
+  type: 'order',
+  symbol: 'BTC-USD',
+  isBuy: i % 2 === 0,
+  price: 100 + i,
+  size: 1,
+  orderType: { type: 'limit', tif: 'GTC' },
+}));
+
+test('pinned parallelism picks the batch mode without changing output', () => {
+  const keypair = new NativeKeypair();
+  const serial = new NativeSigner(keypair);
+  serial.setParallelism(1);
+  const serialTxs = serial.signAll(orders, 1);
+  assert.strictEqual(serial.usage().lastBatchMode, 'serial');
+
+  const parallel = new NativeSigner(keypair);
+  parallel.setParallelism(2);
+  const parallelTxs = parallel.signAll(orders, 1);
+  assert.strictEqual(parallel.usage().lastBatchMode, 'parallel');
+  assert.strictEqual(parallel.usage().lastBatchThreads, 2);
+  assert.deepStrictEqual(parallelTxs, serialTxs);
+});
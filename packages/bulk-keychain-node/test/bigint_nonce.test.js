@@ -0,0 +1,58 @@
+const test = require('node:test');
+const assert = require('node:assert');
+const {
+  NativeKeypair,
+  NativeSigner,
+  prepareOrder,
+  finalizeTransaction,
+  verifyTransaction,
+} = require('../index.js');
+
+const order = { type: 'order', symbol: 'BTC-USD', isBuy: true, price: 100, size: 1 };
+const LARGE = (1n << 60n) + 3n;
+
+test('BigInt nonces above Number.MAX_SAFE_INTEGER round-trip exactly', () => {
+  const signer = new NativeSigner(new NativeKeypair());
+  const tx = signer.sign(order, LARGE);
+  assert.strictEqual(tx.nonce, LARGE);
+  assert.strictEqual(signer.verifySignedTransaction(tx), true);
+  assert.strictEqual(verifyTransaction(tx), true);
+  assert.strictEqual(signer.verifySignedTransaction({ ...tx, nonce: LARGE + 1n }), false);
+
+  const txs = signer.signAll([order, order], LARGE);
+  assert.deepStrictEqual(
+    txs.map((t) => t.nonce),
+    [LARGE, LARGE + 1n],
+  );
+  assert.strictEqual(signer.signFaucet(LARGE).nonce, LARGE);
+});
+
+test('safe nonces stay numbers, whether passed as numbers or BigInts', () => {
+  const signer = new NativeSigner(new NativeKeypair());
+  assert.strictEqual(signer.sign(order, 42).nonce, 42);
+  assert.strictEqual(signer.sign(order, 43n).nonce, 43);
+});
+
+test('unsafe or negative nonces are refused instead of rounded', () => {
+  const signer = new NativeSigner(new NativeKeypair());
+  assert.throws(() => signer.sign(order, 2 ** 60), /pass larger nonces as a BigInt/);
+  assert.throws(() => signer.sign(order, -1), /not a safe integer/);
+  assert.throws(() => signer.sign(order, 1.5), /not a safe integer/);
+  assert.throws(() => signer.sign(order, -1n), /fit in a u64/);
+  assert.throws(() => signer.sign(order, 1n << 64n), /fit in a u64/);
+});
+
+test('prepare options and the pre-sign hook carry BigInt nonces', () => {
+  const keypair = new NativeKeypair();
+  const signer = new NativeSigner(keypair);
+  const prepared = prepareOrder(order, { account: keypair.pubkey, nonce: LARGE });
+  assert.strictEqual(prepared.nonce, LARGE);
+  const direct = signer.sign(order, LARGE);
+  assert.strictEqual(finalizeTransaction(prepared, direct.signature).nonce, LARGE);
+
+  const seen = [];
+  signer.setPreSignHook((_, nonce) => seen.push(nonce));
+  signer.sign(order, LARGE);
+  signer.sign(order, 7);
+  assert.deepStrictEqual(seen, [LARGE, 7]);
+});
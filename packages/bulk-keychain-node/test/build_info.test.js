@@ -0,0 +1,13 @@
+const test = require('node:test');
+const assert = require('node:assert');
+const { buildInfo } = require('../index.js');
+
+test('buildInfo reports the embedded core build', () => {
+  const info = buildInfo();
+  // The addon and core crate share the workspace version.
+  assert.strictEqual(info.crateVersion, info.bindingVersion);
+  assert.ok(info.gitSha.length > 0);
+  assert.ok(info.features.includes('batch'));
+  assert.ok(info.features.includes('agent'));
+  assert.ok(['debug', 'release'].includes(info.profile));
+});
@@ -0,0 +1,40 @@
+const test = require('node:test');
+const assert = require('node:assert');
+const { NativeKeypair, NativeSigner } = require('../index.js');
+
+const clientId = '11111111111111111111111111111112';
+const order = { type: 'order', symbol: 'BTC-USD', isBuy: true, price: 100, size: 1, clientId };
+const cancel = { type: 'cancelByClientId', symbol: 'BTC-USD', clientId };
+
+test('cancelByClientId signs a cancel of the order holding the client ID', () => {
+  const signer = new NativeSigner(new NativeKeypair());
+  signer.setUniqueClientIds(true);
+  const placed = signer.sign(order, 1);
+
+  const tx = signer.sign(cancel, 2);
+  assert.deepStrictEqual(tx.actions[0].cx, { c: 'BTC-USD', oid: placed.orderId });
+  const literal = signer.sign({ type: 'cancel', symbol: 'BTC-USD', orderId: clientId }, 2);
+  assert.notStrictEqual(tx.signature, literal.signature);
+
+  assert.throws(() => signer.sign(cancel, 3), /no live order holds client id/);
+});
+
+test('cancel accepts clientId in place of orderId, but not both', () => {
+  const signer = new NativeSigner(new NativeKeypair());
+  signer.setUniqueClientIds(true);
+  const placed = signer.sign(order, 1);
+  const tx = signer.sign({ type: 'cancel', symbol: 'BTC-USD', clientId }, 2);
+  assert.deepStrictEqual(tx.actions[0].cx, { c: 'BTC-USD', oid: placed.orderId });
+
+  const both = { type: 'cancel', symbol: 'BTC-USD', orderId: placed.orderId, clientId };
+  assert.throws(() => signer.sign(both, 3), /cancel.orderId and cancel.clientId are mutually exclusive/);
+  assert.throws(
+    () => signer.sign({ type: 'cancel', symbol: 'BTC-USD' }, 3),
+    /cancel.orderId or cancel.clientId is required/,
+  );
+});
+
+test('cancelByClientId needs client-ID tracking', () => {
+  const signer = new NativeSigner(new NativeKeypair());
+  assert.throws(() => signer.signAll([cancel], 1), /needs a signer tracking client ids/);
+});
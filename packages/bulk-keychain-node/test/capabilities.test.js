@@ -0,0 +1,33 @@
+const test = require('node:test');
+const assert = require('node:assert');
+const { NativeKeypair, NativeSigner, verifyCapabilities } = require('../index.js');
+
+test('capabilities follow runtime policy changes', () => {
+  const signer = new NativeSigner(new NativeKeypair());
+  const before = signer.capabilities();
+  assert.strictEqual(before.signer, signer.pubkey);
+  assert.strictEqual(before.policySummary.maxOpenOrdersPerSymbol, null);
+  assert.ok(before.supportedActions.includes('order'));
+  assert.ok(before.features.includes('batch'));
+
+  signer.setMaxOpenOrdersPerSymbol(3);
+  signer.setNetwork('testnet');
+  const after = signer.capabilities();
+  assert.strictEqual(after.policySummary.maxOpenOrdersPerSymbol, 3);
+  assert.strictEqual(after.network, 'testnet');
+
+  const scoped = signer.scoped({ symbols: ['BTC-USD'] });
+  const scopedCaps = scoped.capabilities();
+  assert.deepStrictEqual(scopedCaps.supportedActions, ['order']);
+  assert.deepStrictEqual(scopedCaps.scopes[0].symbols, ['BTC-USD']);
+});
+
+test('capabilitiesSigned verifies and detects tampering', () => {
+  const signer = new NativeSigner(new NativeKeypair());
+  signer.setNetwork('mainnet');
+  const signed = signer.capabilitiesSigned();
+  assert.strictEqual(JSON.parse(signed.payload).network, 'mainnet');
+  assert.strictEqual(verifyCapabilities(signed), true);
+  const tampered = { ...signed, payload: signed.payload.replace('mainnet', 'testnet') };
+  assert.strictEqual(verifyCapabilities(tampered), false);
+});
@@ -0,0 +1,56 @@
+const test = require('node:test');
+const assert = require('node:assert');
+const { NativeKeypair, NativeSigner, randomHash } = require('../index.js');
+
+const clientId = randomHash();
+const order = {
+  type: 'order',
+  symbol: 'BTC-USD',
+  isBuy: true,
+  price: 100000,
+  size: 0.1,
+  clientId,
+};
+
+test('a live client id blocks reuse until released or overridden', () => {
+  const signer = new NativeSigner(new NativeKeypair());
+  signer.setUniqueClientIds(true);
+  assert.strictEqual(signer.uniqueClientIds(), true);
+
+  const live = signer.sign(order, 1);
+  assert.throws(
+    () => signer.sign(order, 2),
+    new RegExp(`client id ${clientId} in use by live order ${live.orderId}`),
+  );
+  assert.strictEqual(signer.clientIdOrder(clientId), live.orderId);
+
+  assert.strictEqual(signer.notifyClosed(live.orderId), true);
+  const next = signer.sign(order, 3);
+  const reused = signer.sign(order, 4, { allowClientIdReuse: true });
+  assert.notStrictEqual(reused.orderId, next.orderId);
+  assert.strictEqual(signer.clientIdOrder(clientId), reused.orderId);
+});
+
+test('without enforcement client ids are not tracked', () => {
+  const signer = new NativeSigner(new NativeKeypair());
+  signer.sign(order, 1);
+  signer.sign(order, 2);
+  assert.strictEqual(signer.clientIdOrder(clientId), null);
+});
+
+test('an empty or all-zero client id is refused', () => {
+  const signer = new NativeSigner(new NativeKeypair());
+  assert.throws(
+    () => signer.sign({ ...order, clientId: '' }, 1),
+    /order\.clientId is empty; omit the field to send no client id/,
+  );
+  const zero = { ...order, clientId: '11111111111111111111111111111111' };
+  assert.throws(() => signer.sign(zero, 1), /client id is the all-zero hash/);
+
+  signer.setAllowZeroClientId(true);
+  const { clientId: _, ...withoutClientId } = order;
+  assert.strictEqual(
+    signer.sign(zero, 1).signature,
+    signer.sign(withoutClientId, 1).signature,
+  );
+});
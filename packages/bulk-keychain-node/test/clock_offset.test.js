@@ -0,0 +1,24 @@
+const test = require('node:test');
+const assert = require('node:assert');
+const { NativeKeypair, NativeSigner } = require('../index.js');
+
+const order = {
+  type: 'order',
+  symbol: 'BTC-USD',
+  isBuy: true,
+  price: 100000,
+  size: 0.1,
+  orderType: { type: 'limit', tif: 'GTC' },
+};
+
+test('clock offset shifts timestamp nonces', () => {
+  const signer = new NativeSigner(new NativeKeypair());
+  assert.strictEqual(signer.getClockOffsetMs(), 0);
+
+  signer.setClockOffsetMs(5000);
+  assert.strictEqual(signer.getClockOffsetMs(), 5000);
+
+  const before = Date.now();
+  const signed = signer.sign(order);
+  assert.ok(signed.nonce >= before + 5000, `nonce ${signed.nonce} not 5000ms ahead of ${before}`);
+});
@@ -0,0 +1,5 @@
+const test = require('node:test');
+const { fromModule } = require('../../bulk-keychain-universal');
+const { runConformance } = require('../../bulk-keychain-universal/test/conformance.js');
+
+test('shared API conformance', (t) => runConformance(fromModule(require('../index.js'), 'native'), t));
@@ -0,0 +1,31 @@
+const test = require('node:test');
+const assert = require('node:assert');
+const { NativeKeypair, NativeSigner } = require('../index.js');
+
+const order = { type: 'order', symbol: 'BTC-USD', isBuy: true, price: 100000, size: 0.1 };
+
+test('sign and signAll refuse a passed deadline and count it', () => {
+  const signer = new NativeSigner(new NativeKeypair());
+  const late = { deadline: Date.now() - 1000 };
+  assert.throws(() => signer.sign(order, 1, late), /deadline exceeded by \d+ms after 0 signed/);
+  assert.throws(() => signer.signAll([order, order], 10, late), /after 0 signed/);
+
+  const budget = { deadline: Date.now() + 60_000 };
+  assert.ok(signer.sign(order, 2, budget).signature);
+  assert.strictEqual(signer.signAll([order, order], 20, budget).length, 2);
+  assert.deepStrictEqual(signer.usage(), {
+    signed: 3,
+    deadlineRejections: 2,
+    lastBatchMode: 'serial',
+    lastBatchThreads: 1,
+    reconnects: 0,
+  });
+});
+
+test('a deadline in seconds is refused', () => {
+  const signer = new NativeSigner(new NativeKeypair());
+  const seconds = { deadline: Math.floor(Date.now() / 1000) + 60 };
+  assert.throws(() => signer.sign(order, 1, seconds), /looks like seconds/);
+  assert.throws(() => signer.signAll([order], 10, seconds), /looks like seconds/);
+  assert.strictEqual(signer.usage().signed, 0);
+});
@@ -0,0 +1,25 @@
+const test = require('node:test');
+const assert = require('node:assert');
+const { NativeKeypair } = require('../index.js');
+
+test('the same path derives the same key, and different paths differ', () => {
+  const root = new NativeKeypair();
+  const path = "m/44'/501'/0'/0'";
+  const a = root.fromDerivationPath(path);
+  assert.strictEqual(root.fromDerivationPath(path).pubkey, a.pubkey);
+  assert.notStrictEqual(root.fromDerivationPath("m/44'/501'/0'/1'").pubkey, a.pubkey);
+  assert.notStrictEqual(a.pubkey, root.pubkey);
+
+  const stepwise = root
+    .deriveChild(44, true)
+    .deriveChild(501, true)
+    .deriveChild(0, true)
+    .deriveChild(0, true);
+  assert.strictEqual(stepwise.pubkey, a.pubkey);
+});
+
+test('non-hardened derivation is refused', () => {
+  const root = new NativeKeypair();
+  assert.throws(() => root.deriveChild(0, false), /hardened children only/);
+  assert.throws(() => root.fromDerivationPath("m/44'/60'/0'/0/1"), /invalid derivation path/);
+});
@@ -0,0 +1,34 @@
+const test = require('node:test');
+const assert = require('node:assert');
+const { NativeKeypair, NativeSigner } = require('../index.js');
+
+test('signers with the same seed draw the same client IDs', () => {
+  const keypair = new NativeKeypair();
+  const a = new NativeSigner(keypair);
+  const b = new NativeSigner(keypair);
+  a.setDeterministicSeed(42);
+  b.setDeterministicSeed(42);
+  const draw = (s) => Array.from({ length: 4 }, () => s.randomClientId());
+  assert.deepStrictEqual(draw(a), draw(b));
+
+  // Order IDs of orders carrying those client IDs match too.
+  const order = (clientId) => ({
+    type: 'order', symbol: 'BTC-USD', isBuy: true, price: 100, size: 1, clientId,
+  });
+  assert.strictEqual(
+    a.sign(order(a.randomClientId()), 1).orderId,
+    b.sign(order(b.randomClientId()), 1).orderId,
+  );
+});
+
+test('unseeded signers use the OS RNG', () => {
+  const keypair = new NativeKeypair();
+  const a = new NativeSigner(keypair);
+  const b = new NativeSigner(keypair);
+  assert.notStrictEqual(a.randomClientId(), b.randomClientId());
+
+  a.setDeterministicSeed(1);
+  b.setDeterministicSeed(1);
+  a.setDeterministicSeed(undefined);
+  assert.notStrictEqual(a.randomClientId(), b.randomClientId());
+});
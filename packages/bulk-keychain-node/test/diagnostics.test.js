@@ -0,0 +1,92 @@
+const test = require('node:test');
+const assert = require('node:assert');
+const v8 = require('node:v8');
+const vm = require('node:vm');
+const {
+  NativeKeypair,
+  NativeSigner,
+  getDiagnostics,
+  prepareOrder,
+  setDiagnosticsEnabled,
+} = require('../index.js');
+
+v8.setFlagsFromString('--expose-gc');
+const gc = vm.runInNewContext('gc');
+
+const order = {
+  type: 'order',
+  symbol: 'BTC-USD',
+  isBuy: true,
+  price: 100000,
+  size: 0.1,
+  orderType: { type: 'limit', tif: 'GTC' },
+};
+
+setDiagnosticsEnabled(true);
+
+// Disposed keys stay in the secret census until collected.
+const counters = () => {
+  const { secretCensus, ...rest } = getDiagnostics();
+  return rest;
+};
+
+const collect = async (done) => {
+  for (let i = 0; i < 10 && !done(); i++) {
+    gc();
+    await new Promise((resolve) => setImmediate(resolve));
+  }
+};
+
+test('create/dispose cycles return counters to baseline', () => {
+  const baseline = counters();
+  for (let i = 0; i < 50; i++) {
+    const keypair = new NativeKeypair();
+    const signer = new NativeSigner(keypair);
+    signer.setPreSignHook(() => {});
+    signer.sign(order, i + 1);
+    signer.dispose();
+    keypair.dispose();
+  }
+  assert.deepStrictEqual(counters(), baseline);
+});
+
+test('the secret census counts copies of a key and why they exist', async () => {
+  const census = (fingerprint) =>
+    getDiagnostics().secretCensus.find((e) => e.fingerprint === fingerprint);
+
+  let keypair = new NativeKeypair();
+  const fingerprint = keypair.fingerprint();
+  let signers = [new NativeSigner(keypair), new NativeSigner(keypair)];
+  assert.deepStrictEqual(census(fingerprint), {
+    fingerprint,
+    copies: 3,
+    reasons: { origin: 1, 'NativeSigner::new': 2 },
+  });
+
+  signers = null;
+  await collect(() => census(fingerprint).copies === 1);
+  assert.strictEqual(census(fingerprint).copies, 1);
+
+  keypair = null;
+  await collect(() => census(fingerprint) === undefined);
+  assert.strictEqual(census(fingerprint), undefined);
+});
+
+test('retained prepared messages show up until collected', async () => {
+  const account = new NativeKeypair().pubkey;
+  const baseline = getDiagnostics();
+
+  let leaked = [];
+  for (let i = 0; i < 10; i++) {
+    leaked.push(prepareOrder(order, { account, nonce: i + 1 }));
+  }
+  const bytes = leaked.reduce((sum, p) => sum + p.messageBytes.length, 0);
+  const held = getDiagnostics();
+  assert.strictEqual(held.livePreparedMessages, baseline.livePreparedMessages + 10);
+  assert.strictEqual(held.retainedBytes, baseline.retainedBytes + bytes);
+
+  leaked = null;
+  await collect(() => getDiagnostics().livePreparedMessages <= baseline.livePreparedMessages);
+  assert.strictEqual(getDiagnostics().livePreparedMessages, baseline.livePreparedMessages);
+  assert.strictEqual(getDiagnostics().retainedBytes, baseline.retainedBytes);
+});
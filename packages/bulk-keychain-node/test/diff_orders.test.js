@@ -0,0 +1,22 @@
+const test = require('node:test');
+const assert = require('node:assert');
+const { diffOrders } = require('../index.js');
+
+const order = (isBuy, price) => ({
+  type: 'order',
+  symbol: 'ETH-USD',
+  isBuy,
+  price,
+  size: 1,
+  orderType: { type: 'limit', tif: 'GTC' },
+});
+
+test('diffOrders reports tick deltas and side changes', () => {
+  const spec = { priceDecimals: 2, sizeDecimals: 2, tickUnits: 5 };
+  const diff = diffOrders(order(true, 2000), order(true, 1999.85), spec);
+  assert.strictEqual(diff.priceDeltaTicks, -3);
+  assert.strictEqual(diff.sideChanged, false);
+  assert.strictEqual(diffOrders(order(true, 2000), order(true, 2000)).priceDeltaTicks, undefined);
+  assert.strictEqual(diffOrders(order(true, 2000), order(false, 2000)).sideChanged, true);
+  assert.throws(() => diffOrders({ type: 'cancel', symbol: 'ETH-USD', orderId: '1' }, order(true, 1)));
+});
@@ -0,0 +1,20 @@
+const test = require('node:test');
+const assert = require('node:assert');
+const { confirmPriceMatches } = require('../index.js');
+
+const order = (price) => ({
+  type: 'order',
+  symbol: 'BTC-USD',
+  isBuy: true,
+  price,
+  size: 0.1,
+  orderType: { type: 'limit', tif: 'GTC' },
+});
+
+test('confirmPriceMatches rejects rounded or locale-ambiguous prices', () => {
+  const signed = order(100000.05);
+  confirmPriceMatches('100,000.05', signed);
+  assert.throws(() => confirmPriceMatches('100,000', signed), /order signs 100000\.05/);
+  assert.throws(() => confirmPriceMatches('100000,05', signed), /decimal commas/);
+  assert.throws(() => confirmPriceMatches('100000.05', signed, 1), /would be shown as 100000\.1/);
+});
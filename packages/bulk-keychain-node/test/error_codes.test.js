@@ -0,0 +1,59 @@
+const test = require('node:test');
+const assert = require('node:assert');
+const { NativeKeypair, NativeSigner, parseSymbol } = require('../index.js');
+
+const order = {
+  type: 'order',
+  symbol: 'BTC-USD',
+  isBuy: true,
+  price: 100000,
+  size: 0.1,
+  orderType: { type: 'limit', tif: 'GTC' },
+};
+
+const withCode = (code, message) => (err) => {
+  assert.strictEqual(err.code, code);
+  assert.match(err.message, message);
+  return true;
+};
+
+test('errors carry the core variant name as code', () => {
+  assert.throws(() => NativeKeypair.fromBase58('0OIl'), withCode('InvalidBase58', /invalid base58/));
+  assert.throws(
+    () => NativeKeypair.fromBytes(Buffer.alloc(5, 1)),
+    withCode('InvalidKeyLength', /expected 64, got 5/),
+  );
+
+  const signer = new NativeSigner(new NativeKeypair());
+  assert.throws(
+    () => signer.signAll([order, order], 2n ** 64n - 1n),
+    withCode('NonceOverflow', /nonce overflow/),
+  );
+  assert.throws(
+    () => signer.sign({ ...order, isBuy: undefined }, 1),
+    withCode('InvalidOrderField', /order\.isBuy is required/),
+  );
+  assert.throws(() => parseSymbol('BTCUSD'), withCode('InvalidSymbol', /BASE-QUOTE/));
+});
+
+test('signAllAsync rejects with the code too', async () => {
+  const signer = new NativeSigner(new NativeKeypair());
+  await assert.rejects(
+    signer.signAllAsync([order], 1, { deadline: Date.now() - 60_000 }),
+    withCode('DeadlineExceeded', /deadline/),
+  );
+});
+
+test('binding errors keep the napi status, and hook throws pass through as is', () => {
+  const signer = new NativeSigner(new NativeKeypair());
+  assert.throws(() => signer.sign(order, -1), withCode('GenericFailure', /nonce/));
+
+  const thrown = new RangeError('blocked');
+  signer.setPreSignHook(() => {
+    throw thrown;
+  });
+  assert.throws(
+    () => signer.sign(order, 1),
+    (err) => err === thrown,
+  );
+});
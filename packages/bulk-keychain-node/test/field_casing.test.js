@@ -0,0 +1,44 @@
+const test = require('node:test');
+const assert = require('node:assert');
+const { NativeKeypair, NativeSigner, validateOrderInput } = require('../index.js');
+
+const camel = {
+  type: 'order',
+  symbol: 'BTC-USD',
+  isBuy: true,
+  price: 100000,
+  size: 0.1,
+  reduceOnly: true,
+  orderType: { type: 'limit', tif: 'IOC' },
+};
+const snake = {
+  type: 'order',
+  symbol: 'BTC-USD',
+  is_buy: true,
+  price: 100000,
+  size: 0.1,
+  reduce_only: true,
+  order_type: { type: 'limit', tif: 'IOC' },
+};
+const mixed = { ...camel, reduceOnly: undefined, reduce_only: true, note: 'x' };
+
+test('camel, snake and mixed field names sign identically', () => {
+  const signer = new NativeSigner(new NativeKeypair());
+  const sig = (order) => signer.sign(order, 7).signature;
+  assert.strictEqual(sig(snake), sig(camel));
+  assert.strictEqual(sig(mixed), sig(camel));
+});
+
+test('validateOrderInput normalizes, warns and enforces strict casing', () => {
+  const { order } = validateOrderInput(camel, true);
+  assert.deepStrictEqual(validateOrderInput(snake, true).order, order);
+  assert.strictEqual(order.reduceOnly, true);
+
+  const tolerant = validateOrderInput(mixed);
+  assert.deepStrictEqual(tolerant.order, order);
+  assert.deepStrictEqual(tolerant.warnings, ['note: unknown field']);
+  assert.throws(() => validateOrderInput(mixed, true), /unknown fields: note/);
+  const { note, ...mixedOnly } = mixed;
+  assert.throws(() => validateOrderInput(mixedOnly, true), /mixed field casing: isBuy and reduce_only/);
+  assert.throws(() => validateOrderInput({ ...camel, is_buy: false }), /isBuy is given more than once/);
+});
@@ -0,0 +1,52 @@
+const test = require('node:test');
+const assert = require('node:assert');
+const {
+  NativeKeypair,
+  NativeSigner,
+  prepareOrder,
+  finalizeTransaction,
+  finalizePreparedTransactionBytes,
+  finalizePreparedTransactionHex,
+} = require('../index.js');
+
+const order = { type: 'order', symbol: 'BTC-USD', isBuy: true, price: 100000, size: 0.1 };
+
+function setup() {
+  const keypair = new NativeKeypair();
+  const signer = new NativeSigner(keypair);
+  const prepared = prepareOrder(order, { account: keypair.pubkey, nonce: 5 });
+  return { signer, prepared, signature: signer.signBytes(prepared.messageBytes) };
+}
+
+test('finalize accepts a signature over the prepared message', () => {
+  const { signer, prepared, signature } = setup();
+  const expected = signer.sign(order, 5);
+  assert.strictEqual(finalizePreparedTransactionBytes(prepared, signature).signature, expected.signature);
+  assert.strictEqual(
+    finalizePreparedTransactionHex(prepared, signature.toString('hex')).signature,
+    expected.signature,
+  );
+  assert.strictEqual(finalizeTransaction(prepared, expected.signature).orderId, prepared.orderId);
+});
+
+test('finalize refuses signatures over other bytes', () => {
+  const { signer, prepared } = setup();
+  const wrong = signer.signBytes(Buffer.from('not the message'));
+  assert.throws(
+    () => finalizePreparedTransactionBytes(prepared, wrong),
+    new RegExp(`signature does not verify against signer ${signer.fingerprint()} for message ${prepared.orderId}`),
+  );
+  // Edited actions are caught too: the message is rebuilt from them.
+  const { signature } = setup();
+  const edited = { ...prepared, nonce: prepared.nonce + 1 };
+  assert.throws(() => finalizePreparedTransactionBytes(edited, signature), /does not verify/);
+  // Truncated and mis-encoded signatures fail on decoding.
+  assert.throws(() => finalizeTransaction(prepared, signature.toString('hex')), /invalid/);
+});
+
+test('skipVerify attaches the signature unchecked', () => {
+  const { signer, prepared } = setup();
+  const wrong = signer.signBytes(Buffer.from('not the message'));
+  const tx = finalizePreparedTransactionBytes(prepared, wrong, { skipVerify: true });
+  assert.strictEqual(signer.verifySignedTransaction(tx), false);
+});
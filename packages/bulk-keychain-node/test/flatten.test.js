@@ -0,0 +1,25 @@
+const test = require('node:test');
+const assert = require('node:assert');
+const { NativeKeypair, NativeSigner } = require('../index.js');
+
+const specs = { 'ETH-USD': { spec: { priceDecimals: 1, sizeDecimals: 2 }, maxOrderSize: 1 } };
+const position = (size) => ({ symbol: 'ETH-USD', size, markPrice: 2000 });
+
+test('signFlatten sells a long in capped reduce-only pieces', () => {
+  const signer = new NativeSigner(new NativeKeypair());
+  const txs = signer.signFlatten(position(2.5), { type: 'limit', offsetBps: 25 }, specs, 1);
+  const legs = txs.map((tx) => tx.actions[0].l);
+  assert.deepStrictEqual(legs.map((l) => l.sz), [1, 1, 0.5]);
+  assert.ok(legs.every((l) => !l.b && l.r && l.px === 1995 && l.tif === 'IOC'));
+  assert.deepStrictEqual(txs.map((tx) => tx.nonce), [1, 2, 3]);
+});
+
+test('signFlatten buys back a short and skips a flat position', () => {
+  const signer = new NativeSigner(new NativeKeypair());
+  const [tx] = signer.signFlatten(position(-0.4), { type: 'market' }, specs, 1);
+  const leg = tx.actions[0].m;
+  assert.ok(leg.b && leg.r);
+  assert.strictEqual(leg.sz, 0.4);
+  assert.deepStrictEqual(signer.signFlatten(position(0), { type: 'market' }), []);
+  assert.throws(() => signer.signFlatten(position(1), { type: 'limit' }), /offsetBps is required/);
+});
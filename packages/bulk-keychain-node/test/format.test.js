@@ -0,0 +1,17 @@
+const test = require('node:test');
+const assert = require('node:assert');
+const { formatPrice, formatSize, parseAmount } = require('../index.js');
+
+const spec = { priceDecimals: 2, sizeDecimals: 2 };
+
+test('formatPrice and formatSize render at spec decimals with units', () => {
+  assert.strictEqual(formatPrice('BTC-USD', 100000, spec), '100,000.00 USD');
+  assert.strictEqual(formatSize('BTC-USD', 0.1, spec), '0.10 BTC');
+});
+
+test('parseAmount accepts grouping and suffixes, rejects decimal commas', () => {
+  assert.strictEqual(parseAmount('1,250.5'), '1250.5');
+  assert.strictEqual(parseAmount('0.5k'), '500');
+  assert.throws(() => parseAmount('1.234,56'), /write 1234\.56/);
+  assert.strictEqual(Number(parseAmount(formatPrice('BTC-USD', 99.99, spec).split(' ')[0])), 99.99);
+});
@@ -0,0 +1,37 @@
+const test = require('node:test');
+const assert = require('node:assert');
+const { NativeKeypair, NativeSigner, prepareOrderGroup } = require('../index.js');
+
+const entry = {
+  type: 'order',
+  symbol: 'BTC-USD',
+  isBuy: true,
+  price: 100000,
+  size: 0.1,
+  orderType: { type: 'limit', tif: 'GTC' },
+};
+const sameSideStop = {
+  type: 'stop',
+  symbol: 'BTC-USD',
+  isBuy: true,
+  size: 0.1,
+  triggerPrice: 95000,
+};
+
+test('signGroup reports conflicting legs and refuses them when strict', () => {
+  const signer = new NativeSigner(new NativeKeypair());
+  const tx = signer.signGroup([entry, sameSideStop], 1);
+  assert.deepStrictEqual(
+    tx.groupWarnings.map((w) => [w.index, w.code, w.severity]),
+    [[1, 'protection_same_side', 'critical']],
+  );
+  assert.throws(() => signer.signGroup([entry, sameSideStop], 2, undefined, true), /leg 1/);
+  assert.strictEqual(signer.signGroup([entry], 3).groupWarnings, undefined);
+});
+
+test('prepareOrderGroup carries warnings and honors strictGroup', () => {
+  const account = new NativeKeypair().pubkey;
+  const prepared = prepareOrderGroup([entry, entry], { account, nonce: 1 });
+  assert.strictEqual(prepared.groupWarnings[0].code, 'duplicate_leg');
+  assert.throws(() => prepareOrderGroup([entry, entry], { account, nonce: 1, strictGroup: true }));
+});
@@ -0,0 +1,41 @@
+const test = require('node:test');
+const assert = require('node:assert');
+const { NativeKeypair, NativeSigner, prepareAllIndexed } = require('../index.js');
+
+const order = (price) => ({ type: 'order', symbol: 'BTC-USD', isBuy: true, price, size: 1 });
+
+test('signOrdersBatchIndexed tags each batch with its index', () => {
+  const signer = new NativeSigner(new NativeKeypair());
+  const signed = signer.signOrdersBatchIndexed([[order(100)], [order(101)]], 7);
+  assert.deepStrictEqual(
+    signed.map((tx) => [tx.index, tx.nonce]),
+    [
+      [0, 7],
+      [1, 8],
+    ],
+  );
+});
+
+test('signAllPartialIndexed reports refusals next to signed orders', () => {
+  const signer = new NativeSigner(new NativeKeypair());
+  signer.setMaxOpenOrdersPerSymbol(1);
+  const [first, second] = signer.signAllPartialIndexed([order(100), order(101)], 20);
+  assert.strictEqual(first.index, 0);
+  assert.strictEqual(first.transaction.nonce, 20);
+  assert.strictEqual(first.error, undefined);
+  assert.strictEqual(second.index, 1);
+  assert.strictEqual(second.transaction, undefined);
+  assert.strictEqual(second.code, 'OpenOrderCapExceeded');
+});
+
+test('prepareAllIndexed tags each message with its index', () => {
+  const account = new NativeKeypair().pubkey;
+  const prepared = prepareAllIndexed([order(100), order(101)], { account, nonce: 5 });
+  assert.deepStrictEqual(
+    prepared.map((p) => [p.index, p.nonce]),
+    [
+      [0, 5],
+      [1, 6],
+    ],
+  );
+});
@@ -0,0 +1,32 @@
+const test = require('node:test');
+const assert = require('node:assert');
+const { getJsonSchema, NativeKeypair, NativeSigner } = require('../index.js');
+
+test('getJsonSchema returns each documented schema', () => {
+  for (const name of [
+    'OrderSpec',
+    'OrderTypeInput',
+    'PrepareOptions',
+    'UserSettings',
+    'SignedTransaction',
+    'PreparedMessage',
+  ]) {
+    const schema = getJsonSchema(name);
+    assert.strictEqual(schema.title, name);
+    assert.strictEqual(schema.$schema, 'https://json-schema.org/draft/2020-12/schema');
+  }
+  assert.throws(() => getJsonSchema('OrderInput'), /expected one of/);
+});
+
+test('SignedTransaction schema lists the submitted fields', () => {
+  const schema = getJsonSchema('SignedTransaction');
+  const signer = new NativeSigner(new NativeKeypair());
+  const tx = signer.sign(
+    { type: 'order', symbol: 'BTC-USD', isBuy: true, price: 100000, size: 0.1 },
+    1,
+  );
+  for (const field of schema.required) {
+    assert.ok(field in tx, field);
+  }
+  assert.deepStrictEqual(getJsonSchema('OrderSpec').properties.isBuy, { type: 'boolean' });
+});
@@ -0,0 +1,51 @@
+const test = require('node:test');
+const assert = require('node:assert');
+const { NativeKeypair, NativeSigner } = require('../index.js');
+
+// BIP39 test vector for 16 zero bytes of entropy.
+const ABANDON = 'abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about';
+
+test('fromMnemonic derives the Solana wallet address of the phrase', () => {
+  assert.strictEqual(
+    NativeKeypair.fromMnemonic(ABANDON).pubkey,
+    'HAgk14JpMQLgt6rVgv7cBQFJWFto5Dqxi472uT3DKpqk',
+  );
+  assert.strictEqual(
+    NativeKeypair.fromMnemonic(ABANDON, '', "m/44'/501'/1'/0'").pubkey,
+    'Hh8QwFUA6MtVu1qAoq12ucvFHNwCcVTV7hpWjeY1Hztb',
+  );
+  assert.strictEqual(
+    NativeKeypair.fromMnemonic(ABANDON, 'TREZOR').pubkey,
+    '7zSmbu6gKkb6HB7UDPtHYjwCWuBHU1D4TpNZFm4sndQe',
+  );
+  assert.throws(() => NativeKeypair.fromMnemonic('abandon abandon'), /expected 12 or 24 words, got 2/);
+  assert.throws(
+    () => NativeKeypair.fromMnemonic(ABANDON, '', "m/44'/501'/0'/0"),
+    (err) => err.code === 'InvalidDerivationPath',
+  );
+});
+
+test('fromMnemonicSeed takes the first 32 bytes of the BIP39 seed', () => {
+  const keypair = NativeKeypair.fromMnemonicSeed(ABANDON);
+  assert.strictEqual(
+    Buffer.from(keypair.secretKey()).toString('hex'),
+    '5eb00bbddcf069084889a8ab9155568165f5c453ccb85e70811aaed6f6da5fc1',
+  );
+  assert.throws(() => NativeKeypair.fromMnemonicSeed('abandon abandon'), /invalid mnemonic/);
+});
+
+test('generateMnemonic returns a keypair and the phrase that restores it', () => {
+  const { keypair, mnemonic } = NativeKeypair.generateMnemonic();
+  assert.ok(keypair instanceof NativeKeypair);
+  assert.strictEqual(mnemonic.split(' ').length, 24);
+  assert.strictEqual(NativeKeypair.fromMnemonic(mnemonic).pubkey, keypair.pubkey);
+});
+
+test('NativeSigner.fromMnemonic signs with the key at the path', () => {
+  const signer = NativeSigner.fromMnemonic(ABANDON, "m/44'/501'/0'/0'");
+  assert.strictEqual(signer.pubkey, 'HAgk14JpMQLgt6rVgv7cBQFJWFto5Dqxi472uT3DKpqk');
+  const counter = NativeSigner.fromMnemonic(ABANDON, "m/44'/501'/1'/0'", 'counter');
+  assert.strictEqual(counter.pubkey, 'Hh8QwFUA6MtVu1qAoq12ucvFHNwCcVTV7hpWjeY1Hztb');
+  assert.strictEqual(counter.peekNextNonce(), 0);
+  assert.throws(() => NativeSigner.fromMnemonic(ABANDON, "m/44'/501'/0'/0'", 'sometimes'), /Invalid nonce strategy/);
+});
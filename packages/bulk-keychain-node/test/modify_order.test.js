@@ -0,0 +1,21 @@
+const test = require('node:test');
+const assert = require('node:assert');
+const { NativeKeypair, NativeSigner } = require('../index.js');
+
+test('signModifyOrder signs the same transaction as a modify item', () => {
+  const signer = new NativeSigner(new NativeKeypair());
+  const placed = signer.sign(
+    { type: 'order', symbol: 'BTC-USD', isBuy: true, price: 100000, size: 0.5 },
+    1,
+  );
+
+  const tx = signer.signModifyOrder(placed.orderId, 'BTC-USD', 0.25, 2);
+  assert.deepStrictEqual(tx.actions, [{ mod: { oid: placed.orderId, c: 'BTC-USD', sz: 0.25 } }]);
+  const viaSign = signer.sign(
+    { type: 'modify', orderId: placed.orderId, symbol: 'BTC-USD', amount: 0.25 },
+    2,
+  );
+  assert.strictEqual(tx.signature, viaSign.signature);
+
+  assert.throws(() => signer.signModifyOrder('nope', 'BTC-USD', 0.25, 3), /orderId/);
+});
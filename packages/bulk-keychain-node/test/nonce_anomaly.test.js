@@ -0,0 +1,32 @@
+const test = require('node:test');
+const assert = require('node:assert');
+const { NativeKeypair, NativeSigner } = require('../index.js');
+
+const order = {
+  type: 'order',
+  symbol: 'BTC-USD',
+  isBuy: true,
+  price: 100000,
+  size: 0.1,
+  orderType: { type: 'limit', tif: 'GTC' },
+};
+
+test('clock stepping back is reported once and nonces stay monotonic', async () => {
+  const signer = NativeSigner.withNonceManager(new NativeKeypair(), 'timestamp');
+  const seen = [];
+  signer.onNonceAnomaly((anomaly) => seen.push(anomaly));
+
+  const first = signer.sign(order);
+  // A negative offset looks exactly like the clock stepping back.
+  signer.setClockOffsetMs(-60000);
+  const second = signer.sign(order);
+  const third = signer.sign(order);
+  assert.ok(second.nonce > first.nonce);
+  assert.ok(third.nonce > second.nonce);
+
+  await new Promise((resolve) => setImmediate(resolve));
+  assert.strictEqual(seen.length, 1);
+  assert.strictEqual(seen[0].kind, 'clockRegression');
+  assert.strictEqual(seen[0].lastNonceMs, first.nonce);
+  assert.deepStrictEqual(signer.nonceAnomalies(), seen);
+});
@@ -0,0 +1,25 @@
+const test = require('node:test');
+const assert = require('node:assert');
+const { NativeKeypair, NativeSigner } = require('../index.js');
+
+const order = { type: 'order', symbol: 'BTC-USD', isBuy: true, price: 100, size: 1 };
+
+test('peekNextNonce shows the next nonce without taking it', () => {
+  const signer = NativeSigner.withNonceManager(new NativeKeypair(), 'counter');
+  assert.strictEqual(signer.peekNextNonce(), 0);
+  assert.strictEqual(signer.peekNextNonce(), 0);
+  assert.strictEqual(signer.sign(order).nonce, 0);
+  assert.strictEqual(signer.peekNextNonce(), 1);
+  signer.sign(order);
+  signer.resetNonce();
+  assert.strictEqual(signer.sign(order).nonce, 0);
+});
+
+test('random nonces are safe integers and peek matches the next one', () => {
+  const signer = NativeSigner.withNonceManager(new NativeKeypair(), 'random');
+  const peeked = signer.peekNextNonce();
+  assert.ok(Number.isSafeInteger(peeked));
+  assert.strictEqual(signer.sign(order).nonce, peeked);
+  assert.notStrictEqual(signer.sign(order).nonce, peeked);
+  assert.throws(() => NativeSigner.withNonceManager(new NativeKeypair(), 'dice'), /'random'/);
+});
@@ -0,0 +1,33 @@
+const test = require('node:test');
+const assert = require('node:assert');
+const { NativeKeypair, NativeSigner, shuffleBatch, jitterSizes } = require('../index.js');
+
+const order = (isBuy, size) => ({
+  type: 'order',
+  symbol: 'BTC-USD',
+  isBuy,
+  price: 100000,
+  size,
+  orderType: { type: 'limit', tif: 'GTC' },
+});
+
+test('shuffleBatch is reproducible for a seed', () => {
+  const signer = new NativeSigner(new NativeKeypair());
+  const txs = signer.signAll([0.1, 0.2, 0.3, 0.4].map((s) => order(true, s)), 1);
+  const sigs = (list) => list.map((tx) => tx.signature);
+  assert.deepStrictEqual(sigs(shuffleBatch(txs, 9)), sigs(shuffleBatch(txs, 9)));
+  assert.deepStrictEqual(sigs(shuffleBatch(txs, 9)).sort(), sigs(txs).sort());
+});
+
+test('jitterSizes keeps totals and lot sizes', () => {
+  const orders = [order(true, 0.5), order(true, 1.5), order(false, 2)];
+  const spec = { priceDecimals: 1, sizeDecimals: 2, lotUnits: 10 };
+  const jittered = jitterSizes(orders, 0.4, 3, { 'BTC-USD': spec });
+  assert.strictEqual(Math.round((jittered[0].size + jittered[1].size) * 100), 200);
+  assert.strictEqual(jittered[2].size, 2);
+  for (const o of jittered) {
+    assert.strictEqual(Math.round(o.size * 100) % 10, 0);
+  }
+  assert.deepStrictEqual(jitterSizes(orders, 0.4, 3, { 'BTC-USD': spec }), jittered);
+  assert.throws(() => jitterSizes(orders, 1.5, 3), /jitter pct/);
+});
@@ -0,0 +1,38 @@
+const test = require('node:test');
+const assert = require('node:assert');
+const fs = require('node:fs');
+const os = require('node:os');
+const path = require('node:path');
+const { NativeKeypair, NativeOutbox, prepareOrder, signPreparedWithKeypair } = require('../index.js');
+
+const order = {
+  type: 'order',
+  symbol: 'BTC-USD',
+  isBuy: true,
+  price: 100000,
+  size: 0.1,
+  orderType: { type: 'limit', tif: 'GTC' },
+};
+
+test('outbox recovers pending messages after reopening', () => {
+  const file = path.join(os.tmpdir(), `bulk-outbox-${process.pid}.jsonl`);
+  fs.rmSync(file, { force: true });
+  const keypair = new NativeKeypair();
+  const prepared = prepareOrder(order, { account: keypair.pubkey, nonce: 1 });
+  const { signature } = signPreparedWithKeypair(prepared, keypair);
+
+  const outbox = new NativeOutbox(file);
+  assert.throws(() => new NativeOutbox(file), /open in another process/);
+  const key = outbox.enqueue(prepared);
+  assert.strictEqual(key, prepared.orderId);
+  outbox.close();
+  assert.throws(() => outbox.pending(), /closed/);
+
+  const reopened = new NativeOutbox(file);
+  assert.strictEqual(reopened.pending()[0].orderId, key);
+  assert.strictEqual(reopened.complete(key, signature).signature, signature);
+  assert.throws(() => reopened.complete(key, signature), /not pending/);
+  assert.deepStrictEqual(reopened.expireOlderThan(0), []);
+  reopened.close();
+  fs.rmSync(file);
+});
@@ -0,0 +1,40 @@
+const test = require('node:test');
+const assert = require('node:assert');
+const { NativeKeypair, NativeSigner } = require('../index.js');
+
+const order = (price) => ({ type: 'order', symbol: 'BTC-USD', isBuy: true, price, size: 1 });
+
+test('peekOrderIds reserves nonces that signAllReserved signs at', () => {
+  const signer = new NativeSigner(new NativeKeypair());
+  const orders = [order(100), order(101), { type: 'cancelAll', symbols: ['BTC-USD'] }];
+
+  const { orderIds, reservation } = signer.peekOrderIds(orders);
+  assert.strictEqual(reservation.count, 3);
+  assert.strictEqual(orderIds[2], null);
+
+  const txs = signer.signAllReserved(orders, reservation);
+  assert.deepStrictEqual(
+    txs.map((tx) => tx.orderId ?? null),
+    orderIds,
+  );
+  assert.strictEqual(txs[0].nonce, reservation.baseNonce);
+
+  assert.throws(() => signer.signAllReserved(orders, reservation), /already used/);
+});
+
+test('peekOrderIds with a base nonce matches signAll', () => {
+  const signer = new NativeSigner(new NativeKeypair());
+  const orders = [order(100), order(101)];
+  const { orderIds, reservation } = signer.peekOrderIds(orders, 50);
+  assert.strictEqual(reservation, undefined);
+  assert.deepStrictEqual(
+    signer.signAll(orders, 50).map((tx) => tx.orderId),
+    orderIds,
+  );
+});
+
+test('signAllReserved refuses orders other than the peeked ones', () => {
+  const signer = new NativeSigner(new NativeKeypair());
+  const { reservation } = signer.peekOrderIds([order(100)]);
+  assert.throws(() => signer.signAllReserved([order(99)], reservation), /items differ/);
+});
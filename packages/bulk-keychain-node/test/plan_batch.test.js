@@ -0,0 +1,37 @@
+const test = require('node:test');
+const assert = require('node:assert');
+const { NativeKeypair, NativeSigner } = require('../index.js');
+
+const order = (price, size) => ({ type: 'order', symbol: 'BTC-USD', isBuy: true, price, size });
+
+test('planBatch reports what signAll would do without signing', () => {
+  const signer = new NativeSigner(new NativeKeypair());
+  signer.setMaxOpenOrdersPerSymbol(2);
+  const orders = [order(100, 2), order(101, 1), order(102, 1)];
+
+  const plan = signer.planBatch(orders, 10);
+  assert.deepStrictEqual(plan.nonceRange, { start: 10, end: 13 });
+  assert.strictEqual(plan.estimatedGroups, 2);
+  assert.deepStrictEqual(
+    plan.rejections.map((r) => r.index),
+    [2],
+  );
+  assert.match(plan.rejections[0].error, /open order/i);
+  assert.deepStrictEqual(plan.perSymbolNotional, { 'BTC-USD': 301 });
+  assert.deepStrictEqual(plan.adjustments, []);
+
+  // Nothing was signed or counted.
+  assert.strictEqual(signer.openOrderCount('BTC-USD'), 0);
+  assert.strictEqual(signer.usage().signed, 0);
+});
+
+test('planBatch reports grid roundings with specs', () => {
+  const signer = new NativeSigner(new NativeKeypair());
+  const plan = signer.planBatch([order(100.004, 0.12345)], 1, {
+    'BTC-USD': { priceDecimals: 2, sizeDecimals: 3 },
+  });
+  assert.deepStrictEqual(plan.adjustments, [
+    { index: 0, field: 'price', from: 100.004, to: 100 },
+    { index: 0, field: 'size', from: 0.12345, to: 0.123 },
+  ]);
+});
@@ -0,0 +1,19 @@
+const test = require('node:test');
+const assert = require('node:assert');
+const { NativeKeypair, NativeSigner } = require('../index.js');
+
+test('notifyReconnect bumps the nonce floor and counts reconnects', () => {
+  const signer = NativeSigner.withNonceManager(new NativeKeypair(), 'counter');
+  assert.strictEqual(signer.peekNextNonce(), 0);
+
+  const before = Date.now();
+  const report = signer.notifyReconnect({ bumpNonceFloor: true });
+  assert.ok(report.nonceFloor >= before, `${report.nonceFloor}`);
+  assert.strictEqual(report.openOrdersCleared, 0);
+  assert.strictEqual(report.clientIdsCleared, 0);
+  assert.ok(signer.peekNextNonce() >= report.nonceFloor);
+
+  const untouched = signer.notifyReconnect({});
+  assert.strictEqual(untouched.nonceFloor, undefined);
+  assert.strictEqual(signer.usage().reconnects, 2);
+});
@@ -0,0 +1,30 @@
+const { test } = require('node:test');
+const assert = require('node:assert');
+const { NativeKeypair, NativeSigner } = require('../index.js');
+
+const order = (symbol, size) => ({
+  type: 'order',
+  symbol,
+  isBuy: true,
+  price: 100,
+  size,
+  orderType: { type: 'limit', tif: 'GTC' },
+});
+
+test('scoped signer enforces its config', () => {
+  const signer = new NativeSigner(new NativeKeypair());
+  const scoped = signer.scoped({ symbols: ['BTC-USD'], maxSize: 1, maxSignatures: 2 });
+  assert.strictEqual(scoped.signerPubkey(), signer.signerPubkey());
+
+  scoped.sign(order('BTC-USD', 0.5), 1);
+  assert.throws(() => scoped.sign(order('ETH-USD', 0.5), 2), /scope violation \(symbol\)/);
+  assert.throws(() => scoped.sign(order('BTC-USD', 2), 2), /scope violation \(max_size\)/);
+  assert.strictEqual(scoped.remainingSignatures(), 1);
+});
+
+test('scoped signer cannot widen itself', () => {
+  const signer = new NativeSigner(new NativeKeypair());
+  const scoped = signer.scoped({ symbols: ['BTC-USD'] });
+  assert.throws(() => scoped.scoped({}), /escalation/);
+  scoped.scoped({ symbols: ['BTC-USD'], maxSize: 0.1 });
+});
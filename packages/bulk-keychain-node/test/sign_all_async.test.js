@@ -0,0 +1,43 @@
+const test = require('node:test');
+const assert = require('node:assert');
+const { NativeKeypair, NativeSigner } = require('../index.js');
+
+const orders = Array.from({ length: 50 }, (_, i) => ({
+  type: 'order',
+  symbol: 'BTC-USD',
+  isBuy: i % 2 === 0,
+  price: 100 + i,
+  size: 1,
+}));
+
+test('signAllAsync resolves to what signAll returns', async () => {
+  const signer = new NativeSigner(new NativeKeypair());
+  const pending = signer.signAllAsync(orders, 1000);
+  assert.ok(pending instanceof Promise);
+  const txs = await pending;
+  assert.deepStrictEqual(txs, signer.signAll(orders, 1000));
+  assert.strictEqual(signer.verifySignedTransaction(txs[49]), true);
+});
+
+test('signAllAsync settles nonces and the hook before returning', async () => {
+  const signer = new NativeSigner(new NativeKeypair());
+  const seen = [];
+  signer.setPreSignHook((_, nonce) => seen.push(nonce));
+  const first = signer.signAllAsync(orders.slice(0, 2));
+  assert.strictEqual(seen.length, 2);
+  const txs = await first;
+  assert.deepStrictEqual(
+    txs.map((tx) => tx.nonce),
+    seen,
+  );
+
+  signer.setPreSignHook(() => {
+    throw new Error('blocked');
+  });
+  assert.throws(() => signer.signAllAsync(orders), /blocked/);
+});
+
+test('signAllAsync rejects on signing errors', async () => {
+  const signer = new NativeSigner(new NativeKeypair());
+  await assert.rejects(signer.signAllAsync(orders, 1, { deadline: Date.now() - 60_000 }), /deadline/);
+});
@@ -0,0 +1,36 @@
+const test = require('node:test');
+const assert = require('node:assert');
+const { NativeKeypair, NativeSigner, prepareOrder } = require('../index.js');
+
+test('signBytes round-trips through verifySignature', () => {
+  const signer = new NativeSigner(new NativeKeypair());
+  const message = Buffer.from('protocol message');
+  const signature = signer.signBytes(message);
+  assert.strictEqual(signature.length, 64);
+
+  assert.strictEqual(NativeSigner.verifySignature(signer.pubkey, message, signature), true);
+  assert.strictEqual(
+    NativeSigner.verifySignature(signer.pubkey, Buffer.from('other'), signature),
+    false,
+  );
+  const other = new NativeKeypair().pubkey;
+  assert.strictEqual(NativeSigner.verifySignature(other, message, signature), false);
+  assert.throws(
+    () => NativeSigner.verifySignature(signer.pubkey, message, signature.subarray(0, 63)),
+    /signature length/i,
+  );
+});
+
+test('signBytes signs a prepared message the same way sign does', () => {
+  const keypair = new NativeKeypair();
+  const signer = new NativeSigner(keypair);
+  const order = { type: 'order', symbol: 'BTC-USD', isBuy: true, price: 100000, size: 0.1 };
+  const tx = signer.sign(order, 7);
+  const prepared = prepareOrder(order, { account: keypair.pubkey, nonce: 7 });
+  const signature = signer.signBytes(prepared.messageBytes);
+  assert.strictEqual(
+    NativeSigner.verifySignature(keypair.pubkey, prepared.messageBytes, signature),
+    true,
+  );
+  assert.ok(signer.verifySignedTransaction(tx, prepared.messageBytes));
+});
@@ -0,0 +1,41 @@
+const test = require('node:test');
+const assert = require('node:assert');
+const {
+  NativeKeypair,
+  NativeSigner,
+  prepareOrder,
+  signPreparedWithKeypair,
+} = require('../index.js');
+
+const order = {
+  type: 'order',
+  symbol: 'BTC-USD',
+  isBuy: true,
+  price: 100000,
+  size: 0.1,
+  orderType: { type: 'limit', tif: 'GTC' },
+};
+
+test('signPreparedWithKeypair finalizes with the prepared signer', () => {
+  const keypair = new NativeKeypair();
+  const prepared = prepareOrder(order, { account: keypair.pubkey, nonce: 42 });
+  const signed = signPreparedWithKeypair(prepared, keypair);
+  assert.strictEqual(signed.signer, keypair.pubkey);
+  assert.strictEqual(signed.orderId, prepared.orderId);
+
+  assert.throws(() => signPreparedWithKeypair(prepared, new NativeKeypair()), /signer mismatch/);
+});
+
+test('signer.signPrepared trades for the prepared account with an agent key', () => {
+  const account = new NativeKeypair().pubkey;
+  const agent = new NativeSigner(new NativeKeypair());
+  const prepared = prepareOrder(order, { account, signer: agent.pubkey, nonce: 42 });
+  const signed = agent.signPrepared(prepared);
+  assert.strictEqual(signed.account, account);
+  assert.strictEqual(signed.signer, agent.pubkey);
+  assert.strictEqual(signed.orderId, prepared.orderId);
+  assert.strictEqual(agent.verifySignedTransaction(signed), true);
+
+  const other = new NativeSigner(new NativeKeypair());
+  assert.throws(() => other.signPrepared(prepared), /signer mismatch/);
+});
@@ -0,0 +1,30 @@
+const test = require('node:test');
+const assert = require('node:assert');
+const { NativeKeypair, NativeSignerPool } = require('../index.js');
+
+const order = (price) => ({
+  type: 'order',
+  symbol: 'BTC-USD',
+  isBuy: true,
+  price,
+  size: 0.1,
+  orderType: { type: 'limit', tif: 'GTC' },
+});
+
+test('signMixed keeps per-account nonces and input order', () => {
+  const a = new NativeKeypair().pubkey;
+  const b = new NativeKeypair().pubkey;
+  const stranger = new NativeKeypair().pubkey;
+  const pool = new NativeSignerPool(new NativeKeypair(), [a, b]);
+  assert.deepStrictEqual(pool.accounts().sort(), [a, b].sort());
+
+  const items = [a, b, stranger, a, b].map((account, i) => ({ account, order: order(100000 + i) }));
+  const results = pool.signMixed(items, { [a]: 10, [b]: 20 });
+
+  assert.deepStrictEqual(results.map((r) => r.index), [0, 1, 2, 3, 4]);
+  assert.deepStrictEqual(results.map((r) => r.account), [a, b, stranger, a, b]);
+  assert.deepStrictEqual([0, 1, 3, 4].map((i) => results[i].tx.nonce), [10, 20, 11, 21]);
+  assert.strictEqual(results[3].tx.account, a);
+  assert.strictEqual(results[2].tx, undefined);
+  assert.match(results[2].error, /no signer for account/);
+});
@@ -0,0 +1,34 @@
+const test = require('node:test');
+const assert = require('node:assert');
+const { NativeKeypair, NativeSigner, prepareOrder } = require('../index.js');
+
+const typo = {
+  type: 'order',
+  symbol: 'BTC-USD',
+  isBuy: true,
+  price: 100000,
+  size: 0.1,
+  reduceOnIy: true,
+  orderType: { type: 'limit', tif: 'GTC' },
+};
+
+test('strict signer rejects a typo and suggests the field', () => {
+  const signer = new NativeSigner(new NativeKeypair());
+  assert.ok(signer.sign(typo, 1).signature);
+  signer.setStrictInput(true);
+  assert.throws(() => signer.sign(typo, 1), /unknown fields: reduceOnIy \(did you mean reduceOnly\?\)/);
+  assert.throws(() => signer.signAll([typo], 1), /did you mean reduceOnly\?/);
+});
+
+test('strict prepare options reject typos in orders and options', () => {
+  const account = new NativeKeypair().pubkey;
+  assert.ok(prepareOrder(typo, { account, nonce: 1 }).messageBytes);
+  assert.throws(
+    () => prepareOrder(typo, { account, nonce: 1, strict: true }),
+    /did you mean reduceOnly\?/,
+  );
+  assert.throws(
+    () => prepareOrder(typo, { account, nonse: 1, strict: true }),
+    /nonse \(did you mean nonce\?\)/,
+  );
+});
@@ -0,0 +1,28 @@
+const test = require('node:test');
+const assert = require('node:assert');
+const {
+  NativeKeypair,
+  NativeSigner,
+  finalizePreparedTransactionBytes,
+  prepareGroup,
+  randomHash,
+  verifyTransaction,
+} = require('../index.js');
+
+test('verifyTransaction checks what an external wallet returned', () => {
+  const wallet = new NativeKeypair();
+  const orders = [
+    { type: 'order', symbol: 'BTC-USD', isBuy: true, price: 100000, size: 0.1 },
+    { type: 'order', symbol: 'BTC-USD', isBuy: false, price: 110000, size: 0.1 },
+  ];
+  const prepared = prepareGroup(orders, { account: wallet.pubkey, nonce: 3 });
+  const signature = new NativeSigner(wallet).signBytes(prepared.messageBytes);
+  const tx = finalizePreparedTransactionBytes(prepared, signature);
+  assert.strictEqual(verifyTransaction(tx), true);
+
+  assert.strictEqual(verifyTransaction({ ...tx, nonce: 4 }), false);
+  assert.throws(
+    () => verifyTransaction({ ...tx, orderIds: [randomHash(), randomHash()] }),
+    /order id mismatch/,
+  );
+});
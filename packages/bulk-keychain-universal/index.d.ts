@@ -0,0 +1,428 @@
+// The API shared by the native addon (`bulk-keychain`) and the WASM build
+// (`bulk-keychain-wasm`). `test/types.ts` checks both packages against it, and
+// `test/conformance.js` runs the same assertions against either at runtime.
+//
+// Errors are plain `Error`s; both backends throw the same message for the
+// same input because both come from the Rust core. Core errors also name
+// their variant (`'InvalidBase58'`, `'NonceOverflow'`, ...): as `error.code`
+// from the native addon, and as a `"<code>: "` message prefix from WASM.
+
+/** An order intent: `{ type: 'order' | 'cancel' | 'cancelAll' | ..., ... }` */
+export type OrderInput = { type: string } & Record<string, unknown>;
+
+/**
+ * A number up to `Number.MAX_SAFE_INTEGER`, or a BigInt for any u64. Outputs
+ * are numbers whenever exact; above that the native addon returns a BigInt and
+ * WASM follows `setSerializeLargeIntsAs`.
+ */
+export type Nonce = number | bigint;
+
+export interface SignOptions {
+  /** Latest time (ms since the Unix epoch, on the signer's clock) to sign at; seconds are refused */
+  deadline?: number;
+  /** Sign even if a live order holds the same `clientId` (see `setUniqueClientIds`) */
+  allowClientIdReuse?: boolean;
+}
+
+export interface PrepareOptions {
+  /** Trading account (base58) */
+  account: string;
+  /** Signing key (base58); defaults to the account */
+  signer?: string;
+  nonce?: Nonce;
+  /** `prepareGroup` only */
+  groupKind?: string;
+  /** `prepareGroup` only */
+  strictGroup?: boolean;
+  /** Reject unknown fields in orders and options */
+  strict?: boolean;
+}
+
+export interface FinalizeOptions {
+  /** Attach the signature without verifying it */
+  skipVerify?: boolean;
+}
+
+export interface TransferOptions {
+  account: string;
+  signer?: string;
+  nonce?: Nonce;
+  /** "internal" (default) or "external" */
+  kind?: string;
+}
+
+export interface CreateSubAccountOptions {
+  account: string;
+  signer?: string;
+  nonce?: Nonce;
+  marginSymbol?: string;
+  marginAmount?: number;
+}
+
+export interface LeverageSetting {
+  symbol: string;
+  leverage: number;
+}
+
+export interface UserSettingsInput {
+  maxLeverage: Array<LeverageSetting | [string, number]>;
+}
+
+export interface SymbolSpec {
+  priceDecimals: number;
+  sizeDecimals: number;
+  tickUnits?: number;
+  lotUnits?: number;
+}
+
+export interface GroupLintWarning {
+  index: number;
+  code: string;
+  message: string;
+  /** `info`, `warning` or `critical` */
+  severity: string;
+}
+
+export interface SignedTransaction {
+  /** Compact tagged actions, exactly as submitted */
+  actions: Array<Record<string, unknown>>;
+  /** Not submitted: action kind ('order', 'faucet', ...); ignored on input */
+  actionType?: string;
+  nonce: Nonce;
+  account: string;
+  signer: string;
+  signature: string;
+  /** Not submitted: client-side order tracking */
+  orderId?: string;
+  orderIds?: string[];
+  /** Times re-signed under a fresh nonce; absent if never */
+  resignCount?: number;
+  /** Not signed: group intent tag */
+  groupKind?: string;
+  groupWarnings?: GroupLintWarning[];
+}
+
+export interface IndexedSignedTransaction extends SignedTransaction {
+  /** Index of the source order in the input array */
+  index: number;
+}
+
+/** One order's outcome from `signAllPartialIndexed` */
+export interface PartialSignResult {
+  /** Index of the source order in the input array */
+  index: number;
+  transaction?: SignedTransaction;
+  /** Why the order was refused */
+  error?: string;
+  code?: string;
+}
+
+/** Read-only: a plain object from the native addon, a class from WASM. */
+export interface PreparedMessage {
+  readonly messageBytes: Uint8Array;
+  readonly messageBase58: string;
+  readonly messageBase64: string;
+  readonly messageHex: string;
+  readonly orderId?: string;
+  readonly orderIds?: string[];
+  readonly actions: Array<Record<string, unknown>>;
+  readonly account: string;
+  readonly signer: string;
+  readonly nonce: Nonce;
+  readonly groupKind?: string;
+  readonly groupWarnings?: GroupLintWarning[];
+  /** `prepareAllIndexed` only */
+  readonly index?: number;
+}
+
+export interface SignerUsage {
+  signed: number;
+  deadlineRejections: number;
+  /** `notifyReconnect` calls */
+  reconnects: number;
+  /** Absent before the first batch */
+  lastBatchMode?: 'serial' | 'parallel';
+  lastBatchThreads?: number;
+}
+
+/** What `notifyReconnect` resets; every field defaults to false */
+export interface ReconnectPolicy {
+  /** Forget the orders counted against the open-order cap, resetting it for every symbol */
+  clearOpenOrders?: boolean;
+  /** Release held client IDs */
+  clearDedupe?: boolean;
+  /** Raise auto nonces to at least the current time */
+  bumpNonceFloor?: boolean;
+}
+
+export interface ReconnectReport {
+  openOrdersCleared: number;
+  clientIdsCleared: number;
+  /** Lowest auto nonce from now on, if bumped */
+  nonceFloor?: Nonce;
+}
+
+/** `planBatch`: what `signAll` would do, without signing */
+export interface BatchPlan {
+  /** Limit-order `price * size` per symbol, over the orders that would sign */
+  perSymbolNotional: Record<string, number>;
+  rejections: Array<{ index: number; error: string }>;
+  /** Only with `specs` */
+  adjustments: Array<{ index: number; field: 'price' | 'size'; from: number; to: number }>;
+  /** `end` is exclusive */
+  nonceRange: { start: Nonce; end: Nonce };
+  estimatedGroups: number;
+}
+
+/** Nonces `baseNonce..baseNonce + count` held for one batch until `expiresAt` */
+export interface NonceReservation {
+  id: number;
+  baseNonce: Nonce;
+  count: number;
+  expiresAt: number;
+  itemsHash: string;
+}
+
+/** `peekOrderIds`: order IDs before signing; `null` for cancels */
+export interface PeekedOrderIds {
+  orderIds: Array<string | null>;
+  /** Only without `baseNonce` */
+  reservation?: NonceReservation;
+}
+
+export interface SignedCapabilities {
+  payload: string;
+  signer: string;
+  signature: string;
+}
+
+export interface Diagnostics {
+  enabled: boolean;
+  liveKeypairs: number;
+  liveSigners: number;
+  livePreparedMessages: number;
+  retainedBytes: number;
+  /** Live copies of each secret key, and what holds them */
+  secretCensus: SecretCensusEntry[];
+}
+
+export interface SecretCensusEntry {
+  fingerprint: string;
+  copies: number;
+  /** Copies per purpose, such as 'NativeSigner::new' */
+  reasons: Record<string, number>;
+}
+
+export interface SymbolParts {
+  base: string;
+  quote: string;
+}
+
+export interface Keypair {
+  readonly pubkey: string;
+  toBase58(): string;
+  toBytes(): Uint8Array;
+  secretKey(): Uint8Array;
+  fingerprint(): string;
+  /** SLIP-0010 child at `index`; Ed25519 has hardened children only, so `hardened` must be true */
+  deriveChild(index: number, hardened: boolean): Keypair;
+  /** Key at a hardened path like `m/44'/501'/0'/0'`, where `m` is this keypair */
+  fromDerivationPath(path: string): Keypair;
+}
+
+export interface KeypairConstructor {
+  new (): Keypair;
+  fromBase58(s: string): Keypair;
+  fromBytes(bytes: Uint8Array): Keypair;
+  /**
+   * 12- or 24-word English BIP39 phrase, derived at `derivationPath`
+   * (default `m/44'/501'/0'/0'`, Phantom's first account; WASM: full build only)
+   */
+  fromMnemonic(mnemonic: string, passphrase?: string, derivationPath?: string): Keypair;
+  /** First 32 bytes of the phrase's seed, as `solana-keygen recover` without a path */
+  fromMnemonicSeed(mnemonic: string, passphrase?: string): Keypair;
+  /** A new keypair (at the default path) and its 24-word phrase, the only backup of the key */
+  generateMnemonic(): { keypair: Keypair; mnemonic: string };
+}
+
+export interface Signer {
+  readonly pubkey: string;
+  accountPubkey(): string;
+  signerPubkey(): string;
+  fingerprint(): string;
+
+  sign(order: OrderInput, nonce?: Nonce, options?: SignOptions): SignedTransaction;
+  signAll(orders: OrderInput[], baseNonce?: Nonce, options?: SignOptions): SignedTransaction[];
+  signAllIndexed(orders: OrderInput[], baseNonce?: Nonce): IndexedSignedTransaction[];
+  signAllPartialIndexed(orders: OrderInput[], baseNonce?: Nonce): PartialSignResult[];
+  /** Does not call the pre-sign hook */
+  planBatch(
+    orders: OrderInput[],
+    baseNonce?: Nonce,
+    specs?: Record<string, SymbolSpec>,
+  ): BatchPlan;
+  peekOrderIds(orders: OrderInput[], baseNonce?: Nonce): PeekedOrderIds;
+  signAllReserved(orders: OrderInput[], reservation: NonceReservation): SignedTransaction[];
+  signGroup(
+    orders: OrderInput[],
+    nonce?: Nonce,
+    groupKind?: string,
+    strictGroup?: boolean,
+  ): SignedTransaction;
+  signOrder(orders: OrderInput[], nonce?: Nonce): SignedTransaction;
+  signOrdersBatch(batches: OrderInput[][], baseNonce?: Nonce): SignedTransaction[];
+  /** @deprecated Use signAllIndexed() instead */
+  signOrdersBatchIndexed(batches: OrderInput[][], baseNonce?: Nonce): IndexedSignedTransaction[];
+  /** New size for a live order; a price change needs a cancel-replace */
+  signModifyOrder(orderId: string, symbol: string, amount: number, nonce?: Nonce): SignedTransaction;
+  signFaucet(nonce?: Nonce): SignedTransaction;
+  signAgentWallet(agentPubkey: string, remove: boolean, nonce?: Nonce): SignedTransaction;
+  signUserSettings(
+    settings: UserSettingsInput | LeverageSetting[],
+    nonce?: Nonce,
+  ): SignedTransaction;
+  signTransfer(
+    fromPubkey: string,
+    toPubkey: string,
+    marginSymbol: string,
+    marginAmount: number,
+    kind?: string,
+    nonce?: Nonce,
+  ): SignedTransaction;
+  signCreateSubAccount(
+    name: string,
+    marginSymbol?: string,
+    marginAmount?: number,
+    nonce?: Nonce,
+  ): SignedTransaction;
+  signRemoveSubAccount(toRemove: string, nonce?: Nonce): SignedTransaction;
+  signRenameSubAccount(subaccount: string, name: string, nonce?: Nonce): SignedTransaction;
+  signWhitelistFaucet(targetPubkey: string, whitelist: boolean, nonce?: Nonce): SignedTransaction;
+  resignWithNewNonce(tx: SignedTransaction): SignedTransaction;
+  /** The nonce the next auto-nonced call would use, without taking it */
+  peekNextNonce(): Nonce;
+  /** `counter` and `highFrequency` managers start again from zero */
+  resetNonce(): void;
+  /** Reset client-ID and open-order state after a gateway reconnect */
+  notifyReconnect(policy: ReconnectPolicy): ReconnectReport;
+  verifySignedTransaction(tx: SignedTransaction, messageBytes?: Uint8Array): boolean;
+  /** Raw 64-byte Ed25519 signature over `bytes` */
+  signBytes(bytes: Uint8Array): Uint8Array;
+  signPrepared(prepared: PreparedMessage): SignedTransaction;
+
+  usage(): SignerUsage;
+  capabilities(): Record<string, unknown>;
+  capabilitiesSigned(): SignedCapabilities;
+  setNetwork(network?: string | null): void;
+  /** Test only: seed `randomClientId()`; `undefined` restores the OS RNG */
+  setDeterministicSeed(seed?: number | null): void;
+  randomClientId(): string;
+  setStrictInput(strict: boolean): void;
+  setMaxOpenOrdersPerSymbol(cap?: number | null): void;
+  setMaxResignAgeMs(maxAgeMs?: number | null): void;
+  openOrderCount(symbol: string): number;
+  notifyClosed(orderId: string): boolean;
+  setUniqueClientIds(enabled: boolean): void;
+  uniqueClientIds(): boolean;
+  /** Sign orders whose `clientId` is the all-zero hash; refused by default */
+  setAllowZeroClientId(allowed: boolean): void;
+  clientIdOrder(clientId: string): string | null | undefined;
+  computesOrderId(): boolean;
+  setComputeOrderId(enabled: boolean): void;
+  computesBatchOrderIds(): boolean;
+  setComputeBatchOrderIds(enabled: boolean): void;
+}
+
+export interface SignerConstructor {
+  new (keypair: Keypair): Signer;
+  fromBase58(s: string): Signer;
+  fromKeypairAndAccount(keypair: Keypair, account: string): Signer;
+  /** `strategy`: 'timestamp' | 'counter' | 'highFrequency' | 'random' */
+  withNonceManager(keypair: Keypair, strategy: string): Signer;
+  /** BIP39 phrase derived at `derivationPath`, e.g. `m/44'/501'/0'/0'` (WASM: full build only) */
+  fromMnemonic(phrase: string, derivationPath: string, strategy?: string): Signer;
+  /** Check a raw 64-byte signature; throws if it is not 64 bytes */
+  verifySignature(pubkey: string, message: Uint8Array, signature: Uint8Array): boolean;
+}
+
+export interface Keychain {
+  /** Which package is underneath */
+  readonly backend: 'native' | 'wasm';
+  readonly Keypair: KeypairConstructor;
+  readonly Signer: SignerConstructor;
+
+  prepareOrder(order: OrderInput, options: PrepareOptions): PreparedMessage;
+  prepareAll(orders: OrderInput[], options: PrepareOptions): PreparedMessage[];
+  prepareAllIndexed(orders: OrderInput[], options: PrepareOptions): PreparedMessage[];
+  prepareGroup(orders: OrderInput[], options: PrepareOptions): PreparedMessage;
+  prepareCancel(symbol: string, orderId: string, options: PrepareOptions): PreparedMessage;
+  prepareCancelAll(symbols: string[], options: PrepareOptions): PreparedMessage;
+  prepareCancelReplace(
+    oldOrderId: string,
+    newOrder: OrderInput,
+    options: PrepareOptions,
+  ): PreparedMessage;
+  prepareAgentWallet(agentPubkey: string, remove: boolean, options: PrepareOptions): PreparedMessage;
+  prepareFaucet(options: PrepareOptions): PreparedMessage;
+  prepareUpdateUserSettings(
+    settings: UserSettingsInput | LeverageSetting[],
+    options: PrepareOptions,
+  ): PreparedMessage;
+  prepareTransfer(
+    fromPubkey: string,
+    toPubkey: string,
+    marginSymbol: string,
+    marginAmount: number,
+    options: TransferOptions,
+  ): PreparedMessage;
+  prepareCreateSubAccount(name: string, options: CreateSubAccountOptions): PreparedMessage;
+  prepareRemoveSubAccount(toRemove: string, options: PrepareOptions): PreparedMessage;
+  prepareRenameSubAccount(subaccount: string, name: string, options: PrepareOptions): PreparedMessage;
+
+  /** Throws unless the signature verifies over the message rebuilt from `prepared` */
+  finalizeTransaction(
+    prepared: PreparedMessage,
+    signature: string,
+    options?: FinalizeOptions,
+  ): SignedTransaction;
+  finalizePreparedTransactionBytes(
+    prepared: PreparedMessage,
+    signatureBytes: Uint8Array,
+    options?: FinalizeOptions,
+  ): SignedTransaction;
+  finalizePreparedTransactionHex(
+    prepared: PreparedMessage,
+    signatureHex: string,
+    options?: FinalizeOptions,
+  ): SignedTransaction;
+  signPreparedWithKeypair(prepared: PreparedMessage, keypair: Keypair): SignedTransaction;
+
+  /** `false` if the signature is bad; throws if `orderId`/`orderIds` do not match the actions */
+  verifyTransaction(tx: SignedTransaction): boolean;
+  verifyCapabilities(signed: SignedCapabilities): boolean;
+  computeOrderId(wincodeBytes: Uint8Array): string;
+  validatePubkey(s: string): boolean;
+  validateHash(s: string): boolean;
+  randomHash(): string;
+  currentTimestamp(): number;
+  pubkeyFingerprint(pubkey: string): string;
+  makeSymbol(base: string, quote: string): string;
+  parseSymbol(pair: string): SymbolParts;
+  parseAmount(input: string): string;
+  formatPrice(symbol: string, price: number, spec: SymbolSpec): string;
+  formatSize(symbol: string, size: number, spec: SymbolSpec): string;
+  orderToDisplayString(order: OrderInput): string;
+  diagnostics(): Diagnostics;
+  setDiagnosticsEnabled(enabled: boolean): void;
+}
+
+export interface LoadOptions {
+  /** Defaults to `BULK_KEYCHAIN_BACKEND`, then native with WASM as the fallback */
+  backend?: 'native' | 'wasm';
+}
+
+/** Load a backend, initializing WASM if it is the one chosen. */
+export function loadKeychain(options?: LoadOptions): Promise<Keychain>;
+
+/** Wrap an already loaded `bulk-keychain` or `bulk-keychain-wasm` module. */
+export function fromModule(mod: object, backend: 'native' | 'wasm'): Keychain;
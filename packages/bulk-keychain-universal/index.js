@@ -0,0 +1,73 @@
+'use strict';
+
+// Runtime selection between the native addon (`bulk-keychain`) and the WASM
+// build (`bulk-keychain-wasm`). Both expose the same API; only the class
+// prefixes differ (`NativeSigner` / `WasmSigner`), so `fromModule` maps them
+// onto `Keypair`, `Signer` and `BatchBuilder`.
+
+const BACKENDS = ['native', 'wasm'];
+
+/**
+ * Wrap an already loaded backend module in the shared `Keychain` shape.
+ */
+function fromModule(mod, backend) {
+  if (!BACKENDS.includes(backend)) {
+    throw new Error(`unknown backend ${JSON.stringify(backend)}; expected 'native' or 'wasm'`);
+  }
+  const prefix = backend === 'native' ? 'Native' : 'Wasm';
+  return Object.freeze({
+    ...mod,
+    backend,
+    Keypair: mod[`${prefix}Keypair`],
+    Signer: mod[`${prefix}Signer`],
+    BatchBuilder: mod[`${prefix}BatchBuilder`],
+  });
+}
+
+function loadNative() {
+  return fromModule(require('bulk-keychain'), 'native');
+}
+
+async function loadWasm() {
+  const mod = await import('bulk-keychain-wasm');
+  if (typeof mod.default === 'function') {
+    // `wasm-pack --target web` output fetches its .wasm by URL, which Node
+    // cannot do for files, so hand it the bytes instead.
+    if (typeof process !== 'undefined' && process.versions && process.versions.node) {
+      const { readFileSync } = require('node:fs');
+      const bytes = readFileSync(require.resolve('bulk-keychain-wasm/bulk_keychain_wasm_bg.wasm'));
+      await mod.default({ module_or_path: bytes });
+    } else {
+      await mod.default();
+    }
+  }
+  await mod.initKeychain();
+  return fromModule(mod, 'wasm');
+}
+
+/**
+ * Load a backend: `options.backend`, else `BULK_KEYCHAIN_BACKEND`, else the
+ * native addon with WASM as the fallback.
+ */
+async function loadKeychain(options = {}) {
+  const env = typeof process !== 'undefined' && process.env ? process.env.BULK_KEYCHAIN_BACKEND : undefined;
+  const backend = options.backend || env;
+  if (backend === 'native') return loadNative();
+  if (backend === 'wasm') return loadWasm();
+  if (backend) {
+    throw new Error(`unknown backend ${JSON.stringify(backend)}; expected 'native' or 'wasm'`);
+  }
+  try {
+    return loadNative();
+  } catch (nativeError) {
+    try {
+      return await loadWasm();
+    } catch (wasmError) {
+      throw new Error(
+        `no bulk-keychain backend could be loaded (native: ${nativeError.message}; wasm: ${wasmError.message})`,
+      );
+    }
+  }
+}
+
+module.exports = { loadKeychain, fromModule };
@@ -0,0 +1,227 @@
+'use strict';
+
+// Conformance suite for the shared `Keychain` API (see ../index.d.ts).
+//
+// `runConformance(keychain, t)` runs unmodified against either backend and
+// checks its outputs against `vectors.json`; the native and WASM packages
+// run it from their own test suites. After an intentional wire-format
+// change, regenerate the expected outputs with
+//
+//   node test/conformance.js --write [native|wasm]
+
+const assert = require('node:assert');
+const path = require('node:path');
+
+const VECTORS_PATH = path.join(__dirname, 'vectors.json');
+const vectors = require(VECTORS_PATH);
+
+// Compare outputs as JSON: absent and `undefined` fields are the same, and
+// prepared messages compare by value whether they are plain objects or
+// WASM classes with getters.
+const plain = (value) => JSON.parse(JSON.stringify(value));
+
+const preparedFields = (prepared) =>
+  plain({
+    messageHex: prepared.messageHex,
+    actions: prepared.actions,
+    nonce: prepared.nonce,
+    orderId: prepared.orderId,
+    orderIds: prepared.orderIds,
+    groupKind: prepared.groupKind,
+  });
+
+const errorOf = (fn) => {
+  try {
+    fn();
+  } catch (e) {
+    return e.message;
+  }
+  return null;
+};
+
+function prepareAll(keychain, account) {
+  const { input, nonce } = vectors;
+  const options = { account, nonce };
+  return {
+    order: keychain.prepareOrder(input.orders[0], options),
+    all: keychain.prepareAll(input.orders, options),
+    group: keychain.prepareGroup(input.bracket, { ...options, groupKind: 'bracket' }),
+    cancel: keychain.prepareCancel('BTC-USD', input.orderId, options),
+    cancelAll: keychain.prepareCancelAll(['BTC-USD', 'ETH-USD'], options),
+    cancelReplace: keychain.prepareCancelReplace(input.orderId, input.orders[0], options),
+    agentWallet: keychain.prepareAgentWallet(input.counterparty, false, options),
+    faucet: keychain.prepareFaucet(options),
+    userSettings: keychain.prepareUpdateUserSettings(input.userSettings, options),
+    transfer: keychain.prepareTransfer(account, input.counterparty, 'USDC', 25, {
+      ...options,
+      kind: 'external',
+    }),
+    createSubAccount: keychain.prepareCreateSubAccount('desk', options),
+    removeSubAccount: keychain.prepareRemoveSubAccount(input.counterparty, options),
+    renameSubAccount: keychain.prepareRenameSubAccount(input.counterparty, 'desk-2', options),
+  };
+}
+
+/** Every output the vectors pin down, in JSON-comparable form. */
+function computeResults(keychain) {
+  const { input, nonce } = vectors;
+  const keypair = keychain.Keypair.fromBase58(input.secretKey);
+  const signer = new keychain.Signer(keypair);
+  const account = keypair.pubkey;
+
+  const prepared = prepareAll(keychain, account);
+  const finalize = (p) => plain(keychain.signPreparedWithKeypair(p, keypair));
+  return {
+    keypair: { pubkey: keypair.pubkey, fingerprint: keypair.fingerprint() },
+    signed: plain({
+      sign: input.orders.map((order, i) => signer.sign(order, nonce + i)),
+      signAll: signer.signAll(input.orders, nonce),
+      signAllIndexed: signer.signAllIndexed(input.orders, nonce),
+      signOrder: signer.signOrder(input.orders, nonce),
+      signGroup: signer.signGroup(input.bracket, nonce, 'bracket'),
+      signOrdersBatch: signer.signOrdersBatch([input.orders, input.bracket], nonce),
+      signFaucet: signer.signFaucet(nonce),
+      signAgentWallet: signer.signAgentWallet(input.counterparty, false, nonce),
+      signUserSettings: signer.signUserSettings(input.userSettings, nonce),
+      signTransfer: signer.signTransfer(account, input.counterparty, 'USDC', 25, 'external', nonce),
+      signCreateSubAccount: signer.signCreateSubAccount('desk', undefined, undefined, nonce),
+      signRemoveSubAccount: signer.signRemoveSubAccount(input.counterparty, nonce),
+      signRenameSubAccount: signer.signRenameSubAccount(input.counterparty, 'desk-2', nonce),
+      signWhitelistFaucet: signer.signWhitelistFaucet(input.counterparty, true, nonce),
+    }),
+    prepared: Object.fromEntries(
+      Object.entries(prepared).map(([name, p]) => [
+        name,
+        Array.isArray(p) ? p.map(preparedFields) : preparedFields(p),
+      ]),
+    ),
+    finalized: Object.fromEntries(
+      Object.entries(prepared).map(([name, p]) => [
+        name,
+        Array.isArray(p) ? p.map(finalize) : finalize(p),
+      ]),
+    ),
+    utilities: {
+      pubkeyFingerprint: keychain.pubkeyFingerprint(account),
+      validatePubkey: [keychain.validatePubkey(account), keychain.validatePubkey('nope')],
+      validateHash: [keychain.validateHash(input.orderId), keychain.validateHash('nope')],
+      makeSymbol: keychain.makeSymbol('BTC', 'USD'),
+      parseSymbol: plain(keychain.parseSymbol('ETH-USDC')),
+      parseAmount: ['1,250.5', '0.5k', '1.25M'].map((s) => keychain.parseAmount(s)),
+      formatPrice: keychain.formatPrice('BTC-USD', 100000, input.symbolSpec),
+      formatSize: keychain.formatSize('BTC-USD', 0.1, input.symbolSpec),
+      orderToDisplayString: keychain.orderToDisplayString(input.orders[0]),
+    },
+    errors: {
+      transferKind: errorOf(() =>
+        signer.signTransfer(account, input.counterparty, 'USDC', 1, 'sideways', nonce),
+      ),
+      prepareAccount: errorOf(() => keychain.prepareOrder(input.orders[0], { account: 'nope' })),
+      orderField: errorOf(() => signer.sign({ type: 'order', symbol: 'BTC-USD' }, nonce)),
+      parseAmount: errorOf(() => keychain.parseAmount('1.234,56')),
+      parseSymbol: errorOf(() => keychain.parseSymbol('BTCUSD')),
+    },
+  };
+}
+
+/** Run the suite as subtests of `t`. */
+async function runConformance(keychain, t) {
+  const results = computeResults(keychain);
+  for (const [section, expected] of Object.entries(vectors.expected)) {
+    await t.test(`${keychain.backend}: ${section} match the shared vectors`, () => {
+      assert.deepStrictEqual(results[section], expected);
+    });
+  }
+
+  const { input, nonce } = vectors;
+  const keypair = keychain.Keypair.fromBase58(input.secretKey);
+  const signer = new keychain.Signer(keypair);
+
+  await t.test(`${keychain.backend}: prepare and finalize agree with Signer`, () => {
+    const prepared = keychain.prepareOrder(input.orders[0], { account: keypair.pubkey, nonce });
+    assert.strictEqual(Buffer.from(prepared.messageBytes).toString('hex'), prepared.messageHex);
+    const direct = plain(signer.sign(input.orders[0], nonce));
+    const viaKeypair = plain(keychain.signPreparedWithKeypair(prepared, keypair));
+    assert.deepStrictEqual(viaKeypair, direct);
+    assert.deepStrictEqual(plain(signer.signPrepared(prepared)), direct);
+    assert.deepStrictEqual(plain(keychain.finalizeTransaction(prepared, direct.signature)), direct);
+    // A plain-object copy of the prepared message finalizes the same way.
+    const fields = ['messageBytes', 'messageBase58', 'messageBase64', 'messageHex', 'orderId'];
+    const copy = Object.fromEntries(
+      [...fields, 'orderIds', 'actions', 'account', 'signer', 'nonce'].map((k) => [k, prepared[k]]),
+    );
+    assert.deepStrictEqual(plain(keychain.finalizeTransaction(copy, direct.signature)), direct);
+  });
+
+  await t.test(`${keychain.backend}: finalizeTransaction verifies the signature`, () => {
+    const prepared = keychain.prepareOrder(input.orders[0], { account: keypair.pubkey, nonce });
+    const other = plain(signer.sign(input.orders[0], nonce + 1)).signature;
+    assert.throws(
+      () => keychain.finalizeTransaction(prepared, other),
+      /signature does not verify against signer/,
+    );
+    const unchecked = keychain.finalizeTransaction(prepared, other, { skipVerify: true });
+    assert.strictEqual(unchecked.signature, other);
+  });
+
+  await t.test(`${keychain.backend}: verifySignedTransaction`, () => {
+    const tx = signer.sign(input.orders[0], nonce);
+    assert.strictEqual(signer.verifySignedTransaction(tx), true);
+    assert.strictEqual(signer.verifySignedTransaction({ ...tx, nonce: nonce + 1 }), false);
+  });
+
+  await t.test(`${keychain.backend}: verifyTransaction`, () => {
+    const tx = signer.sign(input.orders[0], nonce);
+    assert.strictEqual(keychain.verifyTransaction(tx), true);
+    assert.strictEqual(keychain.verifyTransaction({ ...tx, nonce: nonce + 1 }), false);
+    assert.throws(
+      () => keychain.verifyTransaction({ ...tx, orderId: input.orderId }),
+      /order id mismatch/,
+    );
+  });
+
+  await t.test(`${keychain.backend}: signBytes and verifySignature`, () => {
+    const message = new TextEncoder().encode('protocol message');
+    const signature = signer.signBytes(message);
+    assert.strictEqual(signature.length, 64);
+    assert.strictEqual(keychain.Signer.verifySignature(keypair.pubkey, message, signature), true);
+    const other = new TextEncoder().encode('other message');
+    assert.strictEqual(keychain.Signer.verifySignature(keypair.pubkey, other, signature), false);
+  });
+
+  await t.test(`${keychain.backend}: user settings shapes are interchangeable`, () => {
+    const { maxLeverage } = input.userSettings;
+    const expected = plain(signer.signUserSettings(input.userSettings, nonce));
+    const pairs = { maxLeverage: maxLeverage.map((l) => [l.symbol, l.leverage]) };
+    assert.deepStrictEqual(plain(signer.signUserSettings(pairs, nonce)), expected);
+    assert.deepStrictEqual(plain(signer.signUserSettings(maxLeverage, nonce)), expected);
+  });
+
+  await t.test(`${keychain.backend}: usage reports the last batch`, () => {
+    const fresh = new keychain.Signer(keypair);
+    const before = plain(fresh.usage());
+    assert.deepStrictEqual(before, { signed: 0, deadlineRejections: 0, reconnects: 0 });
+    fresh.signAll(input.orders, nonce);
+    const after = fresh.usage();
+    assert.strictEqual(after.signed, input.orders.length);
+    assert.ok(['serial', 'parallel'].includes(after.lastBatchMode));
+    assert.strictEqual(typeof after.lastBatchThreads, 'number');
+  });
+}
+
+module.exports = { runConformance, computeResults };
+
+if (require.main === module) {
+  const args = process.argv.slice(2);
+  if (args[0] !== '--write') {
+    console.error('usage: node test/conformance.js --write [native|wasm]');
+    process.exit(2);
+  }
+  const { loadKeychain } = require('..');
+  loadKeychain({ backend: args[1] }).then((keychain) => {
+    const { expected, ...rest } = vectors;
+    const updated = { ...rest, expected: computeResults(keychain) };
+    require('node:fs').writeFileSync(VECTORS_PATH, `${JSON.stringify(updated, null, 2)}\n`);
+    console.log(`wrote ${VECTORS_PATH} from the ${keychain.backend} backend`);
+  });
+}
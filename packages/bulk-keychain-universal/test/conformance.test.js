@@ -0,0 +1,33 @@
+const test = require('node:test');
+const assert = require('node:assert');
+const { loadKeychain } = require('..');
+const { runConformance, computeResults } = require('./conformance.js');
+
+const loaded = {};
+
+for (const backend of ['native', 'wasm']) {
+  test(`conformance: ${backend}`, async (t) => {
+    let keychain;
+    try {
+      keychain = await loadKeychain({ backend });
+    } catch (e) {
+      t.skip(`${backend} backend not installed: ${e.message}`);
+      return;
+    }
+    assert.strictEqual(keychain.backend, backend);
+    loaded[backend] = keychain;
+    await runConformance(keychain, t);
+  });
+}
+
+test('conformance: native and wasm produce identical results', (t) => {
+  if (!loaded.native || !loaded.wasm) {
+    t.skip('needs both backends installed');
+    return;
+  }
+  assert.deepStrictEqual(computeResults(loaded.wasm), computeResults(loaded.native));
+});
+
+test('loadKeychain rejects an unknown backend', async () => {
+  await assert.rejects(loadKeychain({ backend: 'gpu' }), /unknown backend "gpu"/);
+});
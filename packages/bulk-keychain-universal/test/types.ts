@@ -0,0 +1,20 @@
+// Compile-only: both packages' generated definitions must satisfy the
+// shared `Keychain` interface. Run with `pnpm typecheck`.
+
+import * as native from 'bulk-keychain';
+import * as wasm from 'bulk-keychain-wasm';
+import type { Keychain } from '..';
+
+export const nativeKeychain: Keychain = {
+  ...native,
+  backend: 'native',
+  Keypair: native.NativeKeypair,
+  Signer: native.NativeSigner,
+};
+
+export const wasmKeychain: Keychain = {
+  ...wasm,
+  backend: 'wasm',
+  Keypair: wasm.WasmKeypair,
+  Signer: wasm.WasmSigner,
+};
@@ -0,0 +1,23 @@
+#!/usr/bin/env bash
+# Build the full and slim WASM bundles and compare raw .wasm sizes.
+set -euo pipefail
+
+cd "$(dirname "$0")/.."
+TARGET=wasm32-unknown-unknown
+OUT=target/wasm-size
+mkdir -p "$OUT"
+
+build() {
+    local name=$1
+    shift
+    cargo build --release -p bulk-keychain-wasm --target "$TARGET" "$@" >&2
+    cp "target/$TARGET/release/bulk_keychain_wasm.wasm" "$OUT/$name.wasm"
+}
+
+build full
+build slim --no-default-features
+
+full=$(wc -c <"$OUT/full.wasm")
+slim=$(wc -c <"$OUT/slim.wasm")
+printf '%-6s %10s bytes\n' full "$full" slim "$slim"
+printf 'slim is %d%% of full\n' $((slim * 100 / full))